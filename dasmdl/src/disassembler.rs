@@ -1,5 +1,16 @@
-use sailar::format;
-use std::io::{Result, Write};
+//! Converts SAILAR binary modules into the text format accepted by `sailasm`.
+
+use sailar::builder::Builder;
+use sailar::identifier::Id;
+use sailar::index;
+use sailar::instruction::{self, Instruction};
+use sailar::reader::Reader;
+use sailar::record::{self, Record};
+use sailar::signature;
+use sailar::validation::{ModuleContents, ValidModule};
+use std::io::{Read, Write};
+
+type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Indentation {
@@ -13,6 +24,39 @@ impl Default for Indentation {
     }
 }
 
+/// Indicates how references to records and registers are written.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq)]
+pub enum ReferenceStyle {
+    /// Records and registers are given names such as `@code_0` or `$t1`.
+    #[default]
+    Symbolic,
+    /// Records and registers are referred to by their raw indices such as `#0`, and are not given names.
+    Index,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    pub indentation: Indentation,
+    pub references: ReferenceStyle,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    ReadError(#[from] sailar::reader::Error),
+    #[error(transparent)]
+    ValidationError(#[from] sailar::validation::Error),
+    #[error("{0:?} records cannot be disassembled")]
+    UnsupportedRecord(record::Type),
+    #[error("the {0} metadata field cannot be disassembled")]
+    UnsupportedMetadataField(&'static Id),
+    #[error("the {0:?} instruction cannot be disassembled")]
+    UnsupportedInstruction(instruction::Opcode),
+    #[error(transparent)]
+    OutputError(#[from] std::io::Error),
+}
+
 mod output {
     use crate::disassembler::{Indentation, Result, Write};
 
@@ -31,9 +75,7 @@ mod output {
                 writer,
                 indentation: match indentation {
                     Indentation::Tabs => String::from('\t'),
-                    Indentation::Spaces(count) => unsafe {
-                        String::from_utf8_unchecked(vec![b' '; count.into()])
-                    },
+                    Indentation::Spaces(count) => " ".repeat(count.into()),
                 },
                 indented: false,
                 indentation_level: 0,
@@ -54,12 +96,12 @@ mod output {
 
         pub fn write_str(&mut self, s: &str) -> Result<()> {
             self.write_indent()?;
-            self.writer.write_all(s.as_bytes())
+            Ok(self.writer.write_all(s.as_bytes())?)
         }
 
         pub fn write_ln(&mut self) -> Result<()> {
             self.indented = false;
-            self.writer.write_all(&[b'\n'])
+            Ok(self.writer.write_all(b"\n")?)
         }
 
         pub fn write_str_ln(&mut self, s: &str) -> Result<()> {
@@ -68,15 +110,10 @@ mod output {
         }
 
         pub fn write_char(&mut self, c: char) -> Result<()> {
-            let mut buffer = [0u8, 4];
+            let mut buffer = [0u8; 4];
             self.write_str(c.encode_utf8(&mut buffer))
         }
 
-        pub fn write_char_ln(&mut self, c: char) -> Result<()> {
-            self.write_char(c)?;
-            self.write_ln()
-        }
-
         pub fn write_fmt(&mut self, args: std::fmt::Arguments<'_>) -> Result<()> {
             if let Some(s) = args.as_str() {
                 self.write_str(s)
@@ -90,40 +127,19 @@ mod output {
             }
         }
 
-        pub fn write_fmt_ln(&mut self, args: std::fmt::Arguments<'_>) -> Result<()> {
-            self.write_fmt(args)?;
-            self.write_ln()
-        }
-
-        pub fn write_iter<
-            I: std::iter::IntoIterator,
-            F: FnMut(&mut Self, I::Item) -> Result<()>,
-        >(
-            &mut self,
-            iter: I,
-            mut writer: F,
-        ) -> Result<()> {
-            for i in iter {
-                writer(self, i)?;
-            }
-            Ok(())
-        }
-
-        pub fn write_join<
-            I: std::iter::IntoIterator,
-            F: FnMut(&mut Self, I::Item) -> Result<()>,
-        >(
+        pub fn write_join<I: std::iter::IntoIterator, F: FnMut(&mut Self, I::Item) -> Result<()>>(
             &mut self,
             separator: &str,
             iter: I,
             mut writer: F,
         ) -> Result<()> {
-            self.write_iter(iter.into_iter().enumerate(), |out, (index, i)| {
+            for (index, item) in iter.into_iter().enumerate() {
                 if index > 0 {
-                    out.write_str(separator)?;
+                    self.write_str(separator)?;
                 }
-                writer(out, i)
-            })
+                writer(self, item)?;
+            }
+            Ok(())
         }
 
         pub fn indent(&mut self) {
@@ -138,560 +154,561 @@ mod output {
 
 use output::Output;
 
-fn directive<W: FnOnce(&mut Output<'_, O>) -> Result<()>, O: Write>(
-    out: &mut Output<'_, O>,
-    name: &'static str,
-    writer: W,
-) -> Result<()> {
-    out.write_char('.')?;
-    out.write_str(name)?;
-    out.write_char(' ')?;
-    writer(out)?;
-    out.write_char_ln(';')
+/// The kinds of records that can be referred to by labels, used to generate names like `@type_0`.
+#[derive(Clone, Copy)]
+enum RecordName {
+    Identifier,
+    Data,
+    TypeSignature,
+    FunctionSignature,
+    CodeBlock,
+    FunctionTemplate,
+    Function,
 }
 
-fn quoted_identifier<O: Write>(out: &mut Output<'_, O>, id: &format::Identifier) -> Result<()> {
-    out.write_char('\"')?;
-    for c in id.as_str().chars() {
-        match c {
-            '\n' => out.write_str("\\n")?,
-            '\t' => out.write_str("\\t")?,
-            '\"' => out.write_str("\\\"")?,
-            '\\' => out.write_str("\\\\")?,
-            _ if c.is_control() => out.write_fmt(format_args!("\\u{:04X}", u32::from(c)))?,
-            _ => out.write_char(c)?,
+impl RecordName {
+    fn prefix(self) -> &'static str {
+        match self {
+            Self::Identifier => "identifier",
+            Self::Data => "data",
+            Self::TypeSignature => "type",
+            Self::FunctionSignature => "signature",
+            Self::CodeBlock => "code",
+            Self::FunctionTemplate => "template",
+            Self::Function => "function",
         }
     }
-    out.write_char('\"')
 }
 
-fn version_numbers<O: Write>(
-    out: &mut Output<'_, O>,
-    format::VersionNumbers(ref version): &format::VersionNumbers,
-) -> Result<()> {
-    out.write_join(" ", &version.0, |out, n| {
-        out.write_fmt(format_args!("{}", n))
-    })
+struct Disassembler<'a, 'o, 'd, O> {
+    out: Output<'o, O>,
+    references: ReferenceStyle,
+    contents: &'a ModuleContents<'d>,
 }
 
-fn module_header<O: Write>(out: &mut Output<'_, O>, header: &format::ModuleHeader) -> Result<()> {
-    out.write_str_ln(".module {")?;
-    out.indent();
-    out.write_fmt_ln(format_args!("// FieldCount: {}", header.field_count()))?;
-    directive(out, "name", |out| {
-        quoted_identifier(out, &header.identifier.name)
-    })?;
-    directive(out, "version", |out| {
-        version_numbers(out, &header.identifier.version)
-    })?;
-    out.dedent();
-    out.write_str_ln("}")
-}
+impl<O: Write> Disassembler<'_, '_, '_, O> {
+    fn reference<I: Into<usize>>(&mut self, name: RecordName, index: I) -> Result<()> {
+        let index = index.into();
+        match self.references {
+            ReferenceStyle::Symbolic => self.out.write_fmt(format_args!("@{}_{}", name.prefix(), index)),
+            ReferenceStyle::Index => self.out.write_fmt(format_args!("#{}", index)),
+        }
+    }
 
-// TODO: Pad indices.
-fn indexed<I: TryInto<usize>, T, W: FnOnce(&mut Output<'_, O>, &T) -> Result<()>, O: Write>(
-    out: &mut Output<'_, O>,
-    index: I,
-    items: &[T],
-    writer: W,
-) -> Result<()>
-where
-    <I as TryInto<usize>>::Error: std::fmt::Debug,
-{
-    let i = index.try_into().unwrap();
-    out.write_fmt(format_args!("/* {} */ ", i))?;
-    writer(out, &items[i]) // TODO: Print error message if index is invalid.
-}
+    /// Writes the label of a record, which is omitted if records are referred to by index.
+    fn label(&mut self, name: RecordName, index: usize) -> Result<()> {
+        match self.references {
+            ReferenceStyle::Symbolic => self.out.write_fmt(format_args!(" @{}_{}", name.prefix(), index)),
+            ReferenceStyle::Index => Ok(()),
+        }
+    }
 
-fn data_header<O: Write>(out: &mut Output<'_, O>, header: &str, length: usize) -> Result<()> {
-    out.write_str("// ")?;
-    out.write_str_ln(header)?;
-    out.write_fmt_ln(format_args!("// Length = {}", length))
-}
+    fn type_reference(&mut self, index: index::TypeSignature) -> Result<()> {
+        self.reference(RecordName::TypeSignature, index)
+    }
 
-fn commented_module_data<T, P: FnMut(&mut Output<'_, O>, &T) -> Result<()>, O: Write>(
-    out: &mut Output<'_, O>,
-    items: &[T],
-    header: &str,
-    mut printer: P,
-) -> Result<()> {
-    data_header(out, header, items.len())?;
-    // TODO: Pad index integers.
-    for (index, i) in items.iter().enumerate() {
-        out.write_fmt(format_args!("// {} - ", index))?;
-        printer(out, i)?;
-        out.write_ln()?;
+    fn type_list(&mut self, types: &signature::TypeList) -> Result<()> {
+        self.out.write_char('(')?;
+        for (position, index) in types.iter().enumerate() {
+            if position > 0 {
+                self.out.write_str(", ")?;
+            }
+            self.type_reference(index)?;
+        }
+        self.out.write_char(')')
     }
-    Ok(())
-}
 
-fn indexed_identifier<O: Write>(
-    out: &mut Output<'_, O>,
-    index: format::indices::Identifier,
-    identifiers: &[format::Identifier],
-) -> Result<()> {
-    indexed(out, index, identifiers, |out, id| {
-        quoted_identifier(out, id)
-    })
-}
+    fn literal_string(&mut self, s: &str) -> Result<()> {
+        self.out.write_char('"')?;
+        for c in s.chars() {
+            match c {
+                '\n' => self.out.write_str("\\n")?,
+                '\r' => self.out.write_str("\\r")?,
+                '\t' => self.out.write_str("\\t")?,
+                '\"' => self.out.write_str("\\\"")?,
+                '\\' => self.out.write_str("\\\\")?,
+                _ => self.out.write_char(c)?,
+            }
+        }
+        self.out.write_char('"')
+    }
 
-fn quoted_namespace<O: Write>(
-    out: &mut Output<'_, O>,
-    identifiers: &[format::Identifier],
-    namespace: &format::structures::LengthEncodedVector<format::indices::Identifier>,
-) -> Result<()> {
-    out.write_join(", ", &namespace.0, |out, id| {
-        indexed_identifier(out, *id, identifiers)
-    })
-}
+    fn identifier(&mut self, identifier: &Id) -> Result<()> {
+        self.literal_string(identifier.as_str())
+    }
 
-fn primitive_type<O: Write>(
-    out: &mut Output<'_, O>,
-    t: format::type_system::PrimitiveType,
-) -> Result<()> {
-    out.write_fmt(format_args!("{}", t))
-}
+    fn metadata_field(&mut self, field: &record::MetadataField) -> Result<()> {
+        self.out.write_fmt(format_args!(".metadata {} ", field.field_name()))?;
+        match field {
+            record::MetadataField::ModuleIdentifier(identifier) => {
+                self.identifier(identifier.name())?;
+                if !identifier.version().is_empty() {
+                    self.out.write_char(' ')?;
+                    self.out.write_join(".", identifier.version().iter(), |out, number| {
+                        out.write_fmt(format_args!("{}", u32::from(*number)))
+                    })?;
+                }
+            }
+            record::MetadataField::EntryPoint(entry_point) => self.reference(RecordName::Function, *entry_point)?,
+            record::MetadataField::NoInline(function) => self.reference(RecordName::Function, *function)?,
+            unknown => return Err(Error::UnsupportedMetadataField(unknown.field_name())),
+        }
+        self.out.write_ln()
+    }
 
-fn type_signature<O: Write>(
-    out: &mut Output<'_, O>,
-    signature: &format::type_system::AnyType,
-) -> Result<()> {
-    use format::type_system::*;
+    fn data(&mut self, index: usize, bytes: &[u8]) -> Result<()> {
+        const BYTES_PER_LINE: usize = 16;
 
-    match signature {
-        AnyType::Heap(HeapType::Val(SimpleType::Primitive(t))) => primitive_type(out, *t),
-        _ => todo!(),
-    }
-}
+        self.out.write_str(".data")?;
+        self.label(RecordName::Data, index)?;
 
-// method_signature
+        self.out.indent();
+        for (line, chunk) in bytes.chunks(BYTES_PER_LINE).enumerate() {
+            if line > 0 {
+                self.out.write_ln()?;
+            }
 
-const BLOCK_NAME_PREFIX: &str = "BLOCK_";
-const ENTRY_BLOCK_NAME: &str = "ENTRY";
+            for b in chunk.iter() {
+                self.out.write_fmt(format_args!(" {:#04X}", b))?;
+            }
+        }
+        self.out.dedent();
+        self.out.write_ln()
+    }
 
-fn code_register<O: Write>(
-    out: &mut Output<'_, O>,
-    register: format::indices::Register,
-) -> Result<()> {
-    match register {
-        format::indices::Register::Input(index) => out.write_fmt(format_args!("%a{}", index)),
-        format::indices::Register::Temporary(index) => out.write_fmt(format_args!("%t{}", index)),
+    fn type_signature(&mut self, index: usize, signature: &signature::Type) -> Result<()> {
+        self.out.write_str(".signature")?;
+        self.label(RecordName::TypeSignature, index)?;
+        self.out.write_str(" type ")?;
+        match signature {
+            signature::Type::RawPtr(Some(pointee)) => {
+                self.out.write_str("rawptr ")?;
+                self.type_reference(*pointee)?;
+            }
+            signature::Type::FuncPtr(function_signature) => {
+                self.out.write_str("funcptr ")?;
+                self.reference(RecordName::FunctionSignature, *function_signature)?;
+            }
+            _ => self.out.write_fmt(format_args!("{}", signature))?,
+        }
+        self.out.write_ln()
     }
-}
 
-fn numeric_type<O: Write>(
-    out: &mut Output<'_, O>,
-    t: &format::instruction_set::NumericType,
-) -> Result<()> {
-    use format::instruction_set::NumericType;
-    match t {
-        NumericType::Primitive(t) => primitive_type(out, *t),
+    fn function_signature(&mut self, index: usize, signature: &signature::Function) -> Result<()> {
+        self.out.write_str(".signature")?;
+        self.label(RecordName::FunctionSignature, index)?;
+        self.out.write_str(" function ")?;
+        self.type_list(&signature.parameter_types())?;
+        self.out.write_str(" -> ")?;
+        self.type_list(&signature.return_types())?;
+        self.out.write_ln()
     }
-}
 
-fn overflow_behavior<O: Write>(
-    out: &mut Output<'_, O>,
-    behavior: format::instruction_set::OverflowBehavior,
-) -> Result<()> {
-    use format::instruction_set::OverflowBehavior;
+    fn register_name(&mut self, input_count: usize, register: index::Register) -> Result<()> {
+        let index = usize::from(register);
+        match self.references {
+            ReferenceStyle::Symbolic if index < input_count => self.out.write_fmt(format_args!("$i{}", index)),
+            ReferenceStyle::Symbolic => self.out.write_fmt(format_args!("$t{}", index - input_count)),
+            ReferenceStyle::Index => self.out.write_fmt(format_args!("#{}", index)),
+        }
+    }
 
-    match behavior {
-        OverflowBehavior::Ignore => Ok(()),
-        OverflowBehavior::Halt => out.write_str(" ovf.halt"),
-        OverflowBehavior::Flag => out.write_str(" ovf.flag"),
+    fn register_declaration(&mut self, input_count: usize, register: usize, value_type: index::TypeSignature) -> Result<()> {
+        match self.references {
+            ReferenceStyle::Symbolic => self.register_name(input_count, index::Register::from(register))?,
+            ReferenceStyle::Index => self.out.write_char('_')?,
+        }
+        self.out.write_char(':')?;
+        self.type_reference(value_type)
     }
-}
 
-fn basic_arithmetic_instruction<O: Write>(
-    out: &mut Output<'_, O>,
-    name: &str,
-    operation: &format::instruction_set::BasicArithmeticOperation,
-    separator: &str,
-) -> Result<()> {
-    out.write_str(name)?;
-    out.write_char(' ')?;
-    numeric_type(out, &operation.return_type)?;
-    out.write_char(' ')?;
-    code_register(out, operation.x)?;
-    out.write_char(' ')?;
-    out.write_str(separator)?;
-    out.write_char(' ')?;
-    code_register(out, operation.y)?;
-
-    overflow_behavior(out, operation.overflow)
-}
+    fn value(&mut self, input_count: usize, value: &instruction::Value) -> Result<()> {
+        match value {
+            instruction::Value::IndexedRegister(register) => self.register_name(input_count, *register),
+            instruction::Value::Constant(instruction::Constant::Integer(integer)) => match integer {
+                instruction::ConstantInteger::I8(value) => self.out.write_fmt(format_args!("{}i8", value)),
+                instruction::ConstantInteger::I16(value) => self.out.write_fmt(format_args!("{}i16", u16::from_le_bytes(*value))),
+                instruction::ConstantInteger::I32(value) => self.out.write_fmt(format_args!("{}", u32::from_le_bytes(*value))),
+                instruction::ConstantInteger::I64(value) => self.out.write_fmt(format_args!("{}i64", u64::from_le_bytes(*value))),
+            },
+        }
+    }
 
-fn division_instruction<O: Write>(
-    out: &mut Output<'_, O>,
-    name: &str,
-    operation: &format::instruction_set::DivisionOperation,
-) -> Result<()> {
-    use format::instruction_set::DivideByZeroBehavior;
-
-    out.write_str(name)?;
-    out.write_char(' ')?;
-    numeric_type(out, &operation.return_type)?;
-    out.write_char(' ')?;
-    code_register(out, operation.numerator)?;
-    out.write_str(" over ")?;
-    code_register(out, operation.denominator)?;
-
-    match operation.divide_by_zero {
-        DivideByZeroBehavior::Halt => out.write_str(" zeroed.halt")?,
-        DivideByZeroBehavior::Return(return_index) => {
-            out.write_str(" or ")?;
-            code_register(out, return_index)?;
+    /// Writes values separated by spaces, with a leading space before each value.
+    fn values(&mut self, input_count: usize, values: &[&instruction::Value]) -> Result<()> {
+        for value in values.iter() {
+            self.out.write_char(' ')?;
+            self.value(input_count, value)?;
         }
+        Ok(())
     }
 
-    overflow_behavior(out, operation.overflow)
-}
+    /// Writes a comma separated list of values, with a leading space if the list is not empty.
+    fn value_list(&mut self, input_count: usize, values: &[instruction::Value]) -> Result<()> {
+        for (index, value) in values.iter().enumerate() {
+            self.out.write_str(if index > 0 { ", " } else { " " })?;
+            self.value(input_count, value)?;
+        }
+        Ok(())
+    }
 
-fn bitwise_instruction<O: Write>(
-    out: &mut Output<'_, O>,
-    name: &str,
-    operation: &format::instruction_set::BitwiseOperation,
-    separator: Option<&str>,
-) -> Result<()> {
-    out.write_str(name)?;
-    out.write_char(' ')?;
-    numeric_type(out, &operation.result_type)?;
-    out.write_char(' ')?;
-    code_register(out, operation.x)?;
-    out.write_char(' ')?;
-    if let Some(separator) = separator {
-        out.write_str(separator)?;
-        out.write_char(' ')?;
+    /// Writes a parenthesized list of arguments, which is omitted if there are no arguments.
+    fn arguments(&mut self, input_count: usize, arguments: &[instruction::Value]) -> Result<()> {
+        if !arguments.is_empty() {
+            self.out.write_str(" (")?;
+            for (index, argument) in arguments.iter().enumerate() {
+                if index > 0 {
+                    self.out.write_str(", ")?;
+                }
+                self.value(input_count, argument)?;
+            }
+            self.out.write_char(')')?;
+        }
+        Ok(())
     }
-    code_register(out, operation.y)
-}
 
-fn bitwise_shift_instruction<O: Write>(
-    out: &mut Output<'_, O>,
-    name: &str,
-    operation: &format::instruction_set::BitwiseShiftOperation,
-) -> Result<()> {
-    bitwise_instruction(out, name, &operation.0, Some("by"))
-}
+    fn instruction(&mut self, input_count: usize, instruction: &Instruction) -> Result<()> {
+        match instruction {
+            Instruction::Nop => self.out.write_str("nop"),
+            Instruction::Break => self.out.write_str("break"),
+            Instruction::Return(values) => {
+                self.out.write_str("ret")?;
+                self.value_list(input_count, values)
+            }
+            Instruction::Br(target, arguments) => {
+                self.out.write_str("br ")?;
+                self.reference(RecordName::CodeBlock, *target)?;
+                self.arguments(input_count, arguments)
+            }
+            Instruction::BrIf(branch) => {
+                self.out.write_str("br.if")?;
+                self.values(input_count, &[branch.condition()])?;
+                self.out.write_str(" then ")?;
+                self.reference(RecordName::CodeBlock, branch.true_branch())?;
+                self.out.write_str(" else ")?;
+                self.reference(RecordName::CodeBlock, branch.false_branch())?;
+                self.arguments(input_count, branch.arguments())
+            }
+            Instruction::Call(callee, arguments) => {
+                self.out.write_str("call ")?;
+                self.reference(RecordName::Function, *callee)?;
+                self.arguments(input_count, arguments)
+            }
+            Instruction::IAdd(arithmetic) | Instruction::ISub(arithmetic) => {
+                self.out.write_str(if let Instruction::IAdd(_) = instruction {
+                    "iadd"
+                } else {
+                    "isub"
+                })?;
+                match arithmetic.overflow_behavior() {
+                    instruction::OverflowBehavior::Saturate => self.out.write_str(" sat")?,
+                    instruction::OverflowBehavior::Flag => self.out.write_str(" ovf")?,
+                    _ => (),
+                }
+                self.values(input_count, &[arithmetic.x_value(), arithmetic.y_value()])
+            }
+            Instruction::Cmp(comparison) => {
+                self.out.write_str("cmp")?;
+                self.values(input_count, &[comparison.x_value()])?;
+                self.out.write_fmt(format_args!(" {}", comparison.kind()))?;
+                self.values(input_count, &[comparison.y_value()])
+            }
+            Instruction::ThreadSpawn(callee, arguments) => {
+                self.out.write_str("thread.spawn ")?;
+                self.reference(RecordName::Function, *callee)?;
+                self.arguments(input_count, arguments)
+            }
+            Instruction::ThreadJoin(signature, thread) => {
+                self.out.write_str("thread.join ")?;
+                self.reference(RecordName::FunctionSignature, *signature)?;
+                self.values(input_count, &[thread])
+            }
+            Instruction::AtomicLoad(load) => {
+                self.out.write_fmt(format_args!("atomic.load {}", load.ordering()))?;
+                self.values(input_count, &[load.address()])
+            }
+            Instruction::AtomicStore(store) => {
+                self.out.write_fmt(format_args!("atomic.store {}", store.ordering()))?;
+                self.values(input_count, &[store.address(), store.value()])
+            }
+            Instruction::AtomicRmw(operation) => {
+                self.out
+                    .write_fmt(format_args!("atomic.rmw {} {}", operation.operation(), operation.ordering()))?;
+                self.values(input_count, &[operation.address(), operation.value()])
+            }
+            Instruction::AtomicCmpXchg(operation) => {
+                self.out.write_fmt(format_args!(
+                    "atomic.cmpxchg {} {}",
+                    operation.success_ordering(),
+                    operation.failure_ordering()
+                ))?;
+                self.values(
+                    input_count,
+                    &[operation.address(), operation.expected(), operation.replacement()],
+                )
+            }
+            Instruction::Fence(ordering) => self.out.write_fmt(format_args!("fence {}", ordering)),
+            Instruction::VSplat(value) => {
+                self.out.write_str("vec.splat")?;
+                self.values(input_count, &[value])
+            }
+            Instruction::VExtract(vector, lane) => {
+                self.out.write_str("vec.extract")?;
+                self.values(input_count, &[vector])?;
+                self.out.write_fmt(format_args!(" {}", lane))
+            }
+            Instruction::VInsert(insert) => {
+                self.out.write_str("vec.insert")?;
+                self.values(input_count, &[insert.vector()])?;
+                self.out.write_fmt(format_args!(" {}", insert.lane()))?;
+                self.values(input_count, &[insert.value()])
+            }
+            Instruction::VShuffle(shuffle) => {
+                self.out.write_str("vec.shuffle")?;
+                self.values(input_count, &[shuffle.x_value(), shuffle.y_value()])?;
+                self.out.write_str(" (")?;
+                self.out.write_join(", ", shuffle.lanes().iter(), |out, lane| {
+                    out.write_fmt(format_args!("{}", lane))
+                })?;
+                self.out.write_char(')')
+            }
+            unknown => Err(Error::UnsupportedInstruction(unknown.opcode())),
+        }
+    }
 
-fn jump_target<O: Write>(
-    out: &mut Output<'_, O>,
-    target: format::instruction_set::JumpTarget,
-) -> Result<()> {
-    out.write_str(BLOCK_NAME_PREFIX)?;
-    out.write_fmt(format_args!("{}", target.0))
-}
+    fn code_block(&mut self, index: usize, block: &record::CodeBlock) -> Result<()> {
+        let input_types = block.input_types();
+        let temporary_types = block.temporary_types();
+        let input_count = input_types.len();
+
+        self.out.write_str(".code")?;
+        self.label(RecordName::CodeBlock, index)?;
+        self.out.write_str(" (")?;
+        for (register, value_type) in input_types.iter().enumerate() {
+            if register > 0 {
+                self.out.write_str(", ")?;
+            }
+            self.register_declaration(input_count, register, value_type)?;
+        }
+        self.out.write_str(") -> ")?;
+        self.type_list(&block.result_types())?;
+        self.out.write_ln()?;
+        self.out.indent();
+
+        let mut next_temporary = 0;
+        for instruction in block.instructions.iter() {
+            let result_count = self.contents.result_count(instruction);
+            if result_count > 0 {
+                for result in 0..result_count {
+                    if result > 0 {
+                        self.out.write_str(", ")?;
+                    }
+
+                    let temporary = next_temporary + result;
+                    self.register_declaration(input_count, input_count + temporary, temporary_types.get(temporary).unwrap())?;
+                }
+                self.out.write_str(" = ")?;
+            }
+
+            next_temporary += result_count;
+            self.instruction(input_count, instruction)?;
+            self.out.write_ln()?;
+        }
 
-fn many_code_registers<O: Write>(
-    out: &mut Output<'_, O>,
-    registers: &Vec<format::instruction_set::RegisterIndex>,
-) -> Result<()> {
-    if !registers.is_empty() {
-        out.write_char(' ')?;
-        out.write_join(", ", registers, |out, &index| code_register(out, index))?;
+        self.out.dedent();
+        Ok(())
     }
-    Ok(())
-}
 
-const METHOD_DEFINITION_NAME_PREFIX: &str = "method_definition_";
-const METHOD_IMPORT_NAME_PREFIX: &str = "method_import_";
-
-fn method_index<O: Write>(
-    out: &mut Output<'_, O>,
-    index: format::instruction_set::MethodIndex,
-) -> Result<()> {
-    out.write_char('@')?;
-    match index {
-        format::instruction_set::MethodIndex::Defined(definition_index) => {
-            out.write_str(METHOD_DEFINITION_NAME_PREFIX)?;
-            out.write_fmt(format_args!("{}", definition_index))
-        }
-        format::instruction_set::MethodIndex::Imported(import_index) => {
-            out.write_str(METHOD_IMPORT_NAME_PREFIX)?;
-            out.write_fmt(format_args!("{}", import_index))
+    fn function_template(&mut self, index: usize, template: &record::FunctionTemplate) -> Result<()> {
+        self.out.write_str(".define")?;
+        self.label(RecordName::FunctionTemplate, index)?;
+        self.out.write_str(" function ")?;
+        match &template.export {
+            record::Export::Hidden => {
+                // Hidden functions have no symbol, but the text format requires one which is then ignored.
+                self.out.write_str("hidden ")?;
+                self.out
+                    .write_fmt(format_args!("\"{}_{}\"", RecordName::FunctionTemplate.prefix(), index))?;
+            }
+            export => {
+                self.out.write_str(match export.kind() {
+                    record::ExportKind::Export => "export ",
+                    _ => "private ",
+                })?;
+                self.identifier(export.symbol().unwrap())?;
+            }
         }
+        self.out.write_str(" signature ")?;
+        self.reference(RecordName::FunctionSignature, template.signature)?;
+        self.out.write_ln()?;
+        self.out.indent();
+        self.out.write_str("/body ")?;
+        self.reference(RecordName::CodeBlock, template.entry_block)?;
+        self.out.write_ln()?;
+        self.out.dedent();
+        Ok(())
     }
-}
 
-fn code_block<O: Write, R: Fn(format::instruction_set::MethodIndex) -> u8>(
-    out: &mut Output<'_, O>,
-    block: &format::CodeBlock,
-    index: Option<usize>,
-    method_return_count: &R,
-) -> Result<()> {
-    out.write_str(".block $")?;
-    out.write_str(BLOCK_NAME_PREFIX)?;
-    if let Some(i) = index {
-        out.write_fmt(format_args!("{}", i - 1))?;
-    } else {
-        out.write_str(ENTRY_BLOCK_NAME)?;
+    fn function(&mut self, index: usize, function: &record::Function) -> Result<()> {
+        self.out.write_str(".instantiate")?;
+        self.label(RecordName::Function, index)?;
+        self.out.write_str(" function definition ")?;
+        self.reference(RecordName::FunctionTemplate, function.template)?;
+        self.out.write_ln()
     }
-    out.write_str(" (")?;
-    out.write_join(", ", 0..block.input_register_count.0, |out, i| {
-        out.write_fmt(format_args!("%a{}", i))
-    })?;
-    out.write_str_ln(") {")?;
-    out.indent();
-
-    let mut temporary_count = 0usize;
-
-    for instruction in &block.instructions.0 .0 {
-        use format::instruction_set::{Instruction, TailCall};
-
-        let return_count = instruction.return_count(method_return_count);
-        if return_count > 0 {
-            out.write_join(", ", 0..return_count, |out, index| {
-                out.write_fmt(format_args!("%t{}", temporary_count + usize::from(index)))
-            })?;
-            out.write_str(" = ")?;
-        }
 
-        temporary_count += usize::from(return_count);
+    fn record(&mut self, counts: &mut [usize; 7], record: &Record) -> Result<()> {
+        let mut next_index = |name: RecordName| {
+            let count = &mut counts[name as usize];
+            let index = *count;
+            *count += 1;
+            index
+        };
 
-        match instruction {
-            Instruction::Nop => out.write_str("nop")?,
-            Instruction::Ret(values) => {
-                out.write_str("ret")?;
-                many_code_registers(out, &values.0)?;
-            }
-            Instruction::Br(target, input_registers) => {
-                out.write_str("br ")?;
-                jump_target(out, *target)?;
-                many_code_registers(out, &input_registers.0)?
-            }
-            Instruction::BrIf {
-                condition,
-                true_branch,
-                false_branch,
-                input_registers,
-            } => {
-                out.write_str("br.if ")?;
-                code_register(out, *condition)?;
-                out.write_str(" then ")?;
-                jump_target(out, *true_branch)?;
-                out.write_str(" else ")?;
-                jump_target(out, *false_branch)?;
-                many_code_registers(out, &input_registers.0)?
-            }
-            Instruction::Call(call) => {
-                out.write_str("call ");
-                match call.tail_call {
-                    TailCall::Allowed => (),
-                    TailCall::Required => out.write_str("tail.required ")?,
-                    TailCall::Prohibited => out.write_str("tail.prohibited ")?,
-                }
-                method_index(out, call.method)?;
-                many_code_registers(out, &call.arguments.0)?;
-            }
-            Instruction::Add(operation) => {
-                basic_arithmetic_instruction(out, "add", operation, "and")?
-            }
-            Instruction::Sub(operation) => {
-                basic_arithmetic_instruction(out, "sub", operation, "from")?
-            }
-            Instruction::Mul(operation) => {
-                basic_arithmetic_instruction(out, "mul", operation, "by")?
-            }
-            Instruction::Div(operation) => division_instruction(out, "div", operation)?,
-            Instruction::And(operation) => bitwise_instruction(out, "and", operation, None)?,
-            Instruction::Or(operation) => bitwise_instruction(out, "or", operation, None)?,
-            Instruction::Not(result_type, value) => {
-                out.write_str("not ")?;
-                numeric_type(out, result_type)?;
-                out.write_char(' ')?;
-                code_register(out, *value)?;
-            }
-            Instruction::Xor(operation) => bitwise_instruction(out, "xor", operation, None)?,
-            Instruction::ShL(operation) => bitwise_shift_instruction(out, "sh.l", operation)?,
-            Instruction::ShR(operation) => bitwise_shift_instruction(out, "sh.r", operation)?,
-            Instruction::RotL(operation) => bitwise_shift_instruction(out, "rot.l", operation)?,
-            Instruction::RotR(operation) => bitwise_shift_instruction(out, "rot.r", operation)?,
-            Instruction::ConstI(constant) => out.write_fmt(format_args!(
-                "const.i {} {}",
-                constant.integer_type(),
-                constant.value()
-            ))?,
-            Instruction::Break => out.write_str("break")?,
+        match record {
+            Record::MetadataField(field) => self.metadata_field(field),
+            Record::Identifier(identifier) => {
+                self.out.write_str(".identifier")?;
+                self.label(RecordName::Identifier, next_index(RecordName::Identifier))?;
+                self.out.write_char(' ')?;
+                self.identifier(identifier)?;
+                self.out.write_ln()
+            }
+            Record::Data(bytes) => self.data(next_index(RecordName::Data), bytes),
+            Record::TypeSignature(signature) => self.type_signature(next_index(RecordName::TypeSignature), signature),
+            Record::FunctionSignature(signature) => self.function_signature(next_index(RecordName::FunctionSignature), signature),
+            Record::CodeBlock(block) => self.code_block(next_index(RecordName::CodeBlock), block),
+            Record::FunctionTemplate(template) => self.function_template(next_index(RecordName::FunctionTemplate), template),
+            Record::Function(function) => self.function(next_index(RecordName::Function), function),
+            unknown => Err(Error::UnsupportedRecord(unknown.record_type())),
         }
-
-        out.write_char_ln(';')?;
     }
-
-    out.dedent();
-    out.write_str_ln("};")
 }
 
-fn module_method_bodies<O: Write, R: Fn(format::instruction_set::MethodIndex) -> u8>(
-    out: &mut Output<'_, O>,
-    method_bodies: &[format::Code],
-    method_return_count: &R,
-) -> Result<()> {
-    for (index, body) in method_bodies.iter().enumerate() {
-        out.write_fmt_ln(format_args!(".code @code_{} {{", index))?;
-        out.indent();
-        out.write_str(".entry $")?;
-        out.write_str(BLOCK_NAME_PREFIX)?;
-        out.write_str(ENTRY_BLOCK_NAME)?;
-        out.write_char_ln(';')?;
-        code_block(out, &body.entry_block, None, method_return_count)?;
-        out.write_ln()?;
-        for (block_index, block) in body.blocks.iter().enumerate() {
-            code_block(out, block, Some(block_index + 1), method_return_count)?;
-            out.write_ln()?;
+/// Writes the text representation of the records of a module, which must be valid.
+///
+/// The records are written in the same order that they appear in the module, so that assembling the output produces the
+/// original module.
+pub fn disassemble<O: Write>(output: &mut O, module: &Builder<'_>, options: &Options) -> Result<()> {
+    let validated_module = ValidModule::from_records(module.records().iter().cloned())?;
+    let mut disassembler = Disassembler {
+        out: Output::new(output, options.indentation),
+        references: options.references,
+        contents: validated_module.contents(),
+    };
+
+    let out = &mut disassembler.out;
+    out.write_str_ln("; Disassembled by dasmdl")?;
+    out.write_fmt(format_args!(
+        ".format major {}\n.format minor {}\n",
+        module.format_version().major,
+        module.format_version().minor
+    ))?;
+
+    let mut counts = [0usize; 7];
+    let mut previous_type = None;
+    for record in module.records().iter() {
+        // A blank line is written between records of different types, and before every code block and function definition.
+        let record_type = record.record_type();
+        if previous_type != Some(record_type) || matches!(record, Record::CodeBlock(_) | Record::FunctionTemplate(_)) {
+            disassembler.out.write_ln()?;
         }
-        out.dedent();
-        out.write_str_ln("};")?;
+
+        previous_type = Some(record_type);
+        disassembler.record(&mut counts, record)?;
     }
-    Ok(())
-}
 
-fn indexed_namespace<O: Write>(
-    out: &mut Output<'_, O>,
-    index: format::indices::Namespace,
-    identifiers: &[format::Identifier],
-    namespaces: &[format::Namespace],
-) -> Result<()> {
-    indexed(out, index, &namespaces, |out, ns| {
-        quoted_namespace(out, identifiers, ns)
-    })
+    Ok(())
 }
 
-fn visibility<O: Write>(out: &mut Output<'_, O>, visibility: format::Visibility) -> Result<()> {
-    out.write_str(match visibility {
-        format::Visibility::Unspecified => " ",
-        format::Visibility::Public => " public",
-        format::Visibility::Private => " private",
-    })
+/// Reads a binary module from the `source`, and writes its text representation.
+pub fn disassemble_from<O: Write, R: Read>(output: &mut O, source: Reader<R>, options: &Options) -> Result<()> {
+    disassemble(output, &Builder::from_reader(source)?, options)
 }
 
-fn module_definitions<O: Write>(
-    out: &mut Output<'_, O>,
-    identifiers: &[format::Identifier],
-    namespaces: &[format::Namespace],
-    definitions: &format::ModuleDefinitions,
-) -> Result<()> {
-    for (index, type_definition) in definitions.defined_types.iter().enumerate() {
-        out.write_fmt(format_args!(".type @type_{}", index))?;
-        visibility(out, type_definition.visibility)?;
-        // TODO: Print other type attributes.
-        out.write_str_ln(" {")?;
-        out.indent();
-
-        directive(out, "name", |out| {
-            indexed_identifier(out, type_definition.name, identifiers)
-        })?;
-        directive(out, "namespace", |out| {
-            indexed_namespace(out, type_definition.namespace, identifiers, namespaces)
-        })?;
-        out.write_ln()?;
-
-        data_header(out, "Fields", type_definition.fields.len())?;
-        out.write_ln()?;
-
-        data_header(out, "Methods", type_definition.methods.len())?;
-        for method_index in type_definition.methods.iter() {
-            // TODO: Check that method index is in bounds, and that owner is correct.
-            let method = &definitions.defined_methods[usize::try_from(*method_index).unwrap()];
-            out.write_str(".method @")?;
-            out.write_str(METHOD_DEFINITION_NAME_PREFIX)?;
-            out.write_fmt(format_args!("{} (", method_index))?;
-            // TODO: Write signature things.
-            out.write_str(") returns (")?;
-            out.write_str(") ")?;
-            visibility(out, method.visibility)?;
-            // TODO: Write other method attributes.
-            out.write_str_ln(" {")?;
-            out.indent();
-
-            directive(out, "name", |out| {
-                indexed_identifier(out, method.name, identifiers)
-            })?;
-
-            out.dedent();
-            out.write_str_ln("};")?;
-        }
-        out.write_ln()?;
+#[cfg(test)]
+mod tests {
+    use crate::disassembler::{self, Options, ReferenceStyle};
+    use sailar::builder::Builder;
+    use sailar::identifier::Id;
+    use sailar::record::Record;
+    use std::borrow::Cow;
+
+    fn assert_round_trip(module: &Builder, options: &Options) {
+        let mut binary = Vec::new();
+        module.write_to(&mut binary).unwrap();
+
+        let mut text = Vec::new();
+        disassembler::disassemble_from(&mut text, sailar::reader::Reader::new(binary.as_slice()), options).unwrap();
+        let text = String::from_utf8(text).unwrap();
+
+        let mut tree = None;
+        let assembled = match sailasm::assemble(&text, &mut tree) {
+            Ok(assembled) => assembled,
+            Err(errors) => panic!("{:?}\n{}", errors, text),
+        };
 
-        out.dedent();
-        out.write_str_ln("};")?;
+        let mut reassembled_binary = Vec::new();
+        assembled.write_to(&mut reassembled_binary).unwrap();
+        assert!(
+            binary == reassembled_binary,
+            "round trip produced a different module:\n{}",
+            text
+        );
     }
-    Ok(())
-}
 
-pub fn disassemble<O: Write>(
-    output: &mut O,
-    module: &format::Module,
-    indentation: Indentation,
-    // TODO: Have option to have indices be in base 10 or hex.
-) -> Result<()> {
-    let mut out = Output::new(output, indentation);
-    out.write_str_ln("// Disassembled by dasmdl")?;
-    out.write_fmt_ln(format_args!(
-        "// IntegerSize: {} bytes",
-        module.integer_size
-    ))?;
-    out.write_ln()?;
-    out.write_fmt_ln(format_args!(
-        ".format {{ .major {}; .minor {}; }};",
-        module.format_version.major, module.format_version.minor
-    ))?;
-    out.write_ln()?;
-    module_header(&mut out, &module.header.0)?;
-    out.write_ln()?;
-    // TODO: Option to omit identifiers.
-    commented_module_data(&mut out, &module.identifiers, "Identifiers", |out, name| {
-        quoted_identifier(out, name)
-    })?;
-    out.write_ln()?;
-    // TODO: Option to omit namespaces.
-    commented_module_data(&mut out, &module.namespaces, "Namespaces", |out, ns| {
-        quoted_namespace(out, &module.identifiers, ns)
-    })?;
-    out.write_ln()?;
-    commented_module_data(
-        &mut out,
-        &module.type_signatures,
-        "Type Signatures",
-        |out, signature| type_signature(out, signature),
-    )?;
-    out.write_ln()?;
-    // TODO: Print method signatures.
-    out.write_ln()?;
-    {
-        let method_return_count = |index: format::instruction_set::MethodIndex| {
-            // TODO: Print error comments if method or signature is not found while retrieving return value count.
-            let method = &module.definitions.0.defined_methods.0[usize::try_from(index).unwrap()];
-            let signature = &module.method_signatures.0[usize::try_from(method.signature).unwrap()];
-            signature.return_types.0.len().try_into().unwrap()
+    macro_rules! round_trip_tests {
+        ($($test_name: ident => $sample: expr,)*) => {
+            $(
+                #[test]
+                fn $test_name() {
+                    let module = $sample;
+                    assert_round_trip(&module, &Options::default());
+                    assert_round_trip(
+                        &module,
+                        &Options {
+                            references: ReferenceStyle::Index,
+                            ..Default::default()
+                        },
+                    );
+                }
+            )*
         };
-        module_method_bodies(&mut out, &module.method_bodies, &method_return_count)?;
     }
-    out.write_ln()?;
-    // TODO: Print data arrays.
-    out.write_ln()?;
-    // TODO: Print imports.
-    data_header(
-        &mut out,
-        "Module Imports",
-        module.imports.imported_modules.len(),
-    )?;
-    out.write_ln()?;
-    data_header(
-        &mut out,
-        "Type Imports",
-        module.imports.imported_types.len(),
-    )?;
-    out.write_ln()?;
-    data_header(
-        &mut out,
-        "Type Definitions",
-        module.definitions.defined_types.len(),
-    )?;
-    module_definitions(
-        &mut out,
-        &module.identifiers,
-        &module.namespaces,
-        &module.definitions,
-    )?;
-    out.write_ln()
+
+    round_trip_tests! {
+        exit_with_round_trip => sailar_samples::exit_with("Exit".try_into().unwrap(), 42),
+        fibonacci_round_trip => sailar_samples::fibonacci("Fibonacci".try_into().unwrap(), 10),
+        parallel_fibonacci_round_trip => sailar_samples::parallel_fibonacci("ParallelFibonacci".try_into().unwrap(), 10),
+        sum_loop_round_trip => sailar_samples::sum_loop("Loop".try_into().unwrap(), 100),
+        atomic_counter_round_trip => sailar_samples::atomic_counter("Counter".try_into().unwrap(), 1000),
+        vector_sum_round_trip => sailar_samples::vector_sum("Vectors".try_into().unwrap(), 5),
+        vector_call_round_trip => sailar_samples::vector_call("VectorCall".try_into().unwrap(), 5),
+    }
+
+    #[test]
+    fn identifiers_and_data_round_trip() {
+        let mut module = Builder::new();
+        module.add_record(Record::Identifier(Cow::Borrowed(
+            Id::try_from_str("with \"quotes\"\tand\\escapes").unwrap(),
+        )));
+        module.add_record(Record::Identifier(Cow::Borrowed(Id::try_from_str("ünïcödé").unwrap())));
+        module.add_record(Record::Data(Cow::Owned((0u8..40).collect())));
+        assert_round_trip(&module, &Options::default());
+    }
+
+    #[test]
+    fn instructions_are_disassembled() {
+        let mut text = Vec::new();
+        let module = sailar_samples::fibonacci("Fibonacci".try_into().unwrap(), 10);
+        disassembler::disassemble(&mut text, &module, &Options::default()).unwrap();
+        let text = String::from_utf8(text).unwrap();
+        assert!(text.contains("\n    $t0:@type_0 = cmp $i0 lt 2\n"), "{}", text);
+        assert!(text.contains("\n    br.if $t0 then @code_2 else @code_3 ($i0)\n"), "{}", text);
+        assert!(
+            text.contains("\n.define @template_0 function export \"main\" signature @signature_0\n"),
+            "{}",
+            text
+        );
+    }
 }
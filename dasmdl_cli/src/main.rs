@@ -1,31 +1,46 @@
-use structopt::StructOpt;
+//! Executable for disassembling SAILAR binary modules
 
-#[derive(StructOpt)]
+use dasmdl::disassembler::{Indentation, Options, ReferenceStyle};
+
+/// SAILAR bytecode disassembler
+#[derive(Debug, clap::Parser)]
+#[clap(version, about)]
 struct Arguments {
-    /// The input binary module file to assemble.
-    #[structopt(long, short)]
+    /// Path to the SAILAR binary module to disassemble.
+    #[clap(long, short)]
     input: std::path::PathBuf,
-    /// The path to the file containing the disassembled text module.
-    #[structopt(long, short)]
+    /// Path to the file containing the disassembled text module.
+    #[clap(long, short)]
     output: Option<std::path::PathBuf>,
-    //indentation: dasmdl::disassembler::Indentation,
+    /// Number of spaces used to indent instructions.
+    #[clap(long, default_value_t = 4, conflicts_with = "tabs")]
+    spaces: u8,
+    /// Indent instructions with tabs instead of spaces.
+    #[clap(long)]
+    tabs: bool,
+    /// Refer to records and registers by their raw indices, instead of by generated names.
+    #[clap(long)]
+    indices: bool,
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let args = Arguments::from_args();
-    let output_path = args
-        .output
-        .unwrap_or_else(|| args.input.with_extension("txtmdl"));
-    let module = {
-        let mut input = std::io::BufReader::new(std::fs::File::open(args.input)?);
-        sailar::parser::parse_module(&mut input)?
+    let arguments: Arguments = clap::Parser::parse();
+    let options = Options {
+        indentation: if arguments.tabs {
+            Indentation::Tabs
+        } else {
+            Indentation::Spaces(arguments.spaces)
+        },
+        references: if arguments.indices {
+            ReferenceStyle::Index
+        } else {
+            ReferenceStyle::Symbolic
+        },
     };
 
+    let input = std::io::BufReader::new(std::fs::File::open(&arguments.input)?);
+    let output_path = arguments.output.unwrap_or_else(|| arguments.input.with_extension("sailas"));
     let mut output = std::io::BufWriter::new(std::fs::File::create(output_path)?);
-    dasmdl::disassembler::disassemble(
-        &mut output,
-        &module,
-        dasmdl::disassembler::Indentation::default(),
-    )?;
+    dasmdl::disassembler::disassemble_from(&mut output, sailar::reader::Reader::new(input), &options)?;
     Ok(())
 }
@@ -1,8 +1,9 @@
 //! Low-level API for building SAILAR binary modules.
 
 use crate::binary::RawModule;
+use crate::num::VarU28;
 use crate::reader;
-use crate::record::Record;
+use crate::record::{self, Record};
 use crate::versioning;
 use crate::writer;
 use std::io::{Read, Write};
@@ -12,6 +13,27 @@ use std::io::{Read, Write};
 pub struct Builder<'a> {
     format_version: versioning::SupportedFormat,
     records: Vec<Record<'a>>,
+    array_packing: bool,
+}
+
+/// Returns `true` if records of the specified type can be packed into array records.
+///
+/// Since the sizes of array elements are not stored, only identifiers and data, which are preceded by their length, and type
+/// signatures, which are only a few bytes long, are packed. This allows code blocks and function templates to be indexed
+/// without being parsed.
+fn is_packable(record_type: record::Type) -> bool {
+    matches!(
+        record_type,
+        record::Type::Identifier | record::Type::Data | record::Type::TypeSignature
+    )
+}
+
+/// Gets the size of a record with the specified content length, including its type and size.
+fn record_size(content_length: usize) -> std::io::Result<usize> {
+    match VarU28::try_from(content_length) {
+        Ok(length) => Ok(1 + usize::from(length.byte_length().get()) + content_length),
+        Err(error) => Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, error)),
+    }
 }
 
 impl<'a> Builder<'a> {
@@ -19,6 +41,7 @@ impl<'a> Builder<'a> {
         Self {
             format_version,
             records: Vec::default(),
+            array_packing: true,
         }
     }
 
@@ -31,6 +54,19 @@ impl<'a> Builder<'a> {
         &self.format_version
     }
 
+    /// Returns `true` if consecutive records of the same type are written as array records.
+    #[inline]
+    pub fn array_packing(&self) -> bool {
+        self.array_packing
+    }
+
+    /// Sets whether consecutive records of the same type are written as array records, which is enabled by default.
+    ///
+    /// Only identifier, data, and type signature records are packed, and only if it results in a smaller module.
+    pub fn set_array_packing(&mut self, enabled: bool) {
+        self.array_packing = enabled;
+    }
+
     /// Appends a record to this module.
     pub fn add_record<R: Into<Record<'a>>>(&mut self, record: R) {
         self.records.push(record.into());
@@ -44,25 +80,67 @@ impl<'a> Builder<'a> {
 
     /// Writes the binary contents of the SAILAR module to the specified destination.
     pub fn write_to<W: Write>(&self, destination: W) -> std::io::Result<()> {
-        let mut wrapper = writer::Writer::new(destination);
-        let out = &mut wrapper;
+        // The number of records is written first, and is only known once it is decided which records are packed into arrays.
+        let mut record_buffer = Vec::new();
+        let mut record_count = 0usize;
+        let mut content_buffer = Vec::with_capacity(64);
+        let mut array_buffer = Vec::new();
+        let records = &mut writer::VecWriter::new(&mut record_buffer);
+        let mut remaining_records = self.records.as_slice();
 
-        out.write_all(crate::binary::MAGIC)?;
-        out.write_all(&[self.format_version.major, self.format_version.minor])?;
-        out.write_length(self.records.len())?;
+        while let Some(first_record) = remaining_records.first() {
+            let record_type = first_record.record_type();
+            let run_length = if self.array_packing && is_packable(record_type) {
+                remaining_records
+                    .iter()
+                    .take_while(|record| record.record_type() == record_type)
+                    .count()
+            } else {
+                1
+            };
 
-        let mut content_buffer = Vec::with_capacity(64);
+            let (run, following_records) = remaining_records.split_at(run_length);
+            remaining_records = following_records;
+
+            if let [record] = run {
+                content_buffer.clear();
+                writer::VecWriter::new(&mut content_buffer).write_record_content(record)?;
+                records.write_record(record_type, &content_buffer)?;
+                record_count += 1;
+                continue;
+            }
+
+            array_buffer.clear();
+            let mut unpacked_size = 0;
+            let array_content = &mut writer::VecWriter::new(&mut array_buffer);
+            array_content.write_byte(u8::from(record_type))?;
+            array_content.write_length(run.len())?;
+            for record in run.iter() {
+                content_buffer.clear();
+                writer::VecWriter::new(&mut content_buffer).write_record_content(record)?;
+                array_content.write_array_element(record_type, &content_buffer)?;
+                unpacked_size += record_size(content_buffer.len())?;
+            }
 
-        for record in self.records.iter() {
-            content_buffer.clear();
-            let mut record_content = writer::VecWriter::new(&mut content_buffer);
-            record_content.write_record_content(record)?;
-            out.write_byte(u8::from(record.record_type()))?;
-            out.write_length(content_buffer.len())?;
-            out.write_all(&content_buffer)?;
+            if record_size(array_buffer.len())? < unpacked_size {
+                records.write_record(record::Type::Array, &array_buffer)?;
+                record_count += 1;
+            } else {
+                for record in run.iter() {
+                    content_buffer.clear();
+                    writer::VecWriter::new(&mut content_buffer).write_record_content(record)?;
+                    records.write_record(record_type, &content_buffer)?;
+                }
+                record_count += run.len();
+            }
         }
 
-        Ok(())
+        let mut wrapper = writer::Writer::new(destination);
+        let out = &mut wrapper;
+        out.write_all(crate::binary::MAGIC)?;
+        out.write_all(&[self.format_version.major, self.format_version.minor])?;
+        out.write_length(record_count)?;
+        out.write_all(&record_buffer)
     }
 
     /// Converts `self` into a vector containing the module's records.
@@ -100,7 +178,11 @@ impl Builder<'static> {
             records.push(record?);
         }
 
-        Ok(Self { format_version, records })
+        Ok(Self {
+            format_version,
+            records,
+            array_packing: true,
+        })
     }
 
     pub fn read_from<R: Read>(source: R) -> reader::Result<Self> {
@@ -132,3 +214,58 @@ impl<'a> Extend<Record<'a>> for Builder<'a> {
         self.records.extend(iter)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identifier::Identifier;
+    use crate::index;
+    use crate::signature;
+
+    fn sample_builder() -> Builder<'static> {
+        let mut builder = Builder::new();
+        builder.add_record(Identifier::try_from_str("first").unwrap());
+        builder.add_record(Identifier::try_from_str("second").unwrap());
+
+        for integer_type in [
+            signature::IntegerType::U8,
+            signature::IntegerType::S32,
+            signature::IntegerType::U64,
+        ] {
+            builder.add_record(signature::Type::from(integer_type));
+        }
+
+        for (parameter, result) in [(0usize, 1usize), (1, 2), (2, 0)] {
+            builder.add_record(signature::Function::new(
+                vec![index::TypeSignature::from(parameter)],
+                vec![index::TypeSignature::from(result)],
+            ));
+        }
+
+        builder
+    }
+
+    #[test]
+    fn consecutive_records_are_packed_into_arrays() {
+        let mut builder = sample_builder();
+        let packed = builder.to_raw_module();
+        builder.set_array_packing(false);
+        let unpacked = builder.to_raw_module();
+        assert!(packed.len() < unpacked.len());
+
+        let expected = builder.records();
+        assert_eq!(Builder::read_from(packed.bytes()).unwrap().records(), expected);
+        assert_eq!(Builder::read_from(unpacked.bytes()).unwrap().records(), expected);
+
+        let index = reader::SliceReader::new(&packed).to_record_index().unwrap();
+        let array_elements = index
+            .locations()
+            .iter()
+            .map(reader::RecordLocation::is_array_element)
+            .collect::<Vec<_>>();
+
+        // Identifiers are larger when packed, since their lengths are written in both cases, and function signatures are never
+        // packed.
+        assert_eq!(array_elements, [false, false, true, true, true, false, false, false]);
+    }
+}
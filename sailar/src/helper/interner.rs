@@ -0,0 +1,43 @@
+//! Assigns indices to unique values.
+
+use std::hash::Hash;
+
+/// Assigns an index to each unique value.
+#[derive(Clone, Debug)]
+pub(crate) struct Interner<T, I> {
+    values: Vec<T>,
+    lookup: rustc_hash::FxHashMap<T, I>,
+}
+
+impl<T, I> Default for Interner<T, I> {
+    fn default() -> Self {
+        Self {
+            values: Vec::new(),
+            lookup: Default::default(),
+        }
+    }
+}
+
+impl<T, I> Interner<T, I> {
+    pub(crate) fn values(&self) -> &[T] {
+        &self.values
+    }
+
+    pub(crate) fn into_values(self) -> Vec<T> {
+        self.values
+    }
+}
+
+impl<T: Clone + Eq + Hash, I: Copy + From<usize>> Interner<T, I> {
+    /// Adds the value if it was not already added, returning its index.
+    pub(crate) fn intern(&mut self, value: T) -> I {
+        if let Some(index) = self.lookup.get(&value) {
+            return *index;
+        }
+
+        let index = I::from(self.values.len());
+        self.values.push(value.clone());
+        self.lookup.insert(value, index);
+        index
+    }
+}
@@ -3,3 +3,4 @@
 pub mod borrow;
 
 pub(crate) mod buffer;
+pub(crate) mod interner;
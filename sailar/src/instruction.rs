@@ -280,6 +280,489 @@ impl IntegerArithmetic {
     }
 }
 
+/// Indicates how two integer values are compared.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum ComparisonKind {
+    Equal = 0,
+    NotEqual = 1,
+    LessThan = 2,
+    GreaterThan = 3,
+    LessThanOrEqual = 4,
+    GreaterThanOrEqual = 5,
+}
+
+impl From<ComparisonKind> for u8 {
+    #[inline]
+    fn from(kind: ComparisonKind) -> u8 {
+        kind as u8
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{value:#02X} is not a valid comparison kind")]
+pub struct InvalidComparisonKindError {
+    value: u8,
+}
+
+impl TryFrom<u8> for ComparisonKind {
+    type Error = InvalidComparisonKindError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Equal),
+            1 => Ok(Self::NotEqual),
+            2 => Ok(Self::LessThan),
+            3 => Ok(Self::GreaterThan),
+            4 => Ok(Self::LessThanOrEqual),
+            5 => Ok(Self::GreaterThanOrEqual),
+            _ => Err(InvalidComparisonKindError { value }),
+        }
+    }
+}
+
+impl Display for ComparisonKind {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Equal => "eq",
+            Self::NotEqual => "ne",
+            Self::LessThan => "lt",
+            Self::GreaterThan => "gt",
+            Self::LessThanOrEqual => "le",
+            Self::GreaterThanOrEqual => "ge",
+        })
+    }
+}
+
+/// Describes a comparison of two integer values, whether or not the values are signed is inferred from their types.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Comparison {
+    kind: ComparisonKind,
+    x: Value,
+    y: Value,
+}
+
+impl Comparison {
+    pub fn new(kind: ComparisonKind, x: Value, y: Value) -> Self {
+        Self { kind, x, y }
+    }
+
+    #[inline]
+    pub fn kind(&self) -> ComparisonKind {
+        self.kind
+    }
+
+    #[inline]
+    pub fn x_value(&self) -> &Value {
+        &self.x
+    }
+
+    #[inline]
+    pub fn y_value(&self) -> &Value {
+        &self.y
+    }
+}
+
+/// Describes a conditional branch to one of two code blocks.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BranchIf {
+    condition: Value,
+    true_branch: index::CodeBlock,
+    false_branch: index::CodeBlock,
+    arguments: Box<[Value]>,
+}
+
+impl BranchIf {
+    pub fn new(condition: Value, true_branch: index::CodeBlock, false_branch: index::CodeBlock, arguments: Box<[Value]>) -> Self {
+        Self {
+            condition,
+            true_branch,
+            false_branch,
+            arguments,
+        }
+    }
+
+    /// The integer value checked by the branch, a value of zero is `false` while all other values are `true`.
+    #[inline]
+    pub fn condition(&self) -> &Value {
+        &self.condition
+    }
+
+    #[inline]
+    pub fn true_branch(&self) -> index::CodeBlock {
+        self.true_branch
+    }
+
+    #[inline]
+    pub fn false_branch(&self) -> index::CodeBlock {
+        self.false_branch
+    }
+
+    /// The values provided as inputs to whichever block is branched to.
+    #[inline]
+    pub fn arguments(&self) -> &[Value] {
+        &self.arguments
+    }
+}
+
+/// Specifies the constraints on how an atomic operation can be reordered with other memory operations.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum MemoryOrdering {
+    /// Only guarantees that the operation itself is atomic.
+    Relaxed = 0,
+    /// Subsequent memory operations cannot be moved before this operation, only valid for operations that read memory.
+    Acquire = 1,
+    /// Previous memory operations cannot be moved after this operation, only valid for operations that write memory.
+    Release = 2,
+    /// Combines [`MemoryOrdering::Acquire`] and [`MemoryOrdering::Release`], only valid for operations that both read and
+    /// write memory.
+    AcquireRelease = 3,
+    /// Like [`MemoryOrdering::AcquireRelease`], but additionally guarantees that all threads observe all sequentially
+    /// consistent operations in the same order.
+    SequentiallyConsistent = 4,
+}
+
+impl MemoryOrdering {
+    /// Returns `true` if this ordering can be used with an operation that only reads memory.
+    pub fn is_valid_for_load(self) -> bool {
+        matches!(self, Self::Relaxed | Self::Acquire | Self::SequentiallyConsistent)
+    }
+
+    /// Returns `true` if this ordering can be used with an operation that only writes memory.
+    pub fn is_valid_for_store(self) -> bool {
+        matches!(self, Self::Relaxed | Self::Release | Self::SequentiallyConsistent)
+    }
+
+    /// Returns `true` if this ordering can be used when a compare-and-swap whose `success` ordering is specified fails,
+    /// meaning that this ordering is valid for a load and is not stronger than the `success` ordering.
+    pub fn is_valid_for_compare_exchange_failure(self, success: Self) -> bool {
+        match self {
+            Self::Relaxed => true,
+            Self::Acquire => matches!(success, Self::Acquire | Self::AcquireRelease | Self::SequentiallyConsistent),
+            Self::SequentiallyConsistent => success == Self::SequentiallyConsistent,
+            _ => false,
+        }
+    }
+}
+
+impl From<MemoryOrdering> for u8 {
+    #[inline]
+    fn from(ordering: MemoryOrdering) -> u8 {
+        ordering as u8
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{value:#02X} is not a valid memory ordering")]
+pub struct InvalidMemoryOrderingError {
+    value: u8,
+}
+
+impl TryFrom<u8> for MemoryOrdering {
+    type Error = InvalidMemoryOrderingError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Relaxed),
+            1 => Ok(Self::Acquire),
+            2 => Ok(Self::Release),
+            3 => Ok(Self::AcquireRelease),
+            4 => Ok(Self::SequentiallyConsistent),
+            _ => Err(InvalidMemoryOrderingError { value }),
+        }
+    }
+}
+
+impl Display for MemoryOrdering {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Relaxed => "relaxed",
+            Self::Acquire => "acquire",
+            Self::Release => "release",
+            Self::AcquireRelease => "acq_rel",
+            Self::SequentiallyConsistent => "seq_cst",
+        })
+    }
+}
+
+/// Indicates how the value stored in memory is modified by an atomic read-modify-write operation.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[repr(u8)]
+#[non_exhaustive]
+pub enum AtomicOperation {
+    /// Replaces the value in memory.
+    Exchange = 0,
+    Add = 1,
+    Sub = 2,
+    And = 3,
+    Or = 4,
+    Xor = 5,
+}
+
+impl From<AtomicOperation> for u8 {
+    #[inline]
+    fn from(operation: AtomicOperation) -> u8 {
+        operation as u8
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{value:#02X} is not a valid atomic operation")]
+pub struct InvalidAtomicOperationError {
+    value: u8,
+}
+
+impl TryFrom<u8> for AtomicOperation {
+    type Error = InvalidAtomicOperationError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Exchange),
+            1 => Ok(Self::Add),
+            2 => Ok(Self::Sub),
+            3 => Ok(Self::And),
+            4 => Ok(Self::Or),
+            5 => Ok(Self::Xor),
+            _ => Err(InvalidAtomicOperationError { value }),
+        }
+    }
+}
+
+impl Display for AtomicOperation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Exchange => "xchg",
+            Self::Add => "add",
+            Self::Sub => "sub",
+            Self::And => "and",
+            Self::Or => "or",
+            Self::Xor => "xor",
+        })
+    }
+}
+
+/// Describes an atomic read of an integer value from memory.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AtomicLoad {
+    ordering: MemoryOrdering,
+    address: Value,
+}
+
+impl AtomicLoad {
+    pub fn new(ordering: MemoryOrdering, address: Value) -> Self {
+        Self { ordering, address }
+    }
+
+    #[inline]
+    pub fn ordering(&self) -> MemoryOrdering {
+        self.ordering
+    }
+
+    /// The pointer to the integer that is read.
+    #[inline]
+    pub fn address(&self) -> &Value {
+        &self.address
+    }
+}
+
+/// Describes an atomic write of an integer value to memory.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AtomicStore {
+    ordering: MemoryOrdering,
+    address: Value,
+    value: Value,
+}
+
+impl AtomicStore {
+    pub fn new(ordering: MemoryOrdering, address: Value, value: Value) -> Self {
+        Self {
+            ordering,
+            address,
+            value,
+        }
+    }
+
+    #[inline]
+    pub fn ordering(&self) -> MemoryOrdering {
+        self.ordering
+    }
+
+    /// The pointer to the integer that is written.
+    #[inline]
+    pub fn address(&self) -> &Value {
+        &self.address
+    }
+
+    #[inline]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// Describes an atomic modification of an integer value in memory.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AtomicReadModifyWrite {
+    operation: AtomicOperation,
+    ordering: MemoryOrdering,
+    address: Value,
+    value: Value,
+}
+
+impl AtomicReadModifyWrite {
+    pub fn new(operation: AtomicOperation, ordering: MemoryOrdering, address: Value, value: Value) -> Self {
+        Self {
+            operation,
+            ordering,
+            address,
+            value,
+        }
+    }
+
+    #[inline]
+    pub fn operation(&self) -> AtomicOperation {
+        self.operation
+    }
+
+    #[inline]
+    pub fn ordering(&self) -> MemoryOrdering {
+        self.ordering
+    }
+
+    /// The pointer to the integer that is modified.
+    #[inline]
+    pub fn address(&self) -> &Value {
+        &self.address
+    }
+
+    /// The value that is combined with the value in memory.
+    #[inline]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// Describes an atomic compare-and-swap of an integer value in memory.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct AtomicCompareExchange {
+    success_ordering: MemoryOrdering,
+    failure_ordering: MemoryOrdering,
+    address: Value,
+    expected: Value,
+    replacement: Value,
+}
+
+impl AtomicCompareExchange {
+    pub fn new(
+        success_ordering: MemoryOrdering,
+        failure_ordering: MemoryOrdering,
+        address: Value,
+        expected: Value,
+        replacement: Value,
+    ) -> Self {
+        Self {
+            success_ordering,
+            failure_ordering,
+            address,
+            expected,
+            replacement,
+        }
+    }
+
+    /// The ordering used when the value in memory is replaced.
+    #[inline]
+    pub fn success_ordering(&self) -> MemoryOrdering {
+        self.success_ordering
+    }
+
+    /// The ordering used when the value in memory is only read, since it did not equal the expected value.
+    #[inline]
+    pub fn failure_ordering(&self) -> MemoryOrdering {
+        self.failure_ordering
+    }
+
+    /// The pointer to the integer that is compared and possibly replaced.
+    #[inline]
+    pub fn address(&self) -> &Value {
+        &self.address
+    }
+
+    #[inline]
+    pub fn expected(&self) -> &Value {
+        &self.expected
+    }
+
+    #[inline]
+    pub fn replacement(&self) -> &Value {
+        &self.replacement
+    }
+}
+
+/// Describes the replacement of a single lane of a vector.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VectorInsert {
+    vector: Value,
+    value: Value,
+    lane: u16,
+}
+
+impl VectorInsert {
+    pub fn new(vector: Value, value: Value, lane: u16) -> Self {
+        Self { vector, value, lane }
+    }
+
+    #[inline]
+    pub fn vector(&self) -> &Value {
+        &self.vector
+    }
+
+    /// The value that is stored in the lane.
+    #[inline]
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    #[inline]
+    pub fn lane(&self) -> u16 {
+        self.lane
+    }
+}
+
+/// Describes the creation of a vector from the lanes of two other vectors of the same type.
+///
+/// Each index selects a lane from the concatenation of `x` and `y`, so an index equal to the lane count of `x` refers to the
+/// first lane of `y`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VectorShuffle {
+    x: Value,
+    y: Value,
+    lanes: Box<[u16]>,
+}
+
+impl VectorShuffle {
+    pub fn new(x: Value, y: Value, lanes: Box<[u16]>) -> Self {
+        Self { x, y, lanes }
+    }
+
+    #[inline]
+    pub fn x_value(&self) -> &Value {
+        &self.x
+    }
+
+    #[inline]
+    pub fn y_value(&self) -> &Value {
+        &self.y
+    }
+
+    /// The indices of the lanes that make up the resulting vector.
+    #[inline]
+    pub fn lanes(&self) -> &[u16] {
+        &self.lanes
+    }
+}
+
 #[derive(Clone, Debug, thiserror::Error)]
 #[error("{value:#02X} is not a valid opcode")]
 pub struct InvalidOpcodeError {
@@ -342,8 +825,17 @@ instruction_set! {{
     Return(_values: Box<[Value]>,) = 2,
     // Select = 3,
     // Switch = 4,
-    // Br = 5,
-    // BrIf = 6,
+    /// ```text
+    /// br <target> (<argument0>, <argument1>, ...)
+    /// ```
+    /// Transfers control flow to the `target` block, providing the specified values as the inputs of the block.
+    Br(_target: index::CodeBlock, _arguments: Box<[Value]>,) = 5,
+    /// ```text
+    /// br.if <condition> then <true_branch> else <false_branch> (<argument0>, <argument1>, ...)
+    /// ```
+    /// Transfers control flow to the `true_branch` if the `condition` is not zero, or to the `false_branch` otherwise. The
+    /// specified values are provided as the inputs of the block that is branched to.
+    BrIf(_branch: Box<BranchIf>,) = 6,
     /// ```text
     /// <result0>, <result1>, ... = call <function> (<argument0>, <argument1>, ...) ; Call function with return values
     /// call <function> (<argument0>, <argument1>, ...) ; Call function with no return values
@@ -357,14 +849,15 @@ instruction_set! {{
     /// <sum> = iadd sat <x> <y> ; Performs saturating addition
     /// <sum>, <overflowed> = iadd ovf <x> <y>
     /// ```
-    /// Calculates the sum of two integer values.
+    /// Calculates the sum of two integer values. When the operands are integer vectors, the sum of each pair of lanes is
+    /// calculated, and overflow flags are not allowed.
     IAdd(_op: Box<IntegerArithmetic>,) = 0xA,
     /// ```text
     /// <sum> = isub <x> <y> ; Calculates x - y, ignoring any overflow/underflow
     /// <sum> = isub sat <x> <y>
     /// <sum>, <overflowed> = isub ovf <x> <y>
     /// ```
-    /// Calculates the integer result of subtracting `y` from `x`.
+    /// Calculates the integer result of subtracting `y` from `x`. Integer vectors are subtracted lane-wise.
     ISub(_op: Box<IntegerArithmetic>,) = 0xB,
     // TODO: Could introduce muli overflow variant that returns the HIGH overflowing bits instead of just a single I overflow bool.
     //IMul(_op: Box<IntegerArithmetic>,) = 0xC,
@@ -383,9 +876,83 @@ instruction_set! {{
     //Or,
     //Xor,
     //Rotate,
-    //Cmp,
+    /// ```text
+    /// <result> = cmp <x> eq <y> ; Checks if x == y
+    /// <result> = cmp <x> ne <y> ; Checks if x != y
+    /// <result> = cmp <x> lt <y> ; Checks if x < y
+    /// <result> = cmp <x> gt <y> ; Checks if x > y
+    /// <result> = cmp <x> le <y> ; Checks if x <= y
+    /// <result> = cmp <x> ge <y> ; Checks if x >= y
+    /// ```
+    /// Compares two integer values, producing an integer result of `1` if the comparison is true, or `0` otherwise.
+    Cmp(_comparison: Box<Comparison>,) = 0x1C,
     //BitCount,
     //Reverse,
+    /// ```text
+    /// <thread> = thread.spawn <function> (<argument0>, <argument1>, ...)
+    /// ```
+    /// Starts a new thread that calls the specified `function` with the specified arguments, producing an integer handle that
+    /// refers to the new thread.
+    ThreadSpawn(_callee: index::Function, _arguments: Box<[Value]>,) = 0x40,
+    /// ```text
+    /// <result0>, <result1>, ... = thread.join <signature> <thread> ; Join thread with return values
+    /// thread.join <signature> <thread> ; Join thread with no return values
+    /// ```
+    /// Waits for the thread referred to by the integer handle to finish, producing the values returned by the function the
+    /// thread was started with. The return types of the function `signature` specify the types of the returned values.
+    ThreadJoin(_signature: index::FunctionSignature, _thread: Value,) = 0x41,
+    /// ```text
+    /// <result> = atomic.load <ordering> <address>
+    /// ```
+    /// Atomically reads the integer that the raw pointer `address` points to. The ordering must be `relaxed`, `acquire`, or
+    /// `seq_cst`.
+    AtomicLoad(_load: Box<AtomicLoad>,) = 0x48,
+    /// ```text
+    /// atomic.store <ordering> <address> <value>
+    /// ```
+    /// Atomically writes an integer `value` to the location that the raw pointer `address` points to. The ordering must be
+    /// `relaxed`, `release`, or `seq_cst`.
+    AtomicStore(_store: Box<AtomicStore>,) = 0x49,
+    /// ```text
+    /// <previous> = atomic.rmw <operation> <ordering> <address> <value>
+    /// ```
+    /// Atomically modifies the integer that the raw pointer `address` points to, producing the value that was previously
+    /// stored.
+    AtomicRmw(_operation: Box<AtomicReadModifyWrite>,) = 0x4A,
+    /// ```text
+    /// <previous>, <success> = atomic.cmpxchg <success_ordering> <failure_ordering> <address> <expected> <replacement>
+    /// ```
+    /// Atomically replaces the integer that the raw pointer `address` points to with `replacement` if it is equal to
+    /// `expected`. Produces the value that was previously stored, and an integer that is `1` if the value was replaced or
+    /// `0` otherwise. The failure ordering must be `relaxed`, `acquire`, or `seq_cst`, and cannot be stronger than the
+    /// success ordering.
+    AtomicCmpXchg(_operation: Box<AtomicCompareExchange>,) = 0x4B,
+    /// ```text
+    /// fence <ordering>
+    /// ```
+    /// Prevents memory operations from being reordered across the fence, the ordering cannot be `relaxed`.
+    Fence(_ordering: MemoryOrdering,) = 0x4C,
+    /// ```text
+    /// <result> = vec.splat <value>
+    /// ```
+    /// Produces a vector where every lane contains the specified `value`.
+    VSplat(_value: Value,) = 0x50,
+    /// ```text
+    /// <element> = vec.extract <vector> <lane>
+    /// ```
+    /// Produces the value contained in the specified `lane` of a vector.
+    VExtract(_vector: Value, _lane: u16,) = 0x51,
+    /// ```text
+    /// <result> = vec.insert <vector> <lane> <value>
+    /// ```
+    /// Produces a copy of a vector with the specified `lane` replaced by `value`.
+    VInsert(_insert: Box<VectorInsert>,) = 0x52,
+    /// ```text
+    /// <result> = vec.shuffle <x> <y> (<lane0>, <lane1>, ...)
+    /// ```
+    /// Produces a vector whose lanes are selected from the lanes of `x` followed by the lanes of `y`. The number of lanes in
+    /// the result is the number of specified lane indices.
+    VShuffle(_shuffle: Box<VectorShuffle>,) = 0x53,
 }}
 
 impl Instruction {
@@ -399,7 +966,103 @@ impl Instruction {
     /// assert_eq!(Instruction::Return(Default::default()).is_terminator(), true);
     /// ```
     pub fn is_terminator(&self) -> bool {
-        matches!(self, Self::Return(_))
+        matches!(self, Self::Return(_) | Self::Br(_, _) | Self::BrIf(_))
+    }
+
+    /// Calls `f` with each value used by the instruction, in the order that they appear.
+    pub fn for_each_value<F: FnMut(&Value)>(&self, mut f: F) {
+        match self {
+            Self::Nop | Self::Break | Self::Fence(_) => (),
+            Self::Return(values) => values.iter().for_each(&mut f),
+            Self::Br(_, arguments) | Self::Call(_, arguments) | Self::ThreadSpawn(_, arguments) => {
+                arguments.iter().for_each(&mut f)
+            }
+            Self::BrIf(branch) => {
+                f(&branch.condition);
+                branch.arguments.iter().for_each(&mut f);
+            }
+            Self::IAdd(arithmetic) | Self::ISub(arithmetic) => {
+                f(&arithmetic.x);
+                f(&arithmetic.y);
+            }
+            Self::Cmp(comparison) => {
+                f(&comparison.x);
+                f(&comparison.y);
+            }
+            Self::ThreadJoin(_, thread) => f(thread),
+            Self::AtomicLoad(load) => f(&load.address),
+            Self::AtomicStore(store) => {
+                f(&store.address);
+                f(&store.value);
+            }
+            Self::AtomicRmw(operation) => {
+                f(&operation.address);
+                f(&operation.value);
+            }
+            Self::AtomicCmpXchg(operation) => {
+                f(&operation.address);
+                f(&operation.expected);
+                f(&operation.replacement);
+            }
+            Self::VSplat(value) => f(value),
+            Self::VExtract(vector, _) => f(vector),
+            Self::VInsert(insert) => {
+                f(&insert.vector);
+                f(&insert.value);
+            }
+            Self::VShuffle(shuffle) => {
+                f(&shuffle.x);
+                f(&shuffle.y);
+            }
+        }
+    }
+
+    /// Calls `f` with each value used by the instruction, allowing the values to be replaced.
+    pub(crate) fn values_mut<F: FnMut(&mut Value)>(&mut self, mut f: F) {
+        match self {
+            Self::Nop | Self::Break | Self::Fence(_) => (),
+            Self::Return(values) => values.iter_mut().for_each(&mut f),
+            Self::Br(_, arguments) | Self::Call(_, arguments) | Self::ThreadSpawn(_, arguments) => {
+                arguments.iter_mut().for_each(&mut f)
+            }
+            Self::BrIf(branch) => {
+                f(&mut branch.condition);
+                branch.arguments.iter_mut().for_each(&mut f);
+            }
+            Self::IAdd(arithmetic) | Self::ISub(arithmetic) => {
+                f(&mut arithmetic.x);
+                f(&mut arithmetic.y);
+            }
+            Self::Cmp(comparison) => {
+                f(&mut comparison.x);
+                f(&mut comparison.y);
+            }
+            Self::ThreadJoin(_, thread) => f(thread),
+            Self::AtomicLoad(load) => f(&mut load.address),
+            Self::AtomicStore(store) => {
+                f(&mut store.address);
+                f(&mut store.value);
+            }
+            Self::AtomicRmw(operation) => {
+                f(&mut operation.address);
+                f(&mut operation.value);
+            }
+            Self::AtomicCmpXchg(operation) => {
+                f(&mut operation.address);
+                f(&mut operation.expected);
+                f(&mut operation.replacement);
+            }
+            Self::VSplat(value) => f(value),
+            Self::VExtract(vector, _) => f(vector),
+            Self::VInsert(insert) => {
+                f(&mut insert.vector);
+                f(&mut insert.value);
+            }
+            Self::VShuffle(shuffle) => {
+                f(&mut shuffle.x);
+                f(&mut shuffle.y);
+            }
+        }
     }
 }
 
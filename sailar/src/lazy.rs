@@ -0,0 +1,244 @@
+//! Provides a view of a SAILAR module whose records are only parsed when they are requested.
+
+use crate::identifier::Id;
+use crate::index;
+use crate::reader::{self, RecordIndex};
+use crate::record::{self, Record};
+use crate::signature;
+use std::borrow::Cow;
+
+/// A SAILAR module stored in a byte slice, whose records are parsed only when they are requested.
+///
+/// Records are not validated, and are parsed again each time they are requested. Since the sizes of array elements are not
+/// stored, elements of arrays other than identifiers and data are also parsed once when the module is indexed. Modules
+/// written by [`Builder`](crate::builder::Builder) never contain arrays of code blocks or function templates, so these are
+/// only parsed when they are requested.
+#[derive(Clone, Debug)]
+pub struct LazyModule<'data> {
+    records: RecordIndex<'data>,
+    metadata_fields: Vec<usize>,
+    identifiers: Vec<usize>,
+    type_signatures: Vec<usize>,
+    function_signatures: Vec<usize>,
+    data: Vec<usize>,
+    code: Vec<usize>,
+    function_imports: Vec<usize>,
+    function_templates: Vec<usize>,
+    functions: Vec<usize>,
+}
+
+macro_rules! record_accessors {
+    ($($name:ident, $count_name:ident($index:ty) in $field:ident -> $record_type:ty { $case_name:ident };)*) => {
+        $(
+            /// Parses the record at the specified index, or returns `None` if the index is out of bounds.
+            pub fn $name(&self, index: $index) -> Option<reader::Result<$record_type>> {
+                let location = &self.records.locations()[*self.$field.get(usize::from(index))?];
+                Some(self.records.read_record_at(location).map(|record| match record {
+                    Record::$case_name(contents) => contents,
+                    _ => unreachable!("record was indexed by its type"),
+                }))
+            }
+
+            #[inline]
+            pub fn $count_name(&self) -> usize {
+                self.$field.len()
+            }
+        )*
+    };
+}
+
+impl<'data> LazyModule<'data> {
+    /// Groups the records in the `records` index by their type, without parsing them.
+    pub fn from_record_index(records: RecordIndex<'data>) -> Self {
+        let mut module = Self {
+            records,
+            metadata_fields: Vec::new(),
+            identifiers: Vec::new(),
+            type_signatures: Vec::new(),
+            function_signatures: Vec::new(),
+            data: Vec::new(),
+            code: Vec::new(),
+            function_imports: Vec::new(),
+            function_templates: Vec::new(),
+            functions: Vec::new(),
+        };
+
+        for (position, location) in module.records.locations().iter().enumerate() {
+            let lookup = match location.record_type() {
+                record::Type::MetadataField => &mut module.metadata_fields,
+                record::Type::Identifier => &mut module.identifiers,
+                record::Type::TypeSignature => &mut module.type_signatures,
+                record::Type::FunctionSignature => &mut module.function_signatures,
+                record::Type::Data => &mut module.data,
+                record::Type::CodeBlock => &mut module.code,
+                record::Type::FunctionImport => &mut module.function_imports,
+                record::Type::FunctionTemplate => &mut module.function_templates,
+                record::Type::Function => &mut module.functions,
+                record::Type::Array => unreachable!("array records are replaced with their elements"),
+            };
+
+            lookup.push(position);
+        }
+
+        module
+    }
+
+    /// Finds the location of every record in the module stored in the `input` byte slice.
+    pub fn from_slice(input: &'data [u8]) -> reader::Result<Self> {
+        reader::SliceReader::new(input).to_record_index().map(Self::from_record_index)
+    }
+
+    #[inline]
+    pub fn record_index(&self) -> &RecordIndex<'data> {
+        &self.records
+    }
+
+    record_accessors! {
+        metadata_field, metadata_field_count(usize) in metadata_fields -> record::MetadataField<'data> { MetadataField };
+        identifier, identifier_count(index::Identifier) in identifiers -> Cow<'data, Id> { Identifier };
+        type_signature, type_signature_count(index::TypeSignature) in type_signatures -> signature::Type { TypeSignature };
+        function_signature, function_signature_count(index::FunctionSignature) in function_signatures
+            -> signature::Function<'data> { FunctionSignature };
+        data, data_count(usize) in data -> Cow<'data, [u8]> { Data };
+        code_block, code_block_count(index::CodeBlock) in code -> record::CodeBlock<'data> { CodeBlock };
+        function_import, function_import_count(usize) in function_imports -> record::FunctionImport<'data> { FunctionImport };
+        function_template, function_template_count(usize) in function_templates
+            -> record::FunctionTemplate<'data> { FunctionTemplate };
+        function, function_count(index::Function) in functions -> record::Function<'data> { Function };
+    }
+
+    /// Parses each function template in the module, which contain the symbols of all exported functions.
+    pub fn function_templates(&self) -> impl ExactSizeIterator<Item = reader::Result<record::FunctionTemplate<'data>>> + '_ {
+        (0..self.function_template_count()).map(|index| self.function_template(index).unwrap())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::Builder;
+    use crate::instruction::Instruction;
+
+    fn sample_module() -> Vec<u8> {
+        let integer_type = index::TypeSignature::from(0);
+        let mut builder = Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        builder.add_record(signature::Function::new(vec![integer_type], vec![integer_type]));
+        builder.add_record(record::CodeBlock::new(
+            vec![integer_type],
+            vec![integer_type],
+            Vec::new(),
+            vec![Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice())],
+        ));
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportBorrowed(Id::try_from_str("identity").unwrap()),
+            index::FunctionSignature::from(0),
+            index::CodeBlock::from(0),
+        ));
+        builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+        module
+    }
+
+    #[test]
+    fn records_are_parsed_on_request() {
+        let module = sample_module();
+        let lazy = LazyModule::from_slice(&module).unwrap();
+        let (_, records) = reader::SliceReader::new(&module).to_record_reader().unwrap();
+        let records = records.collect::<reader::Result<Vec<_>>>().unwrap();
+
+        assert_eq!(lazy.record_index().locations().len(), records.len());
+        assert_eq!(lazy.code_block_count(), 1);
+        assert_eq!(
+            Record::CodeBlock(lazy.code_block(index::CodeBlock::from(0)).unwrap().unwrap()),
+            records[2]
+        );
+        assert_eq!(
+            Record::FunctionTemplate(lazy.function_template(0).unwrap().unwrap()),
+            records[3]
+        );
+        assert!(lazy.code_block(index::CodeBlock::from(1)).is_none());
+        assert!(lazy.identifier(index::Identifier::from(0)).is_none());
+    }
+
+    #[test]
+    fn unrequested_array_elements_are_not_parsed() {
+        let names = ["first", "second", "third", "fourth", "fifth", "sixth"];
+        let mut builder = Builder::new();
+        for name in names {
+            builder.add_record(crate::identifier::Identifier::try_from_str(name).unwrap());
+        }
+
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+
+        // Replaces the first byte of the first identifier with invalid UTF-8, which is only detected if the element is parsed
+        let first_element = LazyModule::from_slice(&module).unwrap().record_index().locations()[0];
+        assert!(first_element.is_array_element());
+        module[first_element.offset() + 1] = 0xFF;
+
+        let lazy = LazyModule::from_slice(&module).unwrap();
+        assert_eq!(lazy.identifier_count(), names.len());
+        assert!(matches!(lazy.identifier(index::Identifier::from(0)), Some(Err(_))));
+        for (index, name) in names.iter().enumerate().skip(1) {
+            assert_eq!(
+                lazy.identifier(index::Identifier::from(index)).unwrap().unwrap().as_str(),
+                *name
+            );
+        }
+    }
+
+    #[test]
+    fn unrequested_code_blocks_written_by_builder_are_not_parsed() {
+        let integer_type = index::TypeSignature::from(0);
+        let mut builder = Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        for _ in 0..4 {
+            builder.add_record(record::CodeBlock::new(
+                vec![integer_type],
+                vec![integer_type],
+                Vec::new(),
+                vec![Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice())],
+            ));
+        }
+
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+
+        // Overwrites the contents of the first code block, which is only detected if the code block is parsed
+        let first_code_block = *LazyModule::from_slice(&module)
+            .unwrap()
+            .record_index()
+            .locations()
+            .iter()
+            .find(|location| location.record_type() == record::Type::CodeBlock)
+            .unwrap();
+        assert!(!first_code_block.is_array_element());
+        module[first_code_block.offset()..][..first_code_block.length()].fill(0xFF);
+
+        let lazy = LazyModule::from_slice(&module).unwrap();
+        assert_eq!(lazy.code_block_count(), 4);
+        assert!(matches!(lazy.code_block(index::CodeBlock::from(0)), Some(Err(_))));
+        for index in 1..4 {
+            assert!(lazy.code_block(index::CodeBlock::from(index)).unwrap().is_ok());
+        }
+    }
+
+    #[test]
+    fn invalid_records_are_only_reported_when_requested() {
+        let mut module = Vec::new();
+        module.extend_from_slice(crate::binary::MAGIC);
+        module.extend_from_slice(&[
+            crate::versioning::SupportedFormat::CURRENT.major,
+            crate::versioning::SupportedFormat::CURRENT.minor,
+        ]);
+        module.push(2); // Number of records
+        module.extend_from_slice(&[u8::from(record::Type::CodeBlock), 1, 0xFF]);
+        module.extend_from_slice(&[u8::from(record::Type::Data), 2, 0xA, 0xB]);
+
+        let lazy = LazyModule::from_slice(&module).unwrap();
+        assert!(matches!(lazy.data(0), Some(Ok(Cow::Borrowed(&[0xA, 0xB])))));
+        assert!(matches!(lazy.code_block(index::CodeBlock::from(0)), Some(Err(_))));
+    }
+}
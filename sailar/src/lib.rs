@@ -8,7 +8,11 @@ pub mod helper;
 pub mod identifier;
 pub mod index;
 pub mod instruction;
+pub mod lazy;
+pub mod linker;
+pub mod module_builder;
 pub mod num;
+pub mod optimizer;
 pub mod reader;
 pub mod record;
 pub mod signature;
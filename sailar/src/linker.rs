@@ -0,0 +1,778 @@
+//! Combines multiple SAILAR modules into a single module.
+//!
+//! Function imports are resolved to the exported function templates of the other modules, allowing functions in one module
+//! to call functions defined in another. Imports that are not satisfied by any of the modules are kept in the combined
+//! module.
+
+use crate::helper::interner::Interner;
+use crate::identifier::{Id, Identifier};
+use crate::index;
+use crate::instruction::{BranchIf, Instruction};
+use crate::record::{self, Record};
+use crate::signature;
+use crate::validation::{self, ModuleContents, SymbolIndex, ValidModule};
+use std::borrow::Cow;
+use std::collections::hash_map;
+
+/// The error type used when modules cannot be linked together.
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// Used when more than one module exports a definition with the same symbol.
+    #[error(
+        "{duplicate} in module {duplicate_module} has symbol {symbol:?}, but that symbol already corresponds to {existing} in \
+        module {existing_module}"
+    )]
+    DuplicateSymbol {
+        symbol: Identifier,
+        existing_module: usize,
+        existing: SymbolIndex,
+        duplicate_module: usize,
+        duplicate: SymbolIndex,
+    },
+    /// Used when more than one module specifies an entry point.
+    #[error("module {duplicate_module} has an entry point, but module {defined_module} already has an entry point")]
+    DuplicateEntryPoint { defined_module: usize, duplicate_module: usize },
+    /// Used when a function import refers to an exported function template with a different signature.
+    #[error(
+        "function import {import} in module {import_module} has symbol {symbol:?}, but the signature of the function exported \
+        by module {export_module} with that symbol is different"
+    )]
+    ImportSignatureMismatch {
+        symbol: Identifier,
+        import_module: usize,
+        import: index::FunctionTemplate,
+        export_module: usize,
+    },
+    /// Used when the combined module is not valid.
+    #[error(transparent)]
+    InvalidModule(#[from] validation::Error),
+}
+
+/// The new indices of the definitions in an input module, or `None` for definitions that are not included in the output.
+struct ModuleMapping {
+    type_signatures: Vec<Option<index::TypeSignature>>,
+    function_signatures: Vec<Option<index::FunctionSignature>>,
+    code: Vec<Option<index::CodeBlock>>,
+    /// Imports resolved to a function template defined by another module refer to that template.
+    function_imports: Vec<Option<index::FunctionTemplate>>,
+    function_templates: Vec<Option<index::FunctionTemplate>>,
+    functions: Vec<Option<index::Function>>,
+}
+
+impl ModuleMapping {
+    fn new(contents: &ModuleContents) -> Self {
+        Self {
+            type_signatures: vec![None; contents.type_signatures.len()],
+            function_signatures: vec![None; contents.function_signatures.len()],
+            code: vec![None; contents.code.len()],
+            function_imports: vec![None; contents.function_imports.len()],
+            function_templates: vec![None; contents.function_templates.len()],
+            functions: vec![None; contents.functions.len()],
+        }
+    }
+
+    fn code_block(&self, block: index::CodeBlock) -> index::CodeBlock {
+        self.code[usize::from(block)].expect("branch targets of included code blocks should also be included")
+    }
+
+    fn function_template(&self, template: index::FunctionTemplate) -> index::FunctionTemplate {
+        let index = usize::from(template);
+        match self.function_imports.get(index) {
+            Some(import) => *import,
+            None => self.function_templates[index - self.function_imports.len()],
+        }
+        .expect("templates of included functions should also be included")
+    }
+
+    fn function(&self, function: index::Function) -> index::Function {
+        self.functions[usize::from(function)].expect("callees of included code blocks should also be included")
+    }
+}
+
+/// Deduplicates the type signatures and function signatures of all modules.
+#[derive(Default)]
+struct SignatureInterner {
+    type_signatures: Interner<signature::Type, index::TypeSignature>,
+    function_signatures: Interner<(Box<[index::TypeSignature]>, usize), index::FunctionSignature>,
+}
+
+impl SignatureInterner {
+    fn type_signature(
+        &mut self,
+        contents: &ModuleContents,
+        mapping: &mut ModuleMapping,
+        signature: index::TypeSignature,
+    ) -> index::TypeSignature {
+        if let Some(existing) = mapping.type_signatures[usize::from(signature)] {
+            return existing;
+        }
+
+        // Validation ensures that type signatures do not refer to themselves
+        let mapped = match &contents.type_signatures[usize::from(signature)] {
+            signature::Type::RawPtr(Some(pointee)) => {
+                signature::Type::RawPtr(Some(self.type_signature(contents, mapping, *pointee)))
+            }
+            signature::Type::FuncPtr(function) => signature::Type::FuncPtr(self.function_signature(contents, mapping, *function)),
+            other => other.clone(),
+        };
+
+        let index = self.type_signatures.intern(mapped);
+        mapping.type_signatures[usize::from(signature)] = Some(index);
+        index
+    }
+
+    fn type_signature_list(
+        &mut self,
+        contents: &ModuleContents,
+        mapping: &mut ModuleMapping,
+        types: &signature::TypeList,
+    ) -> Box<[index::TypeSignature]> {
+        types
+            .iter()
+            .map(|signature| self.type_signature(contents, mapping, signature))
+            .collect()
+    }
+
+    fn function_signature(
+        &mut self,
+        contents: &ModuleContents,
+        mapping: &mut ModuleMapping,
+        signature: index::FunctionSignature,
+    ) -> index::FunctionSignature {
+        if let Some(existing) = mapping.function_signatures[usize::from(signature)] {
+            return existing;
+        }
+
+        let function = &contents.function_signatures[usize::from(signature)];
+        let types = self.type_signature_list(contents, mapping, function.types());
+        let index = self.function_signatures.intern((types, function.return_type_count));
+        mapping.function_signatures[usize::from(signature)] = Some(index);
+        index
+    }
+}
+
+/// A placeholder index for a definition that is included in the output, which is replaced by [`assign_indices`].
+fn included<I: From<usize>>() -> Option<I> {
+    Some(I::from(0))
+}
+
+/// The module and position of the function template definition that each function import in each module is resolved to,
+/// or `None` for imports that are not satisfied by any of the modules.
+type ResolvedImports = Vec<Vec<Option<(usize, usize)>>>;
+
+/// Determines which functions and code blocks can be reached by calls and branches from the `entry_point`, following calls
+/// to imported functions into the modules that define them.
+fn mark_reachable(
+    modules: &[ValidModule],
+    mappings: &mut [ModuleMapping],
+    resolved_imports: &ResolvedImports,
+    entry_point: (usize, index::Function),
+) {
+    let mut pending_functions = vec![entry_point];
+    let mut pending_definitions = Vec::new();
+    let mut pending_blocks = Vec::new();
+
+    loop {
+        if let Some((module_index, function)) = pending_functions.pop() {
+            let contents = modules[module_index].contents();
+            let mapping = &mut mappings[module_index];
+            let function_index = usize::from(function);
+            if mapping.functions[function_index].is_some() {
+                continue;
+            }
+
+            mapping.functions[function_index] = included();
+            let template = usize::from(contents.functions[function_index].template);
+            match template.checked_sub(contents.function_imports.len()) {
+                Some(definition) => pending_definitions.push((module_index, definition)),
+                None => {
+                    mapping.function_imports[template] = included();
+                    pending_definitions.extend(resolved_imports[module_index][template]);
+                }
+            }
+        } else if let Some((module_index, definition)) = pending_definitions.pop() {
+            let mapping = &mut mappings[module_index];
+            if mapping.function_templates[definition].is_none() {
+                mapping.function_templates[definition] = included();
+                let entry_block = modules[module_index].contents().function_templates[definition].entry_block;
+                pending_blocks.push((module_index, entry_block));
+            }
+        } else if let Some((module_index, block)) = pending_blocks.pop() {
+            let mapping = &mut mappings[module_index];
+            let block_index = usize::from(block);
+            if mapping.code[block_index].is_some() {
+                continue;
+            }
+
+            mapping.code[block_index] = included();
+            for instruction in modules[module_index].contents().code[block_index].instructions.iter() {
+                match instruction {
+                    Instruction::Br(target, _) => pending_blocks.push((module_index, *target)),
+                    Instruction::BrIf(branch) => {
+                        pending_blocks.push((module_index, branch.true_branch()));
+                        pending_blocks.push((module_index, branch.false_branch()));
+                    }
+                    Instruction::Call(callee, _) | Instruction::ThreadSpawn(callee, _) => {
+                        pending_functions.push((module_index, *callee))
+                    }
+                    _ => (),
+                }
+            }
+        } else {
+            break;
+        }
+    }
+}
+
+/// Assigns new indices starting at `first` to the definitions that were marked as included, in the order that they appear
+/// in the modules.
+fn assign_indices<I: Copy + From<usize>>(
+    mappings: &mut [ModuleMapping],
+    first: usize,
+    definitions: fn(&mut ModuleMapping) -> &mut [Option<I>],
+) {
+    let mut count = first;
+    for mapping in mappings.iter_mut() {
+        for definition in definitions(mapping).iter_mut().filter(|definition| definition.is_some()) {
+            *definition = Some(I::from(count));
+            count += 1;
+        }
+    }
+}
+
+/// Combines the definitions of multiple SAILAR modules into a single module.
+///
+/// Identical identifiers, type signatures, and function signatures are only included once in the combined module. Calls to
+/// imported functions that are exported by another module are replaced with calls to the exported function, and identical
+/// imports that are not exported by any module are only included once.
+///
+/// # Examples
+///
+/// ```
+/// # use sailar::linker::Linker;
+/// # use sailar::module_builder::ModuleBuilder;
+/// # use sailar::signature;
+/// let mut linker = Linker::new();
+/// for name in ["first", "second"] {
+///     let mut builder = ModuleBuilder::new();
+///     let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+///     let signature = builder.function_signature([], [integer_type]);
+///     let function = builder.define_function(name.try_into()?, signature);
+///     builder.entry_block(function).ret(vec![0i32.into()]);
+///     linker.add_module(builder.build().unwrap());
+/// }
+///
+/// let module = linker.link().unwrap();
+/// assert_eq!(module.contents().type_signatures.len(), 1);
+/// assert_eq!(module.contents().function_templates.len(), 2);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Linker<'data> {
+    modules: Vec<ValidModule<'data>>,
+    module_identifier: Option<record::ModuleIdentifier<'static>>,
+    strip_unreachable: bool,
+}
+
+impl<'data> Linker<'data> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module whose definitions are included in the combined module.
+    pub fn add_module(&mut self, module: ValidModule<'data>) {
+        self.modules.push(module);
+    }
+
+    /// Sets the identifier of the combined module. The identifiers of the modules that are linked together are not included.
+    pub fn set_module_identifier(&mut self, identifier: record::ModuleIdentifier<'static>) {
+        self.module_identifier = Some(identifier);
+    }
+
+    /// Indicates whether functions and code blocks that cannot be reached from the entry point are removed, along with
+    /// any type signatures, function signatures, and function imports that are no longer used. Defaults to `false`.
+    ///
+    /// Has no effect if none of the modules have an entry point.
+    #[inline]
+    pub fn strip_unreachable(&self) -> bool {
+        self.strip_unreachable
+    }
+
+    pub fn set_strip_unreachable(&mut self, strip: bool) {
+        self.strip_unreachable = strip;
+    }
+
+    /// Combines the modules, returning the validated combined module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for each exported symbol that is defined by more than one module, for each function import whose
+    /// signature differs from the exported function that it refers to, or if more than one module specifies an entry point.
+    pub fn link(&self) -> Result<ValidModule<'static>, Vec<Error>> {
+        let mut errors = Vec::new();
+
+        let mut entry_point = None::<(usize, index::Function)>;
+        for (module_index, module) in self.modules.iter().enumerate() {
+            if let Some(function) = module.contents().entry_point {
+                match entry_point {
+                    Some((defined_module, _)) => errors.push(Error::DuplicateEntryPoint {
+                        defined_module,
+                        duplicate_module: module_index,
+                    }),
+                    None => entry_point = Some((module_index, function)),
+                }
+            }
+        }
+
+        let mut symbols = rustc_hash::FxHashMap::<&Id, (usize, SymbolIndex)>::default();
+        for (module_index, module) in self.modules.iter().enumerate() {
+            for template in module.exports().function_templates.iter() {
+                let symbol = module
+                    .contents()
+                    .function_template_definition(*template)
+                    .and_then(|definition| definition.export.symbol())
+                    .expect("exported function templates should have a symbol");
+
+                match symbols.entry(symbol) {
+                    hash_map::Entry::Occupied(occupied) => errors.push(Error::DuplicateSymbol {
+                        symbol: Identifier::from_id(symbol),
+                        existing_module: occupied.get().0,
+                        existing: occupied.get().1,
+                        duplicate_module: module_index,
+                        duplicate: SymbolIndex::from(*template),
+                    }),
+                    hash_map::Entry::Vacant(vacant) => {
+                        vacant.insert((module_index, SymbolIndex::from(*template)));
+                    }
+                }
+            }
+        }
+
+        let mut mappings = self
+            .modules
+            .iter()
+            .map(|module| ModuleMapping::new(module.contents()))
+            .collect::<Vec<_>>();
+
+        // Signatures are compared by interning them separately, so that signatures only used by unreachable imports are not
+        // included in the output
+        let mut signature_comparer = SignatureInterner::default();
+        let mut comparer_mappings = self
+            .modules
+            .iter()
+            .map(|module| ModuleMapping::new(module.contents()))
+            .collect::<Vec<_>>();
+
+        let mut resolved_imports = ResolvedImports::with_capacity(self.modules.len());
+        for (module_index, module) in self.modules.iter().enumerate() {
+            let contents = module.contents();
+            let mut resolved = Vec::with_capacity(contents.function_imports.len());
+
+            for (import_index, import) in contents.function_imports.iter().enumerate() {
+                let export = match symbols.get(import.symbol.as_ref()) {
+                    Some((export_module, SymbolIndex::FunctionTemplate(template))) => {
+                        let export_contents = self.modules[*export_module].contents();
+                        let definition = usize::from(*template) - export_contents.function_imports.len();
+                        let import_signature = signature_comparer.function_signature(
+                            contents,
+                            &mut comparer_mappings[module_index],
+                            import.signature,
+                        );
+                        let export_signature = signature_comparer.function_signature(
+                            export_contents,
+                            &mut comparer_mappings[*export_module],
+                            export_contents.function_templates[definition].signature,
+                        );
+
+                        if import_signature == export_signature {
+                            Some((*export_module, definition))
+                        } else {
+                            errors.push(Error::ImportSignatureMismatch {
+                                symbol: Identifier::from_id(&import.symbol),
+                                import_module: module_index,
+                                import: index::FunctionTemplate::from(import_index),
+                                export_module: *export_module,
+                            });
+                            None
+                        }
+                    }
+                    None => None,
+                };
+
+                resolved.push(export);
+            }
+
+            resolved_imports.push(resolved);
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut signatures = SignatureInterner::default();
+
+        match entry_point {
+            Some(entry_point) if self.strip_unreachable => {
+                mark_reachable(&self.modules, &mut mappings, &resolved_imports, entry_point)
+            }
+            _ => {
+                for (module, mapping) in self.modules.iter().zip(mappings.iter_mut()) {
+                    let contents = module.contents();
+                    mapping.code.iter_mut().for_each(|block| *block = included());
+                    mapping.function_imports.iter_mut().for_each(|import| *import = included());
+                    mapping
+                        .function_templates
+                        .iter_mut()
+                        .for_each(|template| *template = included());
+                    mapping.functions.iter_mut().for_each(|function| *function = included());
+
+                    for signature in 0..contents.type_signatures.len() {
+                        signatures.type_signature(contents, mapping, index::TypeSignature::from(signature));
+                    }
+
+                    for signature in 0..contents.function_signatures.len() {
+                        signatures.function_signature(contents, mapping, index::FunctionSignature::from(signature));
+                    }
+                }
+            }
+        }
+
+        // Imports that are not resolved come first, with identical imports only included once
+        let mut function_imports = Interner::<(Identifier, index::FunctionSignature), index::FunctionTemplate>::default();
+        for (module_index, module) in self.modules.iter().enumerate() {
+            let contents = module.contents();
+            for (import_index, import) in contents.function_imports.iter().enumerate() {
+                if mappings[module_index].function_imports[import_index].is_some()
+                    && resolved_imports[module_index][import_index].is_none()
+                {
+                    let signature = signatures.function_signature(contents, &mut mappings[module_index], import.signature);
+                    mappings[module_index].function_imports[import_index] =
+                        Some(function_imports.intern((Identifier::from_id(&import.symbol), signature)));
+                }
+            }
+        }
+
+        assign_indices(&mut mappings, 0, |mapping| &mut mapping.code);
+        assign_indices(&mut mappings, function_imports.values().len(), |mapping| {
+            &mut mapping.function_templates
+        });
+        assign_indices(&mut mappings, 0, |mapping| &mut mapping.functions);
+
+        for (module_index, resolved) in resolved_imports.iter().enumerate() {
+            for (import_index, export) in resolved.iter().enumerate() {
+                if let Some((export_module, definition)) = export {
+                    if mappings[module_index].function_imports[import_index].is_some() {
+                        mappings[module_index].function_imports[import_index] =
+                            mappings[*export_module].function_templates[*definition];
+                    }
+                }
+            }
+        }
+
+        let mut identifiers = Interner::<Cow<'static, Id>, usize>::default();
+        let mut data = Vec::<Cow<'static, [u8]>>::new();
+        let mut code = Vec::<record::CodeBlock<'static>>::new();
+        let mut function_templates = Vec::<record::FunctionTemplate<'static>>::new();
+        let mut functions = Vec::<record::Function<'static>>::new();
+
+        for (module_index, (module, mapping)) in self.modules.iter().zip(mappings.iter_mut()).enumerate() {
+            let contents = module.contents();
+
+            for identifier in contents.identifiers.iter() {
+                identifiers.intern(Cow::Owned(Identifier::from_id(identifier)));
+            }
+
+            data.extend(contents.data.iter().map(|bytes| Cow::Owned(bytes.to_vec())));
+
+            for (block_index, block) in contents.code.iter().enumerate() {
+                if mapping.code[block_index].is_none() {
+                    continue;
+                }
+
+                let input_types = signatures.type_signature_list(contents, mapping, &block.input_types());
+                let result_types = signatures.type_signature_list(contents, mapping, &block.result_types());
+                let temporary_types = signatures.type_signature_list(contents, mapping, &block.temporary_types());
+
+                let instructions = block
+                    .instructions
+                    .iter()
+                    .map(|instruction| match instruction {
+                        Instruction::Br(target, arguments) => Instruction::Br(mapping.code_block(*target), arguments.clone()),
+                        Instruction::BrIf(branch) => Instruction::BrIf(Box::new(BranchIf::new(
+                            branch.condition().clone(),
+                            mapping.code_block(branch.true_branch()),
+                            mapping.code_block(branch.false_branch()),
+                            branch.arguments().into(),
+                        ))),
+                        Instruction::Call(callee, arguments) => Instruction::Call(mapping.function(*callee), arguments.clone()),
+                        Instruction::ThreadSpawn(callee, arguments) => {
+                            Instruction::ThreadSpawn(mapping.function(*callee), arguments.clone())
+                        }
+                        Instruction::ThreadJoin(signature, thread) => {
+                            Instruction::ThreadJoin(signatures.function_signature(contents, mapping, *signature), thread.clone())
+                        }
+                        // Other instructions do not refer to any definitions
+                        _ => instruction.clone(),
+                    })
+                    .collect::<Vec<_>>();
+
+                code.push(record::CodeBlock::new(
+                    input_types,
+                    result_types,
+                    temporary_types,
+                    instructions,
+                ));
+            }
+
+            for (template_index, template) in contents.function_templates.iter().enumerate() {
+                if mapping.function_templates[template_index].is_none() {
+                    continue;
+                }
+
+                let export = match template.export.kind() {
+                    record::ExportKind::Export => template.export.clone().into_owned(),
+                    // Private symbols are removed if another module already uses the symbol
+                    record::ExportKind::Private => match template.export.symbol() {
+                        Some(symbol) if !symbols.contains_key(symbol) => {
+                            symbols.insert(
+                                symbol,
+                                (
+                                    module_index,
+                                    SymbolIndex::from(contents.function_template_index(template_index)),
+                                ),
+                            );
+                            template.export.clone().into_owned()
+                        }
+                        _ => record::Export::Hidden,
+                    },
+                    record::ExportKind::Hidden => record::Export::Hidden,
+                };
+
+                function_templates.push(record::FunctionTemplate::new(
+                    export,
+                    signatures.function_signature(contents, mapping, template.signature),
+                    mapping.code_block(template.entry_block),
+                ));
+            }
+
+            for (function_index, function) in contents.functions.iter().enumerate() {
+                if mapping.functions[function_index].is_some() {
+                    functions.push(record::Function::with_template(mapping.function_template(function.template)));
+                }
+            }
+        }
+
+        let mut records = Vec::new();
+
+        if let Some(identifier) = self.module_identifier.clone() {
+            records.push(Record::from(record::MetadataField::ModuleIdentifier(identifier)));
+        }
+
+        if let Some((module_index, function)) = entry_point {
+            records.push(Record::from(record::MetadataField::EntryPoint(
+                mappings[module_index].function(function),
+            )));
+        }
+
+        for (module, mapping) in self.modules.iter().zip(mappings.iter()) {
+            // Functions that are not included in the output no longer need to be marked
+            records.extend(
+                module
+                    .contents()
+                    .no_inline_functions
+                    .iter()
+                    .filter_map(|function| mapping.functions[usize::from(*function)])
+                    .map(|function| Record::from(record::MetadataField::NoInline(function))),
+            );
+        }
+
+        records.extend(identifiers.into_values().into_iter().map(Record::Identifier));
+        records.extend(signatures.type_signatures.into_values().into_iter().map(Record::from));
+        records.extend(
+            signatures
+                .function_signatures
+                .into_values()
+                .into_iter()
+                .map(|(types, return_type_count)| Record::from(signature::Function::from_types(types.into(), return_type_count))),
+        );
+        records.extend(data.into_iter().map(Record::Data));
+        records.extend(code.into_iter().map(Record::from));
+        records.extend(
+            function_imports
+                .into_values()
+                .into_iter()
+                .map(|(symbol, signature)| Record::from(record::FunctionImport::new(Cow::Owned(symbol), signature))),
+        );
+        records.extend(function_templates.into_iter().map(Record::from));
+        records.extend(functions.into_iter().map(Record::from));
+        ValidModule::from_records(records).map_err(|error| vec![Error::from(error)])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::module_builder::ModuleBuilder;
+
+    /// Creates a module where each function returns the result of calling the next function, and the last function returns
+    /// zero.
+    fn call_chain(names: &[&str], is_entry_point: bool) -> ValidModule<'static> {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+        let signature = builder.function_signature([], [integer_type]);
+        let functions = names
+            .iter()
+            .map(|name| builder.define_function(Identifier::try_from_str(name).unwrap(), signature))
+            .collect::<Vec<_>>();
+
+        for (index, function) in functions.iter().enumerate() {
+            let mut body = builder.entry_block(*function);
+            match functions.get(index + 1) {
+                Some(callee) => {
+                    let results = body.call(*callee, Vec::new());
+                    body.ret(vec![results[0].into()]);
+                }
+                None => {
+                    body.ret(vec![0i32.into()]);
+                }
+            }
+        }
+
+        if is_entry_point {
+            builder.set_entry_point(functions[0]);
+        }
+
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn references_are_renumbered() {
+        let mut linker = Linker::new();
+        linker.add_module(call_chain(&["a"], true));
+        linker.add_module(call_chain(&["b", "c"], false));
+
+        let module = linker.link().unwrap();
+        let contents = module.contents();
+        assert_eq!(contents.type_signatures.len(), 1);
+        assert_eq!(contents.function_signatures.len(), 1);
+        assert_eq!(contents.function_templates.len(), 3);
+        assert_eq!(contents.entry_point, Some(index::Function::from(0)));
+        assert!(matches!(
+            contents.code[1].instructions.first(),
+            Some(Instruction::Call(callee, _)) if usize::from(*callee) == 2
+        ));
+    }
+
+    /// Creates a module whose entry point returns the result of calling an imported function with the specified return type.
+    fn import_caller(symbol: &str, return_type: signature::IntegerType) -> ValidModule<'static> {
+        let mut builder = ModuleBuilder::new();
+        let return_type = builder.type_signature(signature::Type::from(return_type));
+        let signature = builder.function_signature([], [return_type]);
+        let import = builder.import_function(Identifier::try_from_str(symbol).unwrap(), signature);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+        let mut body = builder.entry_block(main);
+        let results = body.call(import, Vec::new());
+        body.ret(vec![results[0].into()]);
+        builder.set_entry_point(main);
+        builder.build().unwrap()
+    }
+
+    #[test]
+    fn imports_are_resolved_to_exports_of_other_modules() {
+        let mut linker = Linker::new();
+        linker.add_module(import_caller("helper", signature::IntegerType::S32));
+        linker.add_module(call_chain(&["unused", "helper"], false));
+        linker.set_strip_unreachable(true);
+
+        let module = linker.link().unwrap();
+        let contents = module.contents();
+        assert!(contents.function_imports.is_empty());
+        assert_eq!(contents.function_templates.len(), 2);
+        assert_eq!(
+            contents.function_templates[1].export.symbol(),
+            Some(Id::try_from_str("helper").unwrap())
+        );
+        assert!(matches!(
+            contents.code[0].instructions.first(),
+            Some(Instruction::Call(callee, _)) if contents.functions[usize::from(*callee)].template == index::FunctionTemplate::from(1)
+        ));
+    }
+
+    #[test]
+    fn unresolved_imports_are_kept() {
+        let mut linker = Linker::new();
+        linker.add_module(import_caller("external", signature::IntegerType::S32));
+        linker.add_module(call_chain(&["other"], false));
+
+        let module = linker.link().unwrap();
+        let contents = module.contents();
+        assert_eq!(contents.function_imports.len(), 1);
+        assert_eq!(
+            contents.function_imports[0].symbol.as_ref(),
+            Id::try_from_str("external").unwrap()
+        );
+        assert_eq!(contents.function_templates.len(), 2);
+        assert_eq!(contents.functions[0].template, index::FunctionTemplate::from(0));
+    }
+
+    #[test]
+    fn imports_with_different_signatures_are_errors() {
+        let mut linker = Linker::new();
+        linker.add_module(import_caller("helper", signature::IntegerType::U64));
+        linker.add_module(call_chain(&["helper"], false));
+
+        let errors = linker.link().unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [Error::ImportSignatureMismatch {
+                import_module: 0,
+                export_module: 1,
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn duplicate_exported_symbols_are_errors() {
+        let mut linker = Linker::new();
+        linker.add_module(call_chain(&["a", "b"], true));
+        linker.add_module(call_chain(&["b"], true));
+
+        let errors = linker.link().unwrap_err();
+        assert!(matches!(
+            errors.as_slice(),
+            [
+                Error::DuplicateEntryPoint {
+                    defined_module: 0,
+                    duplicate_module: 1
+                },
+                Error::DuplicateSymbol {
+                    existing_module: 0,
+                    duplicate_module: 1,
+                    ..
+                },
+            ]
+        ));
+    }
+
+    #[test]
+    fn unreachable_definitions_are_stripped() {
+        let mut linker = Linker::new();
+        linker.add_module(call_chain(&["unused"], false));
+        linker.add_module(call_chain(&["main", "helper"], true));
+        linker.add_module(call_chain(&["also_unused"], false));
+        linker.set_strip_unreachable(true);
+
+        let module = linker.link().unwrap();
+        let contents = module.contents();
+        assert_eq!(contents.code.len(), 2);
+        assert_eq!(contents.function_templates.len(), 2);
+        assert_eq!(contents.functions.len(), 2);
+        assert_eq!(contents.entry_point, Some(index::Function::from(0)));
+        assert_eq!(
+            contents.function_templates[1].export.symbol(),
+            Some(Id::try_from_str("helper").unwrap())
+        );
+    }
+}
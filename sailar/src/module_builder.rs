@@ -0,0 +1,626 @@
+//! High-level API for building SAILAR modules, which keeps track of record indices.
+//!
+//! Unlike the [`Builder`](crate::builder::Builder), records can be defined in any order, and identical type signatures,
+//! function signatures, and identifiers are only added once.
+
+use crate::helper::interner::Interner;
+use crate::identifier::Identifier;
+use crate::index;
+use crate::instruction::{self, BranchIf, ComparisonKind, Instruction, IntegerArithmetic, OverflowBehavior, Value};
+use crate::record::{self, Record};
+use crate::signature;
+use crate::validation::{self, ValidModule};
+
+/// Refers to a function defined with [`ModuleBuilder::define_function`] or imported with [`ModuleBuilder::import_function`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct FunctionHandle {
+    signature: index::FunctionSignature,
+    template: index::FunctionTemplate,
+    function: index::Function,
+}
+
+impl FunctionHandle {
+    #[inline]
+    pub fn signature(&self) -> index::FunctionSignature {
+        self.signature
+    }
+
+    #[inline]
+    pub fn template(&self) -> index::FunctionTemplate {
+        self.template
+    }
+
+    #[inline]
+    pub fn function(&self) -> index::Function {
+        self.function
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("function template {0} was not given an entry block")]
+    MissingEntryBlock(index::FunctionTemplate),
+    #[error(transparent)]
+    Validation(#[from] validation::LocatedError),
+}
+
+#[derive(Clone, Debug)]
+struct FunctionTemplate {
+    export: record::Export<'static>,
+    signature: index::FunctionSignature,
+    entry_block: Option<index::CodeBlock>,
+}
+
+/// Allows the construction of a SAILAR module without having to calculate the indices of records.
+///
+/// # Examples
+///
+/// ```
+/// # use sailar::module_builder::ModuleBuilder;
+/// # use sailar::signature;
+/// let mut builder = ModuleBuilder::new();
+/// let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+/// let main_signature = builder.function_signature([], [integer_type]);
+/// let main = builder.define_function("main".try_into()?, main_signature);
+/// builder.entry_block(main).ret(vec![0i32.into()]);
+/// builder.set_entry_point(main);
+/// let module = builder.build().unwrap();
+/// assert_eq!(module.contents().type_signatures.len(), 1);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ModuleBuilder {
+    module_identifier: Option<record::ModuleIdentifier<'static>>,
+    entry_point: Option<index::Function>,
+    no_inline_functions: Vec<index::Function>,
+    identifiers: Interner<Identifier, index::Identifier>,
+    type_signatures: Interner<signature::Type, index::TypeSignature>,
+    function_signatures: Interner<(Box<[index::TypeSignature]>, usize), index::FunctionSignature>,
+    code: Vec<record::CodeBlock<'static>>,
+    function_imports: Vec<record::FunctionImport<'static>>,
+    function_templates: Vec<FunctionTemplate>,
+}
+
+impl ModuleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_module_identifier(&mut self, identifier: record::ModuleIdentifier<'static>) {
+        self.module_identifier = Some(identifier);
+    }
+
+    pub fn set_entry_point(&mut self, function: FunctionHandle) {
+        self.entry_point = Some(function.function);
+    }
+
+    /// Marks the specified `function` so that calls to it are never inlined.
+    pub fn prevent_inlining(&mut self, function: FunctionHandle) {
+        self.no_inline_functions.push(function.function);
+    }
+
+    /// Adds an identifier to the module, returning the index of any identical identifier that was already added.
+    pub fn identifier(&mut self, identifier: Identifier) -> index::Identifier {
+        self.identifiers.intern(identifier)
+    }
+
+    /// Adds a type signature to the module, returning the index of any identical type signature that was already added.
+    pub fn type_signature(&mut self, signature: signature::Type) -> index::TypeSignature {
+        self.type_signatures.intern(signature)
+    }
+
+    /// Adds a function signature to the module, returning the index of any identical function signature that was already
+    /// added.
+    pub fn function_signature<P, R>(&mut self, parameter_types: P, return_types: R) -> index::FunctionSignature
+    where
+        P: IntoIterator<Item = index::TypeSignature>,
+        R: IntoIterator<Item = index::TypeSignature>,
+    {
+        let mut types = return_types.into_iter().collect::<Vec<_>>();
+        let return_type_count = types.len();
+        types.extend(parameter_types);
+        self.function_signatures.intern((types.into_boxed_slice(), return_type_count))
+    }
+
+    fn function_signature_types(&self, signature: index::FunctionSignature) -> (&[index::TypeSignature], usize) {
+        let (types, return_type_count) = &self.function_signatures.values()[usize::from(signature)];
+        (types, *return_type_count)
+    }
+
+    /// Imports a function with the specified `symbol` and `signature` that is defined in another module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `signature` was not added to this module, or if a function was already defined, since imported function
+    /// templates come before the function templates defined in the module.
+    pub fn import_function(&mut self, symbol: Identifier, signature: index::FunctionSignature) -> FunctionHandle {
+        assert!(usize::from(signature) < self.function_signatures.values().len());
+        assert!(
+            self.function_templates.is_empty(),
+            "functions must be imported before any functions are defined"
+        );
+
+        let template = index::FunctionTemplate::from(self.function_imports.len());
+        self.function_imports
+            .push(record::FunctionImport::new(std::borrow::Cow::Owned(symbol), signature));
+
+        FunctionHandle {
+            signature,
+            template,
+            function: index::Function::from(usize::from(template)),
+        }
+    }
+
+    /// Defines an exported function with the specified signature, whose body is later provided by
+    /// [`ModuleBuilder::entry_block`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `signature` was not added to this module.
+    pub fn define_function(&mut self, name: Identifier, signature: index::FunctionSignature) -> FunctionHandle {
+        self.define_function_with_export(record::Export::ExportOwned(name), signature)
+    }
+
+    /// Defines a function with the specified signature, allowing functions that are private or hidden to be defined.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `signature` was not added to this module.
+    pub fn define_function_with_export(
+        &mut self,
+        export: record::Export<'static>,
+        signature: index::FunctionSignature,
+    ) -> FunctionHandle {
+        assert!(usize::from(signature) < self.function_signatures.values().len());
+
+        let template = index::FunctionTemplate::from(self.function_imports.len() + self.function_templates.len());
+        self.function_templates.push(FunctionTemplate {
+            export,
+            signature,
+            entry_block: None,
+        });
+
+        FunctionHandle {
+            signature,
+            template,
+            // Each function template is instantiated exactly once.
+            function: index::Function::from(usize::from(template)),
+        }
+    }
+
+    /// Begins a new code block with the specified input and result types.
+    pub fn code_block<A, R>(&mut self, input_types: A, result_types: R) -> CodeBlockBuilder<'_>
+    where
+        A: Into<Box<[index::TypeSignature]>>,
+        R: Into<Box<[index::TypeSignature]>>,
+    {
+        CodeBlockBuilder {
+            input_types: input_types.into(),
+            result_types: result_types.into(),
+            temporary_types: Vec::new(),
+            instructions: Vec::new(),
+            function: None,
+            finished: false,
+            module: self,
+        }
+    }
+
+    /// Begins the code block that is the entry point of the function's body. The types of the inputs and results of the
+    /// block match the function's signature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `function` is imported.
+    pub fn entry_block(&mut self, function: FunctionHandle) -> CodeBlockBuilder<'_> {
+        assert!(
+            usize::from(function.template) >= self.function_imports.len(),
+            "imported functions cannot have a body"
+        );
+
+        let (types, return_type_count) = self.function_signature_types(function.signature);
+        let (result_types, input_types) = types.split_at(return_type_count);
+        let (input_types, result_types) = (Box::from(input_types), Box::from(result_types));
+        let mut block = self.code_block(input_types, result_types);
+        block.function = Some(function.template);
+        block
+    }
+
+    /// Gets the records that make up the module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for each function that was not given an entry block.
+    pub fn into_records(self) -> Result<Vec<Record<'static>>, Vec<Error>> {
+        let mut records = Vec::new();
+        let mut errors = Vec::new();
+
+        if let Some(identifier) = self.module_identifier {
+            records.push(Record::from(record::MetadataField::ModuleIdentifier(identifier)));
+        }
+
+        if let Some(entry_point) = self.entry_point {
+            records.push(Record::from(record::MetadataField::EntryPoint(entry_point)));
+        }
+
+        records.extend(
+            self.no_inline_functions
+                .into_iter()
+                .map(|function| Record::from(record::MetadataField::NoInline(function))),
+        );
+
+        records.extend(self.identifiers.into_values().into_iter().map(Record::from));
+        records.extend(self.type_signatures.into_values().into_iter().map(Record::from));
+        records.extend(
+            self.function_signatures
+                .into_values()
+                .into_iter()
+                .map(|(types, return_type_count)| Record::from(signature::Function::from_types(types.into(), return_type_count))),
+        );
+        records.extend(self.code.into_iter().map(Record::from));
+
+        let import_count = self.function_imports.len();
+        let template_count = import_count + self.function_templates.len();
+        records.extend(self.function_imports.into_iter().map(Record::from));
+
+        for (index, template) in (import_count..).zip(self.function_templates) {
+            match template.entry_block {
+                Some(entry_block) => records.push(Record::from(record::FunctionTemplate::new(
+                    template.export,
+                    template.signature,
+                    entry_block,
+                ))),
+                None => errors.push(Error::MissingEntryBlock(index::FunctionTemplate::from(index))),
+            }
+        }
+
+        records.extend(
+            (0..template_count)
+                .map(|template| Record::from(record::Function::with_template(index::FunctionTemplate::from(template)))),
+        );
+
+        if errors.is_empty() {
+            Ok(records)
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Validates the module, returning all errors that were found.
+    pub fn build(self) -> Result<ValidModule<'static>, Vec<Error>> {
+        ValidModule::from_records_collecting_errors(self.into_records()?)
+            .map_err(|errors| errors.into_iter().map(Error::from).collect())
+    }
+}
+
+/// Builds a code block, allocating temporary registers for the results of each instruction.
+///
+/// The code block is added to the module when a terminator instruction is emitted, or when
+/// [`CodeBlockBuilder::finish`] is called. In debug builds, dropping a code block that was never added to the module panics.
+#[derive(Debug)]
+#[must_use = "code blocks are only added to the module when they are finished"]
+pub struct CodeBlockBuilder<'b> {
+    module: &'b mut ModuleBuilder,
+    input_types: Box<[index::TypeSignature]>,
+    result_types: Box<[index::TypeSignature]>,
+    temporary_types: Vec<index::TypeSignature>,
+    instructions: Vec<Instruction>,
+    function: Option<index::FunctionTemplate>,
+    finished: bool,
+}
+
+impl CodeBlockBuilder<'_> {
+    /// Gets the module containing this code block, allowing type signatures to be added while the block is built.
+    pub fn module(&mut self) -> &mut ModuleBuilder {
+        self.module
+    }
+
+    /// Gets the register containing the input at the specified index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `index` is not less than the number of inputs.
+    pub fn input(&self, index: usize) -> index::Register {
+        assert!(index < self.input_types.len());
+        index::Register::from(index)
+    }
+
+    pub fn inputs(&self) -> impl ExactSizeIterator<Item = index::Register> {
+        (0..self.input_types.len()).map(index::Register::from)
+    }
+
+    /// Gets the type of the value stored in the specified input or temporary register.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `register` has not been defined yet.
+    pub fn register_type(&self, register: index::Register) -> index::TypeSignature {
+        let index = usize::from(register);
+        match index.checked_sub(self.input_types.len()) {
+            None => self.input_types[index],
+            Some(temporary) => self.temporary_types[temporary],
+        }
+    }
+
+    /// Gets the type of the operands of an arithmetic or comparison instruction, which is the type of the first register,
+    /// since integer constants are untyped.
+    fn operand_type(&self, x: &Value, y: &Value) -> index::TypeSignature {
+        match (x, y) {
+            (Value::IndexedRegister(register), _) | (_, Value::IndexedRegister(register)) => self.register_type(*register),
+            _ => panic!("at least one operand must be a register"),
+        }
+    }
+
+    /// Appends an instruction, allocating a temporary register for each of its results.
+    ///
+    /// Since the result types of most instructions depend on the types of their operands, they must be specified here.
+    pub fn emit<R>(&mut self, instruction: Instruction, result_types: R) -> Box<[index::Register]>
+    where
+        R: IntoIterator<Item = index::TypeSignature>,
+    {
+        let first_register = self.input_types.len() + self.temporary_types.len();
+        self.temporary_types.extend(result_types);
+        self.instructions.push(instruction);
+        (first_register..self.input_types.len() + self.temporary_types.len())
+            .map(index::Register::from)
+            .collect()
+    }
+
+    /// Appends a `call` instruction, allocating temporary registers for each of the function's return values.
+    pub fn call<A: Into<Box<[Value]>>>(&mut self, function: FunctionHandle, arguments: A) -> Box<[index::Register]> {
+        let (types, return_type_count) = self.module.function_signature_types(function.signature);
+        let return_types = types[..return_type_count].to_vec();
+        self.emit(Instruction::Call(function.function, arguments.into()), return_types)
+    }
+
+    /// Appends an `iadd` instruction that ignores overflow, returning the register containing the sum, which has the same
+    /// type as the operands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither operand is a register.
+    pub fn iadd<X: Into<Value>, Y: Into<Value>>(&mut self, x: X, y: Y) -> index::Register {
+        self.integer_arithmetic(Instruction::IAdd, x.into(), y.into())
+    }
+
+    /// Appends an `isub` instruction that ignores overflow, returning the register containing the difference, which has the
+    /// same type as the operands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither operand is a register.
+    pub fn isub<X: Into<Value>, Y: Into<Value>>(&mut self, x: X, y: Y) -> index::Register {
+        self.integer_arithmetic(Instruction::ISub, x.into(), y.into())
+    }
+
+    fn integer_arithmetic(
+        &mut self,
+        instruction: fn(Box<IntegerArithmetic>) -> Instruction,
+        x: Value,
+        y: Value,
+    ) -> index::Register {
+        let operand_type = self.operand_type(&x, &y);
+        let arithmetic = IntegerArithmetic::new(OverflowBehavior::Ignore, x, y);
+        self.emit(instruction(Box::new(arithmetic)), [operand_type])[0]
+    }
+
+    /// Appends a `cmp` instruction, returning the register containing the result of the comparison, which has the same type
+    /// as the operands.
+    ///
+    /// # Panics
+    ///
+    /// Panics if neither operand is a register.
+    pub fn cmp<X: Into<Value>, Y: Into<Value>>(&mut self, x: X, kind: ComparisonKind, y: Y) -> index::Register {
+        let (x, y) = (x.into(), y.into());
+        let operand_type = self.operand_type(&x, &y);
+        let comparison = instruction::Comparison::new(kind, x, y);
+        self.emit(Instruction::Cmp(Box::new(comparison)), [operand_type])[0]
+    }
+
+    /// Adds the code block to the module.
+    pub fn finish(mut self) -> index::CodeBlock {
+        let index = index::CodeBlock::from(self.module.code.len());
+        self.module.code.push(record::CodeBlock::new(
+            std::mem::take(&mut self.input_types),
+            std::mem::take(&mut self.result_types),
+            std::mem::take(&mut self.temporary_types),
+            std::mem::take(&mut self.instructions),
+        ));
+        self.finished = true;
+
+        if let Some(function) = self.function {
+            let definition = usize::from(function) - self.module.function_imports.len();
+            self.module.function_templates[definition].entry_block = Some(index);
+        }
+
+        index
+    }
+
+    /// Appends a `return` instruction, then adds the code block to the module.
+    pub fn ret<V: Into<Box<[Value]>>>(mut self, values: V) -> index::CodeBlock {
+        self.instructions.push(Instruction::Return(values.into()));
+        self.finish()
+    }
+
+    /// Appends a `br` instruction, then adds the code block to the module.
+    pub fn br<A: Into<Box<[Value]>>>(mut self, target: index::CodeBlock, arguments: A) -> index::CodeBlock {
+        self.instructions.push(Instruction::Br(target, arguments.into()));
+        self.finish()
+    }
+
+    /// Appends a `br.if` instruction, then adds the code block to the module.
+    pub fn br_if<C, A>(
+        mut self,
+        condition: C,
+        true_branch: index::CodeBlock,
+        false_branch: index::CodeBlock,
+        arguments: A,
+    ) -> index::CodeBlock
+    where
+        C: Into<Value>,
+        A: Into<Box<[Value]>>,
+    {
+        let branch = BranchIf::new(condition.into(), true_branch, false_branch, arguments.into());
+        self.instructions.push(Instruction::BrIf(Box::new(branch)));
+        self.finish()
+    }
+}
+
+impl Drop for CodeBlockBuilder<'_> {
+    fn drop(&mut self) {
+        debug_assert!(
+            self.finished || std::thread::panicking(),
+            "code block was dropped without being added to the module"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_interned() {
+        let mut builder = ModuleBuilder::new();
+        let u32_type = builder.type_signature(signature::Type::from(signature::IntegerType::U32));
+        let u64_type = builder.type_signature(signature::Type::from(signature::IntegerType::U64));
+        assert_eq!(
+            builder.type_signature(signature::Type::from(signature::IntegerType::U32)),
+            u32_type
+        );
+        assert_ne!(u32_type, u64_type);
+
+        let signature = builder.function_signature([u32_type], [u64_type]);
+        assert_eq!(builder.function_signature([u32_type], [u64_type]), signature);
+        assert_ne!(builder.function_signature([u64_type], [u32_type]), signature);
+        assert_ne!(builder.function_signature([], [u32_type, u64_type]), signature);
+
+        let name = Identifier::try_from_str("name").unwrap();
+        assert_eq!(builder.identifier(name.clone()), builder.identifier(name));
+
+        let module = builder.build().unwrap();
+        assert_eq!(module.contents().type_signatures.len(), 2);
+        assert_eq!(module.contents().function_signatures.len(), 3);
+        assert_eq!(module.contents().identifiers.len(), 1);
+    }
+
+    #[test]
+    fn functions_can_call_each_other() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+        let signature = builder.function_signature([integer_type], [integer_type]);
+        let caller = builder.define_function(Identifier::try_from_str("caller").unwrap(), signature);
+        let callee = builder.define_function(Identifier::try_from_str("callee").unwrap(), signature);
+
+        let mut caller_body = builder.entry_block(caller);
+        let results = caller_body.call(callee, vec![caller_body.input(0).into()]);
+        assert_eq!(results.as_ref(), [index::Register::from(1)]);
+        caller_body.ret(vec![results[0].into()]);
+
+        let callee_body = builder.entry_block(callee);
+        let input = callee_body.input(0);
+        callee_body.ret(vec![input.into()]);
+
+        let module = builder.build().unwrap();
+        assert_eq!(module.contents().code.len(), 2);
+        assert_eq!(module.exports().function_templates.len(), 2);
+    }
+
+    #[test]
+    fn imported_functions_are_round_tripped() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+        let signature = builder.function_signature([], [integer_type]);
+        let import = builder.import_function(Identifier::try_from_str("imported").unwrap(), signature);
+        let caller = builder.define_function(Identifier::try_from_str("caller").unwrap(), signature);
+        let mut body = builder.entry_block(caller);
+        let results = body.call(import, Vec::new());
+        body.ret(vec![results[0].into()]);
+
+        let mut writer = crate::builder::Builder::new();
+        writer.extend(builder.into_records().unwrap());
+        let mut bytes = Vec::new();
+        writer.write_to(&mut bytes).unwrap();
+
+        let module = ValidModule::from_slice(&bytes).unwrap().unwrap();
+        let contents = module.contents();
+        assert_eq!(contents.function_imports.len(), 1);
+        assert_eq!(contents.function_imports[0].symbol.as_str(), "imported");
+        assert_eq!(
+            module.exports().function_templates.as_ref(),
+            [index::FunctionTemplate::from(1)]
+        );
+    }
+
+    #[test]
+    fn typed_instructions_use_operand_types() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U16));
+        let signature = builder.function_signature([integer_type], [integer_type]);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+
+        let mut exit_block = builder.code_block([integer_type], [integer_type]);
+        let difference = exit_block.isub(exit_block.input(0), 1u16);
+        assert_eq!(exit_block.register_type(difference), integer_type);
+        let exit_block = exit_block.ret(vec![difference.into()]);
+
+        let mut body = builder.entry_block(main);
+        let sum = body.iadd(2u16, body.input(0));
+        let is_zero = body.cmp(sum, ComparisonKind::Equal, 0u16);
+        assert_eq!(body.register_type(is_zero), integer_type);
+        body.br_if(is_zero, exit_block, exit_block, vec![sum.into()]);
+
+        let module = builder.build().unwrap();
+        assert_eq!(
+            module.contents().code[1].temporary_types().iter().collect::<Vec<_>>(),
+            [integer_type, integer_type]
+        );
+    }
+
+    #[test]
+    fn functions_can_be_private() {
+        let mut builder = ModuleBuilder::new();
+        let signature = builder.function_signature([], []);
+        let symbol = Identifier::try_from_str("private").unwrap();
+        let function = builder.define_function_with_export(record::Export::PrivateOwned(symbol), signature);
+        builder.entry_block(function).ret(Vec::new());
+
+        let module = builder.build().unwrap();
+        assert!(module.exports().function_templates.is_empty());
+        assert_eq!(
+            module.contents().function_templates[0].export.kind(),
+            record::ExportKind::Private
+        );
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "code block was dropped without being added to the module")]
+    fn unfinished_code_blocks_are_detected() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U8));
+        let mut block = builder.code_block([integer_type], []);
+        block.emit(Instruction::Nop, []);
+    }
+
+    #[test]
+    fn functions_without_bodies_are_errors() {
+        let mut builder = ModuleBuilder::new();
+        let signature = builder.function_signature([], []);
+        builder.define_function(Identifier::try_from_str("empty").unwrap(), signature);
+        assert!(matches!(
+            builder.build().unwrap_err().as_slice(),
+            [Error::MissingEntryBlock(template)] if usize::from(*template) == 0
+        ));
+    }
+
+    #[test]
+    fn invalid_modules_are_errors() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+        let signature = builder.function_signature([], [integer_type]);
+        let function = builder.define_function(Identifier::try_from_str("invalid").unwrap(), signature);
+        builder.entry_block(function).ret(Vec::new());
+        assert!(matches!(builder.build().unwrap_err().as_slice(), [Error::Validation(_)]));
+    }
+}
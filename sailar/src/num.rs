@@ -138,7 +138,7 @@ impl VarU28 {
     /// # use sailar::num::VarU28;
     /// assert!(VarU28::MAX_2 < VarU28::MAX_3);
     /// ```
-    pub const MAX_2: Self = Self::from_u16(0x3FF);
+    pub const MAX_2: Self = Self::from_u16(0x3FFF);
 
     /// The maximum value that can be encoded in 3 bytes.
     ///
@@ -206,13 +206,13 @@ impl VarU28 {
             1 => {
                 let mut buffer = [0u8];
                 source.read_exact(&mut buffer)?;
-                let high_bits = (buffer[0] as u16) >> 2;
+                let high_bits = (buffer[0] as u16) << 6;
                 Ok(Ok(Self::from_u16(((0x3Fu8 & leading_byte) as u16) | high_bits)))
             }
             2 => {
                 let mut buffer = [0u8; 2];
                 source.read_exact(&mut buffer)?;
-                let high_bits = (u16::from_le_bytes(buffer) as u32) >> 3;
+                let high_bits = (u16::from_le_bytes(buffer) as u32) << 5;
                 Ok(Ok(unsafe {
                     // Safety: All 24-bit integers are valid
                     Self::new_unchecked(((0x1Fu8 & leading_byte) as u32) | high_bits)
@@ -242,7 +242,7 @@ impl VarU28 {
     /// VarU28::from_u8(86).write_to(buffer.as_mut_slice()).unwrap();
     /// assert_eq!(buffer[0], 86);
     /// VarU28::from_u8(128).write_to(buffer.as_mut_slice()).unwrap();
-    /// assert_eq!(&buffer[..2], &[0x80, 0x01]);
+    /// assert_eq!(&buffer[..2], &[0x80, 0x02]);
     /// ```
     pub fn write_to<W: std::io::Write>(self, mut destination: W) -> std::io::Result<()> {
         let value = self.get();
@@ -250,17 +250,17 @@ impl VarU28 {
             1 => destination.write_all(&[value as u8]),
             2 => {
                 let value = value as u16;
-                let mut buffer = (value << 1).to_le_bytes();
+                let mut buffer = (value << 2).to_le_bytes();
                 buffer[0] = (value.to_le_bytes()[0] & 0x3Fu8) | 0x80u8;
                 destination.write_all(&buffer)
             }
             3 => {
-                let mut buffer = (value << 2).to_le_bytes();
+                let mut buffer = (value << 3).to_le_bytes();
                 buffer[0] = (value.to_le_bytes()[0] & 0x1Fu8) | 0b1100_0000u8;
                 destination.write_all(&buffer[..3])
             }
             4 => {
-                let mut buffer = (value << 3).to_le_bytes();
+                let mut buffer = (value << 4).to_le_bytes();
                 buffer[0] = (value.to_le_bytes()[0] & 0xFu8) | 0b1110_0000u8;
                 destination.write_all(&buffer)
             }
@@ -385,4 +385,18 @@ mod tests {
             VarU28::from_u8(0b0110_1011)
         );
     }
+
+    #[test]
+    fn values_of_every_length_round_trip() {
+        let mut buffer = Vec::with_capacity(4);
+        for value in [
+            0, 0x7F, 0x80, 0xC0, 0x3FFF, 0x4000, 0x12345, 0x1FFFFF, 0x200000, 0xABCDEF, 0xFFFFFFF,
+        ] {
+            let expected = VarU28::try_from(value).unwrap();
+            buffer.clear();
+            expected.write_to(&mut buffer).unwrap();
+            assert_eq!(buffer.len(), usize::from(expected.byte_length().get()));
+            assert_eq!(VarU28::read_from(buffer.as_slice()).unwrap().unwrap(), expected);
+        }
+    }
 }
@@ -0,0 +1,393 @@
+use crate::index;
+use crate::instruction::{BranchIf, Instruction, Value};
+use crate::optimizer::Pass;
+use crate::record;
+use crate::validation::ModuleContents;
+use rustc_hash::FxHashSet;
+
+/// Replaces `call` instructions with copies of the code blocks of the called function.
+///
+/// The code block containing the call is split in two, with the instructions before the call branching to a copy of the
+/// callee's entry block. Each `ret` in the copied blocks is replaced with a branch to a new block containing the
+/// instructions after the call, and any registers used after the call are passed through the copied blocks as additional
+/// inputs.
+///
+/// Functions are only inlined if the total number of instructions in their code blocks does not exceed the
+/// [`instruction_limit`](Inliner::instruction_limit). Calls to functions marked in the module's
+/// [`no_inline_functions`](ModuleContents::no_inline_functions) are never inlined, and neither are recursive calls into the
+/// function that they call.
+#[derive(Clone, Debug)]
+pub struct Inliner {
+    instruction_limit: usize,
+}
+
+impl Inliner {
+    /// The default maximum number of instructions in a function that can be inlined.
+    pub const DEFAULT_INSTRUCTION_LIMIT: usize = 16;
+
+    pub fn new() -> Self {
+        Self {
+            instruction_limit: Self::DEFAULT_INSTRUCTION_LIMIT,
+        }
+    }
+
+    /// The maximum number of instructions in the code blocks of a function for it to be inlined.
+    pub fn instruction_limit(&self) -> usize {
+        self.instruction_limit
+    }
+
+    pub fn set_instruction_limit(&mut self, limit: usize) {
+        self.instruction_limit = limit;
+    }
+}
+
+impl Default for Inliner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Gets the code blocks reachable from the `entry_block`, in the order that they are discovered.
+fn reachable_blocks(code: &[record::CodeBlock], entry_block: index::CodeBlock) -> Vec<index::CodeBlock> {
+    let mut blocks = vec![entry_block];
+    let mut visited = FxHashSet::default();
+    visited.insert(entry_block);
+
+    let mut next = 0;
+    while let Some(block) = blocks.get(next).copied() {
+        for instruction in code[usize::from(block)].instructions.iter() {
+            let targets = match instruction {
+                Instruction::Br(target, _) => [Some(*target), None],
+                Instruction::BrIf(branch) => [Some(branch.true_branch()), Some(branch.false_branch())],
+                _ => continue,
+            };
+
+            for target in targets.into_iter().flatten() {
+                if visited.insert(target) {
+                    blocks.push(target);
+                }
+            }
+        }
+
+        next += 1;
+    }
+
+    blocks
+}
+
+fn renumber_registers<M: Fn(usize) -> usize>(instruction: &mut Instruction, mapping: M) {
+    instruction.values_mut(|value| {
+        if let Value::IndexedRegister(register) = value {
+            *register = index::Register::from(mapping(usize::from(*register)));
+        }
+    })
+}
+
+fn with_additional_arguments(arguments: &[Value], additional: &[Value]) -> Box<[Value]> {
+    arguments.iter().chain(additional).cloned().collect()
+}
+
+impl Pass for Inliner {
+    fn run(&mut self, contents: &mut ModuleContents<'_>) {
+        // Callees are always copied from the original code, so code inlined into a function is never inlined again
+        let original_code = contents.code.clone();
+
+        // Contains the index of each block to search for calls, along with the index of the original block it came from
+        let mut pending = (0..original_code.len())
+            .map(|index| (index, index::CodeBlock::from(index)))
+            .collect::<Vec<_>>();
+
+        while let Some((block_index, origin)) = pending.pop() {
+            let block = &contents.code[block_index];
+            let mut first_result = block.input_count;
+            let mut call_site = None;
+
+            for (index, instruction) in block.instructions.iter().enumerate() {
+                if let Instruction::Call(callee, _) = instruction {
+                    let template = contents.functions[usize::from(*callee)].template;
+                    // Imported functions have no code that could be inlined
+                    let definition = contents.function_template_definition(template);
+
+                    if let Some(definition) = definition.filter(|_| !contents.no_inline_functions.contains(callee)) {
+                        let callee_blocks = reachable_blocks(&original_code, definition.entry_block);
+
+                        let size = callee_blocks
+                            .iter()
+                            .map(|block| original_code[usize::from(*block)].instructions.len())
+                            .sum::<usize>();
+
+                        if size <= self.instruction_limit && !callee_blocks.contains(&origin) {
+                            call_site = Some((index, first_result, callee_blocks));
+                            break;
+                        }
+                    }
+                }
+
+                first_result += contents.result_count(instruction);
+            }
+
+            let (call_index, first_result, callee_blocks) = match call_site {
+                Some(call_site) => call_site,
+                None => continue,
+            };
+
+            let block = contents.code[block_index].clone();
+            let register_types = block
+                .input_types()
+                .iter()
+                .chain(block.temporary_types().iter())
+                .collect::<Vec<_>>();
+            let call_result_count = contents.result_count(&block.instructions[call_index]);
+            let after_call = first_result + call_result_count;
+
+            // Registers defined before the call that are used after it are passed to the continuation
+            let mut live_registers = Vec::new();
+            for instruction in block.instructions[call_index + 1..].iter() {
+                instruction.for_each_value(|value| {
+                    if let Value::IndexedRegister(register) = value {
+                        let register = usize::from(*register);
+                        if register < first_result && !live_registers.contains(&register) {
+                            live_registers.push(register);
+                        }
+                    }
+                });
+            }
+            live_registers.sort_unstable();
+
+            let live_types = live_registers
+                .iter()
+                .map(|register| register_types[*register])
+                .collect::<Vec<_>>();
+            let live_values = live_registers
+                .iter()
+                .map(|register| Value::from(index::Register::from(*register)))
+                .collect::<Vec<_>>();
+
+            let first_copy = contents.code.len();
+            let continuation = index::CodeBlock::from(first_copy + callee_blocks.len());
+            let copy_index = |callee_block: index::CodeBlock| {
+                let position = callee_blocks.iter().position(|block| *block == callee_block).unwrap();
+                index::CodeBlock::from(first_copy + position)
+            };
+
+            let call_arguments = match &block.instructions[call_index] {
+                Instruction::Call(_, arguments) => arguments.clone(),
+                _ => unreachable!(),
+            };
+
+            // The block containing the call now branches to the callee
+            let mut caller_instructions = block.instructions[..call_index].to_vec();
+            caller_instructions.push(Instruction::Br(
+                copy_index(callee_blocks[0]),
+                with_additional_arguments(&call_arguments, &live_values),
+            ));
+
+            contents.code[block_index] = record::CodeBlock::new(
+                block.input_types().iter().collect::<Vec<_>>(),
+                block.result_types().iter().collect::<Vec<_>>(),
+                register_types[block.input_count..first_result].to_vec(),
+                caller_instructions,
+            );
+
+            for callee_block in callee_blocks.iter() {
+                let callee_block = &original_code[usize::from(*callee_block)];
+                let input_count = callee_block.input_count;
+                let live_inputs = (input_count..input_count + live_values.len())
+                    .map(|register| Value::from(index::Register::from(register)))
+                    .collect::<Vec<_>>();
+
+                let instructions = callee_block
+                    .instructions
+                    .iter()
+                    .map(|instruction| {
+                        let mut instruction = instruction.clone();
+                        renumber_registers(&mut instruction, |register| {
+                            if register < input_count {
+                                register
+                            } else {
+                                register + live_values.len()
+                            }
+                        });
+
+                        match instruction {
+                            Instruction::Return(values) => {
+                                Instruction::Br(continuation, with_additional_arguments(&values, &live_inputs))
+                            }
+                            Instruction::Br(target, arguments) => {
+                                Instruction::Br(copy_index(target), with_additional_arguments(&arguments, &live_inputs))
+                            }
+                            Instruction::BrIf(branch) => Instruction::BrIf(Box::new(BranchIf::new(
+                                branch.condition().clone(),
+                                copy_index(branch.true_branch()),
+                                copy_index(branch.false_branch()),
+                                with_additional_arguments(branch.arguments(), &live_inputs),
+                            ))),
+                            instruction => instruction,
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                contents.code.push(record::CodeBlock::new(
+                    callee_block
+                        .input_types()
+                        .iter()
+                        .chain(live_types.iter().copied())
+                        .collect::<Vec<_>>(),
+                    Vec::new(),
+                    callee_block.temporary_types().iter().collect::<Vec<_>>(),
+                    instructions,
+                ));
+            }
+
+            // The continuation receives the results of the call, followed by the live registers
+            let live_register_count = live_registers.len();
+            let continuation_instructions = block.instructions[call_index + 1..]
+                .iter()
+                .map(|instruction| {
+                    let mut instruction = instruction.clone();
+                    renumber_registers(&mut instruction, |register| {
+                        if register >= after_call {
+                            register - first_result + live_register_count
+                        } else if register >= first_result {
+                            register - first_result
+                        } else {
+                            let position = live_registers.binary_search(&register).unwrap();
+                            call_result_count + position
+                        }
+                    });
+                    instruction
+                })
+                .collect::<Vec<_>>();
+
+            contents.code.push(record::CodeBlock::new(
+                register_types[first_result..after_call]
+                    .iter()
+                    .copied()
+                    .chain(live_types)
+                    .collect::<Vec<_>>(),
+                block.result_types().iter().collect::<Vec<_>>(),
+                register_types[after_call..].to_vec(),
+                continuation_instructions,
+            ));
+
+            pending.push((usize::from(continuation), origin));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identifier::Identifier;
+    use crate::instruction::{IntegerArithmetic, OverflowBehavior};
+    use crate::module_builder::{FunctionHandle, ModuleBuilder};
+    use crate::optimizer::PassManager;
+    use crate::signature;
+    use crate::validation::ValidModule;
+
+    /// Creates a module where `main` adds its input to the result of calling `add_one`, which branches to another block.
+    fn call_sample() -> ValidModule<'static> {
+        call_sample_with(|_, _| ())
+    }
+
+    /// Creates the module returned by [`call_sample`], allowing the builder to be modified before it is built.
+    fn call_sample_with<F: FnOnce(&mut ModuleBuilder, FunctionHandle)>(modify: F) -> ValidModule<'static> {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U32));
+        let signature = builder.function_signature([integer_type], [integer_type]);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+        let add_one = builder.define_function(Identifier::try_from_str("add_one").unwrap(), signature);
+
+        let mut main_body = builder.entry_block(main);
+        let input = main_body.input(0);
+        let results = main_body.call(add_one, vec![input.into()]);
+        let sum = main_body.iadd(input, results[0]);
+        main_body.ret(vec![sum.into()]);
+
+        let mut exit_block = builder.code_block([integer_type], [integer_type]);
+        let exit_input = exit_block.input(0);
+        let incremented = exit_block.iadd(exit_input, 1u32);
+        let exit_block = exit_block.ret(vec![incremented.into()]);
+
+        let add_one_body = builder.entry_block(add_one);
+        let add_one_input = add_one_body.input(0);
+        add_one_body.br(exit_block, vec![add_one_input.into()]);
+
+        modify(&mut builder, add_one);
+        builder.build().unwrap()
+    }
+
+    fn inline(module: ValidModule<'static>, inliner: Inliner) -> ValidModule<'static> {
+        let mut manager = PassManager::new();
+        manager.add_pass(inliner);
+        manager.run(module).unwrap()
+    }
+
+    fn count_calls(module: &ValidModule) -> usize {
+        let code = &module.contents().code;
+        code.iter()
+            .flat_map(|block| block.instructions.iter())
+            .filter(|instruction| matches!(instruction, Instruction::Call(_, _)))
+            .count()
+    }
+
+    #[test]
+    fn calls_are_replaced_with_branches() {
+        let module = inline(call_sample(), Inliner::new());
+        assert_eq!(count_calls(&module), 0);
+
+        let contents = module.contents();
+        let main_block = &contents.code[usize::from(contents.function_templates[0].entry_block)];
+        assert!(matches!(main_block.instructions.as_ref(), [Instruction::Br(_, arguments)] if arguments.len() == 2));
+
+        // The continuation receives the result of the call and the input of main
+        let continuation = contents.code.last().unwrap();
+        assert_eq!(continuation.input_count, 2);
+        assert_eq!(
+            continuation.instructions.as_ref(),
+            [
+                Instruction::IAdd(Box::new(IntegerArithmetic::new(
+                    OverflowBehavior::Ignore,
+                    index::Register::from(1).into(),
+                    index::Register::from(0).into(),
+                ))),
+                Instruction::Return(vec![index::Register::from(2).into()].into_boxed_slice()),
+            ]
+        );
+    }
+
+    #[test]
+    fn large_functions_are_not_inlined() {
+        let mut small_limit = Inliner::new();
+        small_limit.set_instruction_limit(2);
+        assert_eq!(count_calls(&inline(call_sample(), small_limit)), 1);
+    }
+
+    #[test]
+    fn functions_marked_in_module_are_not_inlined() {
+        let module = call_sample_with(|builder, add_one| builder.prevent_inlining(add_one));
+        assert_eq!(module.contents().no_inline_functions, [index::Function::from(1)]);
+
+        // The marker is kept when the module is written and read again
+        let mut builder = crate::builder::Builder::new();
+        builder.extend(module.into_contents().into_records());
+        let mut bytes = Vec::new();
+        builder.write_to(&mut bytes).unwrap();
+        let module = ValidModule::from_builder(crate::builder::Builder::read_from(bytes.as_slice()).unwrap()).unwrap();
+        let inlined = inline(module, Inliner::new());
+        assert_eq!(count_calls(&inlined), 1);
+        assert_eq!(inlined.contents().no_inline_functions, [index::Function::from(1)]);
+    }
+
+    #[test]
+    fn recursive_calls_are_not_inlined_into_themselves() {
+        let mut builder = ModuleBuilder::new();
+        let signature = builder.function_signature([], []);
+        let forever = builder.define_function(Identifier::try_from_str("forever").unwrap(), signature);
+        let mut body = builder.entry_block(forever);
+        body.call(forever, Vec::new());
+        body.ret(Vec::new());
+
+        assert_eq!(count_calls(&inline(builder.build().unwrap(), Inliner::new())), 1);
+    }
+}
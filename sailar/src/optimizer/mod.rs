@@ -0,0 +1,733 @@
+//! Transformations that simplify the contents of SAILAR modules.
+//!
+//! Passes operate on the [`ModuleContents`] of a valid module, and are run in order by a [`PassManager`].
+
+use crate::index;
+use crate::instruction::{BranchIf, ConstantInteger, Instruction, OverflowBehavior, Value};
+use crate::record;
+use crate::signature;
+use crate::validation::{self, ModuleContents, ValidModule};
+
+mod inliner;
+
+pub use inliner::Inliner;
+
+/// A transformation over the contents of a SAILAR module.
+pub trait Pass {
+    /// Transforms the contents of a valid module, which must still be valid afterwards.
+    fn run(&mut self, contents: &mut ModuleContents<'_>);
+}
+
+/// Runs a sequence of passes over a module.
+///
+/// # Examples
+///
+/// ```
+/// # use sailar::instruction::{Instruction, IntegerArithmetic, OverflowBehavior};
+/// # use sailar::module_builder::ModuleBuilder;
+/// # use sailar::optimizer::PassManager;
+/// # use sailar::signature;
+/// let mut builder = ModuleBuilder::new();
+/// let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+/// let signature = builder.function_signature([], [integer_type]);
+/// let main = builder.define_function("main".try_into()?, signature);
+/// let mut body = builder.entry_block(main);
+/// let sum = body.emit(
+///     Instruction::IAdd(Box::new(IntegerArithmetic::new(OverflowBehavior::Ignore, 1i32.into(), 2i32.into()))),
+///     [integer_type],
+/// );
+/// body.ret(vec![sum[0].into()]);
+///
+/// let module = PassManager::with_default_passes().run(builder.build().unwrap())?;
+/// assert_eq!(module.contents().code[0].instructions.as_ref(), [Instruction::Return(vec![3i32.into()].into_boxed_slice())]);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Default)]
+pub struct PassManager {
+    passes: Vec<Box<dyn Pass>>,
+}
+
+impl PassManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a pass manager that runs every pass in this module.
+    pub fn with_default_passes() -> Self {
+        let mut manager = Self::new();
+        manager.add_pass(Inliner::new());
+        manager.add_pass(ConstantFolding);
+        manager.add_pass(NopElimination);
+        manager.add_pass(DeadCodeElimination);
+        manager.add_pass(DeadRecordElimination::new());
+        manager
+    }
+
+    /// Adds a pass that is run after all previously added passes.
+    pub fn add_pass<P: Pass + 'static>(&mut self, pass: P) {
+        self.passes.push(Box::new(pass));
+    }
+
+    /// Runs each pass in the order that they were added, then validates the transformed module.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pass produced an invalid module.
+    pub fn run<'data>(&mut self, module: ValidModule<'data>) -> Result<ValidModule<'data>, validation::Error> {
+        let mut contents = module.into_contents();
+        for pass in self.passes.iter_mut() {
+            pass.run(&mut contents);
+        }
+        ValidModule::from_records(contents.into_records())
+    }
+}
+
+impl std::fmt::Debug for PassManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("PassManager").field("pass_count", &self.passes.len()).finish()
+    }
+}
+
+/// The types of the input and temporary registers of a code block.
+fn register_types<'a>(contents: &'a ModuleContents, block: &record::CodeBlock) -> Vec<&'a signature::Type> {
+    block
+        .input_types()
+        .iter()
+        .chain(block.temporary_types().iter())
+        .map(|index| &contents.type_signatures[usize::from(index)])
+        .collect()
+}
+
+/// The size and sign of an integer type that fits in 64 bits.
+#[derive(Clone, Copy, Debug)]
+struct IntegerKind {
+    bits: u32,
+    signed: bool,
+}
+
+impl IntegerKind {
+    fn from_type(integer_type: &signature::Type) -> Option<Self> {
+        match integer_type {
+            signature::Type::FixedInteger(integer_type) if integer_type.size().bit_size().get() <= 64 => Some(Self {
+                bits: u32::from(integer_type.size().bit_size().get()),
+                signed: integer_type.sign() == signature::IntegerSign::Signed,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Truncates the `value` to fit in this integer type, then sign or zero extends it.
+    fn wrap(self, value: u64) -> u64 {
+        let shift = 64 - self.bits;
+        if shift == 0 {
+            value
+        } else if self.signed {
+            (((value << shift) as i64) >> shift) as u64
+        } else {
+            (value << shift) >> shift
+        }
+    }
+
+    fn widen(self, value: u64) -> i128 {
+        if self.signed {
+            i128::from(value as i64)
+        } else {
+            i128::from(value)
+        }
+    }
+
+    fn minimum(self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn maximum(self) -> i128 {
+        if self.signed {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+
+    /// Gets the value of an untyped integer constant, which is zero extended then wrapped to fit in this integer type.
+    fn constant_value(self, constant: &ConstantInteger) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes[..constant.len()].copy_from_slice(constant);
+        self.wrap(u64::from_le_bytes(bytes))
+    }
+
+    /// Creates the smallest integer constant that contains every bit of this integer type.
+    fn to_constant(self, value: u64) -> ConstantInteger {
+        let bytes = value.to_le_bytes();
+        match self.bits {
+            0..=8 => ConstantInteger::I8(bytes[0]),
+            9..=16 => ConstantInteger::I16([bytes[0], bytes[1]]),
+            17..=32 => ConstantInteger::I32([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            _ => ConstantInteger::I64(bytes),
+        }
+    }
+}
+
+/// Replaces `iadd`, `isub`, and `cmp` instructions whose operands are constant with their results, which are then used
+/// in place of the registers containing them.
+///
+/// The instructions that were folded are left unused, and can be removed by [`DeadCodeElimination`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ConstantFolding;
+
+impl Pass for ConstantFolding {
+    fn run(&mut self, contents: &mut ModuleContents<'_>) {
+        let mut code = std::mem::take(&mut contents.code);
+
+        for block in code.iter_mut() {
+            let kinds = register_types(contents, block)
+                .into_iter()
+                .map(IntegerKind::from_type)
+                .collect::<Vec<_>>();
+
+            let mut known_values = vec![None::<u64>; kinds.len()];
+            let mut next_register = block.input_count;
+            let instructions = block.instructions.to_mut();
+
+            for instruction in instructions.iter_mut() {
+                let first_result = next_register;
+                next_register += contents.result_count(instruction);
+
+                let known_value = |known_values: &[Option<u64>], value: &Value, kind: IntegerKind| match value {
+                    Value::Constant(crate::instruction::Constant::Integer(constant)) => Some(kind.constant_value(constant)),
+                    Value::IndexedRegister(register) => known_values[usize::from(*register)],
+                };
+
+                let replace_known_registers = |known_values: &[Option<u64>], instruction: &mut Instruction| {
+                    instruction.values_mut(|value| {
+                        if let Value::IndexedRegister(register) = value {
+                            let register = usize::from(*register);
+                            if let (Some(known), Some(kind)) = (known_values[register], kinds[register]) {
+                                *value = Value::from(kind.to_constant(known));
+                            }
+                        }
+                    })
+                };
+
+                match instruction {
+                    Instruction::IAdd(arithmetic) | Instruction::ISub(arithmetic) => {
+                        // Vector arithmetic is not folded, since the operands must be registers
+                        if let Some(kind) = kinds[first_result] {
+                            let x = known_value(&known_values, arithmetic.x_value(), kind);
+                            let y = known_value(&known_values, arithmetic.y_value(), kind);
+                            if let (Some(x), Some(y)) = (x, y) {
+                                let is_addition = matches!(instruction, Instruction::IAdd(_));
+                                let (exact, wrapped) = if is_addition {
+                                    (kind.widen(x) + kind.widen(y), kind.wrap(x.wrapping_add(y)))
+                                } else {
+                                    (kind.widen(x) - kind.widen(y), kind.wrap(x.wrapping_sub(y)))
+                                };
+
+                                let overflowed = kind.widen(wrapped) != exact;
+                                let overflow_behavior = match instruction {
+                                    Instruction::IAdd(arithmetic) | Instruction::ISub(arithmetic) => {
+                                        arithmetic.overflow_behavior()
+                                    }
+                                    _ => unreachable!(),
+                                };
+
+                                known_values[first_result] = Some(match overflow_behavior {
+                                    OverflowBehavior::Saturate if overflowed => kind.wrap(if exact < kind.minimum() {
+                                        kind.minimum()
+                                    } else {
+                                        kind.maximum()
+                                    }
+                                        as u64),
+                                    _ => wrapped,
+                                });
+
+                                if overflow_behavior == OverflowBehavior::Flag {
+                                    known_values[first_result + 1] =
+                                        kinds[first_result + 1].map(|flag_kind| flag_kind.wrap(u64::from(overflowed)));
+                                }
+                            } else {
+                                replace_known_registers(&known_values, instruction);
+                            }
+                        }
+                    }
+                    Instruction::Cmp(comparison) => {
+                        // Replacing both operands with constants would change the type of the operands, so the comparison is
+                        // only changed when the result is known
+                        let operand_kind = match (comparison.x_value(), comparison.y_value()) {
+                            (Value::IndexedRegister(register), _) | (_, Value::IndexedRegister(register)) => {
+                                kinds[usize::from(*register)]
+                            }
+                            _ => kinds[first_result],
+                        };
+
+                        if let (Some(operand_kind), Some(result_kind)) = (operand_kind, kinds[first_result]) {
+                            let x = known_value(&known_values, comparison.x_value(), operand_kind);
+                            let y = known_value(&known_values, comparison.y_value(), operand_kind);
+                            if let (Some(x), Some(y)) = (x, y) {
+                                let ordering = if operand_kind.signed {
+                                    (x as i64).cmp(&(y as i64))
+                                } else {
+                                    x.cmp(&y)
+                                };
+
+                                let result = match comparison.kind() {
+                                    crate::instruction::ComparisonKind::Equal => ordering.is_eq(),
+                                    crate::instruction::ComparisonKind::NotEqual => ordering.is_ne(),
+                                    crate::instruction::ComparisonKind::LessThan => ordering.is_lt(),
+                                    crate::instruction::ComparisonKind::GreaterThan => ordering.is_gt(),
+                                    crate::instruction::ComparisonKind::LessThanOrEqual => ordering.is_le(),
+                                    crate::instruction::ComparisonKind::GreaterThanOrEqual => ordering.is_ge(),
+                                };
+
+                                known_values[first_result] = Some(result_kind.wrap(u64::from(result)));
+                            }
+                        }
+                    }
+                    Instruction::Return(_)
+                    | Instruction::Br(_, _)
+                    | Instruction::BrIf(_)
+                    | Instruction::Call(_, _)
+                    | Instruction::ThreadSpawn(_, _) => replace_known_registers(&known_values, instruction),
+                    _ => (),
+                }
+            }
+        }
+
+        contents.code = code;
+    }
+}
+
+/// Removes all `nop` instructions.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NopElimination;
+
+impl Pass for NopElimination {
+    fn run(&mut self, contents: &mut ModuleContents<'_>) {
+        for block in contents.code.iter_mut() {
+            if block
+                .instructions
+                .iter()
+                .any(|instruction| matches!(instruction, Instruction::Nop))
+            {
+                let instructions = block
+                    .instructions
+                    .iter()
+                    .filter(|instruction| !matches!(instruction, Instruction::Nop))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                block.instructions = instructions.into();
+            }
+        }
+    }
+}
+
+/// Removes instructions without side effects whose results are never used, then renumbers the remaining temporary
+/// registers.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeadCodeElimination;
+
+impl Pass for DeadCodeElimination {
+    fn run(&mut self, contents: &mut ModuleContents<'_>) {
+        let mut code = std::mem::take(&mut contents.code);
+
+        for block in code.iter_mut() {
+            let mut instructions = block.instructions.to_vec();
+            let result_counts = instructions
+                .iter()
+                .map(|instruction| contents.result_count(instruction))
+                .collect::<Vec<_>>();
+
+            let register_count = block.register_count();
+            let mut use_counts = vec![0usize; register_count];
+            for instruction in instructions.iter_mut() {
+                instruction.values_mut(|value| {
+                    if let Value::IndexedRegister(register) = value {
+                        use_counts[usize::from(*register)] += 1;
+                    }
+                });
+            }
+
+            // Instructions are visited in reverse, so that instructions only used by removed instructions are also removed
+            let mut is_removed = vec![false; instructions.len()];
+            let mut first_result = register_count;
+            for (index, instruction) in instructions.iter_mut().enumerate().rev() {
+                first_result -= result_counts[index];
+                let has_side_effects = !matches!(
+                    instruction,
+                    Instruction::Nop
+                        | Instruction::IAdd(_)
+                        | Instruction::ISub(_)
+                        | Instruction::Cmp(_)
+                        | Instruction::VSplat(_)
+                        | Instruction::VExtract(_, _)
+                        | Instruction::VInsert(_)
+                        | Instruction::VShuffle(_)
+                );
+
+                if !has_side_effects
+                    && use_counts[first_result..first_result + result_counts[index]]
+                        .iter()
+                        .all(|uses| *uses == 0)
+                {
+                    is_removed[index] = true;
+                    instruction.values_mut(|value| {
+                        if let Value::IndexedRegister(register) = value {
+                            use_counts[usize::from(*register)] -= 1;
+                        }
+                    });
+                }
+            }
+
+            if !is_removed.contains(&true) {
+                continue;
+            }
+
+            let old_temporary_types = block.temporary_types().iter().collect::<Vec<_>>();
+            let mut temporary_types = Vec::with_capacity(old_temporary_types.len());
+            let mut register_mapping = (0..block.input_count).map(Some).collect::<Vec<_>>();
+            for (index, count) in result_counts.iter().enumerate() {
+                for _ in 0..*count {
+                    if is_removed[index] {
+                        register_mapping.push(None);
+                    } else {
+                        register_mapping.push(Some(block.input_count + temporary_types.len()));
+                        temporary_types.push(old_temporary_types[register_mapping.len() - 1 - block.input_count]);
+                    }
+                }
+            }
+
+            let instructions = instructions
+                .into_iter()
+                .zip(is_removed)
+                .filter(|(_, is_removed)| !is_removed)
+                .map(|(mut instruction, _)| {
+                    instruction.values_mut(|value| {
+                        if let Value::IndexedRegister(register) = value {
+                            let renumbered =
+                                register_mapping[usize::from(*register)].expect("used registers should not be removed");
+                            *register = index::Register::from(renumbered);
+                        }
+                    });
+                    instruction
+                })
+                .collect::<Vec<_>>();
+
+            *block = record::CodeBlock::new(
+                block.input_types().iter().collect::<Vec<_>>(),
+                block.result_types().iter().collect::<Vec<_>>(),
+                temporary_types,
+                instructions,
+            );
+        }
+
+        contents.code = code;
+    }
+}
+
+/// Assigns new indices to the definitions that are used, in their original order.
+fn compact_indices<I: From<usize>>(is_used: &[bool]) -> Vec<Option<I>> {
+    let mut count = 0usize;
+    is_used
+        .iter()
+        .map(|is_used| {
+            if *is_used {
+                count += 1;
+                Some(I::from(count - 1))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Removes code blocks that are not the entry block of a function and are not branched to, along with any type
+/// signatures and function signatures that are not used.
+///
+/// Identifiers are kept by default, since they can be referred to by index from outside of the module. They are only
+/// removed if [`strip_identifiers`](DeadRecordElimination::strip_identifiers) is set.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DeadRecordElimination {
+    strip_identifiers: bool,
+}
+
+impl DeadRecordElimination {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Indicates whether all identifier records are removed, since no records in the module refer to them. Defaults to
+    /// `false`.
+    #[inline]
+    pub fn strip_identifiers(&self) -> bool {
+        self.strip_identifiers
+    }
+
+    pub fn set_strip_identifiers(&mut self, strip: bool) {
+        self.strip_identifiers = strip;
+    }
+}
+
+impl Pass for DeadRecordElimination {
+    fn run(&mut self, contents: &mut ModuleContents<'_>) {
+        let mut used_code = vec![false; contents.code.len()];
+        let mut used_function_signatures = vec![false; contents.function_signatures.len()];
+        let mut used_type_signatures = vec![false; contents.type_signatures.len()];
+
+        let mut pending_blocks = contents
+            .function_templates
+            .iter()
+            .map(|template| template.entry_block)
+            .collect::<Vec<_>>();
+        let mut pending_function_signatures = contents
+            .function_imports
+            .iter()
+            .map(|import| import.signature)
+            .chain(contents.function_templates.iter().map(|template| template.signature))
+            .collect::<Vec<_>>();
+        let mut pending_type_signatures = Vec::new();
+
+        while let Some(block) = pending_blocks.pop() {
+            if std::mem::replace(&mut used_code[usize::from(block)], true) {
+                continue;
+            }
+
+            let block = &contents.code[usize::from(block)];
+            pending_type_signatures.extend(block.register_types.iter());
+
+            for instruction in block.instructions.iter() {
+                match instruction {
+                    Instruction::Br(target, _) => pending_blocks.push(*target),
+                    Instruction::BrIf(branch) => {
+                        pending_blocks.push(branch.true_branch());
+                        pending_blocks.push(branch.false_branch());
+                    }
+                    Instruction::ThreadJoin(signature, _) => pending_function_signatures.push(*signature),
+                    _ => (),
+                }
+            }
+        }
+
+        loop {
+            if let Some(signature) = pending_function_signatures.pop() {
+                if !std::mem::replace(&mut used_function_signatures[usize::from(signature)], true) {
+                    pending_type_signatures.extend(contents.function_signatures[usize::from(signature)].types().iter());
+                }
+            } else if let Some(signature) = pending_type_signatures.pop() {
+                if !std::mem::replace(&mut used_type_signatures[usize::from(signature)], true) {
+                    match &contents.type_signatures[usize::from(signature)] {
+                        signature::Type::RawPtr(Some(pointee)) => pending_type_signatures.push(*pointee),
+                        signature::Type::FuncPtr(function) => pending_function_signatures.push(*function),
+                        _ => (),
+                    }
+                }
+            } else {
+                break;
+            }
+        }
+
+        let code_mapping = compact_indices::<index::CodeBlock>(&used_code);
+        let function_signature_mapping = compact_indices::<index::FunctionSignature>(&used_function_signatures);
+        let type_signature_mapping = compact_indices::<index::TypeSignature>(&used_type_signatures);
+
+        let map_code_block = |block: index::CodeBlock| code_mapping[usize::from(block)].expect("code block should be used");
+        let map_function_signature = |signature: index::FunctionSignature| {
+            function_signature_mapping[usize::from(signature)].expect("function signature should be used")
+        };
+        let map_type_signature = |signature: index::TypeSignature| {
+            type_signature_mapping[usize::from(signature)].expect("type signature should be used")
+        };
+        let map_type_list = |types: signature::TypeList| types.iter().map(map_type_signature).collect::<Vec<_>>();
+
+        if self.strip_identifiers {
+            contents.identifiers.clear();
+        }
+
+        contents.type_signatures = std::mem::take(&mut contents.type_signatures)
+            .into_iter()
+            .zip(used_type_signatures)
+            .filter(|(_, is_used)| *is_used)
+            .map(|(signature, _)| match signature {
+                signature::Type::RawPtr(Some(pointee)) => signature::Type::RawPtr(Some(map_type_signature(pointee))),
+                signature::Type::FuncPtr(function) => signature::Type::FuncPtr(map_function_signature(function)),
+                other => other,
+            })
+            .collect();
+
+        contents.function_signatures = std::mem::take(&mut contents.function_signatures)
+            .into_iter()
+            .zip(used_function_signatures)
+            .filter(|(_, is_used)| *is_used)
+            .map(|(signature, _)| {
+                signature::Function::from_types(
+                    map_type_list(signature.types().sublist(0..signature.types().len())).into(),
+                    signature.return_type_count,
+                )
+            })
+            .collect();
+
+        contents.code = std::mem::take(&mut contents.code)
+            .into_iter()
+            .zip(used_code)
+            .filter(|(_, is_used)| *is_used)
+            .map(|(block, _)| {
+                let instructions = block
+                    .instructions
+                    .iter()
+                    .map(|instruction| match instruction {
+                        Instruction::Br(target, arguments) => Instruction::Br(map_code_block(*target), arguments.clone()),
+                        Instruction::BrIf(branch) => Instruction::BrIf(Box::new(BranchIf::new(
+                            branch.condition().clone(),
+                            map_code_block(branch.true_branch()),
+                            map_code_block(branch.false_branch()),
+                            branch.arguments().into(),
+                        ))),
+                        Instruction::ThreadJoin(signature, thread) => {
+                            Instruction::ThreadJoin(map_function_signature(*signature), thread.clone())
+                        }
+                        // Functions are never removed, so calls do not need to be changed
+                        _ => instruction.clone(),
+                    })
+                    .collect::<Vec<_>>();
+
+                record::CodeBlock::new(
+                    map_type_list(block.input_types()),
+                    map_type_list(block.result_types()),
+                    map_type_list(block.temporary_types()),
+                    instructions,
+                )
+            })
+            .collect();
+
+        for import in contents.function_imports.iter_mut() {
+            import.signature = map_function_signature(import.signature);
+        }
+
+        for template in contents.function_templates.iter_mut() {
+            template.signature = map_function_signature(template.signature);
+            template.entry_block = map_code_block(template.entry_block);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identifier::Identifier;
+    use crate::instruction::IntegerArithmetic;
+    use crate::module_builder::ModuleBuilder;
+
+    fn optimize_arithmetic(overflow_behavior: OverflowBehavior, x: u8, y: u8, result_count: usize) -> Box<[Value]> {
+        let mut builder = ModuleBuilder::new();
+        let byte_type = builder.type_signature(signature::Type::from(signature::IntegerType::U8));
+        let return_types = vec![byte_type; result_count];
+        let signature = builder.function_signature([], return_types.iter().copied());
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+        let mut body = builder.entry_block(main);
+        let results = body.emit(
+            Instruction::IAdd(Box::new(IntegerArithmetic::new(overflow_behavior, x.into(), y.into()))),
+            return_types,
+        );
+        body.ret(results.iter().map(|register| Value::from(*register)).collect::<Vec<_>>());
+
+        let module = PassManager::with_default_passes().run(builder.build().unwrap()).unwrap();
+        match module.contents().code[0].instructions.as_ref() {
+            [Instruction::Return(values)] => values.clone(),
+            instructions => panic!("expected single return instruction, but got {:?}", instructions),
+        }
+    }
+
+    #[test]
+    fn constant_folding_respects_overflow_behavior() {
+        assert_eq!(
+            optimize_arithmetic(OverflowBehavior::Ignore, 200, 100, 1).as_ref(),
+            [Value::from(44u8)]
+        );
+        assert_eq!(
+            optimize_arithmetic(OverflowBehavior::Saturate, 200, 100, 1).as_ref(),
+            [Value::from(255u8)]
+        );
+        assert_eq!(
+            optimize_arithmetic(OverflowBehavior::Flag, 200, 100, 2).as_ref(),
+            [Value::from(44u8), Value::from(1u8)]
+        );
+        assert_eq!(
+            optimize_arithmetic(OverflowBehavior::Flag, 1, 2, 2).as_ref(),
+            [Value::from(3u8), Value::from(0u8)]
+        );
+    }
+
+    #[test]
+    fn unused_temporaries_are_removed_and_renumbered() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::S32));
+        let signature = builder.function_signature([integer_type], [integer_type]);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+        let mut body = builder.entry_block(main);
+        let input = body.input(0);
+        body.emit(Instruction::Nop, []);
+        body.isub(input, 1i32);
+        let used = body.iadd(input, 1i32);
+        body.ret(vec![used.into()]);
+
+        let module = PassManager::with_default_passes().run(builder.build().unwrap()).unwrap();
+        let block = &module.contents().code[0];
+        assert_eq!(block.temporary_count(), 1);
+        assert_eq!(
+            block.instructions.as_ref(),
+            [
+                Instruction::IAdd(Box::new(IntegerArithmetic::new(
+                    OverflowBehavior::Ignore,
+                    input.into(),
+                    1i32.into()
+                ))),
+                Instruction::Return(vec![index::Register::from(1).into()].into_boxed_slice()),
+            ]
+        );
+    }
+
+    #[test]
+    fn unreferenced_records_are_removed() {
+        let mut builder = ModuleBuilder::new();
+        let unused_type = builder.type_signature(signature::Type::from(signature::IntegerType::U64));
+        let unused_signature = builder.function_signature([unused_type], []);
+        builder.identifier(Identifier::try_from_str("unused").unwrap());
+        let unused_block = builder.code_block([unused_type], []);
+        unused_block.ret(Vec::new());
+        let signature = builder.function_signature([], []);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+        builder.entry_block(main).ret(Vec::new());
+        assert_ne!(unused_signature, signature);
+
+        let module = PassManager::with_default_passes().run(builder.build().unwrap()).unwrap();
+        let contents = module.contents();
+        assert!(contents.type_signatures.is_empty());
+        assert_eq!(contents.identifiers.len(), 1);
+        assert_eq!(contents.function_signatures.len(), 1);
+        assert_eq!(contents.code.len(), 1);
+        assert_eq!(usize::from(contents.function_templates[0].entry_block), 0);
+        assert_eq!(module.exports().function_templates.len(), 1);
+    }
+
+    #[test]
+    fn identifiers_are_only_removed_when_requested() {
+        let mut builder = ModuleBuilder::new();
+        builder.identifier(Identifier::try_from_str("unused").unwrap());
+        let module = builder.build().unwrap();
+
+        let mut passes = PassManager::new();
+        passes.add_pass(DeadRecordElimination::new());
+        let module = passes.run(module).unwrap();
+        assert_eq!(module.contents().identifiers.len(), 1);
+
+        let mut strip = DeadRecordElimination::new();
+        strip.set_strip_identifiers(true);
+        let mut passes = PassManager::new();
+        passes.add_pass(strip);
+        let module = passes.run(module).unwrap();
+        assert!(module.contents().identifiers.is_empty());
+    }
+}
@@ -92,6 +92,12 @@ pub enum ErrorKind {
     InvalidIntegerTypeSize(#[from] signature::InvalidIntegerSizeError),
     #[error("expected integer size for type signature")]
     MissingIntegerTypeSize,
+    #[error("expected lane count byte for vector type signature")]
+    MissingVectorLaneCount,
+    #[error("vector types must have at least one lane")]
+    InvalidVectorLaneCount,
+    #[error(transparent)]
+    InvalidVectorElementType(#[from] signature::InvalidVectorElementTypeError),
     #[error("expected type signature index")]
     MissingTypeSignatureIndex,
     #[error("expected integer return type count")]
@@ -136,6 +142,38 @@ pub enum ErrorKind {
     MissingInstructionOverflowValue,
     #[error(transparent)]
     InvalidInstructionOverflowValue(#[from] instruction::InvalidOverflowBehaviorError),
+    #[error("expected code block index for branch target")]
+    MissingBranchTarget,
+    #[error("expected comparison kind byte")]
+    MissingComparisonKind,
+    #[error(transparent)]
+    InvalidComparisonKind(#[from] instruction::InvalidComparisonKindError),
+    #[error("expected memory ordering byte")]
+    MissingMemoryOrdering,
+    #[error(transparent)]
+    InvalidMemoryOrdering(#[from] instruction::InvalidMemoryOrderingError),
+    #[error("expected atomic operation byte")]
+    MissingAtomicOperation,
+    #[error(transparent)]
+    InvalidAtomicOperation(#[from] instruction::InvalidAtomicOperationError),
+    #[error("expected vector lane index")]
+    MissingVectorLaneIndex,
+    #[error("expected vector lane index count")]
+    MissingVectorLaneIndexCount,
+    #[error("expected function index for module entry point")]
+    MissingEntryPointIndex,
+    #[error("expected index of function that should not be inlined")]
+    MissingNoInlineFunctionIndex,
+    #[error("expected integer count of module version numbers")]
+    MissingModuleVersionLength,
+    #[error("expected module version number")]
+    MissingModuleVersionNumber,
+    #[error("{0} is not a known metadata field")]
+    UnknownMetadataField(identifier::Identifier),
+    #[error("functions with generic arguments are not yet supported")]
+    UnsupportedGenericArguments,
+    #[error("array records cannot contain array records")]
+    NestedArrayRecord,
     #[error("expected end of file")]
     ExpectedEOF,
     #[error(transparent)]
@@ -263,6 +301,75 @@ impl<R: Read> Wrapper<R> {
 
 type BufferWrapper<'b> = Wrapper<&'b [u8]>;
 
+impl<'data> BufferWrapper<'data> {
+    /// Borrows the next `length` bytes of the input, or returns `None` if fewer than `length` bytes remain.
+    fn take_bytes(&mut self, length: usize) -> Option<&'data [u8]> {
+        self.previous_offset = self.offset;
+        if length > self.source.len() {
+            return None;
+        }
+
+        let (taken, remaining) = self.source.split_at(length);
+        self.source = remaining;
+        self.offset += length;
+        Some(taken)
+    }
+
+    /// Reads `count` type signature indices, returning a list that borrows their encoded form.
+    fn read_type_list(&mut self, count: usize) -> Result<signature::TypeList<'data>> {
+        let start = self.source;
+        for _ in 0..count {
+            self.read_unsigned_integer_try_into::<index::TypeSignature>(|| ErrorKind::MissingTypeSignatureIndex)?;
+        }
+
+        let length = start.len() - self.source.len();
+        Ok(signature::TypeList::from_encoded(&start[..length], count))
+    }
+}
+
+/// Reads the magic number, format version, and the number of records in the module.
+fn read_module_header<R: Read>(source: &mut Wrapper<R>) -> Result<(versioning::SupportedFormat, usize)> {
+    {
+        let mut magic_buffer = [0u8; binary::MAGIC.len()];
+        let magic_length = source.read_bytes(&mut magic_buffer)?;
+        if magic_length < magic_buffer.len() {
+            return source.fail_with(InvalidMagicError::new(&magic_buffer[0..magic_length]));
+        }
+    }
+
+    let format_version = {
+        let mut values = [0u8; 2];
+        let value_count = source.read_bytes(&mut values)?;
+
+        if value_count < 2 {
+            return source.fail_with(ErrorKind::MissingFormatVersion);
+        }
+
+        source.wrap_result(versioning::SupportedFormat::try_from(versioning::Format {
+            major: values[0],
+            minor: values[1],
+        }))?
+    };
+
+    let record_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordCount)?;
+    Ok((format_version, record_count))
+}
+
+fn read_record_type<R: Read>(source: &mut Wrapper<R>) -> Result<record::Type> {
+    let mut type_value = 0u8;
+    if source.read_bytes(std::slice::from_mut(&mut type_value))? == 0 {
+        return source.fail_with(ErrorKind::MissingRecordType);
+    }
+    source.wrap_result(record::Type::try_from(type_value))
+}
+
+/// Reads the type of a record followed by the size of its content, in bytes.
+fn read_record_header<R: Read>(source: &mut Wrapper<R>) -> Result<(record::Type, usize)> {
+    let record_type = read_record_type(source)?;
+    let record_size = source.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordSize)?;
+    Ok((record_type, record_size))
+}
+
 /// Allows the reading of the contents of a SAILAR module from a source.
 #[derive(Debug)]
 pub struct Reader<R> {
@@ -296,43 +403,454 @@ impl<R: Read> Reader<R> {
     /// assert!(matches!(reader.to_record_reader(), Err(_)));
     /// ```
     pub fn to_record_reader(mut self) -> Result<(versioning::SupportedFormat, RecordReader<R>)> {
-        {
-            let mut magic_buffer = [0u8; binary::MAGIC.len()];
-            let magic_length = self.source.read_bytes(&mut magic_buffer)?;
-            if magic_length < magic_buffer.len() {
-                return self.source.fail_with(InvalidMagicError::new(&magic_buffer[0..magic_length]));
+        let (format_version, record_count) = read_module_header(&mut self.source)?;
+        Ok((format_version, RecordReader::new(self.source, record_count)))
+    }
+}
+
+impl<R: Read> From<R> for Reader<R> {
+    #[inline]
+    fn from(source: R) -> Self {
+        Self::new(source)
+    }
+}
+
+fn read_identifier_content<'data>(source: &mut BufferWrapper<'data>, size: usize) -> Result<Cow<'data, identifier::Id>> {
+    match source.take_bytes(size) {
+        Some(bytes) => source.wrap_result(identifier::Id::from_byte_slice(bytes)).map(Cow::Borrowed),
+        None => source.fail_with(ErrorKind::UnexpectedEndOfData {
+            name: "identifier",
+            actual_size: source.source.len(),
+            expected_size: size,
+        }),
+    }
+}
+
+fn read_identifier<'data>(source: &mut BufferWrapper<'data>) -> Result<Cow<'data, identifier::Id>> {
+    let length = source.read_unsigned_integer_try_into(|| ErrorKind::MissingIdentifierLength)?;
+    read_identifier_content(source, length)
+}
+
+fn read_fixed_integer_type_signature(
+    source: &mut BufferWrapper<'_>,
+    sign: signature::IntegerSign,
+) -> Result<signature::IntegerType> {
+    Ok(signature::IntegerType::new(
+        sign,
+        source.read_unsigned_integer_try_into(|| ErrorKind::MissingIntegerTypeSize)?,
+    ))
+}
+
+fn read_type(source: &mut BufferWrapper<'_>) -> Result<signature::Type> {
+    let mut tag_value = 0u8;
+    if source.read_bytes(std::slice::from_mut(&mut tag_value))? == 0 {
+        return source.fail_with(ErrorKind::MissingTypeSignatureTag);
+    }
+
+    Ok(match source.wrap_result(TypeCode::try_from(tag_value))? {
+        TypeCode::U8 => signature::IntegerType::U8.into(),
+        TypeCode::S8 => signature::IntegerType::S8.into(),
+        TypeCode::U16 => signature::IntegerType::U16.into(),
+        TypeCode::S16 => signature::IntegerType::S16.into(),
+        TypeCode::U32 => signature::IntegerType::U32.into(),
+        TypeCode::S32 => signature::IntegerType::S32.into(),
+        TypeCode::U64 => signature::IntegerType::U64.into(),
+        TypeCode::S64 => signature::IntegerType::S64.into(),
+        TypeCode::U128 => signature::IntegerType::U128.into(),
+        TypeCode::S128 => signature::IntegerType::S128.into(),
+        TypeCode::U256 => signature::IntegerType::U256.into(),
+        TypeCode::S256 => signature::IntegerType::S256.into(),
+        TypeCode::UInt => read_fixed_integer_type_signature(source, signature::IntegerSign::Unsigned)?.into(),
+        TypeCode::SInt => read_fixed_integer_type_signature(source, signature::IntegerSign::Signed)?.into(),
+        TypeCode::UAddr => signature::Type::UAddr,
+        TypeCode::SAddr => signature::Type::SAddr,
+        TypeCode::F32 => signature::Type::F32,
+        TypeCode::F64 => signature::Type::F64,
+        TypeCode::VoidPtr => signature::Type::RawPtr(None),
+        TypeCode::RawPtr => signature::Type::RawPtr(Some(
+            source.read_unsigned_integer_try_into(|| ErrorKind::MissingTypeSignatureIndex)?,
+        )),
+        TypeCode::FuncPtr => {
+            signature::Type::FuncPtr(source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionSignatureIndex)?)
+        }
+        TypeCode::Vector => {
+            let mut lane_count = 0u8;
+            if source.read_bytes(std::slice::from_mut(&mut lane_count))? == 0 {
+                return source.fail_with(ErrorKind::MissingVectorLaneCount);
             }
+
+            let lane_count = std::num::NonZeroU8::new(lane_count).ok_or(ErrorKind::InvalidVectorLaneCount);
+            let lane_count = source.wrap_result(lane_count)?;
+            let element_type = read_type(source)?;
+            let element_type = source.wrap_result(signature::VectorElementType::try_from(&element_type))?;
+            signature::Type::Vector(signature::VectorType::new(element_type, lane_count))
         }
+    })
+}
 
-        let format_version = {
-            let mut values = [0u8; 2];
-            let value_count = self.source.read_bytes(&mut values)?;
+fn read_type_signature<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    read_type(source).map(record::Record::from)
+}
 
-            if value_count < 2 {
-                return self.source.fail_with(ErrorKind::MissingFormatVersion);
+fn read_function_signature<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    let return_type_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingReturnTypeCount)?;
+    let parameter_type_count: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingParameterTypeCount)?;
+    let types = source.read_type_list(return_type_count + parameter_type_count)?;
+    Ok(record::Record::from(signature::Function::from_types(
+        types,
+        return_type_count,
+    )))
+}
+
+fn read_code_block<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    let read_code_value = |source: &mut BufferWrapper<'_>| -> Result<instruction::Value> {
+        let mut flag_value = 0u8;
+        if source.read_bytes(std::slice::from_mut(&mut flag_value))? == 0 {
+            return source.fail_with(ErrorKind::MissingInstructionValueFlags);
+        }
+
+        let flags = source.wrap_result(
+            instruction::ValueFlags::from_bits(flag_value).ok_or(ErrorKind::InvalidInstructionValuesFlags(flag_value)),
+        )?;
+
+        if !flags.contains(instruction::ValueFlags::IS_CONSTANT) {
+            Ok(source
+                .read_unsigned_integer_try_into::<index::Register>(|| ErrorKind::MissingRegisterIndex)?
+                .into())
+        } else {
+            if !flags.contains(instruction::ValueFlags::IS_INTEGER) {
+                return source.fail_with(ErrorKind::InvalidConstantValueKind);
             }
 
-            self.source
-                .wrap_result(versioning::SupportedFormat::try_from(versioning::Format {
-                    major: values[0],
-                    minor: values[1],
-                }))?
-        };
+            let embedded_constant_size = (flags & instruction::ValueFlags::INTEGER_SIZE_MASK).bits() >> 2;
+
+            Ok(if flags.contains(instruction::ValueFlags::INTEGER_IS_EMBEDDED) {
+                if flags.contains(instruction::ValueFlags::INTEGER_EMBEDDED_ONE) {
+                    match embedded_constant_size {
+                        0 => instruction::Value::from(1u8),
+                        1 => instruction::Value::from(1u16),
+                        2 => instruction::Value::from(1u32),
+                        3 => instruction::Value::from(1u64),
+                        _ => unreachable!(),
+                    }
+                } else {
+                    match embedded_constant_size {
+                        0 => instruction::Value::from(0u8),
+                        1 => instruction::Value::from(0u16),
+                        2 => instruction::Value::from(0u32),
+                        3 => instruction::Value::from(0u64),
+                        _ => unreachable!(),
+                    }
+                }
+            } else {
+                fn read_constant_bytes<const N: usize, T>(source: &mut BufferWrapper, conversion: fn([u8; N]) -> T) -> Result<T> {
+                    let mut bytes = [0u8; N];
+                    let actual_size = source.read_bytes(&mut bytes)?;
+                    if actual_size != N {
+                        return source.fail_with(ErrorKind::UnexpectedEndOfConstantInteger {
+                            expected: N,
+                            actual: actual_size,
+                        });
+                    }
+                    Ok(conversion(bytes))
+                }
+
+                match embedded_constant_size {
+                    0 => instruction::Value::from(read_constant_bytes(source, u8::from_le_bytes)?),
+                    1 => instruction::Value::from(read_constant_bytes(source, u16::from_le_bytes)?),
+                    2 => instruction::Value::from(read_constant_bytes(source, u32::from_le_bytes)?),
+                    3 => instruction::Value::from(read_constant_bytes(source, u64::from_le_bytes)?),
+                    _ => unreachable!(),
+                }
+            })
+        }
+    };
 
-        let record_count = self.source.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordCount)?;
+    let read_many_code_values = |source: &mut BufferWrapper<'_>| -> Result<Box<[instruction::Value]>> {
+        let count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionValueCount)?;
+        let mut values = Vec::with_capacity(count);
+        for _ in 0..count {
+            values.push(read_code_value(source)?);
+        }
+        Ok(values.into_boxed_slice())
+    };
 
-        Ok((format_version, RecordReader::new(self.source, record_count)))
+    let read_integer_arithmteic = |source: &mut BufferWrapper<'_>| -> Result<Box<instruction::IntegerArithmetic>> {
+        let mut overflow_value = 0u8;
+        if source.read_bytes(std::slice::from_mut(&mut overflow_value))? == 0 {
+            return Err(source.wrap_error(ErrorKind::MissingInstructionOverflowValue));
+        }
+
+        Ok(Box::new(instruction::IntegerArithmetic::new(
+            source.wrap_result(instruction::OverflowBehavior::try_from(overflow_value))?,
+            read_code_value(source)?,
+            read_code_value(source)?,
+        )))
+    };
+
+    let read_comparison = |source: &mut BufferWrapper<'_>| -> Result<Box<instruction::Comparison>> {
+        let mut kind_value = 0u8;
+        if source.read_bytes(std::slice::from_mut(&mut kind_value))? == 0 {
+            return Err(source.wrap_error(ErrorKind::MissingComparisonKind));
+        }
+
+        Ok(Box::new(instruction::Comparison::new(
+            source.wrap_result(instruction::ComparisonKind::try_from(kind_value))?,
+            read_code_value(source)?,
+            read_code_value(source)?,
+        )))
+    };
+
+    let read_memory_ordering = |source: &mut BufferWrapper<'_>| -> Result<instruction::MemoryOrdering> {
+        let mut ordering_value = 0u8;
+        if source.read_bytes(std::slice::from_mut(&mut ordering_value))? == 0 {
+            return Err(source.wrap_error(ErrorKind::MissingMemoryOrdering));
+        }
+
+        source.wrap_result(instruction::MemoryOrdering::try_from(ordering_value))
+    };
+
+    let read_atomic_operation = |source: &mut BufferWrapper<'_>| -> Result<instruction::AtomicOperation> {
+        let mut operation_value = 0u8;
+        if source.read_bytes(std::slice::from_mut(&mut operation_value))? == 0 {
+            return Err(source.wrap_error(ErrorKind::MissingAtomicOperation));
+        }
+
+        source.wrap_result(instruction::AtomicOperation::try_from(operation_value))
+    };
+
+    let read_branch_if = |source: &mut BufferWrapper<'_>| -> Result<Box<instruction::BranchIf>> {
+        Ok(Box::new(instruction::BranchIf::new(
+            read_code_value(source)?,
+            source.read_unsigned_integer_try_into(|| ErrorKind::MissingBranchTarget)?,
+            source.read_unsigned_integer_try_into(|| ErrorKind::MissingBranchTarget)?,
+            read_many_code_values(source)?,
+        )))
+    };
+
+    let read_instruction = |source: &mut BufferWrapper<'_>| -> Result<Instruction> {
+        let mut opcode_value = 0u8;
+        if source.read_bytes(std::slice::from_mut(&mut opcode_value))? == 0 {
+            return source.fail_with(ErrorKind::MissingInstructionOpcode);
+        }
+
+        Ok(match source.wrap_result(Opcode::try_from(opcode_value))? {
+            Opcode::Nop => Instruction::Nop,
+            Opcode::Break => Instruction::Break,
+            Opcode::Return => Instruction::Return(read_many_code_values(source)?),
+            Opcode::Br => Instruction::Br(
+                source.read_unsigned_integer_try_into(|| ErrorKind::MissingBranchTarget)?,
+                read_many_code_values(source)?,
+            ),
+            Opcode::BrIf => Instruction::BrIf(read_branch_if(source)?),
+            Opcode::Call => Instruction::Call(
+                source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionCalleeIndex)?,
+                read_many_code_values(source)?,
+            ),
+            Opcode::IAdd => Instruction::IAdd(read_integer_arithmteic(source)?),
+            Opcode::ISub => Instruction::ISub(read_integer_arithmteic(source)?),
+            Opcode::Cmp => Instruction::Cmp(read_comparison(source)?),
+            Opcode::ThreadSpawn => Instruction::ThreadSpawn(
+                source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionCalleeIndex)?,
+                read_many_code_values(source)?,
+            ),
+            Opcode::ThreadJoin => Instruction::ThreadJoin(
+                source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionSignatureIndex)?,
+                read_code_value(source)?,
+            ),
+            Opcode::AtomicLoad => Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(
+                read_memory_ordering(source)?,
+                read_code_value(source)?,
+            ))),
+            Opcode::AtomicStore => Instruction::AtomicStore(Box::new(instruction::AtomicStore::new(
+                read_memory_ordering(source)?,
+                read_code_value(source)?,
+                read_code_value(source)?,
+            ))),
+            Opcode::AtomicRmw => Instruction::AtomicRmw(Box::new(instruction::AtomicReadModifyWrite::new(
+                read_atomic_operation(source)?,
+                read_memory_ordering(source)?,
+                read_code_value(source)?,
+                read_code_value(source)?,
+            ))),
+            Opcode::AtomicCmpXchg => Instruction::AtomicCmpXchg(Box::new(instruction::AtomicCompareExchange::new(
+                read_memory_ordering(source)?,
+                read_memory_ordering(source)?,
+                read_code_value(source)?,
+                read_code_value(source)?,
+                read_code_value(source)?,
+            ))),
+            Opcode::Fence => Instruction::Fence(read_memory_ordering(source)?),
+            Opcode::VSplat => Instruction::VSplat(read_code_value(source)?),
+            Opcode::VExtract => Instruction::VExtract(
+                read_code_value(source)?,
+                source.read_unsigned_integer_try_into(|| ErrorKind::MissingVectorLaneIndex)?,
+            ),
+            Opcode::VInsert => {
+                let vector = read_code_value(source)?;
+                let lane = source.read_unsigned_integer_try_into(|| ErrorKind::MissingVectorLaneIndex)?;
+                Instruction::VInsert(Box::new(instruction::VectorInsert::new(
+                    vector,
+                    read_code_value(source)?,
+                    lane,
+                )))
+            }
+            Opcode::VShuffle => {
+                let x = read_code_value(source)?;
+                let y = read_code_value(source)?;
+                let count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingVectorLaneIndexCount)?;
+                let mut lanes = Vec::with_capacity(count);
+                for _ in 0..count {
+                    lanes.push(source.read_unsigned_integer_try_into(|| ErrorKind::MissingVectorLaneIndex)?);
+                }
+                Instruction::VShuffle(Box::new(instruction::VectorShuffle::new(x, y, lanes.into_boxed_slice())))
+            }
+        })
+    };
+
+    let input_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingParameterTypeCount)?;
+    let result_count: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingReturnTypeCount)?;
+    let temporary_count: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingTemporaryRegisterCount)?;
+    let register_types = source.read_type_list(input_count + result_count + temporary_count)?;
+
+    let instruction_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionCount)?;
+    let mut instructions = Vec::with_capacity(instruction_count);
+    for _ in 0..instruction_count {
+        instructions.push(read_instruction(source)?);
     }
+
+    Ok(record::Record::from(record::CodeBlock::from_types(
+        register_types,
+        input_count,
+        result_count,
+        CowBox::Boxed(instructions.into_boxed_slice()),
+    )))
 }
 
-impl<R: Read> From<R> for Reader<R> {
-    #[inline]
-    fn from(source: R) -> Self {
-        Self::new(source)
+fn read_export<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Export<'data>> {
+    let flags: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingExportFlags)?;
+    let is_export = flags & 1 == 1;
+    let length = flags >> 1;
+
+    if length == 0 {
+        if is_export {
+            source.fail_with(ErrorKind::EmptyExportSymbol)
+        } else {
+            Ok(record::Export::Hidden)
+        }
+    } else {
+        Ok(match read_identifier_content(source, length)? {
+            Cow::Borrowed(symbol) if is_export => record::Export::ExportBorrowed(symbol),
+            Cow::Borrowed(symbol) => record::Export::PrivateBorrowed(symbol),
+            Cow::Owned(symbol) if is_export => record::Export::ExportOwned(symbol),
+            Cow::Owned(symbol) => record::Export::PrivateOwned(symbol),
+        })
+    }
+}
+
+fn read_function_import<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    Ok(record::Record::FunctionImport(record::FunctionImport {
+        symbol: read_identifier(source)?,
+        signature: source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionSignatureIndex)?,
+    }))
+}
+
+fn read_function_template<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    Ok(record::Record::FunctionTemplate(record::FunctionTemplate {
+        export: read_export(source)?,
+        signature: source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionSignatureIndex)?,
+        entry_block: source.read_unsigned_integer_try_into(|| ErrorKind::MissingCodeBlockIndex)?,
+    }))
+}
+
+fn read_function<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    let template = source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionTemplateIndex)?;
+    let placeholder = source.read_unsigned_integer(|| ErrorKind::MissingReservedInteger)?;
+
+    if placeholder != VarU28::from_u8(0) {
+        return source.fail_with(ErrorKind::UnsupportedGenericArguments);
+    }
+
+    Ok(record::Record::Function(record::Function::with_template(template)))
+}
+
+fn read_metadata_field<'data>(source: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    let field_name = read_identifier(source)?;
+    let field = match field_name.as_str() {
+        "id" => {
+            let name = read_identifier(source)?;
+            let version_length = source.read_unsigned_integer_try_into(|| ErrorKind::MissingModuleVersionLength)?;
+            let mut version = Vec::with_capacity(version_length);
+            for _ in 0..version_length {
+                version.push(source.read_unsigned_integer(|| ErrorKind::MissingModuleVersionNumber)?);
+            }
+
+            record::MetadataField::ModuleIdentifier(record::ModuleIdentifier::new(
+                name,
+                CowBox::Boxed(version.into_boxed_slice()),
+            ))
+        }
+        "main" => record::MetadataField::EntryPoint(source.read_unsigned_integer_try_into(|| ErrorKind::MissingEntryPointIndex)?),
+        "noinline" => {
+            record::MetadataField::NoInline(source.read_unsigned_integer_try_into(|| ErrorKind::MissingNoInlineFunctionIndex)?)
+        }
+        _ => return source.fail_with(ErrorKind::UnknownMetadataField(field_name.into_owned())),
+    };
+
+    Ok(record::Record::MetadataField(field))
+}
+
+/// Parses the content of a record, borrowing any identifiers or byte arrays from the input.
+fn read_record_content<'data>(record_type: record::Type, content: &mut BufferWrapper<'data>) -> Result<record::Record<'data>> {
+    match record_type {
+        record::Type::Identifier => read_identifier_content(content, content.source.len()).map(record::Record::Identifier),
+        record::Type::TypeSignature => read_type_signature(content),
+        record::Type::FunctionSignature => read_function_signature(content),
+        record::Type::Data => {
+            let bytes = content.source;
+            content.source = &[];
+            content.offset += bytes.len();
+            Ok(record::Record::Data(Cow::Borrowed(bytes)))
+        }
+        record::Type::CodeBlock => read_code_block(content),
+        record::Type::FunctionImport => read_function_import(content),
+        record::Type::FunctionTemplate => read_function_template(content),
+        record::Type::Function => read_function(content),
+        record::Type::MetadataField => read_metadata_field(content),
+        record::Type::Array => content.fail_with(ErrorKind::NestedArrayRecord),
     }
 }
 
-type RecordContentReader = for<'c, 'b> fn(&'c mut Wrapper<&'b [u8]>) -> Result<Record>;
+type RecordContentReader = for<'c, 'b> fn(&'c mut Wrapper<&'b [u8]>) -> Result<record::Record<'b>>;
+
+/// The name of an array element that is preceded by its length, and the error used when the length is missing.
+type ElementLengthPrefix = (&'static str, fn() -> ErrorKind);
+
+/// Gets the function used to parse each element of an array record, or `None` if the array contains array records.
+fn array_element_reader(element_type: record::Type) -> Option<RecordContentReader> {
+    Some(match element_type {
+        record::Type::Identifier => |source| read_identifier(source).map(record::Record::Identifier),
+        record::Type::TypeSignature => read_type_signature,
+        record::Type::Data => |source| {
+            let data_size = source.read_unsigned_integer_try_into(|| ErrorKind::MissingDataLength)?;
+            match source.take_bytes(data_size) {
+                Some(bytes) => Ok(record::Record::Data(Cow::Borrowed(bytes))),
+                None => source.fail_with(ErrorKind::UnexpectedEndOfData {
+                    name: "data array",
+                    actual_size: source.source.len(),
+                    expected_size: data_size,
+                }),
+            }
+        },
+        record::Type::MetadataField => read_metadata_field,
+        record::Type::FunctionSignature => read_function_signature,
+        record::Type::CodeBlock => read_code_block,
+        record::Type::FunctionImport => read_function_import,
+        record::Type::FunctionTemplate => read_function_template,
+        record::Type::Function => read_function,
+        record::Type::Array => return None,
+    })
+}
 
 struct ArrayRecordReader {
     file_offset: usize,
@@ -365,7 +883,7 @@ impl ArrayRecordReader {
         if self.element_count > 0 {
             let mut wrapper = Wrapper::with_file_offset(&self.element_buffer[self.element_buffer_offset..], self.file_offset);
             let start_offset = wrapper.offset;
-            let record = (self.element_reader)(&mut wrapper);
+            let record = (self.element_reader)(&mut wrapper).map(record::Record::into_owned);
             let element_size = wrapper.offset - start_offset;
             self.element_count -= 1;
             self.element_buffer_offset += element_size;
@@ -410,16 +928,7 @@ impl<R: Read> RecordReader<R> {
     ///
     /// Returns `Some(Err(_))` when invalid input is encountered, or if an error occurs during reading.
     fn read_record(&mut self) -> Result<Option<Record>> {
-        fn read_record_type<R: Read>(source: &mut Wrapper<R>) -> Result<record::Type> {
-            let mut type_value = 0u8;
-            if source.read_bytes(std::slice::from_mut(&mut type_value))? == 0 {
-                return source.fail_with(ErrorKind::MissingRecordType);
-            }
-            source.wrap_result(record::Type::try_from(type_value))
-        }
-
-        let record_type = read_record_type(&mut self.source)?;
-        let record_size = self.source.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordSize)?;
+        let (record_type, record_size) = read_record_header(&mut self.source)?;
 
         const STACK_BUFFER_LENGTH: usize = 16;
         let mut stack_buffer: [u8; STACK_BUFFER_LENGTH];
@@ -443,313 +952,238 @@ impl<R: Read> RecordReader<R> {
             });
         }
 
-        fn read_identifier_content(source: &mut BufferWrapper, size: usize) -> Result<identifier::Identifier> {
-            let mut buffer = vec![0u8; size];
-            let actual_size = source.read_bytes(&mut buffer)?;
-            if actual_size != buffer.len() {
-                return source.fail_with(ErrorKind::UnexpectedEndOfData {
-                    name: "identifier",
-                    actual_size,
-                    expected_size: size,
-                });
-            }
+        self.count -= 1;
 
-            source.wrap_result(identifier::Identifier::from_utf8(buffer))
-        }
+        match record_type {
+            record::Type::Array => {
+                let array_type = read_record_type(content)?;
+                let array_count = content.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordArrayCount)?;
+                let array_content_offset = content.offset;
+                let array_elements = content.source.to_vec().into_boxed_slice();
 
-        fn read_identifier(source: &mut BufferWrapper<'_>) -> Result<identifier::Identifier> {
-            let length = source.read_unsigned_integer_try_into(|| ErrorKind::MissingIdentifierLength)?;
-            read_identifier_content(source, length)
-        }
+                let element_reader = match array_element_reader(array_type) {
+                    Some(reader) => reader,
+                    None => return content.fail_with(ErrorKind::NestedArrayRecord),
+                };
 
-        fn read_fixed_integer_type_signature(
-            source: &mut BufferWrapper<'_>,
-            sign: signature::IntegerSign,
-        ) -> Result<signature::IntegerType> {
-            Ok(signature::IntegerType::new(
-                sign,
-                source.read_unsigned_integer_try_into(|| ErrorKind::MissingIntegerTypeSize)?,
-            ))
-        }
+                let array_reader = self.array_reader.insert(ArrayRecordReader::new(
+                    array_content_offset,
+                    array_count,
+                    array_elements,
+                    element_reader,
+                ));
 
-        fn read_type_signature(source: &mut BufferWrapper<'_>) -> Result<Record> {
-            let mut tag_value = 0u8;
-            if source.read_bytes(std::slice::from_mut(&mut tag_value))? == 0 {
-                return source.fail_with(ErrorKind::MissingTypeSignatureTag);
+                match array_reader.read_next() {
+                    Some(first_element) => first_element.map(Some),
+                    None => Ok(None),
+                }
             }
+            _ => read_record_content(record_type, content).map(|record| Some(record.into_owned())),
+        }
+    }
 
-            Ok(Record::from(match source.wrap_result(TypeCode::try_from(tag_value))? {
-                TypeCode::U8 => signature::IntegerType::U8.into(),
-                TypeCode::S8 => signature::IntegerType::S8.into(),
-                TypeCode::U16 => signature::IntegerType::U16.into(),
-                TypeCode::S16 => signature::IntegerType::S16.into(),
-                TypeCode::U32 => signature::IntegerType::U32.into(),
-                TypeCode::S32 => signature::IntegerType::S32.into(),
-                TypeCode::U64 => signature::IntegerType::U64.into(),
-                TypeCode::S64 => signature::IntegerType::S64.into(),
-                TypeCode::U128 => signature::IntegerType::U128.into(),
-                TypeCode::S128 => signature::IntegerType::S128.into(),
-                TypeCode::U256 => signature::IntegerType::U256.into(),
-                TypeCode::S256 => signature::IntegerType::S256.into(),
-                TypeCode::UInt => read_fixed_integer_type_signature(source, signature::IntegerSign::Unsigned)?.into(),
-                TypeCode::SInt => read_fixed_integer_type_signature(source, signature::IntegerSign::Signed)?.into(),
-                TypeCode::UAddr => signature::Type::UAddr,
-                TypeCode::SAddr => signature::Type::SAddr,
-                TypeCode::F32 => signature::Type::F32,
-                TypeCode::F64 => signature::Type::F64,
-                TypeCode::VoidPtr => signature::Type::RawPtr(None),
-                TypeCode::RawPtr => signature::Type::RawPtr(Some(
-                    source.read_unsigned_integer_try_into(|| ErrorKind::MissingTypeSignatureIndex)?,
-                )),
-                TypeCode::FuncPtr => {
-                    signature::Type::FuncPtr(source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionSignatureIndex)?)
+    // TODO: Reader could return index of record (e.g. TypeSignature index for TypeSignatures, etc.)
+    /// Returns the next record in the module, or `None` if no records remain in the module.
+    pub fn next_record(&mut self) -> Option<Result<Record>> {
+        match self.array_reader {
+            None => (),
+            Some(ref mut array_reader) => {
+                let element = array_reader.read_next();
+                if element.is_some() {
+                    return element;
+                } else {
+                    self.array_reader = None;
                 }
-            }))
+            }
         }
 
-        fn read_function_signature(source: &mut BufferWrapper<'_>) -> Result<Record> {
-            let return_type_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingReturnTypeCount)?;
-            let parameter_type_count: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingParameterTypeCount)?;
-            let total_count = return_type_count + parameter_type_count;
-            let mut types = Vec::<index::TypeSignature>::with_capacity(total_count);
-            for _ in 0..total_count {
-                types.push(source.read_unsigned_integer_try_into(|| ErrorKind::MissingTypeSignatureIndex)?);
+        // Read records, skipping empty arrays
+        loop {
+            if self.count == 0 {
+                return None;
             }
 
-            Ok(Record::from(signature::Function::from_boxed_slice(
-                types.into_boxed_slice(),
-                return_type_count,
-            )))
+            let result = self.read_record();
+            match result {
+                Ok(Some(record)) => break Some(Ok(record)),
+                Ok(None) => continue,
+                Err(err) => break Some(Err(err)),
+            }
         }
+    }
 
-        fn read_code_block(source: &mut BufferWrapper<'_>) -> Result<Record> {
-            let read_code_value = |source: &mut BufferWrapper<'_>| -> Result<instruction::Value> {
-                let mut flag_value = 0u8;
-                if source.read_bytes(std::slice::from_mut(&mut flag_value))? == 0 {
-                    return source.fail_with(ErrorKind::MissingInstructionValueFlags);
-                }
+    pub fn next_record_transposed(&mut self) -> Result<Option<Record>> {
+        self.next_record().transpose()
+    }
 
-                let flags = source.wrap_result(
-                    instruction::ValueFlags::from_bits(flag_value).ok_or(ErrorKind::InvalidInstructionValuesFlags(flag_value)),
-                )?;
+    /// Skips over any remaining records in the module, and checks that there are no remaining bytes in the input.
+    ///
+    /// # Errors
+    /// If there are still remaining bytes in the input after the module records, then an error is returned.
+    pub fn finish(mut self) -> Result<()> {
+        while let Some(record) = self.next_record() {
+            record?;
+        }
 
-                if !flags.contains(instruction::ValueFlags::IS_CONSTANT) {
-                    Ok(source
-                        .read_unsigned_integer_try_into::<index::Register>(|| ErrorKind::MissingRegisterIndex)?
-                        .into())
-                } else {
-                    if !flags.contains(instruction::ValueFlags::IS_INTEGER) {
-                        return source.fail_with(ErrorKind::InvalidConstantValueKind);
-                    }
+        let mut buffer = [0u8];
+        if self.source.read_bytes(&mut buffer)? != 0 {
+            return self.source.fail_with(ErrorKind::ExpectedEOF);
+        }
 
-                    let embedded_constant_size = (flags & instruction::ValueFlags::INTEGER_SIZE_MASK).bits() >> 2;
-
-                    Ok(if flags.contains(instruction::ValueFlags::INTEGER_IS_EMBEDDED) {
-                        if flags.contains(instruction::ValueFlags::INTEGER_EMBEDDED_ONE) {
-                            match embedded_constant_size {
-                                0 => instruction::Value::from(1u8),
-                                1 => instruction::Value::from(1u16),
-                                2 => instruction::Value::from(1u32),
-                                3 => instruction::Value::from(1u64),
-                                _ => unreachable!(),
-                            }
-                        } else {
-                            match embedded_constant_size {
-                                0 => instruction::Value::from(0u8),
-                                1 => instruction::Value::from(0u16),
-                                2 => instruction::Value::from(0u32),
-                                3 => instruction::Value::from(0u64),
-                                _ => unreachable!(),
-                            }
-                        }
-                    } else {
-                        fn read_constant_bytes<const N: usize, T>(
-                            source: &mut BufferWrapper,
-                            conversion: fn([u8; N]) -> T,
-                        ) -> Result<T> {
-                            let mut bytes = [0u8; N];
-                            let actual_size = source.read_bytes(&mut bytes)?;
-                            if actual_size != N {
-                                return source.fail_with(ErrorKind::UnexpectedEndOfConstantInteger {
-                                    expected: N,
-                                    actual: actual_size,
-                                });
-                            }
-                            Ok(conversion(bytes))
-                        }
+        Ok(())
+    }
+}
 
-                        match embedded_constant_size {
-                            0 => instruction::Value::from(read_constant_bytes(source, u8::from_le_bytes)?),
-                            1 => instruction::Value::from(read_constant_bytes(source, u16::from_le_bytes)?),
-                            2 => instruction::Value::from(read_constant_bytes(source, u32::from_le_bytes)?),
-                            3 => instruction::Value::from(read_constant_bytes(source, u64::from_le_bytes)?),
-                            _ => unreachable!(),
-                        }
-                    })
-                }
-            };
+impl<R: Read> std::iter::Iterator for RecordReader<R> {
+    type Item = Result<Record>;
 
-            let read_many_code_values = |source: &mut BufferWrapper<'_>| -> Result<Box<[instruction::Value]>> {
-                let count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionValueCount)?;
-                let mut values = Vec::with_capacity(count);
-                for _ in 0..count {
-                    values.push(read_code_value(source)?);
-                }
-                Ok(values.into_boxed_slice())
-            };
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_record()
+    }
 
-            let read_integer_arithmteic = |source: &mut BufferWrapper<'_>| -> Result<Box<instruction::IntegerArithmetic>> {
-                let mut overflow_value = 0u8;
-                if source.read_bytes(std::slice::from_mut(&mut overflow_value))? == 0 {
-                    return Err(source.wrap_error(ErrorKind::MissingInstructionOverflowValue));
-                }
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.count, Some(self.count))
+    }
+}
 
-                Ok(Box::new(instruction::IntegerArithmetic::new(
-                    source.wrap_result(instruction::OverflowBehavior::try_from(overflow_value))?,
-                    read_code_value(source)?,
-                    read_code_value(source)?,
-                )))
-            };
+impl<R: Read> std::iter::ExactSizeIterator for RecordReader<R> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.count
+    }
+}
 
-            let read_instruction = |source: &mut BufferWrapper<'_>| -> Result<Instruction> {
-                let mut opcode_value = 0u8;
-                if source.read_bytes(std::slice::from_mut(&mut opcode_value))? == 0 {
-                    return source.fail_with(ErrorKind::MissingInstructionOpcode);
-                }
+/// Reads the contents of a SAILAR module from a byte slice, returning records that borrow identifiers and byte arrays
+/// directly from the input instead of copying them.
+#[derive(Debug)]
+pub struct SliceReader<'data> {
+    source: BufferWrapper<'data>,
+}
 
-                Ok(match source.wrap_result(Opcode::try_from(opcode_value))? {
-                    Opcode::Nop => Instruction::Nop,
-                    Opcode::Break => Instruction::Break,
-                    Opcode::Return => Instruction::Return(read_many_code_values(source)?),
-                    Opcode::Call => Instruction::Call(
-                        source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionCalleeIndex)?,
-                        read_many_code_values(source)?,
-                    ),
-                    Opcode::IAdd => Instruction::IAdd(read_integer_arithmteic(source)?),
-                    Opcode::ISub => Instruction::ISub(read_integer_arithmteic(source)?),
-                })
-            };
+impl<'data> SliceReader<'data> {
+    pub fn new(source: &'data [u8]) -> Self {
+        Self {
+            source: Wrapper::new(source),
+        }
+    }
 
-            let input_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingParameterTypeCount)?;
-            let result_count: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingReturnTypeCount)?;
-            let temporary_count: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingTemporaryRegisterCount)?;
-            let register_count = input_count + result_count + temporary_count;
-            let mut register_types = Vec::<index::TypeSignature>::with_capacity(register_count);
-            for _ in 0..register_count {
-                register_types.push(source.read_unsigned_integer_try_into(|| ErrorKind::MissingTypeSignatureIndex)?);
-            }
+    /// Reads the magic number and format version, returning a [`SliceRecordReader`] to read the contents of the module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sailar::reader::SliceReader;
+    /// let input = "What happens if nonsense is used as input?";
+    /// let reader = SliceReader::new(input.as_bytes());
+    /// assert!(matches!(reader.to_record_reader(), Err(_)));
+    /// ```
+    pub fn to_record_reader(mut self) -> Result<(versioning::SupportedFormat, SliceRecordReader<'data>)> {
+        let (format_version, record_count) = read_module_header(&mut self.source)?;
+        Ok((format_version, SliceRecordReader::new(self.source, record_count)))
+    }
 
-            let instruction_count = source.read_unsigned_integer_try_into(|| ErrorKind::MissingInstructionCount)?;
-            let mut instructions = Vec::with_capacity(instruction_count);
-            for _ in 0..instruction_count {
-                instructions.push(read_instruction(source)?);
-            }
+    /// Reads the magic number and format version, then finds the location of every record in the module, returning a
+    /// [`RecordIndex`] that allows records to be parsed in any order.
+    pub fn to_record_index(self) -> Result<RecordIndex<'data>> {
+        RecordIndex::new(self.source)
+    }
+}
 
-            Ok(Record::from(record::CodeBlock::from_types(
-                CowBox::Boxed(register_types.into_boxed_slice()),
-                input_count,
-                result_count,
-                CowBox::Boxed(instructions.into_boxed_slice()),
-            )))
-        }
+impl<'data> From<&'data [u8]> for SliceReader<'data> {
+    #[inline]
+    fn from(source: &'data [u8]) -> Self {
+        Self::new(source)
+    }
+}
 
-        fn read_export(source: &mut BufferWrapper) -> Result<record::Export<'static>> {
-            let flags: usize = source.read_unsigned_integer_try_into(|| ErrorKind::MissingExportFlags)?;
-            let is_export = flags & 1 == 1;
-            let length = flags >> 1;
+#[derive(Debug)]
+struct SliceArrayReader<'data> {
+    element_count: usize,
+    element_reader: RecordContentReader,
+    elements: BufferWrapper<'data>,
+}
 
-            if is_export && length == 0 {
-                source.fail_with(ErrorKind::EmptyExportSymbol)
-            } else {
-                let symbol = read_identifier_content(source, length)?;
-                Ok(if is_export {
-                    record::Export::ExportOwned(symbol)
-                } else {
-                    record::Export::PrivateOwned(symbol)
-                })
-            }
+impl<'data> SliceArrayReader<'data> {
+    fn read_next(&mut self) -> Option<Result<record::Record<'data>>> {
+        if self.element_count > 0 {
+            self.element_count -= 1;
+            Some((self.element_reader)(&mut self.elements))
+        } else {
+            None
         }
+    }
+}
+
+/// Reads the records of a SAILAR module stored in a byte slice.
+///
+/// Unlike the [`RecordReader`], identifiers, data, and symbols are borrowed from the input.
+#[derive(Debug)]
+pub struct SliceRecordReader<'data> {
+    count: usize,
+    source: BufferWrapper<'data>,
+    array_reader: Option<SliceArrayReader<'data>>,
+}
 
-        fn read_function_template(source: &mut BufferWrapper) -> Result<Record> {
-            Ok(Record::FunctionTemplate(record::FunctionTemplate {
-                export: read_export(source)?,
-                signature: source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionSignatureIndex)?,
-                entry_block: source.read_unsigned_integer_try_into(|| ErrorKind::MissingCodeBlockIndex)?,
-            }))
+impl<'data> SliceRecordReader<'data> {
+    fn new(source: BufferWrapper<'data>, count: usize) -> Self {
+        Self {
+            count,
+            source,
+            array_reader: None,
         }
+    }
 
-        fn read_function(source: &mut BufferWrapper) -> Result<Record> {
-            let template = source.read_unsigned_integer_try_into(|| ErrorKind::MissingFunctionTemplateIndex)?;
-            let placeholder = source.read_unsigned_integer(|| ErrorKind::MissingReservedInteger)?;
+    /// Returns the remaining number of records in the module.
+    #[inline]
+    pub fn record_count(&self) -> usize {
+        self.count
+    }
 
-            if placeholder != VarU28::from_u8(0) {
-                todo!("generic argument count")
+    fn read_record(&mut self) -> Result<Option<record::Record<'data>>> {
+        let (record_type, record_size) = read_record_header(&mut self.source)?;
+        let content_offset = self.source.offset;
+        let content = match self.source.take_bytes(record_size) {
+            Some(content) => content,
+            None => {
+                return self.source.fail_with(ErrorKind::UnexpectedEndOfRecord {
+                    expected_size: record_size,
+                    actual_size: self.source.source.len(),
+                })
             }
+        };
 
-            Ok(Record::Function(record::Function::with_template(template)))
-        }
-
+        let mut content = Wrapper::with_file_offset(content, content_offset);
         self.count -= 1;
 
         match record_type {
             record::Type::Array => {
-                let array_type = read_record_type(content)?;
-                let array_count = content.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordArrayCount)?;
-                let array_content_offset = content.offset;
-                let array_elements = content.source.to_vec().into_boxed_slice();
-
-                let array_reader = self.array_reader.insert(ArrayRecordReader::new(
-                    array_content_offset,
-                    array_count,
-                    array_elements,
-                    match array_type {
-                        record::Type::Identifier => |src| read_identifier(src).map(Record::from),
-                        record::Type::TypeSignature => read_type_signature,
-                        record::Type::Data => |source: &mut BufferWrapper| {
-                            let data_size = source.read_unsigned_integer_try_into(|| ErrorKind::MissingDataLength)?;
-                            let mut buffer = vec![0u8; data_size];
-                            let actual_size = source.read_bytes(&mut buffer)?;
-                            if actual_size < data_size {
-                                return Err(source.wrap_error(ErrorKind::UnexpectedEndOfData {
-                                    name: "data array",
-                                    actual_size,
-                                    expected_size: data_size,
-                                }));
-                            }
-                            Ok(Record::Data(Cow::Owned(buffer)))
-                        },
-                        _ => todo!("add support for array record type {:?}", array_type),
-                    },
-                ));
+                let array_type = read_record_type(&mut content)?;
+                let element_count = content.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordArrayCount)?;
+                let element_reader = match array_element_reader(array_type) {
+                    Some(reader) => reader,
+                    None => return content.fail_with(ErrorKind::NestedArrayRecord),
+                };
+
+                let array_reader = self.array_reader.insert(SliceArrayReader {
+                    element_count,
+                    element_reader,
+                    elements: content,
+                });
 
-                match array_reader.read_next() {
-                    Some(first_element) => first_element.map(Some),
-                    None => Ok(None),
-                }
+                array_reader.read_next().transpose()
             }
-            record::Type::Identifier => read_identifier_content(content, record_size).map(|id| Some(Record::from(id))),
-            record::Type::TypeSignature => read_type_signature(content).map(Some),
-            record::Type::FunctionSignature => read_function_signature(content).map(Some),
-            record::Type::Data => Ok(Some(Record::Data(Cow::Owned(content.source.to_vec())))),
-            record::Type::CodeBlock => read_code_block(content).map(Some),
-            record::Type::FunctionTemplate => read_function_template(content).map(Some),
-            record::Type::Function => read_function(content).map(Some),
-            _ => todo!("parse a {:?}", record_type),
+            _ => read_record_content(record_type, &mut content).map(Some),
         }
     }
 
-    // TODO: Reader could return index of record (e.g. TypeSignature index for TypeSignatures, etc.)
     /// Returns the next record in the module, or `None` if no records remain in the module.
-    pub fn next_record(&mut self) -> Option<Result<Record>> {
-        match self.array_reader {
-            None => (),
-            Some(ref mut array_reader) => {
-                let element = array_reader.read_next();
-                if element.is_some() {
-                    return element;
-                } else {
-                    self.array_reader = None;
-                }
+    pub fn next_record(&mut self) -> Option<Result<record::Record<'data>>> {
+        if let Some(ref mut array_reader) = self.array_reader {
+            let element = array_reader.read_next();
+            if element.is_some() {
+                return element;
+            } else {
+                self.array_reader = None;
             }
         }
 
@@ -759,8 +1193,7 @@ impl<R: Read> RecordReader<R> {
                 return None;
             }
 
-            let result = self.read_record();
-            match result {
+            match self.read_record() {
                 Ok(Some(record)) => break Some(Ok(record)),
                 Ok(None) => continue,
                 Err(err) => break Some(Err(err)),
@@ -768,7 +1201,7 @@ impl<R: Read> RecordReader<R> {
         }
     }
 
-    pub fn next_record_transposed(&mut self) -> Result<Option<Record>> {
+    pub fn next_record_transposed(&mut self) -> Result<Option<record::Record<'data>>> {
         self.next_record().transpose()
     }
 
@@ -781,8 +1214,8 @@ impl<R: Read> RecordReader<R> {
             record?;
         }
 
-        let mut buffer = [0u8];
-        if self.source.read_bytes(&mut buffer)? != 0 {
+        if !self.source.source.is_empty() {
+            self.source.previous_offset = self.source.offset;
             return self.source.fail_with(ErrorKind::ExpectedEOF);
         }
 
@@ -790,8 +1223,8 @@ impl<R: Read> RecordReader<R> {
     }
 }
 
-impl<R: Read> std::iter::Iterator for RecordReader<R> {
-    type Item = Result<Record>;
+impl<'data> std::iter::Iterator for SliceRecordReader<'data> {
+    type Item = Result<record::Record<'data>>;
 
     fn next(&mut self) -> Option<Self::Item> {
         self.next_record()
@@ -802,13 +1235,172 @@ impl<R: Read> std::iter::Iterator for RecordReader<R> {
     }
 }
 
-impl<R: Read> std::iter::ExactSizeIterator for RecordReader<R> {
+impl<'data> std::iter::ExactSizeIterator for SliceRecordReader<'data> {
     #[inline]
     fn len(&self) -> usize {
         self.count
     }
 }
 
+/// The location of a record within a module.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RecordLocation {
+    record_type: record::Type,
+    offset: usize,
+    length: usize,
+    is_array_element: bool,
+}
+
+impl RecordLocation {
+    #[inline]
+    pub fn record_type(&self) -> record::Type {
+        self.record_type
+    }
+
+    /// The offset from the start of the module to the start of the record's content.
+    #[inline]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The size of the record's content, in bytes.
+    #[inline]
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Returns `true` if the record is an element of an array record.
+    #[inline]
+    pub fn is_array_element(&self) -> bool {
+        self.is_array_element
+    }
+}
+
+/// Contains the location of every record in a SAILAR module stored in a byte slice, allowing records to be parsed in any order.
+///
+/// Array records are replaced with the locations of each of their elements. Since the size of each element is not stored,
+/// elements other than identifiers and data, which are preceded by their length, are parsed once when the index is created.
+#[derive(Clone, Debug)]
+pub struct RecordIndex<'data> {
+    source: &'data [u8],
+    format_version: versioning::SupportedFormat,
+    locations: Box<[RecordLocation]>,
+}
+
+impl<'data> RecordIndex<'data> {
+    fn new(mut source: BufferWrapper<'data>) -> Result<Self> {
+        let module = source.source;
+        let (format_version, record_count) = read_module_header(&mut source)?;
+        let mut locations = Vec::with_capacity(record_count);
+
+        for _ in 0..record_count {
+            let (record_type, record_size) = read_record_header(&mut source)?;
+            let content_offset = source.offset;
+            let content = match source.take_bytes(record_size) {
+                Some(content) => content,
+                None => {
+                    return source.fail_with(ErrorKind::UnexpectedEndOfRecord {
+                        expected_size: record_size,
+                        actual_size: source.source.len(),
+                    })
+                }
+            };
+
+            if record_type == record::Type::Array {
+                let mut content = Wrapper::with_file_offset(content, content_offset);
+                let element_type = read_record_type(&mut content)?;
+                let element_count = content.read_unsigned_integer_try_into(|| ErrorKind::MissingRecordArrayCount)?;
+                let element_reader = match array_element_reader(element_type) {
+                    Some(reader) => reader,
+                    None => return content.fail_with(ErrorKind::NestedArrayRecord),
+                };
+
+                // Element sizes are not stored, so elements that are not preceded by their length are parsed to find where the
+                // next one begins.
+                let length_prefix: Option<ElementLengthPrefix> = match element_type {
+                    record::Type::Identifier => Some(("identifier", || ErrorKind::MissingIdentifierLength)),
+                    record::Type::Data => Some(("data array", || ErrorKind::MissingDataLength)),
+                    _ => None,
+                };
+
+                locations.reserve(element_count);
+                for _ in 0..element_count {
+                    let element_offset = content.offset;
+                    if let Some((name, missing_length)) = length_prefix {
+                        let length = content.read_unsigned_integer_try_into(missing_length)?;
+                        if content.take_bytes(length).is_none() {
+                            return content.fail_with(ErrorKind::UnexpectedEndOfData {
+                                name,
+                                actual_size: content.source.len(),
+                                expected_size: length,
+                            });
+                        }
+                    } else {
+                        element_reader(&mut content)?;
+                    }
+
+                    locations.push(RecordLocation {
+                        record_type: element_type,
+                        offset: element_offset,
+                        length: content.offset - element_offset,
+                        is_array_element: true,
+                    });
+                }
+            } else {
+                locations.push(RecordLocation {
+                    record_type,
+                    offset: content_offset,
+                    length: record_size,
+                    is_array_element: false,
+                });
+            }
+        }
+
+        if !source.source.is_empty() {
+            source.previous_offset = source.offset;
+            return source.fail_with(ErrorKind::ExpectedEOF);
+        }
+
+        Ok(Self {
+            source: module,
+            format_version,
+            locations: locations.into_boxed_slice(),
+        })
+    }
+
+    #[inline]
+    pub fn format_version(&self) -> versioning::SupportedFormat {
+        self.format_version
+    }
+
+    /// The locations of all records in the module, with the elements of array records in place of the arrays themselves.
+    #[inline]
+    pub fn locations(&self) -> &[RecordLocation] {
+        &self.locations
+    }
+
+    /// Parses the record at the specified location.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `location` did not originate from this [`RecordIndex`].
+    pub fn read_record_at(&self, location: &RecordLocation) -> Result<record::Record<'data>> {
+        let content = &self.source[location.offset..location.offset + location.length];
+        let mut content = Wrapper::with_file_offset(content, location.offset);
+        if location.is_array_element {
+            // Nested array records are rejected when the index is created.
+            array_element_reader(location.record_type).unwrap()(&mut content)
+        } else {
+            read_record_content(location.record_type, &mut content)
+        }
+    }
+
+    /// Parses the record at the specified `index`, or returns `None` if the index is out of bounds.
+    pub fn read_record(&self, index: usize) -> Option<Result<record::Record<'data>>> {
+        self.locations.get(index).map(|location| self.read_record_at(location))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -856,4 +1448,314 @@ mod tests {
 
         record_reader.finish().unwrap();
     }
+
+    #[test]
+    fn vector_type_signature_is_parsed() {
+        let vector_type = signature::VectorType::new(signature::VectorElementType::F32, std::num::NonZeroU8::new(4).unwrap());
+        let mut builder = crate::builder::Builder::new();
+        builder.add_record(signature::Type::Vector(vector_type));
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+
+        let reader = Reader::new(module.as_slice());
+        let (_, mut record_reader) = reader.to_record_reader().unwrap();
+
+        assert!(matches!(
+            record_reader.next_record_transposed().unwrap(),
+            Some(Record::TypeSignature(ty)) if ty == signature::Type::Vector(vector_type)
+        ));
+
+        record_reader.finish().unwrap();
+    }
+
+    /// Returns `true` if the `borrowed` bytes are located within the `input`.
+    fn is_borrowed_from(input: &[u8], borrowed: &[u8]) -> bool {
+        input.as_ptr_range().contains(&borrowed.as_ptr())
+    }
+
+    fn sample_module() -> Vec<u8> {
+        let integer_type = crate::index::TypeSignature::from(0);
+        let mut builder = crate::builder::Builder::new();
+        builder.add_record(identifier::Identifier::try_from_str("sample").unwrap());
+        builder.add_record(record::Record::Data(Cow::Borrowed(b"some data")));
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        builder.add_record(signature::Function::new(vec![integer_type], vec![integer_type]));
+        builder.add_record(record::CodeBlock::new(
+            vec![integer_type],
+            vec![integer_type],
+            Vec::new(),
+            vec![Instruction::Return(
+                vec![crate::index::Register::from(0).into()].into_boxed_slice(),
+            )],
+        ));
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportOwned(identifier::Identifier::try_from_str("identity").unwrap()),
+            crate::index::FunctionSignature::from(0),
+            crate::index::CodeBlock::from(0),
+        ));
+
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+        module
+    }
+
+    #[test]
+    fn slice_reader_produces_same_records() {
+        let module = sample_module();
+        let (_, stream_records) = Reader::new(module.as_slice()).to_record_reader().unwrap();
+        let (_, slice_records) = SliceReader::new(&module).to_record_reader().unwrap();
+        let expected = stream_records.collect::<Result<Vec<_>>>().unwrap();
+        let actual = slice_records.map(|record| record.map(record::Record::into_owned));
+        assert_eq!(expected, actual.collect::<Result<Vec<_>>>().unwrap());
+    }
+
+    #[test]
+    fn slice_reader_borrows_from_input() {
+        let module = sample_module();
+        let (_, mut record_reader) = SliceReader::new(&module).to_record_reader().unwrap();
+
+        assert!(matches!(
+            record_reader.next_record_transposed().unwrap(),
+            Some(record::Record::Identifier(Cow::Borrowed(identifier))) if is_borrowed_from(&module, identifier.as_str().as_bytes())
+        ));
+
+        assert!(matches!(
+            record_reader.next_record_transposed().unwrap(),
+            Some(record::Record::Data(Cow::Borrowed(bytes))) if is_borrowed_from(&module, bytes)
+        ));
+
+        let is_type_list_borrowed =
+            |types: &signature::TypeList| matches!(types.encoded_bytes(), Some(bytes) if is_borrowed_from(&module, bytes));
+
+        while let Some(record) = record_reader.next_record_transposed().unwrap() {
+            match record {
+                record::Record::FunctionSignature(signature) => {
+                    assert!(is_type_list_borrowed(signature.types()));
+                    assert!(is_type_list_borrowed(&signature.parameter_types()));
+                    assert_eq!(
+                        signature.types().iter().collect::<Vec<_>>(),
+                        [crate::index::TypeSignature::from(0); 2]
+                    );
+                }
+                record::Record::CodeBlock(block) => {
+                    assert!(is_type_list_borrowed(&block.register_types));
+                    assert!(is_type_list_borrowed(&block.result_types()));
+                    assert!(block.temporary_types().is_empty());
+                }
+                record::Record::FunctionTemplate(template) => assert!(
+                    matches!(template.export, record::Export::ExportBorrowed(symbol) if is_borrowed_from(&module, symbol.as_str().as_bytes()))
+                ),
+                _ => (),
+            }
+        }
+
+        record_reader.finish().unwrap();
+    }
+
+    #[test]
+    fn array_of_data_records_is_borrowed() {
+        let module = [
+            b'S',
+            b'A',
+            b'I',
+            b'L',
+            b'A',
+            b'R',
+            versioning::SupportedFormat::CURRENT.major,
+            versioning::SupportedFormat::CURRENT.minor,
+            1, // Number of records
+            1,
+            9,
+            5, // Element type
+            2, // Element count
+            2,
+            0xA,
+            0xB,
+            3,
+            b'A',
+            b'B',
+            b'C',
+        ];
+
+        let (_, mut record_reader) = SliceReader::new(&module).to_record_reader().unwrap();
+
+        assert!(matches!(
+            record_reader.next_record_transposed().unwrap(),
+            Some(record::Record::Data(Cow::Borrowed(bytes))) if bytes == [0xA, 0xB] && is_borrowed_from(&module, bytes)
+        ));
+
+        assert!(matches!(
+            record_reader.next_record_transposed().unwrap(),
+            Some(record::Record::Data(Cow::Borrowed(bytes))) if bytes == b"ABC" && is_borrowed_from(&module, bytes)
+        ));
+
+        record_reader.finish().unwrap();
+    }
+
+    #[test]
+    fn record_index_contains_array_elements() {
+        let module = sample_module();
+        let records = SliceReader::new(&module).to_record_index().unwrap();
+        let (_, expected) = SliceReader::new(&module).to_record_reader().unwrap();
+        for (location, expected) in records.locations().iter().zip(expected) {
+            assert!(!location.is_array_element());
+            assert_eq!(records.read_record_at(location).unwrap(), expected.unwrap());
+        }
+
+        let module = [
+            b'S',
+            b'A',
+            b'I',
+            b'L',
+            b'A',
+            b'R',
+            versioning::SupportedFormat::CURRENT.major,
+            versioning::SupportedFormat::CURRENT.minor,
+            2, // Number of records
+            1,
+            9,
+            5, // Element type
+            2, // Element count
+            2,
+            0xA,
+            0xB,
+            3,
+            b'A',
+            b'B',
+            b'C',
+            5,
+            1,
+            0xD,
+        ];
+
+        let records = SliceReader::new(&module).to_record_index().unwrap();
+        let locations = records.locations();
+        assert_eq!(locations.len(), 3);
+        assert!(locations[0].is_array_element() && locations[1].is_array_element() && !locations[2].is_array_element());
+        assert_eq!(locations[1].offset(), 16);
+        assert!(matches!(
+            records.read_record(1),
+            Some(Ok(record::Record::Data(Cow::Borrowed(b"ABC"))))
+        ));
+        assert!(matches!(
+            records.read_record(2),
+            Some(Ok(record::Record::Data(Cow::Borrowed(&[0xD]))))
+        ));
+        assert!(records.read_record(3).is_none());
+    }
+
+    #[test]
+    fn valid_module_is_read_from_slice() {
+        let module = sample_module();
+        let valid_module = crate::validation::ValidModule::from_slice(&module).unwrap().unwrap();
+        let contents = valid_module.contents();
+        assert!(
+            matches!(&contents.identifiers[..], [Cow::Borrowed(identifier)] if is_borrowed_from(&module, identifier.as_str().as_bytes()))
+        );
+        assert!(matches!(&contents.data[..], [Cow::Borrowed(bytes)] if is_borrowed_from(&module, bytes)));
+    }
+
+    #[test]
+    fn slice_reader_rejects_trailing_bytes() {
+        let mut module = sample_module();
+        module.push(0);
+        assert!(matches!(
+            crate::validation::ValidModule::from_slice(&module),
+            Err(error) if matches!(error.kind(), ErrorKind::ExpectedEOF)
+        ));
+    }
+
+    #[test]
+    fn export_symbols_are_round_tripped() {
+        let exports = [
+            record::Export::ExportOwned(identifier::Identifier::try_from_str("exported").unwrap()),
+            record::Export::PrivateOwned(identifier::Identifier::try_from_str("private").unwrap()),
+            record::Export::Hidden,
+        ];
+
+        let mut builder = crate::builder::Builder::new();
+        for export in exports.iter() {
+            builder.add_record(record::FunctionTemplate::new(
+                export.clone(),
+                crate::index::FunctionSignature::from(0),
+                crate::index::CodeBlock::from(0),
+            ));
+        }
+
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+
+        let reader = Reader::new(module.as_slice());
+        let (_, mut record_reader) = reader.to_record_reader().unwrap();
+
+        for expected in exports.into_iter() {
+            assert!(matches!(
+                record_reader.next_record_transposed().unwrap(),
+                Some(Record::FunctionTemplate(template)) if template.export == expected
+            ));
+        }
+
+        record_reader.finish().unwrap();
+    }
+
+    #[test]
+    fn metadata_fields_are_round_tripped() {
+        let fields = [
+            record::MetadataField::ModuleIdentifier(record::ModuleIdentifier::new_owned(
+                identifier::Identifier::try_from_str("Metadata").unwrap(),
+                vec![VarU28::from_u8(1), VarU28::from_u8(2)],
+            )),
+            record::MetadataField::EntryPoint(crate::index::Function::from(3)),
+            record::MetadataField::NoInline(crate::index::Function::from(2)),
+        ];
+
+        let mut builder = crate::builder::Builder::new();
+        for field in fields.iter() {
+            builder.add_record(field.clone());
+        }
+
+        let mut module = Vec::new();
+        builder.write_to(&mut module).unwrap();
+
+        let (_, stream_records) = Reader::new(module.as_slice()).to_record_reader().unwrap();
+        let (_, slice_records) = SliceReader::new(&module).to_record_reader().unwrap();
+        let expected = fields.into_iter().map(Record::MetadataField).collect::<Vec<_>>();
+        assert_eq!(expected, stream_records.collect::<Result<Vec<_>>>().unwrap());
+        assert_eq!(expected, slice_records.collect::<Result<Vec<_>>>().unwrap());
+    }
+
+    /// Reads a module containing a single record with the specified type and content with both readers, returning the errors.
+    fn read_invalid_record(record_type: u8, content: &[u8]) -> [Error; 2] {
+        let mut module = b"SAILAR".to_vec();
+        module.extend_from_slice(&[
+            versioning::SupportedFormat::CURRENT.major,
+            versioning::SupportedFormat::CURRENT.minor,
+            1, // Number of records
+            record_type,
+            content.len() as u8,
+        ]);
+        module.extend_from_slice(content);
+
+        let (_, mut stream_records) = Reader::new(module.as_slice()).to_record_reader().unwrap();
+        let (_, mut slice_records) = SliceReader::new(&module).to_record_reader().unwrap();
+        [
+            stream_records.next_record().unwrap().unwrap_err(),
+            slice_records.next_record().unwrap().unwrap_err(),
+        ]
+    }
+
+    #[test]
+    fn unsupported_records_are_errors() {
+        for error in read_invalid_record(14, &[0, 1]) {
+            assert!(matches!(error.kind(), ErrorKind::UnsupportedGenericArguments));
+        }
+
+        for error in read_invalid_record(1, &[1, 0]) {
+            assert!(matches!(error.kind(), ErrorKind::NestedArrayRecord));
+        }
+
+        for error in read_invalid_record(0, &[3, b'a', b'b', b'c']) {
+            assert!(matches!(error.kind(), ErrorKind::UnknownMetadataField(name) if name.as_str() == "abc"));
+        }
+    }
 }
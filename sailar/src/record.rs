@@ -73,16 +73,25 @@ impl<'data> Export<'data> {
             None => Ok(VarU28::from_u8(0)),
             Some(symbol) => {
                 let length = symbol.len();
-                match VarU28::try_from(length) {
-                    Ok(bits) => {
-                        let flag = VarU28::from_u8(if self.kind() == ExportKind::Export { 1 } else { 0 });
-                        Ok(flag | bits)
-                    }
-                    Err(_) => Err(SymbolEncodingError { length }),
-                }
+                let flag = if self.kind() == ExportKind::Export { 1 } else { 0 };
+                length
+                    .checked_mul(2)
+                    .and_then(|bits| VarU28::try_from(bits | flag).ok())
+                    .ok_or(SymbolEncodingError { length })
             }
         }
     }
+
+    /// Copies any borrowed symbol, returning an [`Export`] that owns its data.
+    pub fn into_owned(self) -> Export<'static> {
+        match self {
+            Self::Hidden => Export::Hidden,
+            Self::PrivateBorrowed(symbol) => Export::PrivateOwned(symbol.to_identifier()),
+            Self::PrivateOwned(symbol) => Export::PrivateOwned(symbol),
+            Self::ExportBorrowed(symbol) => Export::ExportOwned(symbol.to_identifier()),
+            Self::ExportOwned(symbol) => Export::ExportOwned(symbol),
+        }
+    }
 }
 
 impl Default for Export<'_> {
@@ -121,6 +130,10 @@ impl<'a> ModuleIdentifier<'a> {
     pub fn version(&self) -> &[VarU28] {
         &self.version
     }
+
+    pub fn into_owned(self) -> ModuleIdentifier<'static> {
+        ModuleIdentifier::new(Cow::Owned(self.name.into_owned()), CowBox::Boxed(self.version.into_boxed()))
+    }
 }
 
 /// Contains information describing the module.
@@ -130,6 +143,8 @@ pub enum MetadataField<'a> {
     ModuleIdentifier(ModuleIdentifier<'a>),
     /// Specifies the entry point function of the module.
     EntryPoint(index::Function),
+    /// Indicates that calls to a function should never be inlined.
+    NoInline(index::Function),
 }
 
 impl MetadataField<'_> {
@@ -137,18 +152,27 @@ impl MetadataField<'_> {
         let name = match self {
             Self::ModuleIdentifier(_) => "id",
             Self::EntryPoint(_) => "main",
+            Self::NoInline(_) => "noinline",
         };
 
         // Safety: all above names are assumed to be valid.
         unsafe { Id::from_str_unchecked(name) }
     }
+
+    pub fn into_owned(self) -> MetadataField<'static> {
+        match self {
+            Self::ModuleIdentifier(identifier) => MetadataField::ModuleIdentifier(identifier.into_owned()),
+            Self::EntryPoint(entry_point) => MetadataField::EntryPoint(entry_point),
+            Self::NoInline(function) => MetadataField::NoInline(function),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub struct CodeBlock<'a> {
     /// Contains the types of all input registers, results, and temporary registers in that order.
-    pub register_types: CowBox<'a, [index::TypeSignature]>,
+    pub register_types: signature::TypeList<'a>,
     pub input_count: usize,
     pub result_count: usize,
     pub instructions: CowBox<'a, [instruction::Instruction]>,
@@ -162,7 +186,7 @@ impl<'a> CodeBlock<'a> {
     ///
     /// Panics if the number of input and result registers exceeds the total number of register types.
     pub fn from_types(
-        register_types: CowBox<'a, [index::TypeSignature]>,
+        register_types: signature::TypeList<'a>,
         input_count: usize,
         result_count: usize,
         instructions: CowBox<'a, [instruction::Instruction]>,
@@ -179,14 +203,14 @@ impl<'a> CodeBlock<'a> {
 
     pub fn new<A, R, T, I>(input_types: A, result_types: R, temporary_types: T, instructions: I) -> Self
     where
-        A: Into<CowBox<'a, [index::TypeSignature]>>,
-        R: Into<CowBox<'a, [index::TypeSignature]>>,
-        T: Into<CowBox<'a, [index::TypeSignature]>>,
+        A: Into<signature::TypeList<'a>>,
+        R: Into<signature::TypeList<'a>>,
+        T: Into<signature::TypeList<'a>>,
         I: Into<CowBox<'a, [instruction::Instruction]>>,
     {
-        let input_types: CowBox<'a, [_]> = input_types.into();
-        let result_types: CowBox<'a, [_]> = result_types.into();
-        let temporary_types: CowBox<'a, [_]> = temporary_types.into();
+        let input_types: signature::TypeList<'a> = input_types.into();
+        let result_types: signature::TypeList<'a> = result_types.into();
+        let temporary_types: signature::TypeList<'a> = temporary_types.into();
         let input_count = input_types.len();
         let result_count = result_types.len();
         let register_types = {
@@ -197,14 +221,8 @@ impl<'a> CodeBlock<'a> {
             } else if input_types.is_empty() && result_types.is_empty() {
                 temporary_types
             } else {
-                CowBox::Boxed(
-                    input_types
-                        .iter()
-                        .copied()
-                        .chain(result_types.iter().copied())
-                        .chain(temporary_types.iter().copied())
-                        .collect(),
-                )
+                let register_types = input_types.iter().chain(&result_types).chain(&temporary_types);
+                signature::TypeList::from(register_types.collect::<Box<[_]>>())
             }
         };
 
@@ -220,23 +238,34 @@ impl<'a> CodeBlock<'a> {
         self.register_types.len() - self.result_count
     }
 
-    pub fn input_types(&self) -> &[index::TypeSignature] {
-        &self.register_types[0..self.input_count]
+    pub fn input_types(&self) -> signature::TypeList<'_> {
+        self.register_types.sublist(0..self.input_count)
     }
 
     /// The types of the results of this [`CodeBlock`]. These are the types of the values that are expected to be used in the
     /// block's `ret` instruction, and should be empty if the block branches to another block instead.
-    pub fn result_types(&self) -> &[index::TypeSignature] {
-        &self.register_types[self.input_count..self.input_count + self.result_count]
+    pub fn result_types(&self) -> signature::TypeList<'_> {
+        self.register_types
+            .sublist(self.input_count..self.input_count + self.result_count)
     }
 
-    pub fn temporary_types(&self) -> &[index::TypeSignature] {
-        &self.register_types[self.input_count + self.result_count..]
+    pub fn temporary_types(&self) -> signature::TypeList<'_> {
+        self.register_types
+            .sublist(self.input_count + self.result_count..self.register_types.len())
     }
 
-    pub fn to_function_signature(&self) -> signature::Function {
+    pub fn to_function_signature(&self) -> signature::Function<'_> {
         signature::Function::new(self.input_types(), self.result_types())
     }
+
+    pub fn into_owned(self) -> CodeBlock<'static> {
+        CodeBlock {
+            register_types: self.register_types.into_owned(),
+            input_count: self.input_count,
+            result_count: self.result_count,
+            instructions: CowBox::Boxed(self.instructions.into_boxed()),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -273,6 +302,31 @@ impl<'data> FunctionTemplate<'data> {
             entry_block,
         }
     }
+
+    pub fn into_owned(self) -> FunctionTemplate<'static> {
+        FunctionTemplate::new(self.export.into_owned(), self.signature, self.entry_block)
+    }
+}
+
+/// A function template defined in another module, which is referred to by its symbol.
+///
+/// Function template indices refer to imports first, so the index of the first function template definition is the number
+/// of function imports in the module.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FunctionImport<'data> {
+    pub symbol: Cow<'data, Id>,
+    pub signature: index::FunctionSignature,
+}
+
+impl<'data> FunctionImport<'data> {
+    pub fn new(symbol: Cow<'data, Id>, signature: index::FunctionSignature) -> Self {
+        Self { symbol, signature }
+    }
+
+    pub fn into_owned(self) -> FunctionImport<'static> {
+        FunctionImport::new(Cow::Owned(self.symbol.into_owned()), self.signature)
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -289,6 +343,10 @@ impl<'data> Function<'data> {
             _placeholder: &(),
         }
     }
+
+    pub fn into_owned(self) -> Function<'static> {
+        Function::with_template(self.template)
+    }
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -343,11 +401,11 @@ record_types!(for<'data> {
     //Array = 1,
     Identifier(_identifier: Cow<'data, Id>,) = 2,
     TypeSignature(_signature: signature::Type,) = 3,
-    FunctionSignature(_signature: signature::Function,) = 4,
+    FunctionSignature(_signature: signature::Function<'data>,) = 4,
     Data(_bytes: Cow<'data, [u8]>,) = 5,
     CodeBlock(_code: CodeBlock<'data>,) = 6,
     //ModuleImport = 7,
-    //FunctionImport = 8,
+    FunctionImport(_import: FunctionImport<'data>,) = 8,
     //StructureImport = 9,
     //GlobalImport = 10,
     FunctionTemplate(_template: FunctionTemplate<'data>,) = 11,
@@ -363,6 +421,23 @@ record_types!(for<'data> {
     //DebuggingInformation = 21,
 });
 
+impl Record<'_> {
+    /// Copies any borrowed identifiers, byte arrays, or instructions, returning a [`Record`] that owns all of its data.
+    pub fn into_owned(self) -> Record<'static> {
+        match self {
+            Self::MetadataField(field) => Record::MetadataField(field.into_owned()),
+            Self::Identifier(identifier) => Record::Identifier(Cow::Owned(identifier.into_owned())),
+            Self::TypeSignature(signature) => Record::TypeSignature(signature),
+            Self::FunctionSignature(signature) => Record::FunctionSignature(signature.into_owned()),
+            Self::Data(bytes) => Record::Data(Cow::Owned(bytes.into_owned())),
+            Self::CodeBlock(block) => Record::CodeBlock(block.into_owned()),
+            Self::FunctionImport(import) => Record::FunctionImport(import.into_owned()),
+            Self::FunctionTemplate(template) => Record::FunctionTemplate(template.into_owned()),
+            Self::Function(function) => Record::Function(function.into_owned()),
+        }
+    }
+}
+
 impl From<Type> for u8 {
     fn from(value: Type) -> u8 {
         value as u8
@@ -389,9 +464,9 @@ impl From<signature::Type> for Record<'_> {
     }
 }
 
-impl From<signature::Function> for Record<'_> {
+impl<'data> From<signature::Function<'data>> for Record<'data> {
     #[inline]
-    fn from(signature: signature::Function) -> Self {
+    fn from(signature: signature::Function<'data>) -> Self {
         Self::FunctionSignature(signature)
     }
 }
@@ -403,6 +478,12 @@ impl<'data> From<CodeBlock<'data>> for Record<'data> {
     }
 }
 
+impl<'data> From<FunctionImport<'data>> for Record<'data> {
+    fn from(import: FunctionImport<'data>) -> Self {
+        Self::FunctionImport(import)
+    }
+}
+
 impl<'data> From<FunctionTemplate<'data>> for Record<'data> {
     fn from(template: FunctionTemplate<'data>) -> Self {
         Self::FunctionTemplate(template)
@@ -52,6 +52,8 @@ type_code! {
     RawPtr = 0xCA,
     VoidPtr = 0xCC, // TODO: Replace void pointer with u8 pointer?
     FuncPtr = 0xCF,
+    /// A fixed-length vector of integers or floating point numbers.
+    Vector = 0xE0,
     F32 = 0xF4,
     F64 = 0xF8,
 }
@@ -69,22 +71,241 @@ pub struct InvalidTypeCode {
     value: u8,
 }
 
+/// A list of type signature indices.
+///
+/// Lists read from a module are borrowed in their encoded form, with each index being decoded as the list is iterated.
+#[derive(Clone)]
+pub struct TypeList<'data>(TypeListContents<'data>);
+
+#[derive(Clone)]
+enum TypeListContents<'data> {
+    Borrowed(&'data [index::TypeSignature]),
+    Boxed(Box<[index::TypeSignature]>),
+    /// Contains `count` variable-length integers, which were checked to be valid indices when the list was read.
+    Encoded {
+        count: u32,
+        bytes: &'data [u8],
+    },
+}
+
+/// Skips over the specified number of variable-length integers, which are assumed to be valid.
+fn skip_encoded_indices(bytes: &mut &[u8], count: usize) {
+    for _ in 0..count {
+        let length = bytes[0].leading_ones() as usize + 1;
+        *bytes = &bytes[length..];
+    }
+}
+
+impl<'data> TypeList<'data> {
+    /// Creates a list from a sequence of `count` variable-length integers, which must already have been checked to be valid
+    /// type signature indices.
+    pub(crate) fn from_encoded(bytes: &'data [u8], count: usize) -> Self {
+        Self(TypeListContents::Encoded {
+            count: u32::try_from(count).expect("count of variable-length integers should fit in 32 bits"),
+            bytes,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.0 {
+            TypeListContents::Borrowed(types) => types.len(),
+            TypeListContents::Boxed(types) => types.len(),
+            TypeListContents::Encoded { count, .. } => *count as usize,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn iter(&self) -> TypeListIter<'_> {
+        TypeListIter(match &self.0 {
+            TypeListContents::Borrowed(types) => TypeListIterContents::Decoded(types.iter()),
+            TypeListContents::Boxed(types) => TypeListIterContents::Decoded(types.iter()),
+            TypeListContents::Encoded { count, bytes } => TypeListIterContents::Encoded {
+                bytes,
+                remaining: *count as usize,
+            },
+        })
+    }
+
+    /// Gets the type signature index at the specified position in the list.
+    ///
+    /// For lists borrowed from a module, this decodes all of the indices that come before it.
+    pub fn get(&self, index: usize) -> Option<index::TypeSignature> {
+        match &self.0 {
+            TypeListContents::Borrowed(types) => types.get(index).copied(),
+            TypeListContents::Boxed(types) => types.get(index).copied(),
+            TypeListContents::Encoded { .. } => self.iter().nth(index),
+        }
+    }
+
+    /// Gets a portion of this list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `range` is out of bounds.
+    pub fn sublist(&self, range: std::ops::Range<usize>) -> TypeList<'_> {
+        assert!(range.start <= range.end && range.end <= self.len());
+
+        TypeList(match &self.0 {
+            TypeListContents::Borrowed(types) => TypeListContents::Borrowed(&types[range]),
+            TypeListContents::Boxed(types) => TypeListContents::Borrowed(&types[range]),
+            TypeListContents::Encoded { bytes, .. } => {
+                let mut start = *bytes;
+                skip_encoded_indices(&mut start, range.start);
+                let mut end = start;
+                skip_encoded_indices(&mut end, range.len());
+                TypeListContents::Encoded {
+                    count: range.len() as u32,
+                    bytes: &start[..start.len() - end.len()],
+                }
+            }
+        })
+    }
+
+    /// Gets the encoded indices if they are borrowed from a module.
+    #[cfg(test)]
+    pub(crate) fn encoded_bytes(&self) -> Option<&'data [u8]> {
+        match self.0 {
+            TypeListContents::Encoded { bytes, .. } => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn into_boxed_slice(self) -> Box<[index::TypeSignature]> {
+        match self.0 {
+            TypeListContents::Boxed(types) => types,
+            TypeListContents::Borrowed(types) => Box::from(types),
+            encoded @ TypeListContents::Encoded { .. } => TypeList(encoded).iter().collect(),
+        }
+    }
+
+    /// Decodes or copies the indices if they are borrowed, returning a [`TypeList`] that owns all of its indices.
+    pub fn into_owned(self) -> TypeList<'static> {
+        TypeList(TypeListContents::Boxed(self.into_boxed_slice()))
+    }
+}
+
+impl Default for TypeList<'_> {
+    fn default() -> Self {
+        Self(TypeListContents::Borrowed(&[]))
+    }
+}
+
+impl<'data> From<CowBox<'data, [index::TypeSignature]>> for TypeList<'data> {
+    fn from(types: CowBox<'data, [index::TypeSignature]>) -> Self {
+        Self(match types {
+            CowBox::Borrowed(borrowed) => TypeListContents::Borrowed(borrowed),
+            CowBox::Boxed(boxed) => TypeListContents::Boxed(boxed),
+        })
+    }
+}
+
+impl<'data> From<&'data [index::TypeSignature]> for TypeList<'data> {
+    fn from(types: &'data [index::TypeSignature]) -> Self {
+        Self(TypeListContents::Borrowed(types))
+    }
+}
+
+impl From<Box<[index::TypeSignature]>> for TypeList<'_> {
+    fn from(types: Box<[index::TypeSignature]>) -> Self {
+        Self(TypeListContents::Boxed(types))
+    }
+}
+
+impl From<Vec<index::TypeSignature>> for TypeList<'_> {
+    fn from(types: Vec<index::TypeSignature>) -> Self {
+        Self::from(types.into_boxed_slice())
+    }
+}
+
+impl PartialEq for TypeList<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.iter().eq(other.iter())
+    }
+}
+
+impl Eq for TypeList<'_> {}
+
+impl Debug for TypeList<'_> {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<'a> IntoIterator for &'a TypeList<'_> {
+    type Item = index::TypeSignature;
+    type IntoIter = TypeListIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the indices in a [`TypeList`].
+#[derive(Clone, Debug)]
+pub struct TypeListIter<'a>(TypeListIterContents<'a>);
+
+#[derive(Clone, Debug)]
+enum TypeListIterContents<'a> {
+    Decoded(std::slice::Iter<'a, index::TypeSignature>),
+    Encoded { bytes: &'a [u8], remaining: usize },
+}
+
+impl Iterator for TypeListIter<'_> {
+    type Item = index::TypeSignature;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            TypeListIterContents::Decoded(types) => types.next().copied(),
+            TypeListIterContents::Encoded { remaining: 0, .. } => None,
+            TypeListIterContents::Encoded { bytes, remaining } => {
+                *remaining -= 1;
+                let index = num::VarU28::read_from(bytes)
+                    .ok()
+                    .and_then(Result::ok)
+                    .and_then(|index| index::TypeSignature::try_from(index).ok());
+
+                // The indices were validated when the list was read.
+                Some(index.expect("type list should only contain valid indices"))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let length = self.len();
+        (length, Some(length))
+    }
+}
+
+impl ExactSizeIterator for TypeListIter<'_> {
+    fn len(&self) -> usize {
+        match &self.0 {
+            TypeListIterContents::Decoded(types) => types.len(),
+            TypeListIterContents::Encoded { remaining, .. } => *remaining,
+        }
+    }
+}
+
+impl std::iter::FusedIterator for TypeListIter<'_> {}
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[non_exhaustive]
-pub struct Function {
-    pub types: Box<[index::TypeSignature]>,
+pub struct Function<'data> {
+    pub types: TypeList<'data>,
     pub return_type_count: usize,
 }
 
-impl Function {
-    /// Creates a function signature from a boxed slice of type signature indices, and a specified number of return types.
+impl<'data> Function<'data> {
+    /// Creates a function signature from a list of type signature indices, and a specified number of return types.
     ///
     /// The return types come first, followed by an inferred number of parameter types.
     ///
     /// # Panics
     ///
     /// Panics if the number of return types exceeds the total number of types.
-    pub fn from_boxed_slice(types: Box<[index::TypeSignature]>, return_type_count: usize) -> Self {
+    pub fn from_types(types: TypeList<'data>, return_type_count: usize) -> Self {
         assert!(return_type_count <= types.len());
 
         Self {
@@ -93,27 +314,27 @@ impl Function {
         }
     }
 
-    pub fn new<'a, P, R>(parameter_types: P, return_types: R) -> Self
+    pub fn new<P, R>(parameter_types: P, return_types: R) -> Self
     where
-        P: Into<CowBox<'a, [index::TypeSignature]>>,
-        R: Into<CowBox<'a, [index::TypeSignature]>>,
+        P: Into<TypeList<'data>>,
+        R: Into<TypeList<'data>>,
     {
-        let parameter_types: CowBox<'a, _> = Into::into(parameter_types);
-        let return_types: CowBox<'a, [index::TypeSignature]> = Into::into(return_types);
+        let parameter_types: TypeList<'data> = parameter_types.into();
+        let return_types: TypeList<'data> = return_types.into();
         let return_type_count = return_types.len();
         let types = if parameter_types.is_empty() {
-            return_types.into_boxed()
+            return_types
         } else if return_types.is_empty() {
-            parameter_types.into_boxed()
+            parameter_types
         } else {
-            return_types.iter().copied().chain(parameter_types.iter().copied()).collect()
+            TypeList::from(return_types.iter().chain(parameter_types.iter()).collect::<Box<[_]>>())
         };
 
-        Self::from_boxed_slice(types, return_type_count)
+        Self::from_types(types, return_type_count)
     }
 
     /// The function signature's return types followed by the parameter types.
-    pub fn types(&self) -> &[index::TypeSignature] {
+    pub fn types(&self) -> &TypeList<'data> {
         &self.types
     }
 
@@ -121,12 +342,19 @@ impl Function {
         self.return_type_count
     }
 
-    pub fn return_types(&self) -> &[index::TypeSignature] {
-        &self.types[0..self.return_type_count]
+    pub fn return_types(&self) -> TypeList<'_> {
+        self.types.sublist(0..self.return_type_count)
     }
 
-    pub fn parameter_types(&self) -> &[index::TypeSignature] {
-        &self.types[self.return_type_count..]
+    pub fn parameter_types(&self) -> TypeList<'_> {
+        self.types.sublist(self.return_type_count..self.types.len())
+    }
+
+    pub fn into_owned(self) -> Function<'static> {
+        Function {
+            types: self.types.into_owned(),
+            return_type_count: self.return_type_count,
+        }
     }
 }
 
@@ -344,8 +572,97 @@ impl Display for IntegerType {
     }
 }
 
+/// The type of each lane of a vector type.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub enum VectorElementType {
+    Integer(IntegerType),
+    F32,
+    F64,
+}
+
+impl VectorElementType {
+    pub fn is_integer(self) -> bool {
+        matches!(self, Self::Integer(_))
+    }
+}
+
+impl From<IntegerType> for VectorElementType {
+    fn from(ty: IntegerType) -> Self {
+        Self::Integer(ty)
+    }
+}
+
+impl TryFrom<&Type> for VectorElementType {
+    type Error = InvalidVectorElementTypeError;
+
+    fn try_from(ty: &Type) -> Result<Self, Self::Error> {
+        match ty {
+            Type::FixedInteger(integer_type) => Ok(Self::Integer(*integer_type)),
+            Type::F32 => Ok(Self::F32),
+            Type::F64 => Ok(Self::F64),
+            _ => Err(InvalidVectorElementTypeError(ty.clone())),
+        }
+    }
+}
+
+impl Display for VectorElementType {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        match self {
+            Self::Integer(integer_type) => Display::fmt(integer_type, f),
+            Self::F32 => f.write_str("f32"),
+            Self::F64 => f.write_str("f64"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{0} is not a valid vector element type, only fixed integer and floating point types are allowed")]
+pub struct InvalidVectorElementTypeError(Type);
+
+/// Represents a SIMD vector type, containing a fixed number of lanes that all have the same element type.
+///
+/// # Examples
+///
+/// ```
+/// # use sailar::signature::{IntegerType, VectorElementType, VectorType};
+/// # use std::num::NonZeroU8;
+/// assert_eq!(VectorType::new(VectorElementType::F32, NonZeroU8::new(4).unwrap()).to_string(), "v4xf32");
+/// assert_eq!(VectorType::new(IntegerType::U8.into(), NonZeroU8::new(16).unwrap()).to_string(), "v16xu8");
+/// ```
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct VectorType {
+    element_type: VectorElementType,
+    lane_count: std::num::NonZeroU8,
+}
+
+impl VectorType {
+    pub const fn new(element_type: VectorElementType, lane_count: std::num::NonZeroU8) -> Self {
+        Self {
+            element_type,
+            lane_count,
+        }
+    }
+
+    #[inline]
+    pub const fn element_type(self) -> VectorElementType {
+        self.element_type
+    }
+
+    /// Gets the number of elements in the vector.
+    #[inline]
+    pub const fn lane_count(self) -> std::num::NonZeroU8 {
+        self.lane_count
+    }
+}
+
+impl Display for VectorType {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "v{}x{}", self.lane_count, self.element_type)
+    }
+}
+
 /// Represents a type signature.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Type {
     /// An integer type with a fixed size.
     FixedInteger(IntegerType),
@@ -360,12 +677,38 @@ pub enum Type {
     RawPtr(Option<index::TypeSignature>),
     /// Represents a pointer to a function.
     FuncPtr(index::FunctionSignature),
+    /// A fixed-length vector of integers or floating point numbers, used for SIMD operations.
+    Vector(VectorType),
 }
 
 impl Type {
     pub fn is_integer(&self) -> bool {
         matches!(self, Self::FixedInteger(_) | Self::UAddr | Self::SAddr)
     }
+
+    /// Gets the vector type, if this type is a vector type.
+    pub fn as_vector(&self) -> Option<&VectorType> {
+        match self {
+            Self::Vector(vector_type) => Some(vector_type),
+            _ => None,
+        }
+    }
+}
+
+impl From<VectorType> for Type {
+    fn from(ty: VectorType) -> Self {
+        Self::Vector(ty)
+    }
+}
+
+impl From<VectorElementType> for Type {
+    fn from(ty: VectorElementType) -> Self {
+        match ty {
+            VectorElementType::Integer(integer_type) => Self::FixedInteger(integer_type),
+            VectorElementType::F32 => Self::F32,
+            VectorElementType::F64 => Self::F64,
+        }
+    }
 }
 
 impl From<IntegerType> for Type {
@@ -385,6 +728,7 @@ impl Display for Type {
             Self::RawPtr(None) => f.write_str("voidptr"),
             Self::RawPtr(Some(pointee)) => write!(f, "rawptr {}", pointee),
             Self::FuncPtr(signature) => write!(f, "funcptr {}", signature),
+            Self::Vector(vector_type) => Display::fmt(vector_type, f),
         }
     }
 }
@@ -89,6 +89,12 @@ pub enum InvalidInstructionKind {
     InvalidIndex(#[from] InvalidIndexError),
     #[error("attempted to define more than {count} temporary registers")]
     ExtraneousTemporary { count: usize },
+    /// Used when a register is used before an instruction defines it, or by the instruction that defines it.
+    #[error("register {register} is not defined yet, only {defined_count} registers are defined before this instruction")]
+    UndefinedRegister {
+        register: index::Register,
+        defined_count: usize,
+    },
     #[error("expected result register (register {register}) to be an integer type, but got {actual_type}")]
     ExpectedIntegerResult {
         register: index::Register,
@@ -98,6 +104,39 @@ pub enum InvalidInstructionKind {
     ExpectedTypeForValue(#[from] ValueTypeMismatchError),
     #[error("expected {expected} values, but got {actual}")]
     ValueCountMismatch { expected: usize, actual: usize },
+    #[error("expected value {value} to be an integer, but got {actual_type}")]
+    ExpectedIntegerValue {
+        value: instruction::Value,
+        actual_type: signature::Type,
+    },
+    #[error("expected result register (register {register}) to be of type {expected_type}, but got {actual_type}")]
+    ResultTypeMismatch {
+        register: index::Register,
+        expected_type: signature::Type,
+        actual_type: signature::Type,
+    },
+    #[error("expected value {value} to be a raw pointer to an integer")]
+    ExpectedIntegerPointer { value: instruction::Value },
+    #[error("atomic operations on value {value} are not supported, only 8, 16, 32, or 64-bit integers can be accessed atomically but got {pointee_type}")]
+    UnsupportedAtomicType {
+        value: instruction::Value,
+        pointee_type: signature::Type,
+    },
+    #[error("the memory ordering {ordering} cannot be used with this instruction")]
+    InvalidMemoryOrdering { ordering: instruction::MemoryOrdering },
+    #[error("expected value {value} to be a vector")]
+    ExpectedVectorValue { value: instruction::Value },
+    #[error("expected result register (register {register}) to be a vector type, but got {actual_type}")]
+    ExpectedVectorResult {
+        register: index::Register,
+        actual_type: signature::Type,
+    },
+    #[error("lane index {lane} is out of range, only {lane_count} lanes are available")]
+    VectorLaneOutOfRange { lane: u16, lane_count: u16 },
+    #[error("vector shuffle specified {count} lanes, but vectors must have between 1 and 255 lanes")]
+    InvalidShuffleLaneCount { count: usize },
+    #[error("overflow flags cannot be produced by vector arithmetic")]
+    VectorOverflowFlag,
 }
 
 /// The error type used when a SAILAR instruction is invalid.
@@ -111,6 +150,12 @@ pub struct InvalidInstructionError {
     kind: InvalidInstructionKind,
 }
 
+impl InvalidInstructionError {
+    pub fn kind(&self) -> &InvalidInstructionKind {
+        &self.kind
+    }
+}
+
 impl Display for InvalidInstructionError {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         write!(
@@ -155,6 +200,15 @@ pub enum ErrorKind {
     TypeSignatureCycle(index::TypeSignature),
     #[error("code block {0} must contain at least one instruction")]
     EmptyCodeBlock(index::CodeBlock),
+    /// Used when the instructions of a code block define fewer temporary registers than were declared.
+    #[error(
+        "code block {code_block} declares {declared} temporary registers, but only {defined} are defined by its instructions"
+    )]
+    UndefinedTemporaries {
+        code_block: index::CodeBlock,
+        declared: usize,
+        defined: usize,
+    },
     #[error(transparent)]
     InvalidInstruction(#[from] InvalidInstructionError),
     #[error(transparent)]
@@ -177,6 +231,10 @@ impl Error {
     pub fn from_kind<E: Into<ErrorKind>>(kind: E) -> Self {
         Self(Box::new(kind.into()))
     }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.0
+    }
 }
 
 impl<E: Into<ErrorKind>> From<E> for Error {
@@ -185,6 +243,154 @@ impl<E: Into<ErrorKind>> From<E> for Error {
     }
 }
 
+/// Indicates where in a SAILAR module a validation error occured.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ErrorLocation {
+    record: usize,
+    code_block: Option<index::CodeBlock>,
+    instruction: Option<usize>,
+}
+
+impl ErrorLocation {
+    /// The position of the record in the module, where each element of an array record is counted separately.
+    pub fn record(&self) -> usize {
+        self.record
+    }
+
+    /// The code block that the error occured in, if the error was in a code block record.
+    pub fn code_block(&self) -> Option<index::CodeBlock> {
+        self.code_block
+    }
+
+    /// The index of the instruction in the code block that the error occured in.
+    pub fn instruction(&self) -> Option<usize> {
+        self.instruction
+    }
+}
+
+impl Display for ErrorLocation {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        write!(f, "record {}", self.record)?;
+        if let Some(code_block) = self.code_block {
+            write!(f, ", code block {}", code_block)?;
+        }
+        if let Some(instruction) = self.instruction {
+            write!(f, ", instruction {}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+/// A validation error, along with the location in the module where it occured.
+///
+/// Returned when all errors in a module are collected, see [`ValidModule::from_records_collecting_errors`].
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{location}: {kind}")]
+pub struct LocatedError {
+    location: ErrorLocation,
+    kind: ErrorKind,
+}
+
+impl LocatedError {
+    pub fn location(&self) -> &ErrorLocation {
+        &self.location
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    pub fn into_kind(self) -> ErrorKind {
+        self.kind
+    }
+}
+
+/// Indicates which record an error originated from, where the `index` only counts records of the same type.
+#[derive(Clone, Copy, Debug)]
+struct Origin {
+    record_type: record::Type,
+    index: usize,
+    instruction: Option<usize>,
+}
+
+impl Origin {
+    fn record(record_type: record::Type, index: usize) -> Self {
+        Self {
+            record_type,
+            index,
+            instruction: None,
+        }
+    }
+
+    fn instruction(code_block: index::CodeBlock, instruction: usize) -> Self {
+        Self {
+            record_type: record::Type::CodeBlock,
+            index: usize::from(code_block),
+            instruction: Some(instruction),
+        }
+    }
+}
+
+/// Determines what happens when an error is encountered during validation.
+trait ErrorHandler {
+    /// Reports an error, returning it if validation should stop.
+    fn report(&mut self, origin: Origin, error: Error) -> Result<(), Error>;
+
+    fn has_errors(&self) -> bool;
+}
+
+/// Stops validation at the first error.
+struct FailFast;
+
+impl ErrorHandler for FailFast {
+    fn report(&mut self, _: Origin, error: Error) -> Result<(), Error> {
+        Err(error)
+    }
+
+    fn has_errors(&self) -> bool {
+        false
+    }
+}
+
+/// Collects every error, continuing validation with the next record.
+struct ErrorCollector {
+    /// The types of all records in the order that they appear in the module.
+    record_types: Vec<record::Type>,
+    errors: Vec<LocatedError>,
+}
+
+impl ErrorHandler for ErrorCollector {
+    fn report(&mut self, origin: Origin, error: Error) -> Result<(), Error> {
+        let record = self
+            .record_types
+            .iter()
+            .enumerate()
+            .filter(|(_, record_type)| **record_type == origin.record_type)
+            .nth(origin.index)
+            .map(|(position, _)| position)
+            .expect("errors should originate from records in the module");
+
+        self.errors.push(LocatedError {
+            location: ErrorLocation {
+                record,
+                code_block: if origin.record_type == record::Type::CodeBlock {
+                    Some(index::CodeBlock::from(origin.index))
+                } else {
+                    None
+                },
+                instruction: origin.instruction,
+            },
+            kind: *error.0,
+        });
+
+        Ok(())
+    }
+
+    fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+}
+
 /// Indicates which definitions in a module are exported.
 #[derive(Clone, Debug, Default)]
 #[non_exhaustive]
@@ -200,12 +406,16 @@ pub type ModuleIdentifierSet<'data> = rustc_hash::FxHashSet<record::ModuleIdenti
 pub struct ModuleContents<'data> {
     pub module_identifiers: ModuleIdentifierSet<'data>,
     pub entry_point: Option<index::Function>,
+    /// Functions whose calls should never be inlined, in the order that they were marked.
+    pub no_inline_functions: Vec<index::Function>,
     /// The list of all identifier records in the module.
     pub identifiers: Vec<Cow<'data, crate::identifier::Id>>,
     pub type_signatures: Vec<signature::Type>,
-    pub function_signatures: Vec<signature::Function>,
+    pub function_signatures: Vec<signature::Function<'data>>,
     pub data: Vec<Cow<'data, [u8]>>,
     pub code: Vec<record::CodeBlock<'data>>,
+    /// Function templates defined in other modules, which come before the function templates defined in this module.
+    pub function_imports: Vec<record::FunctionImport<'data>>,
     pub function_templates: Vec<record::FunctionTemplate<'data>>,
     pub functions: Vec<record::Function<'data>>,
 }
@@ -217,6 +427,96 @@ impl<'data> ModuleContents<'data> {
     pub fn is_anonymous(&self) -> bool {
         self.module_identifiers.is_empty()
     }
+
+    /// Gets the definition of the function template with the specified index, or `None` if the template is imported.
+    pub fn function_template_definition(&self, template: index::FunctionTemplate) -> Option<&record::FunctionTemplate<'data>> {
+        let index = usize::from(template).checked_sub(self.function_imports.len())?;
+        self.function_templates.get(index)
+    }
+
+    /// Gets the index of the function template definition at the specified position in the
+    /// [`function_templates`](ModuleContents::function_templates), which comes after any function imports.
+    pub fn function_template_index(&self, definition: usize) -> index::FunctionTemplate {
+        index::FunctionTemplate::from(self.function_imports.len() + definition)
+    }
+
+    /// Gets the signature of the imported or defined function template with the specified index.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the function template is not defined in or imported by this module.
+    pub fn function_template_signature(&self, template: index::FunctionTemplate) -> index::FunctionSignature {
+        match self.function_imports.get(usize::from(template)) {
+            Some(import) => import.signature,
+            None => self.function_templates[usize::from(template) - self.function_imports.len()].signature,
+        }
+    }
+
+    /// Gets the number of temporary registers defined by an instruction in this module.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the instruction refers to a function or function signature that is not defined in this module.
+    pub fn result_count(&self, instruction: &Instruction) -> usize {
+        let function_return_count =
+            |signature: index::FunctionSignature| self.function_signatures[usize::from(signature)].return_type_count;
+
+        match instruction {
+            Instruction::Nop
+            | Instruction::Break
+            | Instruction::Return(_)
+            | Instruction::Br(_, _)
+            | Instruction::BrIf(_)
+            | Instruction::AtomicStore(_)
+            | Instruction::Fence(_) => 0,
+            Instruction::IAdd(arithmetic) | Instruction::ISub(arithmetic) => match arithmetic.overflow_behavior() {
+                instruction::OverflowBehavior::Flag => 2,
+                instruction::OverflowBehavior::Ignore | instruction::OverflowBehavior::Saturate => 1,
+            },
+            Instruction::Call(callee, _) => {
+                let template = self.functions[usize::from(*callee)].template;
+                function_return_count(self.function_template_signature(template))
+            }
+            Instruction::ThreadJoin(signature, _) => function_return_count(*signature),
+            Instruction::AtomicCmpXchg(_) => 2,
+            Instruction::Cmp(_)
+            | Instruction::ThreadSpawn(_, _)
+            | Instruction::AtomicLoad(_)
+            | Instruction::AtomicRmw(_)
+            | Instruction::VSplat(_)
+            | Instruction::VExtract(_, _)
+            | Instruction::VInsert(_)
+            | Instruction::VShuffle(_) => 1,
+        }
+    }
+
+    /// Converts the contents back into records, which can be validated again or written to a module.
+    pub fn into_records(self) -> Vec<Record<'data>> {
+        let mut records = Vec::new();
+        records.extend(
+            self.module_identifiers
+                .into_iter()
+                .map(|identifier| Record::from(record::MetadataField::ModuleIdentifier(identifier))),
+        );
+        records.extend(
+            self.entry_point
+                .map(|entry_point| Record::from(record::MetadataField::EntryPoint(entry_point))),
+        );
+        records.extend(
+            self.no_inline_functions
+                .into_iter()
+                .map(|function| Record::from(record::MetadataField::NoInline(function))),
+        );
+        records.extend(self.identifiers.into_iter().map(Record::Identifier));
+        records.extend(self.type_signatures.into_iter().map(Record::from));
+        records.extend(self.function_signatures.into_iter().map(Record::from));
+        records.extend(self.data.into_iter().map(Record::Data));
+        records.extend(self.code.into_iter().map(Record::from));
+        records.extend(self.function_imports.into_iter().map(Record::from));
+        records.extend(self.function_templates.into_iter().map(Record::from));
+        records.extend(self.functions.into_iter().map(Record::from));
+        records
+    }
 }
 
 /// Represents a validated SAILAR module.
@@ -234,22 +534,42 @@ impl<'data> ValidModule<'data> {
         Self { contents, exports }
     }
 
-    fn validate(mut contents: ModuleContents<'data>, metadata_fields: Vec<record::MetadataField<'data>>) -> Result<Self, Error> {
-        fn get_index_validator<I: index::Index>(length: usize) -> impl Fn(I) -> Result<usize, Error> {
-            move |index: I| {
-                let index = index.into();
-                if index < length {
-                    Ok(index)
-                } else {
-                    Err(InvalidIndexError {
-                        index,
-                        maximum_index: if length == 0 { None } else { Some(length - 1) },
-                        name: I::name(),
-                    })?
+    /// Validates the module, returning `None` if any errors were collected by the `handler`.
+    fn validate<H: ErrorHandler>(
+        mut contents: ModuleContents<'data>,
+        metadata_fields: Vec<record::MetadataField<'data>>,
+        handler: &mut H,
+    ) -> Result<Option<Self>, Error> {
+        /// Reports the error, then skips the rest of the current loop iteration.
+        macro_rules! try_or_continue {
+            ($result:expr, $origin:expr) => {
+                match $result {
+                    Ok(value) => value,
+                    Err(error) => {
+                        handler.report($origin, Error::from(error))?;
+                        continue;
+                    }
                 }
+            };
+        }
+
+        fn check_index<I: index::Index>(index: I, length: usize) -> Result<usize, InvalidIndexError> {
+            let index = index.into();
+            if index < length {
+                Ok(index)
+            } else {
+                Err(InvalidIndexError {
+                    index,
+                    maximum_index: if length == 0 { None } else { Some(length - 1) },
+                    name: I::name(),
+                })
             }
         }
 
+        fn get_index_validator<I: index::Index>(length: usize) -> impl Fn(I) -> Result<usize, Error> {
+            move |index: I| Ok(check_index(index, length)?)
+        }
+
         let check_type_signature_index = get_index_validator(contents.type_signatures.len());
         let check_function_signature_index = get_index_validator(contents.function_signatures.len());
 
@@ -268,19 +588,25 @@ impl<'data> ValidModule<'data> {
                     | signature::Type::F64
                     | signature::Type::SAddr
                     | signature::Type::UAddr
-                    | signature::Type::RawPtr(None) => (),
+                    | signature::Type::RawPtr(None)
+                    | signature::Type::Vector(_) => (),
                     signature::Type::RawPtr(Some(pointee)) => {
-                        check_type_signature_index(*pointee)?;
+                        try_or_continue!(
+                            check_type_signature_index(*pointee),
+                            Origin::record(record::Type::TypeSignature, i)
+                        );
                         type_reference_lookup.entry(*pointee).or_default().insert(current_index);
                     }
                     signature::Type::FuncPtr(signature) => {
-                        check_function_signature_index(*signature)?;
+                        try_or_continue!(
+                            check_function_signature_index(*signature),
+                            Origin::record(record::Type::TypeSignature, i)
+                        );
 
                         // Recursive function signatures should be prevented here
                         contents.function_signatures[usize::from(*signature)]
                             .types()
                             .iter()
-                            .copied()
                             .for_each(|type_signature| {
                                 type_reference_lookup.entry(type_signature).or_default().insert(current_index);
                             });
@@ -297,7 +623,11 @@ impl<'data> ValidModule<'data> {
 
                 while let Some(referer) = type_referer_buffer.pop() {
                     if referer == *referent {
-                        return Err(ErrorKind::TypeSignatureCycle(referer))?;
+                        handler.report(
+                            Origin::record(record::Type::TypeSignature, usize::from(referer)),
+                            Error::from(ErrorKind::TypeSignatureCycle(referer)),
+                        )?;
+                        break;
                     } else if type_referent_lookup.insert(referer) {
                         if let Some(indirect_referers) = type_reference_lookup.get(&referer) {
                             type_referer_buffer.extend(indirect_referers);
@@ -306,34 +636,38 @@ impl<'data> ValidModule<'data> {
                 }
             }
 
-            contents
-                .function_signatures
-                .iter()
-                .flat_map(|signature| signature.types().iter().copied())
-                .try_for_each(|index| {
-                    check_type_signature_index(index)?;
-                    Result::<_, Error>::Ok(())
-                })?;
+            for (i, signature) in contents.function_signatures.iter().enumerate() {
+                for index in signature.types().iter() {
+                    try_or_continue!(
+                        check_type_signature_index(index),
+                        Origin::record(record::Type::FunctionSignature, i)
+                    );
+                }
+            }
+        }
+
+        // Comparing signatures that contain invalid indices or cycles would panic or never terminate
+        if handler.has_errors() {
+            return Ok(None);
         }
 
         struct SignatureComparer<'a> {
             type_signatures: &'a [signature::Type],
-            function_signatures: &'a [signature::Function],
+            function_signatures: &'a [signature::Function<'a>],
         }
 
         impl SignatureComparer<'_> {
-            fn are_type_index_lists_equal(&self, a: &[index::TypeSignature], b: &[index::TypeSignature]) -> bool {
-                a.len() == a.len() && a.iter().zip(b).all(|(x, y)| self.are_type_indices_equal(*x, *y))
+            fn are_type_index_lists_equal(&self, a: &signature::TypeList, b: &signature::TypeList) -> bool {
+                a.len() == b.len() && a.iter().zip(b).all(|(x, y)| self.are_type_indices_equal(x, y))
             }
 
             fn are_function_indices_equal(&self, a: index::FunctionSignature, b: index::FunctionSignature) -> bool {
                 if a == b {
                     true
                 } else {
-                    self.are_type_index_lists_equal(
-                        self.function_signatures[usize::from(a)].types(),
-                        self.function_signatures[usize::from(b)].types(),
-                    )
+                    let a = &self.function_signatures[usize::from(a)];
+                    let b = &self.function_signatures[usize::from(b)];
+                    a.return_type_count == b.return_type_count && self.are_type_index_lists_equal(a.types(), b.types())
                 }
             }
 
@@ -342,6 +676,7 @@ impl<'data> ValidModule<'data> {
                     (signature::Type::RawPtr(Some(c)), signature::Type::RawPtr(Some(d))) => self.are_type_indices_equal(*c, *d),
                     (signature::Type::FuncPtr(c), signature::Type::FuncPtr(d)) => self.are_function_indices_equal(*c, *d),
                     (signature::Type::FixedInteger(c), signature::Type::FixedInteger(d)) => c == d,
+                    (signature::Type::Vector(c), signature::Type::Vector(d)) => c == d,
                     (signature::Type::F32, signature::Type::F32)
                     | (signature::Type::F64, signature::Type::F64)
                     | (signature::Type::UAddr, signature::Type::UAddr)
@@ -369,10 +704,9 @@ impl<'data> ValidModule<'data> {
 
         let get_type_signature = |index| Result::<_, Error>::Ok(&contents.type_signatures[check_type_signature_index(index)?]);
 
-        let get_type_signature_list_owned = |indices: &[index::TypeSignature]| {
+        let get_type_signature_list_owned = |indices: &signature::TypeList| {
             indices
                 .iter()
-                .copied()
                 .map(|index| get_type_signature(index).cloned())
                 .collect::<Result<Box<[_]>, Error>>()
         };
@@ -381,14 +715,29 @@ impl<'data> ValidModule<'data> {
 
         let get_code_block = { |index| Result::<_, Error>::Ok(&contents.code[check_code_block_index(index)?]) };
 
+        // Only the first invalid instruction in a block is reported, since later instructions may depend on its results.
+        // The input types of invalid blocks are not compared against the signatures of functions that use them.
+        let mut invalid_code_blocks = rustc_hash::FxHashSet::<index::CodeBlock>::default();
+
         // TODO: Have a hashmap of all of the blocks that a block potentially branches to, as well as the eventual return type.
         {
             //let mut branch_targets = Vec::<(index::CodeBlock, Box<[signature::Type]>)>::new();
+            let mut register_type_buffer = Vec::<index::TypeSignature>::new();
             for (block_index, block) in contents.code.iter().enumerate() {
                 let block_index = index::CodeBlock::from(block_index);
 
+                // Register types are decoded once, since they are looked up by index for every instruction
+                register_type_buffer.clear();
+                register_type_buffer.extend(block.input_types().iter().chain(block.temporary_types().iter()));
+                let register_types = register_type_buffer.as_slice();
+                let input_count = block.input_count;
+
                 if block.instructions.is_empty() {
-                    return Err(ErrorKind::EmptyCodeBlock(block_index))?;
+                    handler.report(
+                        Origin::record(record::Type::CodeBlock, usize::from(block_index)),
+                        Error::from(ErrorKind::EmptyCodeBlock(block_index)),
+                    )?;
+                    continue;
                 }
 
                 let total_register_count = block.register_count();
@@ -401,127 +750,427 @@ impl<'data> ValidModule<'data> {
                 let current_temporary_count = std::cell::Cell::new(0usize);
                 let increment_temporary_count = || current_temporary_count.set(current_temporary_count.get() + 1);
 
-                let next_temporary_register_index = || index::Register::from(block.input_count + current_temporary_count.get());
-
-                let next_temporary_register_type = || get_type_signature(block.temporary_types()[current_temporary_count.get()]);
+                let next_temporary_register_index = || index::Register::from(input_count + current_temporary_count.get());
 
                 let mut has_terminator = false;
                 let instruction_iterator = block.instructions.iter();
                 let instruction_index = std::cell::Cell::new(0usize);
                 let last_instruction_index = block.instructions.len() - 1;
 
-                for instruction in instruction_iterator {
-                    macro_rules! invalid_instruction {
-                        ($kind:expr) => {
-                            return Err(InvalidInstructionError {
-                                instruction_index: instruction_index.get(),
-                                code_block: block_index,
-                                instruction: instruction.clone(),
-                                kind: $kind.into(),
-                            })?
-                        };
-                    }
-
-                    let validate_register_index = |register: index::Register| -> Result<usize, Error> {
-                        let index = usize::from(register);
-                        match last_register_index {
-                            Some(last) if index <= last => Ok(index),
-                            None | Some(_) => invalid_instruction!(InvalidIndexError {
-                                index,
-                                maximum_index: last_register_index,
-                                name: <index::Register as index::Index>::name(),
-                            }),
+                let validate_instructions = || -> Result<(), Error> {
+                    for instruction in instruction_iterator {
+                        // Temporary registers are defined in order, and cannot be used by the instruction that defines them
+                        let defined_register_count = input_count + current_temporary_count.get();
+
+                        macro_rules! invalid_instruction {
+                            ($kind:expr) => {
+                                return Err(InvalidInstructionError {
+                                    instruction_index: instruction_index.get(),
+                                    code_block: block_index,
+                                    instruction: instruction.clone(),
+                                    kind: $kind.into(),
+                                })?
+                            };
                         }
-                    };
 
-                    let get_register_type_index = |register| -> Result<_, Error> {
-                        let index = validate_register_index(register)?;
-                        Ok(if index < block.input_count {
-                            block.input_types()[index]
-                        } else {
-                            block.temporary_types()[index - block.input_count]
-                        })
-                    };
+                        macro_rules! checked_index {
+                            ($index:expr, $length:expr) => {
+                                match check_index($index, $length) {
+                                    Ok(index) => index,
+                                    Err(error) => invalid_instruction!(error),
+                                }
+                            };
+                        }
 
-                    let get_register_type = |register| get_type_signature(get_register_type_index(register)?);
+                        let next_temporary_register_type_index = || -> Result<index::TypeSignature, Error> {
+                            match register_types.get(input_count + current_temporary_count.get()) {
+                                Some(index) => Ok(*index),
+                                None => invalid_instruction!(InvalidInstructionKind::ExtraneousTemporary {
+                                    count: block.temporary_count()
+                                }),
+                            }
+                        };
 
-                    //let define_temporary_register = |ty: index::TypeSignature| { };
+                        let next_temporary_register_type = || get_type_signature(next_temporary_register_type_index()?);
 
-                    let expected_type_for_value = |value: &instruction::Value, expected: &_| -> Result<(), Error> {
-                        match (value, expected) {
-                            (_, signature::Type::FixedInteger(_)) if expected.is_integer() => Ok(()),
-                            (instruction::Value::IndexedRegister(register_index), _)
-                                if (&signature_comparer)
-                                    .are_type_signatures_equal(get_register_type(*register_index)?, expected) =>
-                            {
-                                Ok(())
+                        let define_temporary_integer = || -> Result<&signature::Type, Error> {
+                            let register_type = next_temporary_register_type()?;
+                            if !register_type.is_integer() {
+                                invalid_instruction!(InvalidInstructionKind::ExpectedIntegerResult {
+                                    register: next_temporary_register_index(),
+                                    actual_type: register_type.clone()
+                                });
                             }
-                            _ => invalid_instruction!(ValueTypeMismatchError {
-                                value: value.clone(),
-                                expected_type: expected.clone(),
-                                actual_type: match value {
-                                    instruction::Value::Constant(instruction::Constant::Integer(_)) => None,
-                                    instruction::Value::IndexedRegister(register) => Some(get_register_type(*register)?.clone()),
-                                },
-                            }),
-                        }
-                    };
 
-                    let expected_types_for_values = |values: &[_], expected: &[_]| -> Result<(), Error> {
-                        if values.len() != expected.len() {
-                            invalid_instruction!(InvalidInstructionKind::ValueCountMismatch {
-                                expected: expected.len(),
-                                actual: values.len()
-                            });
-                        }
+                            increment_temporary_count();
+                            Ok(register_type)
+                        };
 
-                        for (value, expected_type) in values.iter().zip(expected) {
-                            expected_type_for_value(value, get_type_signature(*expected_type)?)?;
-                        }
+                        let define_temporary = |expected: index::TypeSignature| -> Result<(), Error> {
+                            let actual = next_temporary_register_type_index()?;
+                            if !signature_comparer.are_type_indices_equal(expected, actual) {
+                                invalid_instruction!(InvalidInstructionKind::ResultTypeMismatch {
+                                    register: next_temporary_register_index(),
+                                    expected_type: get_type_signature(expected)?.clone(),
+                                    actual_type: get_type_signature(actual)?.clone(),
+                                });
+                            }
 
-                        Ok(())
-                    };
+                            increment_temporary_count();
+                            Ok(())
+                        };
 
-                    if has_terminator {
-                        invalid_instruction!(InvalidInstructionKind::ExpectedTerminatorAsLastInstruction);
-                    }
+                        let define_temporary_of_type = |expected: &signature::Type| -> Result<(), Error> {
+                            let actual = next_temporary_register_type()?;
+                            if !signature_comparer.are_type_signatures_equal(expected, actual) {
+                                invalid_instruction!(InvalidInstructionKind::ResultTypeMismatch {
+                                    register: next_temporary_register_index(),
+                                    expected_type: expected.clone(),
+                                    actual_type: actual.clone(),
+                                });
+                            }
 
-                    match instruction {
-                        Instruction::Nop | Instruction::Break => (),
-                        Instruction::IAdd(arguments) | Instruction::ISub(arguments) => {
-                            let operand_type = next_temporary_register_type()?;
+                            increment_temporary_count();
+                            Ok(())
+                        };
 
-                            if !operand_type.is_integer() {
-                                invalid_instruction!(InvalidInstructionKind::ExpectedIntegerResult {
+                        let define_temporary_vector = || -> Result<signature::VectorType, Error> {
+                            match next_temporary_register_type()? {
+                                signature::Type::Vector(vector_type) => {
+                                    increment_temporary_count();
+                                    Ok(*vector_type)
+                                }
+                                actual_type => invalid_instruction!(InvalidInstructionKind::ExpectedVectorResult {
                                     register: next_temporary_register_index(),
-                                    actual_type: operand_type.clone()
+                                    actual_type: actual_type.clone(),
+                                }),
+                            }
+                        };
+
+                        let validate_register_index = |register: index::Register| -> Result<usize, Error> {
+                            let index = usize::from(register);
+                            match last_register_index {
+                                Some(_) if index < defined_register_count => Ok(index),
+                                Some(last) if index <= last => invalid_instruction!(InvalidInstructionKind::UndefinedRegister {
+                                    register,
+                                    defined_count: defined_register_count,
+                                }),
+                                None | Some(_) => invalid_instruction!(InvalidIndexError {
+                                    index,
+                                    maximum_index: last_register_index,
+                                    name: <index::Register as index::Index>::name(),
+                                }),
+                            }
+                        };
+
+                        let get_register_type_index = |register| -> Result<_, Error> {
+                            let index = validate_register_index(register)?;
+                            Ok(register_types[index])
+                        };
+
+                        let get_register_type = |register| get_type_signature(get_register_type_index(register)?);
+
+                        //let define_temporary_register = |ty: index::TypeSignature| { };
+
+                        let expected_type_for_value = |value: &instruction::Value, expected: &_| -> Result<(), Error> {
+                            match (value, expected) {
+                                (
+                                    instruction::Value::Constant(instruction::Constant::Integer(_)),
+                                    signature::Type::FixedInteger(_),
+                                ) => Ok(()),
+                                (instruction::Value::IndexedRegister(register_index), _)
+                                    if signature_comparer
+                                        .are_type_signatures_equal(get_register_type(*register_index)?, expected) =>
+                                {
+                                    Ok(())
+                                }
+                                _ => invalid_instruction!(ValueTypeMismatchError {
+                                    value: value.clone(),
+                                    expected_type: expected.clone(),
+                                    actual_type: match value {
+                                        instruction::Value::Constant(instruction::Constant::Integer(_)) => None,
+                                        instruction::Value::IndexedRegister(register) =>
+                                            Some(get_register_type(*register)?.clone()),
+                                    },
+                                }),
+                            }
+                        };
+
+                        let expected_types_for_values = |values: &[_], expected: &signature::TypeList| -> Result<(), Error> {
+                            if values.len() != expected.len() {
+                                invalid_instruction!(InvalidInstructionKind::ValueCountMismatch {
+                                    expected: expected.len(),
+                                    actual: values.len()
                                 });
                             }
 
-                            expected_type_for_value(arguments.x_value(), operand_type)?;
-                            expected_type_for_value(arguments.y_value(), operand_type)?;
-                            increment_temporary_count(); // The result of the operation is the operand_type
+                            for (value, expected_type) in values.iter().zip(expected) {
+                                expected_type_for_value(value, get_type_signature(expected_type)?)?;
+                            }
+
+                            Ok(())
+                        };
+
+                        let expected_integer_value = |value: &instruction::Value| -> Result<(), Error> {
+                            match value {
+                                instruction::Value::Constant(instruction::Constant::Integer(_)) => Ok(()),
+                                instruction::Value::IndexedRegister(register) => {
+                                    let actual_type = get_register_type(*register)?;
+                                    if !actual_type.is_integer() {
+                                        invalid_instruction!(InvalidInstructionKind::ExpectedIntegerValue {
+                                            value: value.clone(),
+                                            actual_type: actual_type.clone(),
+                                        });
+                                    }
+                                    Ok(())
+                                }
+                            }
+                        };
 
-                            match arguments.overflow_behavior() {
-                                instruction::OverflowBehavior::Flag => {
-                                    // TODO: Simply check if the next temporary register type is a boolean
-                                    todo!("Define another register for arithmetic overflow")
+                        // Gets the integer type that the pointer points to
+                        let expected_integer_pointer = |address: &instruction::Value| -> Result<index::TypeSignature, Error> {
+                            if let instruction::Value::IndexedRegister(register) = address {
+                                if let signature::Type::RawPtr(Some(pointee)) = get_register_type(*register)? {
+                                    let pointee_type = get_type_signature(*pointee)?;
+                                    match pointee_type {
+                                        signature::Type::FixedInteger(integer_type)
+                                            if matches!(integer_type.size().bit_size().get(), 8 | 16 | 32 | 64) =>
+                                        {
+                                            return Ok(*pointee)
+                                        }
+                                        signature::Type::UAddr | signature::Type::SAddr => return Ok(*pointee),
+                                        signature::Type::FixedInteger(_) => {
+                                            invalid_instruction!(InvalidInstructionKind::UnsupportedAtomicType {
+                                                value: address.clone(),
+                                                pointee_type: pointee_type.clone(),
+                                            })
+                                        }
+                                        _ => (),
+                                    }
                                 }
-                                instruction::OverflowBehavior::Ignore | instruction::OverflowBehavior::Saturate => (),
+                            }
+
+                            invalid_instruction!(InvalidInstructionKind::ExpectedIntegerPointer { value: address.clone() })
+                        };
+
+                        let expected_vector_value = |value: &instruction::Value| -> Result<signature::VectorType, Error> {
+                            if let instruction::Value::IndexedRegister(register) = value {
+                                if let signature::Type::Vector(vector_type) = get_register_type(*register)? {
+                                    return Ok(*vector_type);
+                                }
+                            }
+
+                            invalid_instruction!(InvalidInstructionKind::ExpectedVectorValue { value: value.clone() })
+                        };
+
+                        let expected_lane_index = |lane: u16, lane_count: u16| -> Result<(), Error> {
+                            if lane >= lane_count {
+                                invalid_instruction!(InvalidInstructionKind::VectorLaneOutOfRange { lane, lane_count });
+                            }
+
+                            Ok(())
+                        };
+
+                        let expected_memory_ordering =
+                            |ordering: instruction::MemoryOrdering, is_valid: bool| -> Result<(), Error> {
+                                if !is_valid {
+                                    invalid_instruction!(InvalidInstructionKind::InvalidMemoryOrdering { ordering });
+                                }
+
+                                Ok(())
+                            };
+
+                        let expected_branch_target =
+                            |target: index::CodeBlock, arguments: &[instruction::Value]| -> Result<(), Error> {
+                                let target_block = &contents.code[checked_index!(target, contents.code.len())];
+                                expected_types_for_values(arguments, &target_block.input_types())
+                            };
+
+                        if has_terminator {
+                            invalid_instruction!(InvalidInstructionKind::ExpectedTerminatorAsLastInstruction);
+                        }
+
+                        match instruction {
+                            Instruction::Nop | Instruction::Break => (),
+                            Instruction::IAdd(arguments) | Instruction::ISub(arguments) => {
+                                // The result of the operation is the operand_type
+                                let operand_type = match next_temporary_register_type()? {
+                                    vector @ signature::Type::Vector(vector_type) if vector_type.element_type().is_integer() => {
+                                        if arguments.overflow_behavior() == instruction::OverflowBehavior::Flag {
+                                            invalid_instruction!(InvalidInstructionKind::VectorOverflowFlag);
+                                        }
+
+                                        increment_temporary_count();
+                                        vector
+                                    }
+                                    _ => define_temporary_integer()?,
+                                };
+                                expected_type_for_value(arguments.x_value(), operand_type)?;
+                                expected_type_for_value(arguments.y_value(), operand_type)?;
+
+                                match arguments.overflow_behavior() {
+                                    instruction::OverflowBehavior::Flag => {
+                                        define_temporary_integer()?;
+                                    }
+                                    instruction::OverflowBehavior::Ignore | instruction::OverflowBehavior::Saturate => (),
+                                }
+                            }
+                            Instruction::Cmp(comparison) => {
+                                let result_type = define_temporary_integer()?;
+
+                                // Operand types are inferred from the first register, since integer constants are untyped
+                                let operand_type = match (comparison.x_value(), comparison.y_value()) {
+                                    (instruction::Value::IndexedRegister(register), _)
+                                    | (_, instruction::Value::IndexedRegister(register)) => get_register_type(*register)?,
+                                    _ => result_type,
+                                };
+
+                                expected_integer_value(comparison.x_value())?;
+                                expected_type_for_value(comparison.x_value(), operand_type)?;
+                                expected_type_for_value(comparison.y_value(), operand_type)?;
+                            }
+                            Instruction::Call(callee, arguments) | Instruction::ThreadSpawn(callee, arguments) => {
+                                let function = &contents.functions[checked_index!(*callee, contents.functions.len())];
+                                checked_index!(
+                                    function.template,
+                                    contents.function_imports.len() + contents.function_templates.len()
+                                );
+                                let signature = &contents.function_signatures[checked_index!(
+                                    contents.function_template_signature(function.template),
+                                    contents.function_signatures.len()
+                                )];
+
+                                expected_types_for_values(arguments, &signature.parameter_types())?;
+
+                                if let Instruction::ThreadSpawn(_, _) = instruction {
+                                    // The thread handle
+                                    define_temporary_integer()?;
+                                } else {
+                                    for return_type in signature.return_types().iter() {
+                                        define_temporary(return_type)?;
+                                    }
+                                }
+                            }
+                            Instruction::ThreadJoin(signature, thread) => {
+                                let signature =
+                                    &contents.function_signatures[checked_index!(*signature, contents.function_signatures.len())];
+
+                                expected_integer_value(thread)?;
+
+                                for return_type in signature.return_types().iter() {
+                                    define_temporary(return_type)?;
+                                }
+                            }
+                            Instruction::AtomicLoad(load) => {
+                                expected_memory_ordering(load.ordering(), load.ordering().is_valid_for_load())?;
+                                let value_type = expected_integer_pointer(load.address())?;
+                                define_temporary(value_type)?;
+                            }
+                            Instruction::AtomicStore(store) => {
+                                expected_memory_ordering(store.ordering(), store.ordering().is_valid_for_store())?;
+                                let value_type = expected_integer_pointer(store.address())?;
+                                expected_type_for_value(store.value(), get_type_signature(value_type)?)?;
+                            }
+                            Instruction::AtomicRmw(operation) => {
+                                let value_type = expected_integer_pointer(operation.address())?;
+                                expected_type_for_value(operation.value(), get_type_signature(value_type)?)?;
+                                define_temporary(value_type)?;
+                            }
+                            Instruction::AtomicCmpXchg(operation) => {
+                                expected_memory_ordering(
+                                    operation.failure_ordering(),
+                                    operation
+                                        .failure_ordering()
+                                        .is_valid_for_compare_exchange_failure(operation.success_ordering()),
+                                )?;
+
+                                let value_type = expected_integer_pointer(operation.address())?;
+                                expected_type_for_value(operation.expected(), get_type_signature(value_type)?)?;
+                                expected_type_for_value(operation.replacement(), get_type_signature(value_type)?)?;
+                                define_temporary(value_type)?;
+
+                                // Indicates if the value was replaced
+                                define_temporary_integer()?;
+                            }
+                            Instruction::Fence(ordering) => {
+                                expected_memory_ordering(*ordering, *ordering != instruction::MemoryOrdering::Relaxed)?;
+                            }
+                            Instruction::VSplat(value) => {
+                                let vector_type = define_temporary_vector()?;
+                                expected_type_for_value(value, &signature::Type::from(vector_type.element_type()))?;
+                            }
+                            Instruction::VExtract(vector, lane) => {
+                                let vector_type = expected_vector_value(vector)?;
+                                expected_lane_index(*lane, u16::from(vector_type.lane_count().get()))?;
+                                define_temporary_of_type(&signature::Type::from(vector_type.element_type()))?;
+                            }
+                            Instruction::VInsert(insert) => {
+                                let vector_type = expected_vector_value(insert.vector())?;
+                                expected_lane_index(insert.lane(), u16::from(vector_type.lane_count().get()))?;
+                                expected_type_for_value(insert.value(), &signature::Type::from(vector_type.element_type()))?;
+                                define_temporary_of_type(&signature::Type::Vector(vector_type))?;
+                            }
+                            Instruction::VShuffle(shuffle) => {
+                                let vector_type = expected_vector_value(shuffle.x_value())?;
+                                expected_type_for_value(shuffle.y_value(), &signature::Type::Vector(vector_type))?;
+
+                                // Lanes of y come after the lanes of x
+                                let available_lanes = u16::from(vector_type.lane_count().get()) * 2;
+                                for lane in shuffle.lanes().iter().copied() {
+                                    expected_lane_index(lane, available_lanes)?;
+                                }
+
+                                let lane_count = match u8::try_from(shuffle.lanes().len()).ok().and_then(std::num::NonZeroU8::new)
+                                {
+                                    Some(count) => count,
+                                    None => invalid_instruction!(InvalidInstructionKind::InvalidShuffleLaneCount {
+                                        count: shuffle.lanes().len()
+                                    }),
+                                };
+
+                                let result_type = signature::VectorType::new(vector_type.element_type(), lane_count);
+                                define_temporary_of_type(&signature::Type::Vector(result_type))?;
+                            }
+                            Instruction::Return(values) => {
+                                expected_types_for_values(values.as_ref(), &block.result_types())?;
+                                has_terminator = true;
+                            }
+                            Instruction::Br(target, arguments) => {
+                                expected_branch_target(*target, arguments)?;
+                                has_terminator = true;
+                            }
+                            Instruction::BrIf(branch) => {
+                                expected_integer_value(branch.condition())?;
+                                expected_branch_target(branch.true_branch(), branch.arguments())?;
+                                expected_branch_target(branch.false_branch(), branch.arguments())?;
+                                has_terminator = true;
                             }
                         }
-                        Instruction::Return(values) => {
-                            expected_types_for_values(values.as_ref(), block.result_types())?;
-                            has_terminator = true;
+
+                        if instruction_index.get() < last_instruction_index {
+                            instruction_index.set(instruction_index.get() + 1);
+                        } else if !has_terminator {
+                            invalid_instruction!(InvalidInstructionKind::ExpectedTerminator);
                         }
-                        bad => todo!("validate {:?}", bad),
                     }
 
-                    if instruction_index.get() < last_instruction_index {
-                        instruction_index.set(instruction_index.get() + 1);
-                    } else if !has_terminator {
-                        invalid_instruction!(InvalidInstructionKind::ExpectedTerminator);
-                    }
+                    Ok(())
+                };
+
+                if let Err(error) = validate_instructions() {
+                    handler.report(Origin::instruction(block_index, instruction_index.get()), error)?;
+                    invalid_code_blocks.insert(block_index);
+                } else if current_temporary_count.get() != block.temporary_count() {
+                    let error = ErrorKind::UndefinedTemporaries {
+                        code_block: block_index,
+                        declared: block.temporary_count(),
+                        defined: current_temporary_count.get(),
+                    };
+
+                    handler.report(
+                        Origin::record(record::Type::CodeBlock, usize::from(block_index)),
+                        Error::from(error),
+                    )?;
                 }
             }
         }
@@ -550,25 +1199,38 @@ impl<'data> ValidModule<'data> {
         let mut symbol_lookup = SymbolLookup::default();
         let mut exports = Exports::default();
 
+        for (index, import) in contents.function_imports.iter().enumerate() {
+            if let Err(error) = check_function_signature_index(import.signature) {
+                handler.report(Origin::record(record::Type::FunctionImport, index), error)?;
+            }
+        }
+
         for (index, template) in contents.function_templates.iter().enumerate() {
-            let current_index = index::FunctionTemplate::from(index);
-            check_function_signature_index(template.signature)?;
+            let current_index = contents.function_template_index(index);
+            let origin = Origin::record(record::Type::FunctionTemplate, index);
+            try_or_continue!(check_function_signature_index(template.signature), origin);
 
             if let Some(symbol) = template.export.symbol() {
-                symbol_lookup.try_insert(symbol, current_index.into())?;
+                if let Err(error) = symbol_lookup.try_insert(symbol, current_index.into()) {
+                    handler.report(origin, error)?;
+                }
             }
 
-            let entry_block = get_code_block(template.entry_block)?;
+            let entry_block = try_or_continue!(get_code_block(template.entry_block), origin);
             let signature = &contents.function_signatures[usize::from(template.signature)];
 
-            if !signature_comparer.are_type_index_lists_equal(entry_block.input_types(), signature.parameter_types()) {
-                return Err(FunctionTypeMismatchError {
+            if invalid_code_blocks.contains(&template.entry_block) {
+                // The types of the entry block's inputs may not be valid
+            } else if !signature_comparer.are_type_index_lists_equal(&entry_block.input_types(), &signature.parameter_types()) {
+                let error = FunctionTypeMismatchError {
                     template: current_index,
                     entry_block: template.entry_block,
                     are_parameters_wrong: true,
-                    actual_types: get_type_signature_list_owned(signature.parameter_types())?,
-                    expected_types: get_type_signature_list_owned(entry_block.input_types())?,
-                })?;
+                    actual_types: get_type_signature_list_owned(&signature.parameter_types())?,
+                    expected_types: get_type_signature_list_owned(&entry_block.input_types())?,
+                };
+
+                handler.report(origin, Error::from(error))?;
             }
 
             // TODO: Check to see what the eventual return types are (don't compare to entry block's return types)
@@ -578,35 +1240,58 @@ impl<'data> ValidModule<'data> {
             }
         }
 
-        let check_function_template_index = get_index_validator(contents.function_templates.len());
+        let check_function_template_index =
+            get_index_validator(contents.function_imports.len() + contents.function_templates.len());
+        let check_function_index = get_index_validator(contents.functions.len());
 
-        for instantiation in contents.functions.iter() {
-            check_function_template_index(instantiation.template)?;
+        for (index, instantiation) in contents.functions.iter().enumerate() {
+            if let Err(error) = check_function_template_index(instantiation.template) {
+                handler.report(Origin::record(record::Type::Function, index), error)?;
+            }
         }
 
-        for field in metadata_fields.into_iter() {
+        for (index, field) in metadata_fields.into_iter().enumerate() {
             match field {
                 record::MetadataField::ModuleIdentifier(identifier) => {
                     contents.module_identifiers.insert(identifier);
                 }
                 record::MetadataField::EntryPoint(entry_point) => {
                     if let Some(defined) = contents.entry_point {
-                        return Err(ErrorKind::DuplicateEntryPoint {
+                        let error = ErrorKind::DuplicateEntryPoint {
                             defined,
                             duplicate: entry_point,
-                        })?;
+                        };
+
+                        handler.report(Origin::record(record::Type::MetadataField, index), Error::from(error))?;
+                        continue;
                     }
                     // else if entry point OOB
 
                     contents.entry_point = Some(entry_point);
                 }
+                record::MetadataField::NoInline(function) => {
+                    if let Err(error) = check_function_index(function) {
+                        handler.report(Origin::record(record::Type::MetadataField, index), error)?;
+                    } else if !contents.no_inline_functions.contains(&function) {
+                        contents.no_inline_functions.push(function);
+                    }
+                }
             }
         }
 
-        Ok(Self { contents, exports })
+        if handler.has_errors() {
+            Ok(None)
+        } else {
+            Ok(Some(Self { contents, exports }))
+        }
     }
 
-    pub fn from_records_fallible<R, E>(records: R) -> Result<Result<Self, Error>, E>
+    /// Sorts the `records` by their type, optionally keeping track of the order that they appeared in.
+    #[allow(clippy::type_complexity)]
+    fn collect_records<R, E>(
+        records: R,
+        mut record_types: Option<&mut Vec<record::Type>>,
+    ) -> Result<(ModuleContents<'data>, Vec<record::MetadataField<'data>>), E>
     where
         R: IntoIterator<Item = Result<Record<'data>, E>>,
     {
@@ -614,25 +1299,88 @@ impl<'data> ValidModule<'data> {
         let mut metadata_fields = Vec::new();
 
         for data in records.into_iter() {
-            match data? {
+            let data = data?;
+
+            if let Some(record_types) = record_types.as_mut() {
+                record_types.push(data.record_type());
+            }
+
+            match data {
                 Record::MetadataField(metadata) => metadata_fields.push(metadata),
                 Record::Identifier(identifier) => contents.identifiers.push(identifier),
                 Record::TypeSignature(signature) => contents.type_signatures.push(signature),
                 Record::FunctionSignature(signature) => contents.function_signatures.push(signature),
                 Record::Data(data) => contents.data.push(data),
                 Record::CodeBlock(block) => contents.code.push(block),
+                Record::FunctionImport(import) => contents.function_imports.push(import),
                 Record::FunctionTemplate(template) => contents.function_templates.push(template),
                 Record::Function(function) => contents.functions.push(function),
             }
         }
 
-        Ok(Self::validate(contents, metadata_fields))
+        Ok((contents, metadata_fields))
+    }
+
+    pub fn from_records_fallible<R, E>(records: R) -> Result<Result<Self, Error>, E>
+    where
+        R: IntoIterator<Item = Result<Record<'data>, E>>,
+    {
+        let (contents, metadata_fields) = Self::collect_records(records, None)?;
+        Ok(Self::validate(contents, metadata_fields, &mut FailFast)
+            .map(|module| module.expect("validation should stop at the first error")))
+    }
+
+    /// Validates a module, returning every error that was found instead of stopping at the first.
+    ///
+    /// Errors in type signatures and function signatures prevent the rest of the module from being validated, since
+    /// everything else refers to them. Only the first invalid instruction in each code block is reported, since the types of
+    /// the registers it defines are not known, which would cause misleading errors in the instructions that follow it.
+    pub fn from_records_collecting_errors_fallible<R, E>(records: R) -> Result<Result<Self, Vec<LocatedError>>, E>
+    where
+        R: IntoIterator<Item = Result<Record<'data>, E>>,
+    {
+        let mut collector = ErrorCollector {
+            record_types: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let (contents, metadata_fields) = Self::collect_records(records, Some(&mut collector.record_types))?;
+
+        Ok(
+            match Self::validate(contents, metadata_fields, &mut collector).expect("errors should be collected") {
+                Some(module) => Ok(module),
+                None => Err(collector.errors),
+            },
+        )
+    }
+
+    /// Validates a module, returning every error that was found instead of stopping at the first.
+    ///
+    /// See [`ValidModule::from_records_collecting_errors_fallible`] for more information.
+    pub fn from_records_collecting_errors<R: IntoIterator<Item = Record<'data>>>(records: R) -> Result<Self, Vec<LocatedError>> {
+        Self::from_records_collecting_errors_fallible::<_, std::convert::Infallible>(records.into_iter().map(Ok)).unwrap()
     }
 
     pub fn from_records<R: IntoIterator<Item = Record<'data>>>(records: R) -> Result<Self, Error> {
         Self::from_records_fallible::<_, std::convert::Infallible>(records.into_iter().map(Ok)).unwrap()
     }
 
+    /// Reads and validates a module stored in a byte slice, with identifiers, data, and symbols borrowed from the `input`.
+    pub fn from_slice(input: &'data [u8]) -> crate::reader::Result<Result<Self, Error>> {
+        let (_, mut records) = crate::reader::SliceReader::new(input).to_record_reader()?;
+        let module = Self::from_records_fallible(&mut records)?;
+        records.finish()?;
+        Ok(module)
+    }
+
+    /// Reads a module stored in a byte slice, returning every validation error that was found.
+    pub fn from_slice_collecting_errors(input: &'data [u8]) -> crate::reader::Result<Result<Self, Vec<LocatedError>>> {
+        let (_, mut records) = crate::reader::SliceReader::new(input).to_record_reader()?;
+        let module = Self::from_records_collecting_errors_fallible(&mut records)?;
+        records.finish()?;
+        Ok(module)
+    }
+
     pub fn from_builder(builder: crate::builder::Builder<'static>) -> Result<Self, Error> {
         Self::from_records(builder.into_records())
     }
@@ -682,10 +1430,499 @@ impl<'data> std::fmt::Debug for ValidModule<'data> {
 
 #[cfg(test)]
 mod tests {
-    use crate::validation::ValidModule;
+    use crate::builder::Builder;
+    use crate::identifier::Id;
+    use crate::index;
+    use crate::instruction::{self, Instruction};
+    use crate::record;
+    use crate::signature;
+    use crate::validation::{ErrorKind, InvalidInstructionKind, LocatedError, ValidModule};
 
     #[test]
     fn empty_module_is_always_valid() {
         ValidModule::from_records(std::iter::empty()).unwrap();
     }
+
+    /// Validates a module containing a function that takes an integer pointer and an integer, and returns the result of an
+    /// atomic instruction.
+    fn validate_atomic_instruction(instruction: Instruction) -> Result<ValidModule<'static>, InvalidInstructionKind> {
+        let integer_type = index::TypeSignature::from(0);
+        let pointer_type = index::TypeSignature::from(1);
+        let mut builder = Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        builder.add_record(signature::Type::RawPtr(Some(integer_type)));
+        builder.add_record(signature::Function::new(vec![pointer_type, integer_type], vec![integer_type]));
+        builder.add_record(record::CodeBlock::new(
+            vec![pointer_type, integer_type],
+            vec![integer_type],
+            vec![integer_type],
+            vec![
+                instruction,
+                Instruction::Return(vec![index::Register::from(2).into()].into_boxed_slice()),
+            ],
+        ));
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportBorrowed(Id::try_from_str("test").unwrap()),
+            index::FunctionSignature::from(0),
+            index::CodeBlock::from(0),
+        ));
+
+        ValidModule::from_builder(builder).map_err(|error| match error.kind() {
+            ErrorKind::InvalidInstruction(invalid) => invalid.kind().clone(),
+            unexpected => panic!("unexpected error {unexpected}"),
+        })
+    }
+
+    #[test]
+    fn atomic_instructions_are_valid() {
+        validate_atomic_instruction(Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(
+            instruction::MemoryOrdering::Acquire,
+            index::Register::from(0).into(),
+        ))))
+        .unwrap();
+
+        validate_atomic_instruction(Instruction::AtomicRmw(Box::new(instruction::AtomicReadModifyWrite::new(
+            instruction::AtomicOperation::Add,
+            instruction::MemoryOrdering::AcquireRelease,
+            index::Register::from(0).into(),
+            index::Register::from(1).into(),
+        ))))
+        .unwrap();
+    }
+
+    #[test]
+    fn atomic_load_with_release_ordering_is_invalid() {
+        let error = validate_atomic_instruction(Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(
+            instruction::MemoryOrdering::Release,
+            index::Register::from(0).into(),
+        ))))
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            InvalidInstructionKind::InvalidMemoryOrdering {
+                ordering: instruction::MemoryOrdering::Release
+            }
+        ));
+    }
+
+    #[test]
+    fn compare_exchange_failure_ordering_cannot_be_stronger_than_success_ordering() {
+        let error = validate_atomic_instruction(Instruction::AtomicCmpXchg(Box::new(instruction::AtomicCompareExchange::new(
+            instruction::MemoryOrdering::Release,
+            instruction::MemoryOrdering::Acquire,
+            index::Register::from(0).into(),
+            index::Register::from(1).into(),
+            index::Register::from(1).into(),
+        ))))
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            InvalidInstructionKind::InvalidMemoryOrdering {
+                ordering: instruction::MemoryOrdering::Acquire
+            }
+        ));
+    }
+
+    #[test]
+    fn atomic_address_must_be_integer_pointer() {
+        let error = validate_atomic_instruction(Instruction::AtomicRmw(Box::new(instruction::AtomicReadModifyWrite::new(
+            instruction::AtomicOperation::Add,
+            instruction::MemoryOrdering::Relaxed,
+            index::Register::from(1).into(),
+            index::Register::from(1).into(),
+        ))))
+        .unwrap_err();
+
+        assert!(matches!(error, InvalidInstructionKind::ExpectedIntegerPointer { .. }));
+    }
+
+    #[test]
+    fn atomic_load_of_u24_pointer_is_invalid() {
+        let odd_integer_type = index::TypeSignature::from(0);
+        let pointer_type = index::TypeSignature::from(1);
+        let mut builder = Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::new(
+            signature::IntegerSign::Unsigned,
+            signature::IntegerSize::new(std::num::NonZeroU8::new(24).unwrap()),
+        )));
+        builder.add_record(signature::Type::RawPtr(Some(odd_integer_type)));
+        builder.add_record(signature::Function::new(vec![pointer_type], vec![odd_integer_type]));
+        builder.add_record(record::CodeBlock::new(
+            vec![pointer_type],
+            vec![odd_integer_type],
+            vec![odd_integer_type],
+            vec![
+                Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(
+                    instruction::MemoryOrdering::Relaxed,
+                    index::Register::from(0).into(),
+                ))),
+                Instruction::Return(vec![index::Register::from(1).into()].into_boxed_slice()),
+            ],
+        ));
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportBorrowed(Id::try_from_str("test").unwrap()),
+            index::FunctionSignature::from(0),
+            index::CodeBlock::from(0),
+        ));
+
+        let error = ValidModule::from_builder(builder).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::InvalidInstruction(invalid) if matches!(invalid.kind(), InvalidInstructionKind::UnsupportedAtomicType { .. })
+        ));
+    }
+
+    /// Validates a module containing a function that takes an integer and a vector of four integers, and returns the integer
+    /// produced by the specified instructions.
+    fn validate_vector_instructions(
+        temporary_types: Vec<index::TypeSignature>,
+        mut instructions: Vec<Instruction>,
+    ) -> Result<ValidModule<'static>, InvalidInstructionKind> {
+        let integer_type = index::TypeSignature::from(0);
+        let vector_type = index::TypeSignature::from(1);
+        let result_register = index::Register::from(1 + temporary_types.len());
+        let mut builder = Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        builder.add_record(signature::Type::Vector(signature::VectorType::new(
+            signature::IntegerType::U32.into(),
+            std::num::NonZeroU8::new(4).unwrap(),
+        )));
+        builder.add_record(signature::Function::new(vec![integer_type, vector_type], vec![integer_type]));
+        instructions.push(Instruction::Return(vec![result_register.into()].into_boxed_slice()));
+        builder.add_record(record::CodeBlock::new(
+            vec![integer_type, vector_type],
+            vec![integer_type],
+            temporary_types,
+            instructions,
+        ));
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportBorrowed(Id::try_from_str("test").unwrap()),
+            index::FunctionSignature::from(0),
+            index::CodeBlock::from(0),
+        ));
+
+        ValidModule::from_builder(builder).map_err(|error| match error.kind() {
+            ErrorKind::InvalidInstruction(invalid) => invalid.kind().clone(),
+            unexpected => panic!("unexpected error {unexpected}"),
+        })
+    }
+
+    #[test]
+    fn vector_instructions_are_valid() {
+        let integer_type = index::TypeSignature::from(0);
+        let vector_type = index::TypeSignature::from(1);
+        let register = index::Register::from;
+
+        validate_vector_instructions(
+            vec![vector_type, vector_type, vector_type, vector_type, integer_type],
+            vec![
+                Instruction::VSplat(register(0).into()),
+                Instruction::IAdd(Box::new(instruction::IntegerArithmetic::new(
+                    instruction::OverflowBehavior::Ignore,
+                    register(2).into(),
+                    register(1).into(),
+                ))),
+                Instruction::VShuffle(Box::new(instruction::VectorShuffle::new(
+                    register(3).into(),
+                    register(2).into(),
+                    vec![0, 5, 2, 7].into_boxed_slice(),
+                ))),
+                Instruction::VInsert(Box::new(instruction::VectorInsert::new(
+                    register(4).into(),
+                    register(0).into(),
+                    1,
+                ))),
+                Instruction::VExtract(register(5).into(), 3),
+            ],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn vector_lane_must_be_in_range() {
+        let error = validate_vector_instructions(
+            vec![index::TypeSignature::from(0)],
+            vec![Instruction::VExtract(index::Register::from(1).into(), 4)],
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            InvalidInstructionKind::VectorLaneOutOfRange { lane: 4, lane_count: 4 }
+        ));
+    }
+
+    #[test]
+    fn vector_arithmetic_cannot_produce_overflow_flag() {
+        let vector_type = index::TypeSignature::from(1);
+        let error = validate_vector_instructions(
+            vec![vector_type, index::TypeSignature::from(0)],
+            vec![Instruction::IAdd(Box::new(instruction::IntegerArithmetic::new(
+                instruction::OverflowBehavior::Flag,
+                index::Register::from(1).into(),
+                index::Register::from(1).into(),
+            )))],
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, InvalidInstructionKind::VectorOverflowFlag));
+    }
+
+    #[test]
+    fn vectors_are_not_scalar_operands() {
+        let integer_type = index::TypeSignature::from(0);
+        let register = index::Register::from;
+
+        for instruction in [
+            Instruction::IAdd(Box::new(instruction::IntegerArithmetic::new(
+                instruction::OverflowBehavior::Ignore,
+                register(0).into(),
+                register(1).into(),
+            ))),
+            Instruction::VInsert(Box::new(instruction::VectorInsert::new(
+                register(1).into(),
+                register(1).into(),
+                0,
+            ))),
+        ] {
+            let error = validate_vector_instructions(vec![integer_type, integer_type], vec![instruction]).unwrap_err();
+            assert!(matches!(error, InvalidInstructionKind::ExpectedTypeForValue(_)));
+        }
+
+        let error = validate_vector_instructions(
+            vec![index::TypeSignature::from(1), integer_type],
+            vec![Instruction::VSplat(register(1).into())],
+        )
+        .unwrap_err();
+
+        assert!(matches!(error, InvalidInstructionKind::ExpectedTypeForValue(_)));
+    }
+
+    #[test]
+    fn all_errors_are_collected() {
+        let integer_type = index::TypeSignature::from(0);
+        let records = vec![
+            record::Record::from(signature::Type::from(signature::IntegerType::S32)),
+            signature::Function::new(Vec::new(), vec![integer_type]).into(),
+            record::CodeBlock::new(
+                Vec::new(),
+                vec![integer_type],
+                Vec::new(),
+                vec![Instruction::Return(Box::default())],
+            )
+            .into(),
+            record::CodeBlock::new(Vec::new(), Vec::new(), Vec::new(), Vec::new()).into(),
+            record::FunctionTemplate::new(
+                record::Export::ExportBorrowed(Id::try_from_str("a").unwrap()),
+                index::FunctionSignature::from(0),
+                index::CodeBlock::from(0),
+            )
+            .into(),
+            record::FunctionTemplate::new(
+                record::Export::ExportBorrowed(Id::try_from_str("a").unwrap()),
+                index::FunctionSignature::from(0),
+                index::CodeBlock::from(2),
+            )
+            .into(),
+            record::Function::with_template(index::FunctionTemplate::from(5)).into(),
+            record::MetadataField::EntryPoint(index::Function::from(0)).into(),
+            record::MetadataField::EntryPoint(index::Function::from(0)).into(),
+        ];
+
+        assert!(matches!(
+            ValidModule::from_records(records.clone()).unwrap_err().kind(),
+            ErrorKind::InvalidInstruction(_)
+        ));
+
+        let errors = ValidModule::from_records_collecting_errors(records).unwrap_err();
+        let locations = errors
+            .iter()
+            .map(|error| {
+                let location = error.location();
+                (
+                    location.record(),
+                    location.code_block().map(usize::from),
+                    location.instruction(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            locations,
+            [
+                (2, Some(0), Some(0)),
+                (3, Some(1), None),
+                (5, None, None),
+                (5, None, None),
+                (6, None, None),
+                (8, None, None)
+            ]
+        );
+        assert!(matches!(
+            errors.iter().map(LocatedError::kind).collect::<Vec<_>>().as_slice(),
+            [
+                ErrorKind::InvalidInstruction(_),
+                ErrorKind::EmptyCodeBlock(_),
+                ErrorKind::DuplicateSymbol { .. },
+                ErrorKind::InvalidIndex(_),
+                ErrorKind::InvalidIndex(_),
+                ErrorKind::DuplicateEntryPoint { .. },
+            ]
+        ));
+    }
+
+    #[test]
+    fn invalid_signatures_prevent_further_validation() {
+        let errors = ValidModule::from_records_collecting_errors([
+            record::Record::from(signature::Type::RawPtr(Some(index::TypeSignature::from(5)))),
+            signature::Function::new(vec![index::TypeSignature::from(7)], Vec::new()).into(),
+            record::CodeBlock::new(Vec::new(), Vec::new(), Vec::new(), Vec::new()).into(),
+        ])
+        .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error.kind(), ErrorKind::InvalidIndex(_)) && error.location().code_block().is_none()));
+    }
+
+    /// Collects the errors of a module containing two code blocks with invalid instructions, and optionally a function
+    /// signature that refers to a type that does not exist.
+    fn collect_invalid_code_block_errors(include_invalid_signature: bool) -> Vec<LocatedError> {
+        let mut records = Vec::<record::Record>::new();
+        if include_invalid_signature {
+            records.push(signature::Function::new(vec![index::TypeSignature::from(9)], Vec::new()).into());
+        }
+
+        // Both instructions in the first block are invalid, since registers are used that do not exist and instructions
+        // are placed after the terminator
+        records.push(
+            record::CodeBlock::new(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                vec![
+                    Instruction::Return(vec![index::Register::from(3).into()].into_boxed_slice()),
+                    Instruction::Return(vec![index::Register::from(4).into()].into_boxed_slice()),
+                ],
+            )
+            .into(),
+        );
+        records.push(
+            record::CodeBlock::new(
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                vec![
+                    Instruction::Nop,
+                    Instruction::Return(vec![index::Register::from(1).into()].into_boxed_slice()),
+                ],
+            )
+            .into(),
+        );
+
+        ValidModule::from_records_collecting_errors(records).unwrap_err()
+    }
+
+    #[test]
+    fn only_first_invalid_instruction_in_each_block_and_signature_errors_are_collected() {
+        // Invalid signatures prevent code blocks from being validated, since instructions refer to signatures
+        let errors = collect_invalid_code_block_errors(true);
+        assert_eq!(errors.len(), 1);
+        assert!(matches!(errors[0].kind(), ErrorKind::InvalidIndex(_)));
+        assert_eq!(errors[0].location().code_block(), None);
+
+        // Once an invalid instruction is found, the remaining instructions in its block are skipped, since the types of
+        // the registers defined by the invalid instruction are not known
+        let errors = collect_invalid_code_block_errors(false);
+        let locations = errors
+            .iter()
+            .map(|error| {
+                let location = error.location();
+                (location.code_block().map(usize::from), location.instruction())
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(locations, [(Some(0), Some(0)), (Some(1), Some(1))]);
+        assert!(errors
+            .iter()
+            .all(|error| matches!(error.kind(), ErrorKind::InvalidInstruction(_))));
+    }
+
+    /// Validates a module containing a code block with no inputs, which returns an integer.
+    fn validate_code_block(temporary_count: usize, instructions: Vec<Instruction>) -> Result<ValidModule<'static>, ErrorKind> {
+        let integer_type = index::TypeSignature::from(0);
+        let mut builder = Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        builder.add_record(record::CodeBlock::new(
+            Vec::new(),
+            vec![integer_type],
+            vec![integer_type; temporary_count],
+            instructions,
+        ));
+        ValidModule::from_builder(builder).map_err(|error| error.kind().clone())
+    }
+
+    #[test]
+    fn registers_must_be_defined_before_use() {
+        let error = validate_code_block(
+            1,
+            vec![Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice())],
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            ErrorKind::InvalidInstruction(invalid) if matches!(
+                invalid.kind(),
+                InvalidInstructionKind::UndefinedRegister { defined_count: 0, .. }
+            )
+        ));
+    }
+
+    #[test]
+    fn instructions_cannot_use_their_own_results() {
+        let error = validate_code_block(
+            1,
+            vec![
+                Instruction::IAdd(Box::new(instruction::IntegerArithmetic::new(
+                    instruction::OverflowBehavior::Ignore,
+                    index::Register::from(0).into(),
+                    instruction::Value::from(1u32),
+                ))),
+                Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice()),
+            ],
+        )
+        .unwrap_err();
+
+        assert!(matches!(
+            error,
+            ErrorKind::InvalidInstruction(invalid) if matches!(invalid.kind(), InvalidInstructionKind::UndefinedRegister { .. })
+        ));
+    }
+
+    #[test]
+    fn declared_temporaries_must_be_defined() {
+        let instructions = || {
+            vec![
+                Instruction::IAdd(Box::new(instruction::IntegerArithmetic::new(
+                    instruction::OverflowBehavior::Ignore,
+                    instruction::Value::from(1u32),
+                    instruction::Value::from(2u32),
+                ))),
+                Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice()),
+            ]
+        };
+
+        validate_code_block(1, instructions()).unwrap();
+        assert!(matches!(
+            validate_code_block(2, instructions()).unwrap_err(),
+            ErrorKind::UndefinedTemporaries {
+                declared: 2,
+                defined: 1,
+                ..
+            }
+        ));
+    }
 }
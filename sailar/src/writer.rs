@@ -65,6 +65,7 @@ impl<W: Write> Writer<W> {
                 }
             }
             record::MetadataField::EntryPoint(entry) => self.write_length(*entry)?,
+            record::MetadataField::NoInline(function) => self.write_length(*function)?,
         }
 
         Ok(())
@@ -120,13 +121,18 @@ impl<W: Write> Writer<W> {
                 self.write_length(*index)
             }
             signature::Type::RawPtr(None) => self.write_byte(signature::TypeCode::VoidPtr.into()),
+            signature::Type::Vector(vector_type) => {
+                self.write_byte(signature::TypeCode::Vector.into())?;
+                self.write_byte(vector_type.lane_count().get())?;
+                self.write_type_signature(&signature::Type::from(vector_type.element_type()))
+            }
         }
     }
 
     fn write_function_signature(&mut self, signature: &signature::Function) -> Result {
         self.write_length(signature.return_type_len())?;
         self.write_length(signature.types().len() - signature.return_type_len())?;
-        signature.types().iter().try_for_each(|index| self.write_length(*index))
+        signature.types().iter().try_for_each(|index| self.write_length(index))
     }
 
     fn write_code_value(&mut self, value: &instruction::Value) -> Result {
@@ -149,7 +155,7 @@ impl<W: Write> Writer<W> {
         self.write_length(block.result_count)?;
         self.write_length(block.temporary_count())?;
         for index in block.register_types.iter() {
-            self.write_length(*index)?;
+            self.write_length(index)?;
         }
 
         self.write_length(block.instructions.len())?;
@@ -166,17 +172,90 @@ impl<W: Write> Writer<W> {
                     self.write_length(arguments.len())?;
                     arguments.iter().try_for_each(|argument| self.write_code_value(argument))?;
                 }
+                Instruction::Br(target, arguments) => {
+                    self.write_length(*target)?;
+                    self.write_length(arguments.len())?;
+                    arguments.iter().try_for_each(|argument| self.write_code_value(argument))?;
+                }
+                Instruction::BrIf(branch) => {
+                    self.write_code_value(branch.condition())?;
+                    self.write_length(branch.true_branch())?;
+                    self.write_length(branch.false_branch())?;
+                    self.write_length(branch.arguments().len())?;
+                    branch
+                        .arguments()
+                        .iter()
+                        .try_for_each(|argument| self.write_code_value(argument))?;
+                }
                 Instruction::IAdd(operands) | Instruction::ISub(operands) => {
                     self.write_byte(u8::from(operands.overflow_behavior()))?;
                     self.write_code_value(operands.x_value())?;
                     self.write_code_value(operands.y_value())?;
                 }
+                Instruction::Cmp(comparison) => {
+                    self.write_byte(u8::from(comparison.kind()))?;
+                    self.write_code_value(comparison.x_value())?;
+                    self.write_code_value(comparison.y_value())?;
+                }
+                Instruction::ThreadSpawn(callee, arguments) => {
+                    self.write_length(*callee)?;
+                    self.write_length(arguments.len())?;
+                    arguments.iter().try_for_each(|argument| self.write_code_value(argument))?;
+                }
+                Instruction::ThreadJoin(signature, thread) => {
+                    self.write_length(*signature)?;
+                    self.write_code_value(thread)?;
+                }
+                Instruction::AtomicLoad(load) => {
+                    self.write_byte(u8::from(load.ordering()))?;
+                    self.write_code_value(load.address())?;
+                }
+                Instruction::AtomicStore(store) => {
+                    self.write_byte(u8::from(store.ordering()))?;
+                    self.write_code_value(store.address())?;
+                    self.write_code_value(store.value())?;
+                }
+                Instruction::AtomicRmw(operation) => {
+                    self.write_byte(u8::from(operation.operation()))?;
+                    self.write_byte(u8::from(operation.ordering()))?;
+                    self.write_code_value(operation.address())?;
+                    self.write_code_value(operation.value())?;
+                }
+                Instruction::AtomicCmpXchg(operation) => {
+                    self.write_byte(u8::from(operation.success_ordering()))?;
+                    self.write_byte(u8::from(operation.failure_ordering()))?;
+                    self.write_code_value(operation.address())?;
+                    self.write_code_value(operation.expected())?;
+                    self.write_code_value(operation.replacement())?;
+                }
+                Instruction::Fence(ordering) => self.write_byte(u8::from(*ordering))?,
+                Instruction::VSplat(value) => self.write_code_value(value)?,
+                Instruction::VExtract(vector, lane) => {
+                    self.write_code_value(vector)?;
+                    self.write_length(*lane)?;
+                }
+                Instruction::VInsert(insert) => {
+                    self.write_code_value(insert.vector())?;
+                    self.write_length(insert.lane())?;
+                    self.write_code_value(insert.value())?;
+                }
+                Instruction::VShuffle(shuffle) => {
+                    self.write_code_value(shuffle.x_value())?;
+                    self.write_code_value(shuffle.y_value())?;
+                    self.write_length(shuffle.lanes().len())?;
+                    shuffle.lanes().iter().try_for_each(|lane| self.write_length(*lane))?;
+                }
             }
         }
 
         Ok(())
     }
 
+    fn write_function_import(&mut self, import: &record::FunctionImport) -> Result {
+        self.write_identifier(&import.symbol)?;
+        self.write_length(import.signature)
+    }
+
     fn write_function_template(&mut self, template: &record::FunctionTemplate) -> Result {
         self.write_export(&template.export)?;
         self.write_length(template.signature)?;
@@ -188,6 +267,23 @@ impl<W: Write> Writer<W> {
         self.write_unsigned_integer(0u8)
     }
 
+    /// Writes a record's type and size, followed by its `content`.
+    pub fn write_record(&mut self, record_type: record::Type, content: &[u8]) -> Result {
+        self.write_byte(u8::from(record_type))?;
+        self.write_length(content.len())?;
+        self.write_all(content)
+    }
+
+    /// Writes an element of an array record, given the `content` of the record that it replaces.
+    ///
+    /// Since their size is not implied by their content, identifiers and byte arrays are preceded by their length.
+    pub fn write_array_element(&mut self, record_type: record::Type, content: &[u8]) -> Result {
+        if matches!(record_type, record::Type::Identifier | record::Type::Data) {
+            self.write_length(content.len())?;
+        }
+        self.write_all(content)
+    }
+
     pub fn write_record_content(&mut self, record: &Record) -> Result {
         match record {
             Record::MetadataField(field) => self.write_metadata_field(field),
@@ -196,6 +292,7 @@ impl<W: Write> Writer<W> {
             Record::FunctionSignature(signature) => self.write_function_signature(signature),
             Record::Data(bytes) => self.write_all(bytes.as_ref()),
             Record::CodeBlock(block) => self.write_code_block(block),
+            Record::FunctionImport(import) => self.write_function_import(import),
             Record::FunctionTemplate(template) => self.write_function_template(template),
             Record::Function(function) => self.write_function(function),
         }
@@ -183,7 +183,7 @@ impl<'input> Inputs<'input> {
 
         let mut transpiler = crate::transpiler::Transpiler::new(&type_cache);
         while let Some((function_instantiation, llvm_function)) = function_cache.next_undefined() {
-            transpiler.translate(function_instantiation, llvm_function)?;
+            transpiler.translate(function_instantiation, llvm_function, &function_cache)?;
         }
 
         match self.main_kind {
@@ -331,3 +331,89 @@ impl<'input, 'context> Compilation<'input, 'context> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compile_to_ir(program: sailar::builder::Builder<'static>) -> String {
+        let module = sailar::validation::ValidModule::from_builder(program).unwrap();
+        let context = LlvmContext::create();
+        let compilation = Inputs::new().with_modules([module]).compile_in_context(&context).unwrap();
+        compilation.output_module().print_to_string().to_string()
+    }
+
+    #[test]
+    fn branches_and_calls_are_translated() {
+        let ir = compile_to_ir(sailar_samples::fibonacci("Test".try_into().unwrap(), 10));
+        assert!(ir.contains("phi"));
+        assert!(ir.contains("call"));
+
+        let ir = compile_to_ir(sailar_samples::sum_loop("Test".try_into().unwrap(), 10));
+        assert!(ir.contains("icmp"));
+        assert!(ir.contains("br i1"));
+    }
+
+    #[test]
+    fn atomic_instructions_are_translated() {
+        use sailar::index;
+        use sailar::instruction::{self, Instruction};
+        use sailar::{record, signature};
+
+        let integer_type = index::TypeSignature::from(0);
+        let pointer_type = index::TypeSignature::from(1);
+        let address = index::Register::from(0);
+        let mut builder = sailar::builder::Builder::new();
+        builder.add_record(signature::Type::from(signature::IntegerType::U32));
+        builder.add_record(signature::Type::RawPtr(Some(integer_type)));
+        builder.add_record(signature::Function::new(vec![pointer_type, integer_type], vec![integer_type]));
+
+        // atomic.store seq_cst %i0 %i1; %t0 = atomic.rmw add acq_rel %i0 %i1;
+        // %t1, %t2 = atomic.cmpxchg acq_rel acquire %i0 %t0 0; fence seq_cst; %t3 = atomic.load acquire %i0; ret %t3
+        builder.add_record(record::CodeBlock::new(
+            vec![pointer_type, integer_type],
+            vec![integer_type],
+            vec![integer_type; 4],
+            vec![
+                Instruction::AtomicStore(Box::new(instruction::AtomicStore::new(
+                    instruction::MemoryOrdering::SequentiallyConsistent,
+                    address.into(),
+                    index::Register::from(1).into(),
+                ))),
+                Instruction::AtomicRmw(Box::new(instruction::AtomicReadModifyWrite::new(
+                    instruction::AtomicOperation::Add,
+                    instruction::MemoryOrdering::AcquireRelease,
+                    address.into(),
+                    index::Register::from(1).into(),
+                ))),
+                Instruction::AtomicCmpXchg(Box::new(instruction::AtomicCompareExchange::new(
+                    instruction::MemoryOrdering::AcquireRelease,
+                    instruction::MemoryOrdering::Acquire,
+                    address.into(),
+                    index::Register::from(2).into(),
+                    0u32.into(),
+                ))),
+                Instruction::Fence(instruction::MemoryOrdering::SequentiallyConsistent),
+                Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(
+                    instruction::MemoryOrdering::Acquire,
+                    address.into(),
+                ))),
+                Instruction::Return(vec![index::Register::from(5).into()].into_boxed_slice()),
+            ],
+        ));
+
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportBorrowed(sailar::identifier::Id::try_from_str("test").unwrap()),
+            index::FunctionSignature::from(0),
+            index::CodeBlock::from(0),
+        ));
+        builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+
+        let ir = compile_to_ir(builder);
+        assert!(ir.contains("store atomic"));
+        assert!(ir.contains("atomicrmw add"));
+        assert!(ir.contains("cmpxchg"));
+        assert!(ir.contains("fence seq_cst"));
+        assert!(ir.contains("load atomic"));
+    }
+}
@@ -64,6 +64,9 @@ pub enum CompilationErrorKind {
     Loader(#[from] sailar_load::error::LoaderError),
     #[error(transparent)]
     InvalidTarget(#[from] crate::target::Error),
+    /// Used when a function contains an instruction that cannot yet be translated to LLVM IR.
+    #[error("translation of {0} is not yet supported")]
+    UnsupportedInstruction(String),
     /// Used when validation of a produced LLVM module fails
     #[error(transparent)]
     InvalidOutput(inkwell::support::LLVMString),
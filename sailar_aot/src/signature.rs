@@ -3,9 +3,9 @@
 use crate::compilation::Result;
 use crate::helper::ptr::ArcEq;
 use inkwell::types::{
-    BasicMetadataTypeEnum as LlvmMetadataType, BasicTypeEnum as LlvmBasicType, FunctionType as LlvmFunctionType,
+    BasicMetadataTypeEnum as LlvmMetadataType, BasicType as _, BasicTypeEnum as LlvmBasicType, FunctionType as LlvmFunctionType,
 };
-use sailar_load::type_system::Type;
+use sailar_load::type_system::{Type, VectorElementType};
 use std::cell::RefCell;
 use std::cmp::PartialEq;
 use std::collections::hash_map;
@@ -25,6 +25,7 @@ impl PartialEq for TypeEq {
             | (Type::RawPtr(None), Type::RawPtr(None)) => true,
             (Type::RawPtr(Some(x)), Type::RawPtr(Some(y))) => Arc::ptr_eq(x, y),
             (Type::FuncPtr(x), Type::FuncPtr(y)) => Arc::ptr_eq(x, y),
+            (Type::Vector(x), Type::Vector(y)) => x == y,
             _ => false,
         }
     }
@@ -42,6 +43,7 @@ impl Hash for TypeEq {
             Type::RawPtr(None) => state.write_u8(0xCA),
             Type::RawPtr(Some(raw)) => Arc::as_ptr(raw).hash(state),
             Type::FuncPtr(func) => Arc::as_ptr(func).hash(state),
+            Type::Vector(vector) => vector.hash(state),
         }
     }
 }
@@ -107,6 +109,12 @@ impl<'module, 'context> Cache<'module, 'context> {
     }
 
     pub fn get_basic_type(&self, ty: Type) -> Result<LlvmBasicType<'context>> {
+        // The pointee type is translated first, since the cache cannot be borrowed during recursive calls.
+        let pointee_type = match &ty {
+            Type::RawPtr(Some(pointee)) => Some(self.get_basic_type(pointee.signature()?.clone())?),
+            _ => None,
+        };
+
         Ok(match self.basic_types.borrow_mut().entry(TypeEq(ty)) {
             hash_map::Entry::Occupied(occupied) => *occupied.get(),
             hash_map::Entry::Vacant(vacant) => {
@@ -119,7 +127,23 @@ impl<'module, 'context> Cache<'module, 'context> {
                     Type::F64 => self.context.f64_type().into(),
                     Type::UAddr | Type::SAddr => self.context.ptr_sized_int_type(self.target_data, None).into(),
                     Type::RawPtr(None) => self.context.i8_type().ptr_type(inkwell::AddressSpace::Generic).into(),
-                    // Note: RawPtr and FuncPtr may require recursive calls, will conflict with borrowing of RefCell.
+                    Type::RawPtr(Some(_)) => pointee_type
+                        .expect("pointee type should have been translated")
+                        .ptr_type(inkwell::AddressSpace::Generic)
+                        .into(),
+                    Type::Vector(vector_type) => {
+                        let lane_count = u32::from(vector_type.lane_count().get());
+                        match vector_type.element_type() {
+                            VectorElementType::Integer(integer_type) => self
+                                .context
+                                .custom_width_int_type(integer_type.size().bit_size().get().into())
+                                .vec_type(lane_count),
+                            VectorElementType::F32 => self.context.f32_type().vec_type(lane_count),
+                            VectorElementType::F64 => self.context.f64_type().vec_type(lane_count),
+                        }
+                        .into()
+                    }
+                    // Note: FuncPtr may require recursive calls, will conflict with borrowing of RefCell.
                     bad => todo!("add support for {:?}", bad),
                 };
 
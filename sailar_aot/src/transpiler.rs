@@ -1,42 +1,209 @@
 //! Module for translating from SAILAR bytecode to LLVM IR.
 
 use crate::compilation::Result;
+use crate::error;
 use crate::helper::ptr::ArcEq;
 use inkwell::basic_block::BasicBlock as LlvmBlock;
 use inkwell::builder::Builder as LlvmBuilder;
 use inkwell::values::BasicValueEnum as LlvmBasicValue;
 use inkwell::values::FunctionValue as LlvmFunction;
-use sailar_load::code_block::{Code, TypedInstruction};
+use inkwell::values::IntValue as LlvmIntValue;
+use inkwell::values::PhiValue as LlvmPhi;
+use inkwell::{AtomicOrdering as LlvmAtomicOrdering, AtomicRMWBinOp as LlvmAtomicOperation, IntPredicate};
+use sailar::instruction::{AtomicOperation, ComparisonKind, MemoryOrdering, OverflowBehavior};
+use sailar_load::code_block::{Code, TypedInstruction, TypedValue};
 use sailar_load::type_system::Type;
 use std::collections::hash_map;
 use std::sync::Arc;
 
+/// An LLVM basic block corresponding to a SAILAR code block, along with the phi nodes containing the values of its input
+/// registers.
+#[derive(Clone)]
+struct Block<'context> {
+    block: LlvmBlock<'context>,
+    inputs: Arc<[LlvmPhi<'context>]>,
+}
+
 /// Generates LLVM basic blocks containing translated SAILAR byte code.
 pub struct Transpiler<'cache, 'module, 'context> {
     builder: LlvmBuilder<'context>,
+    /// Used to insert phi nodes into newly created blocks, without changing the position of the main builder.
+    phi_builder: LlvmBuilder<'context>,
     type_cache: &'cache crate::signature::Cache<'module, 'context>,
-    block_lookup: rustc_hash::FxHashMap<ArcEq<Code>, LlvmBlock<'context>>,
-    undefined_blocks: Vec<(Arc<Code>, LlvmBlock<'context>)>,
+    block_lookup: rustc_hash::FxHashMap<ArcEq<Code>, Block<'context>>,
+    undefined_blocks: Vec<(Arc<Code>, Block<'context>)>,
+    input_registers: Arc<[LlvmPhi<'context>]>,
+    temporary_registers: Vec<LlvmBasicValue<'context>>,
+}
+
+fn unsupported<T>(instruction: &TypedInstruction) -> Result<T> {
+    Err(error::CompilationErrorKind::UnsupportedInstruction(format!("{:?}", instruction)).into())
+}
+
+fn integer_sign(value_type: &Type) -> bool {
+    match value_type {
+        Type::FixedInteger(integer_type) => integer_type.sign() == sailar::signature::IntegerSign::Signed,
+        Type::SAddr => true,
+        _ => false,
+    }
+}
+
+/// Gets the LLVM predicate for a comparison, or `None` if the comparison kind cannot yet be translated.
+fn convert_comparison(kind: ComparisonKind, signed: bool) -> Option<IntPredicate> {
+    Some(match (kind, signed) {
+        (ComparisonKind::Equal, _) => IntPredicate::EQ,
+        (ComparisonKind::NotEqual, _) => IntPredicate::NE,
+        (ComparisonKind::LessThan, false) => IntPredicate::ULT,
+        (ComparisonKind::LessThan, true) => IntPredicate::SLT,
+        (ComparisonKind::GreaterThan, false) => IntPredicate::UGT,
+        (ComparisonKind::GreaterThan, true) => IntPredicate::SGT,
+        (ComparisonKind::LessThanOrEqual, false) => IntPredicate::ULE,
+        (ComparisonKind::LessThanOrEqual, true) => IntPredicate::SLE,
+        (ComparisonKind::GreaterThanOrEqual, false) => IntPredicate::UGE,
+        (ComparisonKind::GreaterThanOrEqual, true) => IntPredicate::SGE,
+        _ => return None,
+    })
+}
+
+fn convert_ordering(ordering: MemoryOrdering) -> LlvmAtomicOrdering {
+    match ordering {
+        MemoryOrdering::Relaxed => LlvmAtomicOrdering::Monotonic,
+        MemoryOrdering::Acquire => LlvmAtomicOrdering::Acquire,
+        MemoryOrdering::Release => LlvmAtomicOrdering::Release,
+        MemoryOrdering::AcquireRelease => LlvmAtomicOrdering::AcquireRelease,
+        _ => LlvmAtomicOrdering::SequentiallyConsistent,
+    }
+}
+
+/// Gets the LLVM atomic operation, or `None` if the operation cannot yet be translated.
+fn convert_atomic_operation(operation: AtomicOperation) -> Option<LlvmAtomicOperation> {
+    Some(match operation {
+        AtomicOperation::Exchange => LlvmAtomicOperation::Xchg,
+        AtomicOperation::Add => LlvmAtomicOperation::Add,
+        AtomicOperation::Sub => LlvmAtomicOperation::Sub,
+        AtomicOperation::And => LlvmAtomicOperation::And,
+        AtomicOperation::Or => LlvmAtomicOperation::Or,
+        AtomicOperation::Xor => LlvmAtomicOperation::Xor,
+        _ => return None,
+    })
 }
 
 impl<'cache, 'module, 'context> Transpiler<'cache, 'module, 'context> {
     pub fn new(type_cache: &'cache crate::signature::Cache<'module, 'context>) -> Self {
         Self {
             builder: type_cache.context().create_builder(),
+            phi_builder: type_cache.context().create_builder(),
             type_cache,
             block_lookup: Default::default(),
             undefined_blocks: Vec::new(),
+            input_registers: Arc::new([]),
+            temporary_registers: Vec::new(),
         }
     }
 
-    fn get_or_add_block(&mut self, function: LlvmFunction<'context>, code: Arc<Code>) -> LlvmBlock<'context> {
-        match self.block_lookup.entry(ArcEq::from(code)) {
-            hash_map::Entry::Occupied(occupied) => *occupied.get(),
+    fn get_or_add_block(&mut self, function: LlvmFunction<'context>, code: &Arc<Code>) -> Result<Block<'context>> {
+        Ok(match self.block_lookup.entry(ArcEq::from(code.clone())) {
+            hash_map::Entry::Occupied(occupied) => occupied.get().clone(),
             hash_map::Entry::Vacant(vacant) => {
                 let block = self.type_cache.context().append_basic_block(function, "");
-                self.undefined_blocks.push((ArcEq::to_arc(vacant.key()), block));
-                *vacant.insert(block)
+                self.phi_builder.position_at_end(block);
+
+                let input_types = code.input_types()?;
+                let mut inputs = Vec::with_capacity(input_types.len());
+                for input_type in input_types.iter() {
+                    let input_type = self.type_cache.get_basic_type(input_type.signature()?.clone())?;
+                    inputs.push(self.phi_builder.build_phi(input_type, ""));
+                }
+
+                let block = Block {
+                    block,
+                    inputs: inputs.into(),
+                };
+
+                self.undefined_blocks.push((code.clone(), block.clone()));
+                vacant.insert(block).clone()
             }
+        })
+    }
+
+    /// Branches to the `target` block, providing the `arguments` as the values of its input registers.
+    fn add_branch_arguments(&mut self, target: &Block<'context>, arguments: &[TypedValue]) -> Result<()> {
+        let source = self
+            .builder
+            .get_insert_block()
+            .expect("builder should be positioned in a block");
+        for (phi, argument) in target.inputs.iter().zip(arguments) {
+            let value = self.translate_value(argument)?;
+            phi.add_incoming(&[(&value as &dyn inkwell::values::BasicValue<'context>, source)]);
+        }
+        Ok(())
+    }
+
+    fn get_target_block(
+        &mut self,
+        function: LlvmFunction<'context>,
+        code: &Code,
+        target: sailar::index::CodeBlock,
+    ) -> Result<Block<'context>> {
+        let module = sailar_load::module::Module::upgrade_weak(code.module())?;
+        self.get_or_add_block(function, &module.code_blocks()[usize::from(target)])
+    }
+
+    /// Performs integer addition or subtraction, returning the result and a flag indicating if an overflow occured.
+    fn translate_arithmetic(
+        &self,
+        is_addition: bool,
+        signed: bool,
+        x: LlvmIntValue<'context>,
+        y: LlvmIntValue<'context>,
+    ) -> (LlvmIntValue<'context>, LlvmIntValue<'context>) {
+        let builder = &self.builder;
+        let result = if is_addition {
+            builder.build_int_add(x, y, "")
+        } else {
+            builder.build_int_sub(x, y, "")
+        };
+
+        let zero = x.get_type().const_zero();
+        let overflowed = match (is_addition, signed) {
+            (true, false) => builder.build_int_compare(IntPredicate::ULT, result, x, ""),
+            (false, false) => builder.build_int_compare(IntPredicate::ULT, x, y, ""),
+            (true, true) => {
+                // Overflow occurs when both operands have the same sign, but the result has a different sign.
+                let x_changed = builder.build_xor(x, result, "");
+                let y_changed = builder.build_xor(y, result, "");
+                let changed = builder.build_and(x_changed, y_changed, "");
+                builder.build_int_compare(IntPredicate::SLT, changed, zero, "")
+            }
+            (false, true) => {
+                // Overflow occurs when the operands have different signs, and the sign of the result differs from x.
+                let operands_differ = builder.build_xor(x, y, "");
+                let x_changed = builder.build_xor(x, result, "");
+                let changed = builder.build_and(operands_differ, x_changed, "");
+                builder.build_int_compare(IntPredicate::SLT, changed, zero, "")
+            }
+        };
+
+        (result, overflowed)
+    }
+
+    /// Gets the value that is used when an operation saturates.
+    fn saturated_value(&self, is_addition: bool, signed: bool, x: LlvmIntValue<'context>) -> LlvmIntValue<'context> {
+        let integer_type = x.get_type();
+        if signed {
+            // Overflow can only occur in the direction of the sign of x.
+            let minimum = integer_type
+                .const_int(1, false)
+                .const_shl(integer_type.const_int(u64::from(integer_type.get_bit_width() - 1), false));
+            let maximum = minimum.const_not();
+            let is_negative = self
+                .builder
+                .build_int_compare(IntPredicate::SLT, x, integer_type.const_zero(), "");
+            self.builder.build_select(is_negative, minimum, maximum, "").into_int_value()
+        } else if is_addition {
+            integer_type.const_all_ones()
+        } else {
+            integer_type.const_zero()
         }
     }
 
@@ -69,7 +236,10 @@ impl<'cache, 'module, 'context> Transpiler<'cache, 'module, 'context> {
                     bad => todo!("add translation for {:?}", bad),
                 }
             }
-            sailar::instruction::Value::IndexedRegister(_) => todo!("registers not yet supported"),
+            sailar::instruction::Value::IndexedRegister(register) => match usize::from(*register) {
+                index if index >= self.input_registers.len() => Ok(self.temporary_registers[index - self.input_registers.len()]),
+                index => Ok(self.input_registers[index].as_basic_value()),
+            },
         }
     }
 
@@ -78,16 +248,28 @@ impl<'cache, 'module, 'context> Transpiler<'cache, 'module, 'context> {
         &mut self,
         function: Arc<sailar_load::function::Function>,
         destination: LlvmFunction<'context>,
+        function_cache: &crate::function::Cache<'_, 'module, 'context>,
     ) -> Result<()> {
         self.block_lookup.clear();
         self.undefined_blocks.clear();
 
         let entry_block = function.template()?.as_definition()?.entry_block()?;
 
-        self.get_or_add_block(destination, entry_block.clone());
+        // The entry block may be the target of a branch, so its inputs are provided by a separate block containing the
+        // function's parameters.
+        let prologue = self.type_cache.context().append_basic_block(destination, "");
+        let entry_block = self.get_or_add_block(destination, entry_block)?;
+        self.builder.position_at_end(prologue);
+        for (phi, parameter) in entry_block.inputs.iter().zip(destination.get_param_iter()) {
+            phi.add_incoming(&[(&parameter as &dyn inkwell::values::BasicValue<'context>, prologue)]);
+        }
+        self.builder.build_unconditional_branch(entry_block.block);
 
         while let Some((sailar_block, llvm_block)) = self.undefined_blocks.pop() {
-            self.builder.position_at_end(llvm_block);
+            self.builder.position_at_end(llvm_block.block);
+            self.input_registers = llvm_block.inputs;
+            self.temporary_registers.clear();
+
             for instruction in sailar_block.typed_instructions()?.iter() {
                 match instruction {
                     TypedInstruction::Nop | TypedInstruction::Break => (),
@@ -99,9 +281,210 @@ impl<'cache, 'module, 'context> Transpiler<'cache, 'module, 'context> {
                                 actual_return_value = self.translate_value(value)?;
                                 Some(&actual_return_value)
                             }
-                            _ => todo!("multiple return values not yet supported"),
+                            _ => return unsupported(instruction),
                         });
-                    } //bad => todo!("add support for {:?}", bad),
+                    }
+                    TypedInstruction::Branch(target, arguments) => {
+                        let target = self.get_target_block(destination, &sailar_block, *target)?;
+                        self.add_branch_arguments(&target, arguments)?;
+                        self.builder.build_unconditional_branch(target.block);
+                    }
+                    TypedInstruction::BranchIf(branch) => {
+                        let condition = self.translate_value(branch.condition())?.into_int_value();
+                        let condition =
+                            self.builder
+                                .build_int_compare(IntPredicate::NE, condition, condition.get_type().const_zero(), "");
+                        let true_block = self.get_target_block(destination, &sailar_block, branch.true_branch())?;
+                        let false_block = self.get_target_block(destination, &sailar_block, branch.false_branch())?;
+                        self.add_branch_arguments(&true_block, branch.arguments())?;
+                        self.add_branch_arguments(&false_block, branch.arguments())?;
+                        self.builder
+                            .build_conditional_branch(condition, true_block.block, false_block.block);
+                    }
+                    TypedInstruction::Call(call) => {
+                        if call.result_types().len() > 1 {
+                            return unsupported(instruction);
+                        }
+
+                        let module = sailar_load::module::Module::upgrade_weak(sailar_block.module())?;
+                        let callee = function_cache.get_or_define(module.functions()[usize::from(call.callee())].clone())?;
+                        let mut arguments = Vec::with_capacity(call.arguments().len());
+                        for argument in call.arguments().iter() {
+                            arguments.push(self.translate_value(argument)?.into());
+                        }
+
+                        let result = self.builder.build_call(callee, &arguments, "");
+                        if let Some(value) = result.try_as_basic_value().left() {
+                            self.temporary_registers.push(value);
+                        }
+                    }
+                    TypedInstruction::IAdd(operation) | TypedInstruction::ISub(operation) => {
+                        let is_addition = matches!(instruction, TypedInstruction::IAdd(_));
+                        if let Type::Vector(_) = operation.result_type() {
+                            if operation.overflow_behavior() != OverflowBehavior::Ignore {
+                                return unsupported(instruction);
+                            }
+
+                            let x = self.translate_value(operation.x_value())?.into_vector_value();
+                            let y = self.translate_value(operation.y_value())?.into_vector_value();
+                            let result = if is_addition {
+                                self.builder.build_int_add(x, y, "")
+                            } else {
+                                self.builder.build_int_sub(x, y, "")
+                            };
+                            self.temporary_registers.push(result.into());
+                            continue;
+                        }
+
+                        let signed = integer_sign(operation.result_type());
+                        let x = self.translate_value(operation.x_value())?.into_int_value();
+                        let y = self.translate_value(operation.y_value())?.into_int_value();
+                        let (result, overflowed) = self.translate_arithmetic(is_addition, signed, x, y);
+
+                        match operation.overflow_behavior() {
+                            OverflowBehavior::Ignore => self.temporary_registers.push(result.into()),
+                            OverflowBehavior::Flag => {
+                                self.temporary_registers.push(result.into());
+                                let flag_type = sailar_block.temporary_types()?[self.temporary_registers.len()].signature()?;
+                                let flag_type = self.type_cache.get_basic_type(flag_type.clone())?.into_int_type();
+                                self.temporary_registers
+                                    .push(self.builder.build_int_z_extend(overflowed, flag_type, "").into());
+                            }
+                            OverflowBehavior::Saturate => {
+                                let saturated = self.saturated_value(is_addition, signed, x);
+                                self.temporary_registers
+                                    .push(self.builder.build_select(overflowed, saturated, result, ""));
+                            }
+                            _ => return unsupported(instruction),
+                        }
+                    }
+                    TypedInstruction::Cmp(comparison) => {
+                        let predicate =
+                            match convert_comparison(comparison.kind(), integer_sign(comparison.x_value().value_type())) {
+                                Some(predicate) => predicate,
+                                None => return unsupported(instruction),
+                            };
+                        let x = self.translate_value(comparison.x_value())?.into_int_value();
+                        let y = self.translate_value(comparison.y_value())?.into_int_value();
+                        let result = self.builder.build_int_compare(predicate, x, y, "");
+                        let result_type = self
+                            .type_cache
+                            .get_basic_type(comparison.result_type().clone())?
+                            .into_int_type();
+                        self.temporary_registers
+                            .push(self.builder.build_int_z_extend(result, result_type, "").into());
+                    }
+                    TypedInstruction::AtomicLoad(load) => {
+                        let address = self.translate_value(load.address())?.into_pointer_value();
+                        let value = self.builder.build_load(address, "");
+                        value
+                            .as_instruction_value()
+                            .expect("load should be an instruction")
+                            .set_atomic_ordering(convert_ordering(load.ordering()))
+                            .expect("ordering should be valid for loads");
+                        self.temporary_registers.push(value);
+                    }
+                    TypedInstruction::AtomicStore(store) => {
+                        let address = self.translate_value(store.address())?.into_pointer_value();
+                        let value = self.translate_value(store.value())?;
+                        self.builder
+                            .build_store(address, value)
+                            .set_atomic_ordering(convert_ordering(store.ordering()))
+                            .expect("ordering should be valid for stores");
+                    }
+                    TypedInstruction::AtomicRmw(operation) => {
+                        let atomic_operation = match convert_atomic_operation(operation.operation()) {
+                            Some(atomic_operation) => atomic_operation,
+                            None => return unsupported(instruction),
+                        };
+                        let address = self.translate_value(operation.address())?.into_pointer_value();
+                        let value = self.translate_value(operation.value())?.into_int_value();
+                        let previous = self
+                            .builder
+                            .build_atomicrmw(atomic_operation, address, value, convert_ordering(operation.ordering()))
+                            .expect("value should be an integer of the pointee type");
+                        self.temporary_registers.push(previous.into());
+                    }
+                    TypedInstruction::AtomicCmpXchg(operation) => {
+                        let address = self.translate_value(operation.address())?.into_pointer_value();
+                        let expected = self.translate_value(operation.expected())?;
+                        let replacement = self.translate_value(operation.replacement())?;
+                        let result = self
+                            .builder
+                            .build_cmpxchg(
+                                address,
+                                expected,
+                                replacement,
+                                convert_ordering(operation.success_ordering()),
+                                convert_ordering(operation.failure_ordering()),
+                            )
+                            .expect("values should be integers of the pointee type");
+
+                        let previous = self.builder.build_extract_value(result, 0, "").unwrap();
+                        let replaced = self.builder.build_extract_value(result, 1, "").unwrap().into_int_value();
+                        let success_type = self
+                            .type_cache
+                            .get_basic_type(operation.success_type().clone())?
+                            .into_int_type();
+
+                        self.temporary_registers.push(previous);
+                        self.temporary_registers
+                            .push(self.builder.build_int_z_extend(replaced, success_type, "").into());
+                    }
+                    TypedInstruction::Fence(ordering) => {
+                        self.builder.build_fence(convert_ordering(*ordering), 0, "");
+                    }
+                    TypedInstruction::VSplat(splat) => {
+                        let vector_type = self
+                            .type_cache
+                            .get_basic_type(Type::Vector(splat.result_type()))?
+                            .into_vector_type();
+                        let index_type = self.type_cache.context().i32_type();
+                        let value = self.translate_value(splat.value())?;
+
+                        // Inserts the value into the first lane, then copies the first lane into all other lanes
+                        let first =
+                            self.builder
+                                .build_insert_element(vector_type.get_undef(), value, index_type.const_zero(), "");
+                        let mask = index_type.vec_type(vector_type.get_size()).const_zero();
+                        let result = self.builder.build_shuffle_vector(first, vector_type.get_undef(), mask, "");
+                        self.temporary_registers.push(result.into());
+                    }
+                    TypedInstruction::VExtract(extract) => {
+                        let vector = self.translate_value(extract.vector())?.into_vector_value();
+                        let lane = self
+                            .type_cache
+                            .context()
+                            .i32_type()
+                            .const_int(u64::from(extract.lane()), false);
+                        self.temporary_registers
+                            .push(self.builder.build_extract_element(vector, lane, ""));
+                    }
+                    TypedInstruction::VInsert(insert) => {
+                        let vector = self.translate_value(insert.vector())?.into_vector_value();
+                        let value = self.translate_value(insert.value())?;
+                        let lane = self
+                            .type_cache
+                            .context()
+                            .i32_type()
+                            .const_int(u64::from(insert.lane()), false);
+                        self.temporary_registers
+                            .push(self.builder.build_insert_element(vector, value, lane, "").into());
+                    }
+                    TypedInstruction::VShuffle(shuffle) => {
+                        let x = self.translate_value(shuffle.x_value())?.into_vector_value();
+                        let y = self.translate_value(shuffle.y_value())?.into_vector_value();
+                        let index_type = self.type_cache.context().i32_type();
+                        let mask = shuffle
+                            .lanes()
+                            .iter()
+                            .map(|lane| index_type.const_int(u64::from(*lane), false))
+                            .collect::<Vec<_>>();
+                        let mask = inkwell::types::VectorType::const_vector(&mask);
+                        self.temporary_registers
+                            .push(self.builder.build_shuffle_vector(x, y, mask, "").into());
+                    }
+                    TypedInstruction::ThreadSpawn(_) | TypedInstruction::ThreadJoin(_) => return unsupported(instruction),
                 }
             }
         }
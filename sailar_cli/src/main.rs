@@ -0,0 +1,338 @@
+//! Command line tool for assembling, inspecting, running, and compiling SAILAR modules
+
+use sailar::builder::Builder;
+use sailar::validation::ValidModule;
+use sailar_vm::{call_stack, entry_point, runtime};
+use std::io::Write as _;
+use std::path::{Path, PathBuf};
+
+type Result<T = ()> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+/// Tool for working with SAILAR modules
+#[derive(Debug, clap::Parser)]
+#[clap(version, about)]
+struct Arguments {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Checks that a SAILAR binary module is valid, reporting every error that was found
+    Validate {
+        /// Path to the SAILAR binary module
+        input: PathBuf,
+    },
+    /// Prints the records and the raw contents of a SAILAR binary module
+    Dump {
+        /// Path to the SAILAR binary module
+        input: PathBuf,
+        /// Only print the records of the module
+        #[clap(long, conflicts_with = "hex")]
+        records: bool,
+        /// Only print the contents of the module in hexadecimal
+        #[clap(long)]
+        hex: bool,
+    },
+    /// Assembles a SAILAR assembly file into a binary module
+    Asm {
+        /// Path to the SAILAR assembly file
+        input: PathBuf,
+        /// Path to the file containing the assembled SAILAR binary module
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+    },
+    /// Disassembles a SAILAR binary module into SAILAR assembly
+    Disasm {
+        /// Path to the SAILAR binary module
+        input: PathBuf,
+        /// Path to the file containing the disassembled text module
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        /// Number of spaces used to indent instructions
+        #[clap(long, default_value_t = 4, conflicts_with = "tabs")]
+        spaces: u8,
+        /// Indent instructions with tabs instead of spaces
+        #[clap(long)]
+        tabs: bool,
+        /// Refer to records and registers by their raw indices, instead of by generated names
+        #[clap(long)]
+        indices: bool,
+    },
+    /// Executes the entry point of a SAILAR binary module, exiting with the first value that it returns
+    Run {
+        /// Path to the SAILAR binary module
+        input: PathBuf,
+        /// Sets the maximum number of frames in the call stack before a stack overflow occurs
+        #[clap(long)]
+        call_stack_size: Option<std::num::NonZeroUsize>,
+        /// Integer arguments passed to the entry point
+        #[clap(last = true)]
+        arguments: Vec<String>,
+    },
+    /// Compiles a SAILAR binary module into an object file, requires the compile feature
+    Compile {
+        /// Path to the SAILAR binary module
+        input: PathBuf,
+        /// Path to the object file or LLVM bitcode file
+        #[clap(long, short)]
+        output: Option<PathBuf>,
+        /// Writes LLVM bitcode instead of an object file
+        #[clap(long)]
+        emit_llvm: bool,
+    },
+}
+
+fn read_module(path: &Path) -> Result<Builder<'static>> {
+    let input = std::io::BufReader::new(std::fs::File::open(path)?);
+    Ok(Builder::from_reader(sailar::reader::Reader::new(input))?)
+}
+
+fn validate(input: &Path) -> Result {
+    let contents = std::fs::read(input)?;
+    match ValidModule::from_slice_collecting_errors(&contents)? {
+        Ok(_) => {
+            println!("{} is valid", input.display());
+            Ok(())
+        }
+        Err(errors) => {
+            for e in errors.iter() {
+                eprintln!("error: {}", e);
+            }
+
+            Err(Box::from(format!("{} contains {} errors", input.display(), errors.len())))
+        }
+    }
+}
+
+fn dump(input: &Path, records_only: bool, hex_only: bool) -> Result {
+    let contents = std::fs::read(input)?;
+    let module = Builder::from_reader(sailar::reader::Reader::new(contents.as_slice()))?;
+
+    let output = std::io::stdout();
+    let mut output = std::io::BufWriter::new(output.lock());
+
+    if !hex_only {
+        let format_version = module.format_version();
+        writeln!(output, "format version {}.{}", format_version.major, format_version.minor)?;
+        for (index, record) in module.records().iter().enumerate() {
+            writeln!(output, "{:>4} {:?}", index, record)?;
+        }
+    }
+
+    if !records_only {
+        if !hex_only {
+            writeln!(output)?;
+        }
+
+        // Safety: The contents were successfully read as a module above.
+        let raw_module = unsafe { sailar::binary::RawModule::from_vec_unchecked(contents) };
+        write!(output, "{}", raw_module.hex_dump_to_string())?;
+    }
+
+    output.flush()?;
+    Ok(())
+}
+
+fn assemble(input_path: &Path, output: Option<&Path>) -> Result {
+    let input = sailasm::preprocessor::preprocess_file(input_path)?;
+    let mut tree = None;
+
+    match sailasm::assemble_preprocessed(&input, &mut tree) {
+        Ok(module) => {
+            let default_output;
+            let output = if let Some(path) = output {
+                path
+            } else {
+                default_output = input_path.with_extension("sail");
+                &default_output
+            };
+
+            module.write_to(std::fs::File::create(output)?).map_err(Box::from)
+        }
+        Err(errors) => {
+            let paths = input
+                .files()
+                .iter()
+                .map(|file| file.path().to_string_lossy())
+                .collect::<Vec<_>>();
+
+            let mut renderer = sailasm::diagnostics::Renderer::new(input.files()[0].contents()).with_path(&paths[0]);
+            for (file, path) in input.files().iter().zip(paths.iter()).skip(1) {
+                renderer = renderer.with_file(path, file.contents());
+            }
+
+            let output = std::io::stderr();
+            let mut output = std::io::BufWriter::new(output.lock());
+            for e in errors.iter() {
+                writeln!(output, "{}", renderer.render_to_string(e))?;
+            }
+
+            Err(Box::from(format!("failed with {} errors", errors.len())))
+        }
+    }
+}
+
+fn disassemble(input: &Path, output: Option<&Path>, options: &dasmdl::disassembler::Options) -> Result {
+    let output_path = output.map_or_else(|| input.with_extension("sailas"), Path::to_path_buf);
+    let mut output = std::io::BufWriter::new(std::fs::File::create(output_path)?);
+    dasmdl::disassembler::disassemble(&mut output, &read_module(input)?, options)?;
+    output.flush()?;
+    Ok(())
+}
+
+/// Executes the entry point of a module, returning the exit code of the program.
+fn run(input: &Path, call_stack_size: Option<std::num::NonZeroUsize>, arguments: &[String]) -> Result<i32> {
+    let module = ValidModule::from_builder(read_module(input)?)?;
+    let loader = sailar_load::state::Configuration::new().create_state();
+    let module = loader.load_module(module).unwrap_or_else(|existing| existing);
+    let entry_point = module
+        .entry_point()
+        .ok_or_else(|| format!("{} does not specify an entry point", input.display()))?;
+
+    let mut configuration = runtime::Configuration::new();
+    if let Some(size) = call_stack_size {
+        configuration = configuration.call_stack_size(call_stack::Size::new(size));
+    }
+
+    let return_values = entry_point::execute(&configuration.initialize_runtime(), entry_point, arguments)?;
+    Ok(entry_point::exit_code(&return_values))
+}
+
+#[cfg(feature = "compile")]
+fn compile(input: &Path, output: Option<&Path>, emit_llvm: bool) -> Result {
+    let module = ValidModule::from_builder(read_module(input)?)?;
+    let mut context = None;
+    let compilation = sailar_aot::compilation::Inputs::new()
+        .with_modules(std::iter::once(module))
+        .compile(&mut context)?;
+
+    if emit_llvm {
+        let output = output.map_or_else(|| input.with_extension("bc"), Path::to_path_buf);
+        compilation.write_llvm_bitcode_to_path(&output)?;
+    } else {
+        let output = output.map_or_else(
+            || input.with_extension(if cfg!(windows) { "obj" } else { "o" }),
+            Path::to_path_buf,
+        );
+
+        compilation
+            .write_object_code_to_path(inkwell::targets::FileType::Object, &output)
+            .map_err(|e| e.to_string())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "compile"))]
+fn compile(_: &Path, _: Option<&Path>, _: bool) -> Result {
+    Err(Box::from(
+        "compilation is not supported, since sailar was built without the compile feature",
+    ))
+}
+
+fn main() -> Result {
+    let arguments: Arguments = clap::Parser::parse();
+
+    match arguments.command {
+        Command::Validate { input } => validate(&input),
+        Command::Dump { input, records, hex } => dump(&input, records, hex),
+        Command::Asm { input, output } => assemble(&input, output.as_deref()),
+        Command::Disasm {
+            input,
+            output,
+            spaces,
+            tabs,
+            indices,
+        } => {
+            let options = dasmdl::disassembler::Options {
+                indentation: if tabs {
+                    dasmdl::disassembler::Indentation::Tabs
+                } else {
+                    dasmdl::disassembler::Indentation::Spaces(spaces)
+                },
+                references: if indices {
+                    dasmdl::disassembler::ReferenceStyle::Index
+                } else {
+                    dasmdl::disassembler::ReferenceStyle::Symbolic
+                },
+            };
+
+            disassemble(&input, output.as_deref(), &options)
+        }
+        Command::Run {
+            input,
+            call_stack_size,
+            arguments,
+        } => std::process::exit(run(&input, call_stack_size, &arguments)?),
+        Command::Compile {
+            input,
+            output,
+            emit_llvm,
+        } => compile(&input, output.as_deref(), emit_llvm),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    const ADD: &str = r#".format major 0
+.format minor 19
+.metadata id "Add" 1.0
+.metadata main @main_instance
+.signature @s32 type s32
+.signature @main_signature function (@s32, @s32) -> (@s32)
+
+.code @main_body ($x:@s32, $y:@s32) -> (@s32)
+    $sum = iadd $x $y
+    ret $sum
+
+.define @main function export "main" signature @main_signature
+/body @main_body
+
+.instantiate @main_instance function definition @main
+"#;
+
+    /// Assembles the `ADD` program into a module in a temporary directory unique to the test.
+    fn assemble_add(test_name: &str) -> PathBuf {
+        let directory = std::env::temp_dir().join(format!("sailar_cli_{}_{}", std::process::id(), test_name));
+        std::fs::create_dir_all(&directory).unwrap();
+        let input = directory.join("add.sailasm");
+        std::fs::write(&input, ADD).unwrap();
+        super::assemble(&input, None).unwrap();
+        input.with_extension("sail")
+    }
+
+    fn run(module: &std::path::Path, arguments: &[&str]) -> super::Result<i32> {
+        let arguments = arguments.iter().map(|argument| argument.to_string()).collect::<Vec<_>>();
+        super::run(module, None, &arguments)
+    }
+
+    #[test]
+    fn assembled_module_is_run() {
+        let module = assemble_add("run");
+        assert_eq!(run(&module, &["40", "2"]).unwrap(), 42);
+        assert_eq!(run(&module, &["-5", "2"]).unwrap(), -3);
+    }
+
+    #[test]
+    fn argument_count_mismatch_is_error() {
+        let module = assemble_add("count");
+        let error = run(&module, &["1"]).unwrap_err();
+        assert_eq!(error.to_string(), "entry point expects 2 arguments, but 1 were provided");
+    }
+
+    #[test]
+    fn invalid_arguments_are_errors() {
+        let module = assemble_add("invalid");
+        assert_eq!(
+            run(&module, &["one", "2"]).unwrap_err().to_string(),
+            "argument \"one\" is not an integer"
+        );
+        assert_eq!(
+            run(&module, &["2147483648", "2"]).unwrap_err().to_string(),
+            "argument 2147483648 is out of range for type s32"
+        );
+    }
+}
@@ -0,0 +1,471 @@
+//! Reusable analyses of SAILAR code, such as control flow graphs and register liveness.
+
+use crate::code_block::Code;
+use crate::error;
+use crate::module;
+use sailar::index;
+use sailar::instruction::{Instruction, Value};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// The control flow graph of a function, containing the code blocks reachable from its entry block.
+///
+/// # Examples
+///
+/// ```
+/// # use sailar::identifier::Identifier;
+/// # use sailar::index;
+/// # use sailar::module_builder::ModuleBuilder;
+/// # use sailar::signature;
+/// let mut builder = ModuleBuilder::new();
+/// let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U8));
+/// let signature = builder.function_signature([integer_type], []);
+/// let main = builder.define_function(Identifier::try_from_str("main")?, signature);
+///
+/// // The loop block branches to itself until its input is zero
+/// let exit_block = builder.code_block([integer_type], []).ret(Vec::new());
+/// let loop_block = index::CodeBlock::from(1);
+/// let body = builder.code_block([integer_type], []);
+/// let counter = body.input(0);
+/// assert_eq!(body.br_if(counter, loop_block, exit_block, vec![counter.into()]), loop_block);
+///
+/// let body = builder.entry_block(main);
+/// let input = body.input(0);
+/// let entry_block = body.br(loop_block, vec![input.into()]);
+///
+/// let loader = sailar_load::state::Configuration::new().create_state();
+/// let module = loader.load_module(builder.build().unwrap()).unwrap();
+/// let graph = module.functions()[0].control_flow_graph()?;
+/// assert_eq!(graph.entry_block(), entry_block);
+/// assert_eq!(graph.successors(loop_block), [loop_block, exit_block]);
+/// assert_eq!(graph.predecessors(loop_block), [entry_block, loop_block]);
+/// assert_eq!(graph.immediate_dominator(exit_block), Some(loop_block));
+/// assert!(graph.dominates(entry_block, exit_block));
+/// assert!(!graph.dominates(exit_block, loop_block));
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub struct ControlFlowGraph {
+    /// The reachable code blocks in reverse postorder, starting with the entry block.
+    blocks: Box<[Arc<Code>]>,
+    positions: rustc_hash::FxHashMap<index::CodeBlock, usize>,
+    successors: Box<[Box<[index::CodeBlock]>]>,
+    predecessors: Box<[Box<[index::CodeBlock]>]>,
+    /// The position of the immediate dominator of each block, the entry block is its own immediate dominator.
+    immediate_dominators: Box<[usize]>,
+}
+
+/// Gets the targets of the branch instructions in a code block, without any duplicates.
+fn branch_targets(code: &Code) -> Vec<index::CodeBlock> {
+    let mut targets = Vec::new();
+    for instruction in code.untyped_instructions().iter() {
+        let (first, second) = match instruction {
+            Instruction::Br(target, _) => (*target, None),
+            Instruction::BrIf(branch) => (branch.true_branch(), Some(branch.false_branch())),
+            _ => continue,
+        };
+
+        for target in std::iter::once(first).chain(second) {
+            if !targets.contains(&target) {
+                targets.push(target);
+            }
+        }
+    }
+    targets
+}
+
+impl ControlFlowGraph {
+    pub(crate) fn new(entry_block: &Arc<Code>) -> Result<Self, error::LoaderError> {
+        let module = module::Module::upgrade_weak(entry_block.module())?;
+        let code_blocks = module.code_blocks();
+
+        // Depth-first search to get the blocks in postorder
+        let mut postorder = Vec::new();
+        let mut visited = rustc_hash::FxHashSet::default();
+        let mut stack = vec![(entry_block.index(), branch_targets(entry_block), 0usize)];
+        visited.insert(entry_block.index());
+
+        while let Some((block, targets, next)) = stack.last_mut() {
+            if let Some(target) = targets.get(*next).copied() {
+                *next += 1;
+                if visited.insert(target) {
+                    let target_targets = branch_targets(&code_blocks[usize::from(target)]);
+                    stack.push((target, target_targets, 0));
+                }
+            } else {
+                postorder.push(*block);
+                stack.pop();
+            }
+        }
+
+        let order = postorder.into_iter().rev().collect::<Vec<_>>();
+        let positions = order
+            .iter()
+            .enumerate()
+            .map(|(position, block)| (*block, position))
+            .collect::<rustc_hash::FxHashMap<_, _>>();
+
+        let blocks = order
+            .iter()
+            .map(|block| code_blocks[usize::from(*block)].clone())
+            .collect::<Box<[_]>>();
+
+        let successors = blocks
+            .iter()
+            .map(|block| branch_targets(block).into_boxed_slice())
+            .collect::<Box<[_]>>();
+
+        let mut predecessors = vec![Vec::new(); blocks.len()];
+        for (block, targets) in order.iter().zip(successors.iter()) {
+            for target in targets.iter() {
+                predecessors[positions[target]].push(*block);
+            }
+        }
+
+        // Uses the algorithm described in "A Simple, Fast Dominance Algorithm" by Cooper, Harvey, and Kennedy
+        let mut immediate_dominators = vec![None; blocks.len()];
+        immediate_dominators[0] = Some(0);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for position in 1..blocks.len() {
+                let mut new_dominator = None;
+                for predecessor in predecessors[position].iter() {
+                    let predecessor = positions[predecessor];
+                    if immediate_dominators[predecessor].is_none() {
+                        continue;
+                    }
+
+                    new_dominator = Some(match new_dominator {
+                        None => predecessor,
+                        Some(mut other) => {
+                            let mut current = predecessor;
+                            while current != other {
+                                while current > other {
+                                    current = immediate_dominators[current].unwrap();
+                                }
+                                while other > current {
+                                    other = immediate_dominators[other].unwrap();
+                                }
+                            }
+                            current
+                        }
+                    });
+                }
+
+                if new_dominator != immediate_dominators[position] {
+                    immediate_dominators[position] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        Ok(Self {
+            blocks,
+            positions,
+            successors,
+            predecessors: predecessors.into_iter().map(Vec::into_boxed_slice).collect(),
+            // Every block is reachable from the entry block, so each one has an immediate dominator
+            immediate_dominators: immediate_dominators.into_iter().map(Option::unwrap).collect(),
+        })
+    }
+
+    fn position(&self, block: index::CodeBlock) -> usize {
+        match self.positions.get(&block) {
+            Some(position) => *position,
+            None => panic!("code block #{} is not in the control flow graph", usize::from(block)),
+        }
+    }
+
+    /// The code blocks reachable from the entry block in reverse postorder, so the entry block is always first.
+    pub fn blocks(&self) -> &[Arc<Code>] {
+        &self.blocks
+    }
+
+    pub fn entry_block(&self) -> index::CodeBlock {
+        self.blocks[0].index()
+    }
+
+    pub fn contains(&self, block: index::CodeBlock) -> bool {
+        self.positions.contains_key(&block)
+    }
+
+    /// Gets the blocks that the specified `block` can branch to.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `block` is not reachable from the entry block.
+    pub fn successors(&self, block: index::CodeBlock) -> &[index::CodeBlock] {
+        &self.successors[self.position(block)]
+    }
+
+    /// Gets the blocks that can branch to the specified `block`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `block` is not reachable from the entry block.
+    pub fn predecessors(&self, block: index::CodeBlock) -> &[index::CodeBlock] {
+        &self.predecessors[self.position(block)]
+    }
+
+    /// Gets the closest block that must be executed before the specified `block`, or `None` for the entry block.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the `block` is not reachable from the entry block.
+    pub fn immediate_dominator(&self, block: index::CodeBlock) -> Option<index::CodeBlock> {
+        match self.position(block) {
+            0 => None,
+            position => Some(self.blocks[self.immediate_dominators[position]].index()),
+        }
+    }
+
+    /// Returns `true` if every path from the entry block to `block` passes through the `dominator`. Blocks always dominate
+    /// themselves.
+    ///
+    /// # Panics
+    ///
+    /// Panics if either block is not reachable from the entry block.
+    pub fn dominates(&self, dominator: index::CodeBlock, block: index::CodeBlock) -> bool {
+        let dominator = self.position(dominator);
+        let mut current = self.position(block);
+        loop {
+            if current == dominator {
+                return true;
+            } else if current == 0 {
+                return false;
+            }
+
+            current = self.immediate_dominators[current];
+        }
+    }
+}
+
+impl Debug for ControlFlowGraph {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_map()
+            .entries(
+                self.blocks
+                    .iter()
+                    .zip(self.successors.iter())
+                    .map(|(block, successors)| (block.index(), successors)),
+            )
+            .finish()
+    }
+}
+
+/// Describes where each register in a code block is defined and last used.
+///
+/// Since registers are local to a code block and are each defined exactly once, a register is live from the instruction
+/// that defines it until its last use.
+///
+/// # Examples
+///
+/// ```
+/// # use sailar::identifier::Identifier;
+/// # use sailar::index;
+/// # use sailar::module_builder::ModuleBuilder;
+/// # use sailar::signature;
+/// let mut builder = ModuleBuilder::new();
+/// let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U32));
+/// let signature = builder.function_signature([integer_type], [integer_type]);
+/// let main = builder.define_function(Identifier::try_from_str("main")?, signature);
+/// let mut body = builder.entry_block(main);
+/// let input = body.input(0);
+/// let sum = body.iadd(input, 1u32);
+/// body.ret(vec![sum.into()]);
+///
+/// let loader = sailar_load::state::Configuration::new().create_state();
+/// let module = loader.load_module(builder.build().unwrap()).unwrap();
+/// let liveness = module.code_blocks()[0].liveness()?;
+/// assert_eq!(liveness.definition(input), None);
+/// assert_eq!(liveness.definition(sum), Some(0));
+/// assert_eq!(liveness.last_use(input), Some(0));
+/// assert_eq!(liveness.live_after(0).collect::<Vec<_>>(), [sum]);
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct Liveness {
+    input_count: usize,
+    definitions: Box<[usize]>,
+    last_uses: Box<[Option<usize>]>,
+}
+
+impl Liveness {
+    pub(crate) fn new(code: &Code) -> Result<Self, error::LoaderError> {
+        let typed_instructions = code.typed_instructions()?;
+        let input_count = code.input_types()?.len();
+        let mut definitions = Vec::with_capacity(code.temporary_types()?.len());
+        let mut last_uses = vec![None; input_count + code.temporary_types()?.len()];
+
+        for (index, (instruction, typed_instruction)) in
+            code.untyped_instructions().iter().zip(typed_instructions.iter()).enumerate()
+        {
+            instruction.for_each_value(|value| {
+                if let Value::IndexedRegister(register) = value {
+                    last_uses[usize::from(*register)] = Some(index);
+                }
+            });
+
+            definitions.resize(definitions.len() + typed_instruction.result_count(), index);
+        }
+
+        Ok(Self {
+            input_count,
+            definitions: definitions.into_boxed_slice(),
+            last_uses: last_uses.into_boxed_slice(),
+        })
+    }
+
+    /// Gets the index of the instruction that defines the `register`, or `None` if it is an input register.
+    pub fn definition(&self, register: index::Register) -> Option<usize> {
+        usize::from(register)
+            .checked_sub(self.input_count)
+            .map(|temporary| self.definitions[temporary])
+    }
+
+    /// Gets the index of the last instruction that uses the `register`, or `None` if it is never used.
+    pub fn last_use(&self, register: index::Register) -> Option<usize> {
+        self.last_uses[usize::from(register)]
+    }
+
+    /// Returns `true` if the `register` is used by an instruction after the specified `instruction` index.
+    pub fn is_live_after(&self, register: index::Register, instruction: usize) -> bool {
+        let is_defined = !matches!(self.definition(register), Some(definition) if definition > instruction);
+        is_defined && matches!(self.last_use(register), Some(last_use) if last_use > instruction)
+    }
+
+    /// Gets the registers that are used by instructions after the specified `instruction` index.
+    pub fn live_after(&self, instruction: usize) -> impl Iterator<Item = index::Register> + '_ {
+        (0..self.last_uses.len())
+            .map(index::Register::from)
+            .filter(move |register| self.is_live_after(*register, instruction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::module::Module;
+    use crate::state::{Configuration, State};
+    use sailar::identifier::Identifier;
+    use sailar::index;
+    use sailar::module_builder::ModuleBuilder;
+    use sailar::signature;
+    use std::sync::Arc;
+
+    /// Loads a module, returning it along with the loader, which must be kept alive while the module is used.
+    fn load(builder: ModuleBuilder) -> (Arc<State>, Arc<Module>) {
+        let loader = Configuration::new().create_state();
+        let module = loader.load_module(builder.build().unwrap()).unwrap();
+        (loader, module)
+    }
+
+    /// Begins building a module containing a function with no parameters or return values.
+    fn function_module() -> (ModuleBuilder, sailar::module_builder::FunctionHandle) {
+        let mut builder = ModuleBuilder::new();
+        let signature = builder.function_signature([], []);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+        (builder, main)
+    }
+
+    #[test]
+    fn block_with_only_return_has_no_edges() {
+        // Code blocks cannot be empty, so the smallest possible block contains only a terminator instruction
+        let (mut builder, main) = function_module();
+        let entry_block = builder.entry_block(main).ret(Vec::new());
+        let (_loader, module) = load(builder);
+
+        let graph = module.functions()[0].control_flow_graph().unwrap();
+        assert_eq!(graph.blocks().len(), 1);
+        assert_eq!(graph.entry_block(), entry_block);
+        assert!(graph.successors(entry_block).is_empty());
+        assert!(graph.predecessors(entry_block).is_empty());
+        assert_eq!(graph.immediate_dominator(entry_block), None);
+        assert!(graph.dominates(entry_block, entry_block));
+
+        let liveness = module.code_blocks()[usize::from(entry_block)].liveness().unwrap();
+        assert_eq!(liveness.live_after(0).count(), 0);
+    }
+
+    #[test]
+    fn unreachable_blocks_are_excluded() {
+        let (mut builder, main) = function_module();
+        let exit_block = builder.code_block([], []).ret(Vec::new());
+        let unreachable_block = builder.code_block([], []).br(exit_block, Vec::new());
+        let entry_block = builder.entry_block(main).br(exit_block, Vec::new());
+        let (_loader, module) = load(builder);
+
+        let graph = module.functions()[0].control_flow_graph().unwrap();
+        assert_eq!(
+            graph.blocks().iter().map(|block| block.index()).collect::<Vec<_>>(),
+            [entry_block, exit_block]
+        );
+        assert!(!graph.contains(unreachable_block));
+        assert_eq!(graph.predecessors(exit_block), [entry_block]);
+        assert_eq!(graph.immediate_dominator(exit_block), Some(entry_block));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not in the control flow graph")]
+    fn unreachable_block_successors_panic() {
+        let (mut builder, main) = function_module();
+        let unreachable_block = builder.code_block([], []).ret(Vec::new());
+        builder.entry_block(main).ret(Vec::new());
+        let (_loader, module) = load(builder);
+        module.functions()[0]
+            .control_flow_graph()
+            .unwrap()
+            .successors(unreachable_block);
+    }
+
+    #[test]
+    fn self_loops_are_their_own_predecessors() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U8));
+        let signature = builder.function_signature([integer_type], []);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+
+        // The spin block branches to itself forever, passing its input along
+        let spin_block = index::CodeBlock::from(0);
+        let body = builder.code_block([integer_type], []);
+        let counter = body.input(0);
+        assert_eq!(body.br(spin_block, vec![counter.into()]), spin_block);
+
+        let body = builder.entry_block(main);
+        let input = body.input(0);
+        let entry_block = body.br(spin_block, vec![input.into()]);
+        let (_loader, module) = load(builder);
+
+        let graph = module.functions()[0].control_flow_graph().unwrap();
+        assert_eq!(graph.successors(spin_block), [spin_block]);
+        assert_eq!(graph.predecessors(spin_block), [entry_block, spin_block]);
+        assert_eq!(graph.immediate_dominator(spin_block), Some(entry_block));
+        assert!(graph.dominates(spin_block, spin_block));
+        assert!(!graph.dominates(spin_block, entry_block));
+
+        let liveness = module.code_blocks()[usize::from(spin_block)].liveness().unwrap();
+        assert_eq!(liveness.last_use(counter), Some(0));
+        assert!(!liveness.is_live_after(counter, 0));
+    }
+
+    #[test]
+    fn self_loop_with_both_branches_to_itself_has_one_edge() {
+        let mut builder = ModuleBuilder::new();
+        let integer_type = builder.type_signature(signature::Type::from(signature::IntegerType::U8));
+        let signature = builder.function_signature([integer_type], []);
+        let main = builder.define_function(Identifier::try_from_str("main").unwrap(), signature);
+
+        let loop_block = index::CodeBlock::from(0);
+        let body = builder.code_block([integer_type], []);
+        let counter = body.input(0);
+        assert_eq!(body.br_if(counter, loop_block, loop_block, vec![counter.into()]), loop_block);
+
+        let body = builder.entry_block(main);
+        let input = body.input(0);
+        let entry_block = body.br(loop_block, vec![input.into()]);
+        let (_loader, module) = load(builder);
+
+        let graph = module.functions()[0].control_flow_graph().unwrap();
+        assert_eq!(graph.successors(loop_block), [loop_block]);
+        assert_eq!(graph.predecessors(loop_block), [entry_block, loop_block]);
+    }
+}
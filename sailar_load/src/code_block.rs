@@ -1,5 +1,6 @@
 //! Module for interacting with SAILAR code blocks.
 
+use crate::analysis;
 use crate::error;
 use crate::module;
 use crate::type_system;
@@ -27,12 +28,443 @@ impl TypedValue {
     }
 }
 
+/// An integer arithmetic operation with type information, the operands have the same type as the result.
+#[derive(Clone, Debug)]
+pub struct TypedArithmetic {
+    overflow_behavior: instruction::OverflowBehavior,
+    x: TypedValue,
+    y: TypedValue,
+}
+
+impl TypedArithmetic {
+    pub fn overflow_behavior(&self) -> instruction::OverflowBehavior {
+        self.overflow_behavior
+    }
+
+    pub fn x_value(&self) -> &TypedValue {
+        &self.x
+    }
+
+    pub fn y_value(&self) -> &TypedValue {
+        &self.y
+    }
+
+    /// The type of the result of the operation.
+    pub fn result_type(&self) -> &type_system::Type {
+        self.x.value_type()
+    }
+}
+
+/// An integer comparison with type information, the operands have the same type.
+#[derive(Clone, Debug)]
+pub struct TypedComparison {
+    kind: instruction::ComparisonKind,
+    result_type: type_system::Type,
+    x: TypedValue,
+    y: TypedValue,
+}
+
+impl TypedComparison {
+    pub fn kind(&self) -> instruction::ComparisonKind {
+        self.kind
+    }
+
+    pub fn x_value(&self) -> &TypedValue {
+        &self.x
+    }
+
+    pub fn y_value(&self) -> &TypedValue {
+        &self.y
+    }
+
+    /// The integer type of the boolean result of the comparison.
+    pub fn result_type(&self) -> &type_system::Type {
+        &self.result_type
+    }
+}
+
+/// A conditional branch with type information.
+#[derive(Clone, Debug)]
+pub struct TypedBranchIf {
+    condition: TypedValue,
+    true_branch: sailar::index::CodeBlock,
+    false_branch: sailar::index::CodeBlock,
+    arguments: Box<[TypedValue]>,
+}
+
+impl TypedBranchIf {
+    pub fn condition(&self) -> &TypedValue {
+        &self.condition
+    }
+
+    pub fn true_branch(&self) -> sailar::index::CodeBlock {
+        self.true_branch
+    }
+
+    pub fn false_branch(&self) -> sailar::index::CodeBlock {
+        self.false_branch
+    }
+
+    pub fn arguments(&self) -> &[TypedValue] {
+        &self.arguments
+    }
+}
+
+/// A function call with type information.
+#[derive(Clone, Debug)]
+pub struct TypedCall {
+    callee: sailar::index::Function,
+    arguments: Box<[TypedValue]>,
+    result_types: Box<[type_system::Type]>,
+}
+
+impl TypedCall {
+    /// The index of the called function in the current module.
+    pub fn callee(&self) -> sailar::index::Function {
+        self.callee
+    }
+
+    pub fn arguments(&self) -> &[TypedValue] {
+        &self.arguments
+    }
+
+    /// The types of the temporary registers defined by the call, which are the callee's return types.
+    pub fn result_types(&self) -> &[type_system::Type] {
+        &self.result_types
+    }
+}
+
+/// Starting a new thread with type information.
+#[derive(Clone, Debug)]
+pub struct TypedThreadSpawn {
+    callee: sailar::index::Function,
+    arguments: Box<[TypedValue]>,
+    handle_type: type_system::Type,
+}
+
+impl TypedThreadSpawn {
+    /// The index of the function that the new thread calls.
+    pub fn callee(&self) -> sailar::index::Function {
+        self.callee
+    }
+
+    pub fn arguments(&self) -> &[TypedValue] {
+        &self.arguments
+    }
+
+    /// The integer type of the temporary register that contains the handle to the new thread.
+    pub fn handle_type(&self) -> &type_system::Type {
+        &self.handle_type
+    }
+}
+
+/// Waiting for a thread to finish with type information.
+#[derive(Clone, Debug)]
+pub struct TypedThreadJoin {
+    thread: TypedValue,
+    result_types: Box<[type_system::Type]>,
+}
+
+impl TypedThreadJoin {
+    /// The handle to the thread that is joined.
+    pub fn thread(&self) -> &TypedValue {
+        &self.thread
+    }
+
+    /// The types of the values returned by the thread.
+    pub fn result_types(&self) -> &[type_system::Type] {
+        &self.result_types
+    }
+}
+
+/// An atomic read from memory with type information.
+#[derive(Clone, Debug)]
+pub struct TypedAtomicLoad {
+    ordering: instruction::MemoryOrdering,
+    address: TypedValue,
+    result_type: type_system::Type,
+}
+
+impl TypedAtomicLoad {
+    pub fn ordering(&self) -> instruction::MemoryOrdering {
+        self.ordering
+    }
+
+    /// The raw pointer to the integer that is read.
+    pub fn address(&self) -> &TypedValue {
+        &self.address
+    }
+
+    /// The type of the integer that is read, which is the type that the pointer points to.
+    pub fn result_type(&self) -> &type_system::Type {
+        &self.result_type
+    }
+}
+
+/// An atomic write to memory with type information.
+#[derive(Clone, Debug)]
+pub struct TypedAtomicStore {
+    ordering: instruction::MemoryOrdering,
+    address: TypedValue,
+    value: TypedValue,
+}
+
+impl TypedAtomicStore {
+    pub fn ordering(&self) -> instruction::MemoryOrdering {
+        self.ordering
+    }
+
+    /// The raw pointer to the integer that is written.
+    pub fn address(&self) -> &TypedValue {
+        &self.address
+    }
+
+    pub fn value(&self) -> &TypedValue {
+        &self.value
+    }
+}
+
+/// An atomic read-modify-write operation with type information, the value has the same type as the result.
+#[derive(Clone, Debug)]
+pub struct TypedAtomicReadModifyWrite {
+    operation: instruction::AtomicOperation,
+    ordering: instruction::MemoryOrdering,
+    address: TypedValue,
+    value: TypedValue,
+}
+
+impl TypedAtomicReadModifyWrite {
+    pub fn operation(&self) -> instruction::AtomicOperation {
+        self.operation
+    }
+
+    pub fn ordering(&self) -> instruction::MemoryOrdering {
+        self.ordering
+    }
+
+    /// The raw pointer to the integer that is modified.
+    pub fn address(&self) -> &TypedValue {
+        &self.address
+    }
+
+    pub fn value(&self) -> &TypedValue {
+        &self.value
+    }
+
+    /// The type of the previously stored value.
+    pub fn result_type(&self) -> &type_system::Type {
+        self.value.value_type()
+    }
+}
+
+/// An atomic compare-and-swap with type information, the expected and replacement values have the same type as the
+/// previously stored value.
+#[derive(Clone, Debug)]
+pub struct TypedAtomicCompareExchange {
+    success_ordering: instruction::MemoryOrdering,
+    failure_ordering: instruction::MemoryOrdering,
+    address: TypedValue,
+    expected: TypedValue,
+    replacement: TypedValue,
+    success_type: type_system::Type,
+}
+
+impl TypedAtomicCompareExchange {
+    pub fn success_ordering(&self) -> instruction::MemoryOrdering {
+        self.success_ordering
+    }
+
+    pub fn failure_ordering(&self) -> instruction::MemoryOrdering {
+        self.failure_ordering
+    }
+
+    /// The raw pointer to the integer that is compared and possibly replaced.
+    pub fn address(&self) -> &TypedValue {
+        &self.address
+    }
+
+    pub fn expected(&self) -> &TypedValue {
+        &self.expected
+    }
+
+    pub fn replacement(&self) -> &TypedValue {
+        &self.replacement
+    }
+
+    /// The type of the previously stored value.
+    pub fn result_type(&self) -> &type_system::Type {
+        self.expected.value_type()
+    }
+
+    /// The integer type of the boolean indicating if the value was replaced.
+    pub fn success_type(&self) -> &type_system::Type {
+        &self.success_type
+    }
+}
+
+/// Creates a vector where every lane contains the same value.
+#[derive(Clone, Debug)]
+pub struct TypedVectorSplat {
+    value: TypedValue,
+    result_type: type_system::VectorType,
+}
+
+impl TypedVectorSplat {
+    /// The value stored in each lane, which has the element type of the vector.
+    pub fn value(&self) -> &TypedValue {
+        &self.value
+    }
+
+    pub fn result_type(&self) -> type_system::VectorType {
+        self.result_type
+    }
+}
+
+/// Retrieves the value in a single lane of a vector.
+#[derive(Clone, Debug)]
+pub struct TypedVectorExtract {
+    vector: TypedValue,
+    vector_type: type_system::VectorType,
+    lane: u16,
+}
+
+impl TypedVectorExtract {
+    pub fn vector(&self) -> &TypedValue {
+        &self.vector
+    }
+
+    pub fn vector_type(&self) -> type_system::VectorType {
+        self.vector_type
+    }
+
+    pub fn lane(&self) -> u16 {
+        self.lane
+    }
+
+    /// The type of the extracted value, which is the element type of the vector.
+    pub fn result_type(&self) -> type_system::Type {
+        self.vector_type.element_type().into()
+    }
+}
+
+/// Replaces a single lane of a vector, the result has the same type as the original vector.
+#[derive(Clone, Debug)]
+pub struct TypedVectorInsert {
+    vector: TypedValue,
+    vector_type: type_system::VectorType,
+    value: TypedValue,
+    lane: u16,
+}
+
+impl TypedVectorInsert {
+    pub fn vector(&self) -> &TypedValue {
+        &self.vector
+    }
+
+    pub fn value(&self) -> &TypedValue {
+        &self.value
+    }
+
+    pub fn lane(&self) -> u16 {
+        self.lane
+    }
+
+    pub fn result_type(&self) -> type_system::VectorType {
+        self.vector_type
+    }
+}
+
+/// Selects lanes from two vectors of the same type, the result has the same element type as the operands.
+#[derive(Clone, Debug)]
+pub struct TypedVectorShuffle {
+    x: TypedValue,
+    y: TypedValue,
+    vector_type: type_system::VectorType,
+    lanes: Box<[u16]>,
+}
+
+impl TypedVectorShuffle {
+    pub fn x_value(&self) -> &TypedValue {
+        &self.x
+    }
+
+    pub fn y_value(&self) -> &TypedValue {
+        &self.y
+    }
+
+    /// The type of the `x` and `y` vectors.
+    pub fn vector_type(&self) -> type_system::VectorType {
+        self.vector_type
+    }
+
+    /// The indices of the selected lanes, where the lanes of `y` come after the lanes of `x`.
+    pub fn lanes(&self) -> &[u16] {
+        &self.lanes
+    }
+
+    pub fn result_type(&self) -> type_system::VectorType {
+        // Validation ensures that there is at least one and at most 255 lanes.
+        let lane_count = std::num::NonZeroU8::new(self.lanes.len() as u8).unwrap();
+        type_system::VectorType::new(self.vector_type.element_type(), lane_count)
+    }
+}
+
 /// Represents a SAILAR instruction with type information.
+///
+/// Code blocks and functions are referred to by their indices, which avoids reference cycles between recursive functions or
+/// blocks that branch to themselves.
 #[derive(Clone, Debug)]
 pub enum TypedInstruction {
     Nop,
     Break,
     Return(Box<[TypedValue]>),
+    Branch(sailar::index::CodeBlock, Box<[TypedValue]>),
+    BranchIf(Box<TypedBranchIf>),
+    Call(Box<TypedCall>),
+    IAdd(Box<TypedArithmetic>),
+    ISub(Box<TypedArithmetic>),
+    Cmp(Box<TypedComparison>),
+    ThreadSpawn(Box<TypedThreadSpawn>),
+    ThreadJoin(Box<TypedThreadJoin>),
+    AtomicLoad(Box<TypedAtomicLoad>),
+    AtomicStore(Box<TypedAtomicStore>),
+    AtomicRmw(Box<TypedAtomicReadModifyWrite>),
+    AtomicCmpXchg(Box<TypedAtomicCompareExchange>),
+    Fence(instruction::MemoryOrdering),
+    VSplat(Box<TypedVectorSplat>),
+    VExtract(Box<TypedVectorExtract>),
+    VInsert(Box<TypedVectorInsert>),
+    VShuffle(Box<TypedVectorShuffle>),
+}
+
+impl TypedInstruction {
+    /// Gets the number of temporary registers defined by this instruction.
+    pub fn result_count(&self) -> usize {
+        match self {
+            Self::Nop
+            | Self::Break
+            | Self::Return(_)
+            | Self::Branch(_, _)
+            | Self::BranchIf(_)
+            | Self::AtomicStore(_)
+            | Self::Fence(_) => 0,
+            Self::Call(call) => call.result_types.len(),
+            Self::IAdd(operation) | Self::ISub(operation) => match operation.overflow_behavior {
+                instruction::OverflowBehavior::Flag => 2,
+                _ => 1,
+            },
+            Self::Cmp(_)
+            | Self::ThreadSpawn(_)
+            | Self::AtomicLoad(_)
+            | Self::AtomicRmw(_)
+            | Self::VSplat(_)
+            | Self::VExtract(_)
+            | Self::VInsert(_)
+            | Self::VShuffle(_) => 1,
+            Self::AtomicCmpXchg(_) => 2,
+            Self::ThreadJoin(join) => join.result_types.len(),
+        }
+    }
 }
 
 pub struct Code {
@@ -41,6 +473,7 @@ pub struct Code {
     register_types: type_system::LazySignatureList,
     untyped_instructions: Box<[Instruction]>,
     typed_instructions: lazy_init::Lazy<Result<Box<[TypedInstruction]>, error::LoaderError>>,
+    liveness: lazy_init::Lazy<Result<analysis::Liveness, error::LoaderError>>,
     index: sailar::index::CodeBlock,
     module: Weak<module::Module>,
 }
@@ -54,9 +487,10 @@ impl Code {
         Arc::new(Self {
             input_count: code.input_count,
             result_count: code.result_count,
-            register_types: type_system::LazySignatureList::new(code.register_types.into_boxed()),
+            register_types: type_system::LazySignatureList::new(code.register_types.into_boxed_slice()),
             untyped_instructions: code.instructions.into_boxed(),
             typed_instructions: Default::default(),
+            liveness: Default::default(),
             index,
             module,
         })
@@ -98,24 +532,239 @@ impl Code {
                 let module = module::Module::upgrade_weak(&self.module)?;
                 let result_types = self.result_types()?;
                 let mut typed_instructions = Vec::with_capacity(self.untyped_instructions.len());
+                let register_types = self.all_types()?;
+
+                let get_register_type = |register: sailar::index::Register| {
+                    let index = usize::from(register);
+                    if index < self.input_count {
+                        register_types[index].signature()
+                    } else {
+                        register_types[index + self.result_count].signature()
+                    }
+                };
+
+                let type_value = |value: &instruction::Value, expected_type: &type_system::Type| {
+                    Ok(TypedValue::new(
+                        match value {
+                            instruction::Value::Constant(_) => expected_type.clone(),
+                            instruction::Value::IndexedRegister(register) => get_register_type(*register)?.clone(),
+                        },
+                        value.clone(),
+                    ))
+                };
+
+                let type_values = |values: &[instruction::Value], types: &[Arc<type_system::Signature>]| {
+                    assert!(values.len() == types.len());
+                    values
+                        .iter()
+                        .zip(types)
+                        .map(|(value, value_type)| type_value(value, value_type.signature()?))
+                        .collect::<Result<Box<[_]>, error::LoaderError>>()
+                };
+
+                // Gets the type of an integer pointer and the type of the integer it points to
+                let type_address = |address: &instruction::Value| -> Result<(TypedValue, type_system::Type), error::LoaderError> {
+                    let address_type = match address {
+                        instruction::Value::IndexedRegister(register) => get_register_type(*register)?,
+                        instruction::Value::Constant(_) => unreachable!("constants cannot be pointers"),
+                    };
+
+                    let pointee_type = match address_type {
+                        type_system::Type::RawPtr(Some(pointee)) => pointee.signature()?.clone(),
+                        _ => unreachable!("atomic operations require pointers to integers"),
+                    };
+
+                    Ok((TypedValue::new(address_type.clone(), address.clone()), pointee_type))
+                };
+
+                // Gets the type of a vector register
+                let type_vector =
+                    |vector: &instruction::Value| -> Result<(TypedValue, type_system::VectorType), error::LoaderError> {
+                        let vector_type = match vector {
+                            instruction::Value::IndexedRegister(register) => get_register_type(*register)?,
+                            instruction::Value::Constant(_) => unreachable!("constants cannot be vectors"),
+                        };
+
+                        match vector_type {
+                            type_system::Type::Vector(ty) => Ok((TypedValue::new(vector_type.clone(), vector.clone()), *ty)),
+                            _ => unreachable!("vector operations require vectors"),
+                        }
+                    };
+
+                let block_input_types = |block: sailar::index::CodeBlock| module.code_blocks()[usize::from(block)].input_types();
+
+                let mut temporary_index = 0usize;
 
                 for instruction in self.untyped_instructions.iter() {
-                    typed_instructions.push(match instruction {
+                    let typed_instruction = match instruction {
                         Instruction::Nop => TypedInstruction::Nop,
                         Instruction::Break => TypedInstruction::Break,
-                        Instruction::Return(values) => {
-                            assert!(values.len() == result_types.len());
+                        Instruction::Return(values) => TypedInstruction::Return(type_values(values, result_types)?),
+                        Instruction::Br(target, arguments) => {
+                            TypedInstruction::Branch(*target, type_values(arguments, block_input_types(*target)?)?)
+                        }
+                        Instruction::BrIf(branch) => {
+                            let condition_type = match branch.condition() {
+                                instruction::Value::IndexedRegister(register) => get_register_type(*register)?.clone(),
+                                instruction::Value::Constant(instruction::Constant::Integer(integer)) => {
+                                    type_system::IntegerType::new(type_system::IntegerSign::Unsigned, integer.bit_size()).into()
+                                }
+                            };
 
-                            let mut return_values = Vec::with_capacity(values.len());
+                            TypedInstruction::BranchIf(Box::new(TypedBranchIf {
+                                condition: TypedValue::new(condition_type, branch.condition().clone()),
+                                true_branch: branch.true_branch(),
+                                false_branch: branch.false_branch(),
+                                arguments: type_values(branch.arguments(), block_input_types(branch.true_branch())?)?,
+                            }))
+                        }
+                        Instruction::Call(callee, arguments) => {
+                            let signature = module.functions()[usize::from(*callee)].signature()?;
+                            TypedInstruction::Call(Box::new(TypedCall {
+                                callee: *callee,
+                                arguments: type_values(arguments, signature.parameter_types()?)?,
+                                result_types: signature
+                                    .return_types()?
+                                    .iter()
+                                    .map(|return_type| return_type.signature().cloned())
+                                    .collect::<Result<_, _>>()?,
+                            }))
+                        }
+                        Instruction::IAdd(operation) | Instruction::ISub(operation) => {
+                            let result_type = self.temporary_types()?[temporary_index].signature()?;
+                            let typed_operation = Box::new(TypedArithmetic {
+                                overflow_behavior: operation.overflow_behavior(),
+                                x: type_value(operation.x_value(), result_type)?,
+                                y: type_value(operation.y_value(), result_type)?,
+                            });
 
-                            for (value, return_type) in values.iter().zip(result_types) {
-                                return_values.push(TypedValue::new(return_type.signature()?.clone(), value.clone()));
+                            if let Instruction::IAdd(_) = instruction {
+                                TypedInstruction::IAdd(typed_operation)
+                            } else {
+                                TypedInstruction::ISub(typed_operation)
                             }
+                        }
+                        Instruction::Cmp(comparison) => {
+                            let result_type = self.temporary_types()?[temporary_index].signature()?;
+                            let operand_type = match (comparison.x_value(), comparison.y_value()) {
+                                (instruction::Value::IndexedRegister(register), _)
+                                | (_, instruction::Value::IndexedRegister(register)) => get_register_type(*register)?,
+                                _ => result_type,
+                            };
+
+                            TypedInstruction::Cmp(Box::new(TypedComparison {
+                                kind: comparison.kind(),
+                                result_type: result_type.clone(),
+                                x: type_value(comparison.x_value(), operand_type)?,
+                                y: type_value(comparison.y_value(), operand_type)?,
+                            }))
+                        }
+                        Instruction::ThreadSpawn(callee, arguments) => {
+                            let signature = module.functions()[usize::from(*callee)].signature()?;
+                            TypedInstruction::ThreadSpawn(Box::new(TypedThreadSpawn {
+                                callee: *callee,
+                                arguments: type_values(arguments, signature.parameter_types()?)?,
+                                handle_type: self.temporary_types()?[temporary_index].signature()?.clone(),
+                            }))
+                        }
+                        Instruction::ThreadJoin(signature, thread) => {
+                            let signature = &module.function_signatures()[usize::from(*signature)];
+                            let handle_type = match thread {
+                                instruction::Value::IndexedRegister(register) => get_register_type(*register)?.clone(),
+                                instruction::Value::Constant(instruction::Constant::Integer(integer)) => {
+                                    type_system::IntegerType::new(type_system::IntegerSign::Unsigned, integer.bit_size()).into()
+                                }
+                            };
 
-                            TypedInstruction::Return(return_values.into_boxed_slice())
+                            TypedInstruction::ThreadJoin(Box::new(TypedThreadJoin {
+                                thread: TypedValue::new(handle_type, thread.clone()),
+                                result_types: signature
+                                    .return_types()?
+                                    .iter()
+                                    .map(|return_type| return_type.signature().cloned())
+                                    .collect::<Result<_, _>>()?,
+                            }))
+                        }
+                        Instruction::AtomicLoad(load) => {
+                            let (address, result_type) = type_address(load.address())?;
+                            TypedInstruction::AtomicLoad(Box::new(TypedAtomicLoad {
+                                ordering: load.ordering(),
+                                address,
+                                result_type,
+                            }))
+                        }
+                        Instruction::AtomicStore(store) => {
+                            let (address, value_type) = type_address(store.address())?;
+                            TypedInstruction::AtomicStore(Box::new(TypedAtomicStore {
+                                ordering: store.ordering(),
+                                address,
+                                value: type_value(store.value(), &value_type)?,
+                            }))
+                        }
+                        Instruction::AtomicRmw(operation) => {
+                            let (address, value_type) = type_address(operation.address())?;
+                            TypedInstruction::AtomicRmw(Box::new(TypedAtomicReadModifyWrite {
+                                operation: operation.operation(),
+                                ordering: operation.ordering(),
+                                address,
+                                value: type_value(operation.value(), &value_type)?,
+                            }))
+                        }
+                        Instruction::AtomicCmpXchg(operation) => {
+                            let (address, value_type) = type_address(operation.address())?;
+                            TypedInstruction::AtomicCmpXchg(Box::new(TypedAtomicCompareExchange {
+                                success_ordering: operation.success_ordering(),
+                                failure_ordering: operation.failure_ordering(),
+                                address,
+                                expected: type_value(operation.expected(), &value_type)?,
+                                replacement: type_value(operation.replacement(), &value_type)?,
+                                success_type: self.temporary_types()?[temporary_index + 1].signature()?.clone(),
+                            }))
+                        }
+                        Instruction::Fence(ordering) => TypedInstruction::Fence(*ordering),
+                        Instruction::VSplat(value) => {
+                            let result_type = match self.temporary_types()?[temporary_index].signature()? {
+                                type_system::Type::Vector(ty) => *ty,
+                                _ => unreachable!("splat must produce a vector"),
+                            };
+
+                            TypedInstruction::VSplat(Box::new(TypedVectorSplat {
+                                value: type_value(value, &result_type.element_type().into())?,
+                                result_type,
+                            }))
+                        }
+                        Instruction::VExtract(vector, lane) => {
+                            let (vector, vector_type) = type_vector(vector)?;
+                            TypedInstruction::VExtract(Box::new(TypedVectorExtract {
+                                vector,
+                                vector_type,
+                                lane: *lane,
+                            }))
+                        }
+                        Instruction::VInsert(insert) => {
+                            let (vector, vector_type) = type_vector(insert.vector())?;
+                            TypedInstruction::VInsert(Box::new(TypedVectorInsert {
+                                vector,
+                                vector_type,
+                                value: type_value(insert.value(), &vector_type.element_type().into())?,
+                                lane: insert.lane(),
+                            }))
+                        }
+                        Instruction::VShuffle(shuffle) => {
+                            let (x, vector_type) = type_vector(shuffle.x_value())?;
+                            let (y, _) = type_vector(shuffle.y_value())?;
+                            TypedInstruction::VShuffle(Box::new(TypedVectorShuffle {
+                                x,
+                                y,
+                                vector_type,
+                                lanes: shuffle.lanes().into(),
+                            }))
                         }
                         bad => todo!("translate {:?}", bad),
-                    })
+                    };
+
+                    temporary_index += typed_instruction.result_count();
+                    typed_instructions.push(typed_instruction);
                 }
 
                 Ok(typed_instructions.into_boxed_slice())
@@ -124,6 +773,14 @@ impl Code {
             .map(AsRef::as_ref)
             .map_err(Clone::clone)
     }
+
+    /// Gets where each register in this code block is defined and last used.
+    pub fn liveness(&self) -> Result<&analysis::Liveness, error::LoaderError> {
+        self.liveness
+            .get_or_create(|| analysis::Liveness::new(self))
+            .as_ref()
+            .map_err(Clone::clone)
+    }
 }
 
 impl Debug for Code {
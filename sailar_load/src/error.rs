@@ -61,6 +61,8 @@ impl DroppedError {
 pub enum UnresolvedReferenceKind {
     #[error("could not resolve reference to {0:?}")]
     Module(module::ModuleIdentifier),
+    #[error("could not resolve imported function {0:?}")]
+    FunctionImport(sailar::identifier::Identifier),
 }
 
 /// The error type used when a reference to something could not be resolved.
@@ -1,10 +1,13 @@
 //! Module for interacting with SAILAR function definitions and instantiations.
 
+use crate::analysis;
 use crate::error;
 use crate::module;
 use crate::type_system;
+use sailar::identifier::{Id, Identifier};
 use sailar::record;
 use sailar::signature;
+use std::borrow::Cow;
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::{Arc, Weak};
 
@@ -12,13 +15,18 @@ use std::sync::{Arc, Weak};
 #[derive(Clone, Debug)]
 pub enum Template {
     Defined(Arc<DefinedTemplate>),
-    //Import()
+    Import(Arc<ImportedTemplate>),
 }
 
 impl Template {
+    /// Returns the definition of the function template, or an error if the function template is imported.
     pub fn as_definition(&self) -> Result<&Arc<DefinedTemplate>, error::LoaderError> {
         match self {
             Self::Defined(definition) => Ok(definition),
+            Self::Import(import) => Err(error::LoaderError::new(error::UnresolvedReferenceError::new(
+                error::UnresolvedReferenceKind::FunctionImport(Identifier::from_id(import.symbol())),
+                module::Module::upgrade_weak(&import.module)?,
+            ))),
         }
     }
 
@@ -31,6 +39,12 @@ impl From<Arc<DefinedTemplate>> for Template {
     }
 }
 
+impl From<Arc<ImportedTemplate>> for Template {
+    fn from(import: Arc<ImportedTemplate>) -> Self {
+        Self::Import(import)
+    }
+}
+
 pub struct Signature {
     return_type_count: usize,
     index: sailar::index::FunctionSignature,
@@ -40,14 +54,14 @@ pub struct Signature {
 
 impl Signature {
     pub(crate) fn new(
-        signature: signature::Function,
+        signature: signature::Function<'_>,
         index: sailar::index::FunctionSignature,
         module: Weak<module::Module>,
     ) -> Arc<Self> {
         Arc::new(Self {
             index,
             return_type_count: signature.return_type_count,
-            types: type_system::LazySignatureList::new(signature.types),
+            types: type_system::LazySignatureList::new(signature.types.into_boxed_slice()),
             module,
         })
     }
@@ -127,6 +141,7 @@ pub struct Function {
     index: sailar::index::Function,
     module: Weak<module::Module>,
     signature: lazy_init::Lazy<Result<Arc<Signature>, error::LoaderError>>,
+    control_flow_graph: lazy_init::Lazy<Result<analysis::ControlFlowGraph, error::LoaderError>>,
 }
 
 impl Function {
@@ -140,6 +155,7 @@ impl Function {
             index,
             module,
             signature: Default::default(),
+            control_flow_graph: Default::default(),
         })
     }
 
@@ -163,7 +179,18 @@ impl Function {
     /// Returns the signature of the function template if there are no generic type arguments.
     pub fn signature(&self) -> Result<&Arc<Signature>, error::LoaderError> {
         self.signature
-            .get_or_create(|| self.template()?.as_definition()?.signature().cloned())
+            .get_or_create(|| match self.template()? {
+                Template::Defined(definition) => definition.signature().cloned(),
+                Template::Import(import) => import.signature().cloned(),
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+
+    /// Gets the control flow graph of the function's body.
+    pub fn control_flow_graph(&self) -> Result<&analysis::ControlFlowGraph, error::LoaderError> {
+        self.control_flow_graph
+            .get_or_create(|| analysis::ControlFlowGraph::new(self.template()?.as_definition()?.entry_block()?))
             .as_ref()
             .map_err(Clone::clone)
     }
@@ -259,4 +286,60 @@ impl Debug for DefinedTemplate {
     }
 }
 
+/// Represents a function template defined in another module.
+pub struct ImportedTemplate {
+    index: sailar::index::FunctionTemplate,
+    symbol: Cow<'static, Id>,
+    signature: lazy_init::LazyTransform<sailar::index::FunctionSignature, Result<Arc<Signature>, error::LoaderError>>,
+    module: Weak<module::Module>,
+}
+
+impl ImportedTemplate {
+    pub(crate) fn new(
+        import: record::FunctionImport<'static>,
+        index: sailar::index::FunctionTemplate,
+        module: Weak<module::Module>,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            index,
+            symbol: import.symbol,
+            signature: lazy_init::LazyTransform::new(import.signature),
+            module,
+        })
+    }
+
+    pub fn module(&self) -> &Weak<module::Module> {
+        &self.module
+    }
+
+    pub fn index(&self) -> sailar::index::FunctionTemplate {
+        self.index
+    }
+
+    /// The symbol of the function template in the module that defines it.
+    pub fn symbol(&self) -> &Id {
+        &self.symbol
+    }
+
+    pub fn signature(&self) -> Result<&Arc<Signature>, error::LoaderError> {
+        self.signature
+            .get_or_create(|signature| {
+                module::Module::upgrade_weak(&self.module)
+                    .map(|module| module.function_signatures()[usize::from(signature)].clone())
+            })
+            .as_ref()
+            .map_err(Clone::clone)
+    }
+}
+
+impl Debug for ImportedTemplate {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("Import")
+            .field("index", &self.index)
+            .field("symbol", &self.symbol)
+            .field("signature", &self.signature.get())
+            .finish()
+    }
+}
+
 crate::symbol_wrapper!(pub struct Symbol(DefinedTemplate));
@@ -2,6 +2,7 @@
 //!
 //! The types provided by this module are thread-safe, to allow dependents such as interpreters to use multiple threads.
 
+pub mod analysis;
 pub mod code_block;
 pub mod error;
 pub mod function;
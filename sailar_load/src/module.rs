@@ -24,6 +24,7 @@ pub struct Module {
     type_signatures: Box<[Arc<type_system::Signature>]>,
     function_signatures: Box<[Arc<function::Signature>]>,
     code_blocks: Box<[Arc<code_block::Code>]>,
+    function_imports: Box<[Arc<function::ImportedTemplate>]>,
     defined_function_templates: Box<[Arc<function::DefinedTemplate>]>,
     functions: Box<[Arc<function::Function>]>,
 }
@@ -39,6 +40,7 @@ impl Module {
                 .collect();
 
             let mut symbols = crate::symbol::Lookup::new();
+            let import_count = contents.function_imports.len();
 
             Self {
                 loader,
@@ -63,13 +65,18 @@ impl Module {
                     .enumerate()
                     .map(|(index, code)| code_block::Code::new(code, index.into(), this.clone()))
                     .collect(),
+                function_imports: contents
+                    .function_imports
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, import)| function::ImportedTemplate::new(import, index.into(), this.clone()))
+                    .collect(),
                 defined_function_templates: contents
                     .function_templates
                     .into_iter()
                     .enumerate()
-                    // TODO: Add number of imported function templates to index
                     .map(|(index, template)| {
-                        let a = function::DefinedTemplate::new(template, index.into(), this.clone());
+                        let a = function::DefinedTemplate::new(template, (import_count + index).into(), this.clone());
                         if let Some(symbol) = a.to_symbol() {
                             symbols.insert(symbol);
                         }
@@ -130,6 +137,10 @@ impl Module {
         &self.code_blocks
     }
 
+    pub fn function_imports(&self) -> &[Arc<function::ImportedTemplate>] {
+        &self.function_imports
+    }
+
     pub fn defined_function_templates(&self) -> &[Arc<function::DefinedTemplate>] {
         &self.defined_function_templates
     }
@@ -138,9 +149,16 @@ impl Module {
         &self.functions
     }
 
+    /// Gets the function template with the specified index, where imported function templates come before the function
+    /// templates defined in this module.
     pub fn index_function_template(&self, index: sailar::index::FunctionTemplate) -> function::Template {
-        // TODO: Handle imported function templates
-        self.defined_function_templates[usize::from(index)].clone().into()
+        let index = usize::from(index);
+        match self.function_imports.get(index) {
+            Some(import) => import.clone().into(),
+            None => self.defined_function_templates[index - self.function_imports.len()]
+                .clone()
+                .into(),
+        }
     }
 }
 
@@ -8,7 +8,7 @@ use std::cmp::PartialEq;
 use std::fmt::{Debug, Display, Formatter};
 use std::sync::{Arc, Weak};
 
-pub use signature::{IntegerSign, IntegerSize, IntegerType};
+pub use signature::{IntegerSign, IntegerSize, IntegerType, VectorElementType, VectorType};
 
 #[derive(Clone, Debug)]
 pub enum Type {
@@ -19,6 +19,7 @@ pub enum Type {
     F64,
     RawPtr(Option<Arc<Signature>>),
     FuncPtr(Arc<crate::function::Signature>),
+    Vector(VectorType),
 }
 
 impl Type {
@@ -32,6 +33,7 @@ impl Type {
             signature::Type::RawPtr(None) => Type::RawPtr(None),
             signature::Type::RawPtr(Some(pointee)) => Self::RawPtr(Some(module.type_signatures()[usize::from(*pointee)].clone())),
             signature::Type::FuncPtr(signature) => Self::FuncPtr(module.function_signatures()[usize::from(*signature)].clone()),
+            signature::Type::Vector(ty) => Type::Vector(*ty),
         }
     }
 
@@ -49,6 +51,16 @@ impl From<IntegerType> for Type {
     }
 }
 
+impl From<VectorElementType> for Type {
+    fn from(ty: VectorElementType) -> Self {
+        match ty {
+            VectorElementType::Integer(integer_type) => Self::FixedInteger(integer_type),
+            VectorElementType::F32 => Self::F32,
+            VectorElementType::F64 => Self::F64,
+        }
+    }
+}
+
 impl Display for Type {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
         match self {
@@ -60,6 +72,7 @@ impl Display for Type {
             Self::RawPtr(None) => f.write_str("voidptr"),
             Self::RawPtr(Some(pointee)) => write!(f, "rawptr({})", pointee),
             Self::FuncPtr(signature) => write!(f, "funcptr({})", signature),
+            Self::Vector(ty) => Display::fmt(ty, f),
         }
     }
 }
@@ -72,6 +85,7 @@ impl PartialEq for Type {
             (Self::UAddr, Self::UAddr) | (Self::F32, Self::F32) | (Self::F64, Self::F64) => true,
             (Self::RawPtr(x), Self::RawPtr(y)) => x == y,
             (Self::FuncPtr(x), Self::FuncPtr(y)) => x == y,
+            (Self::Vector(x), Self::Vector(y)) => x == y,
             _ => false,
         }
     }
@@ -4,10 +4,21 @@ use sailar::builder::Builder;
 use sailar::identifier::{Id, Identifier};
 use sailar::index;
 use sailar::instruction::{self, Instruction};
+use sailar::module_builder::ModuleBuilder;
 use sailar::num::VarU28;
 use sailar::record;
 use sailar::signature;
 
+fn sample_module_identifier(name: Identifier) -> record::ModuleIdentifier<'static> {
+    record::ModuleIdentifier::new_owned(name, vec![VarU28::from_u8(1), VarU28::from_u8(1)])
+}
+
+fn builder_with_identifier(name: Identifier) -> Builder<'static> {
+    let mut builder = Builder::new();
+    builder.add_record(record::MetadataField::ModuleIdentifier(sample_module_identifier(name)));
+    builder
+}
+
 /// Produces a sample program containing an entry point function that simple returns with the specified exit code.
 ///
 /// # Examples
@@ -22,52 +33,623 @@ use sailar::signature;
 /// # Result::<_, Box<dyn std::error::Error>>::Ok(())
 /// ```
 pub fn exit_with(name: Identifier, exit_code: u32) -> Builder<'static> {
-    let mut builder = Builder::new();
+    let mut module = ModuleBuilder::new();
+    module.set_module_identifier(sample_module_identifier(name));
 
-    builder.add_record(record::MetadataField::ModuleIdentifier(record::ModuleIdentifier::new_owned(
-        name,
-        vec![VarU28::from_u8(1), VarU28::from_u8(1)],
-    )));
+    let integer_type = module.type_signature(signature::Type::from(signature::IntegerType::S32));
+    let main_signature = module.function_signature([], [integer_type]);
+    let main = module.define_function(Identifier::try_from_str("main").unwrap(), main_signature);
+    module
+        .entry_block(main)
+        .ret(vec![instruction::ConstantInteger::I32(exit_code.to_le_bytes()).into()]);
+    module.set_entry_point(main);
 
-    let integer_type = {
-        builder.add_record(signature::Type::from(signature::IntegerType::S32));
-        index::TypeSignature::from(0)
-    };
+    let mut builder = Builder::new();
+    builder.extend(module.into_records().unwrap());
+    builder
+}
 
-    let main_signature = {
-        builder.add_record(signature::Function::new([].as_slice(), vec![integer_type]));
-        index::FunctionSignature::from(0)
-    };
+fn arithmetic(
+    overflow_behavior: instruction::OverflowBehavior,
+    x: instruction::Value,
+    y: instruction::Value,
+) -> Box<instruction::IntegerArithmetic> {
+    Box::new(instruction::IntegerArithmetic::new(overflow_behavior, x, y))
+}
 
-    let main_code = {
-        let instructions = vec![Instruction::Return(
-            vec![instruction::ConstantInteger::I32(exit_code.to_le_bytes()).into()].into_boxed_slice(),
-        )];
+fn comparison(kind: instruction::ComparisonKind, x: instruction::Value, y: instruction::Value) -> Box<instruction::Comparison> {
+    Box::new(instruction::Comparison::new(kind, x, y))
+}
 
-        builder.add_record(record::CodeBlock::new(
+/// Produces a sample program whose entry point returns the `n`th fibonacci number, calculated with a naive recursive function.
+///
+/// # Examples
+///
+/// ```
+/// use sailar::validation::ValidModule;
+///
+/// let program = sailar_samples::fibonacci("Fibonacci".try_into()?, 10);
+///
+/// ValidModule::from_builder(program)?;
+///
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn fibonacci(name: Identifier, n: u32) -> Builder<'static> {
+    // main: %t0 = call fib (n); ret %t0
+    fibonacci_with_main(name, |integer_type, fib_function, _| {
+        record::CodeBlock::new(
             [].as_slice(),
             vec![integer_type],
+            vec![integer_type],
+            vec![
+                Instruction::Call(fib_function, vec![n.into()].into_boxed_slice()),
+                Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice()),
+            ],
+        )
+    })
+}
+
+/// Produces a sample program whose entry point returns the `n + 2`th fibonacci number, by calculating the `n`th and `n + 1`th
+/// fibonacci numbers in two separate threads.
+///
+/// # Examples
+///
+/// ```
+/// use sailar::validation::ValidModule;
+///
+/// let program = sailar_samples::parallel_fibonacci("ParallelFibonacci".try_into()?, 10);
+///
+/// ValidModule::from_builder(program)?;
+///
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn parallel_fibonacci(name: Identifier, n: u32) -> Builder<'static> {
+    // main: %t0 = thread.spawn fib (n); %t1 = thread.spawn fib (n + 1); %t2 = thread.join %t0; %t3 = thread.join %t1;
+    // %t4 = iadd %t2 %t3; ret %t4
+    fibonacci_with_main(name, |integer_type, fib_function, fib_signature| {
+        record::CodeBlock::new(
             [].as_slice(),
-            instructions,
-        ));
-        index::CodeBlock::from(0)
-    };
+            vec![integer_type],
+            vec![integer_type; 5],
+            vec![
+                Instruction::ThreadSpawn(fib_function, vec![n.into()].into_boxed_slice()),
+                Instruction::ThreadSpawn(fib_function, vec![(n + 1).into()].into_boxed_slice()),
+                Instruction::ThreadJoin(fib_signature, index::Register::from(0).into()),
+                Instruction::ThreadJoin(fib_signature, index::Register::from(1).into()),
+                Instruction::IAdd(arithmetic(
+                    instruction::OverflowBehavior::Ignore,
+                    index::Register::from(2).into(),
+                    index::Register::from(3).into(),
+                )),
+                Instruction::Return(vec![index::Register::from(4).into()].into_boxed_slice()),
+            ],
+        )
+    })
+}
 
-    let main_template = {
-        builder.add_record(record::FunctionTemplate::new(
-            record::Export::ExportBorrowed(Id::try_from_str("main").unwrap()),
-            main_signature,
-            main_code,
-        ));
-        index::FunctionTemplate::from(0)
-    };
+/// Produces a program containing a naive recursive fibonacci function, with an entry point whose code is produced by the
+/// `main` closure, which is given the integer type, the fibonacci function, and its signature.
+fn fibonacci_with_main<M>(name: Identifier, main: M) -> Builder<'static>
+where
+    M: FnOnce(index::TypeSignature, index::Function, index::FunctionSignature) -> record::CodeBlock<'static>,
+{
+    let mut builder = builder_with_identifier(name);
+    let integer_type = index::TypeSignature::from(0);
+    let main_signature = index::FunctionSignature::from(0);
+    let fib_signature = index::FunctionSignature::from(1);
+    let fib_function = index::Function::from(1);
+
+    builder.add_record(signature::Type::from(signature::IntegerType::U32));
+    builder.add_record(signature::Function::new([].as_slice(), vec![integer_type]));
+    builder.add_record(signature::Function::new(vec![integer_type], vec![integer_type]));
+    builder.add_record(main(integer_type, fib_function, fib_signature));
+
+    // fib entry: %t0 = cmp %i0 lt 2; br.if %t0 then base else recursive (%i0)
+    builder.add_record(record::CodeBlock::new(
+        vec![integer_type],
+        vec![integer_type],
+        vec![integer_type],
+        vec![
+            Instruction::Cmp(comparison(
+                instruction::ComparisonKind::LessThan,
+                index::Register::from(0).into(),
+                2u32.into(),
+            )),
+            Instruction::BrIf(Box::new(instruction::BranchIf::new(
+                index::Register::from(1).into(),
+                index::CodeBlock::from(2),
+                index::CodeBlock::from(3),
+                vec![index::Register::from(0).into()].into_boxed_slice(),
+            ))),
+        ],
+    ));
+
+    // base case: ret %i0
+    builder.add_record(record::CodeBlock::new(
+        vec![integer_type],
+        vec![integer_type],
+        [].as_slice(),
+        vec![Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice())],
+    ));
+
+    // recursive case: fib(n - 1) + fib(n - 2)
+    builder.add_record(record::CodeBlock::new(
+        vec![integer_type],
+        vec![integer_type],
+        vec![integer_type; 5],
+        vec![
+            Instruction::ISub(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                index::Register::from(0).into(),
+                1u32.into(),
+            )),
+            Instruction::Call(fib_function, vec![index::Register::from(1).into()].into_boxed_slice()),
+            Instruction::ISub(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                index::Register::from(0).into(),
+                2u32.into(),
+            )),
+            Instruction::Call(fib_function, vec![index::Register::from(3).into()].into_boxed_slice()),
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                index::Register::from(2).into(),
+                index::Register::from(4).into(),
+            )),
+            Instruction::Return(vec![index::Register::from(5).into()].into_boxed_slice()),
+        ],
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::ExportBorrowed(Id::try_from_str("main").unwrap()),
+        main_signature,
+        index::CodeBlock::from(0),
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::PrivateBorrowed(Id::try_from_str("fib").unwrap()),
+        fib_signature,
+        index::CodeBlock::from(1),
+    ));
+
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(1)));
+    builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
+
+    builder
+}
+
+/// Produces a sample program whose entry point uses a loop to return the sum of all integers from `0` to `count - 1`,
+/// wrapping on overflow.
+///
+/// # Examples
+///
+/// ```
+/// use sailar::validation::ValidModule;
+///
+/// let program = sailar_samples::sum_loop("Loop".try_into()?, 100);
+///
+/// ValidModule::from_builder(program)?;
+///
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn sum_loop(name: Identifier, count: u32) -> Builder<'static> {
+    let mut builder = builder_with_identifier(name);
+    let integer_type = index::TypeSignature::from(0);
+    let loop_block = index::CodeBlock::from(1);
+    let counter = index::Register::from(0);
+    let sum = index::Register::from(1);
+
+    builder.add_record(signature::Type::from(signature::IntegerType::U32));
+    builder.add_record(signature::Function::new([].as_slice(), vec![integer_type]));
+
+    // entry: br loop (0, 0)
+    builder.add_record(record::CodeBlock::new(
+        [].as_slice(),
+        vec![integer_type],
+        [].as_slice(),
+        vec![Instruction::Br(loop_block, vec![0u32.into(), 0u32.into()].into_boxed_slice())],
+    ));
+
+    // loop: %t0 = cmp %i0 lt count; br.if %t0 then body else exit (%i0, %i1)
+    builder.add_record(record::CodeBlock::new(
+        vec![integer_type; 2],
+        vec![integer_type],
+        vec![integer_type],
+        vec![
+            Instruction::Cmp(comparison(
+                instruction::ComparisonKind::LessThan,
+                counter.into(),
+                count.into(),
+            )),
+            Instruction::BrIf(Box::new(instruction::BranchIf::new(
+                index::Register::from(2).into(),
+                index::CodeBlock::from(2),
+                index::CodeBlock::from(3),
+                vec![counter.into(), sum.into()].into_boxed_slice(),
+            ))),
+        ],
+    ));
+
+    // body: %t0 = iadd %i1 %i0; %t1 = iadd %i0 1; br loop (%t1, %t0)
+    builder.add_record(record::CodeBlock::new(
+        vec![integer_type; 2],
+        vec![integer_type],
+        vec![integer_type; 2],
+        vec![
+            Instruction::IAdd(arithmetic(instruction::OverflowBehavior::Ignore, sum.into(), counter.into())),
+            Instruction::IAdd(arithmetic(instruction::OverflowBehavior::Ignore, counter.into(), 1u32.into())),
+            Instruction::Br(
+                loop_block,
+                vec![index::Register::from(3).into(), index::Register::from(2).into()].into_boxed_slice(),
+            ),
+        ],
+    ));
 
-    let entry_point = {
-        builder.add_record(record::Function::with_template(main_template));
-        index::Function::from(0)
+    // exit: ret %i1
+    builder.add_record(record::CodeBlock::new(
+        vec![integer_type; 2],
+        vec![integer_type],
+        [].as_slice(),
+        vec![Instruction::Return(vec![sum.into()].into_boxed_slice())],
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::ExportBorrowed(Id::try_from_str("main").unwrap()),
+        index::FunctionSignature::from(0),
+        index::CodeBlock::from(0),
+    ));
+
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+    builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
+
+    builder
+}
+
+/// Produces a sample program whose entry point takes a pointer to a `u32` counter, and starts two threads that each
+/// atomically increment the counter `increments` times.
+///
+/// The entry point returns the final value of the counter, and `1` if it then successfully reset the counter to `0` with a
+/// compare-and-swap.
+///
+/// # Examples
+///
+/// ```
+/// use sailar::validation::ValidModule;
+///
+/// let program = sailar_samples::atomic_counter("Counter".try_into()?, 1000);
+///
+/// ValidModule::from_builder(program)?;
+///
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn atomic_counter(name: Identifier, increments: u32) -> Builder<'static> {
+    let mut builder = builder_with_identifier(name);
+    let integer_type = index::TypeSignature::from(0);
+    let pointer_type = index::TypeSignature::from(1);
+    let worker_function = index::Function::from(1);
+    let worker_signature = index::FunctionSignature::from(1);
+    let loop_block = index::CodeBlock::from(2);
+    let counter = index::Register::from(0);
+
+    builder.add_record(signature::Type::from(signature::IntegerType::U32));
+    builder.add_record(signature::Type::RawPtr(Some(integer_type)));
+    builder.add_record(signature::Function::new(vec![pointer_type], vec![integer_type; 2]));
+    builder.add_record(signature::Function::new(vec![pointer_type], [].as_slice()));
+
+    // main: atomic.store seq_cst %i0 0; %t0 = thread.spawn worker (%i0); %t1 = thread.spawn worker (%i0);
+    // thread.join %t0; thread.join %t1; fence seq_cst; %t2 = atomic.load acquire %i0;
+    // %t3, %t4 = atomic.cmpxchg acq_rel acquire %i0 %t2 0; ret %t2, %t4
+    builder.add_record(record::CodeBlock::new(
+        vec![pointer_type],
+        vec![integer_type; 2],
+        vec![integer_type; 5],
+        vec![
+            Instruction::AtomicStore(Box::new(instruction::AtomicStore::new(
+                instruction::MemoryOrdering::SequentiallyConsistent,
+                counter.into(),
+                0u32.into(),
+            ))),
+            Instruction::ThreadSpawn(worker_function, vec![counter.into()].into_boxed_slice()),
+            Instruction::ThreadSpawn(worker_function, vec![counter.into()].into_boxed_slice()),
+            Instruction::ThreadJoin(worker_signature, index::Register::from(1).into()),
+            Instruction::ThreadJoin(worker_signature, index::Register::from(2).into()),
+            Instruction::Fence(instruction::MemoryOrdering::SequentiallyConsistent),
+            Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(
+                instruction::MemoryOrdering::Acquire,
+                counter.into(),
+            ))),
+            Instruction::AtomicCmpXchg(Box::new(instruction::AtomicCompareExchange::new(
+                instruction::MemoryOrdering::AcquireRelease,
+                instruction::MemoryOrdering::Acquire,
+                counter.into(),
+                index::Register::from(3).into(),
+                0u32.into(),
+            ))),
+            Instruction::Return(vec![index::Register::from(3).into(), index::Register::from(5).into()].into_boxed_slice()),
+        ],
+    ));
+
+    // worker entry: br loop (%i0, 0)
+    builder.add_record(record::CodeBlock::new(
+        vec![pointer_type],
+        [].as_slice(),
+        [].as_slice(),
+        vec![Instruction::Br(
+            loop_block,
+            vec![counter.into(), 0u32.into()].into_boxed_slice(),
+        )],
+    ));
+
+    // loop: %t0 = cmp %i1 lt increments; br.if %t0 then body else exit (%i0, %i1)
+    builder.add_record(record::CodeBlock::new(
+        vec![pointer_type, integer_type],
+        [].as_slice(),
+        vec![integer_type],
+        vec![
+            Instruction::Cmp(comparison(
+                instruction::ComparisonKind::LessThan,
+                index::Register::from(1).into(),
+                increments.into(),
+            )),
+            Instruction::BrIf(Box::new(instruction::BranchIf::new(
+                index::Register::from(2).into(),
+                index::CodeBlock::from(3),
+                index::CodeBlock::from(4),
+                vec![counter.into(), index::Register::from(1).into()].into_boxed_slice(),
+            ))),
+        ],
+    ));
+
+    // body: %t0 = atomic.rmw add relaxed %i0 1; %t1 = iadd %i1 1; br loop (%i0, %t1)
+    builder.add_record(record::CodeBlock::new(
+        vec![pointer_type, integer_type],
+        [].as_slice(),
+        vec![integer_type; 2],
+        vec![
+            Instruction::AtomicRmw(Box::new(instruction::AtomicReadModifyWrite::new(
+                instruction::AtomicOperation::Add,
+                instruction::MemoryOrdering::Relaxed,
+                counter.into(),
+                1u32.into(),
+            ))),
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                index::Register::from(1).into(),
+                1u32.into(),
+            )),
+            Instruction::Br(
+                loop_block,
+                vec![counter.into(), index::Register::from(3).into()].into_boxed_slice(),
+            ),
+        ],
+    ));
+
+    // exit: ret
+    builder.add_record(record::CodeBlock::new(
+        vec![pointer_type, integer_type],
+        [].as_slice(),
+        [].as_slice(),
+        vec![Instruction::Return(Box::default())],
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::ExportBorrowed(Id::try_from_str("main").unwrap()),
+        index::FunctionSignature::from(0),
+        index::CodeBlock::from(0),
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::PrivateBorrowed(Id::try_from_str("worker").unwrap()),
+        worker_signature,
+        index::CodeBlock::from(1),
+    ));
+
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(1)));
+    builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
+
+    builder
+}
+
+/// Produces a sample program whose entry point uses vector instructions to calculate `3 * value + 2`.
+///
+/// The vectors are passed between code blocks, and are combined with lane-wise arithmetic before individual lanes are
+/// extracted and summed.
+///
+/// # Examples
+///
+/// ```
+/// use sailar::validation::ValidModule;
+///
+/// let program = sailar_samples::vector_sum("Vectors".try_into()?, 5);
+///
+/// ValidModule::from_builder(program)?;
+///
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn vector_sum(name: Identifier, value: u32) -> Builder<'static> {
+    let mut builder = builder_with_identifier(name);
+    let integer_type = index::TypeSignature::from(0);
+    let vector_type = index::TypeSignature::from(1);
+    let pair_type = index::TypeSignature::from(2);
+    let register = index::Register::from;
+
+    let vector = |lane_count| {
+        signature::Type::Vector(signature::VectorType::new(
+            signature::IntegerType::U32.into(),
+            std::num::NonZeroU8::new(lane_count).unwrap(),
+        ))
     };
 
-    builder.add_record(record::MetadataField::EntryPoint(entry_point));
+    builder.add_record(signature::Type::from(signature::IntegerType::U32));
+    builder.add_record(vector(4));
+    builder.add_record(vector(2));
+    builder.add_record(signature::Function::new([].as_slice(), vec![integer_type]));
+
+    // entry: %t0 = vec.splat value; %t1 = vec.insert %t0 2 1; br sum (%t1, %t0)
+    builder.add_record(record::CodeBlock::new(
+        [].as_slice(),
+        vec![integer_type],
+        vec![vector_type; 2],
+        vec![
+            Instruction::VSplat(value.into()),
+            Instruction::VInsert(Box::new(instruction::VectorInsert::new(register(0).into(), 1u32.into(), 2))),
+            Instruction::Br(
+                index::CodeBlock::from(1),
+                vec![register(1).into(), register(0).into()].into_boxed_slice(),
+            ),
+        ],
+    ));
+
+    // sum: %t0 = iadd %i0 %i1; %t1 = isub %t0 %i1; %t2 = vec.shuffle %t0 %t1 (2, 6); %t3 = vec.extract %t2 0;
+    // %t4 = vec.extract %t2 1; %t5 = vec.extract %t0 0; %t6 = iadd %t3 %t4; %t7 = iadd %t6 %t5; ret %t7
+    builder.add_record(record::CodeBlock::new(
+        vec![vector_type; 2],
+        vec![integer_type],
+        vec![
+            vector_type,
+            vector_type,
+            pair_type,
+            integer_type,
+            integer_type,
+            integer_type,
+            integer_type,
+            integer_type,
+        ],
+        vec![
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                register(0).into(),
+                register(1).into(),
+            )),
+            Instruction::ISub(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                register(2).into(),
+                register(1).into(),
+            )),
+            Instruction::VShuffle(Box::new(instruction::VectorShuffle::new(
+                register(2).into(),
+                register(3).into(),
+                vec![2, 6].into_boxed_slice(),
+            ))),
+            Instruction::VExtract(register(4).into(), 0),
+            Instruction::VExtract(register(4).into(), 1),
+            Instruction::VExtract(register(2).into(), 0),
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                register(5).into(),
+                register(6).into(),
+            )),
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                register(8).into(),
+                register(7).into(),
+            )),
+            Instruction::Return(vec![register(9).into()].into_boxed_slice()),
+        ],
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::ExportBorrowed(Id::try_from_str("main").unwrap()),
+        index::FunctionSignature::from(0),
+        index::CodeBlock::from(0),
+    ));
+
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+    builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
+
+    builder
+}
+
+/// Produces a sample program whose entry point calculates `6 * value` by passing vectors to and returning vectors from a
+/// function, both by calling it and by executing it in a separate thread.
+///
+/// # Examples
+///
+/// ```
+/// use sailar::validation::ValidModule;
+///
+/// let program = sailar_samples::vector_call("VectorCall".try_into()?, 5);
+///
+/// ValidModule::from_builder(program)?;
+///
+/// # Result::<_, Box<dyn std::error::Error>>::Ok(())
+/// ```
+pub fn vector_call(name: Identifier, value: u32) -> Builder<'static> {
+    let mut builder = builder_with_identifier(name);
+    let integer_type = index::TypeSignature::from(0);
+    let vector_type = index::TypeSignature::from(1);
+    let double_signature = index::FunctionSignature::from(1);
+    let double_function = index::Function::from(1);
+    let register = index::Register::from;
+
+    builder.add_record(signature::Type::from(signature::IntegerType::U32));
+    builder.add_record(signature::Type::Vector(signature::VectorType::new(
+        signature::IntegerType::U32.into(),
+        std::num::NonZeroU8::new(4).unwrap(),
+    )));
+    builder.add_record(signature::Function::new([].as_slice(), vec![integer_type]));
+    builder.add_record(signature::Function::new(vec![vector_type], vec![vector_type]));
+
+    // main: %t0 = vec.splat value; %t1 = call double (%t0); %t2 = thread.spawn double (%t1); %t3 = thread.join %t2;
+    // %t4 = vec.extract %t3 0; %t5 = vec.extract %t1 3; %t6 = iadd %t4 %t5; ret %t6
+    builder.add_record(record::CodeBlock::new(
+        [].as_slice(),
+        vec![integer_type],
+        vec![
+            vector_type,
+            vector_type,
+            integer_type,
+            vector_type,
+            integer_type,
+            integer_type,
+            integer_type,
+        ],
+        vec![
+            Instruction::VSplat(value.into()),
+            Instruction::Call(double_function, vec![register(0).into()].into_boxed_slice()),
+            Instruction::ThreadSpawn(double_function, vec![register(1).into()].into_boxed_slice()),
+            Instruction::ThreadJoin(double_signature, register(2).into()),
+            Instruction::VExtract(register(3).into(), 0),
+            Instruction::VExtract(register(1).into(), 3),
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                register(4).into(),
+                register(5).into(),
+            )),
+            Instruction::Return(vec![register(6).into()].into_boxed_slice()),
+        ],
+    ));
+
+    // double: %t0 = iadd %i0 %i0; ret %t0
+    builder.add_record(record::CodeBlock::new(
+        vec![vector_type],
+        vec![vector_type],
+        vec![vector_type],
+        vec![
+            Instruction::IAdd(arithmetic(
+                instruction::OverflowBehavior::Ignore,
+                register(0).into(),
+                register(0).into(),
+            )),
+            Instruction::Return(vec![register(1).into()].into_boxed_slice()),
+        ],
+    ));
+
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::ExportBorrowed(Id::try_from_str("main").unwrap()),
+        index::FunctionSignature::from(0),
+        index::CodeBlock::from(0),
+    ));
+    builder.add_record(record::FunctionTemplate::new(
+        record::Export::ExportBorrowed(Id::try_from_str("double").unwrap()),
+        double_signature,
+        index::CodeBlock::from(1),
+    ));
+
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+    builder.add_record(record::Function::with_template(index::FunctionTemplate::from(1)));
+    builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
 
     builder
 }
@@ -0,0 +1,141 @@
+//! Module for performing atomic operations on host memory.
+//!
+//! Raw pointers in SAILAR code are host addresses. Atomic operations can only access memory in the regions that the embedder
+//! added to the runtime with [`Runtime::add_memory_region`](crate::runtime::Runtime::add_memory_region).
+
+use crate::bytecode::IntegerKind;
+use crate::error;
+use crate::runtime;
+use sailar::instruction::{AtomicOperation, MemoryOrdering};
+use std::ops::Range;
+use std::sync::atomic::{self, AtomicU16, AtomicU32, AtomicU64, AtomicU8};
+
+fn convert_ordering(ordering: MemoryOrdering) -> atomic::Ordering {
+    match ordering {
+        MemoryOrdering::Relaxed => atomic::Ordering::Relaxed,
+        MemoryOrdering::Acquire => atomic::Ordering::Acquire,
+        MemoryOrdering::Release => atomic::Ordering::Release,
+        MemoryOrdering::AcquireRelease => atomic::Ordering::AcqRel,
+        _ => atomic::Ordering::SeqCst,
+    }
+}
+
+/// Evaluates `$body` with `$atomic` referring to the atomic integer of the appropriate size at `$address`.
+macro_rules! with_atomic {
+    ($kind: expr, $address: expr, |$atomic: ident| $body: expr) => {
+        match $kind.byte_size() {
+            1 => {
+                let $atomic = &*($address as *const AtomicU8);
+                $body
+            }
+            2 => {
+                let $atomic = &*($address as *const AtomicU16);
+                $body
+            }
+            4 => {
+                let $atomic = &*($address as *const AtomicU32);
+                $body
+            }
+            8 => {
+                let $atomic = &*($address as *const AtomicU64);
+                // Conversions to u64 in the body are redundant for 64-bit integers.
+                #[allow(clippy::useless_conversion)]
+                let result = $body;
+                result
+            }
+            // Validation ensures that atomic operations are only performed on 8, 16, 32, or 64-bit integers
+            size => unreachable!("atomic operations on {size} byte integers are not supported"),
+        }
+    };
+}
+
+/// Checks that the `address` is aligned for an atomic access of the specified integer `kind`, and that the accessed integer
+/// is contained in one of the memory `regions`.
+pub(crate) fn check_address(regions: &[Range<usize>], kind: IntegerKind, address: u64) -> runtime::Result<()> {
+    let is_valid = usize::try_from(address).ok().is_some_and(|start| {
+        start != 0
+            && start.is_multiple_of(kind.byte_size())
+            && start
+                .checked_add(kind.byte_size())
+                .is_some_and(|end| regions.iter().any(|region| region.start <= start && end <= region.end))
+    });
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(error::RuntimeError::InvalidAtomicAddress(address))
+    }
+}
+
+/// # Safety
+///
+/// The `address` must be valid for reads of the specified integer `kind`, and must have been checked with [`check_address`].
+pub(crate) unsafe fn load(kind: IntegerKind, address: u64, ordering: MemoryOrdering) -> u64 {
+    with_atomic!(kind, address, |value| u64::from(value.load(convert_ordering(ordering))))
+}
+
+/// # Safety
+///
+/// The `address` must be valid for writes of the specified integer `kind`, and must have been checked with
+/// [`check_address`].
+pub(crate) unsafe fn store(kind: IntegerKind, address: u64, value: u64, ordering: MemoryOrdering) {
+    with_atomic!(kind, address, |destination| destination
+        .store(value as _, convert_ordering(ordering)))
+}
+
+/// Modifies the integer at the `address`, returning the previous value.
+///
+/// # Safety
+///
+/// The `address` must be valid for reads and writes of the specified integer `kind`, and must have been checked with
+/// [`check_address`].
+pub(crate) unsafe fn read_modify_write(
+    operation: AtomicOperation,
+    kind: IntegerKind,
+    address: u64,
+    value: u64,
+    ordering: MemoryOrdering,
+) -> u64 {
+    let ordering = convert_ordering(ordering);
+    with_atomic!(kind, address, |destination| {
+        let value = value as _;
+        u64::from(match operation {
+            AtomicOperation::Exchange => destination.swap(value, ordering),
+            AtomicOperation::Add => destination.fetch_add(value, ordering),
+            AtomicOperation::Sub => destination.fetch_sub(value, ordering),
+            AtomicOperation::And => destination.fetch_and(value, ordering),
+            AtomicOperation::Or => destination.fetch_or(value, ordering),
+            AtomicOperation::Xor => destination.fetch_xor(value, ordering),
+            _ => unreachable!("unknown atomic operation"),
+        })
+    })
+}
+
+/// Replaces the integer at the `address` if it is equal to `expected`, returning the previous value and whether it was
+/// replaced.
+///
+/// # Safety
+///
+/// The `address` must be valid for reads and writes of the specified integer `kind`, and must have been checked with
+/// [`check_address`].
+pub(crate) unsafe fn compare_exchange(
+    kind: IntegerKind,
+    address: u64,
+    expected: u64,
+    replacement: u64,
+    success_ordering: MemoryOrdering,
+    failure_ordering: MemoryOrdering,
+) -> (u64, bool) {
+    let success_ordering = convert_ordering(success_ordering);
+    let failure_ordering = convert_ordering(failure_ordering);
+    with_atomic!(kind, address, |destination| {
+        match destination.compare_exchange(expected as _, replacement as _, success_ordering, failure_ordering) {
+            Ok(previous) => (u64::from(previous), true),
+            Err(previous) => (u64::from(previous), false),
+        }
+    })
+}
+
+pub(crate) fn fence(ordering: MemoryOrdering) {
+    atomic::fence(convert_ordering(ordering))
+}
@@ -0,0 +1,983 @@
+//! Module for the compact bytecode executed by the interpreter.
+//!
+//! Before a function is executed for the first time, all code blocks reachable from its entry block are decoded into a flat
+//! sequence of [`Op`]s. Registers are resolved to slots in a contiguous register file, branch targets are resolved to offsets
+//! into the sequence, and constants are converted ahead of time, allowing the interpreter to avoid any lookups in the hot
+//! path.
+
+use crate::error;
+use crate::runtime;
+use crate::value;
+use sailar::instruction::{AtomicOperation, ComparisonKind, MemoryOrdering, OverflowBehavior};
+use sailar_load::code_block::{Code, TypedInstruction, TypedValue};
+use sailar_load::type_system::{self, IntegerSign};
+use std::collections::hash_map;
+#[cfg(feature = "jit")]
+use std::sync::atomic::{self, AtomicU32};
+use std::sync::Arc;
+
+/// The index of a register within the register window of a stack frame.
+pub type Slot = u32;
+
+/// Uniquely identifies a function that was decoded by a runtime.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[repr(transparent)]
+pub struct FunctionId(u32);
+
+impl From<FunctionId> for usize {
+    fn from(id: FunctionId) -> usize {
+        id.0 as usize
+    }
+}
+
+/// Describes how integer values are stored in the register file.
+///
+/// All integers are stored as 64-bit values, signed integers are sign extended and unsigned integers are zero extended.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct IntegerKind {
+    bits: u8,
+    signed: bool,
+}
+
+impl IntegerKind {
+    /// The kind used for thread identifiers returned to the host.
+    pub(crate) const THREAD_ID: Self = Self { bits: 64, signed: false };
+
+    #[cfg(feature = "jit")]
+    pub(crate) fn new(bits: u8, signed: bool) -> Self {
+        Self { bits, signed }
+    }
+
+    pub fn from_type(value_type: &type_system::Type) -> runtime::Result<Self> {
+        match value_type {
+            type_system::Type::FixedInteger(integer_type) if integer_type.size().bit_size().get() <= 64 => Ok(Self {
+                bits: integer_type.size().bit_size().get() as u8,
+                signed: integer_type.sign() == IntegerSign::Signed,
+            }),
+            type_system::Type::UAddr => Ok(Self { bits: 64, signed: false }),
+            type_system::Type::SAddr => Ok(Self { bits: 64, signed: true }),
+            // Raw pointers are host addresses.
+            type_system::Type::RawPtr(_) => Ok(Self {
+                bits: usize::BITS as u8,
+                signed: false,
+            }),
+            unsupported => Err(error::RuntimeError::UnsupportedType(unsupported.clone())),
+        }
+    }
+
+    pub fn bit_size(self) -> u8 {
+        self.bits
+    }
+
+    pub fn byte_size(self) -> usize {
+        usize::from(self.bits).div_ceil(8)
+    }
+
+    pub fn is_signed(self) -> bool {
+        self.signed
+    }
+
+    /// Truncates the `value` to fit in this integer type, then sign or zero extends it.
+    #[inline]
+    pub fn wrap(self, value: u64) -> u64 {
+        let shift = 64 - u32::from(self.bits);
+        if shift == 0 {
+            value
+        } else if self.signed {
+            (((value << shift) as i64) >> shift) as u64
+        } else {
+            (value << shift) >> shift
+        }
+    }
+
+    fn widen(self, value: u64) -> i128 {
+        if self.signed {
+            i128::from(value as i64)
+        } else {
+            i128::from(value)
+        }
+    }
+
+    fn minimum(self) -> i128 {
+        if self.signed {
+            -(1i128 << (self.bits - 1))
+        } else {
+            0
+        }
+    }
+
+    fn maximum(self) -> i128 {
+        if self.signed {
+            (1i128 << (self.bits - 1)) - 1
+        } else {
+            (1i128 << self.bits) - 1
+        }
+    }
+}
+
+/// An input to an operation.
+#[derive(Copy, Clone, Debug)]
+pub enum Operand {
+    Register(Slot),
+    Constant(u64),
+}
+
+/// A range of elements in one of the pools of a [`Function`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Span {
+    start: u32,
+    length: u32,
+}
+
+impl Span {
+    pub fn length(self) -> usize {
+        self.length as usize
+    }
+
+    fn range(self) -> std::ops::Range<usize> {
+        let start = self.start as usize;
+        start..start + self.length as usize
+    }
+}
+
+/// Copies a value into an input register of a code block.
+#[derive(Copy, Clone, Debug)]
+pub struct Move {
+    pub source: Operand,
+    pub destination: Slot,
+}
+
+/// A list of moves that are performed when branching to a code block.
+#[derive(Copy, Clone, Debug)]
+pub struct Moves {
+    span: Span,
+    /// If `true`, some of the destination registers are also read from, meaning values must be read before any are written.
+    parallel: bool,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum ArithmeticOperation {
+    Add,
+    Sub,
+}
+
+impl ArithmeticOperation {
+    #[inline]
+    fn apply_wrapping(self, x: u64, y: u64) -> u64 {
+        match self {
+            Self::Add => x.wrapping_add(y),
+            Self::Sub => x.wrapping_sub(y),
+        }
+    }
+
+    fn apply_exact(self, x: i128, y: i128) -> i128 {
+        match self {
+            Self::Add => x + y,
+            Self::Sub => x - y,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    Equal,
+    NotEqual,
+    LessThan,
+    GreaterThan,
+    LessThanOrEqual,
+    GreaterThanOrEqual,
+}
+
+impl Comparison {
+    fn from_kind(kind: ComparisonKind) -> runtime::Result<Self> {
+        match kind {
+            ComparisonKind::Equal => Ok(Self::Equal),
+            ComparisonKind::NotEqual => Ok(Self::NotEqual),
+            ComparisonKind::LessThan => Ok(Self::LessThan),
+            ComparisonKind::GreaterThan => Ok(Self::GreaterThan),
+            ComparisonKind::LessThanOrEqual => Ok(Self::LessThanOrEqual),
+            ComparisonKind::GreaterThanOrEqual => Ok(Self::GreaterThanOrEqual),
+            unknown => Err(error::RuntimeError::UnsupportedComparison(unknown)),
+        }
+    }
+
+    #[inline]
+    pub fn apply(self, signed: bool, x: u64, y: u64) -> bool {
+        let ordering = if signed { (x as i64).cmp(&(y as i64)) } else { x.cmp(&y) };
+        match self {
+            Self::Equal => ordering.is_eq(),
+            Self::NotEqual => ordering.is_ne(),
+            Self::LessThan => ordering.is_lt(),
+            Self::GreaterThan => ordering.is_gt(),
+            Self::LessThanOrEqual => ordering.is_le(),
+            Self::GreaterThanOrEqual => ordering.is_ge(),
+        }
+    }
+}
+
+/// A single decoded instruction.
+#[derive(Copy, Clone, Debug)]
+pub enum Op {
+    Nop,
+    Break,
+    Return(Span),
+    Jump {
+        target: u32,
+        moves: Moves,
+    },
+    JumpIf {
+        condition: Operand,
+        true_target: u32,
+        true_moves: Moves,
+        false_target: u32,
+        false_moves: Moves,
+    },
+    Call {
+        callee: FunctionId,
+        arguments: Span,
+        results: Slot,
+    },
+    /// An integer addition or subtraction where overflow is ignored.
+    Arithmetic {
+        operation: ArithmeticOperation,
+        kind: IntegerKind,
+        x: Operand,
+        y: Operand,
+        result: Slot,
+    },
+    /// An integer addition or subtraction that saturates or stores an overflow flag in the register after the `result`.
+    CheckedArithmetic {
+        operation: ArithmeticOperation,
+        overflow_behavior: OverflowBehavior,
+        kind: IntegerKind,
+        x: Operand,
+        y: Operand,
+        result: Slot,
+    },
+    Compare {
+        kind: Comparison,
+        signed: bool,
+        x: Operand,
+        y: Operand,
+        result: Slot,
+        true_value: u64,
+    },
+    ThreadSpawn {
+        callee: FunctionId,
+        arguments: Span,
+        result: Slot,
+        handle_kind: IntegerKind,
+    },
+    ThreadJoin {
+        thread: Operand,
+        results: Slot,
+        result_kinds: Span,
+    },
+    AtomicLoad {
+        ordering: MemoryOrdering,
+        kind: IntegerKind,
+        address: Operand,
+        result: Slot,
+    },
+    AtomicStore {
+        ordering: MemoryOrdering,
+        kind: IntegerKind,
+        address: Operand,
+        value: Operand,
+    },
+    AtomicReadModifyWrite {
+        operation: AtomicOperation,
+        ordering: MemoryOrdering,
+        kind: IntegerKind,
+        address: Operand,
+        value: Operand,
+        result: Slot,
+    },
+    /// An atomic compare-and-swap whose `operands` are the address, expected value, and replacement value. The register after
+    /// the `result` stores whether the value was replaced.
+    AtomicCompareExchange {
+        success_ordering: MemoryOrdering,
+        failure_ordering: MemoryOrdering,
+        kind: IntegerKind,
+        operands: Span,
+        result: Slot,
+        true_value: u64,
+    },
+    Fence(MemoryOrdering),
+    /// A lane-wise integer addition or subtraction, where each lane of the vectors is stored in a consecutive register.
+    VectorArithmetic {
+        operation: ArithmeticOperation,
+        overflow_behavior: OverflowBehavior,
+        kind: IntegerKind,
+        lanes: u8,
+        x: Slot,
+        y: Slot,
+        result: Slot,
+    },
+    VectorSplat {
+        value: Operand,
+        lanes: u8,
+        result: Slot,
+    },
+    /// Copies the register containing a single lane of a vector.
+    VectorExtract {
+        lane: Slot,
+        result: Slot,
+    },
+    VectorInsert {
+        vector: Slot,
+        value: Operand,
+        lane: u8,
+        lanes: u8,
+        result: Slot,
+    },
+    /// Selects lanes from the concatenation of the `x` and `y` vectors, which both have the specified number of `lanes`.
+    VectorShuffle {
+        x: Slot,
+        y: Slot,
+        lanes: u8,
+        indices: Span,
+        result: Slot,
+    },
+}
+
+impl Op {
+    /// Computes the result of an arithmetic operation that saturates or flags on overflow, returning the result and the
+    /// overflow flag.
+    #[inline(never)]
+    pub fn checked_arithmetic(
+        operation: ArithmeticOperation,
+        overflow_behavior: OverflowBehavior,
+        kind: IntegerKind,
+        x: u64,
+        y: u64,
+    ) -> (u64, bool) {
+        let exact = operation.apply_exact(kind.widen(x), kind.widen(y));
+        let wrapped = kind.wrap(operation.apply_wrapping(x, y));
+        let overflowed = kind.widen(wrapped) != exact;
+        match overflow_behavior {
+            OverflowBehavior::Saturate if overflowed => {
+                let saturated = if exact < kind.minimum() {
+                    kind.minimum()
+                } else {
+                    kind.maximum()
+                };
+                (kind.wrap(saturated as u64), overflowed)
+            }
+            _ => (wrapped, overflowed),
+        }
+    }
+}
+
+/// The decoded form of a function.
+#[derive(Debug)]
+pub struct Function {
+    id: FunctionId,
+    source: runtime::Function,
+    code: Box<[Op]>,
+    operands: Box<[Operand]>,
+    moves: Box<[Move]>,
+    lane_indices: Box<[u16]>,
+    result_kinds: Box<[IntegerKind]>,
+    argument_slots: Box<[Slot]>,
+    parameter_kinds: Box<[IntegerKind]>,
+    return_kinds: Box<[IntegerKind]>,
+    register_count: usize,
+    /// The code blocks that are reachable from the entry block, along with the offset of their first instruction.
+    block_offsets: Box<[(sailar::index::CodeBlock, u32)]>,
+    #[cfg(feature = "jit")]
+    call_count: AtomicU32,
+}
+
+impl Function {
+    pub fn id(&self) -> FunctionId {
+        self.id
+    }
+
+    /// The function that was decoded.
+    pub fn source(&self) -> &runtime::Function {
+        &self.source
+    }
+
+    #[inline]
+    pub fn code(&self) -> &[Op] {
+        &self.code
+    }
+
+    #[inline]
+    pub fn operands(&self, span: Span) -> &[Operand] {
+        &self.operands[span.range()]
+    }
+
+    #[inline]
+    pub fn moves(&self, moves: Moves) -> (&[Move], bool) {
+        (&self.moves[moves.span.range()], moves.parallel)
+    }
+
+    #[inline]
+    pub fn lane_indices(&self, span: Span) -> &[u16] {
+        &self.lane_indices[span.range()]
+    }
+
+    /// The types of the values returned by joined threads.
+    #[inline]
+    pub fn result_kinds(&self, span: Span) -> &[IntegerKind] {
+        &self.result_kinds[span.range()]
+    }
+
+    /// The slots of the input registers of the entry block, with a slot for each lane of a vector.
+    pub fn argument_slots(&self) -> &[Slot] {
+        &self.argument_slots
+    }
+
+    /// The kinds of the parameters, with each lane of a vector parameter passed as a separate value.
+    pub fn parameter_kinds(&self) -> &[IntegerKind] {
+        &self.parameter_kinds
+    }
+
+    /// The kinds of the return values, with each lane of a vector return value returned as a separate value.
+    pub fn return_kinds(&self) -> &[IntegerKind] {
+        &self.return_kinds
+    }
+
+    /// The total number of register slots needed to execute this function.
+    pub fn register_count(&self) -> usize {
+        self.register_count
+    }
+
+    /// Gets the range of decoded instructions corresponding to the instructions of a code `block`, returning `None` if the
+    /// block is not reachable from the entry block of this function.
+    fn block_range(&self, block: sailar::index::CodeBlock) -> Option<std::ops::Range<usize>> {
+        let index = self.block_offsets.iter().position(|(other, _)| *other == block)?;
+        let start = self.block_offsets[index].1 as usize;
+        let end = self
+            .block_offsets
+            .get(index + 1)
+            .map_or(self.code.len(), |(_, offset)| *offset as usize);
+        Some(start..end)
+    }
+
+    /// Gets the offset into the decoded [`code`](Function::code) of an `instruction` in a code `block`, returning `None` if
+    /// the block is not reachable from the entry block or does not contain the instruction.
+    ///
+    /// Each instruction in a code block is decoded into exactly one [`Op`].
+    pub fn instruction_offset(&self, block: sailar::index::CodeBlock, instruction: usize) -> Option<usize> {
+        let range = self.block_range(block)?;
+        let offset = range.start.checked_add(instruction)?;
+        if range.contains(&offset) {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+
+    /// Gets the code block and the index of the instruction within it that was decoded into the [`Op`] at the `offset`.
+    pub fn source_location(&self, offset: usize) -> Option<(sailar::index::CodeBlock, usize)> {
+        if offset >= self.code.len() {
+            return None;
+        }
+
+        let (block, start) = self
+            .block_offsets
+            .iter()
+            .take_while(|(_, start)| *start as usize <= offset)
+            .last()?;
+
+        Some((*block, offset - *start as usize))
+    }
+
+    /// Increments the number of times this function was called, returning the new count.
+    #[cfg(feature = "jit")]
+    pub(crate) fn increment_call_count(&self) -> u32 {
+        self.call_count.fetch_add(1, atomic::Ordering::Relaxed).saturating_add(1)
+    }
+
+    /// Converts the arguments passed to this function to their representation in the register file.
+    pub(crate) fn convert_arguments(
+        &self,
+        arguments: &[value::Value],
+        endianness: value::Endianness,
+    ) -> runtime::Result<Box<[u64]>> {
+        self.check_argument_count(arguments.len())?;
+
+        Ok(arguments
+            .iter()
+            .zip(self.parameter_kinds.iter())
+            .map(|(argument, kind)| kind.wrap(argument.to_bits(endianness)))
+            .collect())
+    }
+
+    pub(crate) fn check_argument_count(&self, actual: usize) -> runtime::Result<()> {
+        if actual == self.parameter_kinds.len() {
+            Ok(())
+        } else {
+            Err(error::RuntimeError::ArgumentCountMismatch {
+                expected: self.parameter_kinds.len(),
+                actual,
+            })
+        }
+    }
+
+    /// Converts values from the register file to the values returned by this function.
+    pub(crate) fn convert_return_values<R>(&self, return_values: R, endianness: value::Endianness) -> Box<[value::Value]>
+    where
+        R: IntoIterator<Item = u64>,
+    {
+        return_values
+            .into_iter()
+            .zip(self.return_kinds.iter())
+            .map(|(bits, kind)| value::Value::from_bits(bits, kind.byte_size(), endianness))
+            .collect()
+    }
+}
+
+fn kinds_from_signatures(types: &[Arc<type_system::Signature>]) -> runtime::Result<Box<[IntegerKind]>> {
+    let mut kinds = Vec::with_capacity(types.len());
+    for signature in types.iter() {
+        add_lane_kinds(signature.signature()?, &mut kinds)?;
+    }
+    Ok(kinds.into_boxed_slice())
+}
+
+/// Adds the kind of each register slot used to store a value of the specified type, with one slot for each lane of a vector.
+fn add_lane_kinds(value_type: &type_system::Type, kinds: &mut Vec<IntegerKind>) -> runtime::Result<()> {
+    match value_type {
+        type_system::Type::Vector(vector_type) => {
+            let kind = IntegerKind::from_type(&vector_type.element_type().into())?;
+            kinds.extend(std::iter::repeat_n(kind, usize::from(vector_type.lane_count().get())));
+        }
+        _ => kinds.push(IntegerKind::from_type(value_type)?),
+    }
+    Ok(())
+}
+
+/// Gets the number of register slots needed to store a value of the specified type, vectors store each lane in a separate slot.
+fn slot_count(value_type: &type_system::Type) -> u32 {
+    match value_type {
+        type_system::Type::Vector(vector_type) => u32::from(vector_type.lane_count().get()),
+        _ => 1,
+    }
+}
+
+fn convert_constant(constant: &sailar::instruction::Constant, kind: IntegerKind) -> u64 {
+    match constant {
+        sailar::instruction::Constant::Integer(integer) => {
+            let mut bytes = [0u8; 8];
+            let source: &[u8] = std::borrow::Borrow::borrow(integer);
+            let length = source.len().min(8);
+            bytes[..length].copy_from_slice(&source[..length]);
+            kind.wrap(u64::from_le_bytes(bytes))
+        }
+    }
+}
+
+/// Maps functions to their decoded bytecode, shared by all threads of a runtime.
+#[derive(Debug, Default)]
+pub(crate) struct FunctionTable {
+    lookup: rustc_hash::FxHashMap<*const sailar_load::function::Function, FunctionId>,
+    functions: Vec<(runtime::Function, Option<Arc<Function>>)>,
+}
+
+// Safety: The pointers are only used as keys, and the functions they point to are kept alive by the table.
+unsafe impl Send for FunctionTable {}
+
+impl FunctionTable {
+    pub(crate) fn register(&mut self, function: &runtime::Function) -> FunctionId {
+        match self.lookup.entry(Arc::as_ptr(function)) {
+            hash_map::Entry::Occupied(occupied) => *occupied.get(),
+            hash_map::Entry::Vacant(vacant) => {
+                let id = FunctionId(u32::try_from(self.functions.len()).expect("too many functions"));
+                self.functions.push((function.clone(), None));
+                *vacant.insert(id)
+            }
+        }
+    }
+
+    pub(crate) fn get_or_decode(&mut self, id: FunctionId) -> runtime::Result<Arc<Function>> {
+        if let Some(decoded) = &self.functions[usize::from(id)].1 {
+            return Ok(decoded.clone());
+        }
+
+        let decoded = Arc::new(self.decode(id, self.functions[usize::from(id)].0.clone())?);
+        self.functions[usize::from(id)].1 = Some(decoded.clone());
+        Ok(decoded)
+    }
+
+    fn decode(&mut self, id: FunctionId, function: runtime::Function) -> runtime::Result<Function> {
+        let module = sailar_load::module::Module::upgrade_weak(function.module())?;
+        let entry_block = function.template()?.as_definition()?.entry_block()?.clone();
+        let signature = function.signature()?;
+
+        let mut block_lookup = hash_map::HashMap::<*const Code, usize>::new();
+        /// A block, the slots of each of its input and temporary registers, and the offset of its first instruction.
+        type DecodedBlock = (Arc<Code>, Box<[Slot]>, u32);
+
+        let mut blocks = Vec::<DecodedBlock>::new();
+        let mut register_count = 0u32;
+
+        let mut add_block = |block: &Arc<Code>, blocks: &mut Vec<DecodedBlock>| -> runtime::Result<usize> {
+            Ok(match block_lookup.entry(Arc::as_ptr(block)) {
+                hash_map::Entry::Occupied(occupied) => *occupied.get(),
+                hash_map::Entry::Vacant(vacant) => {
+                    let index = blocks.len();
+                    let mut slots = Vec::with_capacity(block.input_types()?.len() + block.temporary_types()?.len());
+                    for register_type in block.input_types()?.iter().chain(block.temporary_types()?) {
+                        slots.push(register_count);
+                        register_count = register_count
+                            .checked_add(slot_count(register_type.signature()?))
+                            .expect("too many registers");
+                    }
+
+                    blocks.push((block.clone(), slots.into_boxed_slice(), 0));
+                    *vacant.insert(index)
+                }
+            })
+        };
+
+        add_block(&entry_block, &mut blocks)?;
+
+        // Discover all reachable blocks and assign the offsets of their first instruction.
+        let mut instruction_count = 0u32;
+        let mut block_index = 0;
+        while let Some((block, _, offset)) = blocks.get_mut(block_index) {
+            let block = block.clone();
+            *offset = instruction_count;
+            instruction_count += u32::try_from(block.typed_instructions()?.len()).expect("too many instructions");
+
+            for instruction in block.typed_instructions()?.iter() {
+                match instruction {
+                    TypedInstruction::Branch(target, _) => {
+                        add_block(&module.code_blocks()[usize::from(*target)], &mut blocks)?;
+                    }
+                    TypedInstruction::BranchIf(branch) => {
+                        add_block(&module.code_blocks()[usize::from(branch.true_branch())], &mut blocks)?;
+                        add_block(&module.code_blocks()[usize::from(branch.false_branch())], &mut blocks)?;
+                    }
+                    _ => (),
+                }
+            }
+
+            block_index += 1;
+        }
+
+        let mut code = Vec::with_capacity(instruction_count as usize);
+        let mut operands = Vec::new();
+        let mut moves = Vec::new();
+        let mut lane_indices = Vec::new();
+        let mut result_kinds = Vec::new();
+
+        for (block, slots, _) in blocks.iter() {
+            let input_count = block.input_types()?.len();
+            let mut next_temporary = 0usize;
+
+            let register_slot = |value: &TypedValue| match value.raw_value() {
+                sailar::instruction::Value::IndexedRegister(register) => slots[usize::from(*register)],
+                sailar::instruction::Value::Constant(_) => unreachable!("constants cannot be vectors"),
+            };
+
+            let operand = |value: &TypedValue| -> runtime::Result<Operand> {
+                Ok(match value.raw_value() {
+                    sailar::instruction::Value::IndexedRegister(register) => Operand::Register(slots[usize::from(*register)]),
+                    sailar::instruction::Value::Constant(constant) => {
+                        Operand::Constant(convert_constant(constant, IntegerKind::from_type(value.value_type())?))
+                    }
+                })
+            };
+
+            // Each lane of a vector is a separate operand
+            let add_operands = |values: &[TypedValue], operands: &mut Vec<Operand>| -> runtime::Result<Span> {
+                let start = operands.len();
+                for value in values.iter() {
+                    match operand(value)? {
+                        Operand::Register(slot) => {
+                            operands.extend((0..slot_count(value.value_type())).map(|lane| Operand::Register(slot + lane)))
+                        }
+                        constant => operands.push(constant),
+                    }
+                }
+                Ok(Span {
+                    start: start as u32,
+                    length: (operands.len() - start) as u32,
+                })
+            };
+
+            let mut add_moves = |target: sailar::index::CodeBlock, arguments: &[TypedValue]| -> runtime::Result<(u32, Moves)> {
+                let target_block = &blocks[block_lookup[&Arc::as_ptr(&module.code_blocks()[usize::from(target)])]];
+                let start = moves.len();
+                let mut parallel = false;
+
+                for (argument, destination) in arguments.iter().zip(target_block.1.iter().copied()) {
+                    // Each lane of a vector is moved separately
+                    for lane in 0..slot_count(argument.value_type()) {
+                        let source = match operand(argument)? {
+                            Operand::Register(source_slot) => {
+                                parallel |= moves[start..]
+                                    .iter()
+                                    .any(|existing: &Move| existing.destination == source_slot + lane);
+                                Operand::Register(source_slot + lane)
+                            }
+                            constant => constant,
+                        };
+
+                        moves.push(Move {
+                            source,
+                            destination: destination + lane,
+                        });
+                    }
+                }
+
+                Ok((
+                    target_block.2,
+                    Moves {
+                        span: Span {
+                            start: start as u32,
+                            length: (moves.len() - start) as u32,
+                        },
+                        parallel,
+                    },
+                ))
+            };
+
+            for instruction in block.typed_instructions()?.iter() {
+                let result = slots.get(input_count + next_temporary).copied().unwrap_or_default();
+
+                code.push(match instruction {
+                    TypedInstruction::Nop => Op::Nop,
+                    TypedInstruction::Break => Op::Break,
+                    TypedInstruction::Return(values) => Op::Return(add_operands(values, &mut operands)?),
+                    TypedInstruction::Branch(target, arguments) => {
+                        let (target, moves) = add_moves(*target, arguments)?;
+                        Op::Jump { target, moves }
+                    }
+                    TypedInstruction::BranchIf(branch) => {
+                        let (true_target, true_moves) = add_moves(branch.true_branch(), branch.arguments())?;
+                        let (false_target, false_moves) = add_moves(branch.false_branch(), branch.arguments())?;
+                        Op::JumpIf {
+                            condition: operand(branch.condition())?,
+                            true_target,
+                            true_moves,
+                            false_target,
+                            false_moves,
+                        }
+                    }
+                    TypedInstruction::Call(call) => {
+                        let callee = &module.functions()[usize::from(call.callee())];
+                        Op::Call {
+                            callee: self.register(callee),
+                            arguments: add_operands(call.arguments(), &mut operands)?,
+                            results: result,
+                        }
+                    }
+                    TypedInstruction::IAdd(arithmetic) | TypedInstruction::ISub(arithmetic) => {
+                        let operation = if let TypedInstruction::IAdd(_) = instruction {
+                            ArithmeticOperation::Add
+                        } else {
+                            ArithmeticOperation::Sub
+                        };
+
+                        match arithmetic.result_type() {
+                            type_system::Type::Vector(vector_type) => Op::VectorArithmetic {
+                                operation,
+                                overflow_behavior: arithmetic.overflow_behavior(),
+                                kind: IntegerKind::from_type(&vector_type.element_type().into())?,
+                                lanes: vector_type.lane_count().get(),
+                                x: register_slot(arithmetic.x_value()),
+                                y: register_slot(arithmetic.y_value()),
+                                result,
+                            },
+                            result_type => {
+                                let kind = IntegerKind::from_type(result_type)?;
+                                let x = operand(arithmetic.x_value())?;
+                                let y = operand(arithmetic.y_value())?;
+                                match arithmetic.overflow_behavior() {
+                                    OverflowBehavior::Ignore => Op::Arithmetic {
+                                        operation,
+                                        kind,
+                                        x,
+                                        y,
+                                        result,
+                                    },
+                                    overflow_behavior => Op::CheckedArithmetic {
+                                        operation,
+                                        overflow_behavior,
+                                        kind,
+                                        x,
+                                        y,
+                                        result,
+                                    },
+                                }
+                            }
+                        }
+                    }
+                    TypedInstruction::Cmp(comparison) => Op::Compare {
+                        kind: Comparison::from_kind(comparison.kind())?,
+                        signed: IntegerKind::from_type(comparison.x_value().value_type())?.is_signed(),
+                        x: operand(comparison.x_value())?,
+                        y: operand(comparison.y_value())?,
+                        result,
+                        true_value: IntegerKind::from_type(comparison.result_type())?.wrap(1),
+                    },
+                    TypedInstruction::ThreadSpawn(spawn) => {
+                        let callee = &module.functions()[usize::from(spawn.callee())];
+                        Op::ThreadSpawn {
+                            callee: self.register(callee),
+                            arguments: add_operands(spawn.arguments(), &mut operands)?,
+                            result,
+                            handle_kind: IntegerKind::from_type(spawn.handle_type())?,
+                        }
+                    }
+                    TypedInstruction::ThreadJoin(join) => {
+                        let start = result_kinds.len();
+                        for result_type in join.result_types().iter() {
+                            add_lane_kinds(result_type, &mut result_kinds)?;
+                        }
+
+                        Op::ThreadJoin {
+                            thread: operand(join.thread())?,
+                            results: result,
+                            result_kinds: Span {
+                                start: start as u32,
+                                length: (result_kinds.len() - start) as u32,
+                            },
+                        }
+                    }
+                    TypedInstruction::AtomicLoad(load) => Op::AtomicLoad {
+                        ordering: load.ordering(),
+                        kind: IntegerKind::from_type(load.result_type())?,
+                        address: operand(load.address())?,
+                        result,
+                    },
+                    TypedInstruction::AtomicStore(store) => Op::AtomicStore {
+                        ordering: store.ordering(),
+                        kind: IntegerKind::from_type(store.value().value_type())?,
+                        address: operand(store.address())?,
+                        value: operand(store.value())?,
+                    },
+                    TypedInstruction::AtomicRmw(operation) => Op::AtomicReadModifyWrite {
+                        operation: operation.operation(),
+                        ordering: operation.ordering(),
+                        kind: IntegerKind::from_type(operation.result_type())?,
+                        address: operand(operation.address())?,
+                        value: operand(operation.value())?,
+                        result,
+                    },
+                    TypedInstruction::AtomicCmpXchg(operation) => Op::AtomicCompareExchange {
+                        success_ordering: operation.success_ordering(),
+                        failure_ordering: operation.failure_ordering(),
+                        kind: IntegerKind::from_type(operation.result_type())?,
+                        operands: add_operands(
+                            &[
+                                operation.address().clone(),
+                                operation.expected().clone(),
+                                operation.replacement().clone(),
+                            ],
+                            &mut operands,
+                        )?,
+                        result,
+                        true_value: IntegerKind::from_type(operation.success_type())?.wrap(1),
+                    },
+                    TypedInstruction::Fence(ordering) => Op::Fence(*ordering),
+                    TypedInstruction::VSplat(splat) => Op::VectorSplat {
+                        value: operand(splat.value())?,
+                        lanes: splat.result_type().lane_count().get(),
+                        result,
+                    },
+                    TypedInstruction::VExtract(extract) => Op::VectorExtract {
+                        lane: register_slot(extract.vector()) + u32::from(extract.lane()),
+                        result,
+                    },
+                    TypedInstruction::VInsert(insert) => Op::VectorInsert {
+                        vector: register_slot(insert.vector()),
+                        value: operand(insert.value())?,
+                        // Validation ensures that the lane index is less than the lane count
+                        lane: insert.lane() as u8,
+                        lanes: insert.result_type().lane_count().get(),
+                        result,
+                    },
+                    TypedInstruction::VShuffle(shuffle) => {
+                        let start = lane_indices.len() as u32;
+                        lane_indices.extend_from_slice(shuffle.lanes());
+                        Op::VectorShuffle {
+                            x: register_slot(shuffle.x_value()),
+                            y: register_slot(shuffle.y_value()),
+                            lanes: shuffle.vector_type().lane_count().get(),
+                            indices: Span {
+                                start,
+                                length: shuffle.lanes().len() as u32,
+                            },
+                            result,
+                        }
+                    }
+                });
+
+                next_temporary += instruction.result_count();
+            }
+        }
+
+        let mut argument_slots = Vec::new();
+        for (slot, input_type) in blocks[0].1.iter().zip(entry_block.input_types()?) {
+            argument_slots.extend((0..slot_count(input_type.signature()?)).map(|lane| slot + lane));
+        }
+
+        Ok(Function {
+            id,
+            argument_slots: argument_slots.into_boxed_slice(),
+            parameter_kinds: kinds_from_signatures(signature.parameter_types()?)?,
+            return_kinds: kinds_from_signatures(signature.return_types()?)?,
+            register_count: register_count as usize,
+            block_offsets: blocks.iter().map(|(block, _, offset)| (block.index(), *offset)).collect(),
+            #[cfg(feature = "jit")]
+            call_count: AtomicU32::new(0),
+            code: code.into_boxed_slice(),
+            operands: operands.into_boxed_slice(),
+            moves: moves.into_boxed_slice(),
+            lane_indices: lane_indices.into_boxed_slice(),
+            result_kinds: result_kinds.into_boxed_slice(),
+            source: function,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime;
+
+    #[test]
+    fn instruction_offsets_map_to_source_locations() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let module = loader
+            .load_module(
+                sailar::validation::ValidModule::from_builder(sailar_samples::sum_loop("Test".try_into().unwrap(), 10)).unwrap(),
+            )
+            .unwrap();
+
+        let runtime = runtime::Configuration::new().initialize_runtime();
+        let entry_point = module.entry_point().unwrap();
+        let decoded = runtime.decoded_function(entry_point).unwrap();
+        let entry_block = entry_point
+            .template()
+            .unwrap()
+            .as_definition()
+            .unwrap()
+            .entry_block()
+            .unwrap();
+        assert_eq!(decoded.instruction_offset(entry_block.index(), 0), Some(0));
+
+        let mut offsets = Vec::new();
+        for block in module.code_blocks().iter() {
+            let instruction_count = block.typed_instructions().unwrap().len();
+            for instruction in 0..instruction_count {
+                if let Some(offset) = decoded.instruction_offset(block.index(), instruction) {
+                    assert_eq!(decoded.source_location(offset), Some((block.index(), instruction)));
+                    offsets.push(offset);
+                }
+            }
+
+            assert_eq!(decoded.instruction_offset(block.index(), instruction_count), None);
+        }
+
+        offsets.sort_unstable();
+        assert_eq!(offsets, (0..decoded.code().len()).collect::<Vec<_>>());
+        assert_eq!(decoded.source_location(decoded.code().len()), None);
+    }
+}
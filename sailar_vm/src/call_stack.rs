@@ -1,156 +1,50 @@
 //! Module for interacting with the SAILAR virtual machine's call stack.
 
+use crate::bytecode;
+use crate::error;
 use crate::runtime;
-use crate::value;
-use sailar::instruction::{Constant, ConstantInteger};
-use sailar_load::code_block::TypedInstruction;
-use sailar_load::type_system;
-use std::cell::Cell;
 use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
 
-type CodeBlock = std::sync::Arc<sailar_load::code_block::Code>;
-
-#[derive(Clone, Debug)]
-pub struct DefinedFrame {
-    arguments: Box<[value::Value]>,
-    block: CodeBlock,
-    instruction_index: Cell<usize>,
-    temporary_index: Cell<usize>,
-    temporary_registers: Box<[value::Value]>,
+/// A stack frame, containing the state of a function that is currently being executed.
+#[derive(Clone)]
+pub struct Frame {
+    pub(crate) function: Arc<bytecode::Function>,
+    pub(crate) instruction_index: usize,
+    pub(crate) register_base: usize,
+    /// The index in the register file where the return values are stored when this frame returns.
+    pub(crate) result_slot: usize,
 }
 
-impl DefinedFrame {
-    fn new(arguments: Box<[value::Value]>, block: CodeBlock) -> runtime::Result<Self> {
-        assert_eq!(arguments.len(), block.input_types()?.len());
-
-        Ok(Self {
-            arguments,
-            instruction_index: Cell::new(0),
-            temporary_index: Cell::new(0),
-            temporary_registers: vec![value::Value::default(); block.temporary_types()?.len()].into_boxed_slice(),
-            block,
-        })
+impl Frame {
+    pub fn function(&self) -> &runtime::Function {
+        self.function.source()
     }
 
-    pub fn arguments(&self) -> &[value::Value] {
-        &self.arguments
+    pub fn bytecode(&self) -> &Arc<bytecode::Function> {
+        &self.function
     }
 
+    /// The index of the next instruction to execute in the decoded bytecode of the function.
     pub fn instruction_index(&self) -> usize {
-        self.instruction_index.get()
-    }
-
-    pub fn temporary_registers(&self) -> &[value::Value] {
-        &self.temporary_registers[..self.temporary_index.get()]
+        self.instruction_index
     }
 
-    pub(crate) fn next_instruction(&self) -> runtime::Result<Option<&TypedInstruction>> {
-        let current_index = self.instruction_index.get();
-        let instruction = self.block.typed_instructions()?.get(current_index);
-        if instruction.is_some() {
-            self.instruction_index.set(current_index + 1);
-        }
-        Ok(instruction)
-    }
-
-    /// Defines a new temporary register to contain the specified `value`.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the number of defined temporary registers exceeds the number defined in the code block.
-    pub(crate) fn define_temporary(&mut self, value: value::Value) {
-        let current_index = self.temporary_index.get();
-
-        assert!(current_index < self.temporary_registers.len());
-
-        self.temporary_registers[current_index] = value;
-        self.temporary_index.set(current_index + 1);
-    }
-
-    /// Gets the value contained in the specified register.
-    ///
-    /// # Panics
-    ///
-    /// Panics if a register corresponding to the `index` is not defined.
-    pub(crate) fn get_register_value(&self, index: sailar::index::Register) -> &value::Value {
-        let i = usize::from(index);
-        if let Some(argument) = self.arguments.get(i) {
-            argument
-        } else {
-            self.temporary_registers()
-                .get(i - self.arguments.len())
-                .expect("register must be defined")
-        }
-    }
-
-    pub(crate) fn map_typed_value(
-        &self,
-        value: &sailar_load::code_block::TypedValue,
-        endianness: value::Endianness,
-    ) -> value::Value {
-        match value.raw_value() {
-            sailar::instruction::Value::Constant(constant) => match constant {
-                sailar::instruction::Constant::Integer(integer) => {
-                    let integer_type = match value.value_type() {
-                        type_system::Type::FixedInteger(ty) => ty,
-                        bad => panic!("expected integer type but got {:?}", bad),
-                    };
-
-                    value::Value::from_constant_integer(integer.clone(), *integer_type, endianness)
-                }
-            },
-            sailar::instruction::Value::IndexedRegister(index) => self.get_register_value(*index).clone(),
-        }
-    }
-
-    pub(crate) fn map_many_typed_values<'a, V>(&self, values: V, endianness: value::Endianness) -> Box<[value::Value]>
-    where
-        V: IntoIterator<Item = &'a sailar_load::code_block::TypedValue>,
-        V::IntoIter: std::iter::ExactSizeIterator,
-    {
-        values
-            .into_iter()
-            .map(|value| self.map_typed_value(value, endianness))
-            .collect()
+    pub fn return_types(&self) -> runtime::Result<&[Arc<sailar_load::type_system::Signature>]> {
+        Ok(self.function().signature()?.return_types()?)
     }
 }
 
-#[derive(Clone, Debug)]
-pub enum FrameKind {
-    Defined(DefinedFrame),
-    //Foreign(),
-}
-
-impl FrameKind {
-    pub fn arguments(&self) -> &[value::Value] {
-        match self {
-            FrameKind::Defined(defined) => defined.arguments(),
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-pub struct Frame {
-    function: runtime::Function,
-    kind: FrameKind,
-}
-
-impl Frame {
-    pub fn kind(&self) -> &FrameKind {
-        &self.kind
-    }
-
-    pub(crate) fn kind_mut(&mut self) -> &mut FrameKind {
-        &mut self.kind
-    }
-
-    pub fn return_types(&self) -> runtime::Result<&[std::sync::Arc<sailar_load::type_system::Signature>]> {
-        Ok(self.function.signature()?.return_types()?)
+impl Debug for Frame {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("Frame")
+            .field("function", &self.function().index())
+            .field("instruction_index", &self.instruction_index)
+            .field("register_base", &self.register_base)
+            .finish()
     }
 }
 
-//pub struct FrameDisplay
-
 /// Specifies the number of stack frames that the call stack can contain before a stack overflow occurs.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 #[repr(transparent)]
@@ -172,10 +66,12 @@ impl Size {
 }
 
 /// The SAILAR virtual machine call stack.
+///
+/// The registers of all frames are stored in a single contiguous register file, with each frame using a window starting
+/// after the registers of the previous frame.
 pub struct Stack {
-    // Frames are boxed since popping and pushing happens regularly
-    #[allow(clippy::vec_box)]
-    frames: Vec<Box<Frame>>,
+    pub(crate) frames: Vec<Frame>,
+    pub(crate) registers: Vec<u64>,
     size: Size,
 }
 
@@ -183,46 +79,55 @@ impl Stack {
     pub(crate) fn with_size(size: Size) -> Self {
         Self {
             frames: Default::default(),
+            registers: Default::default(),
             size,
         }
     }
 
     /// Returns an iterator over the call stack, yielding the most recently pushed frames first.
     pub fn iter_frames(&self) -> impl std::iter::ExactSizeIterator<Item = &Frame> {
-        self.frames.iter().map(std::borrow::Borrow::borrow).rev()
+        self.frames.iter().rev()
     }
 
     pub fn is_execution_ended(&self) -> bool {
         self.frames.is_empty()
     }
 
-    /// Pops the current stack frame.
-    ///
-    /// # Panics
-    ///
-    /// Panics when the call stack is empty.
-    pub(crate) fn pop(&mut self) -> Box<Frame> {
-        self.frames.pop().expect("call stack underflow")
+    /// Gets the number of frames that can be pushed before a stack overflow occurs.
+    pub fn remaining_frames(&self) -> usize {
+        self.size.get().get() - self.frames.len()
     }
 
-    pub(crate) fn push(&mut self, frame: Box<Frame>) {
-        self.frames.push(frame);
+    /// Gets the raw contents of the registers used by the specified `frame`.
+    pub fn frame_registers(&self, frame: &Frame) -> &[u64] {
+        &self.registers[frame.register_base..frame.register_base + frame.function.register_count()]
     }
 
-    pub(crate) fn push_new(&mut self, callee: runtime::Function, arguments: Box<[value::Value]>) -> runtime::Result<()> {
+    /// Pushes a new frame, reserving space for its registers after the registers of the current frame.
+    pub(crate) fn push_new(&mut self, function: Arc<bytecode::Function>, result_slot: usize) -> runtime::Result<&Frame> {
         if self.frames.len() == self.size.get().get() {
-            todo!("error stack overflow")
+            return Err(error::RuntimeError::StackOverflow(self.size.get()));
+        }
+
+        let register_base = self
+            .frames
+            .last()
+            .map(|previous| previous.register_base + previous.function.register_count())
+            .unwrap_or_default();
+
+        let register_end = register_base + function.register_count();
+        if self.registers.len() < register_end {
+            self.registers.resize(register_end, 0);
         }
 
-        self.push(Box::new(Frame {
-            kind: FrameKind::Defined(DefinedFrame::new(
-                arguments,
-                callee.template()?.as_definition()?.entry_block()?.clone(),
-            )?),
-            function: callee,
-        }));
+        self.frames.push(Frame {
+            function,
+            instruction_index: 0,
+            register_base,
+            result_slot,
+        });
 
-        Ok(())
+        Ok(self.frames.last().unwrap())
     }
 }
 
@@ -0,0 +1,218 @@
+//! Helpers for running an entry point with integer arguments written as text, such as those provided on a command line.
+
+use crate::error::RuntimeError;
+use crate::runtime::{self, Runtime};
+use crate::value::{Endianness, Value};
+use sailar_load::type_system::{IntegerSign, IntegerType, Type};
+use std::sync::Arc;
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error("entry point expects {expected} arguments, but {actual} were provided")]
+    ArgumentCountMismatch { expected: usize, actual: usize },
+    #[error("argument {0:?} is not an integer")]
+    InvalidInteger(Box<str>),
+    #[error("argument {argument} is out of range for type {integer_type}")]
+    ArgumentOutOfRange { argument: i128, integer_type: IntegerType },
+    #[error("arguments cannot be passed to parameters of type {0}")]
+    UnsupportedParameterType(Type),
+    #[error("values of type {0} cannot be returned from an entry point")]
+    UnsupportedReturnType(Type),
+    #[error(transparent)]
+    LoaderError(#[from] sailar_load::error::LoaderError),
+    #[error(transparent)]
+    RuntimeError(#[from] RuntimeError),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Gets the integer type of a parameter or return value, only integers of up to 64 bits are supported.
+fn supported_integer_type(value_type: &Type) -> Option<IntegerType> {
+    match value_type {
+        Type::FixedInteger(integer_type) if integer_type.size().bit_size().get() <= 64 => Some(*integer_type),
+        _ => None,
+    }
+}
+
+/// Gets the range of values that can be stored in an integer of up to 64 bits.
+fn integer_range(integer_type: IntegerType) -> std::ops::RangeInclusive<i128> {
+    let bits = u32::from(integer_type.size().bit_size().get());
+    match integer_type.sign() {
+        IntegerSign::Signed => -(1i128 << (bits - 1))..=(1i128 << (bits - 1)) - 1,
+        IntegerSign::Unsigned => 0..=(1i128 << bits) - 1,
+    }
+}
+
+/// Converts an `argument` into a value of the specified integer type, checking that it is within the range of the type.
+pub fn parse_integer(argument: &str, integer_type: IntegerType, endianness: Endianness) -> Result<Value> {
+    let integer = argument
+        .parse::<i128>()
+        .map_err(|_| Error::InvalidInteger(Box::from(argument)))?;
+
+    if !integer_range(integer_type).contains(&integer) {
+        return Err(Error::ArgumentOutOfRange {
+            argument: integer,
+            integer_type,
+        });
+    }
+
+    let byte_size = usize::from(integer_type.size().byte_size().get());
+    Ok(Value::from_bits(integer as u64, byte_size, endianness))
+}
+
+/// Converts a value of the specified integer type into an integer, sign extending it if the type is signed.
+pub fn to_integer(value: &Value, integer_type: IntegerType, endianness: Endianness) -> i128 {
+    let bits = u32::from(integer_type.size().bit_size().get());
+    let unused_bits = 128 - bits;
+    let value = i128::from(value.to_bits(endianness)) << unused_bits;
+    match integer_type.sign() {
+        IntegerSign::Signed => value >> unused_bits,
+        IntegerSign::Unsigned => ((value as u128) >> unused_bits) as i128,
+    }
+}
+
+/// Converts the `arguments` into the values passed to the parameters of the `entry_point`.
+///
+/// # Errors
+///
+/// Returns an error if the number of arguments does not match the number of parameters, if an argument is not an integer
+/// within the range of its parameter's type, or if a parameter is not an integer of at most 64 bits.
+pub fn parse_arguments<S: AsRef<str>>(
+    entry_point: &runtime::Function,
+    arguments: &[S],
+    endianness: Endianness,
+) -> Result<Vec<Value>> {
+    let parameter_types = entry_point.signature()?.parameter_types()?;
+    if parameter_types.len() != arguments.len() {
+        return Err(Error::ArgumentCountMismatch {
+            expected: parameter_types.len(),
+            actual: arguments.len(),
+        });
+    }
+
+    let mut values = Vec::with_capacity(arguments.len());
+    for (argument, parameter_type) in arguments.iter().zip(parameter_types.iter()) {
+        let parameter_type = parameter_type.signature()?;
+        let integer_type =
+            supported_integer_type(parameter_type).ok_or_else(|| Error::UnsupportedParameterType(parameter_type.clone()))?;
+        values.push(parse_integer(argument.as_ref(), integer_type, endianness)?);
+    }
+
+    Ok(values)
+}
+
+/// Converts the values returned by the `entry_point` into integers, sign extending the values of signed integer types.
+pub fn integer_return_values(
+    entry_point: &runtime::Function,
+    return_values: &[Value],
+    endianness: Endianness,
+) -> Result<Box<[i128]>> {
+    let return_types = entry_point.signature()?.return_types()?;
+    let mut integers = Vec::with_capacity(return_values.len());
+    for (value, return_type) in return_values.iter().zip(return_types.iter()) {
+        let return_type = return_type.signature()?;
+        let integer_type =
+            supported_integer_type(return_type).ok_or_else(|| Error::UnsupportedReturnType(return_type.clone()))?;
+        integers.push(to_integer(value, integer_type, endianness));
+    }
+
+    Ok(integers.into_boxed_slice())
+}
+
+/// Executes the `entry_point` with the specified `arguments`, returning its return values as integers.
+pub fn execute<S: AsRef<str>>(runtime: &Arc<Runtime>, entry_point: &runtime::Function, arguments: &[S]) -> Result<Box<[i128]>> {
+    let arguments = parse_arguments(entry_point, arguments, runtime.endianness())?;
+    let return_values = runtime.execute(entry_point.clone(), arguments)?;
+    integer_return_values(entry_point, &return_values, runtime.endianness())
+}
+
+/// Gets the exit code of a program from the first value returned by its entry point, or `0` if no values were returned.
+pub fn exit_code(return_values: &[i128]) -> i32 {
+    return_values.first().map_or(0, |exit_code| *exit_code as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Error;
+    use crate::runtime;
+    use crate::value::Endianness;
+    use sailar::module_builder::ModuleBuilder;
+    use sailar::signature::{IntegerType, Type};
+
+    /// Loads a program whose entry point returns its arguments.
+    fn identity(loader: &runtime::Loader, parameter_types: &[IntegerType]) -> runtime::Function {
+        let mut module = ModuleBuilder::new();
+        let types = parameter_types
+            .iter()
+            .map(|integer_type| module.type_signature(Type::from(*integer_type)))
+            .collect::<Vec<_>>();
+
+        let signature = module.function_signature(types.clone(), types);
+        let main = module.define_function(sailar::identifier::Identifier::try_from_str("main").unwrap(), signature);
+        let entry_block = module.entry_block(main);
+        let inputs = entry_block.inputs().map(Into::into).collect::<Vec<_>>();
+        entry_block.ret(inputs);
+        module.set_entry_point(main);
+
+        let module = loader.load_module(module.build().unwrap()).unwrap();
+        module.entry_point().unwrap().clone()
+    }
+
+    #[test]
+    fn signed_return_values_are_sign_extended() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = identity(&loader, &[IntegerType::S32, IntegerType::U32, IntegerType::S8]);
+        let runtime = runtime::Configuration::new().initialize_runtime();
+        let results = super::execute(&runtime, &entry_point, &["-1", "4294967295", "-128"]).unwrap();
+        assert_eq!(results.as_ref(), &[-1, 4294967295, -128]);
+        assert_eq!(super::exit_code(&results), -1);
+    }
+
+    #[test]
+    fn out_of_range_arguments_are_errors() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = identity(&loader, &[IntegerType::U8]);
+        for argument in ["256", "-1"] {
+            assert!(matches!(
+                super::parse_arguments(&entry_point, &[argument], Endianness::Little),
+                Err(Error::ArgumentOutOfRange {
+                    integer_type: IntegerType::U8,
+                    ..
+                })
+            ));
+        }
+
+        let entry_point = identity(&loader, &[IntegerType::S64]);
+        assert!(matches!(
+            super::parse_arguments(&entry_point, &["9223372036854775808"], Endianness::Little),
+            Err(Error::ArgumentOutOfRange { .. })
+        ));
+    }
+
+    #[test]
+    fn argument_count_mismatch_is_error() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = identity(&loader, &[IntegerType::S32, IntegerType::S32]);
+        assert!(matches!(
+            super::parse_arguments(&entry_point, &["1"], Endianness::Little),
+            Err(Error::ArgumentCountMismatch { expected: 2, actual: 1 })
+        ));
+    }
+
+    #[test]
+    fn non_integer_arguments_are_errors() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = identity(&loader, &[IntegerType::S32]);
+        assert!(matches!(
+            super::parse_arguments(&entry_point, &["one"], Endianness::Little),
+            Err(Error::InvalidInteger(_))
+        ));
+    }
+
+    #[test]
+    fn big_endian_values_are_converted() {
+        let value = super::parse_integer("-2", IntegerType::S16, Endianness::Big).unwrap();
+        assert_eq!(super::to_integer(&value, IntegerType::S16, Endianness::Big), -2);
+    }
+}
@@ -15,4 +15,26 @@
 pub enum RuntimeError {
     #[error(transparent)]
     LoaderError(#[from] sailar_load::error::LoaderError),
+    #[error("the maximum call stack size of {0} frames was exceeded")]
+    StackOverflow(std::num::NonZeroUsize),
+    #[error("values of type {0} are not supported by the interpreter")]
+    UnsupportedType(sailar_load::type_system::Type),
+    #[error("comparison kind {0} is not supported by the interpreter")]
+    UnsupportedComparison(sailar::instruction::ComparisonKind),
+    #[error("expected {expected} arguments, but got {actual}")]
+    ArgumentCountMismatch { expected: usize, actual: usize },
+    #[error("could not start thread: {0}")]
+    ThreadSpawnFailed(String),
+    #[error("thread {0} does not exist or was already joined")]
+    UnknownThread(u64),
+    #[error("thread {0} panicked")]
+    ThreadPanicked(u64),
+    #[error("thread {thread} returned {actual} values, but {expected} were expected")]
+    ThreadResultCountMismatch { thread: u64, expected: usize, actual: usize },
+    #[error("thread {0} returns values of different types than were expected")]
+    ThreadResultTypeMismatch(u64),
+    #[error("identifier of thread {thread} cannot be stored in a {bits}-bit thread handle")]
+    ThreadHandleOverflow { thread: u64, bits: u8 },
+    #[error("address {0:#X} is not aligned for an atomic operation or is outside of the memory accessible to SAILAR code")]
+    InvalidAtomicAddress(u64),
 }
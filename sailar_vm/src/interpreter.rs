@@ -1,28 +1,71 @@
 //! The SAILAR virtual machine bytecode interpreter, responsible for executing code.
 
+use crate::atomic;
+use crate::bytecode::{self, Op, Operand};
 use crate::call_stack;
 use crate::error;
 use crate::runtime::{self, Runtime};
 use crate::value::Value;
-use sailar_load::code_block::TypedInstruction;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
 
 /// Encapsulates all thread-local state needed to execute a single thread of SAILAR virtual machine bytecode.
+///
+/// Each state can be moved to a different thread, allowing multiple states sharing the same [`Runtime`] to run in parallel.
 pub struct State {
     runtime: Arc<Runtime>,
+    entry_point: Arc<bytecode::Function>,
     call_stack: call_stack::Stack,
+    /// Thread-local cache of decoded functions, used to avoid locking the runtime's function table on every call.
+    functions: Vec<Option<Arc<bytecode::Function>>>,
+    scratch: Vec<u64>,
+    executed_instruction_count: u64,
+    /// Thread-local cache of compiled functions, an inner value of `None` indicates that compilation failed.
+    #[cfg(feature = "jit")]
+    native_functions: Vec<Option<Option<crate::jit::NativeFunction>>>,
+    #[cfg(feature = "jit")]
+    native_results: Vec<u64>,
 }
 
 impl State {
     pub(crate) fn new(
         runtime: Arc<Runtime>,
         entry_point: runtime::Function,
-        arguments: Box<[Value]>,
+        arguments: &[Value],
     ) -> Result<Self, error::RuntimeError> {
-        let mut call_stack = call_stack::Stack::with_size(runtime.call_stack_size());
-        call_stack.push_new(entry_point, arguments)?;
-        Ok(Self { runtime, call_stack })
+        let (_, entry_point) = runtime.decode_entry_point(&entry_point)?;
+        let arguments = entry_point.convert_arguments(arguments, runtime.endianness())?;
+        Self::with_raw_arguments(runtime, entry_point, &arguments)
+    }
+
+    /// Creates a state that executes the decoded `entry_point`, with `arguments` in their register file representation.
+    pub(crate) fn with_raw_arguments(
+        runtime: Arc<Runtime>,
+        entry_point: Arc<bytecode::Function>,
+        arguments: &[u64],
+    ) -> Result<Self, error::RuntimeError> {
+        entry_point.check_argument_count(arguments.len())?;
+
+        let mut state = Self {
+            entry_point: entry_point.clone(),
+            call_stack: call_stack::Stack::with_size(runtime.call_stack_size()),
+            functions: Vec::new(),
+            scratch: Vec::new(),
+            executed_instruction_count: 0,
+            #[cfg(feature = "jit")]
+            native_functions: Vec::new(),
+            #[cfg(feature = "jit")]
+            native_results: Vec::new(),
+            runtime,
+        };
+
+        // The function cache is filled lazily by calls, so programs that make no calls do not allocate it.
+        let base = state.call_stack.push_new(entry_point.clone(), 0)?.register_base;
+        for (argument, slot) in arguments.iter().zip(entry_point.argument_slots()) {
+            state.call_stack.registers[base + *slot as usize] = *argument;
+        }
+
+        Ok(state)
     }
 
     pub fn runtime(&self) -> &Arc<Runtime> {
@@ -33,54 +76,416 @@ impl State {
         &self.call_stack
     }
 
-    pub(crate) fn step(&mut self) -> Result<Option<Box<[Value]>>, error::RuntimeError> {
-        // TODO: How to generate list of stack frames in debugger if current_frame is popped?
-        let mut current_frame = self.call_stack.pop();
+    /// The total number of instructions executed by this thread.
+    pub fn executed_instruction_count(&self) -> u64 {
+        self.executed_instruction_count
+    }
+
+    fn function(&mut self, id: bytecode::FunctionId) -> runtime::Result<Arc<bytecode::Function>> {
+        let index = usize::from(id);
+        if let Some(Some(function)) = self.functions.get(index) {
+            return Ok(function.clone());
+        }
+
+        let function = self.runtime.decode_function(id)?;
+        if self.functions.len() <= index {
+            self.functions.resize(index + 1, None);
+        }
+
+        self.functions[index] = Some(function.clone());
+        Ok(function)
+    }
+
+    /// Gets the compiled version of a function if execution is tiered and it was called enough times.
+    #[cfg(feature = "jit")]
+    fn tier_up(&mut self, id: bytecode::FunctionId, function: &bytecode::Function) -> Option<crate::jit::NativeFunction> {
+        if self.runtime.execution_mode() != runtime::ExecutionMode::Tiered {
+            return None;
+        }
+
+        let index = usize::from(id);
+        if let Some(Some(native)) = self.native_functions.get(index) {
+            return *native;
+        }
+
+        if function.increment_call_count() < self.runtime.tiering_threshold().get() {
+            return None;
+        }
+
+        let native = self.runtime.compile_function(id);
+        if self.native_functions.len() <= index {
+            self.native_functions.resize(index + 1, None);
+        }
+
+        self.native_functions[index] = Some(native);
+        native
+    }
+
+    /// Executes at most `limit` instructions, returning the return values of the entry point if it returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if execution has already ended.
+    fn interpret<const LIMITED: bool>(&mut self, limit: u64) -> runtime::Result<Option<Box<[u64]>>> {
+        let current_frame = self.call_stack.frames.last().expect("execution has already ended");
+        let mut function = current_frame.function.clone();
+        let mut pc = current_frame.instruction_index;
+        let mut base = current_frame.register_base;
+        let mut executed = 0u64;
 
-        enum ControlFlow {
-            Nothing,
-            //Branch(Arc<sailar_load::code_block::Code>),
-            Return(Box<[Value]>),
+        macro_rules! read {
+            ($operand: expr) => {
+                match $operand {
+                    Operand::Register(slot) => self.call_stack.registers[base + slot as usize],
+                    Operand::Constant(value) => value,
+                }
+            };
         }
 
-        let control_flow = match current_frame.kind_mut() {
-            call_stack::FrameKind::Defined(code) => match code.next_instruction()?.expect("missing terminator instruction") {
-                TypedInstruction::Nop => ControlFlow::Nothing,
-                TypedInstruction::Break => {
+        macro_rules! branch {
+            ($target: expr, $moves: expr) => {{
+                let (moves, parallel) = function.moves($moves);
+                if parallel {
+                    self.scratch.clear();
+                    for argument in moves.iter() {
+                        let value = read!(argument.source);
+                        self.scratch.push(value);
+                    }
+
+                    for (argument, value) in moves.iter().zip(self.scratch.iter()) {
+                        self.call_stack.registers[base + argument.destination as usize] = *value;
+                    }
+                } else {
+                    for argument in moves.iter() {
+                        self.call_stack.registers[base + argument.destination as usize] = read!(argument.source);
+                    }
+                }
+
+                pc = $target as usize;
+            }};
+        }
+
+        let result = loop {
+            if LIMITED && executed == limit {
+                break None;
+            }
+
+            executed += 1;
+            let instruction = function.code()[pc];
+            pc += 1;
+
+            match instruction {
+                Op::Nop => (),
+                Op::Break => {
                     // TODO: Add support for breakpoints
-                    ControlFlow::Nothing
                 }
-                TypedInstruction::Return(return_values) => {
-                    ControlFlow::Return(code.map_many_typed_values(return_values.iter(), self.runtime.endianness()))
+                Op::Return(values) => {
+                    let returning_frame = self.call_stack.frames.pop().unwrap();
+
+                    if self.call_stack.frames.is_empty() {
+                        break Some(function.operands(values).iter().map(|value| read!(*value)).collect());
+                    }
+
+                    for (index, value) in function.operands(values).iter().enumerate() {
+                        self.call_stack.registers[returning_frame.result_slot + index] = read!(*value);
+                    }
+
+                    let previous_frame = self.call_stack.frames.last().unwrap();
+                    function = previous_frame.function.clone();
+                    pc = previous_frame.instruction_index;
+                    base = previous_frame.register_base;
+                }
+                Op::Jump { target, moves } => branch!(target, moves),
+                Op::JumpIf {
+                    condition,
+                    true_target,
+                    true_moves,
+                    false_target,
+                    false_moves,
+                } => {
+                    if read!(condition) != 0 {
+                        branch!(true_target, true_moves)
+                    } else {
+                        branch!(false_target, false_moves)
+                    }
                 }
-            },
-        };
+                Op::Call {
+                    callee,
+                    arguments,
+                    results,
+                } => {
+                    let callee_id = callee;
+                    let callee = self.function(callee_id)?;
 
-        match control_flow {
-            ControlFlow::Nothing => self.call_stack.push(current_frame),
-            ControlFlow::Return(return_values) => {
-                // Frame was already popped, so stack doesn't need to be manipulated.
-                if !self.call_stack.is_execution_ended() {
-                    let previous_frame = self.call_stack.pop();
-                    let expected_result_types = current_frame.return_types()?;
+                    #[cfg(feature = "jit")]
+                    if let Some(native) = self.tier_up(callee_id, &callee) {
+                        self.scratch.clear();
+                        for argument in function.operands(arguments).iter() {
+                            let value = read!(*argument);
+                            self.scratch.push(value);
+                        }
 
-                    assert_eq!(return_values.len(), expected_result_types.len());
+                        self.native_results.resize(callee.return_kinds().len(), 0);
+                        let remaining_frames = self.call_stack.remaining_frames();
+                        if native.call(
+                            remaining_frames.min(crate::jit::MAX_NATIVE_CALL_DEPTH),
+                            &self.scratch,
+                            &mut self.native_results,
+                        ) {
+                            let result_slot = base + results as usize;
+                            self.call_stack.registers[result_slot..result_slot + self.native_results.len()]
+                                .copy_from_slice(&self.native_results);
+                            continue;
+                        } else if remaining_frames <= crate::jit::MAX_NATIVE_CALL_DEPTH {
+                            return Err(error::RuntimeError::StackOverflow(self.runtime.call_stack_size().get()));
+                        }
 
-                    // TODO: Add check to ensure value has correct type when returning from a foreign function
-                    // .zip(expected_result_types)
-                    for value in return_values.iter() {
-                        // TODO: Call helper that defines temporary registers to store return_values in previous_frame
+                        // The native call stack was not large enough, so the callee is interpreted instead.
                     }
 
-                    todo!("handle normal returns");
-                    self.call_stack.push(previous_frame);
-                } else {
-                    return Ok(Some(return_values));
+                    self.call_stack.frames.last_mut().unwrap().instruction_index = pc;
+
+                    let callee_base = self
+                        .call_stack
+                        .push_new(callee.clone(), base + results as usize)?
+                        .register_base;
+                    for (argument, slot) in function.operands(arguments).iter().zip(callee.argument_slots()) {
+                        self.call_stack.registers[callee_base + *slot as usize] = read!(*argument);
+                    }
+
+                    function = callee;
+                    pc = 0;
+                    base = callee_base;
+                }
+                Op::Arithmetic {
+                    operation,
+                    kind,
+                    x,
+                    y,
+                    result,
+                } => {
+                    let (x, y) = (read!(x), read!(y));
+                    self.call_stack.registers[base + result as usize] = kind.wrap(match operation {
+                        bytecode::ArithmeticOperation::Add => x.wrapping_add(y),
+                        bytecode::ArithmeticOperation::Sub => x.wrapping_sub(y),
+                    });
+                }
+                Op::CheckedArithmetic {
+                    operation,
+                    overflow_behavior,
+                    kind,
+                    x,
+                    y,
+                    result,
+                } => {
+                    let (value, overflowed) = Op::checked_arithmetic(operation, overflow_behavior, kind, read!(x), read!(y));
+                    let result = base + result as usize;
+                    self.call_stack.registers[result] = value;
+                    if overflow_behavior == sailar::instruction::OverflowBehavior::Flag {
+                        self.call_stack.registers[result + 1] = u64::from(overflowed);
+                    }
+                }
+                Op::Compare {
+                    kind,
+                    signed,
+                    x,
+                    y,
+                    result,
+                    true_value,
+                } => {
+                    self.call_stack.registers[base + result as usize] = if kind.apply(signed, read!(x), read!(y)) {
+                        true_value
+                    } else {
+                        0
+                    };
+                }
+                Op::ThreadSpawn {
+                    callee,
+                    arguments,
+                    result,
+                    handle_kind,
+                } => {
+                    let callee = self.function(callee)?;
+                    let arguments = function.operands(arguments).iter().map(|argument| read!(*argument)).collect();
+                    let thread = self.runtime.spawn_raw(callee, arguments, handle_kind)?;
+                    self.call_stack.registers[base + result as usize] = handle_kind.wrap(thread.get());
+                }
+                Op::ThreadJoin {
+                    thread,
+                    results,
+                    result_kinds,
+                } => {
+                    let thread = crate::thread::ThreadId::new(
+                        std::num::NonZeroU64::new(read!(thread)).ok_or(error::RuntimeError::UnknownThread(0))?,
+                    );
+
+                    let (_, return_values) = self.runtime.join_raw(thread, Some(function.result_kinds(result_kinds)))?;
+                    let results = base + results as usize;
+                    self.call_stack.registers[results..results + return_values.len()].copy_from_slice(&return_values);
+                }
+                Op::AtomicLoad {
+                    ordering,
+                    kind,
+                    address,
+                    result,
+                } => {
+                    let address = read!(address);
+                    self.runtime.check_atomic_address(kind, address)?;
+                    // Safety: The address was checked to be in a memory region provided by the embedder.
+                    let value = unsafe { atomic::load(kind, address, ordering) };
+                    self.call_stack.registers[base + result as usize] = kind.wrap(value);
+                }
+                Op::AtomicStore {
+                    ordering,
+                    kind,
+                    address,
+                    value,
+                } => {
+                    let address = read!(address);
+                    self.runtime.check_atomic_address(kind, address)?;
+                    // Safety: The address was checked to be in a memory region provided by the embedder.
+                    unsafe { atomic::store(kind, address, read!(value), ordering) }
+                }
+                Op::AtomicReadModifyWrite {
+                    operation,
+                    ordering,
+                    kind,
+                    address,
+                    value,
+                    result,
+                } => {
+                    let address = read!(address);
+                    self.runtime.check_atomic_address(kind, address)?;
+                    // Safety: The address was checked to be in a memory region provided by the embedder.
+                    let previous = unsafe { atomic::read_modify_write(operation, kind, address, read!(value), ordering) };
+                    self.call_stack.registers[base + result as usize] = kind.wrap(previous);
+                }
+                Op::AtomicCompareExchange {
+                    success_ordering,
+                    failure_ordering,
+                    kind,
+                    operands,
+                    result,
+                    true_value,
+                } => {
+                    let (address, expected, replacement) = match function.operands(operands) {
+                        [address, expected, replacement] => (read!(*address), read!(*expected), read!(*replacement)),
+                        _ => unreachable!(),
+                    };
+
+                    self.runtime.check_atomic_address(kind, address)?;
+                    // Safety: The address was checked to be in a memory region provided by the embedder.
+                    let (previous, replaced) = unsafe {
+                        atomic::compare_exchange(kind, address, expected, replacement, success_ordering, failure_ordering)
+                    };
+
+                    let result = base + result as usize;
+                    self.call_stack.registers[result] = kind.wrap(previous);
+                    self.call_stack.registers[result + 1] = if replaced { true_value } else { 0 };
+                }
+                Op::Fence(ordering) => atomic::fence(ordering),
+                Op::VectorArithmetic {
+                    operation,
+                    overflow_behavior,
+                    kind,
+                    lanes,
+                    x,
+                    y,
+                    result,
+                } => {
+                    let registers = &mut self.call_stack.registers;
+                    for lane in 0..usize::from(lanes) {
+                        let x = registers[base + x as usize + lane];
+                        let y = registers[base + y as usize + lane];
+                        registers[base + result as usize + lane] = match overflow_behavior {
+                            sailar::instruction::OverflowBehavior::Ignore => kind.wrap(match operation {
+                                bytecode::ArithmeticOperation::Add => x.wrapping_add(y),
+                                bytecode::ArithmeticOperation::Sub => x.wrapping_sub(y),
+                            }),
+                            _ => Op::checked_arithmetic(operation, overflow_behavior, kind, x, y).0,
+                        };
+                    }
+                }
+                Op::VectorSplat { value, lanes, result } => {
+                    let value = read!(value);
+                    let result = base + result as usize;
+                    self.call_stack.registers[result..result + usize::from(lanes)].fill(value);
+                }
+                Op::VectorExtract { lane, result } => {
+                    self.call_stack.registers[base + result as usize] = self.call_stack.registers[base + lane as usize];
+                }
+                Op::VectorInsert {
+                    vector,
+                    value,
+                    lane,
+                    lanes,
+                    result,
+                } => {
+                    let value = read!(value);
+                    let (vector, result) = (base + vector as usize, base + result as usize);
+                    self.call_stack
+                        .registers
+                        .copy_within(vector..vector + usize::from(lanes), result);
+                    self.call_stack.registers[result + usize::from(lane)] = value;
+                }
+                Op::VectorShuffle {
+                    x,
+                    y,
+                    lanes,
+                    indices,
+                    result,
+                } => {
+                    let registers = &mut self.call_stack.registers;
+                    for (lane, index) in function.lane_indices(indices).iter().copied().enumerate() {
+                        let source = if index < u16::from(lanes) {
+                            x as usize + usize::from(index)
+                        } else {
+                            y as usize + usize::from(index - u16::from(lanes))
+                        };
+
+                        registers[base + result as usize + lane] = registers[base + source];
+                    }
                 }
             }
+        };
+
+        self.executed_instruction_count += executed;
+        if let Some(current_frame) = self.call_stack.frames.last_mut() {
+            current_frame.instruction_index = pc;
         }
 
-        Ok(None)
+        Ok(result)
+    }
+
+    fn convert_return_values(&self, return_values: &[u64]) -> Box<[Value]> {
+        self.entry_point
+            .convert_return_values(return_values.iter().copied(), self.runtime.endianness())
+    }
+
+    /// Executes a single instruction, returning the return values of the entry point if it returned.
+    pub fn step(&mut self) -> Result<Option<Box<[Value]>>, error::RuntimeError> {
+        Ok(self
+            .interpret::<true>(1)?
+            .map(|return_values| self.convert_return_values(&return_values)))
+    }
+
+    /// Executes code until the entry point returns, with return values in their register file representation.
+    pub(crate) fn run_raw(&mut self) -> Result<Box<[u64]>, error::RuntimeError> {
+        loop {
+            if let Some(return_values) = self.interpret::<false>(0)? {
+                return Ok(return_values);
+            }
+        }
+    }
+
+    /// Executes code until the entry point returns.
+    ///
+    /// # Panics
+    ///
+    /// Panics if execution has already ended.
+    pub fn run(&mut self) -> Result<Box<[Value]>, error::RuntimeError> {
+        let return_values = self.run_raw()?;
+        Ok(self.convert_return_values(&return_values))
     }
 }
 
@@ -89,3 +494,307 @@ impl Debug for State {
         f.debug_struct("State").field("call_stack", &self.call_stack).finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime;
+    use crate::value::{Endianness, Value};
+
+    fn load_entry_point(loader: &runtime::Loader, program: sailar::builder::Builder<'static>) -> runtime::Function {
+        let module = loader
+            .load_module(sailar::validation::ValidModule::from_builder(program).unwrap())
+            .unwrap();
+
+        module.entry_point().unwrap().clone()
+    }
+
+    fn run_program(program: sailar::builder::Builder<'static>) -> Box<[Value]> {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        runtime::Configuration::new()
+            .initialize_runtime()
+            .execute(load_entry_point(&loader, program), Box::default())
+            .unwrap()
+    }
+
+    fn single_u32(values: &[Value]) -> u32 {
+        assert_eq!(values.len(), 1);
+        values[0].clone().into_u32(Endianness::Little)
+    }
+
+    #[test]
+    fn exit_with_returns_exit_code() {
+        let values = run_program(sailar_samples::exit_with("Test".try_into().unwrap(), 42));
+        assert_eq!(single_u32(&values), 42);
+    }
+
+    #[test]
+    fn fibonacci_is_correct() {
+        let values = run_program(sailar_samples::fibonacci("Test".try_into().unwrap(), 20));
+        assert_eq!(single_u32(&values), 6765);
+    }
+
+    #[test]
+    fn sum_loop_is_correct() {
+        let values = run_program(sailar_samples::sum_loop("Test".try_into().unwrap(), 1000));
+        assert_eq!(single_u32(&values), 499500);
+    }
+
+    #[test]
+    fn vector_sum_is_correct() {
+        let values = run_program(sailar_samples::vector_sum("Test".try_into().unwrap(), 5));
+        assert_eq!(single_u32(&values), 17);
+    }
+
+    #[test]
+    fn vectors_are_passed_to_functions_and_threads() {
+        let values = run_program(sailar_samples::vector_call("Test".try_into().unwrap(), 5));
+        assert_eq!(single_u32(&values), 30);
+
+        // Each lane is passed by the host as a separate value.
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let module = loader
+            .load_module(
+                sailar::validation::ValidModule::from_builder(sailar_samples::vector_call("Test".try_into().unwrap(), 0))
+                    .unwrap(),
+            )
+            .unwrap();
+
+        let runtime = runtime::Configuration::new().initialize_runtime();
+        let lanes = [1u64, 2, 3, 4].map(|lane| Value::from_bits(lane, 4, runtime.endianness()));
+        let doubled = runtime.execute(module.functions()[1].clone(), lanes).unwrap();
+        let doubled = doubled
+            .iter()
+            .map(|lane| lane.clone().into_u32(runtime.endianness()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(doubled, vec![2, 4, 6, 8]);
+    }
+
+    #[test]
+    fn parallel_fibonacci_is_correct() {
+        let values = run_program(sailar_samples::parallel_fibonacci("Test".try_into().unwrap(), 18));
+        assert_eq!(single_u32(&values), 6765);
+    }
+
+    #[test]
+    fn threads_are_spawned_and_joined_by_host() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = load_entry_point(&loader, sailar_samples::fibonacci("Test".try_into().unwrap(), 15));
+        let runtime = runtime::Configuration::new().initialize_runtime();
+
+        let first = runtime.spawn(entry_point.clone(), Box::default()).unwrap();
+        let second = runtime.spawn(entry_point, Box::default()).unwrap();
+        assert_eq!(runtime.threads(), vec![first, second]);
+
+        assert_eq!(single_u32(&runtime.join(second).unwrap()), 610);
+        assert_eq!(single_u32(&runtime.join(first).unwrap()), 610);
+        assert!(runtime.threads().is_empty());
+        assert!(matches!(
+            runtime.join(first),
+            Err(crate::error::RuntimeError::UnknownThread(id)) if id == first.get()
+        ));
+    }
+
+    #[test]
+    fn states_run_in_parallel() {
+        fn assert_send<T: Send>() {}
+        assert_send::<super::State>();
+
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let fibonacci = load_entry_point(&loader, sailar_samples::fibonacci("Fibonacci".try_into().unwrap(), 20));
+        let sum_loop = load_entry_point(&loader, sailar_samples::sum_loop("Loop".try_into().unwrap(), 1000));
+        let runtime = runtime::Configuration::new().initialize_runtime();
+
+        let mut states = [
+            runtime.create_state(fibonacci, Box::default()).unwrap(),
+            runtime.create_state(sum_loop, Box::default()).unwrap(),
+        ];
+
+        let results = std::thread::scope(|scope| {
+            let handles = states
+                .iter_mut()
+                .map(|state| scope.spawn(move || state.run().unwrap()))
+                .collect::<Vec<_>>();
+
+            handles
+                .into_iter()
+                .map(|handle| single_u32(&handle.join().unwrap()))
+                .collect::<Vec<_>>()
+        });
+
+        assert_eq!(results, vec![6765, 499500]);
+    }
+
+    #[test]
+    fn optimized_programs_give_identical_results() {
+        let programs: [fn() -> sailar::builder::Builder<'static>; 5] = [
+            || sailar_samples::fibonacci("Test".try_into().unwrap(), 15),
+            || sailar_samples::parallel_fibonacci("Test".try_into().unwrap(), 12),
+            || sailar_samples::sum_loop("Test".try_into().unwrap(), 100),
+            || sailar_samples::vector_sum("Test".try_into().unwrap(), 5),
+            || sailar_samples::vector_call("Test".try_into().unwrap(), 5),
+        ];
+
+        for program in programs {
+            let module = sailar::validation::ValidModule::from_builder(program()).unwrap();
+            let optimized = sailar::optimizer::PassManager::with_default_passes().run(module).unwrap();
+            let loader = sailar_load::state::Configuration::new().create_state();
+            let entry_point = loader.load_module(optimized).unwrap().entry_point().unwrap().clone();
+            let optimized_values = runtime::Configuration::new()
+                .initialize_runtime()
+                .execute(entry_point, Box::default())
+                .unwrap();
+
+            assert_eq!(single_u32(&optimized_values), single_u32(&run_program(program())));
+        }
+    }
+
+    #[test]
+    fn atomic_counter_modifies_host_memory() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = load_entry_point(&loader, sailar_samples::atomic_counter("Test".try_into().unwrap(), 10000));
+
+        for mode in [
+            runtime::ExecutionMode::Interpreter,
+            runtime::ExecutionMode::Jit,
+            runtime::ExecutionMode::Tiered,
+        ] {
+            let counter = std::sync::atomic::AtomicU32::new(42);
+            let runtime = runtime::Configuration::new().execution_mode(mode).initialize_runtime();
+            // Safety: The counter outlives the runtime, and is only accessed atomically.
+            unsafe { runtime.add_memory_region(counter.as_ptr().cast(), std::mem::size_of_val(&counter)) };
+            let address = Value::from_bits(counter.as_ptr() as u64, std::mem::size_of::<usize>(), runtime.endianness());
+
+            let values = runtime.execute(entry_point.clone(), vec![address]).unwrap();
+            assert_eq!(values.len(), 2);
+            assert_eq!(
+                values[0].clone().into_u32(runtime.endianness()),
+                20000,
+                "incorrect count for {mode:?}"
+            );
+            assert_eq!(
+                values[1].clone().into_u32(runtime.endianness()),
+                1,
+                "counter was not reset for {mode:?}"
+            );
+            assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 0);
+        }
+    }
+
+    #[test]
+    fn wrong_argument_count_is_error() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = load_entry_point(&loader, sailar_samples::exit_with("Test".try_into().unwrap(), 0));
+        let runtime = runtime::Configuration::new().initialize_runtime();
+        let arguments = vec![Value::from_bits(0, 4, runtime.endianness())];
+
+        assert!(matches!(
+            runtime.execute(entry_point.clone(), arguments.clone()),
+            Err(crate::error::RuntimeError::ArgumentCountMismatch { expected: 0, actual: 1 })
+        ));
+        assert!(matches!(
+            runtime.spawn(entry_point.clone(), arguments.clone()),
+            Err(crate::error::RuntimeError::ArgumentCountMismatch { .. })
+        ));
+        assert!(matches!(
+            runtime.create_state(entry_point, arguments),
+            Err(crate::error::RuntimeError::ArgumentCountMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn thread_joined_with_wrong_result_types_is_error() {
+        use sailar::index;
+        use sailar::instruction::Instruction;
+        use sailar::record;
+
+        let unsigned_type = index::TypeSignature::from(0);
+        let signed_type = index::TypeSignature::from(1);
+        let mut builder = sailar::builder::Builder::new();
+        builder.add_record(sailar::signature::Type::from(sailar::signature::IntegerType::U32));
+        builder.add_record(sailar::signature::Type::from(sailar::signature::IntegerType::S32));
+        builder.add_record(sailar::signature::Function::new([].as_slice(), vec![unsigned_type]));
+        builder.add_record(sailar::signature::Function::new([].as_slice(), vec![signed_type]));
+
+        // main: %t0 = thread.spawn worker (); %t1 = thread.join %t0 as () -> s32; ret %t1
+        builder.add_record(record::CodeBlock::new(
+            [].as_slice(),
+            vec![signed_type],
+            vec![unsigned_type, signed_type],
+            vec![
+                Instruction::ThreadSpawn(index::Function::from(1), Box::default()),
+                Instruction::ThreadJoin(index::FunctionSignature::from(1), index::Register::from(0).into()),
+                Instruction::Return(vec![index::Register::from(1).into()].into_boxed_slice()),
+            ],
+        ));
+
+        // worker: ret 7
+        builder.add_record(record::CodeBlock::new(
+            [].as_slice(),
+            vec![unsigned_type],
+            [].as_slice(),
+            vec![Instruction::Return(vec![7u32.into()].into_boxed_slice())],
+        ));
+
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::ExportBorrowed(sailar::identifier::Id::try_from_str("main").unwrap()),
+            index::FunctionSignature::from(1),
+            index::CodeBlock::from(0),
+        ));
+        builder.add_record(record::FunctionTemplate::new(
+            record::Export::PrivateBorrowed(sailar::identifier::Id::try_from_str("worker").unwrap()),
+            index::FunctionSignature::from(0),
+            index::CodeBlock::from(1),
+        ));
+        builder.add_record(record::Function::with_template(index::FunctionTemplate::from(0)));
+        builder.add_record(record::Function::with_template(index::FunctionTemplate::from(1)));
+        builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
+
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = load_entry_point(&loader, builder);
+        let runtime = runtime::Configuration::new().initialize_runtime();
+
+        assert!(matches!(
+            runtime.execute(entry_point, Box::default()),
+            Err(crate::error::RuntimeError::ThreadResultTypeMismatch(1))
+        ));
+
+        // The thread can still be joined with the correct types.
+        let thread = runtime.threads()[0];
+        assert_eq!(single_u32(&runtime.join(thread).unwrap()), 7);
+    }
+
+    #[test]
+    fn atomic_operation_on_null_pointer_fails() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = load_entry_point(&loader, sailar_samples::atomic_counter("Test".try_into().unwrap(), 1));
+        let runtime = runtime::Configuration::new().initialize_runtime();
+        let address = Value::from_bits(0, std::mem::size_of::<usize>(), runtime.endianness());
+
+        assert!(matches!(
+            runtime.execute(entry_point, vec![address]),
+            Err(crate::error::RuntimeError::InvalidAtomicAddress(0))
+        ));
+    }
+
+    #[test]
+    fn atomic_operation_outside_of_memory_regions_fails() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let entry_point = load_entry_point(&loader, sailar_samples::atomic_counter("Test".try_into().unwrap(), 1));
+        let counter = std::sync::atomic::AtomicU32::new(0);
+        let runtime = runtime::Configuration::new().initialize_runtime();
+        let pointer = |address: usize| Value::from_bits(address as u64, std::mem::size_of::<usize>(), runtime.endianness());
+
+        // Safety: The region is smaller than the counter, which outlives the runtime and is only accessed atomically.
+        unsafe { runtime.add_memory_region(counter.as_ptr().cast(), 2) };
+
+        for address in [0x1000, counter.as_ptr() as usize] {
+            assert!(matches!(
+                runtime.execute(entry_point.clone(), vec![pointer(address)]),
+                Err(crate::error::RuntimeError::InvalidAtomicAddress(invalid)) if invalid == address as u64
+            ));
+        }
+
+        assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,762 @@
+//! Just-in-time compilation of SAILAR code to machine code with Cranelift.
+//!
+//! Compiled functions operate on the same representation of values as the interpreter, and use the following signature:
+//!
+//! ```text
+//! extern "C" fn(context: *mut Context, arguments: *const u64, results: *mut u64) -> u32
+//! ```
+//!
+//! A non-zero return value indicates that the call depth limit in the [`Context`] was exceeded, in which case the interpreter
+//! executes the function again from the start. This is only safe because compiled code has no side effects: SAILAR code
+//! can modify the memory regions added with [`Runtime::add_memory_region`] using atomic instructions and fences, and can
+//! spawn and join threads, but functions containing those instructions are never compiled. Calls are compiled along with
+//! every function they can reach, so a function that calls one with side effects, directly or indirectly, is not
+//! compiled either.
+//!
+//! [`Runtime::add_memory_region`]: crate::runtime::Runtime::add_memory_region
+
+use crate::bytecode::{self, ArithmeticOperation, Comparison, FunctionId, IntegerKind, Op, Operand};
+use cranelift_codegen::ir::condcodes::IntCC;
+use cranelift_codegen::ir::{self, types, AbiParam, InstBuilder, MemFlags};
+use cranelift_codegen::settings::{self, Configurable};
+use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext, Variable};
+use cranelift_module::{Linkage, Module};
+use std::collections::hash_map;
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// The maximum number of nested calls to compiled code, used to avoid overflowing the native stack.
+pub const MAX_NATIVE_CALL_DEPTH: usize = 4096;
+
+const CALL_DEPTH_EXCEEDED: u32 = 1;
+
+/// State shared by all compiled functions called in a thread.
+#[derive(Debug)]
+#[repr(C)]
+pub struct Context {
+    remaining_call_depth: u64,
+}
+
+/// A pointer to a compiled function.
+#[derive(Copy, Clone, Debug)]
+pub struct NativeFunction(unsafe extern "C" fn(*mut Context, *const u64, *mut u64) -> u32);
+
+impl NativeFunction {
+    /// Calls the compiled function, returning `false` if the call depth limit was exceeded.
+    pub fn call(self, call_depth: usize, arguments: &[u64], results: &mut [u64]) -> bool {
+        let mut context = Context {
+            remaining_call_depth: call_depth as u64,
+        };
+
+        // Safety: Compiled code only reads and writes the number of arguments and results specified in its signature, which
+        // the caller ensures match the lengths of the slices.
+        unsafe { (self.0)(&mut context, arguments.as_ptr(), results.as_mut_ptr()) == 0 }
+    }
+}
+
+/// Error used when a function contains code that cannot be compiled, meaning it must be interpreted instead.
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{0}")]
+pub struct UnsupportedError(String);
+
+const CHECKED_ARITHMETIC_SYMBOL: &str = "sailar_vm_checked_arithmetic";
+
+fn checked_arithmetic_descriptor(
+    operation: ArithmeticOperation,
+    overflow_behavior: sailar::instruction::OverflowBehavior,
+    kind: IntegerKind,
+) -> u32 {
+    u32::from(kind.bit_size())
+        | u32::from(kind.is_signed()) << 8
+        | u32::from(operation == ArithmeticOperation::Sub) << 9
+        | u32::from(overflow_behavior == sailar::instruction::OverflowBehavior::Saturate) << 10
+}
+
+extern "C" fn checked_arithmetic(descriptor: u32, x: u64, y: u64, overflowed: *mut u64) -> u64 {
+    let kind = IntegerKind::new(descriptor as u8, descriptor & (1 << 8) != 0);
+    let operation = if descriptor & (1 << 9) != 0 {
+        ArithmeticOperation::Sub
+    } else {
+        ArithmeticOperation::Add
+    };
+    let overflow_behavior = if descriptor & (1 << 10) != 0 {
+        sailar::instruction::OverflowBehavior::Saturate
+    } else {
+        sailar::instruction::OverflowBehavior::Flag
+    };
+
+    let (result, flag) = Op::checked_arithmetic(operation, overflow_behavior, kind, x, y);
+
+    // Safety: Compiled code always provides a pointer to a stack slot.
+    unsafe {
+        *overflowed = u64::from(flag);
+    }
+
+    result
+}
+
+enum CompilationState {
+    Compiled(NativeFunction),
+    Unsupported(UnsupportedError),
+}
+
+/// Compiles functions to machine code, keeping the compiled code alive for as long as the runtime exists.
+pub(crate) struct Compiler {
+    module: cranelift_jit::JITModule,
+    context: cranelift_codegen::Context,
+    builder_context: FunctionBuilderContext,
+    /// Maps functions to their declarations and the number of values they return.
+    declarations: hash_map::HashMap<FunctionId, (cranelift_module::FuncId, usize)>,
+    compiled: hash_map::HashMap<FunctionId, CompilationState>,
+    checked_arithmetic: cranelift_module::FuncId,
+}
+
+// Safety: The compiler is only ever accessed through a mutex, and compiled code does not reference any thread-local state.
+unsafe impl Send for Compiler {}
+
+impl Compiler {
+    pub(crate) fn new() -> Result<Self, UnsupportedError> {
+        let mut flags = settings::builder();
+        let mut set_flag = |name, value| {
+            flags
+                .set(name, value)
+                .map_err(|e| UnsupportedError(format!("could not set {name}: {e}")))
+        };
+
+        set_flag("use_colocated_libcalls", "false")?;
+        set_flag("is_pic", "false")?;
+        set_flag("opt_level", "speed")?;
+
+        let isa = cranelift_native::builder()
+            .map_err(|e| UnsupportedError(format!("host machine is not supported: {e}")))?
+            .finish(settings::Flags::new(flags))
+            .map_err(|e| UnsupportedError(e.to_string()))?;
+
+        let mut builder = cranelift_jit::JITBuilder::with_isa(isa, cranelift_module::default_libcall_names());
+        builder.symbol(CHECKED_ARITHMETIC_SYMBOL, checked_arithmetic as *const u8);
+        let mut module = cranelift_jit::JITModule::new(builder);
+
+        let pointer_type = module.target_config().pointer_type();
+        let mut checked_arithmetic_signature = module.make_signature();
+        checked_arithmetic_signature.params.extend([
+            AbiParam::new(types::I32),
+            AbiParam::new(types::I64),
+            AbiParam::new(types::I64),
+            AbiParam::new(pointer_type),
+        ]);
+        checked_arithmetic_signature.returns.push(AbiParam::new(types::I64));
+
+        let checked_arithmetic = module
+            .declare_function(CHECKED_ARITHMETIC_SYMBOL, Linkage::Import, &checked_arithmetic_signature)
+            .map_err(|e| UnsupportedError(e.to_string()))?;
+
+        Ok(Self {
+            context: module.make_context(),
+            builder_context: FunctionBuilderContext::new(),
+            declarations: Default::default(),
+            compiled: Default::default(),
+            checked_arithmetic,
+            module,
+        })
+    }
+
+    fn native_signature(&self) -> ir::Signature {
+        let pointer_type = self.module.target_config().pointer_type();
+        let mut signature = self.module.make_signature();
+        signature.params.extend([AbiParam::new(pointer_type); 3]);
+        signature.returns.push(AbiParam::new(types::I32));
+        signature
+    }
+
+    /// Compiles the specified function and all functions that it calls.
+    ///
+    /// The `decode` closure is used to retrieve the bytecode of called functions.
+    pub(crate) fn compile<D>(&mut self, id: FunctionId, mut decode: D) -> Result<NativeFunction, UnsupportedError>
+    where
+        D: FnMut(FunctionId) -> crate::runtime::Result<Arc<bytecode::Function>>,
+    {
+        match self.compiled.get(&id) {
+            Some(CompilationState::Compiled(native)) => return Ok(*native),
+            Some(CompilationState::Unsupported(error)) => return Err(error.clone()),
+            None => (),
+        }
+
+        // Find all functions that have not been compiled yet. Functions are only declared once it is known that none of their
+        // callees failed to compile, since declared functions must be defined before code calling them can be finalized.
+        let mut pending = vec![id];
+        let mut discovered = std::collections::HashSet::new();
+        let mut functions = Vec::new();
+        while let Some(next) = pending.pop() {
+            match self.compiled.get(&next) {
+                Some(CompilationState::Compiled(_)) => continue,
+                Some(CompilationState::Unsupported(error)) => {
+                    let error = UnsupportedError(format!(
+                        "calls function {} which cannot be compiled: {error}",
+                        usize::from(next)
+                    ));
+                    self.compiled.insert(id, CompilationState::Unsupported(error.clone()));
+                    return Err(error);
+                }
+                None => (),
+            }
+
+            if !discovered.insert(next) {
+                continue;
+            }
+
+            let function = decode(next).map_err(|e| UnsupportedError(e.to_string()))?;
+            for op in function.code().iter() {
+                if let Op::Call { callee, .. } = op {
+                    pending.push(*callee);
+                }
+            }
+
+            functions.push((next, function));
+        }
+
+        let mut declared = Vec::with_capacity(functions.len());
+        for (id, function) in functions.into_iter() {
+            let signature = self.native_signature();
+            let declaration =
+                match self
+                    .module
+                    .declare_function(&format!("sailar_function_{}", usize::from(id)), Linkage::Local, &signature)
+                {
+                    Ok(declaration) => declaration,
+                    Err(error) => {
+                        let error = UnsupportedError(error.to_string());
+                        for (declared_id, _, _) in declared.iter() {
+                            self.compiled
+                                .insert(*declared_id, CompilationState::Unsupported(error.clone()));
+                        }
+                        return Err(error);
+                    }
+                };
+
+            self.declarations.insert(id, (declaration, function.return_kinds().len()));
+            declared.push((id, declaration, function));
+        }
+
+        let functions = declared;
+
+        let mut result = Ok(());
+        for (_, declaration, function) in functions.iter() {
+            result = self.define(*declaration, function);
+            self.module.clear_context(&mut self.context);
+            if result.is_err() {
+                break;
+            }
+        }
+
+        if let Err(error) = result {
+            // Translation may have stopped before the function builder was finalized.
+            self.builder_context = FunctionBuilderContext::new();
+
+            // Functions that were already declared cannot be defined later, so all of them are marked as unsupported.
+            for (id, _, _) in functions.iter() {
+                self.compiled.insert(*id, CompilationState::Unsupported(error.clone()));
+            }
+
+            return Err(error);
+        }
+
+        self.module
+            .finalize_definitions()
+            .map_err(|e| UnsupportedError(e.to_string()))?;
+
+        for (id, declaration, _) in functions.iter() {
+            let code = self.module.get_finalized_function(*declaration);
+
+            // Safety: The function was compiled with the signature expected by NativeFunction.
+            let native = NativeFunction(unsafe {
+                std::mem::transmute::<*const u8, unsafe extern "C" fn(*mut Context, *const u64, *mut u64) -> u32>(code)
+            });
+            self.compiled.insert(*id, CompilationState::Compiled(native));
+        }
+
+        match &self.compiled[&id] {
+            CompilationState::Compiled(native) => Ok(*native),
+            CompilationState::Unsupported(error) => Err(error.clone()),
+        }
+    }
+
+    fn define(&mut self, declaration: cranelift_module::FuncId, function: &bytecode::Function) -> Result<(), UnsupportedError> {
+        let pointer_type = self.module.target_config().pointer_type();
+        self.context.func.signature = self.native_signature();
+
+        let mut builder = FunctionBuilder::new(&mut self.context.func, &mut self.builder_context);
+        let code = function.code();
+
+        for slot in 0..function.register_count() {
+            builder.declare_var(Variable::from_u32(slot as u32), types::I64);
+        }
+
+        let mut blocks = hash_map::HashMap::new();
+        let mut get_block =
+            |builder: &mut FunctionBuilder, target: u32| *blocks.entry(target).or_insert_with(|| builder.create_block());
+
+        let entry_block = builder.create_block();
+        builder.append_block_params_for_function_params(entry_block);
+        builder.switch_to_block(entry_block);
+
+        let parameters = builder.block_params(entry_block).to_vec();
+        let (context, arguments, results) = (parameters[0], parameters[1], parameters[2]);
+        let flags = MemFlags::trusted();
+
+        let exit_with_status = |builder: &mut FunctionBuilder, status: u32| {
+            let status = builder.ins().iconst(types::I32, i64::from(status));
+            builder.ins().return_(&[status]);
+        };
+
+        // Check the call depth.
+        let remaining_call_depth = builder.ins().load(types::I64, flags, context, 0);
+        let call_depth_exceeded = builder.create_block();
+        let body_block = builder.create_block();
+        builder
+            .ins()
+            .brif(remaining_call_depth, body_block, &[], call_depth_exceeded, &[]);
+        builder.switch_to_block(call_depth_exceeded);
+        exit_with_status(&mut builder, CALL_DEPTH_EXCEEDED);
+        builder.switch_to_block(body_block);
+        let decremented_call_depth = builder.ins().iadd_imm(remaining_call_depth, -1);
+        builder.ins().store(flags, decremented_call_depth, context, 0);
+
+        for (index, slot) in function.argument_slots().iter().enumerate() {
+            let argument = builder.ins().load(types::I64, flags, arguments, (index * 8) as i32);
+            builder.def_var(Variable::from_u32(*slot), argument);
+        }
+
+        let first_block = get_block(&mut builder, 0);
+        builder.ins().jump(first_block, &[]);
+
+        let operand = |builder: &mut FunctionBuilder, operand: Operand| match operand {
+            Operand::Register(slot) => builder.use_var(Variable::from_u32(slot)),
+            Operand::Constant(value) => builder.ins().iconst(types::I64, value as i64),
+        };
+
+        let wrap = |builder: &mut FunctionBuilder, value: ir::Value, kind: IntegerKind| {
+            let shift = 64 - i64::from(kind.bit_size());
+            if shift == 0 {
+                value
+            } else if kind.is_signed() {
+                let shifted = builder.ins().ishl_imm(value, shift);
+                builder.ins().sshr_imm(shifted, shift)
+            } else {
+                builder.ins().band_imm(value, (u64::MAX >> shift) as i64)
+            }
+        };
+
+        let branch = |builder: &mut FunctionBuilder, moves: bytecode::Moves| {
+            let (moves, _) = function.moves(moves);
+            let values = moves
+                .iter()
+                .map(|argument| operand(builder, argument.source))
+                .collect::<Vec<_>>();
+            for (argument, value) in moves.iter().zip(values) {
+                builder.def_var(Variable::from_u32(argument.destination), value);
+            }
+        };
+
+        let mut terminated = true;
+        for (offset, op) in code.iter().enumerate() {
+            if terminated {
+                let block = get_block(&mut builder, offset as u32);
+                builder.switch_to_block(block);
+                terminated = false;
+            }
+
+            match *op {
+                Op::ThreadSpawn { .. } | Op::ThreadJoin { .. } => {
+                    return Err(UnsupportedError(
+                        "threads must be spawned and joined by the interpreter".to_string(),
+                    ))
+                }
+                // Functions with side effects are not compiled, see the module documentation.
+                Op::AtomicLoad { .. }
+                | Op::AtomicStore { .. }
+                | Op::AtomicReadModifyWrite { .. }
+                | Op::AtomicCompareExchange { .. }
+                | Op::Fence(_) => {
+                    return Err(UnsupportedError(
+                        "atomic operations must be performed by the interpreter".to_string(),
+                    ))
+                }
+                Op::VectorArithmetic { .. }
+                | Op::VectorSplat { .. }
+                | Op::VectorExtract { .. }
+                | Op::VectorInsert { .. }
+                | Op::VectorShuffle { .. } => {
+                    return Err(UnsupportedError(
+                        "vector operations must be performed by the interpreter".to_string(),
+                    ))
+                }
+                Op::Nop | Op::Break => (),
+                Op::Return(values) => {
+                    for (index, value) in function.operands(values).iter().enumerate() {
+                        let value = operand(&mut builder, *value);
+                        builder.ins().store(flags, value, results, (index * 8) as i32);
+                    }
+
+                    builder.ins().store(flags, remaining_call_depth, context, 0);
+                    exit_with_status(&mut builder, 0);
+                    terminated = true;
+                }
+                Op::Jump { target, moves } => {
+                    branch(&mut builder, moves);
+                    let target = get_block(&mut builder, target);
+                    builder.ins().jump(target, &[]);
+                    terminated = true;
+                }
+                Op::JumpIf {
+                    condition,
+                    true_target,
+                    true_moves,
+                    false_target,
+                    false_moves,
+                } => {
+                    let condition = operand(&mut builder, condition);
+                    let true_block = builder.create_block();
+                    let false_block = builder.create_block();
+                    builder.ins().brif(condition, true_block, &[], false_block, &[]);
+
+                    for (block, target, moves) in [
+                        (true_block, true_target, true_moves),
+                        (false_block, false_target, false_moves),
+                    ] {
+                        builder.switch_to_block(block);
+                        branch(&mut builder, moves);
+                        let target = get_block(&mut builder, target);
+                        builder.ins().jump(target, &[]);
+                    }
+
+                    terminated = true;
+                }
+                Op::Call {
+                    callee,
+                    arguments: argument_span,
+                    results: result_slot,
+                } => {
+                    let callee_arguments = function.operands(argument_span);
+                    let (callee_declaration, result_count) = self.declarations[&callee];
+                    let callee_reference = self.module.declare_func_in_func(callee_declaration, builder.func);
+                    let argument_slot = builder.create_sized_stack_slot(ir::StackSlotData::new(
+                        ir::StackSlotKind::ExplicitSlot,
+                        (callee_arguments.len().max(1) * 8) as u32,
+                        3,
+                    ));
+                    let result_slot_data = builder.create_sized_stack_slot(ir::StackSlotData::new(
+                        ir::StackSlotKind::ExplicitSlot,
+                        (result_count.max(1) * 8) as u32,
+                        3,
+                    ));
+
+                    for (index, argument) in callee_arguments.iter().enumerate() {
+                        let value = operand(&mut builder, *argument);
+                        builder.ins().stack_store(value, argument_slot, (index * 8) as i32);
+                    }
+
+                    let argument_address = builder.ins().stack_addr(pointer_type, argument_slot, 0);
+                    let result_address = builder.ins().stack_addr(pointer_type, result_slot_data, 0);
+                    let call = builder
+                        .ins()
+                        .call(callee_reference, &[context, argument_address, result_address]);
+                    let status = builder.inst_results(call)[0];
+
+                    let failed_block = builder.create_block();
+                    let succeeded_block = builder.create_block();
+                    builder.ins().brif(status, failed_block, &[], succeeded_block, &[]);
+                    builder.switch_to_block(failed_block);
+                    builder.ins().return_(&[status]);
+                    builder.switch_to_block(succeeded_block);
+
+                    for index in 0..result_count {
+                        let value = builder.ins().stack_load(types::I64, result_slot_data, (index * 8) as i32);
+                        builder.def_var(Variable::from_u32(result_slot + index as u32), value);
+                    }
+                }
+                Op::Arithmetic {
+                    operation,
+                    kind,
+                    x,
+                    y,
+                    result,
+                } => {
+                    let x = operand(&mut builder, x);
+                    let y = operand(&mut builder, y);
+                    let value = match operation {
+                        ArithmeticOperation::Add => builder.ins().iadd(x, y),
+                        ArithmeticOperation::Sub => builder.ins().isub(x, y),
+                    };
+                    let value = wrap(&mut builder, value, kind);
+                    builder.def_var(Variable::from_u32(result), value);
+                }
+                Op::CheckedArithmetic {
+                    operation,
+                    overflow_behavior,
+                    kind,
+                    x,
+                    y,
+                    result,
+                } => {
+                    let helper = self.module.declare_func_in_func(self.checked_arithmetic, builder.func);
+                    let flag_slot =
+                        builder.create_sized_stack_slot(ir::StackSlotData::new(ir::StackSlotKind::ExplicitSlot, 8, 3));
+                    let flag_address = builder.ins().stack_addr(pointer_type, flag_slot, 0);
+                    let descriptor = builder.ins().iconst(
+                        types::I32,
+                        i64::from(checked_arithmetic_descriptor(operation, overflow_behavior, kind)),
+                    );
+                    let x = operand(&mut builder, x);
+                    let y = operand(&mut builder, y);
+                    let call = builder.ins().call(helper, &[descriptor, x, y, flag_address]);
+                    let value = builder.inst_results(call)[0];
+                    builder.def_var(Variable::from_u32(result), value);
+
+                    if overflow_behavior == sailar::instruction::OverflowBehavior::Flag {
+                        let flag = builder.ins().stack_load(types::I64, flag_slot, 0);
+                        builder.def_var(Variable::from_u32(result + 1), flag);
+                    }
+                }
+                Op::Compare {
+                    kind,
+                    signed,
+                    x,
+                    y,
+                    result,
+                    true_value,
+                } => {
+                    let condition = match kind {
+                        Comparison::Equal => IntCC::Equal,
+                        Comparison::NotEqual => IntCC::NotEqual,
+                        Comparison::LessThan if signed => IntCC::SignedLessThan,
+                        Comparison::LessThan => IntCC::UnsignedLessThan,
+                        Comparison::GreaterThan if signed => IntCC::SignedGreaterThan,
+                        Comparison::GreaterThan => IntCC::UnsignedGreaterThan,
+                        Comparison::LessThanOrEqual if signed => IntCC::SignedLessThanOrEqual,
+                        Comparison::LessThanOrEqual => IntCC::UnsignedLessThanOrEqual,
+                        Comparison::GreaterThanOrEqual if signed => IntCC::SignedGreaterThanOrEqual,
+                        Comparison::GreaterThanOrEqual => IntCC::UnsignedGreaterThanOrEqual,
+                    };
+
+                    let x = operand(&mut builder, x);
+                    let y = operand(&mut builder, y);
+                    let comparison = builder.ins().icmp(condition, x, y);
+                    let true_value = builder.ins().iconst(types::I64, true_value as i64);
+                    let false_value = builder.ins().iconst(types::I64, 0);
+                    let value = builder.ins().select(comparison, true_value, false_value);
+                    builder.def_var(Variable::from_u32(result), value);
+                }
+            }
+        }
+
+        builder.seal_all_blocks();
+        builder.finalize();
+
+        self.module
+            .define_function(declaration, &mut self.context)
+            .map_err(|e| UnsupportedError(e.to_string()))
+    }
+}
+
+impl Debug for Compiler {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_struct("Compiler")
+            .field("compiled_count", &self.compiled.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::{self, ExecutionMode};
+    use crate::value::Endianness;
+
+    fn run_program(program: sailar::builder::Builder<'static>, mode: ExecutionMode) -> Vec<u64> {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let module = loader
+            .load_module(sailar::validation::ValidModule::from_builder(program).unwrap())
+            .unwrap();
+
+        let runtime = runtime::Configuration::new()
+            .execution_mode(mode)
+            .tiering_threshold(std::num::NonZeroU32::new(2).unwrap())
+            .initialize_runtime();
+
+        let entry_point = module.entry_point().unwrap();
+
+        // Executed multiple times to ensure that tiered execution eventually calls compiled code.
+        let results = (0..3)
+            .map(|_| {
+                let values = runtime.execute(entry_point.clone(), Box::default()).unwrap();
+                values
+                    .iter()
+                    .map(|value| value.to_bits(Endianness::Little))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert!(results.windows(2).all(|pair| pair[0] == pair[1]));
+        results.into_iter().next().unwrap()
+    }
+
+    fn assert_same_results(program: fn() -> sailar::builder::Builder<'static>) {
+        let expected = run_program(program(), ExecutionMode::Interpreter);
+        for mode in [ExecutionMode::Jit, ExecutionMode::Tiered] {
+            assert_eq!(
+                expected,
+                run_program(program(), mode),
+                "results for {mode:?} differ from interpreter"
+            );
+        }
+    }
+
+    #[test]
+    fn samples_are_compiled() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let module = loader
+            .load_module(
+                sailar::validation::ValidModule::from_builder(sailar_samples::fibonacci("Test".try_into().unwrap(), 5)).unwrap(),
+            )
+            .unwrap();
+
+        let runtime = runtime::Configuration::new()
+            .execution_mode(ExecutionMode::Jit)
+            .initialize_runtime();
+
+        let (id, _) = runtime.decode_entry_point(module.entry_point().unwrap()).unwrap();
+        assert!(runtime.compile_function(id).is_some());
+    }
+
+    #[test]
+    fn stack_overflow_is_identical() {
+        for mode in [ExecutionMode::Interpreter, ExecutionMode::Jit] {
+            let loader = sailar_load::state::Configuration::new().create_state();
+            let module = loader
+                .load_module(
+                    sailar::validation::ValidModule::from_builder(sailar_samples::fibonacci("Test".try_into().unwrap(), 10))
+                        .unwrap(),
+                )
+                .unwrap();
+
+            let result = runtime::Configuration::new()
+                .execution_mode(mode)
+                .call_stack_size(crate::call_stack::Size::new(std::num::NonZeroUsize::new(4).unwrap()))
+                .initialize_runtime()
+                .execute(module.entry_point().unwrap().clone(), Box::default());
+
+            assert!(
+                matches!(result, Err(crate::error::RuntimeError::StackOverflow(_))),
+                "expected stack overflow for {mode:?}"
+            );
+        }
+    }
+
+    /// Produces a program whose entry point cannot be compiled, and starts a thread whose entry point calls the same function
+    /// as the entry point.
+    fn spawn_and_call_shared_function() -> sailar::builder::Builder<'static> {
+        use sailar::index;
+        use sailar::instruction::{self, Instruction};
+        use sailar::record;
+
+        let integer_type = index::TypeSignature::from(0);
+        let signature = index::FunctionSignature::from(0);
+        let worker_function = index::Function::from(1);
+        let leaf_function = index::Function::from(2);
+        let mut builder = sailar::builder::Builder::new();
+
+        builder.add_record(sailar::signature::Type::from(sailar::signature::IntegerType::U32));
+        builder.add_record(sailar::signature::Function::new([].as_slice(), vec![integer_type]));
+
+        // main: %t0 = thread.spawn worker (); %t1 = call leaf (); %t2 = thread.join %t0; %t3 = iadd %t1 %t2; ret %t3
+        builder.add_record(record::CodeBlock::new(
+            [].as_slice(),
+            vec![integer_type],
+            vec![integer_type; 4],
+            vec![
+                Instruction::ThreadSpawn(worker_function, Box::default()),
+                Instruction::Call(leaf_function, Box::default()),
+                Instruction::ThreadJoin(signature, index::Register::from(0).into()),
+                Instruction::IAdd(Box::new(instruction::IntegerArithmetic::new(
+                    instruction::OverflowBehavior::Ignore,
+                    index::Register::from(1).into(),
+                    index::Register::from(2).into(),
+                ))),
+                Instruction::Return(vec![index::Register::from(3).into()].into_boxed_slice()),
+            ],
+        ));
+
+        // worker: %t0 = call leaf (); ret %t0
+        builder.add_record(record::CodeBlock::new(
+            [].as_slice(),
+            vec![integer_type],
+            vec![integer_type],
+            vec![
+                Instruction::Call(leaf_function, Box::default()),
+                Instruction::Return(vec![index::Register::from(0).into()].into_boxed_slice()),
+            ],
+        ));
+
+        // leaf: ret 2
+        builder.add_record(record::CodeBlock::new(
+            [].as_slice(),
+            vec![integer_type],
+            [].as_slice(),
+            vec![Instruction::Return(vec![2u32.into()].into_boxed_slice())],
+        ));
+
+        for (name, export, block) in [("main", true, 0usize), ("worker", false, 1), ("leaf", false, 2)] {
+            let symbol = sailar::identifier::Id::try_from_str(name).unwrap();
+            builder.add_record(record::FunctionTemplate::new(
+                if export {
+                    record::Export::ExportBorrowed(symbol)
+                } else {
+                    record::Export::PrivateBorrowed(symbol)
+                },
+                signature,
+                index::CodeBlock::from(block),
+            ));
+        }
+
+        for template in 0usize..3 {
+            builder.add_record(record::Function::with_template(index::FunctionTemplate::from(template)));
+        }
+
+        builder.add_record(record::MetadataField::EntryPoint(index::Function::from(0)));
+        builder
+    }
+
+    #[test]
+    fn callers_of_unsupported_functions_are_interpreted() {
+        assert_same_results(spawn_and_call_shared_function);
+    }
+
+    #[test]
+    fn parallel_fibonacci_is_identical() {
+        assert_same_results(|| sailar_samples::parallel_fibonacci("Test".try_into().unwrap(), 12));
+    }
+
+    #[test]
+    fn exit_with_is_identical() {
+        assert_same_results(|| sailar_samples::exit_with("Test".try_into().unwrap(), 0xABCD));
+    }
+
+    #[test]
+    fn fibonacci_is_identical() {
+        assert_same_results(|| sailar_samples::fibonacci("Test".try_into().unwrap(), 15));
+    }
+
+    #[test]
+    fn vector_sum_is_identical() {
+        assert_same_results(|| sailar_samples::vector_sum("Test".try_into().unwrap(), 0xFFFF_FFFF));
+    }
+
+    #[test]
+    fn vector_call_is_identical() {
+        assert_same_results(|| sailar_samples::vector_call("Test".try_into().unwrap(), 7));
+    }
+
+    #[test]
+    fn sum_loop_is_identical() {
+        assert_same_results(|| sailar_samples::sum_loop("Test".try_into().unwrap(), 100000));
+    }
+}
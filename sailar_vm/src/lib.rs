@@ -1,7 +1,13 @@
 //! The SAILAR interpreted virtual machine.
 
+mod atomic;
+pub mod bytecode;
 pub mod call_stack;
+pub mod entry_point;
 pub mod error;
 pub mod interpreter;
+#[cfg(feature = "jit")]
+pub mod jit;
 pub mod runtime;
+pub mod thread;
 pub mod value;
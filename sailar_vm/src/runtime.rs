@@ -1,10 +1,14 @@
 //! Module to interact with the SAILAR virtual machine runtime.
 
+use crate::bytecode;
 use crate::call_stack;
 use crate::error;
 use crate::interpreter;
+use crate::thread;
 use crate::value;
-use std::sync::Arc;
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::sync::{Arc, Mutex, RwLock};
 
 pub type Result<T> = std::result::Result<T, error::RuntimeError>;
 
@@ -13,12 +17,34 @@ pub type Loader = Arc<sailar_load::state::State>;
 
 pub type Function = Arc<sailar_load::function::Function>;
 
+/// Specifies how SAILAR code is executed.
+///
+/// Functions that cannot be compiled to machine code are always interpreted. Compilation requires the `jit` feature, without
+/// it all modes behave like [`ExecutionMode::Interpreter`].
+#[derive(Copy, Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub enum ExecutionMode {
+    /// All code is interpreted.
+    #[default]
+    Interpreter,
+    /// Functions are compiled to machine code before they are executed.
+    Jit,
+    /// Functions are interpreted until they are called a certain number of times, after which they are compiled to machine
+    /// code.
+    Tiered,
+}
+
 /// Encapsulates all state needed to execute the SAILAR virtual machine.
 #[derive(Debug)]
 pub struct Runtime {
     call_stack_size: call_stack::Size,
     endianness: value::Endianness,
-    // TODO: Could have hash_map that maps threads to their interpreter state?
+    execution_mode: ExecutionMode,
+    tiering_threshold: NonZeroU32,
+    function_table: Mutex<bytecode::FunctionTable>,
+    #[cfg(feature = "jit")]
+    compiler: Option<Mutex<crate::jit::Compiler>>,
+    threads: thread::Registry,
+    memory_regions: RwLock<Vec<Range<usize>>>,
 }
 
 /// Used configuring the properties of the SAILAR virtual machine.
@@ -27,13 +53,23 @@ pub struct Runtime {
 pub struct Configuration {
     call_stack_size: call_stack::Size,
     endianness: value::Endianness,
+    execution_mode: ExecutionMode,
+    tiering_threshold: NonZeroU32,
 }
 
 impl Configuration {
+    /// The default number of calls after which a function is compiled when execution is tiered.
+    pub const DEFAULT_TIERING_THRESHOLD: NonZeroU32 = match NonZeroU32::new(100) {
+        Some(threshold) => threshold,
+        None => unreachable!(),
+    };
+
     pub fn new() -> Self {
         Self {
             call_stack_size: call_stack::Size::DEFAULT,
             endianness: Default::default(),
+            execution_mode: Default::default(),
+            tiering_threshold: Self::DEFAULT_TIERING_THRESHOLD,
         }
     }
 
@@ -50,10 +86,37 @@ impl Configuration {
         Self { endianness, ..self }
     }
 
+    /// Sets whether code is interpreted, compiled to machine code, or both.
+    pub fn execution_mode(self, mode: ExecutionMode) -> Self {
+        Self {
+            execution_mode: mode,
+            ..self
+        }
+    }
+
+    /// Sets the number of times a function must be called before it is compiled when execution is tiered.
+    pub fn tiering_threshold(self, threshold: NonZeroU32) -> Self {
+        Self {
+            tiering_threshold: threshold,
+            ..self
+        }
+    }
+
     pub fn initialize_runtime(self) -> Arc<Runtime> {
         Arc::new(Runtime {
             call_stack_size: self.call_stack_size,
             endianness: self.endianness,
+            execution_mode: self.execution_mode,
+            tiering_threshold: self.tiering_threshold,
+            function_table: Default::default(),
+            threads: Default::default(),
+            memory_regions: Default::default(),
+            #[cfg(feature = "jit")]
+            compiler: match self.execution_mode {
+                ExecutionMode::Interpreter => None,
+                // If the host is not supported, all code is interpreted instead.
+                ExecutionMode::Jit | ExecutionMode::Tiered => crate::jit::Compiler::new().ok().map(Mutex::new),
+            },
         })
     }
 }
@@ -73,27 +136,166 @@ impl Runtime {
         self.endianness
     }
 
-    fn execute_entry_point(
-        self: &Arc<Self>,
-        entry_point: Function,
-        arguments: Box<[value::Value]>,
-    ) -> Result<Box<[value::Value]>> {
-        let mut state = interpreter::State::new(self.clone(), entry_point, arguments)?;
-
-        loop {
-            match state.step()? {
-                Some(return_values) => return Ok(return_values),
-                None => continue,
-            }
+    pub fn execution_mode(&self) -> ExecutionMode {
+        self.execution_mode
+    }
+
+    pub fn tiering_threshold(&self) -> NonZeroU32 {
+        self.tiering_threshold
+    }
+
+    /// Allows SAILAR code executed by this runtime to access the `length` bytes of host memory starting at `start` with
+    /// atomic operations.
+    ///
+    /// # Safety
+    ///
+    /// The memory region must be valid for reads and writes until the runtime is dropped, and must only be accessed with
+    /// atomic operations while SAILAR code is executing.
+    pub unsafe fn add_memory_region(&self, start: *mut u8, length: usize) {
+        let start = start as usize;
+        let end = start.saturating_add(length);
+        self.memory_regions.write().unwrap().push(start..end);
+    }
+
+    /// Checks that an atomic operation on an integer of the specified `kind` at the `address` only accesses memory in a
+    /// region added by the embedder.
+    pub(crate) fn check_atomic_address(&self, kind: bytecode::IntegerKind, address: u64) -> Result<()> {
+        crate::atomic::check_address(&self.memory_regions.read().unwrap(), kind, address)
+    }
+
+    /// Gets the decoded bytecode of a function that is called from outside of SAILAR code, decoding it if necessary.
+    pub(crate) fn decode_entry_point(&self, function: &Function) -> Result<(bytecode::FunctionId, Arc<bytecode::Function>)> {
+        let mut function_table = self.function_table.lock().unwrap();
+        let id = function_table.register(function);
+        Ok((id, function_table.get_or_decode(id)?))
+    }
+
+    /// Gets the decoded bytecode of a `function`, decoding it if it has not been decoded yet.
+    pub fn decoded_function(&self, function: &Function) -> Result<Arc<bytecode::Function>> {
+        Ok(self.decode_entry_point(function)?.1)
+    }
+
+    /// Gets the decoded bytecode of a function, decoding it if it has not been decoded yet.
+    pub(crate) fn decode_function(&self, id: bytecode::FunctionId) -> Result<Arc<bytecode::Function>> {
+        self.function_table.lock().unwrap().get_or_decode(id)
+    }
+
+    /// Compiles the specified function, returning `None` if compilation is not enabled or not supported for the function.
+    #[cfg(feature = "jit")]
+    pub(crate) fn compile_function(&self, id: bytecode::FunctionId) -> Option<crate::jit::NativeFunction> {
+        let mut compiler = self.compiler.as_ref()?.lock().unwrap();
+        compiler.compile(id, |callee| self.decode_function(callee)).ok()
+    }
+
+    /// Executes the compiled version of the specified function, returning `None` if the function must be interpreted instead.
+    #[cfg(feature = "jit")]
+    fn execute_compiled(&self, function: &bytecode::Function, arguments: &[u64]) -> Result<Option<Box<[u64]>>> {
+        if self.compiler.is_none() {
+            return Ok(None);
+        }
+
+        if self.execution_mode == ExecutionMode::Tiered && function.increment_call_count() < self.tiering_threshold.get() {
+            return Ok(None);
+        }
+
+        let native = match self.compile_function(function.id()) {
+            Some(native) => native,
+            None => return Ok(None),
+        };
+
+        let mut results = vec![0u64; function.return_kinds().len()].into_boxed_slice();
+        let call_stack_size = self.call_stack_size.get().get();
+
+        if native.call(
+            call_stack_size.min(crate::jit::MAX_NATIVE_CALL_DEPTH),
+            arguments,
+            &mut results,
+        ) {
+            Ok(Some(results))
+        } else if call_stack_size <= crate::jit::MAX_NATIVE_CALL_DEPTH {
+            Err(error::RuntimeError::StackOverflow(self.call_stack_size.get()))
+        } else {
+            // The native call stack was not large enough, so the function is interpreted instead.
+            Ok(None)
         }
     }
 
-    /// Interprets the specified function in the current thread, providing the specified `arguments`.
+    /// Creates the interpreter state used to execute the specified function in the current thread.
+    pub fn create_state<E, A>(self: &Arc<Self>, entry_point: E, arguments: A) -> Result<interpreter::State>
+    where
+        E: Into<Function>,
+        A: Into<Box<[value::Value]>>,
+    {
+        interpreter::State::new(self.clone(), entry_point.into(), &arguments.into())
+    }
+
+    /// Executes the specified function in the current thread, providing the specified `arguments`.
+    ///
+    /// Each lane of a vector parameter or return value is passed as a separate value.
     pub fn execute<E, A>(self: &Arc<Self>, entry_point: E, arguments: A) -> Result<Box<[value::Value]>>
     where
         E: Into<Function>,
         A: Into<Box<[value::Value]>>,
     {
-        self.execute_entry_point(entry_point.into(), arguments.into())
+        let (_, entry_point) = self.decode_entry_point(&entry_point.into())?;
+        let arguments = entry_point.convert_arguments(&arguments.into(), self.endianness)?;
+        let return_values = self.execute_raw(entry_point.clone(), &arguments)?;
+        Ok(entry_point.convert_return_values(return_values.iter().copied(), self.endianness))
+    }
+
+    /// Executes a function in the current thread, with arguments and return values in their register file representation.
+    fn execute_raw(self: &Arc<Self>, entry_point: Arc<bytecode::Function>, arguments: &[u64]) -> Result<Box<[u64]>> {
+        #[cfg(feature = "jit")]
+        if let Some(return_values) = self.execute_compiled(&entry_point, arguments)? {
+            return Ok(return_values);
+        }
+
+        interpreter::State::with_raw_arguments(self.clone(), entry_point, arguments)?.run_raw()
+    }
+
+    pub(crate) fn spawn_raw(
+        self: &Arc<Self>,
+        entry_point: Arc<bytecode::Function>,
+        arguments: Box<[u64]>,
+        handle_kind: bytecode::IntegerKind,
+    ) -> Result<thread::ThreadId> {
+        let runtime = self.clone();
+        let thread_entry_point = entry_point.clone();
+        self.threads.spawn(entry_point, handle_kind, move || {
+            runtime.execute_raw(thread_entry_point, &arguments)
+        })
+    }
+
+    pub(crate) fn join_raw(
+        &self,
+        thread: thread::ThreadId,
+        result_kinds: Option<&[bytecode::IntegerKind]>,
+    ) -> Result<(Arc<bytecode::Function>, Box<[u64]>)> {
+        self.threads.join(thread, result_kinds)
+    }
+
+    /// Starts a new thread that executes the specified function, providing the specified `arguments`.
+    pub fn spawn<E, A>(self: &Arc<Self>, entry_point: E, arguments: A) -> Result<thread::ThreadId>
+    where
+        E: Into<Function>,
+        A: Into<Box<[value::Value]>>,
+    {
+        let (_, entry_point) = self.decode_entry_point(&entry_point.into())?;
+        let arguments = entry_point.convert_arguments(&arguments.into(), self.endianness)?;
+        self.spawn_raw(entry_point, arguments, bytecode::IntegerKind::THREAD_ID)
+    }
+
+    /// Waits for a thread started by this runtime or by SAILAR code to finish, returning the values returned by its entry
+    /// point.
+    ///
+    /// Each thread can only be joined once.
+    pub fn join(&self, thread: thread::ThreadId) -> Result<Box<[value::Value]>> {
+        let (entry_point, return_values) = self.join_raw(thread, None)?;
+        Ok(entry_point.convert_return_values(return_values.iter().copied(), self.endianness))
+    }
+
+    /// Gets the threads that have been started but not yet joined, sorted by their identifiers.
+    pub fn threads(&self) -> Vec<thread::ThreadId> {
+        self.threads.ids()
     }
 }
@@ -0,0 +1,120 @@
+//! Module for keeping track of the threads started by a SAILAR virtual machine runtime.
+
+use crate::bytecode;
+use crate::error;
+use crate::runtime;
+use std::collections::hash_map;
+use std::fmt::{Display, Formatter};
+use std::num::NonZeroU64;
+use std::sync::atomic::{self, AtomicU64};
+use std::sync::{Arc, Mutex};
+
+/// Uniquely identifies a thread started by a runtime.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[repr(transparent)]
+pub struct ThreadId(NonZeroU64);
+
+impl ThreadId {
+    pub const fn new(id: NonZeroU64) -> Self {
+        Self(id)
+    }
+
+    pub const fn get(self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl Display for ThreadId {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+struct Thread {
+    entry_point: Arc<bytecode::Function>,
+    handle: std::thread::JoinHandle<runtime::Result<Box<[u64]>>>,
+}
+
+/// Contains the threads that were started but have not yet been joined.
+#[derive(Default)]
+pub(crate) struct Registry {
+    next_id: AtomicU64,
+    threads: Mutex<hash_map::HashMap<ThreadId, Thread>>,
+}
+
+impl Registry {
+    /// Starts a thread, failing if its identifier cannot be stored in a register of the specified `handle_kind`.
+    pub(crate) fn spawn<F>(
+        &self,
+        entry_point: Arc<bytecode::Function>,
+        handle_kind: bytecode::IntegerKind,
+        body: F,
+    ) -> runtime::Result<ThreadId>
+    where
+        F: FnOnce() -> runtime::Result<Box<[u64]>> + Send + 'static,
+    {
+        let id = ThreadId(NonZeroU64::new(self.next_id.fetch_add(1, atomic::Ordering::Relaxed) + 1).unwrap());
+        if handle_kind.wrap(id.get()) != id.get() {
+            return Err(error::RuntimeError::ThreadHandleOverflow {
+                thread: id.get(),
+                bits: handle_kind.bit_size(),
+            });
+        }
+
+        // The registry is locked before the thread is started, ensuring that it cannot be joined before it is registered.
+        let mut threads = self.threads.lock().unwrap();
+        let handle = std::thread::Builder::new()
+            .name(format!("sailar-{id}"))
+            .spawn(body)
+            .map_err(|e| error::RuntimeError::ThreadSpawnFailed(e.to_string()))?;
+
+        threads.insert(id, Thread { entry_point, handle });
+        Ok(id)
+    }
+
+    /// Waits for the thread to finish, returning its entry point and the values it returned.
+    ///
+    /// If `result_kinds` is specified, the thread is only joined if they match the return types of its entry point.
+    pub(crate) fn join(
+        &self,
+        id: ThreadId,
+        result_kinds: Option<&[bytecode::IntegerKind]>,
+    ) -> runtime::Result<(Arc<bytecode::Function>, Box<[u64]>)> {
+        let thread = {
+            let mut threads = self.threads.lock().unwrap();
+            let thread = threads.get(&id).ok_or(error::RuntimeError::UnknownThread(id.get()))?;
+
+            if let Some(expected) = result_kinds {
+                let actual = thread.entry_point.return_kinds();
+                if expected.len() != actual.len() {
+                    return Err(error::RuntimeError::ThreadResultCountMismatch {
+                        thread: id.get(),
+                        expected: expected.len(),
+                        actual: actual.len(),
+                    });
+                } else if expected != actual {
+                    return Err(error::RuntimeError::ThreadResultTypeMismatch(id.get()));
+                }
+            }
+
+            threads.remove(&id).unwrap()
+        };
+
+        match thread.handle.join() {
+            Ok(result) => Ok((thread.entry_point, result?)),
+            Err(_) => Err(error::RuntimeError::ThreadPanicked(id.get())),
+        }
+    }
+
+    pub(crate) fn ids(&self) -> Vec<ThreadId> {
+        let mut ids = self.threads.lock().unwrap().keys().copied().collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids
+    }
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
+        f.debug_set().entries(self.ids()).finish()
+    }
+}
@@ -72,6 +72,49 @@ impl Value {
     }
 }
 
+impl Value {
+    /// Creates a value containing the lowest bytes of the specified `bits`.
+    pub fn from_bits(bits: u64, byte_size: usize, endianness: Endianness) -> Self {
+        let mut value = match byte_size {
+            1 => Value::I8(0),
+            2 => Value::I16([0; 2]),
+            3 => Value::I24([0; 3]),
+            4 => Value::I32([0; 4]),
+            5 => Value::I40([0; 5]),
+            6 => Value::I48([0; 6]),
+            7 => Value::I56([0; 7]),
+            8 => Value::I64([0; 8]),
+            _ => Value::Boxed(vec![0u8; byte_size].into_boxed_slice()),
+        };
+
+        let length = byte_size.min(8);
+        let destination = BorrowMut::<[u8]>::borrow_mut(&mut value);
+        match endianness {
+            Endianness::Little => destination[..length].copy_from_slice(&bits.to_le_bytes()[..length]),
+            Endianness::Big => destination[byte_size - length..].copy_from_slice(&bits.to_be_bytes()[8 - length..]),
+        }
+
+        value
+    }
+
+    /// Gets the lowest 64 bits of this value, zero extending if the value contains less than 8 bytes.
+    pub fn to_bits(&self, endianness: Endianness) -> u64 {
+        let source = Borrow::<[u8]>::borrow(self);
+        let length = source.len().min(8);
+        let mut bytes = [0u8; 8];
+        match endianness {
+            Endianness::Little => {
+                bytes[..length].copy_from_slice(&source[..length]);
+                u64::from_le_bytes(bytes)
+            }
+            Endianness::Big => {
+                bytes[8 - length..].copy_from_slice(&source[source.len() - length..]);
+                u64::from_be_bytes(bytes)
+            }
+        }
+    }
+}
+
 macro_rules! value_conversion_to_integer {
     ($name:ident, $destination:ty) => {
         impl Value {
@@ -1,50 +1,121 @@
 //! Executable for assembling SAILAR assembly files
 
+use std::path::{Path, PathBuf};
+
 /// SAILAR bytecode assembler
 #[derive(Debug, clap::Parser)]
-#[clap(version, about)]
+#[clap(version, about, subcommand_negates_reqs = true)]
 struct Arguments {
     /// Path to the SAILAR assembly file
-    #[clap(long, short)]
-    input: std::path::PathBuf,
+    #[clap(long, short, required = true)]
+    input: Option<PathBuf>,
     /// Path to the file containing the assembled SAILAR binary module.
     #[clap(long, short)]
-    output: Option<std::path::PathBuf>,
+    output: Option<PathBuf>,
+    #[clap(subcommand)]
+    command: Option<Command>,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let arguments: Arguments = clap::Parser::parse();
-    let input = std::fs::read_to_string(&arguments.input)?;
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    /// Rewrites SAILAR assembly files in a canonical layout
+    Fmt {
+        /// Paths to the SAILAR assembly files to format
+        #[clap(required = true)]
+        files: Vec<PathBuf>,
+        /// Instead of modifying the files, fails if any of them are not already formatted
+        #[clap(long)]
+        check: bool,
+    },
+}
+
+fn print_errors(renderer: &sailasm::diagnostics::Renderer, errors: &[sailasm::AnyError]) -> std::io::Result<()> {
+    use std::io::Write as _;
+
+    let output = std::io::stderr();
+    let output_handle = output.lock();
+    let mut buffered_output = std::io::BufWriter::new(output_handle);
+
+    for e in errors.iter() {
+        writeln!(buffered_output, "{}", renderer.render_to_string(e))?;
+    }
+
+    Ok(())
+}
+
+fn assemble(input_path: &Path, output: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+    let input = sailasm::preprocessor::preprocess_file(input_path)?;
     let mut tree = None;
 
-    match sailasm::assemble(&input, &mut tree) {
+    match sailasm::assemble_preprocessed(&input, &mut tree) {
         Ok(module) => {
             let default_output;
-            let output = if let Some(path) = &arguments.output {
+            let output = if let Some(path) = output {
                 path
             } else {
-                default_output = arguments.input.with_extension("sail");
+                default_output = input_path.with_extension("sail");
                 &default_output
             };
 
             module.write_to(std::fs::File::create(output)?).map_err(Box::from)
         }
         Err(errors) => {
-            use std::io::Write as _;
-
-            let output = std::io::stderr();
-            let output_handle = output.lock();
-            let mut buffered_output = std::io::BufWriter::new(output_handle);
-
-            for e in errors.iter() {
-                write!(buffered_output, "error")?;
-                if let Some(location) = e.location() {
-                    write!(buffered_output, "{}", location)?;
-                }
-                writeln!(buffered_output, ": {}", e.kind())?;
+            let paths = input
+                .files()
+                .iter()
+                .map(|file| file.path().to_string_lossy())
+                .collect::<Vec<_>>();
+
+            let mut renderer = sailasm::diagnostics::Renderer::new(input.files()[0].contents()).with_path(&paths[0]);
+            for (file, path) in input.files().iter().zip(paths.iter()).skip(1) {
+                renderer = renderer.with_file(path, file.contents());
             }
 
+            print_errors(&renderer, &errors)?;
             Err(Box::from(format!("failed with {} errors", errors.len())))
         }
     }
 }
+
+fn format(files: &[PathBuf], check: bool) -> Result<(), Box<dyn std::error::Error>> {
+    let options = sailasm::formatter::Options::default();
+    let mut failed = 0usize;
+    let mut unformatted = 0usize;
+
+    for path in files.iter() {
+        let input = std::fs::read_to_string(path)?;
+        match sailasm::formatter::format(&input, &options) {
+            Ok(formatted) if formatted == input => (),
+            Ok(_) if check => {
+                println!("{} is not formatted", path.display());
+                unformatted += 1;
+            }
+            Ok(formatted) => std::fs::write(path, formatted)?,
+            Err(errors) => {
+                let path = path.to_string_lossy();
+                print_errors(&sailasm::diagnostics::Renderer::new(&input).with_path(&path), &errors)?;
+                failed += 1;
+            }
+        }
+    }
+
+    if failed > 0 {
+        Err(Box::from(format!("could not format {} files", failed)))
+    } else if unformatted > 0 {
+        Err(Box::from(format!("{} files are not formatted", unformatted)))
+    } else {
+        Ok(())
+    }
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let arguments: Arguments = clap::Parser::parse();
+
+    match &arguments.command {
+        Some(Command::Fmt { files, check }) => format(files, *check),
+        None => assemble(
+            arguments.input.as_deref().expect("input path is required"),
+            arguments.output.as_deref(),
+        ),
+    }
+}
@@ -49,6 +49,11 @@ document.addEventListener('DOMContentLoaded', async (_) => {
      */
     const outputDownloadButton = document.getElementById('output-download');
 
+    /**
+     * @type {HTMLInputElement}
+     */
+    const runArgumentsInput = document.getElementById('run-arguments');
+
     const output = document.getElementById('output-area').appendChild(document.createElement('pre'));
     output.style = 'width: 100%; height: 100%; margin: 0';
 
@@ -57,7 +62,7 @@ document.addEventListener('DOMContentLoaded', async (_) => {
         lineNumbers: true,
         lint:  true,
         mode: assemblerModeName,
-        value: '.format major 0\n.format minor 18\n.metadata id "MyModule" 1.0.0\n; Add additional directives below\n\n',
+        value: '.format major 0\n.format minor 19\n.metadata id "MyModule" 1.0.0\n; Add additional directives below\n\n',
     });
 
     const refreshCodeEditor = setTimeout(() => editor.refresh(), 0);
@@ -66,18 +71,15 @@ document.addEventListener('DOMContentLoaded', async (_) => {
         function update() {
             let errors = [];
 
-            function appendOutputError(error, locations) {
+            function appendOutputError(error, locations, rendered) {
                 if (errors.length === 0) {
                     output.innerHTML = '';
                 }
 
                 outputDownloadButton.onclick = null;
-                output.innerHTML += 'error';
-
+                output.appendChild(document.createTextNode(rendered + '\n'));
 
                 if (locations !== null) {
-                    output.innerHTML += `(${locations[0]},${locations[1]})-(${locations[2]},${locations[3]})`;
-
                     // Assembler location numbers start at 1, while CodeMirror expects numbers starting at 0.
                     errors.push({
                         from: CodeMirror.Pos(locations[0] - 1, locations[1] - 1),
@@ -86,8 +88,10 @@ document.addEventListener('DOMContentLoaded', async (_) => {
                         severity: "error",
                     });
                 }
+            }
 
-                output.innerHTML += ': ' + error + '\n';
+            function appendOutputMessage(message) {
+                output.appendChild(document.createTextNode(message + '\n'));
             }
 
             /**
@@ -147,6 +151,17 @@ document.addEventListener('DOMContentLoaded', async (_) => {
                                 .padStart(2, '0');
                         }
 
+                        break;
+                    case 2:
+                        asm.disassemble(module, appendOutputMessage, (text) => {
+                            output.innerText = text;
+                        });
+                        break;
+                    case 3:
+                        const runArguments = runArgumentsInput.value.split(/\s+/).filter((argument) => argument !== '');
+                        asm.run(module, runArguments, appendOutputMessage, (returnValues) => {
+                            output.innerText = 'returned ' + returnValues.join(', ');
+                        });
                         break;
                     default:
                         output.innerHTML = 'unknown output type';
@@ -160,6 +175,8 @@ document.addEventListener('DOMContentLoaded', async (_) => {
 
         asm.register_panic_hook();
         globalAssemblyLinter = update;
+        outputTypeSelection.onchange = update;
+        runArgumentsInput.onchange = update;
         update();
     }).catch(console.error);
 
@@ -1,17 +1,31 @@
 #![doc = include_str!("../README.md")]
 
+use sailar_vm::{call_stack, entry_point, runtime};
+use std::path::Path;
 use wasm_bindgen::prelude::*;
 
 type JsArray = js_sys::Array;
 type JsFunction = js_sys::Function;
 type JsResult<T> = Result<T, JsValue>;
 
+type Error = Box<dyn std::error::Error>;
+
+/// The path given to the source code that is being assembled, since files cannot be accessed from the browser.
+const INPUT_PATH: &str = "input.sailasm";
+
+/// The maximum number of stack frames used when running a module, kept small since the memory available to WebAssembly is
+/// limited.
+const CALL_STACK_SIZE: call_stack::Size = call_stack::Size::new(match std::num::NonZeroUsize::new(0xFFFF) {
+    Some(size) => size,
+    None => unreachable!(),
+});
+
 struct PrintWrapper {
     error_function: JsFunction,
 }
 
 impl PrintWrapper {
-    fn print_error(&self, message: &str, location: Option<&sailasm::ast::LocationRange>) -> JsResult<()> {
+    fn print_error(&self, message: &str, location: Option<&sailasm::ast::LocationRange>, rendered: &str) -> JsResult<()> {
         fn convert_location_number(number: sailasm::ast::LocationNumber) -> JsResult<JsValue> {
             Ok(JsValue::from(
                 u32::try_from(number.get()).map_err(|e| JsValue::from(e.to_string()))?,
@@ -29,18 +43,60 @@ impl PrintWrapper {
         };
 
         self.error_function
-            .call2(
+            .call3(
                 &JsValue::NULL,
                 &JsValue::from_str(message),
                 js_location
                     .as_ref()
                     .map(|location| location.as_ref())
                     .unwrap_or(&JsValue::NULL),
+                &JsValue::from_str(rendered),
             )
             .map(|_| ())
     }
 }
 
+/// Expands the macros in the `input`, any files that it attempts to include cannot be found.
+fn preprocess(input: &str) -> sailasm::preprocessor::Output {
+    sailasm::preprocessor::preprocess(Path::new(INPUT_PATH), |path| {
+        if path == Path::new(INPUT_PATH) {
+            Ok(input.to_string())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "files cannot be included in the browser",
+            ))
+        }
+    })
+    .expect("input should always be loaded")
+}
+
+fn read_module(module: &[u8]) -> Result<sailar::builder::Builder<'static>, sailar::reader::Error> {
+    sailar::builder::Builder::from_reader(sailar::reader::Reader::new(module))
+}
+
+fn disassemble_module(module: &[u8]) -> Result<String, Error> {
+    let mut output = Vec::new();
+    dasmdl::disassembler::disassemble(&mut output, &read_module(module)?, &Default::default())?;
+    Ok(String::from_utf8(output)?)
+}
+
+/// Executes the entry point of a module, returning its return values as integers.
+///
+/// The values of signed integer types are sign extended.
+fn run_module(module: &[u8], arguments: &[String]) -> Result<Box<[i128]>, Error> {
+    let module = sailar::validation::ValidModule::from_builder(read_module(module)?)?;
+    let loader = sailar_load::state::Configuration::new().create_state();
+    let module = loader.load_module(module).unwrap_or_else(|existing| existing);
+    let entry_point = module.entry_point().ok_or("module does not specify an entry point")?;
+
+    let runtime = runtime::Configuration::new()
+        .call_stack_size(CALL_STACK_SIZE)
+        .initialize_runtime();
+
+    Ok(entry_point::execute(&runtime, entry_point, arguments)?)
+}
+
 #[wasm_bindgen]
 pub fn register_panic_hook() {
     std::panic::set_hook(Box::new(console_error_panic_hook::hook));
@@ -52,9 +108,10 @@ pub fn assemble(input: &str, print_error: JsFunction, write_output: JsFunction)
         error_function: print_error,
     };
 
+    let input = preprocess(input);
     let mut parse_tree = None;
 
-    match sailasm::assemble(input, &mut parse_tree) {
+    match sailasm::assemble_preprocessed(&input, &mut parse_tree) {
         Ok(module) => {
             let mut buffer = Vec::default();
             module.write_to(&mut buffer).unwrap();
@@ -65,14 +122,133 @@ pub fn assemble(input: &str, print_error: JsFunction, write_output: JsFunction)
         Err(errors) => {
             use std::fmt::Write as _;
 
+            let renderer = sailasm::diagnostics::Renderer::new(input.files()[0].contents()).with_path(INPUT_PATH);
             let mut message_buffer = String::default();
             for e in errors.iter() {
                 message_buffer.clear();
                 write!(&mut message_buffer, "{}", e.kind()).unwrap();
-                print_wrapper.print_error(&message_buffer, e.location())?;
+                print_wrapper.print_error(&message_buffer, e.location(), &renderer.render_to_string(e))?;
             }
 
             Ok(())
         }
     }
 }
+
+/// Disassembles a SAILAR binary module, passing the resulting text to `write_output`.
+#[wasm_bindgen]
+pub fn disassemble(module: &[u8], print_error: JsFunction, write_output: JsFunction) -> JsResult<()> {
+    match disassemble_module(module) {
+        Ok(text) => write_output.call1(&JsValue::NULL, &JsValue::from_str(&text)).map(|_| ()),
+        Err(e) => print_error
+            .call1(&JsValue::NULL, &JsValue::from_str(&e.to_string()))
+            .map(|_| ()),
+    }
+}
+
+/// Executes the entry point of a SAILAR binary module with the integer `arguments`, passing an array containing each
+/// return value as a decimal string to `write_output`.
+#[wasm_bindgen]
+pub fn run(module: &[u8], arguments: JsArray, print_error: JsFunction, write_output: JsFunction) -> JsResult<()> {
+    let arguments = arguments
+        .iter()
+        .map(|argument| {
+            argument
+                .as_string()
+                .or_else(|| argument.as_f64().map(|number| number.to_string()))
+                .ok_or_else(|| JsValue::from_str("arguments must be strings or numbers"))
+        })
+        .collect::<JsResult<Vec<_>>>()?;
+
+    match run_module(module, &arguments) {
+        Ok(return_values) => {
+            let output = return_values
+                .iter()
+                .map(|value| JsValue::from_str(&value.to_string()))
+                .collect::<JsArray>();
+
+            write_output.call1(&JsValue::NULL, &output).map(|_| ())
+        }
+        Err(e) => print_error
+            .call1(&JsValue::NULL, &JsValue::from_str(&e.to_string()))
+            .map(|_| ()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    const ADD: &str = r#".format major 0
+.format minor 19
+.metadata id "Add" 1.0
+.metadata main @main_instance
+.signature @u32 type u32
+.signature @main_signature function (@u32, @u32) -> (@u32)
+
+.macro add %x %y %result
+    %result = iadd %x %y
+.endmacro
+
+.code @main_body ($x:@u32, $y:@u32) -> (@u32)
+    .expand add $x $y $sum
+    ret $sum
+
+.define @main function export "main" signature @main_signature
+/body @main_body
+
+.instantiate @main_instance function definition @main
+"#;
+
+    fn assemble(input: &str) -> Vec<u8> {
+        let input = super::preprocess(input);
+        let mut parse_tree = None;
+        let module = sailasm::assemble_preprocessed(&input, &mut parse_tree).unwrap();
+        let mut buffer = Vec::new();
+        module.write_to(&mut buffer).unwrap();
+        buffer
+    }
+
+    #[test]
+    fn module_is_run() {
+        let module = assemble(ADD);
+        let arguments = ["40".to_string(), "2".to_string()];
+        assert_eq!(super::run_module(&module, &arguments).unwrap().as_ref(), &[42]);
+    }
+
+    #[test]
+    fn signed_results_are_sign_extended() {
+        let module = assemble(&ADD.replace("u32", "s32"));
+        let arguments = ["-3".to_string(), "2".to_string()];
+        assert_eq!(super::run_module(&module, &arguments).unwrap().as_ref(), &[-1]);
+    }
+
+    #[test]
+    fn out_of_range_arguments_are_errors() {
+        let module = assemble(ADD);
+        for argument in ["-1", "4294967296"] {
+            let arguments = [argument.to_string(), "0".to_string()];
+            assert_eq!(
+                super::run_module(&module, &arguments).unwrap_err().to_string(),
+                format!("argument {} is out of range for type u32", argument)
+            );
+        }
+    }
+
+    #[test]
+    fn incorrect_argument_count_is_error() {
+        let module = assemble(ADD);
+        assert!(super::run_module(&module, &["1".to_string()]).is_err());
+    }
+
+    #[test]
+    fn module_is_disassembled() {
+        let text = super::disassemble_module(&assemble(ADD)).unwrap();
+        assert!(text.contains("iadd"));
+        assert!(text.contains("export \"main\""));
+    }
+
+    #[test]
+    fn includes_are_errors() {
+        let input = super::preprocess(".include \"other.sailasm\"\n");
+        assert_eq!(input.errors().len(), 1);
+    }
+}
@@ -1,11 +1,13 @@
 //! Provides functions for assembling SAILAR modules given an abstract syntax tree.
 
 use crate::ast;
+use crate::diagnostics::Label;
 use crate::parser;
-use sailar::binary;
-use sailar::binary::instruction::{self, Instruction};
-use sailar::binary::record;
-use sailar::binary::Builder;
+use sailar::builder::Builder;
+use sailar::index;
+use sailar::instruction::{self, Instruction};
+use sailar::record;
+use sailar::signature;
 use sailar::versioning;
 use std::borrow::Cow;
 use std::collections::hash_map;
@@ -30,10 +32,22 @@ pub enum ErrorKind {
     DuplicateRegisterDefinition(Box<str>),
     #[error("expected {expected} temporary registers to be introduced, but got {actual}")]
     TemporaryRegisterCountMismatch { expected: usize, actual: usize },
+    #[error("type of temporary register cannot be inferred")]
+    AmbiguousTemporaryRegisterType,
+    #[error("type of temporary register was inferred to be {0}, but no corresponding type signature is defined")]
+    UndefinedInferredType(signature::Type),
     #[error("invalid integer value: {0}")]
     InvalidIntegerValue(std::num::ParseIntError),
     #[error("non-foreign function definition defines {0} entry blocks, but only 1 is currently supported")]
     UnsupportedFunctionEntryCount(usize),
+    #[error("foreign function bodies are not yet supported")]
+    UnsupportedForeignFunctionBody,
+    #[error("array directives are not yet supported")]
+    UnsupportedArrayDirective,
+    #[error("instantiation of function imports is not yet supported")]
+    UnsupportedFunctionImport,
+    #[error("invalid module version number: {0}")]
+    InvalidModuleVersionNumber(sailar::num::IntegerEncodingError),
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -41,6 +55,7 @@ pub enum ErrorKind {
 pub struct Error {
     kind: ErrorKind,
     location: Option<ast::LocationRange>,
+    labels: Vec<Label>,
 }
 
 impl Error {
@@ -48,6 +63,7 @@ impl Error {
         Self {
             kind: kind.into(),
             location,
+            labels: Vec::new(),
         }
     }
 
@@ -64,9 +80,21 @@ impl Error {
     pub fn location(&self) -> Option<&ast::LocationRange> {
         self.location.as_ref()
     }
+
+    /// Attaches a secondary location to this error.
+    pub fn with_label<M: Into<Cow<'static, str>>>(mut self, location: ast::LocationRange, message: M) -> Self {
+        self.labels.push(Label::new(location, message));
+        self
+    }
+
+    /// Secondary locations in the source code related to this error.
+    #[inline]
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug)]
 enum FormatVersion {
     Unspecified,
     MajorOnly(u8),
@@ -79,7 +107,7 @@ impl TryFrom<FormatVersion> for versioning::Format {
 
     fn try_from(version: FormatVersion) -> Result<Self, Error> {
         match version {
-            FormatVersion::Unspecified => Ok(*versioning::SupportedFormat::MINIMUM),
+            FormatVersion::Unspecified => Ok(*versioning::SupportedFormat::CURRENT),
             FormatVersion::MajorOnly(major) => Ok(Self::new(major, 0)),
             FormatVersion::MinorOnly(_) => Err(Error::new(ErrorKind::MissingMajorFormatVersion, None)),
             FormatVersion::Full(full) => Ok(full),
@@ -90,19 +118,22 @@ impl TryFrom<FormatVersion> for versioning::Format {
 /// Ensures that no symbols are defined more than once.
 #[derive(Default)]
 struct SymbolSet<'s> {
-    lookup: rustc_hash::FxHashSet<&'s sailar::Id>,
+    lookup: rustc_hash::FxHashMap<&'s sailar::identifier::Id, ast::LocationRange>,
 }
 
 impl<'s> SymbolSet<'s> {
     fn insert(&mut self, symbol: &ast::Symbol<'s>) -> Result<(), Error> {
         let name = symbol.item();
-        if self.lookup.insert(name) {
-            Ok(())
-        } else {
-            Err(Error::with_location(
+        match self.lookup.entry(name) {
+            hash_map::Entry::Vacant(vacant) => {
+                vacant.insert(symbol.location().clone());
+                Ok(())
+            }
+            hash_map::Entry::Occupied(occupied) => Err(Error::with_location(
                 ErrorKind::DuplicateSymbolDefinition(Box::from(name.as_str())),
                 symbol.location().clone(),
-            ))
+            )
+            .with_label(occupied.get().clone(), "first defined here")),
         }
     }
 }
@@ -141,6 +172,12 @@ impl NamedItem for &ast::FunctionDefinition<'_> {
     }
 }
 
+impl NamedItem for &ast::DefinitionOrImport<'_> {
+    fn item_name() -> &'static str {
+        "function instantiation"
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct UnresolvedReferenceError {
     item_name: &'static str,
@@ -162,7 +199,7 @@ impl std::error::Error for UnresolvedReferenceError {}
 
 struct SymbolMap<'s, T> {
     items: Vec<T>,
-    lookup: rustc_hash::FxHashMap<&'s sailar::Id, usize>,
+    lookup: rustc_hash::FxHashMap<&'s sailar::identifier::Id, usize>,
 }
 
 impl<'s, T> SymbolMap<'s, T> {
@@ -176,7 +213,7 @@ impl<'s, T> SymbolMap<'s, T> {
         self.items.len()
     }
 
-    fn insert(&mut self, symbol: Option<&'s sailar::Id>, item: T) -> usize {
+    fn insert(&mut self, symbol: Option<&'s sailar::identifier::Id>, item: T) -> usize {
         let index = self.push(item);
         if let Some(symbol) = symbol {
             self.lookup.insert(symbol, index);
@@ -188,7 +225,7 @@ impl<'s, T> SymbolMap<'s, T> {
         self.insert(symbol.map(|s| *s.item()), item)
     }
 
-    fn get_index_from_symbol(&self, symbol: &'s sailar::Id) -> Option<usize> {
+    fn get_index_from_symbol(&self, symbol: &'s sailar::identifier::Id) -> Option<usize> {
         self.lookup.get(symbol).copied()
     }
 
@@ -246,11 +283,52 @@ struct TypeSignatureAssembler<'t, 's> {
 
 type TypeSignatureMap<'t, 's> = SymbolMap<'s, TypeSignatureAssembler<'t, 's>>;
 
+/// The kind of record produced by a directive.
+#[derive(Clone, Copy, Debug)]
+enum RecordKind {
+    ModuleIdentifier,
+    EntryPoint,
+    NoInline,
+    Identifier,
+    Data,
+    TypeSignature,
+    FunctionSignature,
+    CodeBlock,
+    FunctionTemplate,
+    Function,
+}
+
+/// Stores assembled records by kind, since records of each kind are assembled together but must be written in the same
+/// order as the directives that produced them.
+#[derive(Default)]
+struct AssembledRecords<'t>([std::collections::VecDeque<record::Record<'t>>; 10]);
+
+impl<'t> AssembledRecords<'t> {
+    fn push<R: Into<record::Record<'t>>>(&mut self, kind: RecordKind, record: R) {
+        self.0[kind as usize].push_back(record.into());
+    }
+
+    fn into_builder(mut self, format_version: versioning::SupportedFormat, order: &[RecordKind]) -> Builder<'t> {
+        let mut builder = Builder::with_format_version(format_version);
+        for &kind in order.iter() {
+            if let Some(record) = self.0[kind as usize].pop_front() {
+                builder.add_record(record);
+            }
+        }
+        builder
+    }
+}
+
 struct Directives<'t, 's> {
     format_version: FormatVersion,
-    module_identifier: Option<(&'t sailar::Id, Box<[usize]>)>,
+    /// The locations of the directives specifying the major and minor format version numbers.
+    format_version_locations: [Option<&'t ast::LocationRange>; 2],
+    module_identifier: Option<(&'t sailar::identifier::Id, &'t [u32], &'t ast::LocationRange)>,
+    record_order: Vec<RecordKind>,
+    entry_point: Option<(&'t ast::Reference<'s>, &'t ast::LocationRange)>,
+    no_inline_functions: Vec<&'t ast::Reference<'s>>,
     symbols: SymbolSet<'s>,
-    identifiers: SymbolMap<'s, &'t sailar::Id>,
+    identifiers: SymbolMap<'s, &'t sailar::identifier::Id>,
     #[allow(clippy::borrowed_box)]
     data_arrays: SymbolMap<'s, &'t Box<[u8]>>,
     type_signatures: TypeSignatureMap<'t, 's>,
@@ -264,7 +342,11 @@ struct Directives<'t, 's> {
 fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Output<'s>) -> Directives<'t, 's> {
     let mut directives = Directives {
         format_version: FormatVersion::Unspecified,
+        format_version_locations: [None; 2],
         module_identifier: None,
+        record_order: Vec::default(),
+        entry_point: None,
+        no_inline_functions: Vec::default(),
         symbols: Default::default(),
         identifiers: Default::default(),
         data_arrays: Default::default(),
@@ -285,40 +367,79 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
 
     for directive in input.tree().iter() {
         match directive.item() {
-            ast::Directive::Array => todo!("array record generation is not yet supported"),
+            ast::Directive::Array => errors.push(Error::with_location(
+                ErrorKind::UnsupportedArrayDirective,
+                directive.location().clone(),
+            )),
             ast::Directive::Format(ast::FormatVersionKind::Major, major) => match directives.format_version {
-                FormatVersion::Unspecified => directives.format_version = FormatVersion::MajorOnly(*major),
+                FormatVersion::Unspecified => {
+                    directives.format_version = FormatVersion::MajorOnly(*major);
+                    directives.format_version_locations[0] = Some(directive.location());
+                }
                 FormatVersion::MinorOnly(minor) => {
-                    directives.format_version = FormatVersion::Full(versioning::Format::new(*major, minor))
+                    directives.format_version = FormatVersion::Full(versioning::Format::new(*major, minor));
+                    directives.format_version_locations[0] = Some(directive.location());
+                }
+                FormatVersion::MajorOnly(_) | FormatVersion::Full(_) => {
+                    let mut error = Error::with_location(
+                        ErrorKind::DuplicateFormatVersion(ast::FormatVersionKind::Major),
+                        directive.location().clone(),
+                    );
+
+                    if let Some(first) = directives.format_version_locations[0] {
+                        error = error.with_label(first.clone(), "first specified here");
+                    }
+
+                    errors.push(error);
                 }
-                FormatVersion::MajorOnly(_) | FormatVersion::Full(_) => errors.push(Error::with_location(
-                    ErrorKind::DuplicateFormatVersion(ast::FormatVersionKind::Major),
-                    directive.location().clone(),
-                )),
             },
             ast::Directive::Format(ast::FormatVersionKind::Minor, minor) => match directives.format_version {
-                FormatVersion::Unspecified => directives.format_version = FormatVersion::MinorOnly(*minor),
+                FormatVersion::Unspecified => {
+                    directives.format_version = FormatVersion::MinorOnly(*minor);
+                    directives.format_version_locations[1] = Some(directive.location());
+                }
                 FormatVersion::MajorOnly(major) => {
-                    directives.format_version = FormatVersion::Full(versioning::Format::new(major, *minor))
+                    directives.format_version = FormatVersion::Full(versioning::Format::new(major, *minor));
+                    directives.format_version_locations[1] = Some(directive.location());
+                }
+                FormatVersion::MinorOnly(_) | FormatVersion::Full(_) => {
+                    let mut error = Error::with_location(
+                        ErrorKind::DuplicateFormatVersion(ast::FormatVersionKind::Minor),
+                        directive.location().clone(),
+                    );
+
+                    if let Some(first) = directives.format_version_locations[1] {
+                        error = error.with_label(first.clone(), "first specified here");
+                    }
+
+                    errors.push(error);
                 }
-                FormatVersion::MinorOnly(_) | FormatVersion::Full(_) => errors.push(Error::with_location(
-                    ErrorKind::DuplicateFormatVersion(ast::FormatVersionKind::Minor),
-                    directive.location().clone(),
-                )),
             },
             ast::Directive::Metadata(metadata) => match metadata {
                 ast::Metadata::Identifier(name, version_numbers) => match directives.module_identifier {
-                    Some(_) => errors.push(Error::with_location(
-                        ErrorKind::DuplicateMetadataField("id"),
-                        directive.location().clone(),
-                    )),
+                    Some((_, _, first)) => errors.push(
+                        Error::with_location(ErrorKind::DuplicateMetadataField("id"), directive.location().clone())
+                            .with_label(first.clone(), "first defined here"),
+                    ),
+                    None => {
+                        directives.module_identifier = Some((name.item(), version_numbers, directive.location()));
+                        directives.record_order.push(RecordKind::ModuleIdentifier);
+                    }
+                },
+                ast::Metadata::EntryPoint(entry_point) => match directives.entry_point {
+                    Some((_, first)) => errors.push(
+                        Error::with_location(ErrorKind::DuplicateMetadataField("main"), directive.location().clone())
+                            .with_label(first.clone(), "first defined here"),
+                    ),
                     None => {
-                        directives.module_identifier = Some((
-                            name.item(),
-                            version_numbers.iter().map(|v| usize::try_from(*v).unwrap()).collect(),
-                        ))
+                        directives.entry_point = Some((entry_point, directive.location()));
+                        directives.record_order.push(RecordKind::EntryPoint);
                     }
                 },
+                ast::Metadata::NoInline(function) => {
+                    directives.no_inline_functions.push(function);
+                    directives.record_order.push(RecordKind::NoInline);
+                }
             },
             ast::Directive::Identifier(symbol, identifier) => {
                 if let Some(symbol) = symbol {
@@ -326,6 +447,7 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
                 }
 
                 directives.identifiers.insert_with_symbol(symbol.as_ref(), identifier.item());
+                directives.record_order.push(RecordKind::Identifier);
             }
             ast::Directive::Data(symbol, data) => {
                 if let Some(symbol) = symbol {
@@ -333,6 +455,7 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
                 }
 
                 directives.data_arrays.insert_with_symbol(symbol.as_ref(), data.item());
+                directives.record_order.push(RecordKind::Data);
             }
             ast::Directive::Signature(label, signature) => {
                 if let Some(symbol) = label {
@@ -355,11 +478,13 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
                                 },
                             },
                         );
+                        directives.record_order.push(RecordKind::TypeSignature);
                     }
                     ast::Signature::Function(function_signature) => {
                         directives
                             .function_signatures
                             .insert_with_symbol(label.as_ref(), function_signature);
+                        directives.record_order.push(RecordKind::FunctionSignature);
                     }
                 }
             }
@@ -369,6 +494,7 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
                 }
 
                 directives.code_blocks.insert_with_symbol(symbol.as_ref(), code);
+                directives.record_order.push(RecordKind::CodeBlock);
             }
             ast::Directive::FunctionDefinition(symbol, definition) => {
                 if let Some(symbol) = symbol {
@@ -378,6 +504,7 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
                 directives
                     .function_definitions
                     .insert_with_symbol(symbol.as_ref(), definition);
+                directives.record_order.push(RecordKind::FunctionTemplate);
             }
             ast::Directive::FunctionInstantiation(symbol, instantiation) => {
                 if let Some(symbol) = symbol {
@@ -387,6 +514,7 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
                 directives
                     .function_instantiations
                     .insert_with_symbol(symbol.as_ref(), instantiation);
+                directives.record_order.push(RecordKind::Function);
             }
         }
     }
@@ -394,6 +522,18 @@ fn get_record_definitions<'t, 's>(errors: &mut Vec<Error>, input: &'t parser::Ou
     directives
 }
 
+/// The type of a temporary register, as determined from the instruction that defines it.
+#[derive(Clone)]
+enum InferredType {
+    Known(index::TypeSignature),
+    /// The type was inferred, but no type signature with the inferred type was defined.
+    Undefined(signature::Type),
+    /// A type annotation is required, since the instruction does not provide enough information to determine the type.
+    Ambiguous,
+    /// The type could not be determined due to an error that is reported elsewhere.
+    Unknown,
+}
+
 #[repr(transparent)]
 struct RegisterSymbol<'t, 's>(&'t ast::Symbol<'s>);
 
@@ -413,8 +553,8 @@ impl std::hash::Hash for RegisterSymbol<'_, '_> {
 
 #[derive(Default)]
 struct RegisterMap<'t, 's> {
-    types: Vec<binary::index::TypeSignature>,
-    lookup: rustc_hash::FxHashMap<RegisterSymbol<'t, 's>, binary::index::Register>,
+    types: Vec<index::TypeSignature>,
+    lookup: rustc_hash::FxHashMap<RegisterSymbol<'t, 's>, index::Register>,
 }
 
 impl<'t, 's> RegisterMap<'t, 's> {
@@ -427,7 +567,7 @@ impl<'t, 's> RegisterMap<'t, 's> {
         self.types.len()
     }
 
-    fn get_register_index(&self, reference: &'t ast::Reference<'s>) -> Result<binary::index::Register, Error> {
+    fn get_register_index(&self, reference: &'t ast::Reference<'s>) -> Result<index::Register, Error> {
         // TODO: Remove duplicated code with SymbolMap.
         let location;
         let result = match reference {
@@ -436,7 +576,7 @@ impl<'t, 's> RegisterMap<'t, 's> {
                 usize::try_from(*index.item())
                     .ok()
                     .filter(|i| *i < self.len())
-                    .map(binary::index::Register::from)
+                    .map(index::Register::from)
             }
             ast::Reference::Label(label) => {
                 location = label.location();
@@ -461,9 +601,9 @@ impl<'t, 's> RegisterMap<'t, 's> {
     fn try_insert(
         &mut self,
         symbol: Option<&'t ast::Symbol<'s>>,
-        value_type: binary::index::TypeSignature,
-    ) -> Result<binary::index::Register, Error> {
-        let index = binary::index::Register::from(self.types.len());
+        value_type: index::TypeSignature,
+    ) -> Result<index::Register, Error> {
+        let index = index::Register::from(self.types.len());
         if let Some(symbol) = symbol {
             match self.lookup.entry(RegisterSymbol(symbol)) {
                 hash_map::Entry::Vacant(vacant) => {
@@ -472,8 +612,9 @@ impl<'t, 's> RegisterMap<'t, 's> {
                 hash_map::Entry::Occupied(occupied) => {
                     return Err(Error::with_location(
                         ErrorKind::DuplicateRegisterDefinition(Box::from(symbol.item().as_str())),
-                        occupied.key().0.location().clone(),
-                    ));
+                        symbol.location().clone(),
+                    )
+                    .with_label(occupied.key().0.location().clone(), "first defined here"));
                 }
             }
         }
@@ -482,45 +623,403 @@ impl<'t, 's> RegisterMap<'t, 's> {
         Ok(index)
     }
 
-    fn get_register_types(&self) -> Box<[binary::index::TypeSignature]> {
-        self.types.clone().into_boxed_slice()
+    /// Gets the types of the input registers followed by the types of the temporary registers.
+    fn register_types(&self) -> &[index::TypeSignature] {
+        &self.types
+    }
+}
+
+fn get_instruction_value<'t, 's>(
+    register_lookup: &RegisterMap<'t, 's>,
+    value: &'t ast::Value<'s>,
+) -> Result<instruction::Value, Error> {
+    use crate::lexer::IntegerLiteralType;
+
+    match value {
+        ast::Value::LiteralInteger(integer) => {
+            let digits = integer.item();
+
+            macro_rules! convert_integer {
+                ($integer_type: ty) => {
+                    <$integer_type>::try_from(digits)
+                        .map(|v| v.into())
+                        .map_err(|e| Error::with_location(ErrorKind::InvalidIntegerValue(e), integer.location().clone()))
+                };
+            }
+
+            match digits.integer_type() {
+                IntegerLiteralType::I8 => convert_integer!(u8),
+                IntegerLiteralType::I16 => convert_integer!(u16),
+                IntegerLiteralType::Unspecified | IntegerLiteralType::I32 => convert_integer!(u32),
+                IntegerLiteralType::I64 => convert_integer!(u64),
+            }
+        }
+        ast::Value::Register(register) => register_lookup
+            .get_register_index(register)
+            .map(instruction::Value::IndexedRegister),
+    }
+}
+
+fn get_many_instruction_values<'t, 's>(
+    register_lookup: &RegisterMap<'t, 's>,
+    errors: &mut Vec<Error>,
+    values: &'t [ast::Value<'s>],
+) -> Box<[instruction::Value]> {
+    let mut converted_values = Vec::with_capacity(values.len());
+    for value in values.iter() {
+        match get_instruction_value(register_lookup, value) {
+            Ok(v) => converted_values.push(v),
+            Err(e) => errors.push(e),
+        }
+    }
+    converted_values.into_boxed_slice()
+}
+
+impl<'t, 's> Directives<'t, 's> {
+    fn get_function_signature(&self, signature: &ast::Reference<'s>) -> Result<&'t ast::FunctionSignature<'s>, Error> {
+        self.function_signatures
+            .get_index_from_reference(signature)
+            .map(|index| self.function_signatures.items[index])
+    }
+
+    /// Gets the signature of the function template that is instantiated, or `None` if the function or its signature could
+    /// not be found.
+    fn get_instantiation_signature(&self, instantiation: usize) -> Option<&'t ast::FunctionSignature<'s>> {
+        match self.function_instantiations.items.get(instantiation)? {
+            ast::DefinitionOrImport::Definition(definition) => {
+                let definition = self.function_definitions.get_index_from_reference(definition).ok()?;
+                self.get_function_signature(self.function_definitions.items[definition].signature())
+                    .ok()
+            }
+            ast::DefinitionOrImport::Import(_) => None,
+        }
+    }
+
+    /// Gets the number of values returned by the function template that is instantiated, or `None` if the function or its
+    /// signature could not be found.
+    fn get_function_return_count(&self, instantiation: usize) -> Option<usize> {
+        self.get_instantiation_signature(instantiation)
+            .map(|signature| signature.return_types().len())
+    }
+
+    /// Infers the types of the temporary registers defined by an instruction, appending them to `inferred`.
+    ///
+    /// The `type_signatures` contain the type signatures of the module, or `None` if a type signature could not be
+    /// assembled.
+    fn infer_temporary_types(
+        &self,
+        register_types: &[index::TypeSignature],
+        type_signatures: &[Option<signature::Type>],
+        instruction: &Instruction,
+        inferred: &mut Vec<InferredType>,
+    ) {
+        let register_type = |value: &instruction::Value| match value {
+            instruction::Value::IndexedRegister(register) => register_types.get(usize::from(*register)).copied(),
+            instruction::Value::Constant(_) => None,
+        };
+
+        let type_structure = |value: &instruction::Value| {
+            register_type(value).and_then(|value_type| type_signatures.get(usize::from(value_type))?.as_ref())
+        };
+
+        let find_type = |value_type: signature::Type| match type_signatures.iter().position(|t| t.as_ref() == Some(&value_type)) {
+            Some(index) => InferredType::Known(index::TypeSignature::from(index)),
+            None => InferredType::Undefined(value_type),
+        };
+
+        // Integer constants are untyped, so the type is inferred from the first register
+        let operand_type = |x, y| {
+            register_type(x)
+                .or_else(|| register_type(y))
+                .map_or(InferredType::Ambiguous, InferredType::Known)
+        };
+
+        let pointee_type = |address| match type_structure(address) {
+            Some(signature::Type::RawPtr(Some(pointee))) => InferredType::Known(*pointee),
+            _ => InferredType::Ambiguous,
+        };
+
+        let vector_type = |vector| match type_structure(vector) {
+            Some(signature::Type::Vector(vector_type)) => Some(*vector_type),
+            _ => None,
+        };
+
+        let mut return_types = |signature: &ast::FunctionSignature<'s>| {
+            for return_type in signature.return_types().iter() {
+                inferred.push(match self.type_signatures.get_index_from_reference(return_type) {
+                    Ok(index) => InferredType::Known(index::TypeSignature::from(index)),
+                    Err(_) => InferredType::Unknown,
+                });
+            }
+        };
+
+        match instruction {
+            Instruction::IAdd(arithmetic) | Instruction::ISub(arithmetic) => {
+                let result_type = operand_type(arithmetic.x_value(), arithmetic.y_value());
+                if arithmetic.overflow_behavior() == instruction::OverflowBehavior::Flag {
+                    // The overflow flag is given the same type as the result
+                    inferred.push(result_type.clone());
+                }
+                inferred.push(result_type);
+            }
+            Instruction::Cmp(comparison) => inferred.push(operand_type(comparison.x_value(), comparison.y_value())),
+            Instruction::Call(callee, _) => {
+                if let Some(signature) = self.get_instantiation_signature(usize::from(*callee)) {
+                    return_types(signature);
+                }
+            }
+            Instruction::ThreadJoin(signature, _) => {
+                if let Some(signature) = self.function_signatures.items.get(usize::from(*signature)) {
+                    return_types(signature);
+                }
+            }
+            Instruction::AtomicLoad(load) => inferred.push(pointee_type(load.address())),
+            Instruction::AtomicRmw(operation) => inferred.push(pointee_type(operation.address())),
+            Instruction::AtomicCmpXchg(operation) => {
+                // The flag indicating if the value was replaced is given the same type as the value
+                let value_type = pointee_type(operation.address());
+                inferred.push(value_type.clone());
+                inferred.push(value_type);
+            }
+            Instruction::VExtract(vector, _) => inferred.push(match vector_type(vector) {
+                Some(vector_type) => find_type(signature::Type::from(vector_type.element_type())),
+                None => InferredType::Ambiguous,
+            }),
+            Instruction::VInsert(insert) => {
+                inferred.push(register_type(insert.vector()).map_or(InferredType::Ambiguous, InferredType::Known))
+            }
+            Instruction::VShuffle(shuffle) => {
+                let lane_count = u8::try_from(shuffle.lanes().len()).ok().and_then(std::num::NonZeroU8::new);
+                inferred.push(match (vector_type(shuffle.x_value()), lane_count) {
+                    (Some(vector_type), Some(lane_count)) => find_type(signature::Type::Vector(signature::VectorType::new(
+                        vector_type.element_type(),
+                        lane_count,
+                    ))),
+                    _ => InferredType::Ambiguous,
+                });
+            }
+            // The size of thread handles and the number of lanes of splatted vectors cannot be determined
+            _ => (),
+        }
+    }
+
+    /// Converts an instruction, returning the number of temporary registers that it is expected to define if it is known.
+    fn assemble_instruction(
+        &self,
+        register_lookup: &RegisterMap<'t, 's>,
+        errors: &mut Vec<Error>,
+        instruction: &'t ast::Instruction<'s>,
+    ) -> Result<(Instruction, Option<usize>), Error> {
+        let value = |value: &'t ast::Value<'s>| get_instruction_value(register_lookup, value);
+        let code_block = |block| self.code_blocks.get_index_from_reference(block).map(index::CodeBlock::from);
+        let function = |callee| self.function_instantiations.get_index_from_reference(callee);
+
+        let integer_arithmetic = |overflow_behavior: &ast::OverflowBehavior, x, y| -> Result<_, Error> {
+            let result_count = if *overflow_behavior == ast::OverflowBehavior::Flag {
+                2
+            } else {
+                1
+            };
+            let arithmetic = instruction::IntegerArithmetic::new(*overflow_behavior, value(x)?, value(y)?);
+            Ok((Box::new(arithmetic), Some(result_count)))
+        };
+
+        Ok(match instruction {
+            ast::Instruction::Nop => (Instruction::Nop, Some(0)),
+            ast::Instruction::Break => (Instruction::Break, Some(0)),
+            ast::Instruction::Ret(values) => (
+                Instruction::Return(get_many_instruction_values(register_lookup, errors, values)),
+                Some(0),
+            ),
+            ast::Instruction::Br(target, arguments) => (
+                Instruction::Br(
+                    code_block(target)?,
+                    get_many_instruction_values(register_lookup, errors, arguments),
+                ),
+                Some(0),
+            ),
+            ast::Instruction::BrIf {
+                condition,
+                true_branch,
+                false_branch,
+                arguments,
+            } => (
+                Instruction::BrIf(Box::new(instruction::BranchIf::new(
+                    value(condition)?,
+                    code_block(true_branch)?,
+                    code_block(false_branch)?,
+                    get_many_instruction_values(register_lookup, errors, arguments),
+                ))),
+                Some(0),
+            ),
+            ast::Instruction::Call(callee, arguments) => {
+                let callee = function(callee)?;
+                (
+                    Instruction::Call(
+                        index::Function::from(callee),
+                        get_many_instruction_values(register_lookup, errors, arguments),
+                    ),
+                    self.get_function_return_count(callee),
+                )
+            }
+            ast::Instruction::IAdd(overflow_behavior, x, y) => {
+                let (arithmetic, result_count) = integer_arithmetic(overflow_behavior, x, y)?;
+                (Instruction::IAdd(arithmetic), result_count)
+            }
+            ast::Instruction::ISub(overflow_behavior, x, y) => {
+                let (arithmetic, result_count) = integer_arithmetic(overflow_behavior, x, y)?;
+                (Instruction::ISub(arithmetic), result_count)
+            }
+            ast::Instruction::Cmp(x, kind, y) => (
+                Instruction::Cmp(Box::new(instruction::Comparison::new(*kind, value(x)?, value(y)?))),
+                Some(1),
+            ),
+            ast::Instruction::ThreadSpawn(callee, arguments) => (
+                Instruction::ThreadSpawn(
+                    index::Function::from(function(callee)?),
+                    get_many_instruction_values(register_lookup, errors, arguments),
+                ),
+                Some(1),
+            ),
+            ast::Instruction::ThreadJoin(signature, thread) => {
+                let return_count = self.get_function_signature(signature)?.return_types().len();
+                let signature = self.function_signatures.get_index_from_reference(signature)?;
+                (
+                    Instruction::ThreadJoin(index::FunctionSignature::from(signature), value(thread)?),
+                    Some(return_count),
+                )
+            }
+            ast::Instruction::AtomicLoad(ordering, address) => (
+                Instruction::AtomicLoad(Box::new(instruction::AtomicLoad::new(*ordering, value(address)?))),
+                Some(1),
+            ),
+            ast::Instruction::AtomicStore(ordering, address, stored) => (
+                Instruction::AtomicStore(Box::new(instruction::AtomicStore::new(
+                    *ordering,
+                    value(address)?,
+                    value(stored)?,
+                ))),
+                Some(0),
+            ),
+            ast::Instruction::AtomicRmw(operation, ordering, address, operand) => (
+                Instruction::AtomicRmw(Box::new(instruction::AtomicReadModifyWrite::new(
+                    *operation,
+                    *ordering,
+                    value(address)?,
+                    value(operand)?,
+                ))),
+                Some(1),
+            ),
+            ast::Instruction::AtomicCmpXchg {
+                success_ordering,
+                failure_ordering,
+                address,
+                expected,
+                replacement,
+            } => (
+                Instruction::AtomicCmpXchg(Box::new(instruction::AtomicCompareExchange::new(
+                    *success_ordering,
+                    *failure_ordering,
+                    value(address)?,
+                    value(expected)?,
+                    value(replacement)?,
+                ))),
+                Some(2),
+            ),
+            ast::Instruction::Fence(ordering) => (Instruction::Fence(*ordering), Some(0)),
+            ast::Instruction::VSplat(element) => (Instruction::VSplat(value(element)?), Some(1)),
+            ast::Instruction::VExtract(vector, lane) => (Instruction::VExtract(value(vector)?, *lane), Some(1)),
+            ast::Instruction::VInsert(vector, lane, element) => (
+                Instruction::VInsert(Box::new(instruction::VectorInsert::new(
+                    value(vector)?,
+                    value(element)?,
+                    *lane,
+                ))),
+                Some(1),
+            ),
+            ast::Instruction::VShuffle(x, y, lanes) => (
+                Instruction::VShuffle(Box::new(instruction::VectorShuffle::new(value(x)?, value(y)?, lanes.clone()))),
+                Some(1),
+            ),
+        })
     }
 }
 
 /// The second pass of the assembler, produces record definitions in the module for every directive.
-fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directives<'t, 's>) -> Builder<'t> {
+fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, directives: Directives<'t, 's>) -> Builder<'t> {
+    let [major_format_location, minor_format_location] = directives.format_version_locations;
+
     let format_version = match versioning::Format::try_from(directives.format_version) {
         Ok(version) => version,
         Err(e) => {
-            errors.push(e);
-            *versioning::SupportedFormat::MINIMUM
+            errors.push(Error::new(e.kind, minor_format_location.cloned()));
+            *versioning::SupportedFormat::CURRENT
         }
     };
 
     let actual_format_version = match versioning::SupportedFormat::try_from(format_version) {
         Ok(version) => version,
         Err(e) => {
-            errors.push(Error::new(e, None));
-            versioning::SupportedFormat::MINIMUM
+            let mut error = Error::new(e, major_format_location.cloned());
+            if let (Some(_), Some(minor)) = (major_format_location, minor_format_location) {
+                error = error.with_label(minor.clone(), "minor version specified here");
+            }
+
+            errors.push(error);
+            versioning::SupportedFormat::CURRENT
         }
     };
 
-    let mut builder = Builder::with_format_version(actual_format_version);
+    let mut records = AssembledRecords::default();
 
-    if let Some((name, version_numbers)) = directives.module_identifier.take() {
-        builder.add_record(record::Record::MetadataField(record::MetadataField::ModuleIdentifier(
-            record::ModuleIdentifier::new(Cow::Borrowed(name), sailar::helper::borrow::CowBox::Boxed(version_numbers)),
-        )))
+    if let Some((name, version_numbers, location)) = directives.module_identifier {
+        let mut version = Vec::with_capacity(version_numbers.len());
+        for number in version_numbers.iter().copied() {
+            match sailar::num::VarU28::try_from(number) {
+                Ok(number) => version.push(number),
+                Err(e) => errors.push(Error::with_location(
+                    ErrorKind::InvalidModuleVersionNumber(e),
+                    location.clone(),
+                )),
+            }
+        }
+
+        records.push(
+            RecordKind::ModuleIdentifier,
+            record::MetadataField::ModuleIdentifier(record::ModuleIdentifier::new(Cow::Borrowed(name), version.into())),
+        );
     }
 
-    for id in directives.identifiers.iter() {
-        builder.add_record(record::Record::Identifier(Cow::Borrowed(id)))
+    if let Some((entry_point, _)) = directives.entry_point {
+        match directives.function_instantiations.get_index_from_reference(entry_point) {
+            Ok(index) => records.push(
+                RecordKind::EntryPoint,
+                record::MetadataField::EntryPoint(index::Function::from(index)),
+            ),
+            Err(e) => errors.push(e),
+        }
     }
 
-    for data in directives.data_arrays.iter() {
-        builder.add_record(record::Record::Data(Cow::Borrowed(record::DataArray::from_bytes(data))));
+    for function in directives.no_inline_functions.iter().copied() {
+        match directives.function_instantiations.get_index_from_reference(function) {
+            Ok(index) => records.push(
+                RecordKind::NoInline,
+                record::MetadataField::NoInline(index::Function::from(index)),
+            ),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    for id in directives.identifiers.iter().copied() {
+        records.push(RecordKind::Identifier, record::Record::Identifier(Cow::Borrowed(id)))
     }
 
+    for &data in directives.data_arrays.iter() {
+        records.push(RecordKind::Data, record::Record::Data(Cow::Borrowed(data.as_ref())));
+    }
+
+    let mut type_signature_structures = Vec::with_capacity(directives.type_signatures.len());
+
     for (index, assembler) in (0u32..).zip(directives.type_signatures.iter()) {
         for reference in assembler.references.iter().copied() {
             match reference {
@@ -543,37 +1042,43 @@ fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directiv
         }
 
         let signature = match assembler.signature {
-            ast::TypeSignature::U8 => binary::signature::Type::U8,
-            ast::TypeSignature::S8 => binary::signature::Type::S8,
-            ast::TypeSignature::U16 => binary::signature::Type::U16,
-            ast::TypeSignature::S16 => binary::signature::Type::S16,
-            ast::TypeSignature::U32 => binary::signature::Type::U32,
-            ast::TypeSignature::S32 => binary::signature::Type::S32,
-            ast::TypeSignature::U64 => binary::signature::Type::U64,
-            ast::TypeSignature::S64 => binary::signature::Type::S64,
-            ast::TypeSignature::UAddr => binary::signature::Type::UAddr,
-            ast::TypeSignature::SAddr => binary::signature::Type::SAddr,
-            ast::TypeSignature::F32 => binary::signature::Type::F32,
-            ast::TypeSignature::F64 => binary::signature::Type::F64,
-            ast::TypeSignature::VoidPtr => binary::signature::Type::RawPtr(None),
+            ast::TypeSignature::FixedInteger(integer_type) => signature::Type::FixedInteger(*integer_type),
+            ast::TypeSignature::UAddr => signature::Type::UAddr,
+            ast::TypeSignature::SAddr => signature::Type::SAddr,
+            ast::TypeSignature::F32 => signature::Type::F32,
+            ast::TypeSignature::F64 => signature::Type::F64,
+            ast::TypeSignature::VoidPtr => signature::Type::RawPtr(None),
             ast::TypeSignature::RawPtr(pointee) => match directives.type_signatures.get_index_from_reference(pointee) {
-                Ok(index) => binary::signature::Type::RawPtr(Some(binary::index::TypeSignature::from(index))),
+                Ok(index) => signature::Type::RawPtr(Some(index::TypeSignature::from(index))),
                 Err(e) => {
                     errors.push(e);
+                    type_signature_structures.push(None);
                     continue;
                 }
             },
+            ast::TypeSignature::FuncPtr(function_signature) => {
+                match directives.function_signatures.get_index_from_reference(function_signature) {
+                    Ok(index) => signature::Type::FuncPtr(index::FunctionSignature::from(index)),
+                    Err(e) => {
+                        errors.push(e);
+                        type_signature_structures.push(None);
+                        continue;
+                    }
+                }
+            }
+            ast::TypeSignature::Vector(vector_type) => signature::Type::Vector(*vector_type),
         };
 
-        builder.add_record(record::Record::from(signature));
+        type_signature_structures.push(Some(signature.clone()));
+        records.push(RecordKind::TypeSignature, signature);
     }
 
     let get_type_signature_indices =
-        |references: &'t [ast::Reference<'s>], indices: &mut Vec<binary::index::TypeSignature>, errors: &mut Vec<Error>| {
+        |references: &'t [ast::Reference<'s>], indices: &mut Vec<index::TypeSignature>, errors: &mut Vec<Error>| {
             let mut failed = false;
             for r in references.iter() {
                 match directives.type_signatures.get_index_from_reference(r) {
-                    Ok(index) if !failed => indices.push(binary::index::TypeSignature::try_from(index).unwrap()),
+                    Ok(index) if !failed => indices.push(index::TypeSignature::from(index)),
                     Ok(_) => (),
                     Err(e) => {
                         errors.push(e);
@@ -589,25 +1094,43 @@ fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directiv
         let mut indices = Vec::with_capacity(return_types.len() + parameter_types.len());
         get_type_signature_indices(return_types, &mut indices, errors);
         get_type_signature_indices(parameter_types, &mut indices, errors);
-        builder.add_record(record::Record::from(binary::signature::Function::from_boxed_slice(
-            indices.into_boxed_slice(),
-            return_types.len(),
-        )));
+        records.push(
+            RecordKind::FunctionSignature,
+            signature::Function::from_types(indices.into(), return_types.len()),
+        );
     }
 
     let mut register_lookup = RegisterMap::default();
     let mut instruction_buffer = Vec::default();
 
+    /// Defines registers in the current code block, using the `inferred` types for registers without a type annotation.
     fn define_typed_registers<'t, 's>(
         register_lookup: &mut RegisterMap<'t, 's>,
         type_signatures: &TypeSignatureMap<'t, 's>,
         errors: &mut Vec<Error>,
         registers: &'t [ast::Located<ast::TypedRegister<'s>>],
+        inferred: &[InferredType],
     ) {
-        for r in registers.iter() {
-            match type_signatures.get_index_from_reference(r.item().value_type()) {
+        for (position, r) in registers.iter().enumerate() {
+            let value_type = match (r.item().value_type(), inferred.get(position)) {
+                (Some(value_type), _) => type_signatures
+                    .get_index_from_reference(value_type)
+                    .map(index::TypeSignature::from),
+                (None, Some(InferredType::Known(value_type))) => Ok(*value_type),
+                (None, Some(InferredType::Undefined(value_type))) => Err(Error::with_location(
+                    ErrorKind::UndefinedInferredType(value_type.clone()),
+                    r.location().clone(),
+                )),
+                (None, Some(InferredType::Ambiguous) | None) => Err(Error::with_location(
+                    ErrorKind::AmbiguousTemporaryRegisterType,
+                    r.location().clone(),
+                )),
+                (None, Some(InferredType::Unknown)) => continue,
+            };
+
+            match value_type {
                 Ok(value_type) => {
-                    if let Err(e) = register_lookup.try_insert(r.item().symbol(), value_type.into()) {
+                    if let Err(e) = register_lookup.try_insert(r.item().symbol(), value_type) {
                         errors.push(e);
                     }
                 }
@@ -616,53 +1139,9 @@ fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directiv
         }
     }
 
-    fn get_instruction_value<'t, 's>(
-        register_lookup: &RegisterMap<'t, 's>,
-        value: &'t ast::Value<'s>,
-    ) -> Result<instruction::Value, Error> {
-        use crate::lexer::IntegerLiteralType;
-
-        match value {
-            ast::Value::LiteralInteger(integer) => {
-                let digits = integer.item();
-
-                macro_rules! convert_integer {
-                    ($integer_type: ty) => {
-                        <$integer_type>::try_from(digits)
-                            .map(|v| v.into())
-                            .map_err(|e| Error::with_location(ErrorKind::InvalidIntegerValue(e), integer.location().clone()))
-                    };
-                }
-
-                match digits.integer_type() {
-                    IntegerLiteralType::I8 => convert_integer!(u8),
-                    IntegerLiteralType::I16 => convert_integer!(u16),
-                    IntegerLiteralType::Unspecified | IntegerLiteralType::I32 => convert_integer!(u32),
-                    IntegerLiteralType::I64 => convert_integer!(u64),
-                }
-            }
-            ast::Value::Register(register) => register_lookup
-                .get_register_index(register)
-                .map(instruction::Value::IndexedRegister),
-        }
-    }
-
-    fn get_many_instruction_values<'t, 's>(
-        register_lookup: &RegisterMap<'t, 's>,
-        errors: &mut Vec<Error>,
-        values: &'t [ast::Value<'s>],
-    ) -> Box<[instruction::Value]> {
-        let mut converted_values = Vec::with_capacity(values.len());
-        for value in values.iter() {
-            match get_instruction_value(register_lookup, value) {
-                Ok(v) => converted_values.push(v),
-                Err(e) => errors.push(e),
-            }
-        }
-        converted_values.into_boxed_slice()
-    }
+    let mut inferred_types = Vec::default();
 
-    for code_block in directives.code_blocks.iter() {
+    for &code_block in directives.code_blocks.iter() {
         register_lookup.clear();
         instruction_buffer.clear();
 
@@ -671,61 +1150,81 @@ fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directiv
             &directives.type_signatures,
             errors,
             code_block.input_registers(),
+            &[],
         );
 
         let input_count = register_lookup.len();
-
-        for result_type in code_block.result_types().iter() {
-            if let Err(e) = directives.type_signatures.get_index_from_reference(result_type) {
-                errors.push(e)
-            }
-        }
-
-        let result_count = register_lookup.len() - input_count;
+        let mut result_types = Vec::with_capacity(code_block.result_types().len());
+        get_type_signature_indices(code_block.result_types(), &mut result_types, errors);
 
         for statement in code_block.statements().iter() {
-            let (instruction, expected_temporary_count) = match statement.instruction().item() {
-                ast::Instruction::Nop => (Instruction::Nop, 0),
-                ast::Instruction::Break => (Instruction::Break, 0),
-                ast::Instruction::Ret(return_values) => (
-                    Instruction::Ret(get_many_instruction_values(&register_lookup, errors, return_values.as_ref())),
-                    0,
-                ),
-            };
+            let (instruction, expected_temporary_count) =
+                match directives.assemble_instruction(&register_lookup, errors, statement.instruction().item()) {
+                    Ok(instruction) => instruction,
+                    Err(e) => {
+                        errors.push(e);
+                        continue;
+                    }
+                };
+
+            inferred_types.clear();
+            if statement.results().iter().any(|r| r.item().value_type().is_none()) {
+                directives.infer_temporary_types(
+                    register_lookup.register_types(),
+                    &type_signature_structures,
+                    &instruction,
+                    &mut inferred_types,
+                );
+            }
 
             let actual_temporary_count = statement.results().len();
-            if expected_temporary_count != actual_temporary_count {
-                errors.push(Error::with_location(
-                    ErrorKind::TemporaryRegisterCountMismatch {
-                        expected: expected_temporary_count,
-                        actual: actual_temporary_count,
-                    },
-                    if statement.results().is_empty() {
-                        statement.instruction().location().clone()
-                    } else {
-                        ast::LocationRange::new(
-                            statement.results().first().unwrap().location().start().clone(),
-                            statement.results().last().unwrap().location().end().clone(),
-                        )
-                    },
-                ));
+            match expected_temporary_count {
+                Some(expected) if expected != actual_temporary_count => {
+                    // Avoid reporting additional errors for registers that do not correspond to any result
+                    inferred_types.resize(actual_temporary_count, InferredType::Unknown);
+                    errors.push(Error::with_location(
+                        ErrorKind::TemporaryRegisterCountMismatch {
+                            expected,
+                            actual: actual_temporary_count,
+                        },
+                        if statement.results().is_empty() {
+                            statement.instruction().location().clone()
+                        } else {
+                            ast::LocationRange::new(
+                                statement.results().first().unwrap().location().start().clone(),
+                                statement.results().last().unwrap().location().end().clone(),
+                            )
+                        },
+                    ))
+                }
+                _ => (),
             }
 
-            define_typed_registers(&mut register_lookup, &directives.type_signatures, errors, statement.results());
+            define_typed_registers(
+                &mut register_lookup,
+                &directives.type_signatures,
+                errors,
+                statement.results(),
+                &inferred_types,
+            );
 
             instruction_buffer.push(instruction);
         }
 
-        builder.add_record(record::Record::from(record::CodeBlock::from_types(
-            register_lookup.get_register_types().into(),
-            input_count,
-            result_count,
-            instruction_buffer.clone().into_boxed_slice().into(),
-        )));
+        let (input_types, temporary_types) = register_lookup.register_types().split_at(input_count);
+        records.push(
+            RecordKind::CodeBlock,
+            record::CodeBlock::new(
+                input_types.to_vec(),
+                result_types,
+                temporary_types.to_vec(),
+                instruction_buffer.clone(),
+            ),
+        );
     }
 
     // TODO: Have lookup for module symbols to prevent duplicates.
-    for definition in directives.function_definitions.iter() {
+    for &definition in directives.function_definitions.iter() {
         let signature = match directives
             .function_signatures
             .get_index_from_reference(definition.signature())
@@ -737,10 +1236,10 @@ fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directiv
             }
         };
 
-        let body = match definition.body() {
+        let entry_block = match definition.body() {
             ast::FunctionBody::Defined(blocks) => match blocks.first() {
                 Some(entry_block) if blocks.len() == 1 => match directives.code_blocks.get_index_from_reference(entry_block) {
-                    Ok(index) => record::FunctionBody::Definition(index.into()),
+                    Ok(index) => index,
                     Err(e) => {
                         errors.push(e);
                         continue;
@@ -755,39 +1254,57 @@ fn assemble_directives<'t, 's>(errors: &mut Vec<Error>, mut directives: Directiv
                     continue;
                 }
             },
-            ast::FunctionBody::Foreign { function_name, library } => {
-                todo!("foreign function assemble")
+            ast::FunctionBody::Foreign { function_name, .. } => {
+                errors.push(Error::with_location(
+                    ErrorKind::UnsupportedForeignFunctionBody,
+                    function_name.location().clone(),
+                ));
+
+                continue;
             }
         };
 
-        builder.add_record(record::Record::from(record::FunctionDefinition::new(
-            definition.access_modifier(),
-            signature.into(),
-            Cow::Borrowed(definition.identifier().item().as_ref()),
-            body,
-        )))
+        let symbol: &'t sailar::identifier::Id = definition.identifier().item();
+        let export = match definition.access_modifier() {
+            ast::ExportKind::Hidden => record::Export::Hidden,
+            ast::ExportKind::Private => record::Export::PrivateBorrowed(symbol),
+            ast::ExportKind::Export => record::Export::ExportBorrowed(symbol),
+        };
+
+        records.push(
+            RecordKind::FunctionTemplate,
+            record::FunctionTemplate::new(
+                export,
+                index::FunctionSignature::from(signature),
+                index::CodeBlock::from(entry_block),
+            ),
+        );
     }
 
-    for instantiation in directives.function_instantiations.iter() {
+    for instantiation in directives.function_instantiations.iter().copied() {
         let template_index = match instantiation {
             ast::DefinitionOrImport::Definition(definition) => {
                 directives.function_definitions.get_index_from_reference(definition)
                 // + number of function imports
             }
-            ast::DefinitionOrImport::Import(import) => todo!("instantiation of function imports is not yet supported"),
+            ast::DefinitionOrImport::Import(import) => Err(Error::with_location(
+                ErrorKind::UnsupportedFunctionImport,
+                import.location().clone(),
+            )),
         };
 
         match template_index {
             Ok(index) => {
-                builder.add_record(record::Record::from(record::FunctionInstantiation::from_template(
-                    binary::index::FunctionInstantiation::from(index),
-                )));
+                records.push(
+                    RecordKind::Function,
+                    record::Function::with_template(index::FunctionTemplate::from(index)),
+                );
             }
             Err(e) => errors.push(e),
         }
     }
 
-    builder
+    records.into_builder(actual_format_version, &directives.record_order)
 }
 
 /// Assembles a SAILAR module from an abstract syntax tree.
@@ -802,3 +1319,238 @@ pub fn assemble<'tree, 'source: 'tree>(input: &'tree parser::Output<'source>) ->
         Err(errors)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use sailar::validation::ValidModule;
+
+    fn assemble_and_validate(input: &str) -> ValidModule<'static> {
+        let mut tree = None;
+        let builder = crate::assemble(input, &mut tree).unwrap();
+        ValidModule::from_records(builder.into_records().into_iter().map(sailar::record::Record::into_owned)).unwrap()
+    }
+
+    #[test]
+    fn functions_and_entry_point_are_assembled() {
+        let module = assemble_and_validate(
+            r#"
+.format major 0
+.format minor 19
+.metadata id "Arithmetic" 1.0
+.metadata main @main_instance
+.metadata noinline @add_instance
+.signature @u32 type u32
+.signature @add_signature function (@u32, @u32) -> (@u32)
+.signature @main_signature function () -> (@u32)
+
+.code @add_body ($x:@u32, $y:@u32) -> (@u32)
+$sum:@u32 = iadd sat $x $y
+$difference:@u32, $overflowed:@u32 = isub ovf $sum 1
+ret $difference
+
+.code @main_body () -> (@u32)
+$result:@u32 = call @add_instance (2, 3)
+$thread:@u32 = thread.spawn @add_instance ($result, 5)
+$joined:@u32 = thread.join @add_signature $thread
+ret $joined
+
+.define @add function export "add" signature @add_signature
+/body @add_body
+
+.define @main function private "main" signature @main_signature
+/body @main_body
+
+.instantiate @add_instance function definition @add
+.instantiate @main_instance function definition @main
+"#,
+        );
+
+        let contents = module.contents();
+        assert_eq!(Some(sailar::index::Function::from(1)), contents.entry_point);
+        assert_eq!([sailar::index::Function::from(0)], contents.no_inline_functions.as_slice());
+        assert_eq!(2, contents.function_templates.len());
+        assert_eq!(
+            &[sailar::index::FunctionTemplate::from(0)],
+            module.exports().function_templates.as_slice()
+        );
+    }
+
+    #[test]
+    fn branches_are_assembled() {
+        let module = assemble_and_validate(
+            r#"
+.signature @u32 type u32
+.signature @max_signature function (@u32, @u32) -> (@u32)
+
+.code @max_entry ($a:@u32, $b:@u32) -> (@u32)
+$greater:@u32 = cmp $a gt $b
+br.if $greater then @return_first else @return_second ($a, $b)
+
+.code @return_first ($a:@u32, $b:@u32) -> (@u32)
+ret $a
+
+.code @return_second ($a:@u32, $b:@u32) -> (@u32)
+br @return_first ($b, $a)
+
+.define @max function export "max" signature @max_signature
+/body @max_entry
+
+.inst func def @max
+"#,
+        );
+
+        assert_eq!(3, module.contents().code.len());
+    }
+
+    #[test]
+    fn atomic_and_vector_instructions_are_assembled() {
+        let module = assemble_and_validate(
+            r#"
+.signature @u32 type u32
+.signature @u32_pointer type rawptr @u32
+.signature @u32_vector type v4xu32
+.signature @signature function (@u32_pointer, @u32) -> (@u32)
+
+.code @body ($address:@u32_pointer, $value:@u32) -> (@u32)
+$old:@u32 = atomic.rmw add seq_cst $address $value
+$previous:@u32, $replaced:@u32 = atomic.cmpxchg acq_rel acquire $address $old $value
+atomic.store release $address $previous
+fence seq_cst
+$vector:@u32_vector = vec.splat $replaced
+$inserted:@u32_vector = vec.insert $vector 3 $value
+$shuffled:@u32_vector = vec.shuffle $vector $inserted (7, 6, 1, 0)
+$lane:@u32 = vec.extract $shuffled 0
+ret $lane
+
+.define @example function "example" signature @signature
+/body @body
+"#,
+        );
+
+        assert_eq!(9, module.contents().code[0].instructions.len());
+    }
+
+    #[test]
+    fn mismatched_temporary_register_count_is_error() {
+        let input = r#"
+.signature @u32 type u32
+.code ($x:@u32) -> (@u32)
+$sum:@u32 = iadd ovf $x $x
+ret $sum
+"#;
+
+        let errors = crate::assemble(input, &mut None).unwrap_err();
+        assert_eq!(1, errors.len());
+        assert!(matches!(
+            errors[0].kind(),
+            crate::AnyErrorKind::Assembler(super::ErrorKind::TemporaryRegisterCountMismatch { expected: 2, actual: 1 })
+        ));
+    }
+
+    #[test]
+    fn temporary_register_types_are_inferred() {
+        let module = assemble_and_validate(
+            r#"
+.signature @u32 type u32
+.signature @u64 type u64
+.signature @u32_pointer type rawptr @u32
+.signature @u32_vector type v4xu32
+.signature @pair_signature function (@u32_pointer, @u64) -> (@u64, @u32)
+.signature @caller_signature function (@u32_pointer) -> (@u32)
+
+.code @pair_body ($address:@u32_pointer, $value:@u64) -> (@u64, @u32)
+$sum, $overflowed = iadd ovf $value 1
+$old = atomic.load acquire $address
+$vector:@u32_vector = vec.splat $old
+$lane = vec.extract $vector 2
+$is_zero = cmp 0 eq $lane
+ret $sum, $is_zero
+
+.code @caller_body ($address:@u32_pointer) -> (@u32)
+$first, $second = call @pair_instance ($address, 5)
+ret $second
+
+.define @pair function export "pair" signature @pair_signature
+/body @pair_body
+
+.define @caller function export "caller" signature @caller_signature
+/body @caller_body
+
+.inst @pair_instance func def @pair
+"#,
+        );
+
+        let u32_type = sailar::index::TypeSignature::from(0);
+        let u64_type = sailar::index::TypeSignature::from(1);
+        let vector_type = sailar::index::TypeSignature::from(3);
+        let code = &module.contents().code;
+        assert_eq!(
+            vec![u64_type, u64_type, u32_type, vector_type, u32_type, u32_type],
+            code[0].temporary_types().iter().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![u64_type, u32_type], code[1].temporary_types().iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn ambiguous_temporary_register_type_is_error() {
+        let input = r#"
+.signature @u8_vector type v16xu8
+.code ($vector:@u8_vector) -> ()
+$sum = iadd 1 2
+$lane = vec.extract $vector 0
+ret
+"#;
+
+        let errors = crate::assemble(input, &mut None).unwrap_err();
+        assert_eq!(2, errors.len());
+        assert!(matches!(
+            errors[0].kind(),
+            crate::AnyErrorKind::Assembler(super::ErrorKind::AmbiguousTemporaryRegisterType)
+        ));
+        assert!(matches!(
+            errors[1].kind(),
+            crate::AnyErrorKind::Assembler(super::ErrorKind::UndefinedInferredType(_))
+        ));
+    }
+
+    #[test]
+    fn duplicate_register_is_labeled_with_first_definition() {
+        let input = r#"
+.signature @u32 type u32
+.code ($x:@u32, $x:@u32) -> ()
+ret
+"#;
+
+        let errors = crate::assemble(input, &mut None).unwrap_err();
+        assert_eq!(1, errors.len());
+        assert!(matches!(
+            errors[0].kind(),
+            crate::AnyErrorKind::Assembler(super::ErrorKind::DuplicateRegisterDefinition(_))
+        ));
+        assert_eq!(17, errors[0].location().unwrap().start().column.get());
+        assert_eq!(1, errors[0].labels().len());
+        assert_eq!(8, errors[0].labels()[0].location().start().column.get());
+    }
+
+    #[test]
+    fn unsupported_directives_are_errors() {
+        let input = r#"
+.array
+.identifier "a"
+.instantiate @imported function import @a
+"#;
+
+        let errors = crate::assemble(input, &mut None).unwrap_err();
+        assert_eq!(2, errors.len());
+        assert!(matches!(
+            errors[0].kind(),
+            crate::AnyErrorKind::Assembler(super::ErrorKind::UnsupportedArrayDirective)
+        ));
+        assert_eq!(2, errors[0].location().unwrap().start().line.get());
+        assert!(matches!(
+            errors[1].kind(),
+            crate::AnyErrorKind::Assembler(super::ErrorKind::UnsupportedFunctionImport)
+        ));
+        assert_eq!(4, errors[1].location().unwrap().start().line.get());
+    }
+}
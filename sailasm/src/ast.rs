@@ -57,17 +57,38 @@ impl From<Location> for (usize, usize) {
     }
 }
 
+/// Identifies a source file, such as a file that was included by another file.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct FileId(usize);
+
+impl FileId {
+    /// The file that was originally given to the assembler.
+    pub const ROOT: Self = Self(0);
+
+    pub(crate) fn new(index: usize) -> Self {
+        Self(index)
+    }
+
+    /// The index of the file, where the root file is at index 0.
+    #[inline]
+    pub fn index(self) -> usize {
+        self.0
+    }
+}
+
 /// An inclusive range of locations in the input.
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct LocationRange {
     start: Location,
     end: Location,
+    file: FileId,
 }
 
 impl LocationRange {
     pub const START: Self = Self {
         start: Location::START,
         end: Location::START,
+        file: FileId::ROOT,
     };
 
     pub fn new(start: Location, end: Location) -> Self {
@@ -75,7 +96,16 @@ impl LocationRange {
             panic!("end location {} must not come before start location {}", end, start);
         }
 
-        Self { start, end }
+        Self {
+            start,
+            end,
+            file: FileId::ROOT,
+        }
+    }
+
+    /// Sets the file that this range of locations refers to.
+    pub fn with_file(self, file: FileId) -> Self {
+        Self { file, ..self }
     }
 
     #[inline]
@@ -87,6 +117,12 @@ impl LocationRange {
     pub fn end(&self) -> &Location {
         &self.end
     }
+
+    /// The file containing this range of locations, which is [`FileId::ROOT`] unless the range refers to an included file.
+    #[inline]
+    pub fn file(&self) -> FileId {
+        self.file
+    }
 }
 
 impl From<&Location> for LocationRange {
@@ -115,10 +151,10 @@ impl From<&LocationRange> for std::ops::RangeInclusive<Location> {
 
 impl Ord for LocationRange {
     fn cmp(&self, other: &Self) -> Ordering {
-        match self.start.cmp(&other.start) {
-            Ordering::Equal => self.end.cmp(&other.end),
-            c => c,
-        }
+        self.file
+            .cmp(&other.file)
+            .then_with(|| self.start.cmp(&other.start))
+            .then_with(|| self.end.cmp(&other.end))
     }
 }
 
@@ -179,7 +215,7 @@ impl<N> From<Located<N>> for (N, LocationRange) {
     }
 }
 
-pub type Symbol<'source> = Located<&'source sailar::Id>;
+pub type Symbol<'source> = Located<&'source sailar::identifier::Id>;
 
 /// Represents a label or numeric index used to refer to something.
 #[derive(Clone, Debug, PartialEq)]
@@ -229,7 +265,7 @@ pub struct LiteralString<'source> {
     actual_contents: Option<Box<str>>,
 }
 
-pub type Identifier<'source> = Cow<'source, sailar::Id>;
+pub type Identifier<'source> = Cow<'source, sailar::identifier::Id>;
 
 impl<'source> LiteralString<'source> {
     /// Creates a string literal from its contents, which can pontentially contain escape sequences.
@@ -290,8 +326,8 @@ impl<'source> LiteralString<'source> {
 
     pub fn try_into_identifier(self) -> Result<Identifier<'source>, sailar::identifier::InvalidError> {
         match self.actual_contents {
-            Some(actual_contents) => sailar::Identifier::try_from(actual_contents).map(Cow::Owned),
-            None => sailar::Id::try_from_str(self.original_contents).map(Cow::Borrowed),
+            Some(actual_contents) => sailar::identifier::Identifier::try_from(actual_contents).map(Cow::Owned),
+            None => sailar::identifier::Id::try_from_str(self.original_contents).map(Cow::Borrowed),
         }
     }
 }
@@ -337,24 +373,25 @@ impl Display for FormatVersionKind {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Metadata<'source> {
     Identifier(Located<Identifier<'source>>, Box<[u32]>),
+    /// Refers to the function instantiation that is the entry point of the module.
+    EntryPoint(Reference<'source>),
+    /// Refers to a function instantiation whose calls should never be inlined.
+    NoInline(Reference<'source>),
 }
 
+pub use sailar::signature::{IntegerType, VectorType};
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum TypeSignature<'source> {
-    U8,
-    S8,
-    U16,
-    S16,
-    U32,
-    S32,
-    U64,
-    S64,
+    FixedInteger(IntegerType),
     UAddr,
     SAddr,
     F32,
     F64,
     RawPtr(Reference<'source>),
     VoidPtr,
+    FuncPtr(Reference<'source>),
+    Vector(VectorType),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -369,11 +406,11 @@ impl<'source> FunctionSignature<'source> {
     /// # Panics
     ///
     /// Panics if the number of parameters exceeds the actual number of types.
-    pub(crate) fn from_vec(types: &mut Vec<Reference<'source>>, parameter_type_count: usize) -> Self {
+    pub(crate) fn from_slice(types: &[Reference<'source>], parameter_type_count: usize) -> Self {
         assert!(parameter_type_count <= types.len());
 
         Self {
-            types: types.clone().into_boxed_slice(),
+            types: Box::from(types),
             parameter_type_count,
         }
     }
@@ -397,11 +434,11 @@ pub enum Signature<'source> {
 #[derive(Clone, Debug, PartialEq)]
 pub struct TypedRegister<'source> {
     symbol: Option<Symbol<'source>>,
-    value_type: Reference<'source>,
+    value_type: Option<Reference<'source>>,
 }
 
 impl<'source> TypedRegister<'source> {
-    pub fn new(symbol: Option<Symbol<'source>>, value_type: Reference<'source>) -> Self {
+    pub fn new(symbol: Option<Symbol<'source>>, value_type: Option<Reference<'source>>) -> Self {
         Self { symbol, value_type }
     }
 
@@ -412,10 +449,11 @@ impl<'source> TypedRegister<'source> {
         self.symbol.as_ref()
     }
 
-    /// Gets the type of the value contained in this register.
+    /// Gets the type of the value contained in this register, or `None` if the type of this temporary register is
+    /// inferred from the instruction that defines it.
     #[inline]
-    pub fn value_type(&self) -> &Reference<'source> {
-        &self.value_type
+    pub fn value_type(&self) -> Option<&Reference<'source>> {
+        self.value_type.as_ref()
     }
 }
 
@@ -436,12 +474,43 @@ impl<'source> Value<'source> {
     }
 }
 
+pub use sailar::instruction::{AtomicOperation, ComparisonKind, MemoryOrdering, OverflowBehavior};
+
+/// Represents an instruction, see [`sailar::instruction::Instruction`] for the syntax of each instruction.
 #[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 pub enum Instruction<'source> {
     Nop,
     Break,
     Ret(Box<[Value<'source>]>),
+    Br(Reference<'source>, Box<[Value<'source>]>),
+    BrIf {
+        condition: Value<'source>,
+        true_branch: Reference<'source>,
+        false_branch: Reference<'source>,
+        arguments: Box<[Value<'source>]>,
+    },
+    Call(Reference<'source>, Box<[Value<'source>]>),
+    IAdd(OverflowBehavior, Value<'source>, Value<'source>),
+    ISub(OverflowBehavior, Value<'source>, Value<'source>),
+    Cmp(Value<'source>, ComparisonKind, Value<'source>),
+    ThreadSpawn(Reference<'source>, Box<[Value<'source>]>),
+    ThreadJoin(Reference<'source>, Value<'source>),
+    AtomicLoad(MemoryOrdering, Value<'source>),
+    AtomicStore(MemoryOrdering, Value<'source>, Value<'source>),
+    AtomicRmw(AtomicOperation, MemoryOrdering, Value<'source>, Value<'source>),
+    AtomicCmpXchg {
+        success_ordering: MemoryOrdering,
+        failure_ordering: MemoryOrdering,
+        address: Value<'source>,
+        expected: Value<'source>,
+        replacement: Value<'source>,
+    },
+    Fence(MemoryOrdering),
+    VSplat(Value<'source>),
+    VExtract(Value<'source>, u16),
+    VInsert(Value<'source>, u16, Value<'source>),
+    VShuffle(Value<'source>, Value<'source>, Box<[u16]>),
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -506,7 +575,7 @@ impl<'source> CodeBlock<'source> {
     }
 }
 
-pub use sailar::binary::record::Export;
+pub use sailar::record::ExportKind;
 
 #[derive(Clone, Debug, PartialEq)]
 pub enum FunctionBody<'source> {
@@ -519,7 +588,7 @@ pub enum FunctionBody<'source> {
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct FunctionDefinition<'source> {
-    access_modifier: Export,
+    access_modifier: ExportKind,
     identifier: Located<Identifier<'source>>,
     signature: Reference<'source>,
     body: FunctionBody<'source>,
@@ -527,7 +596,7 @@ pub struct FunctionDefinition<'source> {
 
 impl<'source> FunctionDefinition<'source> {
     pub fn new(
-        access_modifier: Export,
+        access_modifier: ExportKind,
         identifier: Located<Identifier<'source>>,
         signature: Reference<'source>,
         body: FunctionBody<'source>,
@@ -541,7 +610,7 @@ impl<'source> FunctionDefinition<'source> {
     }
 
     #[inline]
-    pub fn access_modifier(&self) -> Export {
+    pub fn access_modifier(&self) -> ExportKind {
         self.access_modifier
     }
 
@@ -602,6 +671,8 @@ pub enum Directive<'source> {
     /// .metadata id "MyModule" 1.2.3 ; Specifies the name and version of the module
     /// .metadata id "MyModuleNoVersion"
     /// .meta id "ShortenedVersion"
+    /// .metadata main @my_function_instantiation ; Specifies the entry point function of the module
+    /// .metadata noinline @my_function_instantiation ; Calls to the function are never inlined
     /// ```
     /// Specifies information about the module.
     Metadata(Metadata<'source>),
@@ -629,7 +700,9 @@ pub enum Directive<'source> {
     /// ; Referred to by numeric index or by label
     /// .signature @my_type type s64
     /// .signature @my_pointer_type type rawptr #0
-    /// .signature @my_function_signature (@my_type, #1) -> (#1)
+    /// .signature @my_function_pointer_type type funcptr @my_function_signature
+    /// .signature @my_vector_type type v4xf32
+    /// .signature @my_function_signature function (@my_type, #1) -> (#1)
     /// .sig @shorter_function_signature func (#0, #1) -> (#2)
     /// ```
     /// Defines a record containing a type signature or a function signature. Used to indicate the types of registers, struct
@@ -640,18 +713,25 @@ pub enum Directive<'source> {
     /// .code ($a0:@my_argument_type, $a1:@my_other_type) -> (@my_return_type)
     /// nop
     /// $r0:@my_other_type, $r1:@my_other_other_type = call @my_function ($a0, $a1)
-    /// $r2:@some_integer = addi $r0, 3
-    /// ret
+    /// $r2:@some_integer = iadd $r0 3
+    /// $r3:@some_integer, $r4:@some_integer = isub ovf $r2 1
+    /// $r5:@some_integer = cmp $r3 lt $r2
+    /// $r6 = iadd $r2 $r4 ; Type of $r6 is inferred to be the same as $r2 and $r4
+    /// br.if $r5 then @my_code_block else @my_other_code_block ($r0, $r1)
     ///
     /// ; Referred to by numeric index or by label
     /// .code @my_code_block ($a0:#12, $a1:#34) -> (#5, #6)
     /// ; Insert instructions here...
-    /// ret
+    /// ret $a0, $a1
     /// ```
     /// Defines a code block containing instructions.
+    ///
+    /// The types of temporary registers can be omitted when they can be inferred from the instruction, such as from the
+    /// types of its operands or from the return types of the function that is called. A type annotation is required when
+    /// the type is ambiguous, such as for the result of an `iadd` on two integer literals.
     Code(Option<Symbol<'source>>, CodeBlock<'source>),
     /// ```text
-    /// .define function @my_function public "MyFunction" signature @my_signature
+    /// .define @my_function function export "MyFunction" signature @my_signature
     /// /body @my_code_block
     ///
     /// .define function private "MyForeignFunction" signature @my_signature foreign "foreign_function_name" from @library_name
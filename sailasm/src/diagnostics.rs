@@ -0,0 +1,333 @@
+//! Rendering of errors alongside the lines of source code that caused them.
+//!
+//! Errors produced during assembly carry a primary [`LocationRange`], along with any number of secondary [`Label`]s and notes.
+//! Tools that have their own way of displaying diagnostics, such as a language server, can use these directly, while
+//! command-line tools can use the [`Renderer`] to produce output similar to the following:
+//!
+//! ```text
+//! error: symbol @main is defined more than once
+//!  --> example.sailas:3:9
+//!   |
+//! 1 | .code @main () -> ()
+//!   |       ----- first defined here
+//! 2 |
+//! 3 | .define @main function export "main" signature @sig
+//!   |         ^^^^^
+//! ```
+
+use crate::ast::{FileId, Location, LocationNumber, LocationRange};
+use crate::AnyError;
+use std::borrow::Cow;
+use std::fmt::Write;
+
+/// A secondary location associated with an error, such as the location of a previous definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Label {
+    location: LocationRange,
+    message: Cow<'static, str>,
+}
+
+impl Label {
+    pub fn new<M: Into<Cow<'static, str>>>(location: LocationRange, message: M) -> Self {
+        Self {
+            location,
+            message: message.into(),
+        }
+    }
+
+    #[inline]
+    pub fn location(&self) -> &LocationRange {
+        &self.location
+    }
+
+    #[inline]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Annotation<'e> {
+    start_column: usize,
+    end_column: usize,
+    marker: char,
+    message: Option<&'e str>,
+}
+
+#[derive(Clone, Debug)]
+struct SourceFile<'a> {
+    lines: Vec<&'a str>,
+    path: Option<&'a str>,
+}
+
+impl<'a> SourceFile<'a> {
+    fn new(source: &'a str, path: Option<&'a str>) -> Self {
+        Self {
+            lines: source.lines().collect(),
+            path,
+        }
+    }
+
+    fn line(&self, number: usize) -> &'a str {
+        self.lines.get(number - 1).copied().unwrap_or_default()
+    }
+}
+
+/// Writes errors along with snippets of the source code that they refer to.
+#[derive(Clone, Debug)]
+pub struct Renderer<'a> {
+    files: Vec<SourceFile<'a>>,
+}
+
+impl<'a> Renderer<'a> {
+    /// Creates a renderer for errors in the given source code.
+    pub fn new(source: &'a str) -> Self {
+        Self {
+            files: vec![SourceFile::new(source, None)],
+        }
+    }
+
+    /// Sets the path to the source file, which is displayed before the line and column numbers.
+    pub fn with_path(mut self, path: &'a str) -> Self {
+        self.files[0].path = Some(path);
+        self
+    }
+
+    /// Adds the source code of a file that was included by the original source file, which is given the next
+    /// [`FileId`](crate::ast::FileId).
+    pub fn with_file(mut self, path: &'a str, source: &'a str) -> Self {
+        self.files.push(SourceFile::new(source, Some(path)));
+        self
+    }
+
+    fn file(&self, file: FileId) -> &SourceFile<'a> {
+        self.files.get(file.index()).unwrap_or(&self.files[0])
+    }
+
+    fn write_line<W: Write>(
+        &self,
+        output: &mut W,
+        file: FileId,
+        number: usize,
+        gutter_width: usize,
+    ) -> Result<&'a str, std::fmt::Error> {
+        let line = self.file(file).line(number);
+        write!(output, "{:>gutter_width$} |", number)?;
+        if !line.trim().is_empty() {
+            write!(output, " {}", line.trim_end())?;
+        }
+        output.write_char('\n')?;
+        Ok(line)
+    }
+
+    fn write_path<W: Write>(&self, output: &mut W, file: FileId, location: &Location) -> std::fmt::Result {
+        if let Some(path) = self.file(file).path {
+            write!(output, "{}:", path)?;
+        }
+        writeln!(output, "{}:{}", location.line, location.column)
+    }
+
+    fn annotate<'e>(
+        &self,
+        annotations: &mut std::collections::BTreeMap<(FileId, usize), Vec<Annotation<'e>>>,
+        location: &LocationRange,
+        marker: char,
+        message: Option<&'e str>,
+    ) {
+        let Location {
+            line: start_line,
+            column: start_column,
+        } = location.start();
+        let Location {
+            line: end_line,
+            column: end_column,
+        } = location.end();
+
+        for number in start_line.get()..=end_line.get() {
+            let line = self.file(location.file()).line(number);
+            let length = line.chars().count();
+            let is_last = number == end_line.get();
+
+            let start = if number == start_line.get() {
+                start_column.get()
+            } else {
+                line.chars().take_while(|c| c.is_whitespace()).count() + 1
+            };
+
+            // The end of a location range produced by the parser is the column just after the last character.
+            let end = if is_last { end_column.get() - 1 } else { length }.min(length).max(start);
+
+            annotations.entry((location.file(), number)).or_default().push(Annotation {
+                start_column: start,
+                end_column: end,
+                marker,
+                message: if is_last { message } else { None },
+            });
+        }
+    }
+
+    /// Writes the error message, the lines of source code containing the error, and any notes.
+    pub fn render<W: Write>(&self, error: &AnyError, output: &mut W) -> std::fmt::Result {
+        writeln!(output, "error: {}", error.kind())?;
+
+        let mut annotations = std::collections::BTreeMap::<(FileId, usize), Vec<Annotation>>::new();
+        if let Some(location) = error.location() {
+            self.annotate(&mut annotations, location, '^', None);
+        }
+
+        for label in error.labels() {
+            self.annotate(&mut annotations, label.location(), '-', Some(label.message()));
+        }
+
+        let gutter_width = annotations.keys().map(|(_, line)| line.to_string().len()).max().unwrap_or(0);
+
+        let primary_file = error.location().map(LocationRange::file);
+        if let Some(location) = error.location() {
+            write!(output, "{:gutter_width$}--> ", "")?;
+            self.write_path(output, location.file(), location.start())?;
+        }
+
+        if !annotations.is_empty() {
+            writeln!(output, "{:gutter_width$} |", "")?;
+        }
+
+        let mut previous_line = None;
+        for ((file, number), line_annotations) in annotations.iter_mut() {
+            match previous_line {
+                Some((previous_file, previous)) if previous_file == *file && number - previous == 2 => {
+                    self.write_line(output, *file, number - 1, gutter_width)?;
+                }
+                Some((previous_file, previous)) if previous_file == *file && number - previous > 2 => writeln!(output, "...")?,
+                _ => (),
+            }
+
+            // Lines in other files are preceded by the path to the file.
+            let file_changed = match previous_line {
+                Some((previous_file, _)) => previous_file != *file,
+                None => primary_file != Some(*file),
+            };
+
+            line_annotations.sort_by_key(|annotation| annotation.start_column);
+
+            if file_changed {
+                let first_column = line_annotations[0].start_column;
+                write!(output, "{:gutter_width$}::: ", "")?;
+                self.write_path(
+                    output,
+                    *file,
+                    &Location::new(
+                        LocationNumber::new(*number).unwrap(),
+                        LocationNumber::new(first_column).unwrap(),
+                    ),
+                )?;
+            }
+
+            let line = self.write_line(output, *file, *number, gutter_width)?;
+
+            for annotation in line_annotations.iter() {
+                write!(output, "{:gutter_width$} | ", "")?;
+
+                // Tabs are kept so that the markers line up with the source code.
+                for c in line.chars().take(annotation.start_column - 1) {
+                    output.write_char(if c == '\t' { '\t' } else { ' ' })?;
+                }
+
+                for _ in annotation.start_column..=annotation.end_column {
+                    output.write_char(annotation.marker)?;
+                }
+
+                if let Some(message) = annotation.message {
+                    write!(output, " {}", message)?;
+                }
+
+                output.write_char('\n')?;
+            }
+
+            previous_line = Some((*file, *number));
+        }
+
+        if !error.notes().is_empty() && !annotations.is_empty() {
+            writeln!(output, "{:gutter_width$} |", "")?;
+        }
+
+        for note in error.notes() {
+            writeln!(output, "{:gutter_width$} = note: {}", "", note)?;
+        }
+
+        Ok(())
+    }
+
+    /// Renders an error into a new [`String`].
+    pub fn render_to_string(&self, error: &AnyError) -> String {
+        let mut output = String::new();
+        self.render(error, &mut output).expect("writing to a string should not fail");
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::diagnostics::Renderer;
+
+    fn render_errors(input: &str) -> String {
+        let mut tree = None;
+        let errors = crate::assemble(input, &mut tree).unwrap_err();
+        let renderer = Renderer::new(input).with_path("test.sailas");
+        errors.iter().map(|error| renderer.render_to_string(error)).collect()
+    }
+
+    #[test]
+    fn duplicate_symbol_points_to_first_definition() {
+        let input = ".format major 0\n.format minor 19\n.identifier @name \"a\"\n\n.identifier @name \"b\"\n";
+        assert_eq!(
+            render_errors(input),
+            "error: symbol @name is defined more than once\n --> test.sailas:5:13\n  |\n3 | .identifier @name \"a\"\n  |             ----- first defined here\n4 |\n5 | .identifier @name \"b\"\n  |             ^^^^^\n"
+        );
+    }
+
+    #[test]
+    fn tabs_are_preserved_before_markers() {
+        let input = ".format major 0\n.format minor 19\n.signature @t type u32\n.code @c () -> ()\n\t$x:@t = nop\n\tret\n";
+        let rendered = render_errors(input);
+        assert!(rendered.contains("5 | \t$x:@t = nop\n  | \t^"), "{}", rendered);
+        assert!(rendered.contains("= note:"), "{}", rendered);
+    }
+
+    #[test]
+    fn errors_without_locations_only_include_message() {
+        let error = crate::assembler::Error::new(crate::assembler::ErrorKind::UnsupportedForeignFunctionBody, None);
+        assert_eq!(
+            Renderer::new("").render_to_string(&crate::AnyError::from(&error)),
+            "error: foreign function bodies are not yet supported\n"
+        );
+    }
+
+    #[test]
+    fn labels_in_other_files_include_path() {
+        use crate::ast::{FileId, Location, LocationNumber, LocationRange};
+
+        let location = |file: usize, line: usize, start: usize, end: usize| {
+            let line = LocationNumber::new(line).unwrap();
+            LocationRange::new(
+                Location::new(line, LocationNumber::new(start).unwrap()),
+                Location::new(line, LocationNumber::new(end).unwrap()),
+            )
+            .with_file(FileId::new(file))
+        };
+
+        let error = crate::preprocessor::Error::new(
+            crate::preprocessor::ErrorKind::DuplicateMacroDefinition(Box::from("m")),
+            location(0, 2, 8, 9),
+        )
+        .with_label(location(1, 1, 8, 9), "first defined here");
+
+        let renderer = Renderer::new(".include \"other.sailasm\"\n.macro m\n.endmacro\n")
+            .with_path("main.sailasm")
+            .with_file("other.sailasm", ".macro m\n.endmacro\n");
+
+        assert_eq!(
+            renderer.render_to_string(&crate::AnyError::from(&error)),
+            "error: macro m is defined more than once\n --> main.sailasm:2:8\n  |\n2 | .macro m\n  |        ^\n ::: other.sailasm:1:8\n1 | .macro m\n  |        - first defined here\n"
+        );
+    }
+}
@@ -0,0 +1,810 @@
+//! Prints SAILAR assembly in a canonical layout.
+//!
+//! The formatter parses the input into [`ast::Directive`]s and prints them back out, so the output does not depend on how
+//! the original text was laid out:
+//!
+//! - Directives use their full names (`.signature` instead of `.sig`), and keywords that have shorter aliases are
+//!   written out in full (`function` instead of `func`, `ret` instead of `return`).
+//! - The statements of a code block and the `/body` of a function definition are indented by one level.
+//! - The `=` of consecutive statements that define temporary registers are aligned.
+//! - Integer literals use a lowercase base prefix and uppercase hexadecimal digits, and data bytes are written as
+//!   hexadecimal with up to 16 bytes on each line.
+//! - String literals only use escape sequences where they are required.
+//! - Comments are kept, either after the line they were on or before the directive or statement that follows them.
+//! - Consecutive blank lines are collapsed into one.
+//!
+//! Formatting is idempotent, so formatting already formatted code does not change it.
+
+use crate::ast;
+use crate::lexer::{split_comment, IntegerLiteralBase, IntegerLiteralType, LiteralDigits};
+use crate::AnyError;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Indentation {
+    Tabs,
+    Spaces(u8),
+}
+
+impl Default for Indentation {
+    fn default() -> Self {
+        Indentation::Spaces(4)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Options {
+    pub indentation: Indentation,
+}
+
+/// The maximum number of bytes written on each line of a `.data` directive.
+const BYTES_PER_LINE: usize = 16;
+
+fn has_code(line: &str) -> bool {
+    !split_comment(line).0.trim().is_empty()
+}
+
+/// A directive, statement, or nested directive, which is printed on its own line.
+#[derive(Debug)]
+struct Element {
+    depth: usize,
+    /// The temporary registers defined by a statement, which are aligned with those of the surrounding statements.
+    results: Option<String>,
+    text: String,
+    /// Lines that continue the element, which are indented one level further.
+    continuation: Vec<String>,
+    /// Indicates that the continuation lines are copied from the source code without any changes, such as the body of a
+    /// macro.
+    verbatim: bool,
+    /// Indicates that the element is a code block or one of its statements.
+    in_code_block: bool,
+    /// The range of lines, starting at 1, that the element occupies in the original source code.
+    first_line: usize,
+    last_line: usize,
+}
+
+impl Element {
+    fn new(depth: usize, text: String, location: &ast::LocationRange) -> Self {
+        Self {
+            depth,
+            results: None,
+            text,
+            continuation: Vec::new(),
+            verbatim: false,
+            in_code_block: false,
+            first_line: location.start().line.get(),
+            last_line: location.end().line.get(),
+        }
+    }
+
+    fn on_line(depth: usize, text: String, location: &ast::LocationRange) -> Self {
+        let mut element = Self::new(depth, text, location);
+        element.last_line = element.first_line;
+        element
+    }
+}
+
+fn literal_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn literal_integer(out: &mut String, digits: &LiteralDigits) {
+    match digits.base() {
+        IntegerLiteralBase::Binary => out.push_str("0b"),
+        IntegerLiteralBase::Decimal => (),
+        IntegerLiteralBase::Hexadecimal => out.push_str("0x"),
+    }
+
+    out.extend(digits.digits().chars().map(|c| c.to_ascii_uppercase()));
+
+    out.push_str(match digits.integer_type() {
+        IntegerLiteralType::Unspecified => "",
+        IntegerLiteralType::I8 => "i8",
+        IntegerLiteralType::I16 => "i16",
+        IntegerLiteralType::I32 => "i32",
+        IntegerLiteralType::I64 => "i64",
+    });
+}
+
+fn symbol(out: &mut String, sigil: char, symbol: &ast::Symbol) {
+    out.push(sigil);
+    out.push_str(symbol.item().as_str());
+}
+
+/// Writes the symbol of a directive with a leading space, if it has one.
+fn label(out: &mut String, label: Option<&ast::Symbol>) {
+    if let Some(label) = label {
+        out.push(' ');
+        symbol(out, '@', label);
+    }
+}
+
+fn reference(out: &mut String, reference: &ast::Reference) {
+    match reference {
+        ast::Reference::Index(index) => out.push_str(&format!("#{}", index.item())),
+        ast::Reference::Label(label) => symbol(out, '@', label),
+    }
+}
+
+fn type_list(out: &mut String, types: &[ast::Reference]) {
+    out.push('(');
+    for (position, type_reference) in types.iter().enumerate() {
+        if position > 0 {
+            out.push_str(", ");
+        }
+        reference(out, type_reference);
+    }
+    out.push(')');
+}
+
+fn typed_register_list(out: &mut String, registers: &[ast::Located<ast::TypedRegister>]) {
+    for (position, register) in registers.iter().enumerate() {
+        if position > 0 {
+            out.push_str(", ");
+        }
+
+        match register.item().symbol() {
+            Some(name) => symbol(out, '$', name),
+            None => out.push('_'),
+        }
+
+        if let Some(value_type) = register.item().value_type() {
+            out.push(':');
+            reference(out, value_type);
+        }
+    }
+}
+
+fn value(out: &mut String, value: &ast::Value) {
+    match value {
+        ast::Value::LiteralInteger(integer) => literal_integer(out, integer.item()),
+        ast::Value::Register(ast::Reference::Label(register)) => symbol(out, '$', register),
+        ast::Value::Register(index) => reference(out, index),
+    }
+}
+
+/// Writes values separated by spaces, with a leading space before each value.
+fn values(out: &mut String, values: &[&ast::Value]) {
+    for v in values.iter() {
+        out.push(' ');
+        value(out, v);
+    }
+}
+
+/// Writes a parenthesized list of arguments, which is omitted if there are no arguments.
+fn arguments(out: &mut String, arguments: &[ast::Value]) {
+    if !arguments.is_empty() {
+        out.push_str(" (");
+        for (position, argument) in arguments.iter().enumerate() {
+            if position > 0 {
+                out.push_str(", ");
+            }
+            value(out, argument);
+        }
+        out.push(')');
+    }
+}
+
+fn integer_arithmetic(out: &mut String, name: &str, overflow_behavior: ast::OverflowBehavior, x: &ast::Value, y: &ast::Value) {
+    out.push_str(name);
+    match overflow_behavior {
+        ast::OverflowBehavior::Saturate => out.push_str(" sat"),
+        ast::OverflowBehavior::Flag => out.push_str(" ovf"),
+        _ => (),
+    }
+    values(out, &[x, y]);
+}
+
+fn instruction(out: &mut String, instruction: &ast::Instruction) {
+    match instruction {
+        ast::Instruction::Nop => out.push_str("nop"),
+        ast::Instruction::Break => out.push_str("break"),
+        ast::Instruction::Ret(results) => {
+            out.push_str("ret");
+            for (position, result) in results.iter().enumerate() {
+                out.push_str(if position > 0 { ", " } else { " " });
+                value(out, result);
+            }
+        }
+        ast::Instruction::Br(target, inputs) => {
+            out.push_str("br ");
+            reference(out, target);
+            arguments(out, inputs);
+        }
+        ast::Instruction::BrIf {
+            condition,
+            true_branch,
+            false_branch,
+            arguments: inputs,
+        } => {
+            out.push_str("br.if");
+            values(out, &[condition]);
+            out.push_str(" then ");
+            reference(out, true_branch);
+            out.push_str(" else ");
+            reference(out, false_branch);
+            arguments(out, inputs);
+        }
+        ast::Instruction::Call(callee, inputs) => {
+            out.push_str("call ");
+            reference(out, callee);
+            arguments(out, inputs);
+        }
+        ast::Instruction::IAdd(overflow_behavior, x, y) => integer_arithmetic(out, "iadd", *overflow_behavior, x, y),
+        ast::Instruction::ISub(overflow_behavior, x, y) => integer_arithmetic(out, "isub", *overflow_behavior, x, y),
+        ast::Instruction::Cmp(x, kind, y) => {
+            out.push_str("cmp");
+            values(out, &[x]);
+            out.push_str(&format!(" {}", kind));
+            values(out, &[y]);
+        }
+        ast::Instruction::ThreadSpawn(callee, inputs) => {
+            out.push_str("thread.spawn ");
+            reference(out, callee);
+            arguments(out, inputs);
+        }
+        ast::Instruction::ThreadJoin(signature, thread) => {
+            out.push_str("thread.join ");
+            reference(out, signature);
+            values(out, &[thread]);
+        }
+        ast::Instruction::AtomicLoad(ordering, address) => {
+            out.push_str(&format!("atomic.load {}", ordering));
+            values(out, &[address]);
+        }
+        ast::Instruction::AtomicStore(ordering, address, stored) => {
+            out.push_str(&format!("atomic.store {}", ordering));
+            values(out, &[address, stored]);
+        }
+        ast::Instruction::AtomicRmw(operation, ordering, address, operand) => {
+            out.push_str(&format!("atomic.rmw {} {}", operation, ordering));
+            values(out, &[address, operand]);
+        }
+        ast::Instruction::AtomicCmpXchg {
+            success_ordering,
+            failure_ordering,
+            address,
+            expected,
+            replacement,
+        } => {
+            out.push_str(&format!("atomic.cmpxchg {} {}", success_ordering, failure_ordering));
+            values(out, &[address, expected, replacement]);
+        }
+        ast::Instruction::Fence(ordering) => out.push_str(&format!("fence {}", ordering)),
+        ast::Instruction::VSplat(vector) => {
+            out.push_str("vec.splat");
+            values(out, &[vector]);
+        }
+        ast::Instruction::VExtract(vector, lane) => {
+            out.push_str("vec.extract");
+            values(out, &[vector]);
+            out.push_str(&format!(" {}", lane));
+        }
+        ast::Instruction::VInsert(vector, lane, inserted) => {
+            out.push_str("vec.insert");
+            values(out, &[vector]);
+            out.push_str(&format!(" {}", lane));
+            values(out, &[inserted]);
+        }
+        ast::Instruction::VShuffle(x, y, lanes) => {
+            out.push_str("vec.shuffle");
+            values(out, &[x, y]);
+            out.push_str(" (");
+            for (position, lane) in lanes.iter().enumerate() {
+                if position > 0 {
+                    out.push_str(", ");
+                }
+                out.push_str(&lane.to_string());
+            }
+            out.push(')');
+        }
+    }
+}
+
+fn type_signature(out: &mut String, signature: &ast::TypeSignature) {
+    match signature {
+        ast::TypeSignature::FixedInteger(integer_type) => out.push_str(&integer_type.to_string()),
+        ast::TypeSignature::UAddr => out.push_str("uaddr"),
+        ast::TypeSignature::SAddr => out.push_str("saddr"),
+        ast::TypeSignature::F32 => out.push_str("f32"),
+        ast::TypeSignature::F64 => out.push_str("f64"),
+        ast::TypeSignature::RawPtr(pointee) => {
+            out.push_str("rawptr ");
+            reference(out, pointee);
+        }
+        ast::TypeSignature::VoidPtr => out.push_str("voidptr"),
+        ast::TypeSignature::FuncPtr(signature) => {
+            out.push_str("funcptr ");
+            reference(out, signature);
+        }
+        ast::TypeSignature::Vector(vector_type) => out.push_str(&vector_type.to_string()),
+    }
+}
+
+/// Converts a directive into the elements that are printed for it.
+fn directive_elements(elements: &mut Vec<Element>, directive: &ast::Located<ast::Directive>) {
+    let location = directive.location();
+    let mut out = String::new();
+    match directive.item() {
+        ast::Directive::Array => out.push_str(".array"),
+        ast::Directive::Format(kind, version) => out.push_str(&format!(".format {} {}", kind, version)),
+        ast::Directive::Metadata(ast::Metadata::Identifier(name, version)) => {
+            out.push_str(".metadata id ");
+            literal_string(&mut out, name.item().as_str());
+            for (position, number) in version.iter().enumerate() {
+                out.push(if position > 0 { '.' } else { ' ' });
+                out.push_str(&number.to_string());
+            }
+        }
+        ast::Directive::Metadata(ast::Metadata::EntryPoint(entry_point)) => {
+            out.push_str(".metadata main ");
+            reference(&mut out, entry_point);
+        }
+        ast::Directive::Metadata(ast::Metadata::NoInline(function)) => {
+            out.push_str(".metadata noinline ");
+            reference(&mut out, function);
+        }
+        ast::Directive::Identifier(symbol, identifier) => {
+            out.push_str(".identifier");
+            label(&mut out, symbol.as_ref());
+            out.push(' ');
+            literal_string(&mut out, identifier.item().as_str());
+        }
+        ast::Directive::Data(symbol, bytes) => {
+            out.push_str(".data");
+            label(&mut out, symbol.as_ref());
+
+            let mut lines = bytes
+                .item()
+                .chunks(BYTES_PER_LINE)
+                .map(|chunk| chunk.iter().map(|b| format!("{:#04X}", b)).collect::<Vec<_>>().join(" "));
+
+            if let Some(first) = lines.next() {
+                out.push(' ');
+                out.push_str(&first);
+            }
+
+            let mut element = Element::new(0, out, location);
+            element.continuation = lines.collect();
+            elements.push(element);
+            return;
+        }
+        ast::Directive::Signature(symbol, signature) => {
+            out.push_str(".signature");
+            label(&mut out, symbol.as_ref());
+            match signature.item() {
+                ast::Signature::Type(signature) => {
+                    out.push_str(" type ");
+                    type_signature(&mut out, signature);
+                }
+                ast::Signature::Function(signature) => {
+                    out.push_str(" function ");
+                    type_list(&mut out, signature.parameter_types());
+                    out.push_str(" -> ");
+                    type_list(&mut out, signature.return_types());
+                }
+            }
+        }
+        ast::Directive::Code(symbol, code) => {
+            out.push_str(".code");
+            label(&mut out, symbol.as_ref());
+            out.push_str(" (");
+            typed_register_list(&mut out, code.input_registers());
+            out.push_str(") -> ");
+            type_list(&mut out, code.result_types());
+            let mut header = Element::on_line(0, out, location);
+            header.in_code_block = true;
+            elements.push(header);
+
+            for statement in code.statements() {
+                let mut text = String::new();
+                instruction(&mut text, statement.instruction().item());
+
+                let instruction_location = statement.instruction().location();
+                let start = statement
+                    .results()
+                    .first()
+                    .map(|result| result.location().start())
+                    .unwrap_or_else(|| instruction_location.start());
+
+                let mut element = Element::new(
+                    1,
+                    text,
+                    &ast::LocationRange::new(start.clone(), instruction_location.end().clone()),
+                );
+
+                element.in_code_block = true;
+                if !statement.results().is_empty() {
+                    let mut results = String::new();
+                    typed_register_list(&mut results, statement.results());
+                    element.results = Some(results);
+                }
+
+                elements.push(element);
+            }
+
+            return;
+        }
+        ast::Directive::FunctionDefinition(symbol, definition) => {
+            out.push_str(".define");
+            label(&mut out, symbol.as_ref());
+            out.push_str(match definition.access_modifier() {
+                ast::ExportKind::Export => " function export ",
+                ast::ExportKind::Private => " function private ",
+                ast::ExportKind::Hidden => " function hidden ",
+            });
+            literal_string(&mut out, definition.identifier().item().as_str());
+            out.push_str(" signature ");
+            reference(&mut out, definition.signature());
+
+            match definition.body() {
+                ast::FunctionBody::Defined(blocks) => {
+                    elements.push(Element::on_line(0, out, location));
+                    for block in blocks.iter() {
+                        let mut text = String::from("/body ");
+                        reference(&mut text, block);
+                        elements.push(Element::on_line(1, text, block.location()));
+                    }
+                    return;
+                }
+                ast::FunctionBody::Foreign { function_name, library } => {
+                    out.push_str(" foreign ");
+                    literal_string(&mut out, function_name.item().as_str());
+                    out.push_str(" from ");
+                    reference(&mut out, library);
+                }
+            }
+        }
+        ast::Directive::FunctionInstantiation(symbol, template) => {
+            out.push_str(".instantiate");
+            label(&mut out, symbol.as_ref());
+            match template {
+                ast::DefinitionOrImport::Definition(definition) => {
+                    out.push_str(" function definition ");
+                    reference(&mut out, definition);
+                }
+                ast::DefinitionOrImport::Import(import) => {
+                    out.push_str(" function import ");
+                    reference(&mut out, import);
+                }
+            }
+        }
+    }
+
+    elements.push(Element::new(0, out, location));
+}
+
+enum Line<'e, 's> {
+    Blank,
+    Comment(usize, &'s str),
+    /// An element, along with any comment that was on the same line.
+    Element(&'e Element, Option<&'s str>),
+}
+
+/// Places the comments and blank lines of the source code around the elements.
+fn layout<'e, 's>(source: &[&'s str], elements: &'e [Element]) -> Vec<Line<'e, 's>> {
+    let mut lines = Vec::new();
+    let mut pending_blank = false;
+
+    // Lines that are not part of any element are only made of whitespace and comments.
+    let mut gap = |lines: &mut Vec<Line<'e, 's>>, range: std::ops::Range<usize>, depth: usize| {
+        for number in range {
+            match split_comment(source[number - 1]) {
+                (_, Some(comment)) => {
+                    if pending_blank && !lines.is_empty() {
+                        lines.push(Line::Blank);
+                    }
+                    pending_blank = false;
+                    lines.push(Line::Comment(depth, comment));
+                }
+                (code, None) if code.trim().is_empty() => pending_blank = true,
+                _ => (),
+            }
+        }
+
+        if pending_blank && !lines.is_empty() {
+            lines.push(Line::Blank);
+        }
+        pending_blank = false;
+    };
+
+    let mut next_line = 1;
+    for (index, element) in elements.iter().enumerate() {
+        gap(&mut lines, next_line..element.first_line, element.depth);
+
+        // Multi-line elements such as data can be followed by blank lines and comments that belong to the next element.
+        let mut last_line = element.last_line.min(source.len()).max(element.first_line);
+        if let Some(next) = elements.get(index + 1) {
+            last_line = last_line.min(next.first_line.saturating_sub(1)).max(element.first_line);
+        }
+        while last_line > element.first_line && !has_code(source[last_line - 1]) {
+            last_line -= 1;
+        }
+
+        // Comments on the other lines of the element are moved before it.
+        for number in (element.first_line + 1..=last_line).filter(|_| !element.verbatim) {
+            if let (_, Some(comment)) = split_comment(source[number - 1]) {
+                lines.push(Line::Comment(element.depth, comment));
+            }
+        }
+
+        let trailing = source.get(element.first_line - 1).and_then(|line| split_comment(line).1);
+        lines.push(Line::Element(element, trailing));
+        next_line = last_line + 1;
+    }
+
+    gap(&mut lines, next_line..source.len() + 1, 0);
+
+    while let Some(Line::Blank) = lines.last() {
+        lines.pop();
+    }
+
+    lines
+}
+
+/// Replaces the lines containing preprocessor directives with blank lines, returning the elements for those lines.
+///
+/// Macro definitions are kept as they are, since their contents cannot be parsed until the macro is expanded.
+fn preprocessor_elements(source: &[&str], masked: &mut String) -> Vec<Element> {
+    let mut elements = Vec::new();
+    let mut lines = source.iter().enumerate();
+    while let Some((index, line)) = lines.next() {
+        if let Some(words) = crate::preprocessor::directive_words(line) {
+            let location = ast::LocationRange::from(ast::Location::new(
+                ast::LocationNumber::new(index + 1).unwrap(),
+                ast::LOCATION_NUMBER_START,
+            ));
+
+            let mut element = Element::on_line(0, words.join(" "), &location);
+
+            match words[0] {
+                ".macro" => {
+                    element.verbatim = true;
+                    for (index, line) in lines.by_ref() {
+                        masked.push('\n');
+                        element.continuation.push(line.trim_end().to_owned());
+                        element.last_line = index + 1;
+                        if let Some([".endmacro", ..]) = crate::preprocessor::directive_words(line).as_deref() {
+                            break;
+                        }
+                    }
+                }
+                // Macros can be expanded inside of code blocks.
+                ".expand" => element.depth = usize::from(line.starts_with(char::is_whitespace)),
+                _ => (),
+            }
+
+            elements.push(element);
+        } else {
+            masked.push_str(line);
+        }
+
+        masked.push('\n');
+    }
+    elements
+}
+
+/// Parses the input and prints it in a canonical layout, or returns any errors encountered during parsing.
+pub fn format(input: &str, options: &Options) -> Result<String, Vec<AnyError>> {
+    let source = input.lines().collect::<Vec<_>>();
+    let mut masked = String::with_capacity(input.len());
+    let mut elements = preprocessor_elements(&source, &mut masked);
+
+    let tokens = crate::lexer::tokenize(&masked);
+    let tree = crate::parser::parse(&tokens);
+
+    if !tree.errors().is_empty() {
+        return Err(tree.errors().iter().map(AnyError::from).collect());
+    }
+
+    for directive in tree.tree() {
+        directive_elements(&mut elements, directive);
+    }
+
+    elements.sort_by_key(|element| element.first_line);
+
+    // Indented macro expansions are only kept indented if they are inside of a code block.
+    let mut in_code_block = false;
+    for element in elements.iter_mut() {
+        if element.depth > 0 && element.text.starts_with(".expand") {
+            if in_code_block {
+                element.in_code_block = true;
+            } else {
+                element.depth = 0;
+            }
+        }
+        in_code_block = element.in_code_block;
+    }
+
+    let lines = layout(&source, &elements);
+
+    let indentation = match options.indentation {
+        Indentation::Tabs => String::from("\t"),
+        Indentation::Spaces(count) => " ".repeat(count.into()),
+    };
+
+    // Statements are grouped into runs separated by blank lines, and the results of each statement in a run are padded so
+    // that each = is at the same column.
+    let mut result_widths = vec![0; lines.len()];
+    let mut run_start = 0;
+    for index in 0..=lines.len() {
+        let in_run = match lines.get(index) {
+            Some(Line::Comment(_, _)) => true,
+            Some(Line::Element(element, _)) => element.depth > 0,
+            _ => false,
+        };
+
+        if !in_run {
+            let width = lines[run_start..index]
+                .iter()
+                .filter_map(|line| match line {
+                    Line::Element(element, _) => element.results.as_ref().map(String::len),
+                    _ => None,
+                })
+                .max()
+                .unwrap_or(0);
+
+            result_widths[run_start..index].fill(width);
+            run_start = index + 1;
+        }
+    }
+
+    let mut output = String::with_capacity(input.len());
+    for (line, width) in lines.iter().zip(result_widths) {
+        match line {
+            Line::Blank => (),
+            Line::Comment(depth, comment) => {
+                output.push_str(&indentation.repeat(*depth));
+                output.push_str(comment);
+            }
+            Line::Element(element, trailing) => {
+                output.push_str(&indentation.repeat(element.depth));
+                if let Some(results) = &element.results {
+                    output.push_str(&format!("{:width$} = ", results));
+                }
+                output.push_str(&element.text);
+                if let Some(comment) = trailing {
+                    output.push(' ');
+                    output.push_str(comment);
+                }
+                for continuation in element.continuation.iter() {
+                    output.push('\n');
+                    if !element.verbatim {
+                        output.push_str(&indentation.repeat(element.depth + 1));
+                    }
+                    output.push_str(continuation);
+                }
+            }
+        }
+        output.push('\n');
+    }
+
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::formatter::{format, Indentation, Options};
+
+    fn format_default(input: &str) -> String {
+        format(input, &Options::default()).unwrap()
+    }
+
+    const UNFORMATTED: &str = r#"; Adds two numbers
+
+
+.format major 0
+.format minor 19 ; The current version
+.meta id "Example" 1.2
+.sig @u32 type u32
+.sig @add_signature func (@u32,@u32)->(@u32)
+.data @bytes 0xa 0X0b 0b1
+   255 ; Last byte
+.code @add_body ($x:@u32,$y:@u32) -> (@u32)
+$sum:@u32 = iadd sat $x $y ; Saturates
+; Check for overflow
+$difference:@u32,$overflowed:@u32=isub ovf $sum 0xffi32
+
+nop
+$z:@u32 = cmp $sum lt 10
+return $sum
+.def @add func public "add\\" signature @add_signature
+/body @add_body
+.inst @add_instance func def @add
+; The end
+"#;
+
+    const FORMATTED: &str = r#"; Adds two numbers
+
+.format major 0
+.format minor 19 ; The current version
+.metadata id "Example" 1.2
+.signature @u32 type u32
+.signature @add_signature function (@u32, @u32) -> (@u32)
+; Last byte
+.data @bytes 0x0A 0x0B 0x01 0xFF
+.code @add_body ($x:@u32, $y:@u32) -> (@u32)
+    $sum:@u32                          = iadd sat $x $y ; Saturates
+    ; Check for overflow
+    $difference:@u32, $overflowed:@u32 = isub ovf $sum 0xFFi32
+
+    nop
+    $z:@u32 = cmp $sum lt 10
+    ret $sum
+.define @add function export "add\\" signature @add_signature
+    /body @add_body
+.instantiate @add_instance function definition @add
+; The end
+"#;
+
+    #[test]
+    fn layout_is_normalized() {
+        assert_eq!(format_default(UNFORMATTED), FORMATTED);
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let formatted = format_default(UNFORMATTED);
+        assert_eq!(format_default(&formatted), formatted);
+    }
+
+    #[test]
+    fn formatted_code_assembles_to_same_module() {
+        let formatted = format_default(UNFORMATTED);
+
+        let assemble = |input: &str| {
+            let mut tree = None;
+            let mut buffer = Vec::new();
+            crate::assemble(input, &mut tree).unwrap().write_to(&mut buffer).unwrap();
+            buffer
+        };
+
+        assert_eq!(assemble(UNFORMATTED), assemble(&formatted));
+    }
+
+    #[test]
+    fn long_data_is_split_across_lines() {
+        let input = format!(".data {}\n", (0u8..20).map(|b| b.to_string()).collect::<Vec<_>>().join(" "));
+        let options = Options {
+            indentation: Indentation::Tabs,
+        };
+        let formatted = format(&input, &options).unwrap();
+
+        assert_eq!(
+            formatted,
+            ".data 0x00 0x01 0x02 0x03 0x04 0x05 0x06 0x07 0x08 0x09 0x0A 0x0B 0x0C 0x0D 0x0E 0x0F\n\t0x10 0x11 0x12 0x13\n"
+        );
+        assert_eq!(format(&formatted, &options).unwrap(), formatted);
+    }
+
+    #[test]
+    fn semicolons_in_strings_are_not_comments() {
+        assert_eq!(format_default(".ident @a \"a;b\"   ; c\n"), ".identifier @a \"a;b\" ; c\n");
+    }
+
+    #[test]
+    fn preprocessor_directives_are_kept() {
+        let input = ".include  \"common.sailasm\" ; Signatures\n\n.macro ret_one %t\n  ; Body is unchanged\n  $one:%t = iadd 1 0\n  ret $one\n.endmacro\n.code @c () -> (@u32)\n  .expand ret_one @u32\n.expand ret_one  @u32\n";
+        let formatted = format_default(input);
+        assert_eq!(
+            formatted,
+            ".include \"common.sailasm\" ; Signatures\n\n.macro ret_one %t\n  ; Body is unchanged\n  $one:%t = iadd 1 0\n  ret $one\n.endmacro\n.code @c () -> (@u32)\n    .expand ret_one @u32\n.expand ret_one @u32\n"
+        );
+        assert_eq!(format_default(&formatted), formatted);
+    }
+
+    #[test]
+    fn parse_errors_are_returned() {
+        assert!(format(".format major\n", &Options::default()).is_err());
+    }
+}
@@ -215,22 +215,19 @@ fn literal_integer_contents<'s>(lex: &mut logos::Lexer<'s, Token<'s>>) -> Litera
 
     match token.get(0..2) {
         Some("0x" | "0X") => {
-            base = IntegerLiteralBase::Binary;
+            base = IntegerLiteralBase::Hexadecimal;
             token = &token[2..];
         }
         Some("0b" | "0B") => {
-            base = IntegerLiteralBase::Hexadecimal;
+            base = IntegerLiteralBase::Binary;
             token = &token[2..];
         }
-        _ => {
-            base = IntegerLiteralBase::Decimal;
-            token = token;
-        }
+        _ => base = IntegerLiteralBase::Decimal,
     }
 
     // TODO: Could account for digit separators by spitting on '_' and allocating a String.
 
-    let (digits, integer_type) = match token.rfind(&['u', 's']) {
+    let (digits, integer_type) = match token.rfind('i') {
         Some(type_index) => token.split_at(type_index),
         None => (token, ""),
     };
@@ -261,8 +258,8 @@ fn directive<'s>(lex: &mut logos::Lexer<'s, Token<'s>>) -> &'s str {
     &lex.slice()[1..]
 }
 
-fn symbol<'s>(lex: &mut logos::Lexer<'s, Token<'s>>) -> Result<&'s sailar::Id, sailar::identifier::InvalidError> {
-    sailar::Id::try_from_str(&lex.slice()[1..])
+fn symbol<'s>(lex: &mut logos::Lexer<'s, Token<'s>>) -> Result<&'s sailar::identifier::Id, sailar::identifier::InvalidError> {
+    sailar::identifier::Id::try_from_str(&lex.slice()[1..])
 }
 
 #[derive(Logos, Debug, PartialEq)]
@@ -290,10 +287,11 @@ pub enum Token<'s> {
     #[regex(r"/[a-zA-Z]+", directive)]
     NestedDirective(&'s str),
     #[regex(r"@[a-zA-Z_0-9]+", symbol)]
-    Label(&'s sailar::Id),
-    #[regex(r"[a-zA-Z][a-zA-Z_0-9]*")]
+    Label(&'s sailar::identifier::Id),
+    /// A keyword or instruction name, which may contain periods (e.g. `br.if` or `atomic.load`).
+    #[regex(r"[a-zA-Z][a-zA-Z_0-9]*(\.[a-zA-Z_0-9]+)*")]
     Word(&'s str),
-    #[regex("\"[a-zA-Z0-9_ \\?\\\\/!\\*\\+\\.]*\"", literal_string_contents)]
+    #[regex(r#""([^"\\\r\n]|\\.)*""#, literal_string_contents)]
     LiteralString(&'s str),
     #[regex(
         "((0[Bb][01][01_]*)|(0[Xx][0-9a-fA-F][0-9a-fA-F_]*)|[0-9][0-9_]*)(i(8|16|32|64))?",
@@ -303,15 +301,31 @@ pub enum Token<'s> {
     #[regex("#[0-9]+", index_contents)]
     Index(LiteralDigits<'s>),
     #[regex(r"\$[a-zA-Z_0-9]+", symbol)]
-    Register(&'s sailar::Id),
+    Register(&'s sailar::identifier::Id),
     #[regex(r"\n|\r|(\r\n)")]
     Newline,
     #[error]
     #[regex(r"[ \t]+", logos::skip)]
-    #[regex(r";[ \t\w\d;\?!\\/\.\*\+-=#]*", logos::skip)]
+    #[regex(r";[^\r\n]*", logos::skip)]
     Unknown,
 }
 
+/// Splits a line into its code and its comment, if any.
+pub(crate) fn split_comment(line: &str) -> (&str, Option<&str>) {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (offset, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ';' if !in_string => return (&line[..offset], Some(line[offset..].trim_end())),
+            _ => (),
+        }
+    }
+    (line, None)
+}
+
 #[derive(Debug)]
 pub struct Output<'source> {
     tokens: Vec<(Token<'source>, Range<usize>)>,
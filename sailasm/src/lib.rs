@@ -1,11 +1,18 @@
 //! Library for parsing and assembling the SAILAR text format.
 //!
 //! For a quick overview of the basic syntax and available directives, see [`ast::Directive`].
+//!
+//! Include files and macros are handled by the [`preprocessor`], see [`assemble_preprocessed`].
+
+use std::borrow::Cow;
 
 pub mod assembler;
 pub mod ast;
+pub mod diagnostics;
+pub mod formatter;
 pub mod lexer;
 pub mod parser;
+pub mod preprocessor;
 
 #[derive(Clone, Debug, thiserror::Error)]
 pub enum AnyErrorKind {
@@ -13,6 +20,8 @@ pub enum AnyErrorKind {
     Parser(#[from] parser::ErrorKind),
     #[error(transparent)]
     Assembler(#[from] assembler::ErrorKind),
+    #[error(transparent)]
+    Preprocessor(#[from] preprocessor::ErrorKind),
 }
 
 /// Represents an error that occured at any point during assembly.
@@ -21,9 +30,40 @@ pub enum AnyErrorKind {
 pub struct AnyError {
     kind: AnyErrorKind,
     location: Option<ast::LocationRange>,
+    labels: Vec<diagnostics::Label>,
+    notes: Vec<Cow<'static, str>>,
 }
 
 impl AnyError {
+    fn new(kind: AnyErrorKind, location: Option<ast::LocationRange>, labels: Vec<diagnostics::Label>) -> Self {
+        let mut notes = Vec::new();
+        match &kind {
+            AnyErrorKind::Parser(parser::ErrorKind::InvalidEscapeSequence(_)) => {
+                notes.push(Cow::Borrowed(r#"valid escape sequences are \t, \n, \r, \", \' and \\"#))
+            }
+            AnyErrorKind::Assembler(assembler::ErrorKind::MissingMajorFormatVersion) => {
+                notes.push(Cow::Borrowed("the major format version is specified with .format major"))
+            }
+            AnyErrorKind::Assembler(assembler::ErrorKind::TemporaryRegisterCountMismatch { .. }) => notes.push(Cow::Borrowed(
+                "each result of an instruction is assigned to a temporary register, as in $name:@type = ...",
+            )),
+            AnyErrorKind::Assembler(assembler::ErrorKind::AmbiguousTemporaryRegisterType) => notes.push(Cow::Borrowed(
+                "the type of the temporary register must be specified, as in $name:@type = ...",
+            )),
+            AnyErrorKind::Assembler(assembler::ErrorKind::UndefinedInferredType(_)) => notes.push(Cow::Borrowed(
+                "define a type signature with the inferred type, or specify the type of the temporary register",
+            )),
+            _ => (),
+        }
+
+        Self {
+            kind,
+            location,
+            labels,
+            notes,
+        }
+    }
+
     #[inline]
     pub fn kind(&self) -> &AnyErrorKind {
         &self.kind
@@ -33,23 +73,55 @@ impl AnyError {
     pub fn location(&self) -> Option<&ast::LocationRange> {
         self.location.as_ref()
     }
+
+    /// Secondary locations in the source code related to this error, such as the location of a previous definition.
+    #[inline]
+    pub fn labels(&self) -> &[diagnostics::Label] {
+        &self.labels
+    }
+
+    /// Additional messages that explain how to fix this error.
+    #[inline]
+    pub fn notes(&self) -> &[Cow<'static, str>] {
+        &self.notes
+    }
+
+    fn map_locations<F: Fn(&ast::LocationRange) -> ast::LocationRange>(self, f: F) -> Self {
+        Self {
+            location: self.location.as_ref().map(&f),
+            labels: self
+                .labels
+                .iter()
+                .map(|label| diagnostics::Label::new(f(label.location()), label.message().to_owned()))
+                .collect(),
+            ..self
+        }
+    }
 }
 
 impl From<&parser::Error> for AnyError {
     fn from(error: &parser::Error) -> Self {
-        Self {
-            kind: error.kind().clone().into(),
-            location: Some(error.location().clone()),
-        }
+        Self::new(error.kind().clone().into(), Some(error.location().clone()), Vec::new())
     }
 }
 
 impl From<&assembler::Error> for AnyError {
     fn from(error: &assembler::Error) -> Self {
-        Self {
-            kind: error.kind().clone().into(),
-            location: error.location().cloned(),
-        }
+        Self::new(
+            error.kind().clone().into(),
+            error.location().cloned(),
+            error.labels().to_vec(),
+        )
+    }
+}
+
+impl From<&preprocessor::Error> for AnyError {
+    fn from(error: &preprocessor::Error) -> Self {
+        Self::new(
+            error.kind().clone().into(),
+            Some(error.location().clone()),
+            error.labels().to_vec(),
+        )
     }
 }
 
@@ -66,7 +138,7 @@ where
 pub fn assemble<'tree, 'source: 'tree>(
     input: &'source str,
     parse_tree: &'tree mut Option<parser::Output<'source>>,
-) -> Result<sailar::binary::Builder<'tree>, Vec<AnyError>> {
+) -> Result<sailar::builder::Builder<'tree>, Vec<AnyError>> {
     let mut errors = Vec::default();
     let tokens = lexer::tokenize(input);
     let tree = parse_tree.insert(parser::parse(&tokens));
@@ -82,3 +154,27 @@ pub fn assemble<'tree, 'source: 'tree>(
     errors.sort_by_key(|e| e.location().cloned());
     Err(errors)
 }
+
+/// Assembles source code produced by the [`preprocessor`], reporting errors at their locations in the original files.
+///
+/// If any errors occured during preprocessing, then only those errors are returned.
+pub fn assemble_preprocessed<'tree, 'source: 'tree>(
+    input: &'source preprocessor::Output,
+    parse_tree: &'tree mut Option<parser::Output<'source>>,
+) -> Result<sailar::builder::Builder<'tree>, Vec<AnyError>> {
+    if !input.errors().is_empty() {
+        let mut errors = Vec::default();
+        extend_errors_from_slice(&mut errors, input.errors());
+        return Err(errors);
+    }
+
+    assemble(input.text(), parse_tree).map_err(|errors| {
+        let mut errors = errors
+            .into_iter()
+            .map(|error| error.map_locations(|location| input.original_location(location)))
+            .collect::<Vec<_>>();
+
+        errors.sort_by_key(|e| e.location().cloned());
+        errors
+    })
+}
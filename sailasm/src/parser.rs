@@ -90,6 +90,12 @@ pub enum ErrorKind {
     ExpectedInstantiationKind,
     #[error("expected definition or import")]
     ExpectedDefinitionOrImport,
+    #[error("expected {0}")]
+    ExpectedKeyword(&'static str),
+    #[error("expected function entry point")]
+    ExpectedEntryPoint,
+    #[error("expected function that should not be inlined")]
+    ExpectedNoInlineFunction,
 }
 
 #[derive(Clone, Debug, thiserror::Error, PartialEq)]
@@ -163,6 +169,11 @@ impl<'tree, 'source, S: Iterator<Item = &'tree (Token<'source>, Range<usize>)>>
         Self::token_from_offsets(self.source.peek(), self.locations)
     }
 
+    /// Skips all tokens until the end of the current line, without consuming the newline.
+    fn skip_to_end_of_line(&mut self) {
+        while self.next_token_if(|token| !matches!(token, Token::Newline)).is_some() {}
+    }
+
     fn skip_current_line(&mut self) {
         loop {
             match self.next_token() {
@@ -330,20 +341,28 @@ fn parse_reference<'t, 's>(
 
 /// Parses a comma separated list of register symbols with type annotations, appending the parsed registers to the
 /// `typed_register_buffer` of the `state`.
-fn parse_typed_register_list<'t, 's>(state: &mut State<'t, 's>) -> Box<[ast::Located<ast::TypedRegister<'s>>]> {
+///
+/// If `types_required` is `false`, then the type annotations may be omitted.
+fn parse_typed_register_list<'t, 's>(
+    state: &mut State<'t, 's>,
+    types_required: bool,
+) -> Box<[ast::Located<ast::TypedRegister<'s>>]> {
     state.typed_register_buffer.clear();
 
     loop {
         let register_symbol;
         let start_location;
+        let symbol_end_location;
         match state.input.peek_next_token() {
             Some(((Token::Register(name), _), location)) => {
                 start_location = location.start().clone();
+                symbol_end_location = location.end().clone();
                 register_symbol = Some(ast::Symbol::with_range(name, location));
             }
             Some(((Token::Underscore, _), location)) => {
                 register_symbol = None;
                 start_location = location.start().clone();
+                symbol_end_location = location.end().clone();
             }
             bad => {
                 if !state.typed_register_buffer.is_empty() {
@@ -363,6 +382,20 @@ fn parse_typed_register_list<'t, 's>(state: &mut State<'t, 's>) -> Box<[ast::Loc
             Some(((Token::Colon, _), _)) => {
                 state.input.next_token();
             }
+            _ if !types_required => {
+                state.typed_register_buffer.push(ast::Located::new(
+                    ast::TypedRegister::new(register_symbol, None),
+                    start_location,
+                    symbol_end_location,
+                ));
+
+                if let Some(((Token::Comma, _), _)) = state.input.peek_next_token() {
+                    state.input.next_token();
+                    continue;
+                } else {
+                    break;
+                }
+            }
             bad => {
                 state.push_error(
                     ErrorKind::ExpectedRegisterType,
@@ -379,7 +412,7 @@ fn parse_typed_register_list<'t, 's>(state: &mut State<'t, 's>) -> Box<[ast::Loc
                 let end_location = register_type.location().end().clone();
 
                 state.typed_register_buffer.push(ast::Located::new(
-                    ast::TypedRegister::new(register_symbol, register_type),
+                    ast::TypedRegister::new(register_symbol, Some(register_type)),
                     start_location,
                     end_location,
                 ));
@@ -411,6 +444,11 @@ fn parse_instruction_value<'t, 's>(state: &mut State<'t, 's>) -> Option<ast::Val
                 register, location,
             ))))
         }
+        Some(((Token::Index(_), _), _)) => {
+            let mut register = None;
+            parse_reference(state, |_, r| register = Some(ast::Value::Register(r)), |_, _| ());
+            register
+        }
         Some(((Token::LiteralInteger(integer), _), location)) => {
             state.input.next_token();
             Some(ast::Value::LiteralInteger(ast::Located::with_range(
@@ -432,23 +470,12 @@ fn parse_instruction_value_list<'t, 's>(state: &mut State<'t, 's>) -> Box<[ast::
             if state.input.next_token_if(|t| matches!(t, Token::Comma)).is_none() {
                 break;
             }
-        } else if !state.instruction_value_buffer.is_empty() {
-            let location = match state.input.peek_next_token() {
-                Some(((token, _), location)) => {
-                    match token {
-                        Token::Newline => (),
-                        _ => {
-                            state.input.next_token();
-                        }
-                    }
-
-                    location.clone()
-                }
-                None => state.locations().get_last().unwrap().into(),
-            };
-
-            state.push_error(ErrorKind::ExpectedInstructionValue, location);
         } else {
+            if !state.instruction_value_buffer.is_empty() {
+                let location = token_location_or_last(state.input.peek_next_token().as_ref(), state.locations());
+                state.push_error(ErrorKind::ExpectedInstructionValue, location);
+            }
+
             break;
         }
     }
@@ -456,18 +483,17 @@ fn parse_instruction_value_list<'t, 's>(state: &mut State<'t, 's>) -> Box<[ast::
     state.instruction_value_buffer.clone().into_boxed_slice()
 }
 
-fn parse_access_modifier<'t, 's>(state: &mut State<'t, 's>) -> ast::Export {
-    match state.input.peek_next_token() {
-        Some(((Token::Word("public"), _), _)) => {
-            state.input.next_token();
-            ast::Export::Public
-        }
-        Some(((Token::Word("private"), _), _)) => {
-            state.input.next_token();
-            ast::Export::Private
-        }
-        _ => ast::Export::default(),
-    }
+/// Parses the access modifier of a definition, which defaults to `private` if omitted.
+fn parse_access_modifier<'t, 's>(state: &mut State<'t, 's>) -> ast::ExportKind {
+    let kind = match state.input.peek_next_token() {
+        Some(((Token::Word("export" | "public"), _), _)) => ast::ExportKind::Export,
+        Some(((Token::Word("private"), _), _)) => ast::ExportKind::Private,
+        Some(((Token::Word("hidden"), _), _)) => ast::ExportKind::Hidden,
+        _ => return ast::ExportKind::Private,
+    };
+
+    state.input.next_token();
+    kind
 }
 
 fn parse_definition_or_import<'t, 's>(
@@ -501,6 +527,368 @@ fn parse_definition_or_import<'t, 's>(
     }
 }
 
+/// Parses a comma separated list of type signature references enclosed in parenthesis, appending the references to the
+/// `reference_buffer`.
+fn parse_type_reference_list<'t, 's>(
+    state: &mut State<'t, 's>,
+    previous_location: &ast::Location,
+    reference_buffer: &mut Vec<ast::Reference<'s>>,
+    success: impl FnOnce(&mut State<'t, 's>, &mut Vec<ast::Reference<'s>>, ast::LocationRange),
+) {
+    let start_location;
+    let mut end_location;
+    match state.input.next_token() {
+        Some(((Token::OpenParenthesis, _), location)) => {
+            start_location = location.start().clone();
+            end_location = std::cell::RefCell::new(location.end().clone());
+        }
+        bad => {
+            state.push_error(
+                ErrorKind::ExpectedFunctionSignatureTypeList,
+                token_location_or_else(bad.as_ref(), || ast::LocationRange::from(previous_location.clone())),
+            );
+            return;
+        }
+    }
+
+    let mut parse_type_reference = |state: &mut State<'t, 's>| {
+        parse_reference(
+            state,
+            |_, s| {
+                *end_location.borrow_mut() = s.location().end().clone();
+                reference_buffer.push(s);
+            },
+            |s, b| {
+                s.push_error(
+                    ErrorKind::ExpectedReference,
+                    token_location_or_else(b.as_ref(), || ast::LocationRange::from(end_location.clone())),
+                )
+            },
+        )
+    };
+
+    if !matches!(state.input.peek_next_token(), Some(((Token::CloseParenthesis, _), _))) {
+        parse_type_reference(state);
+
+        while state.input.next_token_if(|t| matches!(t, Token::Comma)).is_some() {
+            parse_type_reference(state);
+        }
+    }
+
+    match state.input.next_token() {
+        Some(((Token::CloseParenthesis, _), location)) => {
+            *end_location.get_mut() = location.end().clone();
+        }
+        bad => {
+            state.push_error(
+                ErrorKind::ExpectedClosingParenthesis,
+                token_location_or_else(bad.as_ref(), || ast::LocationRange::from(end_location.into_inner())),
+            );
+            return;
+        }
+    }
+
+    success(
+        state,
+        reference_buffer,
+        ast::LocationRange::new(start_location, end_location.into_inner()),
+    )
+}
+
+/// Parses the name of a type that does not refer to any other type signatures, such as `u32`, `saddr`, or `v4xf32`.
+fn parse_primitive_type(name: &str) -> Option<ast::TypeSignature<'static>> {
+    use sailar::signature::{IntegerSign, IntegerSize, VectorElementType};
+
+    fn integer_type(name: &str) -> Option<ast::IntegerType> {
+        let sign = match name.get(0..1)? {
+            "u" => IntegerSign::Unsigned,
+            "s" => IntegerSign::Signed,
+            _ => return None,
+        };
+
+        let size = match name[1..].parse::<u16>().ok()? {
+            256 => IntegerSize::I256,
+            bit_size => IntegerSize::new(std::num::NonZeroU8::new(u8::try_from(bit_size).ok()?)?),
+        };
+
+        Some(ast::IntegerType::new(sign, size))
+    }
+
+    Some(match name {
+        "uaddr" => ast::TypeSignature::UAddr,
+        "saddr" => ast::TypeSignature::SAddr,
+        "f32" => ast::TypeSignature::F32,
+        "f64" => ast::TypeSignature::F64,
+        _ => match integer_type(name) {
+            Some(integer_type) => ast::TypeSignature::FixedInteger(integer_type),
+            None => {
+                let (lane_count, element_type) = name.strip_prefix('v')?.split_once('x')?;
+                let element_type = match element_type {
+                    "f32" => VectorElementType::F32,
+                    "f64" => VectorElementType::F64,
+                    _ => VectorElementType::Integer(integer_type(element_type)?),
+                };
+
+                ast::TypeSignature::Vector(ast::VectorType::new(element_type, lane_count.parse().ok()?))
+            }
+        },
+    })
+}
+
+fn expect_instruction_value<'t, 's>(state: &mut State<'t, 's>) -> Option<ast::Value<'s>> {
+    let value = parse_instruction_value(state);
+    if value.is_none() {
+        let location = token_location_or_last(state.input.peek_next_token().as_ref(), state.locations());
+        state.push_error(ErrorKind::ExpectedInstructionValue, location);
+    }
+    value
+}
+
+fn expect_instruction_reference<'t, 's>(state: &mut State<'t, 's>) -> Option<ast::Reference<'s>> {
+    match state.input.peek_next_token() {
+        Some(((Token::Label(_) | Token::Index(_), _), _)) => {
+            let mut reference = None;
+            parse_reference(state, |_, r| reference = Some(r), |_, _| ());
+            reference
+        }
+        bad => {
+            state.push_error(
+                ErrorKind::ExpectedReference,
+                token_location_or_last(bad.as_ref(), state.locations()),
+            );
+            None
+        }
+    }
+}
+
+/// Parses a keyword used by an instruction, such as a memory ordering or comparison kind.
+fn expect_instruction_keyword<'t, 's, T>(
+    state: &mut State<'t, 's>,
+    description: &'static str,
+    keyword: impl FnOnce(&str) -> Option<T>,
+) -> Option<T> {
+    let (token, location) = match state.input.peek_next_token() {
+        Some(((Token::Word(word), _), location)) => (keyword(word), location),
+        bad => (None, token_location_or_last(bad.as_ref(), state.locations())),
+    };
+
+    if token.is_some() {
+        state.input.next_token();
+    } else {
+        state.push_error(ErrorKind::ExpectedKeyword(description), location);
+    }
+
+    token
+}
+
+fn expect_keyword<'t, 's>(state: &mut State<'t, 's>, keyword: &'static str) -> Option<()> {
+    expect_instruction_keyword(state, keyword, |word| if word == keyword { Some(()) } else { None })
+}
+
+fn expect_memory_ordering<'t, 's>(state: &mut State<'t, 's>) -> Option<ast::MemoryOrdering> {
+    expect_instruction_keyword(state, "memory ordering", |word| match word {
+        "relaxed" => Some(ast::MemoryOrdering::Relaxed),
+        "acquire" => Some(ast::MemoryOrdering::Acquire),
+        "release" => Some(ast::MemoryOrdering::Release),
+        "acq_rel" => Some(ast::MemoryOrdering::AcquireRelease),
+        "seq_cst" => Some(ast::MemoryOrdering::SequentiallyConsistent),
+        _ => None,
+    })
+}
+
+fn expect_lane_index<'t, 's>(state: &mut State<'t, 's>) -> Option<u16> {
+    match state.input.peek_next_token() {
+        Some(((Token::LiteralInteger(digits), _), location)) => {
+            state.input.next_token();
+            match u16::try_from(digits) {
+                Ok(lane) => Some(lane),
+                Err(e) => {
+                    state.push_error(ErrorKind::InvalidIntegerLiteral(e), location);
+                    None
+                }
+            }
+        }
+        bad => {
+            state.push_error(
+                ErrorKind::ExpectedKeyword("lane index"),
+                token_location_or_last(bad.as_ref(), state.locations()),
+            );
+            None
+        }
+    }
+}
+
+fn expect_closing_parenthesis<'t, 's>(state: &mut State<'t, 's>) -> Option<()> {
+    match state.input.peek_next_token() {
+        Some(((Token::CloseParenthesis, _), _)) => {
+            state.input.next_token();
+            Some(())
+        }
+        bad => {
+            state.push_error(
+                ErrorKind::ExpectedClosingParenthesis,
+                token_location_or_last(bad.as_ref(), state.locations()),
+            );
+            None
+        }
+    }
+}
+
+/// Parses the arguments of a branch or call instruction, which are optional and enclosed in parenthesis.
+fn parse_instruction_arguments<'t, 's>(state: &mut State<'t, 's>) -> Option<Box<[ast::Value<'s>]>> {
+    if state.input.next_token_if(|t| matches!(t, Token::OpenParenthesis)).is_none() {
+        return Some(Box::default());
+    }
+
+    let arguments = parse_instruction_value_list(state);
+    expect_closing_parenthesis(state)?;
+    Some(arguments)
+}
+
+fn parse_integer_arithmetic<'t, 's>(
+    state: &mut State<'t, 's>,
+) -> Option<(ast::OverflowBehavior, ast::Value<'s>, ast::Value<'s>)> {
+    let overflow_behavior = match state.input.peek_next_token() {
+        Some(((Token::Word("sat"), _), _)) => ast::OverflowBehavior::Saturate,
+        Some(((Token::Word("ovf"), _), _)) => ast::OverflowBehavior::Flag,
+        _ => ast::OverflowBehavior::Ignore,
+    };
+
+    if overflow_behavior != ast::OverflowBehavior::Ignore {
+        state.input.next_token();
+    }
+
+    let x = expect_instruction_value(state)?;
+    let y = expect_instruction_value(state)?;
+    Some((overflow_behavior, x, y))
+}
+
+/// Parses the operands of an instruction, returning `None` if an error occured.
+fn parse_instruction<'t, 's>(
+    state: &mut State<'t, 's>,
+    name: &str,
+    location: &ast::LocationRange,
+) -> Option<ast::Instruction<'s>> {
+    Some(match name {
+        "nop" => ast::Instruction::Nop,
+        "break" => ast::Instruction::Break,
+        "ret" | "return" => ast::Instruction::Ret(parse_instruction_value_list(state)),
+        "br" => {
+            let target = expect_instruction_reference(state)?;
+            ast::Instruction::Br(target, parse_instruction_arguments(state)?)
+        }
+        "br.if" => {
+            let condition = expect_instruction_value(state)?;
+            expect_keyword(state, "then")?;
+            let true_branch = expect_instruction_reference(state)?;
+            expect_keyword(state, "else")?;
+            let false_branch = expect_instruction_reference(state)?;
+            ast::Instruction::BrIf {
+                condition,
+                true_branch,
+                false_branch,
+                arguments: parse_instruction_arguments(state)?,
+            }
+        }
+        "call" => {
+            let callee = expect_instruction_reference(state)?;
+            ast::Instruction::Call(callee, parse_instruction_arguments(state)?)
+        }
+        "iadd" => {
+            let (overflow_behavior, x, y) = parse_integer_arithmetic(state)?;
+            ast::Instruction::IAdd(overflow_behavior, x, y)
+        }
+        "isub" => {
+            let (overflow_behavior, x, y) = parse_integer_arithmetic(state)?;
+            ast::Instruction::ISub(overflow_behavior, x, y)
+        }
+        "cmp" => {
+            let x = expect_instruction_value(state)?;
+            let kind = expect_instruction_keyword(state, "comparison kind", |word| match word {
+                "eq" => Some(ast::ComparisonKind::Equal),
+                "ne" => Some(ast::ComparisonKind::NotEqual),
+                "lt" => Some(ast::ComparisonKind::LessThan),
+                "gt" => Some(ast::ComparisonKind::GreaterThan),
+                "le" => Some(ast::ComparisonKind::LessThanOrEqual),
+                "ge" => Some(ast::ComparisonKind::GreaterThanOrEqual),
+                _ => None,
+            })?;
+            ast::Instruction::Cmp(x, kind, expect_instruction_value(state)?)
+        }
+        "thread.spawn" => {
+            let callee = expect_instruction_reference(state)?;
+            ast::Instruction::ThreadSpawn(callee, parse_instruction_arguments(state)?)
+        }
+        "thread.join" => {
+            let signature = expect_instruction_reference(state)?;
+            ast::Instruction::ThreadJoin(signature, expect_instruction_value(state)?)
+        }
+        "atomic.load" => {
+            let ordering = expect_memory_ordering(state)?;
+            ast::Instruction::AtomicLoad(ordering, expect_instruction_value(state)?)
+        }
+        "atomic.store" => {
+            let ordering = expect_memory_ordering(state)?;
+            let address = expect_instruction_value(state)?;
+            ast::Instruction::AtomicStore(ordering, address, expect_instruction_value(state)?)
+        }
+        "atomic.rmw" => {
+            let operation = expect_instruction_keyword(state, "atomic operation", |word| match word {
+                "xchg" => Some(ast::AtomicOperation::Exchange),
+                "add" => Some(ast::AtomicOperation::Add),
+                "sub" => Some(ast::AtomicOperation::Sub),
+                "and" => Some(ast::AtomicOperation::And),
+                "or" => Some(ast::AtomicOperation::Or),
+                "xor" => Some(ast::AtomicOperation::Xor),
+                _ => None,
+            })?;
+            let ordering = expect_memory_ordering(state)?;
+            let address = expect_instruction_value(state)?;
+            ast::Instruction::AtomicRmw(operation, ordering, address, expect_instruction_value(state)?)
+        }
+        "atomic.cmpxchg" => ast::Instruction::AtomicCmpXchg {
+            success_ordering: expect_memory_ordering(state)?,
+            failure_ordering: expect_memory_ordering(state)?,
+            address: expect_instruction_value(state)?,
+            expected: expect_instruction_value(state)?,
+            replacement: expect_instruction_value(state)?,
+        },
+        "fence" => ast::Instruction::Fence(expect_memory_ordering(state)?),
+        "vec.splat" => ast::Instruction::VSplat(expect_instruction_value(state)?),
+        "vec.extract" => {
+            let vector = expect_instruction_value(state)?;
+            ast::Instruction::VExtract(vector, expect_lane_index(state)?)
+        }
+        "vec.insert" => {
+            let vector = expect_instruction_value(state)?;
+            let lane = expect_lane_index(state)?;
+            ast::Instruction::VInsert(vector, lane, expect_instruction_value(state)?)
+        }
+        "vec.shuffle" => {
+            let x = expect_instruction_value(state)?;
+            let y = expect_instruction_value(state)?;
+
+            if state.input.next_token_if(|t| matches!(t, Token::OpenParenthesis)).is_none() {
+                let location = token_location_or_last(state.input.peek_next_token().as_ref(), state.locations());
+                state.push_error(ErrorKind::ExpectedKeyword("list of lane indices"), location);
+                return None;
+            }
+
+            let mut lanes = vec![expect_lane_index(state)?];
+            while state.input.next_token_if(|t| matches!(t, Token::Comma)).is_some() {
+                lanes.push(expect_lane_index(state)?);
+            }
+
+            expect_closing_parenthesis(state)?;
+            ast::Instruction::VShuffle(x, y, lanes.into_boxed_slice())
+        }
+        unknown => {
+            state.push_error(ErrorKind::UnknownInstruction(Box::from(unknown)), location.clone());
+            return None;
+        }
+    })
+}
+
 /// Transfers a sequence of tokens into an abstract syntax tree.
 pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
     let mut state = State {
@@ -619,6 +1007,44 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                             state.input.skip_current_line();
                         },
                     ),
+                    "main" => parse_reference(
+                        &mut state,
+                        |state, entry_point| {
+                            let end_location = entry_point.location().end().clone();
+                            state.expect_newline_or_end();
+                            state.output.tree.push(ast::Located::new(
+                                ast::Directive::Metadata(ast::Metadata::EntryPoint(entry_point)),
+                                start_location.start().clone(),
+                                end_location,
+                            ));
+                        },
+                        |state, bad| {
+                            state.push_error(
+                                ErrorKind::ExpectedEntryPoint,
+                                token_location_or_last(bad.as_ref(), state.locations()),
+                            );
+                            state.input.skip_current_line();
+                        },
+                    ),
+                    "noinline" => parse_reference(
+                        &mut state,
+                        |state, function| {
+                            let end_location = function.location().end().clone();
+                            state.expect_newline_or_end();
+                            state.output.tree.push(ast::Located::new(
+                                ast::Directive::Metadata(ast::Metadata::NoInline(function)),
+                                start_location.start().clone(),
+                                end_location,
+                            ));
+                        },
+                        |state, bad| {
+                            state.push_error(
+                                ErrorKind::ExpectedNoInlineFunction,
+                                token_location_or_last(bad.as_ref(), state.locations()),
+                            );
+                            state.input.skip_current_line();
+                        },
+                    ),
                     unknown => {
                         state.push_error(ErrorKind::UnknownMetadataFieldName(Box::from(unknown)), location);
                         state.input.skip_current_line();
@@ -708,12 +1134,12 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                         let signature_start_location;
                         let end_location;
                         let type_signature = match state.input.next_token() {
-                            Some(((Token::Word("rawptr"), _), pointer_type_location)) => {
-                                let mut pointee_type = None;
+                            Some(((Token::Word(pointer_kind @ ("rawptr" | "funcptr")), _), pointer_type_location)) => {
+                                let mut referenced_type = None;
 
                                 parse_reference(
                                     &mut state,
-                                    |_, pt| pointee_type = Some(pt),
+                                    |_, pt| referenced_type = Some(pt),
                                     |state, token| {
                                         state.push_error(
                                             ErrorKind::InvalidPointeeType,
@@ -724,10 +1150,14 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                                     },
                                 );
 
-                                if let Some(pointee_type) = pointee_type {
+                                if let Some(referenced_type) = referenced_type {
                                     signature_start_location = pointer_type_location.start().clone();
-                                    end_location = pointee_type.location().end().clone();
-                                    ast::TypeSignature::RawPtr(pointee_type)
+                                    end_location = referenced_type.location().end().clone();
+                                    if *pointer_kind == "rawptr" {
+                                        ast::TypeSignature::RawPtr(referenced_type)
+                                    } else {
+                                        ast::TypeSignature::FuncPtr(referenced_type)
+                                    }
                                 } else {
                                     continue;
                                 }
@@ -740,21 +1170,13 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                             Some(((Token::Word(primitive_type_name), _), location)) => {
                                 signature_start_location = location.start().clone();
                                 end_location = location.end().clone();
-                                match *primitive_type_name {
-                                    "u8" => ast::TypeSignature::U8,
-                                    "s8" => ast::TypeSignature::S8,
-                                    "u16" => ast::TypeSignature::U16,
-                                    "s16" => ast::TypeSignature::S16,
-                                    "u32" => ast::TypeSignature::U32,
-                                    "s32" => ast::TypeSignature::S32,
-                                    "u64" => ast::TypeSignature::U64,
-                                    "s64" => ast::TypeSignature::S64,
-                                    "uaddr" => ast::TypeSignature::UAddr,
-                                    "saddr" => ast::TypeSignature::SAddr,
-                                    "f32" => ast::TypeSignature::F32,
-                                    "f64" => ast::TypeSignature::F64,
-                                    bad => {
-                                        state.push_error(ErrorKind::UnknownPrimitiveType(Box::from(bad)), location);
+                                match parse_primitive_type(primitive_type_name) {
+                                    Some(primitive_type) => primitive_type,
+                                    None => {
+                                        state.push_error(
+                                            ErrorKind::UnknownPrimitiveType(Box::from(*primitive_type_name)),
+                                            location,
+                                        );
                                         state.input.skip_current_line();
                                         continue;
                                     }
@@ -784,80 +1206,12 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                         ));
                     }
                     Some(((Token::Word("function" | "func"), _), location)) => {
-                        fn function_types<'t, 's>(
-                            state: &mut State<'t, 's>,
-                            signature_location: &ast::Location,
-                            reference_buffer: &mut Vec<ast::Reference<'s>>,
-                            success: impl FnOnce(&mut State<'t, 's>, &mut Vec<ast::Reference<'s>>, ast::LocationRange),
-                        ) {
-                            let start_location;
-                            let mut end_location;
-                            match state.input.next_token() {
-                                Some(((Token::OpenParenthesis, _), location)) => {
-                                    start_location = location.start().clone();
-                                    end_location = std::cell::RefCell::new(location.end().clone());
-                                }
-                                bad => {
-                                    state.push_error(
-                                        ErrorKind::ExpectedFunctionSignatureTypeList,
-                                        token_location_or_else(bad.as_ref(), || {
-                                            ast::LocationRange::from(signature_location.clone())
-                                        }),
-                                    );
-                                    return;
-                                }
-                            }
-
-                            let mut parse_type_reference = |state: &mut State<'t, 's>| {
-                                parse_reference(
-                                    state,
-                                    |_, s| {
-                                        *end_location.borrow_mut() = s.location().end().clone();
-                                        reference_buffer.push(s);
-                                    },
-                                    |s, b| {
-                                        s.push_error(
-                                            ErrorKind::ExpectedReference,
-                                            token_location_or_else(b.as_ref(), || ast::LocationRange::from(end_location.clone())),
-                                        )
-                                    },
-                                )
-                            };
-
-                            parse_type_reference(state);
-
-                            while state.input.next_token_if(|t| matches!(t, Token::Comma)).is_some() {
-                                parse_type_reference(state);
-                            }
-
-                            match state.input.next_token() {
-                                Some(((Token::CloseParenthesis, _), location)) => {
-                                    *end_location.get_mut() = location.end().clone();
-                                }
-                                bad => {
-                                    state.push_error(
-                                        ErrorKind::ExpectedReference,
-                                        token_location_or_else(bad.as_ref(), || {
-                                            ast::LocationRange::from(end_location.into_inner())
-                                        }),
-                                    );
-                                    return;
-                                }
-                            }
-
-                            success(
-                                state,
-                                reference_buffer,
-                                ast::LocationRange::new(start_location, end_location.into_inner()),
-                            )
-                        }
-
                         let mut reference_buffer = std::mem::take(&mut state.reference_buffer);
                         reference_buffer.clear();
 
                         let start_location = location.end().clone();
 
-                        function_types(
+                        parse_type_reference_list(
                             &mut state,
                             &start_location,
                             &mut reference_buffer,
@@ -865,9 +1219,14 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                                 let parameter_type_count = reference_buffer.len();
                                 let mut result_types_location = None;
                                 if state.input.next_token_if(|t| matches!(t, Token::ResultSymbol)).is_some() {
-                                    function_types(state, parameter_types_location.end(), reference_buffer, |_, _, l| {
-                                        result_types_location = Some(l);
-                                    });
+                                    parse_type_reference_list(
+                                        state,
+                                        parameter_types_location.end(),
+                                        reference_buffer,
+                                        |_, _, l| {
+                                            result_types_location = Some(l);
+                                        },
+                                    );
                                 }
 
                                 state.expect_newline_or_end();
@@ -881,7 +1240,7 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                                     ast::Directive::Signature(
                                         symbol,
                                         ast::Located::new(
-                                            ast::Signature::Function(ast::FunctionSignature::from_vec(
+                                            ast::Signature::Function(ast::FunctionSignature::from_slice(
                                                 reference_buffer,
                                                 parameter_type_count,
                                             )),
@@ -917,7 +1276,7 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
 
                 let input_registers;
                 if state.input.next_token_if(|t| matches!(t, Token::OpenParenthesis)).is_some() {
-                    input_registers = parse_typed_register_list(&mut state);
+                    input_registers = parse_typed_register_list(&mut state, true);
 
                     match state.input.peek_next_token() {
                         Some(((Token::CloseParenthesis, _), _)) => {
@@ -933,9 +1292,19 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                 };
 
                 let result_types = match state.input.peek_next_token() {
-                    Some(((Token::ResultSymbol, _), _)) => {
+                    Some(((Token::ResultSymbol, _), location)) => {
                         state.input.next_token();
-                        todo!("parse result types of code block")
+
+                        let mut reference_buffer = std::mem::take(&mut state.reference_buffer);
+                        let mut result_types = Box::default();
+                        reference_buffer.clear();
+
+                        parse_type_reference_list(&mut state, location.end(), &mut reference_buffer, |_, types, _| {
+                            result_types = types.clone().into_boxed_slice();
+                        });
+
+                        state.reference_buffer = reference_buffer;
+                        result_types
                     }
                     Some(((Token::Newline, _), _)) | None => {
                         // Do not consume newline token or EOF, as following call expects a newline or EOF.
@@ -956,7 +1325,7 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                 loop {
                     assert!(is_start_of_line, "statements must begin at the start of a line");
 
-                    let temporary_registers = parse_typed_register_list(&mut state);
+                    let temporary_registers = parse_typed_register_list(&mut state, false);
 
                     if !temporary_registers.is_empty() {
                         match state.input.peek_next_token() {
@@ -978,12 +1347,10 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                             state.input.next_token();
                             end_location = location.end().clone();
 
-                            let instruction = match *instruction_name {
-                                "nop" => ast::Instruction::Nop,
-                                "break" => ast::Instruction::Break,
-                                "ret" => ast::Instruction::Ret(parse_instruction_value_list(&mut state)),
-                                unknown => {
-                                    state.push_error(ErrorKind::UnknownInstruction(Box::from(unknown)), location.clone());
+                            let instruction = match parse_instruction(&mut state, instruction_name, &location) {
+                                Some(instruction) => instruction,
+                                None => {
+                                    state.input.skip_to_end_of_line();
                                     ast::Instruction::Nop
                                 }
                             };
@@ -1151,9 +1518,53 @@ pub fn parse<'source>(input: &lexer::Output<'source>) -> Output<'source> {
                                         Some(((Token::Word("foreign"), _), _)) => {
                                             state.input.next_token();
 
-                                            parse_literal_identifier(
+                                            parse_literal_string(
                                                 state,
-                                                |state, function_name| todo!("foreign function bodies are not yet supported"),
+                                                |state, function_name| {
+                                                    let library = match state.input.next_token() {
+                                                        Some(((Token::Word("from"), _), _)) => {
+                                                            let mut library = None;
+                                                            parse_reference(
+                                                                state,
+                                                                |_, r| library = Some(r),
+                                                                |state, bad| {
+                                                                    state.push_error(
+                                                                        ErrorKind::ExpectedReference,
+                                                                        token_location_or_last(bad.as_ref(), state.locations()),
+                                                                    )
+                                                                },
+                                                            );
+                                                            library
+                                                        }
+                                                        bad => {
+                                                            state.push_error(
+                                                                ErrorKind::ExpectedKeyword("from"),
+                                                                token_location_or_last(bad.as_ref(), state.locations()),
+                                                            );
+                                                            None
+                                                        }
+                                                    };
+
+                                                    if let Some(library) = library {
+                                                        let end_location = library.location().end().clone();
+                                                        state.expect_newline_or_end();
+                                                        state.output.tree.push(ast::Located::new(
+                                                            ast::Directive::FunctionDefinition(
+                                                                symbol,
+                                                                ast::FunctionDefinition::new(
+                                                                    access_modifier,
+                                                                    identifier,
+                                                                    signature,
+                                                                    ast::FunctionBody::Foreign { function_name, library },
+                                                                ),
+                                                            ),
+                                                            start_location.start().clone(),
+                                                            end_location,
+                                                        ));
+                                                    } else {
+                                                        state.input.skip_current_line();
+                                                    }
+                                                },
                                                 |state, bad| {
                                                     state.push_error(
                                                         ErrorKind::ExpectedForeignFunctionIdentifier,
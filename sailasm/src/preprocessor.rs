@@ -0,0 +1,777 @@
+//! Expands include directives and macros before SAILAR assembly is parsed.
+//!
+//! ```text
+//! .include "common.sailasm" ; Paths are relative to the directory of the including file
+//!
+//! .macro binary_signature %name %type
+//! .signature %name function (%type, %type) -> (%type)
+//! .endmacro
+//!
+//! .expand binary_signature @add_signature @u32
+//! ```
+//!
+//! Parameters can also be written as `%{name}`, which allows them to be directly followed by other characters, as in
+//! `@%{name}_signature`. Macro arguments are separated by whitespace, and string literals or lists enclosed in parenthesis are treated as a
+//! single argument. Macros can expand other macros, and are available in any file processed after their definition.
+//!
+//! The preprocessor produces a single piece of text that can be parsed and assembled as usual, along with a mapping from
+//! every character in that text back to the file and line it came from. Use [`crate::assemble_preprocessed`] to report
+//! errors at their original locations.
+
+use crate::ast::{self, FileId, Location, LocationNumber, LocationRange};
+use crate::diagnostics::Label;
+use crate::lexer::split_comment;
+use std::borrow::Cow;
+use std::ops::Range;
+use std::path::{Component, Path, PathBuf};
+
+#[derive(Clone, Debug, thiserror::Error, PartialEq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    #[error("expected path to included file")]
+    ExpectedIncludePath,
+    #[error("could not include {path}: {reason}")]
+    IncludeFailed { path: Box<str>, reason: Box<str> },
+    #[error("{0} cannot be included by itself")]
+    IncludeCycle(Box<str>),
+    #[error("expected macro name")]
+    ExpectedMacroName,
+    #[error("expected macro parameter name starting with %")]
+    ExpectedMacroParameter,
+    #[error("macro {0} is defined more than once")]
+    DuplicateMacroDefinition(Box<str>),
+    #[error("macros cannot be defined inside of other macros")]
+    NestedMacroDefinition,
+    #[error("expected .endmacro at end of macro {0}")]
+    UnterminatedMacro(Box<str>),
+    #[error(".endmacro must be preceded by a .macro directive")]
+    UnexpectedEndMacro,
+    #[error("%{0} is not a parameter of the macro")]
+    UnknownMacroParameter(Box<str>),
+    #[error("{0} is not a known macro")]
+    UnknownMacro(Box<str>),
+    #[error("macro {name} expects {expected} arguments, but got {actual}")]
+    MacroArgumentCountMismatch { name: Box<str>, expected: usize, actual: usize },
+    #[error("macro {0} cannot be expanded inside of itself")]
+    RecursiveMacroExpansion(Box<str>),
+}
+
+#[derive(Clone, Debug, thiserror::Error)]
+#[error("{kind}")]
+pub struct Error {
+    kind: ErrorKind,
+    location: LocationRange,
+    labels: Vec<Label>,
+}
+
+impl Error {
+    pub fn new(kind: ErrorKind, location: LocationRange) -> Self {
+        Self {
+            kind,
+            location,
+            labels: Vec::new(),
+        }
+    }
+
+    /// Attaches a secondary location to this error.
+    pub fn with_label<M: Into<Cow<'static, str>>>(mut self, location: LocationRange, message: M) -> Self {
+        self.labels.push(Label::new(location, message));
+        self
+    }
+
+    #[inline]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    #[inline]
+    pub fn location(&self) -> &LocationRange {
+        &self.location
+    }
+
+    /// Secondary locations in the source code related to this error.
+    #[inline]
+    pub fn labels(&self) -> &[Label] {
+        &self.labels
+    }
+}
+
+/// Maps a run of characters in a line of output back to where they came from.
+#[derive(Clone, Debug)]
+struct Segment {
+    /// The column in the line of output where the run starts.
+    column: usize,
+    file: FileId,
+    line: LocationNumber,
+    /// The column in the original line where the run starts.
+    original_column: usize,
+}
+
+/// Gets the file, line, and column that the character at the given column originally came from.
+fn origin(segments: &[Segment], column: usize) -> (FileId, LocationNumber, usize) {
+    let segment = segments
+        .iter()
+        .rev()
+        .find(|segment| segment.column <= column)
+        .or_else(|| segments.first())
+        .expect("lines should have at least one segment");
+
+    (
+        segment.file,
+        segment.line,
+        segment.original_column + column.saturating_sub(segment.column),
+    )
+}
+
+/// A line of input, along with where each of its characters came from.
+#[derive(Clone, Debug)]
+struct SourceLine {
+    text: Box<str>,
+    segments: Vec<Segment>,
+}
+
+impl SourceLine {
+    fn new(file: FileId, line: usize, text: &str) -> Self {
+        Self {
+            text: Box::from(text),
+            segments: vec![Segment {
+                column: 1,
+                file,
+                line: LocationNumber::new(line).expect("line numbers start at 1"),
+                original_column: 1,
+            }],
+        }
+    }
+
+    fn column(&self, offset: usize) -> usize {
+        self.text[..offset].chars().count() + 1
+    }
+
+    fn location(&self, range: Range<usize>) -> LocationRange {
+        let (file, line, column) = origin(&self.segments, self.column(range.start));
+        let length = self.text[range].chars().count();
+        let location = |column| Location::new(line, LocationNumber::new(column).expect("columns start at 1"));
+        LocationRange::new(location(column), location(column + length)).with_file(file)
+    }
+
+    fn file(&self) -> FileId {
+        self.segments[0].file
+    }
+}
+
+#[derive(Default)]
+struct LineBuilder {
+    text: String,
+    length: usize,
+    segments: Vec<Segment>,
+}
+
+impl LineBuilder {
+    /// Appends part of another line, keeping track of where each of the appended characters came from.
+    fn push(&mut self, source: &SourceLine, range: Range<usize>) {
+        let start = source.column(range.start);
+        let text = &source.text[range];
+        let end = start + text.chars().count();
+
+        for (index, segment) in source.segments.iter().enumerate() {
+            let segment_end = source.segments.get(index + 1).map_or(usize::MAX, |next| next.column);
+            if segment_end > start && segment.column < end {
+                let from = segment.column.max(start);
+                self.segments.push(Segment {
+                    column: self.length + 1 + from - start,
+                    file: segment.file,
+                    line: segment.line,
+                    original_column: segment.original_column + from - segment.column,
+                });
+            }
+        }
+
+        self.text.push_str(text);
+        self.length += end - start;
+    }
+
+    /// Completes the line, using the start of the `fallback` line as the origin if the line is empty.
+    fn finish(mut self, fallback: &SourceLine) -> SourceLine {
+        if self.segments.is_empty() {
+            self.segments.push(fallback.segments[0].clone());
+        }
+
+        SourceLine {
+            text: self.text.into_boxed_str(),
+            segments: self.segments,
+        }
+    }
+}
+
+/// Splits code into words separated by whitespace, where string literals and lists enclosed in parenthesis are kept
+/// together.
+fn split_words(code: &str) -> Vec<Range<usize>> {
+    let mut words = Vec::new();
+    let mut start = None;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (offset, c) in code.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => (),
+            }
+            continue;
+        }
+
+        match c {
+            _ if c.is_whitespace() && depth == 0 => {
+                if let Some(start) = start.take() {
+                    words.push(start..offset);
+                }
+                continue;
+            }
+            '"' => in_string = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ => (),
+        }
+
+        start.get_or_insert(offset);
+    }
+
+    if let Some(start) = start {
+        words.push(start..code.len());
+    }
+
+    words
+}
+
+/// Gets the words on a line if it starts with a directive that is handled by the preprocessor.
+pub(crate) fn directive_words(line: &str) -> Option<Vec<&str>> {
+    let (code, _) = split_comment(line);
+    let words = split_words(code).into_iter().map(|word| &code[word]).collect::<Vec<_>>();
+    match words.first() {
+        Some(&(".include" | ".macro" | ".endmacro" | ".expand")) => Some(words),
+        _ => None,
+    }
+}
+
+fn is_name(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'_')
+}
+
+/// Finds all uses of macro parameters such as `%name` or `%{name}`, returning the range of each use along with the range
+/// of the parameter name.
+fn parameter_uses(code: &str) -> Vec<(Range<usize>, Range<usize>)> {
+    let bytes = code.as_bytes();
+    let is_name_byte = |b: &&u8| b.is_ascii_alphanumeric() || **b == b'_';
+    let mut uses = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        if bytes[offset] == b'%' {
+            let braced = bytes.get(offset + 1) == Some(&b'{');
+            let name_start = offset + 1 + usize::from(braced);
+            let name_end = name_start + bytes[name_start..].iter().take_while(is_name_byte).count();
+            let end = if braced {
+                Some(name_end + 1).filter(|_| bytes.get(name_end) == Some(&b'}'))
+            } else {
+                Some(name_end)
+            };
+
+            if let Some(end) = end.filter(|_| name_end > name_start) {
+                uses.push((offset..end, name_start..name_end));
+                offset = end;
+                continue;
+            }
+        }
+        offset += 1;
+    }
+    uses
+}
+
+/// Removes `.` and `..` components from a path without accessing the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => (),
+            Component::ParentDir if matches!(normalized.components().next_back(), Some(Component::Normal(_))) => {
+                normalized.pop();
+            }
+            other => normalized.push(other.as_os_str()),
+        }
+    }
+    normalized
+}
+
+#[derive(Debug)]
+pub struct SourceFile {
+    path: PathBuf,
+    contents: String,
+}
+
+impl SourceFile {
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    #[inline]
+    pub fn contents(&self) -> &str {
+        &self.contents
+    }
+}
+
+#[derive(Debug)]
+pub struct Output {
+    files: Vec<SourceFile>,
+    text: String,
+    lines: Vec<Vec<Segment>>,
+    errors: Vec<Error>,
+}
+
+impl Output {
+    /// The preprocessed source code, with all includes and macros expanded.
+    #[inline]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// All files that were read, where the position of each file corresponds to its [`FileId`].
+    #[inline]
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    #[inline]
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    /// Maps a location in the preprocessed [`text`](Output::text) back to the file and line where it came from.
+    pub fn original_location(&self, location: &LocationRange) -> LocationRange {
+        // The end of a range is the column after the last character, which may have come from somewhere else entirely.
+        let find_origin = |location: &Location, offset: usize| {
+            let segments = self.lines.get(location.line.get() - 1).or_else(|| self.lines.last())?;
+            let (file, line, column) = origin(segments, location.column.get().saturating_sub(offset));
+            Some((file, Location::new(line, LocationNumber::new(column + offset)?)))
+        };
+
+        let end_offset = usize::from(location.end() > location.start());
+        match (find_origin(location.start(), 0), find_origin(location.end(), end_offset)) {
+            (Some((file, start)), Some((end_file, end))) if file == end_file && end >= start => {
+                LocationRange::new(start, end).with_file(file)
+            }
+            (Some((file, start)), _) => LocationRange::from(start).with_file(file),
+            (None, _) => location.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Macro {
+    location: LocationRange,
+    parameters: Vec<Box<str>>,
+    body: Vec<SourceLine>,
+}
+
+struct Preprocessor<'l> {
+    loader: &'l mut dyn FnMut(&Path) -> std::io::Result<String>,
+    output: Output,
+    macros: rustc_hash::FxHashMap<Box<str>, Macro>,
+    /// The paths of the files currently being included, used to detect cycles.
+    includes: Vec<PathBuf>,
+    /// The names of the macros currently being expanded, used to detect recursion.
+    expansions: Vec<Box<str>>,
+}
+
+impl Preprocessor<'_> {
+    fn push_error(&mut self, error: Error) {
+        self.output.errors.push(error);
+    }
+
+    fn emit(&mut self, line: SourceLine) {
+        self.output.text.push_str(&line.text);
+        self.output.text.push('\n');
+        self.output.lines.push(line.segments);
+    }
+
+    fn file(&mut self, file: FileId) {
+        let lines = self.output.files[file.index()]
+            .contents
+            .lines()
+            .enumerate()
+            .map(|(index, text)| SourceLine::new(file, index + 1, text))
+            .collect::<Vec<_>>();
+
+        let mut lines = lines.into_iter();
+        while let Some(line) = lines.next() {
+            self.line(line, &mut lines);
+        }
+    }
+
+    fn line(&mut self, line: SourceLine, remaining: &mut dyn Iterator<Item = SourceLine>) {
+        let (code, _) = split_comment(&line.text);
+        let words = split_words(code);
+        match words.first().map(|word| &code[word.clone()]) {
+            Some(".include") => self.include(&line, &words),
+            Some(".macro") => self.define_macro(&line, &words, remaining),
+            Some(".endmacro") => self.push_error(Error::new(ErrorKind::UnexpectedEndMacro, line.location(words[0].clone()))),
+            Some(".expand") => self.expand(&line, &words),
+            _ => self.emit(line),
+        }
+    }
+
+    fn include(&mut self, line: &SourceLine, words: &[Range<usize>]) {
+        let path = match words {
+            [_, path] => line.text[path.clone()]
+                .strip_prefix('"')
+                .and_then(|path| path.strip_suffix('"'))
+                .and_then(|path| ast::LiteralString::try_from(path).ok())
+                .map(|path| PathBuf::from(path.as_str())),
+            _ => None,
+        };
+
+        let location = line.location(words.get(1).unwrap_or(&words[0]).clone());
+        let path = match path {
+            Some(path) => path,
+            None => return self.push_error(Error::new(ErrorKind::ExpectedIncludePath, location)),
+        };
+
+        let including_file = &self.output.files[line.file().index()].path;
+        let path = normalize(&including_file.parent().unwrap_or_else(|| Path::new("")).join(path));
+
+        if self.includes.contains(&path) {
+            return self.push_error(Error::new(
+                ErrorKind::IncludeCycle(Box::from(path.to_string_lossy())),
+                location,
+            ));
+        }
+
+        match (self.loader)(&path) {
+            Ok(contents) => {
+                let file = FileId::new(self.output.files.len());
+                self.output.files.push(SourceFile {
+                    path: path.clone(),
+                    contents,
+                });
+                self.includes.push(path);
+                self.file(file);
+                self.includes.pop();
+            }
+            Err(error) => self.push_error(Error::new(
+                ErrorKind::IncludeFailed {
+                    path: Box::from(path.to_string_lossy()),
+                    reason: Box::from(error.to_string()),
+                },
+                location,
+            )),
+        }
+    }
+
+    fn define_macro(&mut self, line: &SourceLine, words: &[Range<usize>], remaining: &mut dyn Iterator<Item = SourceLine>) {
+        let name_word = words.get(1).unwrap_or(&words[0]).clone();
+        let location = line.location(name_word.clone());
+        let name = Some(&line.text[name_word]).filter(|name| words.len() > 1 && is_name(name));
+        if name.is_none() {
+            self.push_error(Error::new(ErrorKind::ExpectedMacroName, location.clone()));
+        }
+
+        let mut parameters = Vec::new();
+        for word in words.iter().skip(2) {
+            match line.text[word.clone()].strip_prefix('%').filter(|name| is_name(name)) {
+                Some(parameter) => parameters.push(Box::<str>::from(parameter)),
+                None => self.push_error(Error::new(ErrorKind::ExpectedMacroParameter, line.location(word.clone()))),
+            }
+        }
+
+        let mut body = Vec::new();
+        let mut terminated = false;
+        for body_line in remaining {
+            let (code, _) = split_comment(&body_line.text);
+            match split_words(code).first() {
+                Some(word) if &code[word.clone()] == ".endmacro" => {
+                    terminated = true;
+                    break;
+                }
+                Some(word) if &code[word.clone()] == ".macro" => {
+                    self.push_error(Error::new(ErrorKind::NestedMacroDefinition, body_line.location(word.clone())));
+                }
+                _ => {
+                    for (usage, name) in parameter_uses(code) {
+                        let parameter = &code[name];
+                        if !parameters.iter().any(|name| **name == *parameter) {
+                            self.push_error(Error::new(
+                                ErrorKind::UnknownMacroParameter(Box::from(parameter)),
+                                body_line.location(usage),
+                            ));
+                        }
+                    }
+
+                    body.push(body_line);
+                }
+            }
+        }
+
+        if !terminated {
+            self.push_error(Error::new(
+                ErrorKind::UnterminatedMacro(Box::from(name.unwrap_or_default())),
+                location.clone(),
+            ));
+        }
+
+        if let Some(name) = name {
+            match self.macros.get(name) {
+                Some(existing) => {
+                    let error = Error::new(ErrorKind::DuplicateMacroDefinition(Box::from(name)), location)
+                        .with_label(existing.location.clone(), "first defined here");
+                    self.push_error(error);
+                }
+                None => {
+                    self.macros.insert(
+                        Box::from(name),
+                        Macro {
+                            location,
+                            parameters,
+                            body,
+                        },
+                    );
+                }
+            }
+        }
+    }
+
+    fn expand(&mut self, line: &SourceLine, words: &[Range<usize>]) {
+        let name_word = match words.get(1) {
+            Some(word) => word.clone(),
+            None => return self.push_error(Error::new(ErrorKind::ExpectedMacroName, line.location(words[0].clone()))),
+        };
+
+        let name = &line.text[name_word.clone()];
+        let location = line.location(name_word);
+        let arguments = &words[2..];
+
+        let definition = match self.macros.get(name) {
+            Some(definition) => definition,
+            None => return self.push_error(Error::new(ErrorKind::UnknownMacro(Box::from(name)), location)),
+        };
+
+        if definition.parameters.len() != arguments.len() {
+            let error = Error::new(
+                ErrorKind::MacroArgumentCountMismatch {
+                    name: Box::from(name),
+                    expected: definition.parameters.len(),
+                    actual: arguments.len(),
+                },
+                location,
+            )
+            .with_label(definition.location.clone(), "macro defined here");
+            return self.push_error(error);
+        }
+
+        if self.expansions.iter().any(|expansion| **expansion == *name) {
+            return self.push_error(Error::new(ErrorKind::RecursiveMacroExpansion(Box::from(name)), location));
+        }
+
+        let expanded = definition
+            .body
+            .iter()
+            .map(|body_line| {
+                let (code, _) = split_comment(&body_line.text);
+                let mut builder = LineBuilder::default();
+                let mut copied = 0;
+                for (usage, name) in parameter_uses(code) {
+                    let parameter = &code[name];
+                    if let Some(index) = definition.parameters.iter().position(|name| **name == *parameter) {
+                        builder.push(body_line, copied..usage.start);
+                        builder.push(line, arguments[index].clone());
+                        copied = usage.end;
+                    }
+                }
+                builder.push(body_line, copied..body_line.text.len());
+                builder.finish(body_line)
+            })
+            .collect::<Vec<_>>();
+
+        self.expansions.push(Box::from(name));
+        let mut expanded = expanded.into_iter();
+        while let Some(expanded_line) = expanded.next() {
+            self.line(expanded_line, &mut expanded);
+        }
+        self.expansions.pop();
+    }
+}
+
+/// Expands the includes and macros in the file at the given `path`, using the `loader` to read the contents of each file.
+///
+/// # Errors
+///
+/// Returns an error if the `loader` could not read the file at the given `path`, errors that occur in included files are
+/// instead included in the [`Output`].
+pub fn preprocess<L: FnMut(&Path) -> std::io::Result<String>>(path: &Path, mut loader: L) -> std::io::Result<Output> {
+    let contents = loader(path)?;
+    let path = normalize(path);
+    let mut preprocessor = Preprocessor {
+        loader: &mut loader,
+        output: Output {
+            files: vec![SourceFile {
+                path: path.clone(),
+                contents,
+            }],
+            text: String::new(),
+            lines: Vec::new(),
+            errors: Vec::new(),
+        },
+        macros: Default::default(),
+        includes: vec![path],
+        expansions: Vec::new(),
+    };
+
+    preprocessor.file(FileId::ROOT);
+    Ok(preprocessor.output)
+}
+
+/// Expands the includes and macros in the file at the given `path`, reading files from the file system.
+pub fn preprocess_file(path: &Path) -> std::io::Result<Output> {
+    preprocess(path, |path| std::fs::read_to_string(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ast::FileId;
+    use crate::preprocessor::{preprocess, ErrorKind, Output};
+    use std::path::{Path, PathBuf};
+
+    fn preprocess_files(files: &[(&str, &str)]) -> Output {
+        let files = files
+            .iter()
+            .map(|(path, contents)| (PathBuf::from(path), contents.to_string()))
+            .collect::<std::collections::HashMap<_, _>>();
+
+        preprocess(Path::new("main.sailasm"), |path| {
+            files
+                .get(path)
+                .cloned()
+                .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
+        })
+        .unwrap()
+    }
+
+    const COMMON: &str = r#".signature @u32 type u32
+
+.macro binary %name %operation
+.signature @%{name}_signature function (@u32, @u32) -> (@u32)
+.code @%{name}_body ($x:@u32, $y:@u32) -> (@u32)
+    $result:@u32 = %operation $x $y
+    ret $result
+.define @%name function export "%name" signature @%{name}_signature
+    /body @%{name}_body
+.endmacro
+"#;
+
+    #[test]
+    fn includes_and_macros_are_expanded() {
+        let output = preprocess_files(&[
+            (
+                "main.sailasm",
+                ".format major 0\n.format minor 19\n.include \"lib/common.sailasm\"\n.expand binary add iadd\n.expand binary sub isub\n",
+            ),
+            ("lib/common.sailasm", COMMON),
+        ]);
+
+        assert!(output.errors().is_empty(), "{:?}", output.errors());
+        assert_eq!(Path::new("lib/common.sailasm"), output.files()[1].path());
+        assert!(output
+            .text()
+            .contains(".define @sub function export \"sub\" signature @sub_signature\n"));
+
+        let mut tree = None;
+        let module = crate::assemble_preprocessed(&output, &mut tree).unwrap();
+        let mut buffer = Vec::new();
+        module.write_to(&mut buffer).unwrap();
+    }
+
+    #[test]
+    fn errors_point_to_original_locations() {
+        let output = preprocess_files(&[
+            (
+                "main.sailasm",
+                ".format major 0\n.format minor 19\n.include \"common.sailasm\"\n\n.expand binary add imul\n",
+            ),
+            ("common.sailasm", COMMON),
+        ]);
+
+        let mut tree = None;
+        let errors = crate::assemble_preprocessed(&output, &mut tree).unwrap_err();
+        let location = errors[0].location().unwrap();
+        assert_eq!(FileId::ROOT, location.file());
+        assert_eq!((5, 20), location.start().clone().into());
+        assert_eq!((5, 24), location.end().clone().into());
+
+        let renderer = crate::diagnostics::Renderer::new(output.files()[0].contents())
+            .with_path("main.sailasm")
+            .with_file("common.sailasm", output.files()[1].contents());
+
+        assert_eq!(
+            renderer.render_to_string(&errors[0]),
+            "error: imul is not a known instruction\n --> main.sailasm:5:20\n  |\n5 | .expand binary add imul\n  |                    ^^^^\n"
+        );
+    }
+
+    #[test]
+    fn errors_in_included_files_refer_to_those_files() {
+        let output = preprocess_files(&[
+            (
+                "main.sailasm",
+                ".format major 0\n.format minor 19\n\n.include \"other.sailasm\"\n",
+            ),
+            ("other.sailasm", ".signature @t type u32\n.signature @t type u64\n"),
+        ]);
+
+        let mut tree = None;
+        let errors = crate::assemble_preprocessed(&output, &mut tree).unwrap_err();
+        let location = errors[0].location().unwrap();
+        assert_eq!(1, location.file().index());
+        assert_eq!((2, 12), location.start().clone().into());
+        assert_eq!(1, errors[0].labels()[0].location().start().line.get());
+    }
+
+    #[test]
+    fn include_cycles_are_detected() {
+        let output = preprocess_files(&[
+            ("main.sailasm", ".include \"a.sailasm\"\n"),
+            ("a.sailasm", ".include \"./b.sailasm\"\n"),
+            ("b.sailasm", ".include \"main.sailasm\"\n"),
+        ]);
+
+        assert_eq!(
+            output.errors().iter().map(|error| error.kind()).collect::<Vec<_>>(),
+            vec![&ErrorKind::IncludeCycle(Box::from("main.sailasm"))]
+        );
+        assert_eq!(2, output.errors()[0].location().file().index());
+    }
+
+    #[test]
+    fn macro_errors_are_reported() {
+        let output = preprocess_files(&[(
+            "main.sailasm",
+            ".macro m %a\n.expand m %b\n.endmacro\n.expand m 1 2\n.expand m 1\n.expand n\n.endmacro\n.macro m\n.endmacro\n",
+        )]);
+
+        assert_eq!(
+            output.errors().iter().map(|error| error.kind().clone()).collect::<Vec<_>>(),
+            vec![
+                ErrorKind::UnknownMacroParameter(Box::from("b")),
+                ErrorKind::MacroArgumentCountMismatch {
+                    name: Box::from("m"),
+                    expected: 1,
+                    actual: 2
+                },
+                ErrorKind::RecursiveMacroExpansion(Box::from("m")),
+                ErrorKind::UnknownMacro(Box::from("n")),
+                ErrorKind::UnexpectedEndMacro,
+                ErrorKind::DuplicateMacroDefinition(Box::from("m")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Collects the symbols defined and referenced in a SAILAR assembly document.
+
+use sailasm::ast::{self, Location, LocationRange};
+use sailasm::AnyError;
+
+/// The names of all top-level directives, without the leading `.`.
+pub const DIRECTIVES: &[&str] = &[
+    "array",
+    "format",
+    "metadata",
+    "identifier",
+    "data",
+    "signature",
+    "code",
+    "define",
+    "instantiate",
+];
+
+/// The names of all nested directives, without the leading `/`.
+pub const NESTED_DIRECTIVES: &[&str] = &["body"];
+
+/// The mnemonics of all instructions that can appear in a code block.
+pub const INSTRUCTIONS: &[&str] = &[
+    "nop",
+    "break",
+    "ret",
+    "br",
+    "br.if",
+    "call",
+    "iadd",
+    "isub",
+    "cmp",
+    "thread.spawn",
+    "thread.join",
+    "atomic.load",
+    "atomic.store",
+    "atomic.rmw",
+    "atomic.cmpxchg",
+    "fence",
+    "vec.splat",
+    "vec.extract",
+    "vec.insert",
+    "vec.shuffle",
+];
+
+/// The kind of directive that defines a symbol.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DefinitionKind {
+    Identifier,
+    Data,
+    TypeSignature,
+    FunctionSignature,
+    CodeBlock,
+    Function,
+    FunctionInstantiation,
+}
+
+/// A symbol defined by a directive.
+#[derive(Clone, Debug)]
+pub struct Definition {
+    name: Box<str>,
+    kind: DefinitionKind,
+    location: LocationRange,
+    declaration: Box<str>,
+    /// Another symbol whose declaration is relevant to this one, such as the signature of a function.
+    related: Option<Box<str>>,
+}
+
+impl Definition {
+    /// The name of the symbol, without the leading `@`.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn kind(&self) -> DefinitionKind {
+        self.kind
+    }
+
+    /// The location of the symbol in the directive that defines it.
+    #[inline]
+    pub fn location(&self) -> &LocationRange {
+        &self.location
+    }
+
+    /// The first line of the directive that defines the symbol.
+    #[inline]
+    pub fn declaration(&self) -> &str {
+        &self.declaration
+    }
+}
+
+/// A use of a symbol by name.
+#[derive(Clone, Debug)]
+pub struct Reference {
+    name: Box<str>,
+    location: LocationRange,
+}
+
+impl Reference {
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[inline]
+    pub fn location(&self) -> &LocationRange {
+        &self.location
+    }
+}
+
+/// The result of parsing and assembling a document.
+#[derive(Debug, Default)]
+pub struct Analysis {
+    errors: Vec<AnyError>,
+    definitions: Vec<Definition>,
+    references: Vec<Reference>,
+}
+
+/// Checks that the location is within the range, allowing a cursor placed directly after a symbol to refer to it.
+fn contains(range: &LocationRange, location: &Location) -> bool {
+    range.start() <= location && location <= range.end()
+}
+
+impl Analysis {
+    pub fn new(source: &str) -> Self {
+        let mut tree = None;
+        let errors = sailasm::assemble(source, &mut tree).err().unwrap_or_default();
+        let mut analysis = Self {
+            errors,
+            ..Self::default()
+        };
+
+        if let Some(tree) = &tree {
+            let lines = source.lines().collect::<Vec<_>>();
+            for directive in tree.tree() {
+                analysis.add_directive(&lines, directive);
+            }
+        }
+
+        analysis
+    }
+
+    fn add_definition(
+        &mut self,
+        lines: &[&str],
+        directive: &ast::Located<ast::Directive>,
+        symbol: Option<&ast::Symbol>,
+        kind: DefinitionKind,
+        related: Option<&ast::Reference>,
+    ) {
+        if let Some(symbol) = symbol {
+            let declaration = lines
+                .get(directive.location().start().line.get() - 1)
+                .copied()
+                .unwrap_or_default();
+
+            self.definitions.push(Definition {
+                name: Box::from(symbol.item().as_str()),
+                kind,
+                location: symbol.location().clone(),
+                declaration: Box::from(declaration.trim()),
+                related: match related {
+                    Some(ast::Reference::Label(label)) => Some(Box::from(label.item().as_str())),
+                    _ => None,
+                },
+            });
+        }
+    }
+
+    fn add_reference(&mut self, reference: &ast::Reference) {
+        if let ast::Reference::Label(label) = reference {
+            self.references.push(Reference {
+                name: Box::from(label.item().as_str()),
+                location: label.location().clone(),
+            });
+        }
+    }
+
+    fn add_directive(&mut self, lines: &[&str], directive: &ast::Located<ast::Directive>) {
+        match directive.item() {
+            ast::Directive::Array | ast::Directive::Format(_, _) => (),
+            ast::Directive::Metadata(ast::Metadata::Identifier(_, _)) => (),
+            ast::Directive::Metadata(ast::Metadata::EntryPoint(entry_point)) => self.add_reference(entry_point),
+            ast::Directive::Metadata(ast::Metadata::NoInline(function)) => self.add_reference(function),
+            ast::Directive::Identifier(symbol, _) => {
+                self.add_definition(lines, directive, symbol.as_ref(), DefinitionKind::Identifier, None)
+            }
+            ast::Directive::Data(symbol, _) => self.add_definition(lines, directive, symbol.as_ref(), DefinitionKind::Data, None),
+            ast::Directive::Signature(symbol, signature) => match signature.item() {
+                ast::Signature::Type(signature) => {
+                    self.add_definition(lines, directive, symbol.as_ref(), DefinitionKind::TypeSignature, None);
+                    match signature {
+                        ast::TypeSignature::RawPtr(pointee) => self.add_reference(pointee),
+                        ast::TypeSignature::FuncPtr(signature) => self.add_reference(signature),
+                        _ => (),
+                    }
+                }
+                ast::Signature::Function(signature) => {
+                    self.add_definition(lines, directive, symbol.as_ref(), DefinitionKind::FunctionSignature, None);
+                    for parameter_type in signature.parameter_types() {
+                        self.add_reference(parameter_type);
+                    }
+                    for return_type in signature.return_types() {
+                        self.add_reference(return_type);
+                    }
+                }
+            },
+            ast::Directive::Code(symbol, code) => {
+                self.add_definition(lines, directive, symbol.as_ref(), DefinitionKind::CodeBlock, None);
+                for register in code.input_registers() {
+                    if let Some(value_type) = register.item().value_type() {
+                        self.add_reference(value_type);
+                    }
+                }
+                for result_type in code.result_types() {
+                    self.add_reference(result_type);
+                }
+                for statement in code.statements() {
+                    for register in statement.results() {
+                        if let Some(value_type) = register.item().value_type() {
+                            self.add_reference(value_type);
+                        }
+                    }
+                    self.add_instruction(statement.instruction().item());
+                }
+            }
+            ast::Directive::FunctionDefinition(symbol, definition) => {
+                self.add_definition(
+                    lines,
+                    directive,
+                    symbol.as_ref(),
+                    DefinitionKind::Function,
+                    Some(definition.signature()),
+                );
+                self.add_reference(definition.signature());
+                match definition.body() {
+                    ast::FunctionBody::Defined(blocks) => {
+                        for block in blocks.iter() {
+                            self.add_reference(block);
+                        }
+                    }
+                    ast::FunctionBody::Foreign { library, .. } => self.add_reference(library),
+                }
+            }
+            ast::Directive::FunctionInstantiation(symbol, template) => {
+                let (ast::DefinitionOrImport::Definition(template) | ast::DefinitionOrImport::Import(template)) = template;
+                self.add_definition(
+                    lines,
+                    directive,
+                    symbol.as_ref(),
+                    DefinitionKind::FunctionInstantiation,
+                    Some(template),
+                );
+                self.add_reference(template);
+            }
+        }
+    }
+
+    fn add_instruction(&mut self, instruction: &ast::Instruction) {
+        match instruction {
+            ast::Instruction::Br(target, _)
+            | ast::Instruction::Call(target, _)
+            | ast::Instruction::ThreadSpawn(target, _)
+            | ast::Instruction::ThreadJoin(target, _) => self.add_reference(target),
+            ast::Instruction::BrIf {
+                true_branch,
+                false_branch,
+                ..
+            } => {
+                self.add_reference(true_branch);
+                self.add_reference(false_branch);
+            }
+            _ => (),
+        }
+    }
+
+    /// Errors encountered while parsing or assembling the document.
+    #[inline]
+    pub fn errors(&self) -> &[AnyError] {
+        &self.errors
+    }
+
+    #[inline]
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+
+    /// Gets the name of the symbol defined or referenced at the given location.
+    pub fn symbol_at(&self, location: &Location) -> Option<&str> {
+        self.definitions
+            .iter()
+            .map(|definition| (definition.name(), definition.location()))
+            .chain(
+                self.references
+                    .iter()
+                    .map(|reference| (reference.name(), reference.location())),
+            )
+            .find(|(_, range)| contains(range, location))
+            .map(|(name, _)| name)
+    }
+
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().find(|definition| definition.name() == name)
+    }
+
+    /// Gets the locations of all uses of the symbol with the given name, not including its definition.
+    pub fn references<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a LocationRange> + 'a {
+        self.references
+            .iter()
+            .filter(move |reference| reference.name() == name)
+            .map(Reference::location)
+    }
+
+    /// Gets the declaration of a symbol, followed by the declarations of any signatures or definitions it refers to.
+    pub fn hover(&self, name: &str) -> Option<Vec<&str>> {
+        let mut definition = self.definition(name)?;
+        let mut declarations = vec![definition.declaration()];
+
+        while let Some(related) = definition.related.as_deref() {
+            match self.definition(related) {
+                Some(next) if !declarations.contains(&next.declaration()) => {
+                    declarations.push(next.declaration());
+                    definition = next;
+                }
+                _ => break,
+            }
+        }
+
+        Some(declarations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analysis::{Analysis, DefinitionKind};
+    use sailasm::ast::{Location, LocationNumber};
+
+    const INPUT: &str = r#".format major 0
+.format minor 19
+.signature @u32 type u32
+.signature @add_signature function (@u32, @u32) -> (@u32)
+.code @add_body ($x:@u32, $y:@u32) -> (@u32)
+    $sum:@u32 = iadd sat $x $y
+    ret $sum
+.define @add function export "add" signature @add_signature
+    /body @add_body
+.instantiate @add_instance function definition @add
+"#;
+
+    fn location(line: usize, column: usize) -> Location {
+        Location::new(LocationNumber::new(line).unwrap(), LocationNumber::new(column).unwrap())
+    }
+
+    #[test]
+    fn definitions_and_references_are_collected() {
+        let analysis = Analysis::new(INPUT);
+        assert!(analysis.errors().is_empty(), "{:?}", analysis.errors());
+        assert_eq!(Some("u32"), analysis.symbol_at(&location(4, 38)));
+        assert_eq!(DefinitionKind::TypeSignature, analysis.definition("u32").unwrap().kind());
+        assert_eq!(7, analysis.references("u32").count());
+        assert_eq!(Some("add_body"), analysis.symbol_at(&location(9, 11)));
+        assert_eq!(&location(5, 7), analysis.definition("add_body").unwrap().location().start());
+    }
+
+    #[test]
+    fn hover_includes_related_signatures() {
+        let analysis = Analysis::new(INPUT);
+        assert_eq!(
+            Some(vec![
+                ".instantiate @add_instance function definition @add",
+                ".define @add function export \"add\" signature @add_signature",
+                ".signature @add_signature function (@u32, @u32) -> (@u32)",
+            ]),
+            analysis.hover("add_instance")
+        );
+    }
+}
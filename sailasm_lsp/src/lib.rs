@@ -0,0 +1,7 @@
+//! Language server for the SAILAR text format, built on top of the [`sailasm`] parser and assembler.
+//!
+//! Provides diagnostics for assembler errors, go to definition, find references, and hover for symbols, and completion for
+//! directives, instructions, and symbols.
+
+pub mod analysis;
+pub mod server;
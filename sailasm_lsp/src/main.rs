@@ -0,0 +1,9 @@
+//! Executable for the SAILAR assembly language server, which communicates over standard input and output.
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let (connection, io_threads) = lsp_server::Connection::stdio();
+    sailasm_lsp::server::run(&connection)?;
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
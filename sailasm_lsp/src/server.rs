@@ -0,0 +1,508 @@
+//! Handles Language Server Protocol messages for SAILAR assembly documents.
+
+use crate::analysis::{self, Analysis};
+use lsp_server::{Connection, Message, Notification, Request, Response};
+use lsp_types::notification::Notification as _;
+use lsp_types::request::Request as _;
+use lsp_types::{notification, request, Position, Range, Url};
+use sailasm::ast::{self, LocationNumber, LocationRange};
+use std::collections::HashMap;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    #[error(transparent)]
+    ProtocolError(#[from] lsp_server::ProtocolError),
+    #[error(transparent)]
+    SerializationError(#[from] serde_json::Error),
+    #[error("the connection to the client was closed")]
+    Disconnected,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Gets the capabilities of the server that are sent in response to the client's `initialize` request.
+pub fn capabilities() -> lsp_types::ServerCapabilities {
+    lsp_types::ServerCapabilities {
+        text_document_sync: Some(lsp_types::TextDocumentSyncCapability::Kind(
+            lsp_types::TextDocumentSyncKind::FULL,
+        )),
+        definition_provider: Some(lsp_types::OneOf::Left(true)),
+        references_provider: Some(lsp_types::OneOf::Left(true)),
+        hover_provider: Some(lsp_types::HoverProviderCapability::Simple(true)),
+        completion_provider: Some(lsp_types::CompletionOptions {
+            trigger_characters: Some(vec![".".to_string(), "/".to_string(), "@".to_string()]),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Converts a one-based line and character column into a zero-based line and UTF-16 offset.
+fn to_position(lines: &[&str], line: LocationNumber, column: usize) -> Position {
+    let text = lines.get(line.get() - 1).copied().unwrap_or_default();
+    let character = text.chars().take(column).map(char::len_utf16).sum::<usize>();
+    Position::new((line.get() - 1) as u32, character as u32)
+}
+
+fn to_range(lines: &[&str], location: &LocationRange) -> Range {
+    Range::new(
+        to_position(lines, location.start().line, location.start().column.get() - 1),
+        to_position(lines, location.end().line, location.end().column.get() - 1),
+    )
+}
+
+fn to_location(lines: &[&str], position: Position) -> Option<ast::Location> {
+    let text = lines.get(position.line as usize)?;
+    let mut offset = 0;
+    let column = text
+        .chars()
+        .take_while(|c| {
+            offset += c.len_utf16();
+            offset <= position.character as usize
+        })
+        .count();
+
+    Some(ast::Location::new(
+        LocationNumber::new(position.line as usize + 1)?,
+        LocationNumber::new(column + 1)?,
+    ))
+}
+
+struct Document {
+    text: String,
+    analysis: Analysis,
+}
+
+impl Document {
+    fn new(text: String) -> Self {
+        Self {
+            analysis: Analysis::new(&text),
+            text,
+        }
+    }
+
+    fn lines(&self) -> Vec<&str> {
+        self.text.lines().collect()
+    }
+
+    fn symbol_at(&self, position: Position) -> Option<&str> {
+        self.analysis.symbol_at(&to_location(&self.lines(), position)?)
+    }
+}
+
+/// Stores the contents of all documents opened by the client.
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    fn document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    fn publish_diagnostics(&self, uri: Url) -> Notification {
+        let diagnostics = match self.document(&uri) {
+            Some(document) => {
+                let lines = document.lines();
+                document
+                    .analysis
+                    .errors()
+                    .iter()
+                    .map(|error| {
+                        let mut message = error.kind().to_string();
+                        for note in error.notes() {
+                            message.push_str("\nnote: ");
+                            message.push_str(note);
+                        }
+
+                        lsp_types::Diagnostic {
+                            range: error
+                                .location()
+                                .map(|location| to_range(&lines, location))
+                                .unwrap_or_default(),
+                            severity: Some(lsp_types::DiagnosticSeverity::ERROR),
+                            source: Some("sailasm".to_string()),
+                            message,
+                            related_information: if error.labels().is_empty() {
+                                None
+                            } else {
+                                Some(
+                                    error
+                                        .labels()
+                                        .iter()
+                                        .map(|label| lsp_types::DiagnosticRelatedInformation {
+                                            location: lsp_types::Location::new(uri.clone(), to_range(&lines, label.location())),
+                                            message: label.message().to_string(),
+                                        })
+                                        .collect(),
+                                )
+                            },
+                            ..Default::default()
+                        }
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        Notification::new(
+            notification::PublishDiagnostics::METHOD.to_string(),
+            lsp_types::PublishDiagnosticsParams::new(uri, diagnostics, None),
+        )
+    }
+
+    /// Updates the stored documents, returning a notification containing new diagnostics if a document was changed.
+    pub fn handle_notification(&mut self, notification: Notification) -> Result<Option<Notification>> {
+        match notification.method.as_str() {
+            notification::DidOpenTextDocument::METHOD => {
+                let params = serde_json::from_value::<lsp_types::DidOpenTextDocumentParams>(notification.params)?;
+                let uri = params.text_document.uri;
+                self.documents.insert(uri.clone(), Document::new(params.text_document.text));
+                Ok(Some(self.publish_diagnostics(uri)))
+            }
+            notification::DidChangeTextDocument::METHOD => {
+                let params = serde_json::from_value::<lsp_types::DidChangeTextDocumentParams>(notification.params)?;
+                let uri = params.text_document.uri;
+                match params.content_changes.into_iter().last() {
+                    Some(change) => {
+                        self.documents.insert(uri.clone(), Document::new(change.text));
+                        Ok(Some(self.publish_diagnostics(uri)))
+                    }
+                    None => Ok(None),
+                }
+            }
+            notification::DidCloseTextDocument::METHOD => {
+                let params = serde_json::from_value::<lsp_types::DidCloseTextDocumentParams>(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                Ok(Some(self.publish_diagnostics(params.text_document.uri)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn respond<R: lsp_types::request::Request>(&self, request: Request, handler: fn(&Self, R::Params) -> R::Result) -> Response {
+        match serde_json::from_value::<R::Params>(request.params) {
+            Ok(params) => Response::new_ok(request.id, handler(self, params)),
+            Err(e) => Response::new_err(request.id, lsp_server::ErrorCode::InvalidParams as i32, e.to_string()),
+        }
+    }
+
+    pub fn handle_request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            request::GotoDefinition::METHOD => self.respond::<request::GotoDefinition>(request, Self::goto_definition),
+            request::References::METHOD => self.respond::<request::References>(request, Self::references),
+            request::HoverRequest::METHOD => self.respond::<request::HoverRequest>(request, Self::hover),
+            request::Completion::METHOD => self.respond::<request::Completion>(request, Self::completion),
+            _ => Response::new_err(
+                request.id,
+                lsp_server::ErrorCode::MethodNotFound as i32,
+                format!("unsupported request {}", request.method),
+            ),
+        }
+    }
+
+    fn goto_definition(&self, params: lsp_types::GotoDefinitionParams) -> Option<lsp_types::GotoDefinitionResponse> {
+        let position = params.text_document_position_params;
+        let document = self.document(&position.text_document.uri)?;
+        let definition = document.analysis.definition(document.symbol_at(position.position)?)?;
+        Some(lsp_types::GotoDefinitionResponse::Scalar(lsp_types::Location::new(
+            position.text_document.uri,
+            to_range(&document.lines(), definition.location()),
+        )))
+    }
+
+    fn references(&self, params: lsp_types::ReferenceParams) -> Option<Vec<lsp_types::Location>> {
+        let position = params.text_document_position;
+        let document = self.document(&position.text_document.uri)?;
+        let lines = document.lines();
+        let name = document.symbol_at(position.position)?;
+        let mut locations = Vec::new();
+
+        if params.context.include_declaration {
+            if let Some(definition) = document.analysis.definition(name) {
+                locations.push(lsp_types::Location::new(
+                    position.text_document.uri.clone(),
+                    to_range(&lines, definition.location()),
+                ));
+            }
+        }
+
+        locations.extend(
+            document
+                .analysis
+                .references(name)
+                .map(|location| lsp_types::Location::new(position.text_document.uri.clone(), to_range(&lines, location))),
+        );
+
+        Some(locations)
+    }
+
+    fn hover(&self, params: lsp_types::HoverParams) -> Option<lsp_types::Hover> {
+        let position = params.text_document_position_params;
+        let document = self.document(&position.text_document.uri)?;
+        let declarations = document.analysis.hover(document.symbol_at(position.position)?)?;
+        Some(lsp_types::Hover {
+            contents: lsp_types::HoverContents::Markup(lsp_types::MarkupContent {
+                kind: lsp_types::MarkupKind::Markdown,
+                value: format!("```sailasm\n{}\n```", declarations.join("\n")),
+            }),
+            range: None,
+        })
+    }
+
+    fn completion(&self, params: lsp_types::CompletionParams) -> Option<lsp_types::CompletionResponse> {
+        let position = params.text_document_position;
+        let document = self.document(&position.text_document.uri)?;
+        let lines = document.lines();
+        let cursor = to_location(&lines, position.position)
+            .map(|location| location.column.get() - 1)
+            .unwrap_or_default();
+        let line = lines.get(position.position.line as usize).copied().unwrap_or_default();
+        let before_cursor = line.chars().take(cursor).collect::<String>();
+
+        // The word being completed, including any leading sigil.
+        let word = before_cursor
+            .rsplit(|c: char| c.is_whitespace() || matches!(c, '(' | ')' | ',' | ':' | '='))
+            .next()
+            .unwrap_or_default();
+
+        let range = Range::new(
+            Position::new(
+                position.position.line,
+                position.position.character - word.chars().map(char::len_utf16).sum::<usize>() as u32,
+            ),
+            position.position,
+        );
+
+        let item = |label: String, kind: lsp_types::CompletionItemKind, detail: Option<String>| lsp_types::CompletionItem {
+            text_edit: Some(lsp_types::CompletionTextEdit::Edit(lsp_types::TextEdit::new(
+                range,
+                label.clone(),
+            ))),
+            label,
+            kind: Some(kind),
+            detail,
+            ..Default::default()
+        };
+
+        let items = match word.chars().next() {
+            Some('.') => analysis::DIRECTIVES
+                .iter()
+                .map(|name| item(format!(".{}", name), lsp_types::CompletionItemKind::KEYWORD, None))
+                .collect(),
+            Some('/') => analysis::NESTED_DIRECTIVES
+                .iter()
+                .map(|name| item(format!("/{}", name), lsp_types::CompletionItemKind::KEYWORD, None))
+                .collect(),
+            Some('@') => document
+                .analysis
+                .definitions()
+                .iter()
+                .map(|definition| {
+                    item(
+                        format!("@{}", definition.name()),
+                        match definition.kind() {
+                            analysis::DefinitionKind::Identifier | analysis::DefinitionKind::Data => {
+                                lsp_types::CompletionItemKind::CONSTANT
+                            }
+                            analysis::DefinitionKind::TypeSignature | analysis::DefinitionKind::FunctionSignature => {
+                                lsp_types::CompletionItemKind::TYPE_PARAMETER
+                            }
+                            analysis::DefinitionKind::CodeBlock => lsp_types::CompletionItemKind::MODULE,
+                            analysis::DefinitionKind::Function | analysis::DefinitionKind::FunctionInstantiation => {
+                                lsp_types::CompletionItemKind::FUNCTION
+                            }
+                        },
+                        Some(definition.declaration().to_string()),
+                    )
+                })
+                .collect(),
+            _ => analysis::INSTRUCTIONS
+                .iter()
+                .map(|name| item(name.to_string(), lsp_types::CompletionItemKind::OPERATOR, None))
+                .collect(),
+        };
+
+        Some(lsp_types::CompletionResponse::Array(items))
+    }
+}
+
+fn send(connection: &Connection, message: Message) -> Result<()> {
+    connection.sender.send(message).map_err(|_| Error::Disconnected)
+}
+
+/// Runs the language server until the client requests that it shut down.
+pub fn run(connection: &Connection) -> Result<()> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+
+    let mut server = Server::default();
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+
+                send(connection, Message::Response(server.handle_request(request)))?;
+            }
+            Message::Notification(notification) => {
+                if let Some(diagnostics) = server.handle_notification(notification)? {
+                    send(connection, Message::Notification(diagnostics))?;
+                }
+            }
+            Message::Response(_) => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_server::{Connection, Message};
+    use serde_json::json;
+
+    const URI: &str = "file:///test.sailas";
+
+    const INPUT: &str =
+        ".format major 0\n.format minor 19\n.signature @u32 type u32\n.code @body ($x:@u32) -> (@u32)\n    ret $x\n";
+
+    /// Sends the messages to a server running on another thread, then collects all messages sent back.
+    fn run_script(messages: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+        let (server, client) = Connection::memory();
+        let thread = std::thread::spawn(move || crate::server::run(&server));
+
+        let mut script = vec![
+            json!({ "jsonrpc": "2.0", "id": 0, "method": "initialize", "params": { "capabilities": {} } }),
+            json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} }),
+        ];
+        script.extend(messages);
+        script.push(json!({ "jsonrpc": "2.0", "id": 1000, "method": "shutdown" }));
+        script.push(json!({ "jsonrpc": "2.0", "method": "exit" }));
+
+        for message in script {
+            client
+                .sender
+                .send(serde_json::from_value::<Message>(message).unwrap())
+                .unwrap();
+        }
+
+        thread.join().unwrap().unwrap();
+        client
+            .receiver
+            .try_iter()
+            .map(|message| serde_json::to_value(message).unwrap())
+            .collect()
+    }
+
+    fn open(text: &str) -> serde_json::Value {
+        json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": URI, "languageId": "sailasm", "version": 1, "text": text } }
+        })
+    }
+
+    fn request(id: u32, method: &str, line: u32, character: u32) -> serde_json::Value {
+        let mut request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": {
+                "textDocument": { "uri": URI },
+                "position": { "line": line, "character": character }
+            }
+        });
+
+        if method == "textDocument/references" {
+            request["params"]["context"] = json!({ "includeDeclaration": true });
+        }
+
+        request
+    }
+
+    fn response(messages: &[serde_json::Value], id: u32) -> &serde_json::Value {
+        &messages.iter().find(|message| message["id"] == id).unwrap()["result"]
+    }
+
+    #[test]
+    fn diagnostics_are_published_with_related_information() {
+        let messages = run_script(vec![open(".identifier @a \"a\"\n.identifier @a \"b\"\n")]);
+        let diagnostics = &messages
+            .iter()
+            .find(|message| message["method"] == "textDocument/publishDiagnostics")
+            .unwrap()["params"]["diagnostics"];
+
+        assert_eq!(1, diagnostics.as_array().unwrap().len());
+        assert_eq!("symbol @a is defined more than once", diagnostics[0]["message"]);
+        assert_eq!(json!({ "line": 1, "character": 12 }), diagnostics[0]["range"]["start"]);
+        assert_eq!(json!({ "line": 1, "character": 14 }), diagnostics[0]["range"]["end"]);
+        assert_eq!("first defined here", diagnostics[0]["relatedInformation"][0]["message"]);
+        assert_eq!(
+            0,
+            diagnostics[0]["relatedInformation"][0]["location"]["range"]["start"]["line"]
+        );
+    }
+
+    #[test]
+    fn array_directive_is_published_as_diagnostic() {
+        let messages = run_script(vec![
+            open(".array\n.identifier @a \"a\"\n"),
+            request(1, "textDocument/hover", 1, 13),
+        ]);
+        let diagnostics = &messages
+            .iter()
+            .find(|message| message["method"] == "textDocument/publishDiagnostics")
+            .unwrap()["params"]["diagnostics"];
+
+        assert_eq!(1, diagnostics.as_array().unwrap().len());
+        assert_eq!("array directives are not yet supported", diagnostics[0]["message"]);
+        assert_eq!(json!({ "line": 0, "character": 0 }), diagnostics[0]["range"]["start"]);
+        assert!(!response(&messages, 1).is_null());
+    }
+
+    #[test]
+    fn symbol_requests_are_answered() {
+        let messages = run_script(vec![
+            open(INPUT),
+            request(1, "textDocument/definition", 3, 18),
+            request(2, "textDocument/references", 2, 12),
+            request(3, "textDocument/hover", 3, 29),
+            request(4, "textDocument/completion", 4, 5),
+        ]);
+
+        assert_eq!(
+            json!({ "uri": URI, "range": { "start": { "line": 2, "character": 11 }, "end": { "line": 2, "character": 15 } } }),
+            *response(&messages, 1)
+        );
+        assert_eq!(3, response(&messages, 2).as_array().unwrap().len());
+        assert_eq!(
+            "```sailasm\n.signature @u32 type u32\n```",
+            response(&messages, 3)["contents"]["value"]
+        );
+        assert!(response(&messages, 4)
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|item| item["label"] == "ret"));
+    }
+
+    #[test]
+    fn completion_depends_on_prefix() {
+        let messages = run_script(vec![
+            open(".code @body () -> ()\n    ret\n.sig\n@\n"),
+            request(1, "textDocument/completion", 2, 4),
+            request(2, "textDocument/completion", 3, 1),
+        ]);
+
+        let directives = response(&messages, 1).as_array().unwrap();
+        assert!(directives.iter().any(|item| item["label"] == ".signature"));
+        assert_eq!(
+            json!({ "line": 2, "character": 0 }),
+            directives[0]["textEdit"]["range"]["start"]
+        );
+        assert_eq!("@body", response(&messages, 2)[0]["label"]);
+    }
+}
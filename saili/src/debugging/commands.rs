@@ -1,15 +1,19 @@
-use sailar_vm::interpreter::{debugger, Interpreter, LoaderError};
+use super::Breakpoint;
+use sailar_load::error::LoaderError;
+use sailar_load::module::Module;
+use sailar_vm::interpreter::State;
 use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::sync::Arc;
 
 #[derive(thiserror::Error, Debug)]
 pub(super) enum Error {
     #[error("{0}")]
     Message(Cow<'static, str>),
     #[error(transparent)]
-    Argument(#[from] clap::Error),
-    #[error(transparent)]
     Loader(#[from] LoaderError),
-    //Runtime(#[from] )
+    #[error(transparent)]
+    Runtime(#[from] sailar_vm::error::RuntimeError),
 }
 
 impl From<&'static str> for Error {
@@ -24,12 +28,30 @@ impl From<String> for Error {
     }
 }
 
-pub(super) type Result = std::result::Result<Option<debugger::Reply>, Error>;
+/// Indicates how the debugger should proceed with the execution of the program.
+#[derive(Clone, Copy, Debug)]
+pub(super) enum Reply {
+    /// Executes the specified number of instructions.
+    Step(usize),
+    /// Executes instructions until a breakpoint is reached or the program ends.
+    Continue,
+    /// Stops execution of the program.
+    Quit,
+}
+
+pub(super) type Result = std::result::Result<Option<Reply>, Error>;
+
+/// The state that a command can inspect or modify.
+pub(super) struct Context<'a> {
+    pub(super) state: &'a State,
+    pub(super) module: &'a Arc<Module>,
+    pub(super) breakpoints: &'a mut BTreeSet<Breakpoint>,
+}
 
 #[derive(Clone, Copy)]
 pub struct Command {
     pub(super) description: &'static str,
-    pub(super) command: &'static dyn Fn(&Lookup, &[&str], &mut Interpreter) -> Result,
+    pub(super) command: fn(&Lookup, &[&str], &mut Context) -> Result,
 }
 
 pub struct Lookup {
@@ -38,15 +60,20 @@ pub struct Lookup {
 }
 
 impl Command {
-    pub fn execute(
-        &self,
-        commands: &Lookup,
-        input: &[&str],
-        interpreter: &mut Interpreter,
-    ) -> Option<debugger::Reply> {
-        (self.command)(commands, input, interpreter).unwrap_or_else(|error| {
+    pub(super) fn execute(&self, commands: &Lookup, input: &[&str], context: &mut Context) -> Option<Reply> {
+        (self.command)(commands, input, context).unwrap_or_else(|error| {
             eprintln!("Error: {}", error);
             None
         })
     }
 }
+
+/// Parses an optional numeric argument of a command, using a `default` value if it is omitted.
+pub(super) fn parse_count(input: &[&str], index: usize, default: usize) -> std::result::Result<usize, Error> {
+    match input.get(index) {
+        Some(argument) => argument
+            .parse()
+            .map_err(|_| Error::from(format!("expected an integer argument, but got {:?}", argument))),
+        None => Ok(default),
+    }
+}
@@ -5,11 +5,14 @@ pub struct Cache {
 
 impl Cache {
     // TODO: Properly handle quoted strings.
-    pub fn read_command(&mut self) -> Vec<&str> {
-        // Line and argument buffers are not cached, though this probably doesn't impact performance.
+    /// Reads a line containing a command and its arguments, returning `None` if the end of the input was reached.
+    pub fn read_command(&mut self) -> std::io::Result<Option<Vec<&str>>> {
+        // Argument buffers are not cached, though this probably doesn't impact performance.
         self.line_buffer.clear();
-        std::io::stdin().read_line(&mut self.line_buffer).unwrap();
+        if std::io::stdin().read_line(&mut self.line_buffer)? == 0 {
+            return Ok(None);
+        }
 
-        self.line_buffer.split_whitespace().collect()
+        Ok(Some(self.line_buffer.split_whitespace().collect()))
     }
 }
@@ -1,78 +1,173 @@
-use clap::Parser as _;
-use sailar::format::Identifier;
-use sailar_vm::interpreter::{call_stack, debugger, Interpreter};
-use std::fmt::{Display, Formatter, Write as _};
+use sailar_load::function::Function;
+use sailar_load::module::Module;
+use sailar_vm::bytecode::Op;
+use sailar_vm::call_stack::Frame;
+use sailar_vm::error::RuntimeError;
+use sailar_vm::interpreter::State;
+use sailar_vm::value::Value;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
 use std::io::Write as _;
+use std::sync::Arc;
 
 mod commands;
 mod input;
 
-use commands::Error;
+use commands::{Context, Error, Reply};
+
+/// An instruction in a code block of a function where execution is paused.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct Breakpoint {
+    function: sailar::index::Function,
+    block: sailar::index::CodeBlock,
+    instruction: usize,
+    /// The offset of the instruction in the decoded bytecode of the function.
+    offset: usize,
+}
+
+impl Breakpoint {
+    /// Creates a breakpoint at an `instruction` in a code `block` of the `function`, or in its entry block if no block is
+    /// specified.
+    fn new(
+        state: &State,
+        function: &Arc<Function>,
+        block: Option<sailar::index::CodeBlock>,
+        instruction: usize,
+    ) -> Result<Self, Error> {
+        let block = match block {
+            Some(block) => block,
+            None => function.template()?.as_definition()?.entry_block()?.index(),
+        };
+
+        let offset = state
+            .runtime()
+            .decoded_function(function)?
+            .instruction_offset(block, instruction)
+            .ok_or_else(|| {
+                Error::from(format!(
+                    "block #{} of {} does not contain instruction {}",
+                    usize::from(block),
+                    FunctionName(function),
+                    instruction
+                ))
+            })?;
+
+        Ok(Self {
+            function: function.index(),
+            block,
+            instruction,
+            offset,
+        })
+    }
+
+    fn is_at(&self, frame: &Frame) -> bool {
+        self.function == frame.function().index() && self.offset == frame.instruction_index()
+    }
+}
+
+fn function_symbol(function: &Function) -> Option<&sailar::identifier::Id> {
+    function
+        .template()
+        .ok()
+        .and_then(|template| template.as_definition().ok())
+        .and_then(|definition| definition.export().symbol())
+}
 
 #[derive(Debug)]
-pub struct Location<'a>(pub Option<&'a debugger::InstructionLocation>);
+pub struct FunctionName<'a>(pub &'a Function);
 
-impl Display for Location<'_> {
+impl Display for FunctionName<'_> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        match self.0 {
-            Some(location) => write!(
-                f,
-                "at block {} index {}",
-                location.block_index, location.code_index
-            ),
-            None => f.write_str("(external call)"),
+        let index = usize::from(self.0.index());
+        match function_symbol(self.0) {
+            Some(symbol) => write!(f, "@{} (#{})", symbol, index),
+            None => write!(f, "#{}", index),
         }
     }
 }
 
 #[derive(Debug)]
-pub struct ModuleSymbol<'a>(pub &'a debugger::ModuleIdentifier);
+pub struct Location<'a>(pub &'a Frame);
 
-impl Display for ModuleSymbol<'_> {
+impl Display for Location<'_> {
     fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.0.name)?;
-        let version = &self.0.version;
-        if version.is_empty() {
-            f.write_str(", v")?;
-            for (i, number) in version.0.iter().enumerate() {
-                if i > 0 {
-                    f.write_char('.')?;
-                }
-                Display::fmt(&number, f)?;
-            }
+        let bytecode = self.0.bytecode();
+        match bytecode.source_location(self.0.instruction_index()) {
+            Some((block, instruction)) => write!(
+                f,
+                "{} at block #{} instruction {}: {:?}",
+                FunctionName(self.0.function()),
+                usize::from(block),
+                instruction,
+                bytecode.code()[self.0.instruction_index()]
+            ),
+            None => write!(f, "{} at end of function", FunctionName(self.0.function())),
         }
-        Ok(())
     }
 }
 
-#[derive(Debug)]
-pub struct FunctionSymbol<'a>(pub &'a debugger::FunctionSymbol<'static>);
+/// Parses the arguments of a command specifying the location of a breakpoint, which are a function followed by an optional
+/// code block index and instruction index.
+fn parse_breakpoint(input: &[&str], context: &Context) -> Result<Breakpoint, Error> {
+    let name = input.get(1).ok_or("expected a function symbol or index")?;
+    let function = find_function(context.module, name)?;
+    let block = match input.get(2) {
+        Some(block) => Some(sailar::index::CodeBlock::from(
+            block
+                .strip_prefix('#')
+                .unwrap_or(block)
+                .parse::<usize>()
+                .map_err(|_| Error::from(format!("{:?} is not a valid code block index", block)))?,
+        )),
+        None => None,
+    };
 
-impl Display for FunctionSymbol<'_> {
-    fn fmt(&self, f: &mut Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            "\"{}\" from {}",
-            self.0.symbol(),
-            ModuleSymbol(self.0.module())
-        )
+    Breakpoint::new(context.state, function, block, commands::parse_count(input, 3, 0)?)
+}
+
+/// Finds a function in the `module` by its symbol, or by its index if prefixed with `#`.
+fn find_function<'a>(module: &'a Module, name: &str) -> Result<&'a Arc<Function>, Error> {
+    let function = if let Some(index) = name.strip_prefix('#') {
+        let index = index
+            .parse::<usize>()
+            .map_err(|_| Error::from(format!("{:?} is not a valid function index", index)))?;
+        module.functions().get(index)
+    } else {
+        let symbol = name.strip_prefix('@').unwrap_or(name);
+        module
+            .functions()
+            .iter()
+            .find(|function| function_symbol(function).is_some_and(|export| export.as_str() == symbol))
+    };
+
+    function.ok_or_else(|| Error::from(format!("no function found with symbol {:?}", name)))
+}
+
+fn print_registers(state: &State, frame: &Frame) {
+    for (index, register) in state.call_stack().frame_registers(frame).iter().enumerate() {
+        println!("  %{} = {} ({:#X})", index, register, register);
     }
 }
 
-fn print_registers(prefix: char, registers: &[debugger::Register]) {
-    for (index, register) in registers.iter().enumerate() {
-        println!("  %{}{} = {}", prefix, index, register);
+fn print_stack_trace(state: &State, show_registers: bool) {
+    for (depth, frame) in state.call_stack().iter_frames().enumerate() {
+        println!("#{} {}", depth, Location(frame));
+        if show_registers {
+            print_registers(state, frame);
+        }
     }
 }
 
 pub struct CommandLineDebugger {
     started: bool,
+    module: Arc<Module>,
+    breakpoints: BTreeSet<Breakpoint>,
     commands: commands::Lookup,
     input_buffer: input::Cache,
 }
 
 impl CommandLineDebugger {
-    pub fn new() -> Self {
+    pub fn new(module: Arc<Module>) -> Self {
         let mut commands = std::collections::BTreeMap::new();
         let mut command_name_width = 0usize;
 
@@ -86,7 +181,7 @@ impl CommandLineDebugger {
                     $name,
                     commands::Command {
                         description: $description,
-                        command: &$command,
+                        command: $command,
                     },
                 );
             }};
@@ -94,79 +189,63 @@ impl CommandLineDebugger {
 
         command!("help", "lists all commands", |commands, _, _| {
             for (name, commands::Command { description, .. }) in &commands.commands {
-                println!(
-                    "{:width$} - {}",
-                    name,
-                    description,
-                    width = commands.name_width
-                );
+                println!("{:width$} - {}", name, description, width = commands.name_width);
             }
 
             Ok(None)
         });
 
         command!(
-            "detach",
-            "stops debugging and continues execution of the program",
-            |_, _, _| { Ok(Some(debugger::Reply::Detach)) }
+            "step",
+            "executes the next instruction, or the specified number of instructions",
+            |_, input, _| Ok(Some(Reply::Step(commands::parse_count(input, 1, 1)?)))
+        );
+
+        command!(
+            "cont",
+            "continues execution of the program until a breakpoint is reached",
+            |_, _, _| Ok(Some(Reply::Continue))
         );
 
         command!(
             "break",
-            "sets a breakpoint in the specified function",
-            |_, command, interpreter| {
-                #[derive(clap::Parser, Debug)]
-                #[clap(
-                    name = "break",
-                    about,
-                    global_setting(clap::AppSettings::DisableHelpFlag)
-                )]
-                // TODO: COuld have get and set subcommands and have set be the "main" one.
-                struct Arguments {
-                    /// The symbol name of the function to set a breakpoint in.
-                    #[clap(short, long, parse(try_from_str = std::convert::TryFrom::try_from), value_name = "SYMBOL")]
-                    function: Identifier,
-                    #[clap(short, long, default_value_t)]
-                    block: usize,
-                    #[clap(short, long, default_value_t)]
-                    instruction: usize,
+            "sets a breakpoint at a function, specified by symbol or #index, followed by an optional block index and instruction index",
+            |_, input, context| {
+                let breakpoint = parse_breakpoint(input, context)?;
+                if !context.breakpoints.insert(breakpoint) {
+                    return Err(Error::from("breakpoint already exists"));
                 }
 
-                let arguments = Arguments::try_parse_from(command)?;
-
-                let matches = interpreter
-                    .loader()
-                    .lookup_function(debugger::Symbol::Owned(arguments.function));
-
-                match matches.first() {
-                    Some(function) if matches.len() == 1 => {
-                        interpreter.call_stack().breakpoints_mut().insert(
-                            call_stack::Breakpoint::with_symbol(
-                                debugger::BlockIndex(if arguments.block == 0 {
-                                    None
-                                } else {
-                                    Some(arguments.block - 1)
-                                }),
-                                arguments.instruction,
-                                function.full_symbol()?.to_owned(),
-                            ),
-                        );
-
-                        Ok(None)
+                Ok(None)
+            }
+        );
+
+        command!(
+            "delete",
+            "removes a breakpoint at an instruction in a function, or all breakpoints if no function is specified",
+            |_, input, context| {
+                match input.get(1) {
+                    Some(_) => {
+                        let breakpoint = parse_breakpoint(input, context)?;
+                        if !context.breakpoints.remove(&breakpoint) {
+                            return Err(Error::from("no breakpoint exists at the specified location"));
+                        }
                     }
-                    Some(_) => Err(Error::from("multiples matches for function symbol")),
-                    None => Err(Error::from("no function found with symbol")),
+                    None => context.breakpoints.clear(),
                 }
+
+                Ok(None)
             }
         );
 
-        command!("points", "lists all breakpoints", |_, _, interpreter| {
-            for breakpoint in interpreter.call_stack().breakpoints_mut().iter() {
-                let function = breakpoint.function();
+        command!("points", "lists all breakpoints", |_, _, context| {
+            for breakpoint in context.breakpoints.iter() {
+                let function = &context.module.functions()[usize::from(breakpoint.function)];
                 println!(
-                    "- {} {}",
-                    FunctionSymbol(function),
-                    Location(Some(breakpoint.location()))
+                    "- {} at block #{} instruction {}",
+                    FunctionName(function),
+                    usize::from(breakpoint.block),
+                    breakpoint.instruction
                 );
             }
 
@@ -175,42 +254,42 @@ impl CommandLineDebugger {
 
         command!(
             "where",
-            "prints a stack trace",
-            |_, command, interpreter| {
-                #[derive(clap::Parser, Debug)]
-                #[clap(
-                    name = "where",
-                    about,
-                    global_setting(clap::AppSettings::DisableHelpFlag)
-                )]
-                struct Arguments {
-                    #[clap(long)]
-                    show_registers: bool,
-                }
+            "prints a stack trace, including the registers of each frame if --registers is specified",
+            |_, input, context| {
+                let show_registers = match input.get(1) {
+                    Some(&"--registers") => true,
+                    Some(argument) => return Err(Error::from(format!("unknown argument {:?}", argument))),
+                    None => false,
+                };
 
-                let arguments = Arguments::try_parse_from(command)?;
-
-                for frame in interpreter.call_stack().stack_trace() {
-                    println!(
-                        "- {} {}",
-                        FunctionSymbol(frame.function()),
-                        Location(frame.location().as_ref())
-                    );
-                    if arguments.show_registers {
-                        print_registers('i', frame.input_registers());
-                        print_registers('t', frame.temporary_registers());
-                    }
-                }
+                print_stack_trace(context.state, show_registers);
                 Ok(None)
             }
         );
 
-        command!("cont", "continues execution of the program", |_, _, _| {
-            Ok(Some(debugger::Reply::Continue))
-        });
+        command!(
+            "registers",
+            "prints the registers of the current frame, or the frame at the specified depth",
+            |_, input, context| {
+                let depth = commands::parse_count(input, 1, 0)?;
+                let frame = context
+                    .state
+                    .call_stack()
+                    .iter_frames()
+                    .nth(depth)
+                    .ok_or_else(|| Error::from(format!("no frame exists at depth {}", depth)))?;
+
+                print_registers(context.state, frame);
+                Ok(None)
+            }
+        );
+
+        command!("quit", "stops execution of the program", |_, _, _| Ok(Some(Reply::Quit)));
 
         Self {
             started: false,
+            module,
+            breakpoints: BTreeSet::new(),
             commands: commands::Lookup {
                 commands,
                 name_width: command_name_width,
@@ -218,24 +297,44 @@ impl CommandLineDebugger {
             input_buffer: input::Cache::default(),
         }
     }
-}
 
-impl debugger::Debugger for CommandLineDebugger {
-    fn inspect(&mut self, interpreter: &mut Interpreter) -> debugger::Reply {
-        if !self.started {
-            println!("Type 'help' for help, or 'cont' to begin program execution");
-            self.started = true;
+    /// Checks if execution should pause before executing the next instruction.
+    fn is_paused(&self, state: &State) -> bool {
+        match state.call_stack().iter_frames().next() {
+            Some(frame) => {
+                matches!(frame.bytecode().code().get(frame.instruction_index()), Some(Op::Break))
+                    || self.breakpoints.iter().any(|breakpoint| breakpoint.is_at(frame))
+            }
+            None => false,
+        }
+    }
+
+    fn print_location(state: &State) {
+        if let Some(frame) = state.call_stack().iter_frames().next() {
+            println!("{}", Location(frame));
         }
+    }
 
+    fn read_reply(&mut self, state: &State) -> std::io::Result<Reply> {
         loop {
             print!("> ");
-            std::io::stdout().flush().unwrap();
+            std::io::stdout().flush()?;
+
+            let input = match self.input_buffer.read_command()? {
+                Some(input) => input,
+                None => return Ok(Reply::Quit),
+            };
 
-            let input = self.input_buffer.read_command();
             if let Some(name) = input.first() {
                 if let Some(command) = self.commands.commands.get(name) {
-                    if let Some(reply) = command.execute(&self.commands, &input, interpreter) {
-                        return reply;
+                    let mut context = Context {
+                        state,
+                        module: &self.module,
+                        breakpoints: &mut self.breakpoints,
+                    };
+
+                    if let Some(reply) = command.execute(&self.commands, &input, &mut context) {
+                        return Ok(reply);
                     }
                 } else {
                     eprintln!("'{}' is not a valid command", name);
@@ -243,4 +342,139 @@ impl debugger::Debugger for CommandLineDebugger {
             }
         }
     }
+
+    fn step(state: &mut State) -> Result<Option<Box<[Value]>>, RuntimeError> {
+        state.step().inspect_err(|_| print_stack_trace(state, false))
+    }
+
+    /// Executes instructions until a breakpoint is reached, returning the return values of the entry point if it returned.
+    fn continue_execution(&mut self, state: &mut State) -> Result<Option<Box<[Value]>>, RuntimeError> {
+        // Breakpoints at the current location are only hit if execution has not yet begun.
+        let mut resuming = self.started;
+        self.started = true;
+        while resuming || !self.is_paused(state) {
+            resuming = false;
+            if let Some(return_values) = Self::step(state)? {
+                return Ok(Some(return_values));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Executes the program, pausing to read commands from standard input.
+    ///
+    /// Returns `None` if execution of the program was stopped before the entry point returned.
+    pub fn run(&mut self, state: &mut State) -> Result<Option<Box<[Value]>>, RuntimeError> {
+        println!("Type 'help' for help, or 'cont' to begin program execution");
+
+        loop {
+            let reply = self.read_reply(state).unwrap_or_else(|error| {
+                eprintln!("Error: {}", error);
+                Reply::Quit
+            });
+
+            match reply {
+                Reply::Step(count) => {
+                    self.started = true;
+                    for _ in 0..count {
+                        if let Some(return_values) = Self::step(state)? {
+                            return Ok(Some(return_values));
+                        }
+                    }
+
+                    Self::print_location(state);
+                }
+                Reply::Continue => {
+                    if let Some(return_values) = self.continue_execution(state)? {
+                        return Ok(Some(return_values));
+                    }
+
+                    Self::print_location(state);
+                }
+                Reply::Quit => return Ok(None),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Breakpoint, CommandLineDebugger, Context};
+    use sailar::index::CodeBlock;
+    use sailar_vm::runtime;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn breakpoints_stop_at_instruction_in_block() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let module = loader
+            .load_module(
+                sailar::validation::ValidModule::from_builder(sailar_samples::sum_loop("Loop".try_into().unwrap(), 3)).unwrap(),
+            )
+            .unwrap();
+
+        let entry_point = module.entry_point().unwrap().clone();
+        let mut state = runtime::Configuration::new()
+            .initialize_runtime()
+            .create_state(entry_point, Box::default())
+            .unwrap();
+
+        let breakpoint = {
+            let mut breakpoints = BTreeSet::new();
+            let context = Context {
+                state: &state,
+                module: &module,
+                breakpoints: &mut breakpoints,
+            };
+
+            // The second instruction of the loop body, which increments the counter.
+            super::parse_breakpoint(&["break", "main", "#2", "1"], &context).unwrap()
+        };
+
+        let mut debugger = CommandLineDebugger::new(module.clone());
+        debugger.breakpoints.insert(breakpoint);
+
+        for counter in 0..3u64 {
+            assert!(debugger.continue_execution(&mut state).unwrap().is_none());
+            let frame = state.call_stack().iter_frames().next().unwrap();
+            assert_eq!(
+                frame.bytecode().source_location(frame.instruction_index()),
+                Some((CodeBlock::from(2), 1))
+            );
+            // The counter input of the loop body is stored after the registers of the entry block and the loop condition.
+            assert_eq!(state.call_stack().frame_registers(frame)[3], counter);
+        }
+
+        let return_values = debugger.continue_execution(&mut state).unwrap().unwrap();
+        assert_eq!(return_values[0].clone().into_u32(sailar_vm::value::Endianness::Little), 3);
+    }
+
+    #[test]
+    fn breakpoint_outside_of_block_is_error() {
+        let loader = sailar_load::state::Configuration::new().create_state();
+        let module = loader
+            .load_module(
+                sailar::validation::ValidModule::from_builder(sailar_samples::sum_loop("Loop".try_into().unwrap(), 3)).unwrap(),
+            )
+            .unwrap();
+
+        let state = runtime::Configuration::new()
+            .initialize_runtime()
+            .create_state(module.entry_point().unwrap().clone(), Box::default())
+            .unwrap();
+
+        let mut breakpoints = BTreeSet::new();
+        let context = Context {
+            state: &state,
+            module: &module,
+            breakpoints: &mut breakpoints,
+        };
+
+        assert!(super::parse_breakpoint(&["break", "main", "#3", "1"], &context).is_err());
+        assert!(matches!(
+            super::parse_breakpoint(&["break", "main"], &context),
+            Ok(Breakpoint { offset: 0, .. })
+        ));
+    }
 }
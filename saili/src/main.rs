@@ -1,10 +1,10 @@
-use clap::{self, Parser as _};
-use sailar_vm::runtime;
+use clap::Parser as _;
+use sailar_vm::{call_stack, entry_point, runtime};
 
 mod debugging;
 
 #[derive(clap::Parser, Debug)]
-#[clap()]
+#[clap(version, about)]
 struct Arguments {
     /// Path to the program to run.
     #[clap(long, short)]
@@ -12,80 +12,120 @@ struct Arguments {
     /// If set, launches the debugger.
     #[clap(long)]
     interactive: bool,
-    /// Sets the maximum number of memory that can be allocated on the stack, in bytes.
+    /// Sets the maximum number of frames in the call stack before a stack overflow occurs.
     #[clap(long)]
-    stack_memory_capacity: Option<std::num::NonZeroUsize>,
+    call_stack_size: Option<std::num::NonZeroUsize>,
+    /// Integer arguments passed to the entry point of the program, specified after `--`.
+    #[clap(last = true)]
+    arguments: Vec<String>,
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let all_arguments: Vec<String> = std::env::args().collect();
-    let application_arguments_start = all_arguments.iter().position(|arg| arg == "--");
-
-    let interpreter_arguments = Arguments::parse_from(
-        &all_arguments[0..application_arguments_start.unwrap_or(all_arguments.len())],
-    );
+/// Runs the program, returning its exit code.
+fn run(arguments: Arguments) -> Result<i32, Box<dyn std::error::Error>> {
+    let program = {
+        let input = std::io::BufReader::new(std::fs::File::open(&arguments.program)?);
+        let builder = sailar::builder::Builder::from_reader(sailar::reader::Reader::new(input))?;
+        sailar::validation::ValidModule::from_builder(builder)?
+    };
 
-    let result = {
-        let application_arguments: Vec<_> = application_arguments_start
-            .map(|start| {
-                all_arguments[start + 1..all_arguments.len()]
-                    .iter()
-                    .map(String::as_str)
-                    .collect()
-            })
-            .unwrap_or_default();
+    let loader = sailar_load::state::Configuration::new().create_state();
+    let program = loader.load_module(program).unwrap_or_else(|existing| existing);
+    let entry_point = program
+        .entry_point()
+        .ok_or_else(|| format!("{} does not specify an entry point", arguments.program.display()))?
+        .clone();
 
-        let (application, _) =
-            sailar::parser::parse_module(&mut std::fs::File::open(interpreter_arguments.program)?)?;
+    let mut configuration = runtime::Configuration::new();
+    if let Some(size) = arguments.call_stack_size {
+        configuration = configuration.call_stack_size(call_stack::Size::new(size));
+    }
 
-        let mut initializer = runtime::Initializer::new();
+    let runtime = configuration.initialize_runtime();
+    let entry_point_arguments = entry_point::parse_arguments(&entry_point, &arguments.arguments, runtime.endianness())?;
 
-        if let Some(capacity) = interpreter_arguments.stack_memory_capacity {
-            initializer.set_value_stack_capacity(capacity);
+    let return_values = if arguments.interactive {
+        let mut state = runtime.create_state(entry_point.clone(), entry_point_arguments)?;
+        match debugging::CommandLineDebugger::new(program).run(&mut state)? {
+            Some(return_values) => return_values,
+            None => return Err(Box::from("execution of the program was stopped")),
         }
-
-        let mut resolver = (); // TODO: Add resolver that reads modules passed in as imports.
-
-        let runtime = runtime::Runtime::initialize(&mut initializer, &mut resolver, application);
-
-        let mut debugger;
-
-        runtime.invoke_entry_point(
-            application_arguments.as_ref(),
-            if interpreter_arguments.interactive {
-                debugger = debugging::CommandLineDebugger::new();
-                Some(&mut debugger as &mut dyn sailar_vm::interpreter::debugger::Debugger)
-            } else {
-                None
-            },
-        )
+    } else {
+        runtime.execute(entry_point.clone(), entry_point_arguments)?
     };
 
-    match result {
+    let return_values = entry_point::integer_return_values(&entry_point, &return_values, runtime.endianness())?;
+    Ok(entry_point::exit_code(&return_values))
+}
+
+fn main() {
+    match run(Arguments::parse()) {
         Ok(exit_code) => std::process::exit(exit_code),
-        Err(runtime::Error::InterpreterError(error)) => {
+        Err(error) => {
             eprintln!("Error: {}", error);
-            for frame in error.stack_trace() {
-                eprint!("- {} ", debugging::FunctionSymbol(frame.function()));
-
-                match frame.location() {
-                    Some(location) => eprintln!(
-                        "at block {} instruction {}",
-                        location.block_index, location.code_index
-                    ),
-                    None => eprintln!("(external call)"),
-                }
-
-                // TODO: Have option to hide register values.
-                for (index, input) in frame.input_registers().iter().enumerate() {
-                    eprintln!(" > %i{} = {}", index, input);
-                }
-                for (index, temporary) in frame.temporary_registers().iter().enumerate() {
-                    eprintln!(" > %t{} = {}", index, temporary);
-                }
-            }
             std::process::exit(1)
         }
-        Err(error) => Err(Box::new(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Arguments;
+
+    const SUBTRACT: &str = r#".format major 0
+.format minor 19
+.metadata id "Subtract" 1.0
+.metadata main @main_instance
+.signature @s32 type s32
+.signature @main_signature function (@s32, @s32) -> (@s32)
+
+.code @main_body ($x:@s32, $y:@s32) -> (@s32)
+    $difference = isub $x $y
+    ret $difference
+
+.define @main function export "main" signature @main_signature
+/body @main_body
+
+.instantiate @main_instance function definition @main
+"#;
+
+    /// Assembles the `SUBTRACT` program and runs it with the specified `arguments`.
+    fn run(test_name: &str, arguments: &[&str]) -> Result<i32, Box<dyn std::error::Error>> {
+        let mut parse_tree = None;
+        let module = sailasm::assemble(SUBTRACT, &mut parse_tree).unwrap();
+        let directory = std::env::temp_dir().join(format!("saili_{}_{}", std::process::id(), test_name));
+        std::fs::create_dir_all(&directory).unwrap();
+        let program = directory.join("subtract.sail");
+        module.write_to(std::fs::File::create(&program).unwrap()).unwrap();
+
+        super::run(Arguments {
+            program,
+            interactive: false,
+            call_stack_size: None,
+            arguments: arguments.iter().map(|argument| argument.to_string()).collect(),
+        })
+    }
+
+    #[test]
+    fn assembled_program_is_run() {
+        assert_eq!(run("run", &["50", "8"]).unwrap(), 42);
+        assert_eq!(run("negative", &["1", "2"]).unwrap(), -1);
+    }
+
+    #[test]
+    fn argument_count_mismatch_is_error() {
+        let error = run("count", &["1", "2", "3"]).unwrap_err();
+        assert_eq!(error.to_string(), "entry point expects 2 arguments, but 3 were provided");
+    }
+
+    #[test]
+    fn invalid_arguments_are_errors() {
+        assert_eq!(
+            run("invalid", &["1", "two"]).unwrap_err().to_string(),
+            "argument \"two\" is not an integer"
+        );
+        assert_eq!(
+            run("range", &["-2147483649", "0"]).unwrap_err().to_string(),
+            "argument -2147483649 is out of range for type s32"
+        );
     }
 }